@@ -0,0 +1,29 @@
+use std::{env, path::PathBuf};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // use the bundled protoc so building does not need protobuf installed
+    if env::var_os("PROTOC").is_none() {
+        // SAFETY: build scripts are single threaded
+        unsafe { env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?) };
+    }
+
+    // sqlx::migrate! embeds the migrations, rebuild when one is added or changed
+    println!("cargo:rerun-if-changed=migrations");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+
+    tonic_build::configure()
+        .build_client(false)
+        .file_descriptor_set_path(out_dir.join("gatherer_descriptor.bin"))
+        .compile_protos(
+            &[
+                "proto/equipment_events.proto",
+                "proto/equipment_types.proto",
+                "proto/mode_groups.proto",
+                "proto/modes.proto",
+            ],
+            &["proto"],
+        )?;
+
+    Ok(())
+}
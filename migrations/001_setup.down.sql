@@ -0,0 +1,11 @@
+-- reverts 001_setup.up.sql
+-- the ignition role is shared by every database on the server and holds no password, it is left in place
+
+DROP SCHEMA operations;
+DROP SCHEMA app;
+DROP SCHEMA core;
+
+DROP COLLATION case_insensitive;
+DROP FUNCTION trigger_updated_at(regclass);
+DROP FUNCTION set_updated_at();
+DROP EXTENSION IF EXISTS "uuid-ossp";
@@ -53,16 +53,6 @@ create collation case_insensitive (provider = icu, locale = 'und-u-ks-level2', d
 
 -- end of the launchbadge boiler plate
 
--- create the db
-DO $$
-BEGIN
-    IF NOT EXISTS (
-        SELECT FROM pg_database WHERE datname = 'mes'
-    ) THEN
-        PERFORM dblink_exec('dbname=postgres', 'CREATE DATABASE mes');
-    END IF;
-END $$;
-
 -- schemas
 DO $$
 BEGIN
@@ -85,12 +75,14 @@ BEGIN
     END IF;
 END $$;
 
--- my normal user
+-- role for the ignition container, created without login so no credential lives in the repo
+-- the login and password are provisioned outside migrations:
+--     ALTER ROLE ignition LOGIN PASSWORD '...';
 DO $$
 BEGIN
     IF NOT EXISTS (
-        SELECT FROM pg_roles WHERE rolname = 'ignition' -- for my ignition container
+        SELECT FROM pg_roles WHERE rolname = 'ignition'
     ) THEN
-        CREATE ROLE ignition LOGIN PASSWORD 'P@ssword1!';
+        CREATE ROLE ignition NOLOGIN;
     END IF;
 END $$;
\ No newline at end of file
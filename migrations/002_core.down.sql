@@ -0,0 +1,10 @@
+-- reverts 002_core.up.sql, the seeded types, groups, modes and states go with the tables
+
+DROP TABLE core.equipment_state_group_mapping;
+DROP TABLE core.equipment_mode_group_mapping;
+DROP TABLE core.state;
+DROP TABLE core.state_group;
+DROP TABLE core.mode;
+DROP TABLE core.mode_group;
+DROP TABLE core.equipment;
+DROP TABLE core.equipment_type;
@@ -0,0 +1,31 @@
+-- reverts 003_core_eqTypes_strored_procedures.up.sql
+
+COMMENT ON TABLE core.equipment_type IS NULL;
+COMMENT ON COLUMN core.equipment_type.type_name IS NULL;
+
+-- drops every overload of the procedures by name
+DO $$
+DECLARE
+    proc regprocedure;
+BEGIN
+    FOR proc IN
+        SELECT p.oid::regprocedure
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname = 'core' AND p.proname IN (
+        'deleteequipmenttype',
+        'getallequipmenttypes',
+        'getequipmenttypebyid',
+        'getequipmenttypebyname',
+        'insertequipmenttype',
+        'searchequipmenttypes',
+        'updateequipmenttype'
+        )
+    LOOP
+        EXECUTE format('DROP FUNCTION %s', proc);
+    END LOOP;
+END $$;
+
+ALTER TABLE core.equipment_type
+DROP CONSTRAINT chk_equipment_type_name_length,
+DROP CONSTRAINT chk_equipment_type_name_not_empty;
@@ -0,0 +1,57 @@
+-- reverts 004_core_mode_stored_procedures.up.sql
+
+COMMENT ON TABLE core.mode_group IS NULL;
+COMMENT ON TABLE core.mode IS NULL;
+
+-- drops every overload of the procedures by name
+DO $$
+DECLARE
+    proc regprocedure;
+BEGIN
+    FOR proc IN
+        SELECT p.oid::regprocedure
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname = 'core' AND p.proname IN (
+        'assignequipmenttomodegroup',
+        'bulkassignequipmenttomodegroup',
+        'bulkinsertmodes',
+        'deletemode',
+        'deletemodegroup',
+        'getallmodegroups',
+        'getallmodes',
+        'getequipmentformodegroup',
+        'getmodebydescription',
+        'getmodebyid',
+        'getmodegroupbyid',
+        'getmodegroupbyidcontext',
+        'getmodegroupbyname',
+        'getmodegroupusagestats',
+        'getmodeusagestats',
+        'getmodesbymodegroupid',
+        'getmodesbymodegroupname',
+        'insertmode',
+        'insertmodegroup',
+        'modeexists',
+        'modegroupexists',
+        'movemodetomodegroup',
+        'searchmodegroups',
+        'searchmodes',
+        'unassignequipmentfrommodegroup',
+        'updatemode',
+        'updatemodegroup'
+        )
+    LOOP
+        EXECUTE format('DROP FUNCTION %s', proc);
+    END LOOP;
+END $$;
+
+ALTER TABLE core.mode
+DROP CONSTRAINT chk_mode_description_length,
+DROP CONSTRAINT chk_mode_description_not_empty;
+
+ALTER TABLE core.mode_group
+DROP CONSTRAINT chk_mode_group_description_length,
+DROP CONSTRAINT chk_mode_group_description_not_empty,
+DROP CONSTRAINT chk_mode_group_name_length,
+DROP CONSTRAINT chk_mode_group_name_not_empty;
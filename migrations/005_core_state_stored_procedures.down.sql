@@ -0,0 +1,59 @@
+-- reverts 005_core_state_stored_procedures.up.sql
+
+COMMENT ON TABLE core.state_group IS NULL;
+COMMENT ON TABLE core.state IS NULL;
+
+-- drops every overload of the procedures by name
+DO $$
+DECLARE
+    proc regprocedure;
+BEGIN
+    FOR proc IN
+        SELECT p.oid::regprocedure
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname = 'core' AND p.proname IN (
+        'assignequipmenttostategroup',
+        'bulkassignequipmenttostategroup',
+        'bulkinsertstates',
+        'deletestate',
+        'deletestategroup',
+        'getallstategroups',
+        'getallstates',
+        'getavailablestatecodes',
+        'getequipmentforstategroup',
+        'getstatebycode',
+        'getstatebydescription',
+        'getstatebyid',
+        'getstategroupbyid',
+        'getstategroupbyidcontext',
+        'getstategroupbyname',
+        'getstategroupbynamecontext',
+        'getstategroupusagestats',
+        'getstateusagestats',
+        'getstatesbystategroupid',
+        'insertstate',
+        'insertstategroup',
+        'searchstategroups',
+        'searchstates',
+        'stateexists',
+        'stategroupexists',
+        'unassignequipmentfromstategroup',
+        'updatestate',
+        'updatestategroup'
+        )
+    LOOP
+        EXECUTE format('DROP FUNCTION %s', proc);
+    END LOOP;
+END $$;
+
+ALTER TABLE core.state
+DROP CONSTRAINT chk_state_description_length,
+DROP CONSTRAINT chk_state_description_not_empty,
+DROP CONSTRAINT chk_state_code_range;
+
+ALTER TABLE core.state_group
+DROP CONSTRAINT chk_state_group_description_length,
+DROP CONSTRAINT chk_state_group_description_not_empty,
+DROP CONSTRAINT chk_state_group_name_length,
+DROP CONSTRAINT chk_state_group_name_not_empty;
@@ -0,0 +1,39 @@
+-- reverts 006_eq_stored_procedures.up.sql
+
+COMMENT ON TABLE core.equipment IS NULL;
+COMMENT ON COLUMN core.equipment.equipment_name IS NULL;
+COMMENT ON COLUMN core.equipment.equipment_parent_id IS NULL;
+COMMENT ON COLUMN core.equipment.equipment_metadata IS NULL;
+
+-- drops every overload of the procedures by name
+DO $$
+DECLARE
+    proc regprocedure;
+BEGIN
+    FOR proc IN
+        SELECT p.oid::regprocedure
+        FROM pg_proc p
+        JOIN pg_namespace n ON n.oid = p.pronamespace
+        WHERE n.nspname = 'core' AND p.proname IN (
+        'deleteequipment',
+        'equipmentexists',
+        'getallequipment',
+        'getallequipmentjson',
+        'getequipmentbyid',
+        'insertequipment',
+        'setequipmentconfig',
+        'updateequipment'
+        )
+    LOOP
+        EXECUTE format('DROP FUNCTION %s', proc);
+    END LOOP;
+END $$;
+
+DROP INDEX core.idx_equipment_unique_root_name_per_type;
+DROP INDEX core.idx_equipment_unique_name_per_parent_type;
+
+ALTER TABLE core.equipment
+DROP CONSTRAINT chk_equipment_metadata_valid_json,
+DROP CONSTRAINT chk_equipment_no_self_reference,
+DROP CONSTRAINT chk_equipment_name_length,
+DROP CONSTRAINT chk_equipment_name_not_empty;
@@ -0,0 +1,3 @@
+-- reverts 007_equipment_type_rules.up.sql
+
+DROP TABLE core.equipment_type_rule;
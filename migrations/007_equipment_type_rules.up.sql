@@ -0,0 +1,42 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        core
+Version:       1.0.0
+Description:   Equipment type hierarchy rules (which type may be parented under which)
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- one row per allowed parent/child type pair
+-- a null parent_type_id marks a type that is allowed at the root of the hierarchy
+CREATE TABLE core.equipment_type_rule (
+    rule_id uuid PRIMARY KEY DEFAULT uuid_generate_v1mc(),
+    parent_type_id uuid REFERENCES core.equipment_type(type_id) ON DELETE CASCADE,
+    child_type_id uuid NOT NULL REFERENCES core.equipment_type(type_id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz,
+    CONSTRAINT uq_equipment_type_rule UNIQUE NULLS NOT DISTINCT (parent_type_id, child_type_id)
+);
+
+SELECT trigger_updated_at('core.equipment_type_rule');
+
+CREATE INDEX idx_equipment_type_rule_child ON core.equipment_type_rule(child_type_id);
+
+COMMENT ON TABLE core.equipment_type_rule IS 'Allowed parent/child equipment type pairs, validated on equipment create and reparent';
+COMMENT ON COLUMN core.equipment_type_rule.parent_type_id IS 'Parent equipment type, null when the child type may be a root';
+
+-- default isa-95 hierarchy: enterprise > site > area > line > cell
+INSERT INTO core.equipment_type_rule (parent_type_id, child_type_id)
+SELECT p.type_id, c.type_id
+FROM (VALUES
+    (NULL, 'enterprise'),
+    ('enterprise', 'site'),
+    ('site', 'area'),
+    ('area', 'line'),
+    ('line', 'cell')
+) AS r(parent_name, child_name)
+JOIN core.equipment_type c ON c.type_name = r.child_name
+LEFT JOIN core.equipment_type p ON p.type_name = r.parent_name;
@@ -0,0 +1,4 @@
+-- reverts 008_equipment_mode_history.up.sql
+
+DROP VIEW core.current_equipment_mode;
+DROP TABLE core.equipment_mode_history;
@@ -0,0 +1,47 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        core
+Version:       1.0.0
+Description:   Equipment mode history and current mode view
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- every mode an equipment has been in, one row per interval
+-- ended_at is null for the mode the equipment is currently in
+CREATE TABLE core.equipment_mode_history (
+    history_id uuid PRIMARY KEY DEFAULT uuid_generate_v1mc(),
+    equipment_id uuid NOT NULL REFERENCES core.equipment(equipment_id) ON DELETE CASCADE,
+    mode_id uuid NOT NULL REFERENCES core.mode(mode_id),
+    set_at timestamptz NOT NULL DEFAULT now(),
+    ended_at timestamptz,
+    set_by text,
+    CONSTRAINT chk_equipment_mode_history_interval CHECK (ended_at IS NULL OR ended_at >= set_at)
+);
+
+-- only one open interval per equipment
+CREATE UNIQUE INDEX uq_equipment_mode_history_current
+    ON core.equipment_mode_history(equipment_id) WHERE ended_at IS NULL;
+
+CREATE INDEX idx_equipment_mode_history_equipment_set_at
+    ON core.equipment_mode_history(equipment_id, set_at DESC);
+
+COMMENT ON TABLE core.equipment_mode_history IS 'Mode changes per equipment, each row is the interval the equipment spent in a mode';
+COMMENT ON COLUMN core.equipment_mode_history.ended_at IS 'When the next mode was set, null while the mode is current';
+
+CREATE VIEW core.current_equipment_mode AS
+SELECT
+    h.equipment_id,
+    h.mode_id,
+    m.mode_description,
+    m.mode_group_id,
+    h.set_at,
+    h.set_by
+FROM core.equipment_mode_history h
+JOIN core.mode m ON m.mode_id = h.mode_id
+WHERE h.ended_at IS NULL;
+
+COMMENT ON VIEW core.current_equipment_mode IS 'The mode each equipment is currently in';
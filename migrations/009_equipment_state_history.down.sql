@@ -0,0 +1,4 @@
+-- reverts 009_equipment_state_history.up.sql
+
+DROP VIEW core.current_equipment_state;
+DROP TABLE core.equipment_state_history;
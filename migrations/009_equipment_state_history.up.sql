@@ -0,0 +1,52 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        core
+Version:       1.0.0
+Description:   Equipment state history and current state view
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- every state an equipment has reported, one row per interval
+-- ended_at is null for the state the equipment is currently in
+CREATE TABLE core.equipment_state_history (
+    history_id uuid PRIMARY KEY DEFAULT uuid_generate_v1mc(),
+    equipment_id uuid NOT NULL REFERENCES core.equipment(equipment_id) ON DELETE CASCADE,
+    state_id uuid NOT NULL REFERENCES core.state(state_id),
+    -- raw code as received from the plc, kept even if the state is later renumbered
+    state_code integer NOT NULL,
+    state_value jsonb,
+    started_at timestamptz NOT NULL DEFAULT now(),
+    ended_at timestamptz,
+    updated_by text,
+    CONSTRAINT chk_equipment_state_history_interval CHECK (ended_at IS NULL OR ended_at >= started_at)
+);
+
+-- only one open interval per equipment
+CREATE UNIQUE INDEX uq_equipment_state_history_current
+    ON core.equipment_state_history(equipment_id) WHERE ended_at IS NULL;
+
+CREATE INDEX idx_equipment_state_history_equipment_started_at
+    ON core.equipment_state_history(equipment_id, started_at DESC);
+
+COMMENT ON TABLE core.equipment_state_history IS 'State changes per equipment, each row is the interval the equipment spent in a state';
+COMMENT ON COLUMN core.equipment_state_history.ended_at IS 'When the next state was reported, null while the state is current';
+
+CREATE VIEW core.current_equipment_state AS
+SELECT
+    h.equipment_id,
+    h.state_id,
+    h.state_code,
+    s.state_description,
+    s.state_group_id,
+    h.state_value,
+    h.started_at,
+    h.updated_by
+FROM core.equipment_state_history h
+JOIN core.state s ON s.state_id = h.state_id
+WHERE h.ended_at IS NULL;
+
+COMMENT ON VIEW core.current_equipment_state IS 'The state each equipment is currently in';
@@ -0,0 +1,5 @@
+-- reverts 010_operations.up.sql
+
+drop table operations.job;
+drop table operations.work_order;
+drop table operations.product;
@@ -0,0 +1,67 @@
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Schema:        operations
+Version:       1.1.0
+Description:   Operations tables: products, work orders and jobs
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-16  hunter  fix table syntax, schema name and metadata column types,
+                        link work orders to products and jobs to equipment
+===========================================
+*/
+
+-- product table
+create table operations.product (
+    product_id uuid primary key default uuid_generate_v1mc(),
+    product_name text collate "case_insensitive" unique not null,
+    product_description text,
+    product_metadata jsonb not null default '{}'::jsonb,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+    constraint chk_product_name_not_empty check (length(trim(product_name)) > 0)
+);
+
+select trigger_updated_at('operations.product');
+
+
+-- work order table
+create table operations.work_order (
+    work_order_id uuid primary key default uuid_generate_v1mc(),
+    work_order_name text collate "case_insensitive" unique not null,
+    product_id uuid not null references operations.product(product_id),
+    planned_quantity integer,
+    work_order_metadata jsonb not null default '{}'::jsonb,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+    constraint chk_work_order_name_not_empty check (length(trim(work_order_name)) > 0),
+    constraint chk_work_order_planned_quantity check (planned_quantity is null or planned_quantity > 0)
+);
+
+select trigger_updated_at('operations.work_order');
+
+create index idx_work_order_product on operations.work_order(product_id);
+
+
+-- job table
+-- a job is the part of a work order that runs on one piece of equipment
+create table operations.job (
+    job_id uuid primary key default uuid_generate_v1mc(),
+    work_order_id uuid not null references operations.work_order(work_order_id),
+    equipment_id uuid not null references core.equipment(equipment_id),
+    planned_quantity integer,
+    job_metadata jsonb not null default '{}'::jsonb,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+    constraint chk_job_planned_quantity check (planned_quantity is null or planned_quantity > 0)
+);
+
+select trigger_updated_at('operations.job');
+
+create index idx_job_work_order on operations.job(work_order_id);
+create index idx_job_equipment on operations.job(equipment_id);
+
+comment on table operations.product is 'Products that can be produced';
+comment on table operations.work_order is 'Orders to produce a quantity of a product';
+comment on table operations.job is 'Work order execution on a single piece of equipment';
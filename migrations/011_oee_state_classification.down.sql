@@ -0,0 +1,3 @@
+-- reverts 011_oee_state_classification.up.sql
+
+DROP TABLE core.state_classification;
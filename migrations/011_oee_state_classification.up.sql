@@ -0,0 +1,52 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        core
+Version:       1.0.0
+Description:   OEE classification of state codes per state group
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- how time spent in a state code counts towards oee
+--   running             productive time
+--   performance_loss    minor stops (blocked, starved), lowers performance
+--   unplanned_downtime  stops, lowers availability
+--   planned_downtime    excluded from planned production time
+-- codes without a row are treated as unplanned downtime
+CREATE TABLE core.state_classification (
+    classification_id uuid PRIMARY KEY DEFAULT uuid_generate_v1mc(),
+    state_group_id uuid NOT NULL REFERENCES core.state_group(state_group_id) ON DELETE CASCADE,
+    state_code integer NOT NULL,
+    category varchar(32) NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz,
+    CONSTRAINT uq_state_classification UNIQUE (state_group_id, state_code),
+    CONSTRAINT chk_state_classification_category
+        CHECK (category IN ('running', 'performance_loss', 'unplanned_downtime', 'planned_downtime'))
+);
+
+SELECT trigger_updated_at('core.state_classification');
+
+COMMENT ON TABLE core.state_classification IS 'Configurable oee category for each state code of a state group';
+
+-- defaults for the seeded state group
+INSERT INTO core.state_classification (state_group_id, state_code, category)
+SELECT sg.state_group_id, c.state_code, c.category
+FROM core.state_group sg
+CROSS JOIN (VALUES
+    (0, 'planned_downtime'),
+    (1, 'running'),
+    (2, 'planned_downtime'),
+    (3, 'unplanned_downtime'),
+    (4, 'unplanned_downtime'),
+    (5, 'performance_loss'),
+    (6, 'performance_loss'),
+    (7, 'planned_downtime'),
+    (8, 'unplanned_downtime'),
+    (9, 'planned_downtime'),
+    (10, 'unplanned_downtime')
+) AS c(state_code, category)
+WHERE sg.state_group_name = 'Default MES State Group';
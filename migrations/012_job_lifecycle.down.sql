@@ -0,0 +1,13 @@
+-- reverts 012_job_lifecycle.up.sql
+
+drop table operations.job_status_history;
+
+drop index operations.idx_job_equipment_status;
+
+alter table operations.job
+    drop constraint chk_job_status,
+    drop column cancelled_at,
+    drop column completed_at,
+    drop column started_at,
+    drop column released_at,
+    drop column job_status;
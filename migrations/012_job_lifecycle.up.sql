@@ -0,0 +1,41 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        operations
+Version:       1.0.0
+Description:   Job lifecycle status and status transition history
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- planned -> released -> running <-> paused -> completed, cancelled from any non final status
+-- the legal transitions are enforced by the service, the check only guards the values
+alter table operations.job
+    add column job_status text not null default 'planned',
+    add column released_at timestamptz,
+    add column started_at timestamptz,
+    add column completed_at timestamptz,
+    add column cancelled_at timestamptz,
+    add constraint chk_job_status check (
+        job_status in ('planned', 'released', 'running', 'paused', 'completed', 'cancelled')
+    );
+
+create index idx_job_equipment_status on operations.job(equipment_id, job_status);
+
+comment on column operations.job.started_at is 'First time the job was started, resumes do not move it';
+
+-- every status change of a job, including each pause and resume
+create table operations.job_status_history (
+    history_id uuid primary key default uuid_generate_v1mc(),
+    job_id uuid not null references operations.job(job_id) on delete cascade,
+    from_status text not null,
+    to_status text not null,
+    changed_at timestamptz not null default now(),
+    changed_by text
+);
+
+create index idx_job_status_history_job on operations.job_status_history(job_id, changed_at);
+
+comment on table operations.job_status_history is 'Status transitions per job';
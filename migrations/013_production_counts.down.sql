@@ -0,0 +1,3 @@
+-- reverts 013_production_counts.up.sql
+
+drop table operations.production_count;
@@ -0,0 +1,39 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        operations
+Version:       1.0.0
+Description:   Produced quantities per equipment and job
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- one row per count report, the quantities are always what was produced since the previous report
+-- plc counter readings are kept in the *_counter columns so the next reading can be turned into a delta
+create table operations.production_count (
+    count_id uuid primary key default uuid_generate_v1mc(),
+    equipment_id uuid not null references core.equipment(equipment_id) on delete cascade,
+    job_id uuid references operations.job(job_id),
+    recorded_at timestamptz not null default now(),
+    good_quantity bigint not null default 0,
+    scrap_quantity bigint not null default 0,
+    rework_quantity bigint not null default 0,
+    good_counter bigint,
+    scrap_counter bigint,
+    rework_counter bigint,
+    rollover boolean not null default false,
+    recorded_by text,
+    created_at timestamptz not null default now(),
+    constraint chk_production_count_quantities check (
+        good_quantity >= 0 and scrap_quantity >= 0 and rework_quantity >= 0
+    )
+);
+
+create index idx_production_count_equipment_recorded_at
+    on operations.production_count(equipment_id, recorded_at desc);
+create index idx_production_count_job on operations.production_count(job_id);
+
+comment on table operations.production_count is 'Good, scrap and rework quantities reported by equipment';
+comment on column operations.production_count.rollover is 'The counter reading was lower than the previous one and wrapped or was reset';
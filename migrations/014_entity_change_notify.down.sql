@@ -0,0 +1,11 @@
+-- reverts 014_entity_change_notify.up.sql
+
+DROP TRIGGER notify_entity_change ON core.state;
+DROP TRIGGER notify_entity_change ON core.state_group;
+DROP TRIGGER notify_entity_change ON core.mode;
+DROP TRIGGER notify_entity_change ON core.mode_group;
+DROP TRIGGER notify_entity_change ON core.equipment_type;
+DROP TRIGGER notify_entity_change ON core.equipment;
+
+DROP FUNCTION core.trigger_notify_entity_change(regclass, text, text);
+DROP FUNCTION core.notify_entity_change();
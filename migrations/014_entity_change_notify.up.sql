@@ -0,0 +1,67 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        core
+Version:       1.0.0
+Description:   Notify live feed listeners about created, updated and deleted core entities
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- Most core writes go through stored procedures, so the notifications come from triggers instead of
+-- the rust query code. Mode and state changes notify from their recording queries on the same channel.
+-- Only ids are sent, listeners load what they need themselves (NOTIFY payloads are capped at 8000 bytes).
+--
+-- select core.trigger_notify_entity_change('<table name>', '<event type>', '<id column>');
+--
+-- after a `CREATE TABLE` adds a table to the feed.
+CREATE OR REPLACE FUNCTION core.notify_entity_change()
+    RETURNS TRIGGER AS
+$$
+DECLARE
+    entity jsonb;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        entity := to_jsonb(OLD);
+    ELSIF TG_OP = 'UPDATE' AND OLD IS NOT DISTINCT FROM NEW THEN
+        RETURN NULL;
+    ELSE
+        entity := to_jsonb(NEW);
+    END IF;
+
+    PERFORM pg_notify('mes_events', json_build_object(
+        'event_type', TG_ARGV[0],
+        'action', CASE TG_OP WHEN 'INSERT' THEN 'created' WHEN 'UPDATE' THEN 'updated' ELSE 'deleted' END,
+        'id', entity ->> TG_ARGV[1],
+        'equipment_id', entity ->> 'equipment_id',
+        'parent_id', entity ->> 'equipment_parent_id'
+    )::text);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION core.trigger_notify_entity_change(
+    tablename regclass,
+    event_type text,
+    id_column text
+)
+    RETURNS void AS
+$$
+BEGIN
+    EXECUTE format('CREATE TRIGGER notify_entity_change
+        AFTER INSERT OR UPDATE OR DELETE
+        ON %s
+        FOR EACH ROW
+    EXECUTE FUNCTION core.notify_entity_change(%L, %L);', tablename, event_type, id_column);
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT core.trigger_notify_entity_change('core.equipment', 'equipment', 'equipment_id');
+SELECT core.trigger_notify_entity_change('core.equipment_type', 'equipment_type', 'type_id');
+SELECT core.trigger_notify_entity_change('core.mode_group', 'mode_group', 'mode_group_id');
+SELECT core.trigger_notify_entity_change('core.mode', 'mode', 'mode_id');
+SELECT core.trigger_notify_entity_change('core.state_group', 'state_group', 'state_group_id');
+SELECT core.trigger_notify_entity_change('core.state', 'state', 'state_id');
@@ -0,0 +1,3 @@
+-- reverts 015_api_keys.up.sql
+
+drop table app.api_key;
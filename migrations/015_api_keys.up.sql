@@ -0,0 +1,34 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        app
+Version:       1.0.0
+Description:   API keys for authenticating http clients
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- only a sha-256 hash of each key is kept, the key itself is shown once when it is issued
+-- read allows GET requests, write everything else, admin also the /api/v1/admin routes
+create table app.api_key (
+    api_key_id uuid primary key default uuid_generate_v1mc(),
+    key_name text collate "case_insensitive" unique not null,
+    key_prefix text not null,
+    key_hash text not null unique,
+    scopes text[] not null,
+    last_used_at timestamptz,
+    revoked_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+    constraint chk_api_key_name_not_empty check (length(trim(key_name)) > 0),
+    constraint chk_api_key_scopes check (
+        cardinality(scopes) > 0 and scopes <@ array['read', 'write', 'admin']
+    )
+);
+
+select trigger_updated_at('app.api_key');
+
+comment on table app.api_key is 'Bearer keys for the http api, revoked keys are kept for reference';
+comment on column app.api_key.key_prefix is 'Start of the key, lets people tell their keys apart without the secret';
@@ -0,0 +1,16 @@
+-- reverts 016_audit_log.up.sql
+
+drop trigger audit_change on core.equipment_state_group_mapping;
+drop trigger audit_change on core.equipment_mode_group_mapping;
+drop trigger audit_change on core.state;
+drop trigger audit_change on core.state_group;
+drop trigger audit_change on core.mode;
+drop trigger audit_change on core.mode_group;
+drop trigger audit_change on core.equipment_type_rule;
+drop trigger audit_change on core.equipment_type;
+drop trigger audit_change on core.equipment;
+
+drop function app.trigger_audit(regclass, text, text);
+drop function app.audit_change();
+
+drop table app.audit_log;
@@ -0,0 +1,102 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-17
+Schema:        app
+Version:       1.0.0
+Description:   Audit log of changes to the plant model in core
+Change Log:
+    2026-10-17  hunter  init
+===========================================
+*/
+
+-- one row per changed row, before is null for created rows and after is null for deleted ones
+create table app.audit_log (
+    audit_id uuid primary key default uuid_generate_v1mc(),
+    occurred_at timestamptz not null default now(),
+    actor text,
+    action text not null,
+    entity_type text not null,
+    entity_id uuid not null,
+    before jsonb,
+    after jsonb,
+    transaction_id bigint not null default txid_current(),
+    constraint chk_audit_log_action check (action in ('created', 'updated', 'deleted'))
+);
+
+create index idx_audit_log_entity on app.audit_log(entity_type, entity_id, occurred_at desc);
+create index idx_audit_log_actor on app.audit_log(actor, occurred_at desc);
+create index idx_audit_log_occurred_at on app.audit_log(occurred_at desc);
+
+comment on table app.audit_log is 'Changes to core configuration tables, written by triggers in the same transaction as the change';
+comment on column app.audit_log.actor is 'Who made the change, from the app.actor setting, null for changes made outside the api';
+comment on column app.audit_log.transaction_id is 'Rows written by the same transaction share this, e.g. a deleted equipment and its mappings';
+
+-- Like the entity change notifications (014) this is done with triggers, the stored procedures
+-- write most of core. The api sets the actor for its transaction with
+--
+-- select set_config('app.actor', '<actor>', true);
+--
+-- and anything else that writes core shows up with a null actor.
+--
+-- select app.trigger_audit('<table name>', '<entity type>', '<id column>');
+--
+-- after a `CREATE TABLE` adds a table to the log.
+create or replace function app.audit_change()
+    returns trigger as
+$$
+declare
+    before_row jsonb;
+    after_row jsonb;
+begin
+    if TG_OP = 'UPDATE' and OLD is not distinct from NEW then
+        return null;
+    end if;
+
+    if TG_OP in ('UPDATE', 'DELETE') then
+        before_row := to_jsonb(OLD);
+    end if;
+    if TG_OP in ('INSERT', 'UPDATE') then
+        after_row := to_jsonb(NEW);
+    end if;
+
+    insert into app.audit_log (actor, action, entity_type, entity_id, before, after)
+    values (
+        nullif(current_setting('app.actor', true), ''),
+        case TG_OP when 'INSERT' then 'created' when 'UPDATE' then 'updated' else 'deleted' end,
+        TG_ARGV[0],
+        (coalesce(after_row, before_row) ->> TG_ARGV[1])::uuid,
+        before_row,
+        after_row
+    );
+
+    return null;
+end;
+$$ language plpgsql;
+
+create or replace function app.trigger_audit(
+    tablename regclass,
+    entity_type text,
+    id_column text
+)
+    returns void as
+$$
+begin
+    execute format('create trigger audit_change
+        after insert or update or delete
+        on %s
+        for each row
+    execute function app.audit_change(%L, %L);', tablename, entity_type, id_column);
+end;
+$$ language plpgsql;
+
+select app.trigger_audit('core.equipment', 'equipment', 'equipment_id');
+select app.trigger_audit('core.equipment_type', 'equipment_type', 'type_id');
+select app.trigger_audit('core.equipment_type_rule', 'equipment_type_rule', 'rule_id');
+select app.trigger_audit('core.mode_group', 'mode_group', 'mode_group_id');
+select app.trigger_audit('core.mode', 'mode', 'mode_id');
+select app.trigger_audit('core.state_group', 'state_group', 'state_group_id');
+select app.trigger_audit('core.state', 'state', 'state_id');
+-- the mappings have no id of their own, they are logged against the equipment
+select app.trigger_audit('core.equipment_mode_group_mapping', 'equipment_mode_group', 'equipment_id');
+select app.trigger_audit('core.equipment_state_group_mapping', 'equipment_state_group', 'equipment_id');
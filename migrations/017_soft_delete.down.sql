@@ -0,0 +1,132 @@
+-- reverts 017_soft_delete.up.sql
+-- soft deleted rows are removed for good, the constraints below can't hold with them
+
+drop function if exists core.purge_deleted(interval);
+
+delete from core.equipment_mode_history h using core.mode m
+    where m.mode_id = h.mode_id and m.deleted_at is not null;
+delete from core.equipment_state_history h using core.state s
+    where s.state_id = h.state_id and s.deleted_at is not null;
+delete from operations.production_count p using operations.job j, core.equipment e
+    where j.job_id = p.job_id and e.equipment_id = j.equipment_id and e.deleted_at is not null;
+delete from operations.job j using core.equipment e
+    where e.equipment_id = j.equipment_id and e.deleted_at is not null;
+-- leaves first, a deleted parent only has deleted children
+do $$
+begin
+    while exists (select 1 from core.equipment where deleted_at is not null) loop
+        delete from core.equipment e
+        where e.deleted_at is not null
+          and not exists (select 1 from core.equipment c where c.equipment_parent_id = e.equipment_id);
+    end loop;
+end;
+$$;
+delete from core.mode where deleted_at is not null;
+delete from core.state where deleted_at is not null;
+delete from core.mode_group where deleted_at is not null;
+delete from core.state_group where deleted_at is not null;
+delete from core.equipment_type where deleted_at is not null;
+
+CREATE OR REPLACE FUNCTION core.notify_entity_change()
+    RETURNS TRIGGER AS
+$$
+DECLARE
+    entity jsonb;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        entity := to_jsonb(OLD);
+    ELSIF TG_OP = 'UPDATE' AND OLD IS NOT DISTINCT FROM NEW THEN
+        RETURN NULL;
+    ELSE
+        entity := to_jsonb(NEW);
+    END IF;
+
+    PERFORM pg_notify('mes_events', json_build_object(
+        'event_type', TG_ARGV[0],
+        'action', CASE TG_OP WHEN 'INSERT' THEN 'created' WHEN 'UPDATE' THEN 'updated' ELSE 'deleted' END,
+        'id', entity ->> TG_ARGV[1],
+        'equipment_id', entity ->> 'equipment_id',
+        'parent_id', entity ->> 'equipment_parent_id'
+    )::text);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+create or replace function app.audit_change()
+    returns trigger as
+$$
+declare
+    before_row jsonb;
+    after_row jsonb;
+begin
+    if TG_OP = 'UPDATE' and OLD is not distinct from NEW then
+        return null;
+    end if;
+
+    if TG_OP in ('UPDATE', 'DELETE') then
+        before_row := to_jsonb(OLD);
+    end if;
+    if TG_OP in ('INSERT', 'UPDATE') then
+        after_row := to_jsonb(NEW);
+    end if;
+
+    insert into app.audit_log (actor, action, entity_type, entity_id, before, after)
+    values (
+        nullif(current_setting('app.actor', true), ''),
+        case TG_OP when 'INSERT' then 'created' when 'UPDATE' then 'updated' else 'deleted' end,
+        TG_ARGV[0],
+        (coalesce(after_row, before_row) ->> TG_ARGV[1])::uuid,
+        before_row,
+        after_row
+    );
+
+    return null;
+end;
+$$ language plpgsql;
+
+update app.audit_log set action = 'deleted' where action = 'purged';
+update app.audit_log set action = 'updated' where action = 'restored';
+alter table app.audit_log drop constraint chk_audit_log_action;
+alter table app.audit_log add constraint chk_audit_log_action
+    check (action in ('created', 'updated', 'deleted'));
+
+drop index core.idx_equipment_type_deleted_at;
+drop index core.idx_equipment_deleted_at;
+drop index core.idx_mode_group_deleted_at;
+drop index core.idx_mode_deleted_at;
+drop index core.idx_state_group_deleted_at;
+drop index core.idx_state_deleted_at;
+
+drop index core.idx_equipment_unique_name_per_parent_type;
+drop index core.idx_equipment_unique_root_name_per_type;
+CREATE UNIQUE INDEX idx_equipment_unique_name_per_parent_type
+ON core.equipment (equipment_parent_id, equipment_type_id, equipment_name)
+WHERE equipment_parent_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_equipment_unique_root_name_per_type
+ON core.equipment (equipment_type_id, equipment_name)
+WHERE equipment_parent_id IS NULL;
+
+drop index core.idx_state_unique_code_per_group;
+drop index core.idx_state_unique_description_per_group;
+alter table core.state add constraint state_state_group_id_state_code_key unique (state_group_id, state_code);
+alter table core.state add constraint state_state_group_id_state_description_key unique (state_group_id, state_description);
+
+drop index core.idx_state_group_unique_name;
+alter table core.state_group add constraint state_group_state_group_name_key unique (state_group_name);
+
+drop index core.idx_mode_unique_description_per_group;
+alter table core.mode add constraint mode_mode_group_id_mode_description_key unique (mode_group_id, mode_description);
+
+drop index core.idx_mode_group_unique_name;
+alter table core.mode_group add constraint mode_group_mode_group_name_key unique (mode_group_name);
+
+drop index core.idx_equipment_type_unique_name;
+alter table core.equipment_type add constraint equipment_type_type_name_key unique (type_name);
+
+alter table core.state drop column deleted_at;
+alter table core.state_group drop column deleted_at;
+alter table core.mode drop column deleted_at;
+alter table core.mode_group drop column deleted_at;
+alter table core.equipment drop column deleted_at;
+alter table core.equipment_type drop column deleted_at;
@@ -0,0 +1,262 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-17
+Schema:        core
+Version:       1.0.0
+Description:   Soft delete for the plant model entities, with restore and a retention purge
+Change Log:
+    2026-10-17  hunter  init
+===========================================
+*/
+
+-- Deleting an entity sets deleted_at instead of removing the row, so the mode and state
+-- group mappings, history and audit trail of a mistakenly deleted entity survive and a
+-- restore clears deleted_at again. Queries in the api only see rows where deleted_at is
+-- null. core.purge_deleted removes rows that have been deleted for longer than the
+-- retention.
+--
+-- The stored procedures from 003 to 006 still see every row.
+alter table core.equipment_type add column deleted_at timestamptz;
+alter table core.equipment add column deleted_at timestamptz;
+alter table core.mode_group add column deleted_at timestamptz;
+alter table core.mode add column deleted_at timestamptz;
+alter table core.state_group add column deleted_at timestamptz;
+alter table core.state add column deleted_at timestamptz;
+
+comment on column core.equipment_type.deleted_at is 'When the row was soft deleted, null while it is live';
+comment on column core.equipment.deleted_at is 'When the row was soft deleted, null while it is live';
+comment on column core.mode_group.deleted_at is 'When the row was soft deleted, null while it is live';
+comment on column core.mode.deleted_at is 'When the row was soft deleted, null while it is live';
+comment on column core.state_group.deleted_at is 'When the row was soft deleted, null while it is live';
+comment on column core.state.deleted_at is 'When the row was soft deleted, null while it is live';
+
+-- names only have to be unique among live rows, a deleted row must not block creating its
+-- replacement. Restoring a row whose name was taken since fails on these indexes.
+alter table core.equipment_type drop constraint equipment_type_type_name_key;
+create unique index idx_equipment_type_unique_name
+    on core.equipment_type(type_name) where deleted_at is null;
+
+alter table core.mode_group drop constraint mode_group_mode_group_name_key;
+create unique index idx_mode_group_unique_name
+    on core.mode_group(mode_group_name) where deleted_at is null;
+
+alter table core.mode drop constraint mode_mode_group_id_mode_description_key;
+create unique index idx_mode_unique_description_per_group
+    on core.mode(mode_group_id, mode_description) where deleted_at is null;
+
+alter table core.state_group drop constraint state_group_state_group_name_key;
+create unique index idx_state_group_unique_name
+    on core.state_group(state_group_name) where deleted_at is null;
+
+alter table core.state drop constraint state_state_group_id_state_code_key;
+alter table core.state drop constraint state_state_group_id_state_description_key;
+create unique index idx_state_unique_code_per_group
+    on core.state(state_group_id, state_code) where deleted_at is null;
+create unique index idx_state_unique_description_per_group
+    on core.state(state_group_id, state_description) where deleted_at is null;
+
+drop index core.idx_equipment_unique_name_per_parent_type;
+drop index core.idx_equipment_unique_root_name_per_type;
+create unique index idx_equipment_unique_name_per_parent_type
+    on core.equipment(equipment_parent_id, equipment_type_id, equipment_name)
+    where equipment_parent_id is not null and deleted_at is null;
+create unique index idx_equipment_unique_root_name_per_type
+    on core.equipment(equipment_type_id, equipment_name)
+    where equipment_parent_id is null and deleted_at is null;
+
+-- for the purge
+create index idx_equipment_type_deleted_at on core.equipment_type(deleted_at) where deleted_at is not null;
+create index idx_equipment_deleted_at on core.equipment(deleted_at) where deleted_at is not null;
+create index idx_mode_group_deleted_at on core.mode_group(deleted_at) where deleted_at is not null;
+create index idx_mode_deleted_at on core.mode(deleted_at) where deleted_at is not null;
+create index idx_state_group_deleted_at on core.state_group(deleted_at) where deleted_at is not null;
+create index idx_state_deleted_at on core.state(deleted_at) where deleted_at is not null;
+
+-- setting deleted_at is logged as deleted, clearing it as restored, and removing a row that
+-- was already soft deleted as purged
+alter table app.audit_log drop constraint chk_audit_log_action;
+alter table app.audit_log add constraint chk_audit_log_action
+    check (action in ('created', 'updated', 'deleted', 'restored', 'purged'));
+
+create or replace function app.audit_change()
+    returns trigger as
+$$
+declare
+    before_row jsonb;
+    after_row jsonb;
+    change text;
+begin
+    if TG_OP = 'UPDATE' and OLD is not distinct from NEW then
+        return null;
+    end if;
+
+    if TG_OP in ('UPDATE', 'DELETE') then
+        before_row := to_jsonb(OLD);
+    end if;
+    if TG_OP in ('INSERT', 'UPDATE') then
+        after_row := to_jsonb(NEW);
+    end if;
+
+    change := case TG_OP when 'INSERT' then 'created' when 'UPDATE' then 'updated' else 'deleted' end;
+    if TG_OP = 'UPDATE' and before_row ->> 'deleted_at' is null and after_row ->> 'deleted_at' is not null then
+        change := 'deleted';
+    elsif TG_OP = 'UPDATE' and before_row ->> 'deleted_at' is not null and after_row ->> 'deleted_at' is null then
+        change := 'restored';
+    elsif TG_OP = 'DELETE' and before_row ->> 'deleted_at' is not null then
+        change := 'purged';
+    end if;
+
+    insert into app.audit_log (actor, action, entity_type, entity_id, before, after)
+    values (
+        nullif(current_setting('app.actor', true), ''),
+        change,
+        TG_ARGV[0],
+        (coalesce(after_row, before_row) ->> TG_ARGV[1])::uuid,
+        before_row,
+        after_row
+    );
+
+    return null;
+end;
+$$ language plpgsql;
+
+-- for listeners a soft deleted row is gone and a restored one is new, purging a row they
+-- were already told was deleted is not announced again
+CREATE OR REPLACE FUNCTION core.notify_entity_change()
+    RETURNS TRIGGER AS
+$$
+DECLARE
+    entity jsonb;
+    change text;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        entity := to_jsonb(OLD);
+        IF entity ->> 'deleted_at' IS NOT NULL THEN
+            RETURN NULL;
+        END IF;
+        change := 'deleted';
+    ELSIF TG_OP = 'UPDATE' AND OLD IS NOT DISTINCT FROM NEW THEN
+        RETURN NULL;
+    ELSE
+        entity := to_jsonb(NEW);
+        change := CASE TG_OP WHEN 'INSERT' THEN 'created' ELSE 'updated' END;
+        IF TG_OP = 'UPDATE' AND to_jsonb(OLD) ->> 'deleted_at' IS NULL AND entity ->> 'deleted_at' IS NOT NULL THEN
+            change := 'deleted';
+        ELSIF TG_OP = 'UPDATE' AND to_jsonb(OLD) ->> 'deleted_at' IS NOT NULL AND entity ->> 'deleted_at' IS NULL THEN
+            change := 'created';
+        ELSIF entity ->> 'deleted_at' IS NOT NULL THEN
+            -- changes to a row that stays deleted
+            RETURN NULL;
+        END IF;
+    END IF;
+
+    PERFORM pg_notify('mes_events', json_build_object(
+        'event_type', TG_ARGV[0],
+        'action', change,
+        'id', entity ->> TG_ARGV[1],
+        'equipment_id', entity ->> 'equipment_id',
+        'parent_id', entity ->> 'equipment_parent_id'
+    )::text);
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+/*
+===========================================
+Function:      core.purge_deleted
+Description:   Permanently removes rows soft deleted before now() - p_retention. Rows that
+               are still referenced, e.g. a mode with history or equipment with jobs, are
+               kept and tried again on the next purge. Equipment with mode/state history
+               or production counts is kept too, those rows would cascade with it.
+               Children go before their parents.
+Parameters:    p_retention - how long deleted rows are kept
+Returns:       Number of purged rows per entity type
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.purge_deleted(p_retention interval)
+    RETURNS TABLE (entity_type text, purged bigint) AS
+$$
+DECLARE
+    v_cutoff timestamptz := now() - p_retention;
+    v_id uuid;
+    v_count bigint;
+BEGIN
+    IF p_retention IS NULL OR p_retention < interval '0' THEN
+        RAISE EXCEPTION 'retention must not be negative';
+    END IF;
+
+    -- children are deleted before their parents, oldest first removes them first
+    v_count := 0;
+    FOR v_id IN SELECT e.equipment_id FROM core.equipment e
+                WHERE e.deleted_at < v_cutoff
+                  AND NOT EXISTS (SELECT FROM core.equipment_mode_history h WHERE h.equipment_id = e.equipment_id)
+                  AND NOT EXISTS (SELECT FROM core.equipment_state_history h WHERE h.equipment_id = e.equipment_id)
+                  AND NOT EXISTS (SELECT FROM operations.production_count c WHERE c.equipment_id = e.equipment_id)
+                ORDER BY e.deleted_at LOOP
+        BEGIN
+            DELETE FROM core.equipment WHERE equipment_id = v_id;
+            v_count := v_count + 1;
+        EXCEPTION WHEN foreign_key_violation THEN
+            NULL;
+        END;
+    END LOOP;
+    entity_type := 'equipment'; purged := v_count; RETURN NEXT;
+
+    v_count := 0;
+    FOR v_id IN SELECT m.mode_id FROM core.mode m WHERE m.deleted_at < v_cutoff LOOP
+        BEGIN
+            DELETE FROM core.mode WHERE mode_id = v_id;
+            v_count := v_count + 1;
+        EXCEPTION WHEN foreign_key_violation THEN
+            NULL;
+        END;
+    END LOOP;
+    entity_type := 'mode'; purged := v_count; RETURN NEXT;
+
+    v_count := 0;
+    FOR v_id IN SELECT s.state_id FROM core.state s WHERE s.deleted_at < v_cutoff LOOP
+        BEGIN
+            DELETE FROM core.state WHERE state_id = v_id;
+            v_count := v_count + 1;
+        EXCEPTION WHEN foreign_key_violation THEN
+            NULL;
+        END;
+    END LOOP;
+    entity_type := 'state'; purged := v_count; RETURN NEXT;
+
+    v_count := 0;
+    FOR v_id IN SELECT g.mode_group_id FROM core.mode_group g WHERE g.deleted_at < v_cutoff LOOP
+        BEGIN
+            DELETE FROM core.mode_group WHERE mode_group_id = v_id;
+            v_count := v_count + 1;
+        EXCEPTION WHEN foreign_key_violation THEN
+            NULL;
+        END;
+    END LOOP;
+    entity_type := 'mode_group'; purged := v_count; RETURN NEXT;
+
+    v_count := 0;
+    FOR v_id IN SELECT g.state_group_id FROM core.state_group g WHERE g.deleted_at < v_cutoff LOOP
+        BEGIN
+            DELETE FROM core.state_group WHERE state_group_id = v_id;
+            v_count := v_count + 1;
+        EXCEPTION WHEN foreign_key_violation THEN
+            NULL;
+        END;
+    END LOOP;
+    entity_type := 'state_group'; purged := v_count; RETURN NEXT;
+
+    v_count := 0;
+    FOR v_id IN SELECT t.type_id FROM core.equipment_type t WHERE t.deleted_at < v_cutoff LOOP
+        BEGIN
+            DELETE FROM core.equipment_type WHERE type_id = v_id;
+            v_count := v_count + 1;
+        EXCEPTION WHEN foreign_key_violation THEN
+            NULL;
+        END;
+    END LOOP;
+    entity_type := 'equipment_type'; purged := v_count; RETURN NEXT;
+END;
+$$ LANGUAGE plpgsql;
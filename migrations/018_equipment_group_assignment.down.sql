@@ -0,0 +1,815 @@
+-- reverts 018_equipment_group_assignment.up.sql
+-- the versions from 004 and 005
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.assignEquipmentToModeGroup
+Version:       1.0.0
+Description:   Assigns equipment to a mode group (creates mapping)
+Parameters:
+    p_equipment_id UUID                 -- Required equipment ID
+    p_mode_group_id UUID                -- Required mode group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Assignment result data as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.assignEquipmentToModeGroup(
+    p_equipment_id uuid,
+    p_mode_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_equipment_name varchar(255);
+    v_mode_group_name varchar(255);
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment assigned to mode group successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_equipment_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment ID cannot be null';
+        RETURN;
+    END IF;
+    
+    IF p_mode_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- verify equipment exists
+    SELECT equipment_name INTO v_equipment_name
+    FROM core.equipment
+    WHERE equipment_id = p_equipment_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment not found';
+        RETURN;
+    END IF;
+    
+    -- verify mode group exists
+    SELECT mode_group_name INTO v_mode_group_name
+    FROM core.mode_group
+    WHERE mode_group_id = p_mode_group_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+    
+    -- check if assignment already exists
+    IF EXISTS (
+        SELECT 1 FROM core.equipment_mode_group_mapping 
+        WHERE equipment_id = p_equipment_id AND mode_group_id = p_mode_group_id
+    ) THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment is already assigned to this mode group';
+        RETURN;
+    END IF;
+    
+    -- create the assignment
+    INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+    VALUES (p_equipment_id, p_mode_group_id);
+    
+    "Data" := jsonb_build_object(
+        'equipment_id', p_equipment_id,
+        'equipment_name', v_equipment_name,
+        'mode_group_id', p_mode_group_id,
+        'mode_group_name', v_mode_group_name,
+        'assigned_at', now()
+    );
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment is already assigned to this mode group';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.unassignEquipmentFromModeGroup
+Version:       1.0.0
+Description:   Removes equipment assignment from a mode group
+Parameters:
+    p_equipment_id UUID                 -- Required equipment ID
+    p_mode_group_id UUID                -- Required mode group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Unassignment result data as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.unassignEquipmentFromModeGroup(
+    p_equipment_id uuid,
+    p_mode_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_equipment_name varchar(255);
+    v_mode_group_name varchar(255);
+    v_deleted_count integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment unassigned from mode group successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_equipment_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment ID cannot be null';
+        RETURN;
+    END IF;
+    
+    IF p_mode_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- get names for response (even if assignment doesn't exist)
+    SELECT equipment_name INTO v_equipment_name
+    FROM core.equipment
+    WHERE equipment_id = p_equipment_id;
+    
+    SELECT mode_group_name INTO v_mode_group_name
+    FROM core.mode_group
+    WHERE mode_group_id = p_mode_group_id;
+    
+    -- remove the assignment
+    DELETE FROM core.equipment_mode_group_mapping
+    WHERE equipment_id = p_equipment_id AND mode_group_id = p_mode_group_id;
+    
+    GET DIAGNOSTICS v_deleted_count = ROW_COUNT;
+    
+    IF v_deleted_count = 0 THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment assignment not found';
+        RETURN;
+    END IF;
+    
+    "Data" := jsonb_build_object(
+        'equipment_id', p_equipment_id,
+        'equipment_name', COALESCE(v_equipment_name, 'Unknown'),
+        'mode_group_id', p_mode_group_id,
+        'mode_group_name', COALESCE(v_mode_group_name, 'Unknown'),
+        'unassigned_at', now()
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getEquipmentForModeGroup
+Version:       1.0.0
+Description:   Retrieves all equipment assigned to a specific mode group
+Parameters:
+    p_mode_group_id UUID                -- Required mode group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Equipment list as JSON array
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getEquipmentForModeGroup(
+    p_mode_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_results jsonb;
+    v_mode_group_name varchar(255);
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment list retrieved successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_mode_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- verify mode group exists
+    SELECT mode_group_name INTO v_mode_group_name
+    FROM core.mode_group
+    WHERE mode_group_id = p_mode_group_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+    
+    -- get equipment list
+    SELECT COALESCE(jsonb_agg(
+        jsonb_build_object(
+            'equipment_id', e.equipment_id,
+            'equipment_name', e.equipment_name,
+            'equipment_type_id', e.equipment_type_id,
+            'equipment_type_name', et.type_name,
+            'equipment_enabled', e.equipment_enabled,
+            'equipment_parent_id', e.equipment_parent_id,
+            'created_at', e.created_at,
+            'updated_at', e.updated_at
+        ) ORDER BY e.equipment_name
+    ), '[]'::jsonb)
+    INTO v_results
+    FROM core.equipment_mode_group_mapping emgm
+    JOIN core.equipment e ON emgm.equipment_id = e.equipment_id
+    JOIN core.equipment_type et ON e.equipment_type_id = et.type_id
+    WHERE emgm.mode_group_id = p_mode_group_id;
+    
+    "Data" := jsonb_build_object(
+        'mode_group_id', p_mode_group_id,
+        'mode_group_name', v_mode_group_name,
+        'equipment', v_results,
+        'equipment_count', jsonb_array_length(v_results)
+    );
+    
+    "Message" := 'Found ' || jsonb_array_length(v_results) || ' equipment item(s) for mode group: ' || v_mode_group_name;
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.bulkAssignEquipmentToModeGroup
+Version:       1.0.0
+Description:   Assigns multiple equipment items to a mode group in batch
+Parameters:
+    p_equipment_ids UUID[]              -- Required array of equipment IDs
+    p_mode_group_id UUID                -- Required mode group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Bulk assignment results as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.bulkAssignEquipmentToModeGroup(
+    p_equipment_ids uuid[],
+    p_mode_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_mode_group_name varchar(255);
+    v_valid_equipment_ids uuid[];
+    v_existing_assignments uuid[];
+    v_new_assignments uuid[];
+    v_assigned_count integer := 0;
+    v_skipped_count integer := 0;
+    equipment_id uuid;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Bulk assignment completed';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_equipment_ids IS NULL OR array_length(p_equipment_ids, 1) IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'No equipment IDs provided';
+        RETURN;
+    END IF;
+    
+    IF p_mode_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- verify mode group exists
+    SELECT mode_group_name INTO v_mode_group_name
+    FROM core.mode_group
+    WHERE mode_group_id = p_mode_group_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+    
+    -- get valid equipment ids (ones that actually exist)
+    SELECT array_agg(equipment_id) INTO v_valid_equipment_ids
+    FROM core.equipment
+    WHERE equipment_id = ANY(p_equipment_ids);
+    
+    IF v_valid_equipment_ids IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'No valid equipment IDs found';
+        RETURN;
+    END IF;
+    
+    -- get existing assignments
+    SELECT array_agg(equipment_id) INTO v_existing_assignments
+    FROM core.equipment_mode_group_mapping
+    WHERE equipment_id = ANY(v_valid_equipment_ids) 
+    AND mode_group_id = p_mode_group_id;
+    
+    -- determine new assignments (exclude existing ones)
+    IF v_existing_assignments IS NULL THEN
+        v_new_assignments := v_valid_equipment_ids;
+    ELSE
+        SELECT array_agg(eq_id) INTO v_new_assignments
+        FROM unnest(v_valid_equipment_ids) AS eq_id
+        WHERE eq_id != ALL(v_existing_assignments);
+    END IF;
+    
+    -- perform bulk insert for new assignments
+    IF v_new_assignments IS NOT NULL AND array_length(v_new_assignments, 1) > 0 THEN
+        INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+        SELECT unnest(v_new_assignments), p_mode_group_id;
+        
+        GET DIAGNOSTICS v_assigned_count = ROW_COUNT;
+    END IF;
+    
+    -- calculate skipped count
+    v_skipped_count := COALESCE(array_length(v_existing_assignments, 1), 0);
+    
+    "Data" := jsonb_build_object(
+        'mode_group_id', p_mode_group_id,
+        'mode_group_name', v_mode_group_name,
+        'requested_count', array_length(p_equipment_ids, 1),
+        'valid_equipment_count', array_length(v_valid_equipment_ids, 1),
+        'assigned_count', v_assigned_count,
+        'skipped_count', v_skipped_count,
+        'assigned_equipment_ids', COALESCE(v_new_assignments, ARRAY[]::uuid[]),
+        'skipped_equipment_ids', COALESCE(v_existing_assignments, ARRAY[]::uuid[])
+    );
+    
+    IF v_assigned_count > 0 AND v_skipped_count > 0 THEN
+        "Message" := 'Partial success: ' || v_assigned_count || ' assigned, ' || v_skipped_count || ' skipped (already assigned)';
+    ELSIF v_assigned_count > 0 THEN
+        "Message" := 'Successfully assigned ' || v_assigned_count || ' equipment item(s)';
+    ELSIF v_skipped_count > 0 THEN
+        "Message" := 'All ' || v_skipped_count || ' equipment item(s) were already assigned';
+        "Status" := 'Error';
+    END IF;
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.assignEquipmentToStateGroup
+Version:       1.0.0
+Description:   Assigns equipment to a state group (creates mapping)
+Parameters:
+    p_equipment_id UUID                 -- Required equipment ID
+    p_state_group_id UUID               -- Required state group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Assignment result data as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.assignEquipmentToStateGroup(
+    p_equipment_id uuid,
+    p_state_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_equipment_name varchar(255);
+    v_state_group_name varchar(255);
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment assigned to state group successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_equipment_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment ID cannot be null';
+        RETURN;
+    END IF;
+    
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- does equipment exists
+    SELECT equipment_name INTO v_equipment_name
+    FROM core.equipment
+    WHERE equipment_id = p_equipment_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment not found';
+        RETURN;
+    END IF;
+    
+    -- does state group exists
+    SELECT state_group_name INTO v_state_group_name
+    FROM core.state_group
+    WHERE state_group_id = p_state_group_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    --does the assignment already exist
+    IF EXISTS (
+        SELECT 1 FROM core.equipment_state_group_mapping 
+        WHERE equipment_id = p_equipment_id AND state_group_id = p_state_group_id
+    ) THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment is already assigned to this state group';
+        RETURN;
+    END IF;
+    
+    -- new assignment
+    INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+    VALUES (p_equipment_id, p_state_group_id);
+    
+    "Data" := jsonb_build_object(
+        'equipment_id', p_equipment_id,
+        'equipment_name', v_equipment_name,
+        'state_group_id', p_state_group_id,
+        'state_group_name', v_state_group_name,
+        'assigned_at', now()
+    );
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment is already assigned to this state group';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.unassignEquipmentFromStateGroup
+Version:       1.0.0
+Description:   Removes equipment assignment from a state group
+Parameters:
+    p_equipment_id UUID                 -- Required equipment ID
+    p_state_group_id UUID               -- Required state group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Unassignment result data as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.unassignEquipmentFromStateGroup(
+    p_equipment_id uuid,
+    p_state_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_equipment_name varchar(255);
+    v_state_group_name varchar(255);
+    v_deleted_count integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment unassigned from state group successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_equipment_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment ID cannot be null';
+        RETURN;
+    END IF;
+    
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- get names
+    SELECT equipment_name INTO v_equipment_name
+    FROM core.equipment
+    WHERE equipment_id = p_equipment_id;
+    
+    SELECT state_group_name INTO v_state_group_name
+    FROM core.state_group
+    WHERE state_group_id = p_state_group_id;
+    
+    -- remove the assignment
+    DELETE FROM core.equipment_state_group_mapping
+    WHERE equipment_id = p_equipment_id AND state_group_id = p_state_group_id;
+    
+    GET DIAGNOSTICS v_deleted_count = ROW_COUNT;
+    
+    IF v_deleted_count = 0 THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment assignment not found';
+        RETURN;
+    END IF;
+    
+    "Data" := jsonb_build_object(
+        'equipment_id', p_equipment_id,
+        'equipment_name', COALESCE(v_equipment_name, 'Unknown'),
+        'state_group_id', p_state_group_id,
+        'state_group_name', COALESCE(v_state_group_name, 'Unknown'),
+        'unassigned_at', now()
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getEquipmentForStateGroup
+Version:       1.0.0
+Description:   Retrieves all equipment assigned to a specific state group
+Parameters:
+    p_state_group_id UUID               -- Required state group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Equipment list as JSON array
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getEquipmentForStateGroup(
+    p_state_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_results jsonb;
+    v_state_group_name varchar(255);
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment list retrieved successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- state group exists
+    SELECT state_group_name INTO v_state_group_name
+    FROM core.state_group
+    WHERE state_group_id = p_state_group_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    -- equipment list
+    SELECT COALESCE(jsonb_agg(
+        jsonb_build_object(
+            'equipment_id', e.equipment_id,
+            'equipment_name', e.equipment_name,
+            'equipment_type_id', e.equipment_type_id,
+            'equipment_type_name', et.type_name,
+            'equipment_enabled', e.equipment_enabled,
+            'equipment_parent_id', e.equipment_parent_id,
+            'created_at', e.created_at,
+            'updated_at', e.updated_at
+        ) ORDER BY e.equipment_name
+    ), '[]'::jsonb)
+    INTO v_results
+    FROM core.equipment_state_group_mapping esgm
+    JOIN core.equipment e ON esgm.equipment_id = e.equipment_id
+    JOIN core.equipment_type et ON e.equipment_type_id = et.type_id
+    WHERE esgm.state_group_id = p_state_group_id;
+    
+    "Data" := jsonb_build_object(
+        'state_group_id', p_state_group_id,
+        'state_group_name', v_state_group_name,
+        'equipment', v_results,
+        'equipment_count', jsonb_array_length(v_results)
+    );
+    
+    "Message" := 'Found ' || jsonb_array_length(v_results) || ' equipment item(s) for state group: ' || v_state_group_name;
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.bulkAssignEquipmentToStateGroup
+Version:       1.0.0
+Description:   Assigns multiple equipment items to a state group in batch
+Parameters:
+    p_equipment_ids UUID[]              -- Required array of equipment IDs
+    p_state_group_id UUID               -- Required state group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Bulk assignment results as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.bulkAssignEquipmentToStateGroup(
+    p_equipment_ids uuid[],
+    p_state_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_state_group_name varchar(255);
+    v_valid_equipment_ids uuid[];
+    v_existing_assignments uuid[];
+    v_new_assignments uuid[];
+    v_assigned_count integer := 0;
+    v_skipped_count integer := 0;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Bulk assignment completed';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_equipment_ids IS NULL OR array_length(p_equipment_ids, 1) IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'No equipment IDs provided';
+        RETURN;
+    END IF;
+    
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- state group exists
+    SELECT state_group_name INTO v_state_group_name
+    FROM core.state_group
+    WHERE state_group_id = p_state_group_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    -- valid eq_ids
+    SELECT array_agg(equipment_id) INTO v_valid_equipment_ids
+    FROM core.equipment
+    WHERE equipment_id = ANY(p_equipment_ids);
+    
+    IF v_valid_equipment_ids IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'No valid equipment IDs found';
+        RETURN;
+    END IF;
+    
+    -- existing
+    SELECT array_agg(equipment_id) INTO v_existing_assignments
+    FROM core.equipment_state_group_mapping
+    WHERE equipment_id = ANY(v_valid_equipment_ids) 
+    AND state_group_id = p_state_group_id;
+    
+    -- new assignments - existing ones
+    IF v_existing_assignments IS NULL THEN
+        v_new_assignments := v_valid_equipment_ids;
+    ELSE
+        SELECT array_agg(eq_id) INTO v_new_assignments
+        FROM unnest(v_valid_equipment_ids) AS eq_id
+        WHERE eq_id != ALL(v_existing_assignments);
+    END IF;
+    
+    -- perform bulk insert for new assignments
+    IF v_new_assignments IS NOT NULL AND array_length(v_new_assignments, 1) > 0 THEN
+        INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+        SELECT unnest(v_new_assignments), p_state_group_id;
+        
+        GET DIAGNOSTICS v_assigned_count = ROW_COUNT;
+    END IF;
+    
+    -- skipped count
+    v_skipped_count := COALESCE(array_length(v_existing_assignments, 1), 0);
+    
+    "Data" := jsonb_build_object(
+        'state_group_id', p_state_group_id,
+        'state_group_name', v_state_group_name,
+        'requested_count', array_length(p_equipment_ids, 1),
+        'valid_equipment_count', array_length(v_valid_equipment_ids, 1),
+        'assigned_count', v_assigned_count,
+        'skipped_count', v_skipped_count,
+        'assigned_equipment_ids', COALESCE(v_new_assignments, ARRAY[]::uuid[]),
+        'skipped_equipment_ids', COALESCE(v_existing_assignments, ARRAY[]::uuid[])
+    );
+    
+    IF v_assigned_count > 0 AND v_skipped_count > 0 THEN
+        "Message" := 'Partial success: ' || v_assigned_count || ' assigned, ' || v_skipped_count || ' skipped (already assigned)';
+    ELSIF v_assigned_count > 0 THEN
+        "Message" := 'Successfully assigned ' || v_assigned_count || ' equipment item(s)';
+    ELSIF v_skipped_count > 0 THEN
+        "Message" := 'All ' || v_skipped_count || ' equipment item(s) were already assigned';
+        "Status" := 'Error';
+    END IF;
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
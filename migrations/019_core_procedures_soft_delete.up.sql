@@ -0,0 +1,1446 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-17
+Schema:        core
+Version:       1.0.0
+Description:   Equipment type, mode group and state group procedures the api calls
+Change Log:
+    2026-10-17  hunter  init
+===========================================
+*/
+
+-- The api can run its equipment type, mode group and state group queries through these
+-- procedures (the stored-procedures feature), so SQL clients and the api share the same
+-- rules. The versions from 003, 004 and 005 predate soft delete (017): these only see live
+-- rows and delete by setting deleted_at. A forced group delete soft deletes the group's
+-- modes or states and keeps the equipment mappings, so a restore brings the group back as
+-- it was. Mode group descriptions can be 2048 characters like the column allows.
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getEquipmentTypeById
+Version:       1.1.0
+Description:   Retrieves specific equipment type by ID with usage count
+Parameters:
+    p_type_id UUID                      -- Required equipment type ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Equipment type data as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getEquipmentTypeById(
+    p_type_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_result RECORD;
+BEGIN
+    -- output parameters
+    "Status" := 'Success';
+    "Message" := 'Equipment type retrieved successfully';
+    "Data" := NULL;
+    
+    -- input validation
+    IF p_type_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- get equipment type data
+    SELECT 
+        et.type_id,
+        et.type_name,
+        COALESCE(COUNT(e.equipment_id), 0) as equipment_count,
+        et.created_at,
+        et.updated_at
+    INTO v_result
+    FROM core.equipment_type et
+    LEFT JOIN core.equipment e ON et.type_id = e.equipment_type_id AND e.deleted_at IS NULL
+    WHERE et.type_id = p_type_id AND et.deleted_at IS NULL
+    GROUP BY et.type_id, et.type_name, et.created_at, et.updated_at;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type not found';
+        RETURN;
+    END IF;
+    
+    -- convert result to json
+    "Data" := to_jsonb(v_result);
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getEquipmentTypeByName
+Version:       1.1.0
+Description:   Retrieves specific equipment type by name with usage count
+Parameters:
+    p_type_name TEXT                    -- Required equipment type name
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Equipment type data as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getEquipmentTypeByName(
+    p_type_name text,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_result RECORD;
+BEGIN
+    -- output
+    "Status" := 'Success';
+    "Message" := 'Equipment type retrieved successfully';
+    "Data" := NULL;
+    
+    -- input validation
+    IF p_type_name IS NULL OR trim(p_type_name) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type name cannot be empty';
+        RETURN;
+    END IF;
+    
+    -- get equipment type data
+    SELECT 
+        et.type_id,
+        et.type_name,
+        COALESCE(COUNT(e.equipment_id), 0) as equipment_count,
+        et.created_at,
+        et.updated_at
+    INTO v_result
+    FROM core.equipment_type et
+    LEFT JOIN core.equipment e ON et.type_id = e.equipment_type_id AND e.deleted_at IS NULL
+    WHERE et.type_name = trim(p_type_name) AND et.deleted_at IS NULL
+    GROUP BY et.type_id, et.type_name, et.created_at, et.updated_at;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type not found';
+        RETURN;
+    END IF;
+    
+    -- convert result to json
+    "Data" := to_jsonb(v_result);
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.insertEquipmentType
+Version:       1.1.0
+Description:   Creates new equipment type with validation
+Parameters:
+    p_type_name TEXT                    -- Required equipment type name
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- New equipment type data as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.insertEquipmentType(
+    p_type_name text,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_new_id uuid;
+    v_trimmed_name text;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment type created successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_type_name IS NULL OR trim(p_type_name) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type name cannot be empty';
+        RETURN;
+    END IF;
+    
+    v_trimmed_name := trim(p_type_name);
+    
+    -- validate length
+    IF length(v_trimmed_name) < 2 OR length(v_trimmed_name) > 255 THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type name must be between 2 and 255 characters';
+        RETURN;
+    END IF;
+    
+    -- check for existing type
+    IF EXISTS (SELECT 1 FROM core.equipment_type WHERE type_name = v_trimmed_name AND deleted_at IS NULL) THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type already exists';
+        RETURN;
+    END IF;
+    
+    -- insert new equipment type
+    INSERT INTO core.equipment_type (type_name)
+    VALUES (v_trimmed_name)
+    RETURNING type_id INTO v_new_id;
+    
+    -- return created data
+    "Data" := jsonb_build_object(
+        'type_id', v_new_id,
+        'type_name', v_trimmed_name,
+        'equipment_count', 0,
+        'created_at', now()
+    );
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type already exists';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.updateEquipmentType
+Version:       1.1.0
+Description:   Updates existing equipment type with validation
+Parameters:
+    p_type_id UUID                      -- Required equipment type ID
+    p_type_name TEXT                    -- Required new equipment type name
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Updated equipment type data as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.updateEquipmentType(
+    p_type_id uuid,
+    p_type_name text,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_trimmed_name text;
+    v_result RECORD;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment type updated successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_type_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type ID cannot be null';
+        RETURN;
+    END IF;
+    
+    IF p_type_name IS NULL OR trim(p_type_name) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type name cannot be empty';
+        RETURN;
+    END IF;
+    
+    v_trimmed_name := trim(p_type_name);
+    
+    -- validate length
+    IF length(v_trimmed_name) < 2 OR length(v_trimmed_name) > 255 THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type name must be between 2 and 255 characters';
+        RETURN;
+    END IF;
+    
+    -- check if equipment type id exists
+    IF NOT EXISTS (SELECT 1 FROM core.equipment_type WHERE type_id = p_type_id AND deleted_at IS NULL) THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type not found';
+        RETURN;
+    END IF;
+    
+    -- check for conflicts (excluding current type)
+    IF EXISTS (
+        SELECT 1 FROM core.equipment_type 
+        WHERE type_name = v_trimmed_name AND type_id != p_type_id AND deleted_at IS NULL
+    ) THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type name already exists';
+        RETURN;
+    END IF;
+    
+    -- update the equipment type
+    UPDATE core.equipment_type
+    SET type_name = v_trimmed_name,
+        updated_at = now()
+    WHERE type_id = p_type_id AND deleted_at IS NULL;
+    
+    -- get updated data with equipment count
+    SELECT 
+        et.type_id,
+        et.type_name,
+        COALESCE(COUNT(e.equipment_id), 0) as equipment_count,
+        et.created_at,
+        et.updated_at
+    INTO v_result
+    FROM core.equipment_type et
+    LEFT JOIN core.equipment e ON et.type_id = e.equipment_type_id AND e.deleted_at IS NULL
+    WHERE et.type_id = p_type_id
+    GROUP BY et.type_id, et.type_name, et.created_at, et.updated_at;
+    
+    "Data" := to_jsonb(v_result);
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type name already exists';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.deleteEquipmentType
+Version:       1.1.0
+Description:   Safely deletes equipment type with dependency checks
+Parameters:
+    p_type_id UUID                      -- Required equipment type ID
+    p_force_delete BOOLEAN=FALSE        -- Optional force delete flag
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Operation result data as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.deleteEquipmentType(
+    p_type_id uuid,
+    p_force_delete boolean DEFAULT FALSE,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_equipment_count integer;
+    v_type_name text;
+    v_deleted_count integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Equipment type deleted successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_type_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- get equipment type info and usage count
+    SELECT 
+        et.type_name, 
+        COALESCE(COUNT(e.equipment_id), 0)
+    INTO v_type_name, v_equipment_count
+    FROM core.equipment_type et
+    LEFT JOIN core.equipment e ON et.type_id = e.equipment_type_id AND e.deleted_at IS NULL
+    WHERE et.type_id = p_type_id AND et.deleted_at IS NULL
+    GROUP BY et.type_name;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type not found';
+        RETURN;
+    END IF;
+    
+    -- check if it's a default type
+    IF v_type_name IN ('enterprise', 'site', 'area', 'line', 'cell') THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete default equipment type: ' || v_type_name;
+        RETURN;
+    END IF;
+    
+    -- check for dependent equipment
+    IF v_equipment_count > 0 AND NOT p_force_delete THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete equipment type: ' || v_equipment_count || ' equipment items are using this type. Use force_delete=true to override.';
+        RETURN;
+    END IF;
+    
+    -- the soft delete doesn't trip the foreign key, live equipment still needs its type
+    IF v_equipment_count > 0 THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete equipment type: equipment items are still using this type';
+        RETURN;
+    END IF;
+    
+    -- soft delete the equipment type
+    UPDATE core.equipment_type SET deleted_at = now()
+    WHERE type_id = p_type_id AND deleted_at IS NULL;
+    GET DIAGNOSTICS v_deleted_count = ROW_COUNT;
+    
+    IF v_deleted_count = 0 THEN
+        "Status" := 'Error';
+        "Message" := 'Equipment type could not be deleted';
+        RETURN;
+    END IF;
+    
+    "Data" := jsonb_build_object(
+        'deleted_type_id', p_type_id,
+        'deleted_type_name', v_type_name,
+        'affected_equipment_count', v_equipment_count,
+        'force_delete_used', p_force_delete
+    );
+    
+EXCEPTION 
+    WHEN foreign_key_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete equipment type: equipment items are still using this type';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getModeGroupById
+Version:       1.1.0
+Description:   Retrieves specific mode group by ID
+Parameters:
+    p_mode_group_id UUID                -- Required mode group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Mode group data with modes and equipment as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getModeGroupById(
+    p_mode_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_mode_group RECORD;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Mode group retrieved successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_mode_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- get mode group basic info
+    SELECT 
+        mg.mode_group_id,
+        mg.mode_group_name,
+        mg.mode_group_description,
+        mg.created_at,
+        mg.updated_at
+    INTO v_mode_group
+    FROM core.mode_group mg
+    WHERE mg.mode_group_id = p_mode_group_id AND mg.deleted_at IS NULL;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+    
+    -- build complete response
+    "Data" := jsonb_build_object(
+        'mode_group_id', v_mode_group.mode_group_id,
+        'mode_group_name', v_mode_group.mode_group_name,
+        'mode_group_description', v_mode_group.mode_group_description,
+        'created_at', v_mode_group.created_at,
+        'updated_at', v_mode_group.updated_at
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getModeGroupByName
+Version:       1.1.0
+Description:   Retrieves specific mode group by name
+Parameters:
+    p_mode_group_name VARCHAR(255)      -- Required mode group name
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Mode group data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getModeGroupByName(
+    p_mode_group_name varchar(255),
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_mode_group_id uuid;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Mode group retrieved successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_mode_group_name IS NULL OR trim(p_mode_group_name) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group name cannot be empty';
+        RETURN;
+    END IF;
+    
+    -- get mode group ID by name
+    SELECT mode_group_id INTO v_mode_group_id
+    FROM core.mode_group
+    WHERE mode_group_name = trim(p_mode_group_name) AND deleted_at IS NULL;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+    
+    -- delegate to getModeGroupById
+    SELECT mg."Status", mg."Message", mg."Data"
+    INTO "Status", "Message", "Data"
+    FROM core.getModeGroupById(v_mode_group_id) mg;
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.insertModeGroup
+Version:       1.1.0
+Description:   Creates new mode group with validation
+Parameters:
+    p_mode_group_name VARCHAR(255)      -- Required mode group name
+    p_mode_group_description TEXT       -- Required mode group description
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- New mode group data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.insertModeGroup(
+    p_mode_group_name varchar(255),
+    p_mode_group_description text,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_new_id uuid;
+    v_trimmed_name varchar(255);
+    v_trimmed_description text;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Mode group created successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_mode_group_name IS NULL OR trim(p_mode_group_name) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group name cannot be empty';
+        RETURN;
+    END IF;
+    
+    IF p_mode_group_description IS NULL OR trim(p_mode_group_description) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group description cannot be empty';
+        RETURN;
+    END IF;
+    
+    v_trimmed_name := trim(p_mode_group_name);
+    v_trimmed_description := trim(p_mode_group_description);
+    
+    -- validate lengths
+    IF length(v_trimmed_name) < 2 OR length(v_trimmed_name) > 255 THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group name must be between 2 and 255 characters';
+        RETURN;
+    END IF;
+    
+    IF length(v_trimmed_description) < 5 OR length(v_trimmed_description) > 2048 THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group description must be between 5 and 2048 characters';
+        RETURN;
+    END IF;
+    
+    -- check for lengh
+    IF EXISTS (SELECT 1 FROM core.mode_group WHERE mode_group_name = v_trimmed_name AND deleted_at IS NULL) THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group name already exists';
+        RETURN;
+    END IF;
+    
+    -- add new mode group
+    INSERT INTO core.mode_group (mode_group_name, mode_group_description)
+    VALUES (v_trimmed_name, v_trimmed_description)
+    RETURNING mode_group_id INTO v_new_id;
+    
+    -- created data
+    "Data" := jsonb_build_object(
+        'mode_group_id', v_new_id,
+        'mode_group_name', v_trimmed_name,
+        'mode_group_description', v_trimmed_description,
+        'mode_count', 0,
+        'equipment_count', 0,
+        'created_at', now()
+    );
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group name already exists';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.updateModeGroup
+Version:       1.1.0
+Description:   Updates existing mode group with validation
+Parameters:
+    p_mode_group_id UUID                -- Required mode group ID
+    p_mode_group_name VARCHAR(255)      -- Optional new mode group name (null = no change)
+    p_mode_group_description TEXT       -- Optional new mode group description (null = no change)
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Updated mode group data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.updateModeGroup(
+    p_mode_group_id uuid,
+    p_mode_group_name varchar(255) DEFAULT NULL,
+    p_mode_group_description text DEFAULT NULL,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_current_record RECORD;
+    v_new_name varchar(255);
+    v_new_description text;
+    v_changes_made boolean := false;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Mode group updated successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_mode_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- get the current counts
+    SELECT mode_group_name, mode_group_description
+    INTO v_current_record
+    FROM core.mode_group
+    WHERE mode_group_id = p_mode_group_id AND deleted_at IS NULL;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+    
+    -- determine what to update (COALESCE pattern for partial updates)
+    v_new_name := COALESCE(trim(p_mode_group_name), v_current_record.mode_group_name);
+    v_new_description := COALESCE(trim(p_mode_group_description), v_current_record.mode_group_description);
+    
+    -- validate new values if provided
+    IF p_mode_group_name IS NOT NULL THEN
+        IF trim(p_mode_group_name) = '' THEN
+            "Status" := 'Error';
+            "Message" := 'Mode group name cannot be empty';
+            RETURN;
+        END IF;
+        
+        IF length(v_new_name) < 2 OR length(v_new_name) > 255 THEN
+            "Status" := 'Error';
+            "Message" := 'Mode group name must be between 2 and 255 characters';
+            RETURN;
+        END IF;
+        
+        -- check for conflicts
+        IF EXISTS (
+            SELECT 1 FROM core.mode_group 
+            WHERE mode_group_name = v_new_name AND mode_group_id != p_mode_group_id AND deleted_at IS NULL
+        ) THEN
+            "Status" := 'Error';
+            "Message" := 'Mode group name already exists';
+            RETURN;
+        END IF;
+        
+        v_changes_made := true;
+    END IF;
+    
+    IF p_mode_group_description IS NOT NULL THEN
+        IF trim(p_mode_group_description) = '' THEN
+            "Status" := 'Error';
+            "Message" := 'Mode group description cannot be empty';
+            RETURN;
+        END IF;
+        
+        IF length(v_new_description) < 5 OR length(v_new_description) > 2048 THEN
+            "Status" := 'Error';
+            "Message" := 'Mode group description must be between 5 and 2048 characters';
+            RETURN;
+        END IF;
+        
+        v_changes_made := true;
+    END IF;
+    
+    -- Update if changes were made
+    IF v_changes_made THEN
+        UPDATE core.mode_group
+        SET mode_group_name = v_new_name,
+            mode_group_description = v_new_description,
+            updated_at = now()
+        WHERE mode_group_id = p_mode_group_id AND deleted_at IS NULL;
+    ELSE
+        "Message" := 'No changes were made to mode group';
+    END IF;
+    
+    -- Return updated data using getModeGroupById
+    SELECT mg."Data" INTO "Data"
+    FROM core.getModeGroupById(p_mode_group_id) mg
+    WHERE mg."Status" = 'Success';
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group name already exists';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.deleteModeGroup
+Version:       1.1.0
+Description:   Safely deletes mode group with dependency checks
+Parameters:
+    p_mode_group_id UUID                -- Required mode group ID
+    p_force_delete BOOLEAN=FALSE        -- Optional force delete flag
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Operation result data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.deleteModeGroup(
+    p_mode_group_id uuid,
+    p_force_delete boolean DEFAULT FALSE,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_mode_count integer;
+    v_equipment_count integer;
+    v_mode_group_name varchar(255);
+    v_deleted_count integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Mode group deleted successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_mode_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- get mode group info and dependency counts
+    SELECT 
+        mg.mode_group_name,
+        COALESCE(COUNT(DISTINCT m.mode_id), 0),
+        COALESCE(COUNT(DISTINCT emgm.equipment_id), 0)
+    INTO v_mode_group_name, v_mode_count, v_equipment_count
+    FROM core.mode_group mg
+    LEFT JOIN core.mode m ON mg.mode_group_id = m.mode_group_id AND m.deleted_at IS NULL
+    LEFT JOIN core.equipment_mode_group_mapping emgm ON mg.mode_group_id = emgm.mode_group_id
+        AND emgm.equipment_id IN (SELECT equipment_id FROM core.equipment WHERE deleted_at IS NULL)
+    WHERE mg.mode_group_id = p_mode_group_id AND mg.deleted_at IS NULL
+    GROUP BY mg.mode_group_name;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+    
+    -- check if it's the default mode group even if force is true
+    IF v_mode_group_name = 'Default MES Mode Group' THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete default MES mode group';
+        RETURN;
+    END IF;
+    
+    -- check for deps
+    IF (v_mode_count > 0 OR v_equipment_count > 0) AND NOT p_force_delete THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete mode group: ' || v_mode_count || ' mode(s) and ' || v_equipment_count || ' equipment association(s) exist. Use force_delete=true to override.';
+        RETURN;
+    END IF;
+    
+    -- for delete, start with associations
+    -- force soft deletes the modes, the equipment mappings are kept for a restore
+    IF p_force_delete THEN
+        UPDATE core.mode SET deleted_at = now()
+        WHERE mode_group_id = p_mode_group_id AND deleted_at IS NULL;
+    END IF;
+    
+    -- soft delete the mode group
+    UPDATE core.mode_group SET deleted_at = now()
+    WHERE mode_group_id = p_mode_group_id AND deleted_at IS NULL;
+    GET DIAGNOSTICS v_deleted_count = ROW_COUNT;
+    
+    IF v_deleted_count = 0 THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group could not be deleted';
+        RETURN;
+    END IF;
+    
+    "Data" := jsonb_build_object(
+        'deleted_mode_group_id', p_mode_group_id,
+        'deleted_mode_group_name', v_mode_group_name,
+        'affected_mode_count', v_mode_count,
+        'affected_equipment_count', v_equipment_count,
+        'force_delete_used', p_force_delete
+    );
+    
+EXCEPTION 
+    WHEN foreign_key_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete mode group: dependencies still exist';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getStateGroupById
+Version:       1.1.0
+Description:   Retrieves specific state group by ID
+Parameters:
+    p_state_group_id UUID               -- Required state group ID
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- State group data with states and equipment as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getStateGroupById(
+    p_state_group_id uuid,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_state_group RECORD;
+    v_states jsonb;
+    v_equipment jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'State group retrieved successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- basic state group info
+    SELECT 
+        sg.state_group_id,
+        sg.state_group_name,
+        sg.state_group_description,
+        sg.created_at,
+        sg.updated_at
+    INTO v_state_group
+    FROM core.state_group sg
+    WHERE sg.state_group_id = p_state_group_id AND sg.deleted_at IS NULL;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    -- response
+    "Data" := jsonb_build_object(
+        'state_group_id', v_state_group.state_group_id,
+        'state_group_name', v_state_group.state_group_name,
+        'state_group_description', v_state_group.state_group_description,
+        'created_at', v_state_group.created_at,
+        'updated_at', v_state_group.updated_at
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getStateGroupByName
+Version:       1.1.0
+Description:   Retrieves specific state group by name
+Parameters:
+    p_state_group_name VARCHAR(255)     -- Required state group name
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- State group data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getStateGroupByName(
+    p_state_group_name varchar(255),
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_state_group_id uuid;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'State group retrieved successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_state_group_name IS NULL OR trim(p_state_group_name) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'State group name cannot be empty';
+        RETURN;
+    END IF;
+    
+    -- get state by id
+    SELECT state_group_id INTO v_state_group_id
+    FROM core.state_group
+    WHERE state_group_name = trim(p_state_group_name) AND deleted_at IS NULL;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    -- Delegate to getStateGroupById
+    SELECT sg."Status", sg."Message", sg."Data"
+    INTO "Status", "Message", "Data"
+    FROM core.getStateGroupById(v_state_group_id) sg;
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.insertStateGroup
+Version:       1.1.0
+Description:   Creates new state group with validation
+Parameters:
+    p_state_group_name VARCHAR(255)     -- Required state group name
+    p_state_group_description TEXT      -- Required state group description
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- New state group data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.insertStateGroup(
+    p_state_group_name varchar(255),
+    p_state_group_description text,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_new_id uuid;
+    v_trimmed_name varchar(255);
+    v_trimmed_description text;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'State group created successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_state_group_name IS NULL OR trim(p_state_group_name) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'State group name cannot be empty';
+        RETURN;
+    END IF;
+    
+    IF p_state_group_description IS NULL OR trim(p_state_group_description) = '' THEN
+        "Status" := 'Error';
+        "Message" := 'State group description cannot be empty';
+        RETURN;
+    END IF;
+    
+    v_trimmed_name := trim(p_state_group_name);
+    v_trimmed_description := trim(p_state_group_description);
+    
+    -- validation sizes
+    IF length(v_trimmed_name) < 2 OR length(v_trimmed_name) > 255 THEN
+        "Status" := 'Error';
+        "Message" := 'State group name must be between 2 and 255 characters';
+        RETURN;
+    END IF;
+    
+    IF length(v_trimmed_description) < 5 OR length(v_trimmed_description) > 2048 THEN
+        "Status" := 'Error';
+        "Message" := 'State group description must be between 5 and 2048 characters';
+        RETURN;
+    END IF;
+    
+    -- state group exist
+    IF EXISTS (SELECT 1 FROM core.state_group WHERE state_group_name = v_trimmed_name AND deleted_at IS NULL) THEN
+        "Status" := 'Error';
+        "Message" := 'State group name already exists';
+        RETURN;
+    END IF;
+    
+    -- insert new state group
+    INSERT INTO core.state_group (state_group_name, state_group_description)
+    VALUES (v_trimmed_name, v_trimmed_description)
+    RETURNING state_group_id INTO v_new_id;
+    
+    -- data
+    "Data" := jsonb_build_object(
+        'state_group_id', v_new_id,
+        'state_group_name', v_trimmed_name,
+        'state_group_description', v_trimmed_description,
+        'state_count', 0,
+        'equipment_count', 0,
+        'created_at', now()
+    );
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'State group name already exists';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.updateStateGroup
+Version:       1.1.0
+Description:   Updates existing state group with validation
+Parameters:
+    p_state_group_id UUID               -- Required state group ID
+    p_state_group_name VARCHAR(255)     -- Optional new state group name (null = no change)
+    p_state_group_description TEXT      -- Optional new state group description (null = no change)
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Updated state group data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.updateStateGroup(
+    p_state_group_id uuid,
+    p_state_group_name varchar(255) DEFAULT NULL,
+    p_state_group_description text DEFAULT NULL,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_current_record RECORD;
+    v_new_name varchar(255);
+    v_new_description text;
+    v_changes_made boolean := false;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'State group updated successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- current values
+    SELECT state_group_name, state_group_description
+    INTO v_current_record
+    FROM core.state_group
+    WHERE state_group_id = p_state_group_id AND deleted_at IS NULL;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    -- what to update
+    v_new_name := COALESCE(trim(p_state_group_name), v_current_record.state_group_name);
+    v_new_description := COALESCE(trim(p_state_group_description), v_current_record.state_group_description);
+    
+    -- validate the new values with if to check provided
+    IF p_state_group_name IS NOT NULL THEN
+        IF trim(p_state_group_name) = '' THEN
+            "Status" := 'Error';
+            "Message" := 'State group name cannot be empty';
+            RETURN;
+        END IF;
+        
+        IF length(v_new_name) < 2 OR length(v_new_name) > 255 THEN
+            "Status" := 'Error';
+            "Message" := 'State group name must be between 2 and 255 characters';
+            RETURN;
+        END IF;
+        
+        -- name conflicts
+        IF EXISTS (
+            SELECT 1 FROM core.state_group 
+            WHERE state_group_name = v_new_name AND state_group_id != p_state_group_id AND deleted_at IS NULL
+        ) THEN
+            "Status" := 'Error';
+            "Message" := 'State group name already exists';
+            RETURN;
+        END IF;
+        
+        v_changes_made := true;
+    END IF;
+    
+    IF p_state_group_description IS NOT NULL THEN
+        IF trim(p_state_group_description) = '' THEN
+            "Status" := 'Error';
+            "Message" := 'State group description cannot be empty';
+            RETURN;
+        END IF;
+        
+        IF length(v_new_description) < 5 OR length(v_new_description) > 2048 THEN
+            "Status" := 'Error';
+            "Message" := 'State group description must be between 5 and 2048 characters';
+            RETURN;
+        END IF;
+        
+        v_changes_made := true;
+    END IF;
+    
+    -- update if changes were made
+    IF v_changes_made THEN
+        UPDATE core.state_group
+        SET state_group_name = v_new_name,
+            state_group_description = v_new_description,
+            updated_at = now()
+        WHERE state_group_id = p_state_group_id AND deleted_at IS NULL;
+    ELSE
+        "Message" := 'No changes were made to state group';
+    END IF;
+    
+    -- return updated data using getStateGroupById
+    SELECT sg."Data" INTO "Data"
+    FROM core.getStateGroupById(p_state_group_id) sg
+    WHERE sg."Status" = 'Success';
+    
+EXCEPTION 
+    WHEN unique_violation THEN
+        "Status" := 'Error';
+        "Message" := 'State group name already exists';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.deleteStateGroup
+Version:       1.1.0
+Description:   Safely deletes state group with dependency checks
+Parameters:
+    p_state_group_id UUID               -- Required state group ID
+    p_force_delete BOOLEAN=FALSE        -- Optional force delete flag
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Operation result data as JSON
+Change Log:
+    2025-07-28  hunter init
+    2026-10-17  hunter  only live rows, delete is a soft delete
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.deleteStateGroup(
+    p_state_group_id uuid,
+    p_force_delete boolean DEFAULT FALSE,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_state_count integer;
+    v_equipment_count integer;
+    v_state_group_name varchar(255);
+    v_deleted_count integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'State group deleted successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    -- state group info and dependency counts
+    SELECT 
+        sg.state_group_name,
+        COALESCE(COUNT(DISTINCT s.state_id), 0),
+        COALESCE(COUNT(DISTINCT esgm.equipment_id), 0)
+    INTO v_state_group_name, v_state_count, v_equipment_count
+    FROM core.state_group sg
+    LEFT JOIN core.state s ON sg.state_group_id = s.state_group_id AND s.deleted_at IS NULL
+    LEFT JOIN core.equipment_state_group_mapping esgm ON sg.state_group_id = esgm.state_group_id
+        AND esgm.equipment_id IN (SELECT equipment_id FROM core.equipment WHERE deleted_at IS NULL)
+    WHERE sg.state_group_id = p_state_group_id AND sg.deleted_at IS NULL
+    GROUP BY sg.state_group_name;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    -- default state group will not be deleted
+    IF v_state_group_name = 'Default MES State Group' THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete default MES state group';
+        RETURN;
+    END IF;
+    
+    -- check for dependencies
+    IF (v_state_count > 0 OR v_equipment_count > 0) AND NOT p_force_delete THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete state group: ' || v_state_count || ' state(s) and ' || v_equipment_count || ' equipment association(s) exist. Use force_delete=true to override.';
+        RETURN;
+    END IF;
+    
+    -- force delete remove dependencies first
+    -- force soft deletes the states, the equipment mappings are kept for a restore
+    IF p_force_delete THEN
+        UPDATE core.state SET deleted_at = now()
+        WHERE state_group_id = p_state_group_id AND deleted_at IS NULL;
+    END IF;
+    
+    -- soft delete the state group
+    UPDATE core.state_group SET deleted_at = now()
+    WHERE state_group_id = p_state_group_id AND deleted_at IS NULL;
+    GET DIAGNOSTICS v_deleted_count = ROW_COUNT;
+    
+    IF v_deleted_count = 0 THEN
+        "Status" := 'Error';
+        "Message" := 'State group could not be deleted';
+        RETURN;
+    END IF;
+    
+    "Data" := jsonb_build_object(
+        'deleted_state_group_id', p_state_group_id,
+        'deleted_state_group_name', v_state_group_name,
+        'affected_state_count', v_state_count,
+        'affected_equipment_count', v_equipment_count,
+        'force_delete_used', p_force_delete
+    );
+    
+EXCEPTION 
+    WHEN foreign_key_violation THEN
+        "Status" := 'Error';
+        "Message" := 'Cannot delete state group: dependencies still exist';
+        "Data" := NULL;
+    WHEN OTHERS THEN
+        "Status" := 'Error';
+        "Message" := 'Unexpected error: ' || SQLERRM;
+        "Data" := NULL;
+END;
+$$;
@@ -0,0 +1,359 @@
+-- reverts 020_usage_stats_procedures.up.sql
+-- the versions from 004 and 005
+
+DROP FUNCTION core.getModeGroupUsageStats(timestamptz, timestamptz, uuid);
+DROP FUNCTION core.getModeUsageStats(timestamptz, timestamptz, uuid);
+DROP FUNCTION core.getStateGroupUsageStats(timestamptz, timestamptz, uuid);
+DROP FUNCTION core.getStateUsageStats(timestamptz, timestamptz, uuid);
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getModeGroupUsageStats
+Version:       1.0.0
+Description:   Retrieves detailed usage statistics for all mode groups
+Parameters:
+    None
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage statistics as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getModeGroupUsageStats(
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_results jsonb;
+    v_total_mode_groups integer;
+    v_active_mode_groups integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Usage statistics retrieved successfully';
+    "Data" := NULL;
+    
+    -- get stats
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'mode_group_id', mg.mode_group_id,
+            'mode_group_name', mg.mode_group_name,
+            'mode_group_description', mg.mode_group_description,
+            'mode_count', COALESCE(COUNT(DISTINCT m.mode_id), 0),
+            'equipment_count', COALESCE(COUNT(DISTINCT emgm.equipment_id), 0),
+            'enabled_equipment_count', COALESCE(COUNT(DISTINCT CASE WHEN e.equipment_enabled THEN emgm.equipment_id END), 0),
+            'is_default_group', (mg.mode_group_name = 'Default MES Mode Group'),
+            'created_at', mg.created_at,
+            'updated_at', mg.updated_at
+        ) ORDER BY mg.mode_group_name
+    )
+    INTO v_results
+    FROM core.mode_group mg
+    LEFT JOIN core.mode m ON mg.mode_group_id = m.mode_group_id
+    LEFT JOIN core.equipment_mode_group_mapping emgm ON mg.mode_group_id = emgm.mode_group_id
+    LEFT JOIN core.equipment e ON emgm.equipment_id = e.equipment_id
+    GROUP BY mg.mode_group_id, mg.mode_group_name, mg.mode_group_description, mg.created_at, mg.updated_at;
+    
+    -- get summary counts
+    SELECT 
+        COUNT(*),
+        COUNT(CASE WHEN mg.mode_group_id IN (
+            SELECT DISTINCT emgm.mode_group_id 
+            FROM core.equipment_mode_group_mapping emgm
+        ) THEN 1 END)
+    INTO v_total_mode_groups, v_active_mode_groups
+    FROM core.mode_group mg;
+    
+    "Data" := jsonb_build_object(
+        'mode_groups', COALESCE(v_results, '[]'::jsonb),
+        'summary', jsonb_build_object(
+            'total_mode_groups', v_total_mode_groups,
+            'active_mode_groups', v_active_mode_groups,
+            'unused_mode_groups', v_total_mode_groups - v_active_mode_groups
+        )
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28 
+Procedure:     core.getModeUsageStats
+Version:       1.0.0
+Description:   Retrieves detailed usage statistics for all modes
+Parameters:
+    None
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage statistics as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getModeUsageStats(
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_results jsonb;
+    v_summary jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Mode usage statistics retrieved successfully';
+    "Data" := NULL;
+    
+    -- detailed mode statistics
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'mode_id', m.mode_id,
+            'mode_description', m.mode_description,
+            'mode_group', jsonb_build_object(
+                'mode_group_id', mg.mode_group_id,
+                'mode_group_name', mg.mode_group_name,
+                'mode_group_description', mg.mode_group_description
+            ),
+            'is_default_mode', (mg.mode_group_name = 'Default MES Mode Group'),
+            'created_at', m.created_at,
+            'updated_at', m.updated_at
+        ) ORDER BY mg.mode_group_name, m.mode_description
+    )
+    INTO v_results
+    FROM core.mode m
+    JOIN core.mode_group mg ON m.mode_group_id = mg.mode_group_id;
+    
+    -- summart stats
+    SELECT jsonb_build_object(
+        'total_modes', COUNT(*),
+        'default_modes', COUNT(CASE WHEN mg.mode_group_name = 'Default MES Mode Group' THEN 1 END),
+        'custom_modes', COUNT(CASE WHEN mg.mode_group_name != 'Default MES Mode Group' THEN 1 END),
+        'mode_groups_with_modes', COUNT(DISTINCT m.mode_group_id),
+        'total_mode_groups', (SELECT COUNT(*) FROM core.mode_group)
+    )
+    INTO v_summary
+    FROM core.mode m
+    JOIN core.mode_group mg ON m.mode_group_id = mg.mode_group_id;
+    
+    "Data" := jsonb_build_object(
+        'modes', COALESCE(v_results, '[]'::jsonb),
+        'summary', v_summary
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getStateGroupUsageStats
+Version:       1.0.0
+Description:   Retrieves detailed usage stats for all state groups
+Parameters:
+    None
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage statistics as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getStateGroupUsageStats(
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_results jsonb;
+    v_total_state_groups integer;
+    v_active_state_groups integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Usage statistics retrieved successfully';
+    "Data" := NULL;
+    
+    -- stats
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'state_group_id', sg.state_group_id,
+            'state_group_name', sg.state_group_name,
+            'state_group_description', sg.state_group_description,
+            'state_count', COALESCE(COUNT(DISTINCT s.state_id), 0),
+            'equipment_count', COALESCE(COUNT(DISTINCT esgm.equipment_id), 0),
+            'enabled_equipment_count', COALESCE(COUNT(DISTINCT CASE WHEN e.equipment_enabled THEN esgm.equipment_id END), 0),
+            'state_code_range', CASE 
+                WHEN COUNT(s.state_id) > 0 THEN 
+                    jsonb_build_object(
+                        'min_code', MIN(s.state_code),
+                        'max_code', MAX(s.state_code)
+                    )
+                ELSE NULL
+            END,
+            'is_default_group', (sg.state_group_name = 'Default MES State Group'),
+            'created_at', sg.created_at,
+            'updated_at', sg.updated_at
+        ) ORDER BY sg.state_group_name
+    )
+    INTO v_results
+    FROM core.state_group sg
+    LEFT JOIN core.state s ON sg.state_group_id = s.state_group_id
+    LEFT JOIN core.equipment_state_group_mapping esgm ON sg.state_group_id = esgm.state_group_id
+    LEFT JOIN core.equipment e ON esgm.equipment_id = e.equipment_id
+    GROUP BY sg.state_group_id, sg.state_group_name, sg.state_group_description, sg.created_at, sg.updated_at;
+    
+    -- counts
+    SELECT 
+        COUNT(*),
+        COUNT(CASE WHEN sg.state_group_id IN (
+            SELECT DISTINCT esgm.state_group_id 
+            FROM core.equipment_state_group_mapping esgm
+        ) THEN 1 END)
+    INTO v_total_state_groups, v_active_state_groups
+    FROM core.state_group sg;
+    
+    "Data" := jsonb_build_object(
+        'state_groups', COALESCE(v_results, '[]'::jsonb),
+        'summary', jsonb_build_object(
+            'total_state_groups', v_total_state_groups,
+            'active_state_groups', v_active_state_groups,
+            'unused_state_groups', v_total_state_groups - v_active_state_groups
+        )
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getStateUsageStats
+Version:       1.0.0
+Description:   Retrieves detailed usage stats for all states
+Parameters:
+    None
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage stats as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getStateUsageStats(
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_results jsonb;
+    v_summary jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'State usage statistics retrieved successfully';
+    "Data" := NULL;
+    
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'state_group_id', sg.state_group_id,
+            'state_group_name', sg.state_group_name,
+            'state_group_description', sg.state_group_description,
+            'state_count', COALESCE(COUNT(s.state_id), 0),
+            'code_range', CASE 
+                WHEN COUNT(s.state_id) > 0 THEN 
+                    jsonb_build_object(
+                        'min_code', MIN(s.state_code),
+                        'max_code', MAX(s.state_code),
+                        'code_span', MAX(s.state_code) - MIN(s.state_code) + 1
+                    )
+                ELSE NULL
+            END,
+            'states', COALESCE(jsonb_agg(
+                jsonb_build_object(
+                    'state_id', s.state_id,
+                    'state_code', s.state_code,
+                    'state_description', s.state_description,
+                    'created_at', s.created_at,
+                    'updated_at', s.updated_at
+                ) ORDER BY s.state_code
+            ) FILTER (WHERE s.state_id IS NOT NULL), '[]'::jsonb),
+            'is_default_group', (sg.state_group_name = 'Default MES State Group'),
+            'created_at', sg.created_at,
+            'updated_at', sg.updated_at
+        ) ORDER BY sg.state_group_name
+    )
+    INTO v_results
+    FROM core.state_group sg
+    LEFT JOIN core.state s ON sg.state_group_id = s.state_group_id
+    GROUP BY sg.state_group_id, sg.state_group_name, sg.state_group_description, sg.created_at, sg.updated_at;
+
+    SELECT jsonb_build_object(
+        'total_states', (SELECT COUNT(*) FROM core.state),
+        'total_state_groups', (SELECT COUNT(*) FROM core.state_group),
+        'state_groups_with_states', (SELECT COUNT(DISTINCT state_group_id) FROM core.state),
+        'default_states', (
+            SELECT COUNT(*) 
+            FROM core.state s 
+            JOIN core.state_group sg ON s.state_group_id = sg.state_group_id 
+            WHERE sg.state_group_name = 'Default MES State Group'
+        ),
+        'custom_states', (
+            SELECT COUNT(*) 
+            FROM core.state s 
+            JOIN core.state_group sg ON s.state_group_id = sg.state_group_id 
+            WHERE sg.state_group_name != 'Default MES State Group'
+        ),
+        'code_range_overall', (
+            SELECT jsonb_build_object(
+                'min_code', MIN(state_code),
+                'max_code', MAX(state_code),
+                'total_span', MAX(state_code) - MIN(state_code) + 1
+            )
+            FROM core.state
+        )
+    )
+    INTO v_summary;
+    
+    "Data" := jsonb_build_object(
+        'state_groups', COALESCE(v_results, '[]'::jsonb),
+        'summary', v_summary
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
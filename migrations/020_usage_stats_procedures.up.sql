@@ -0,0 +1,585 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-17
+Schema:        core
+Version:       1.0.0
+Description:   Mode and state usage statistics with time spent over a range
+Change Log:
+    2026-10-17  hunter  init
+===========================================
+*/
+
+-- The usage stats from 004 and 005 counted deleted rows and three of the four failed on
+-- nested aggregates. These only see live rows and add how long equipment spent in each
+-- mode or state between p_from and p_to, read from the mode and state history, so codes
+-- nobody has used in a while can be found before they are cleaned up. The range defaults
+-- to the last 30 days, an interval still open counts up to now.
+-- The parameters changed, so the old functions are dropped rather than replaced.
+
+DROP FUNCTION core.getModeGroupUsageStats();
+DROP FUNCTION core.getModeUsageStats();
+DROP FUNCTION core.getStateGroupUsageStats();
+DROP FUNCTION core.getStateUsageStats();
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getModeGroupUsageStats
+Version:       1.1.0
+Description:   Retrieves usage statistics and runtime for mode groups
+Parameters:
+    p_from TIMESTAMPTZ                  -- Optional start of the range (null = 30 days before p_to)
+    p_to TIMESTAMPTZ                    -- Optional end of the range (null = now)
+    p_mode_group_id UUID                -- Optional mode group filter (null = all groups)
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage statistics as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, runtime over a range, optional group filter
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getModeGroupUsageStats(
+    p_from timestamptz DEFAULT NULL,
+    p_to timestamptz DEFAULT NULL,
+    p_mode_group_id uuid DEFAULT NULL,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_to timestamptz := COALESCE(p_to, now());
+    v_from timestamptz := COALESCE(p_from, COALESCE(p_to, now()) - interval '30 days');
+    v_results jsonb;
+    v_summary jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Usage statistics retrieved successfully';
+    "Data" := NULL;
+
+    IF v_from >= v_to THEN
+        "Status" := 'Error';
+        "Message" := 'Invalid time range: from must be before to';
+        RETURN;
+    END IF;
+
+    IF p_mode_group_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM core.mode_group
+        WHERE mode_group_id = p_mode_group_id AND deleted_at IS NULL
+    ) THEN
+        "Status" := 'Error';
+        "Message" := 'Mode group not found';
+        RETURN;
+    END IF;
+
+    -- runtime per live mode, then rolled up per group
+    WITH mode_usage AS (
+        SELECT
+            m.mode_id,
+            m.mode_group_id,
+            COALESCE(SUM(EXTRACT(EPOCH FROM
+                LEAST(COALESCE(h.ended_at, now()), v_to) - GREATEST(h.set_at, v_from)
+            )) FILTER (WHERE h.set_at < v_to AND COALESCE(h.ended_at, now()) > v_from), 0)::double precision AS runtime_seconds,
+            -- the left join gives modes never used one row without history
+            MAX(COALESCE(h.ended_at, now())) FILTER (WHERE h.history_id IS NOT NULL) AS last_used_at
+        FROM core.mode m
+        LEFT JOIN core.equipment_mode_history h ON h.mode_id = m.mode_id
+        WHERE m.deleted_at IS NULL
+        GROUP BY m.mode_id, m.mode_group_id
+    )
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'mode_group_id', mg.mode_group_id,
+            'mode_group_name', mg.mode_group_name,
+            'mode_group_description', mg.mode_group_description,
+            'mode_count', mu.mode_count,
+            'unused_mode_count', mu.unused_mode_count,
+            'equipment_count', eq.equipment_count,
+            'enabled_equipment_count', eq.enabled_equipment_count,
+            'runtime_seconds', mu.runtime_seconds,
+            'last_used_at', mu.last_used_at,
+            'is_default_group', (mg.mode_group_name = 'Default MES Mode Group'),
+            'created_at', mg.created_at,
+            'updated_at', mg.updated_at
+        ) ORDER BY mg.mode_group_name
+    )
+    INTO v_results
+    FROM core.mode_group mg
+    CROSS JOIN LATERAL (
+        SELECT
+            COUNT(*) AS mode_count,
+            COUNT(*) FILTER (WHERE u.runtime_seconds = 0) AS unused_mode_count,
+            COALESCE(SUM(u.runtime_seconds), 0) AS runtime_seconds,
+            MAX(u.last_used_at) AS last_used_at
+        FROM mode_usage u
+        WHERE u.mode_group_id = mg.mode_group_id
+    ) mu
+    CROSS JOIN LATERAL (
+        SELECT
+            COUNT(*) AS equipment_count,
+            COUNT(*) FILTER (WHERE e.equipment_enabled) AS enabled_equipment_count
+        FROM core.equipment_mode_group_mapping emgm
+        JOIN core.equipment e ON e.equipment_id = emgm.equipment_id
+        WHERE emgm.mode_group_id = mg.mode_group_id AND e.deleted_at IS NULL
+    ) eq
+    WHERE mg.deleted_at IS NULL
+      AND (p_mode_group_id IS NULL OR mg.mode_group_id = p_mode_group_id);
+
+    -- summary over the groups returned
+    SELECT jsonb_build_object(
+        'total_mode_groups', COUNT(*),
+        'active_mode_groups', COUNT(*) FILTER (WHERE (g->>'equipment_count')::integer > 0),
+        'unused_mode_groups', COUNT(*) FILTER (WHERE (g->>'equipment_count')::integer = 0),
+        'idle_mode_groups', COUNT(*) FILTER (WHERE (g->>'runtime_seconds')::double precision = 0)
+    )
+    INTO v_summary
+    FROM jsonb_array_elements(COALESCE(v_results, '[]'::jsonb)) g;
+
+    "Data" := jsonb_build_object(
+        'from', v_from,
+        'to', v_to,
+        'mode_groups', COALESCE(v_results, '[]'::jsonb),
+        'summary', v_summary
+    );
+
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getModeUsageStats
+Version:       1.1.0
+Description:   Retrieves usage statistics and runtime for modes
+Parameters:
+    p_from TIMESTAMPTZ                  -- Optional start of the range (null = 30 days before p_to)
+    p_to TIMESTAMPTZ                    -- Optional end of the range (null = now)
+    p_mode_id UUID                      -- Optional mode filter (null = all modes)
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage statistics as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, runtime over a range, optional mode filter
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getModeUsageStats(
+    p_from timestamptz DEFAULT NULL,
+    p_to timestamptz DEFAULT NULL,
+    p_mode_id uuid DEFAULT NULL,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_to timestamptz := COALESCE(p_to, now());
+    v_from timestamptz := COALESCE(p_from, COALESCE(p_to, now()) - interval '30 days');
+    v_results jsonb;
+    v_summary jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Mode usage statistics retrieved successfully';
+    "Data" := NULL;
+
+    IF v_from >= v_to THEN
+        "Status" := 'Error';
+        "Message" := 'Invalid time range: from must be before to';
+        RETURN;
+    END IF;
+
+    IF p_mode_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM core.mode WHERE mode_id = p_mode_id AND deleted_at IS NULL
+    ) THEN
+        "Status" := 'Error';
+        "Message" := 'Mode not found';
+        RETURN;
+    END IF;
+
+    -- detailed mode statistics
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'mode_id', m.mode_id,
+            'mode_description', m.mode_description,
+            'mode_group', jsonb_build_object(
+                'mode_group_id', mg.mode_group_id,
+                'mode_group_name', mg.mode_group_name,
+                'mode_group_description', mg.mode_group_description
+            ),
+            'is_default_mode', (mg.mode_group_name = 'Default MES Mode Group'),
+            'runtime_seconds', u.runtime_seconds,
+            'equipment_count', u.equipment_count,
+            'last_used_at', u.last_used_at,
+            'created_at', m.created_at,
+            'updated_at', m.updated_at
+        ) ORDER BY mg.mode_group_name, m.mode_description
+    )
+    INTO v_results
+    FROM core.mode m
+    JOIN core.mode_group mg ON m.mode_group_id = mg.mode_group_id
+    CROSS JOIN LATERAL (
+        SELECT
+            COALESCE(SUM(EXTRACT(EPOCH FROM
+                LEAST(COALESCE(h.ended_at, now()), v_to) - GREATEST(h.set_at, v_from)
+            )) FILTER (WHERE h.set_at < v_to AND COALESCE(h.ended_at, now()) > v_from), 0)::double precision AS runtime_seconds,
+            COUNT(DISTINCT h.equipment_id) FILTER (WHERE h.set_at < v_to AND COALESCE(h.ended_at, now()) > v_from) AS equipment_count,
+            MAX(COALESCE(h.ended_at, now())) AS last_used_at
+        FROM core.equipment_mode_history h
+        WHERE h.mode_id = m.mode_id
+    ) u
+    WHERE m.deleted_at IS NULL AND mg.deleted_at IS NULL
+      AND (p_mode_id IS NULL OR m.mode_id = p_mode_id);
+
+    -- summary over the modes returned
+    SELECT jsonb_build_object(
+        'total_modes', COUNT(*),
+        'default_modes', COUNT(*) FILTER (WHERE (x->>'is_default_mode')::boolean),
+        'custom_modes', COUNT(*) FILTER (WHERE NOT (x->>'is_default_mode')::boolean),
+        'unused_modes', COUNT(*) FILTER (WHERE (x->>'runtime_seconds')::double precision = 0),
+        'mode_groups_with_modes', COUNT(DISTINCT x->'mode_group'->>'mode_group_id'),
+        'total_mode_groups', (SELECT COUNT(*) FROM core.mode_group WHERE deleted_at IS NULL)
+    )
+    INTO v_summary
+    FROM jsonb_array_elements(COALESCE(v_results, '[]'::jsonb)) x;
+
+    "Data" := jsonb_build_object(
+        'from', v_from,
+        'to', v_to,
+        'modes', COALESCE(v_results, '[]'::jsonb),
+        'summary', v_summary
+    );
+
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getStateGroupUsageStats
+Version:       1.1.0
+Description:   Retrieves usage stats and runtime for state groups
+Parameters:
+    p_from TIMESTAMPTZ                  -- Optional start of the range (null = 30 days before p_to)
+    p_to TIMESTAMPTZ                    -- Optional end of the range (null = now)
+    p_state_group_id UUID               -- Optional state group filter (null = all groups)
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage statistics as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, runtime over a range, optional group filter
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getStateGroupUsageStats(
+    p_from timestamptz DEFAULT NULL,
+    p_to timestamptz DEFAULT NULL,
+    p_state_group_id uuid DEFAULT NULL,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_to timestamptz := COALESCE(p_to, now());
+    v_from timestamptz := COALESCE(p_from, COALESCE(p_to, now()) - interval '30 days');
+    v_results jsonb;
+    v_summary jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Usage statistics retrieved successfully';
+    "Data" := NULL;
+
+    IF v_from >= v_to THEN
+        "Status" := 'Error';
+        "Message" := 'Invalid time range: from must be before to';
+        RETURN;
+    END IF;
+
+    IF p_state_group_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM core.state_group
+        WHERE state_group_id = p_state_group_id AND deleted_at IS NULL
+    ) THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+
+    -- runtime per live state, then rolled up per group
+    WITH state_usage AS (
+        SELECT
+            s.state_id,
+            s.state_group_id,
+            s.state_code,
+            COALESCE(SUM(EXTRACT(EPOCH FROM
+                LEAST(COALESCE(h.ended_at, now()), v_to) - GREATEST(h.started_at, v_from)
+            )) FILTER (WHERE h.started_at < v_to AND COALESCE(h.ended_at, now()) > v_from), 0)::double precision AS runtime_seconds,
+            -- the left join gives states never reported one row without history
+            MAX(COALESCE(h.ended_at, now())) FILTER (WHERE h.history_id IS NOT NULL) AS last_used_at
+        FROM core.state s
+        LEFT JOIN core.equipment_state_history h ON h.state_id = s.state_id
+        WHERE s.deleted_at IS NULL
+        GROUP BY s.state_id, s.state_group_id, s.state_code
+    )
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'state_group_id', sg.state_group_id,
+            'state_group_name', sg.state_group_name,
+            'state_group_description', sg.state_group_description,
+            'state_count', su.state_count,
+            'unused_state_count', su.unused_state_count,
+            'equipment_count', eq.equipment_count,
+            'enabled_equipment_count', eq.enabled_equipment_count,
+            'state_code_range', CASE
+                WHEN su.state_count > 0 THEN
+                    jsonb_build_object(
+                        'min_code', su.min_code,
+                        'max_code', su.max_code,
+                        'code_span', su.max_code - su.min_code + 1
+                    )
+                ELSE NULL
+            END,
+            'runtime_seconds', su.runtime_seconds,
+            'last_used_at', su.last_used_at,
+            'is_default_group', (sg.state_group_name = 'Default MES State Group'),
+            'created_at', sg.created_at,
+            'updated_at', sg.updated_at
+        ) ORDER BY sg.state_group_name
+    )
+    INTO v_results
+    FROM core.state_group sg
+    CROSS JOIN LATERAL (
+        SELECT
+            COUNT(*) AS state_count,
+            COUNT(*) FILTER (WHERE u.runtime_seconds = 0) AS unused_state_count,
+            MIN(u.state_code) AS min_code,
+            MAX(u.state_code) AS max_code,
+            COALESCE(SUM(u.runtime_seconds), 0) AS runtime_seconds,
+            MAX(u.last_used_at) AS last_used_at
+        FROM state_usage u
+        WHERE u.state_group_id = sg.state_group_id
+    ) su
+    CROSS JOIN LATERAL (
+        SELECT
+            COUNT(*) AS equipment_count,
+            COUNT(*) FILTER (WHERE e.equipment_enabled) AS enabled_equipment_count
+        FROM core.equipment_state_group_mapping esgm
+        JOIN core.equipment e ON e.equipment_id = esgm.equipment_id
+        WHERE esgm.state_group_id = sg.state_group_id AND e.deleted_at IS NULL
+    ) eq
+    WHERE sg.deleted_at IS NULL
+      AND (p_state_group_id IS NULL OR sg.state_group_id = p_state_group_id);
+
+    -- summary over the groups returned
+    SELECT jsonb_build_object(
+        'total_state_groups', COUNT(*),
+        'active_state_groups', COUNT(*) FILTER (WHERE (g->>'equipment_count')::integer > 0),
+        'unused_state_groups', COUNT(*) FILTER (WHERE (g->>'equipment_count')::integer = 0),
+        'idle_state_groups', COUNT(*) FILTER (WHERE (g->>'runtime_seconds')::double precision = 0)
+    )
+    INTO v_summary
+    FROM jsonb_array_elements(COALESCE(v_results, '[]'::jsonb)) g;
+
+    "Data" := jsonb_build_object(
+        'from', v_from,
+        'to', v_to,
+        'state_groups', COALESCE(v_results, '[]'::jsonb),
+        'summary', v_summary
+    );
+
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getStateUsageStats
+Version:       1.1.0
+Description:   Retrieves usage stats and runtime for states, grouped by state group
+Parameters:
+    p_from TIMESTAMPTZ                  -- Optional start of the range (null = 30 days before p_to)
+    p_to TIMESTAMPTZ                    -- Optional end of the range (null = now)
+    p_state_id UUID                     -- Optional state filter (null = all states)
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Usage stats as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, runtime over a range, optional state filter
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getStateUsageStats(
+    p_from timestamptz DEFAULT NULL,
+    p_to timestamptz DEFAULT NULL,
+    p_state_id uuid DEFAULT NULL,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_to timestamptz := COALESCE(p_to, now());
+    v_from timestamptz := COALESCE(p_from, COALESCE(p_to, now()) - interval '30 days');
+    v_results jsonb;
+    v_summary jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'State usage statistics retrieved successfully';
+    "Data" := NULL;
+
+    IF v_from >= v_to THEN
+        "Status" := 'Error';
+        "Message" := 'Invalid time range: from must be before to';
+        RETURN;
+    END IF;
+
+    IF p_state_id IS NOT NULL AND NOT EXISTS (
+        SELECT 1 FROM core.state WHERE state_id = p_state_id AND deleted_at IS NULL
+    ) THEN
+        "Status" := 'Error';
+        "Message" := 'State not found';
+        RETURN;
+    END IF;
+
+    -- states are aggregated per group first, the old version nested the two aggregates
+    WITH state_usage AS (
+        SELECT
+            s.state_id,
+            s.state_group_id,
+            s.state_code,
+            s.state_description,
+            s.created_at,
+            s.updated_at,
+            u.runtime_seconds,
+            u.equipment_count,
+            u.last_used_at
+        FROM core.state s
+        CROSS JOIN LATERAL (
+            SELECT
+                COALESCE(SUM(EXTRACT(EPOCH FROM
+                    LEAST(COALESCE(h.ended_at, now()), v_to) - GREATEST(h.started_at, v_from)
+                )) FILTER (WHERE h.started_at < v_to AND COALESCE(h.ended_at, now()) > v_from), 0)::double precision AS runtime_seconds,
+                COUNT(DISTINCT h.equipment_id) FILTER (WHERE h.started_at < v_to AND COALESCE(h.ended_at, now()) > v_from) AS equipment_count,
+                MAX(COALESCE(h.ended_at, now())) AS last_used_at
+            FROM core.equipment_state_history h
+            WHERE h.state_id = s.state_id
+        ) u
+        WHERE s.deleted_at IS NULL
+          AND (p_state_id IS NULL OR s.state_id = p_state_id)
+    ),
+    group_usage AS (
+        SELECT
+            u.state_group_id,
+            COUNT(*) AS state_count,
+            COUNT(*) FILTER (WHERE u.runtime_seconds = 0) AS unused_state_count,
+            MIN(u.state_code) AS min_code,
+            MAX(u.state_code) AS max_code,
+            SUM(u.runtime_seconds) AS runtime_seconds,
+            jsonb_agg(
+                jsonb_build_object(
+                    'state_id', u.state_id,
+                    'state_code', u.state_code,
+                    'state_description', u.state_description,
+                    'runtime_seconds', u.runtime_seconds,
+                    'equipment_count', u.equipment_count,
+                    'last_used_at', u.last_used_at,
+                    'created_at', u.created_at,
+                    'updated_at', u.updated_at
+                ) ORDER BY u.state_code
+            ) AS states
+        FROM state_usage u
+        GROUP BY u.state_group_id
+    )
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'state_group_id', sg.state_group_id,
+            'state_group_name', sg.state_group_name,
+            'state_group_description', sg.state_group_description,
+            'state_count', COALESCE(gu.state_count, 0),
+            'unused_state_count', COALESCE(gu.unused_state_count, 0),
+            'code_range', CASE
+                WHEN gu.state_count > 0 THEN
+                    jsonb_build_object(
+                        'min_code', gu.min_code,
+                        'max_code', gu.max_code,
+                        'code_span', gu.max_code - gu.min_code + 1
+                    )
+                ELSE NULL
+            END,
+            'runtime_seconds', COALESCE(gu.runtime_seconds, 0),
+            'states', COALESCE(gu.states, '[]'::jsonb),
+            'is_default_group', (sg.state_group_name = 'Default MES State Group'),
+            'created_at', sg.created_at,
+            'updated_at', sg.updated_at
+        ) ORDER BY sg.state_group_name
+    )
+    INTO v_results
+    FROM core.state_group sg
+    LEFT JOIN group_usage gu ON gu.state_group_id = sg.state_group_id
+    WHERE sg.deleted_at IS NULL
+      -- a single state only brings its own group
+      AND (p_state_id IS NULL OR gu.state_group_id IS NOT NULL);
+
+    -- summary over the states returned
+    SELECT jsonb_build_object(
+        'total_states', COALESCE(SUM((g->>'state_count')::integer), 0),
+        'total_state_groups', (SELECT COUNT(*) FROM core.state_group WHERE deleted_at IS NULL),
+        'state_groups_with_states', COUNT(*) FILTER (WHERE (g->>'state_count')::integer > 0),
+        'default_states', COALESCE(SUM((g->>'state_count')::integer) FILTER (WHERE (g->>'is_default_group')::boolean), 0),
+        'custom_states', COALESCE(SUM((g->>'state_count')::integer) FILTER (WHERE NOT (g->>'is_default_group')::boolean), 0),
+        'unused_states', COALESCE(SUM((g->>'unused_state_count')::integer), 0),
+        'code_range_overall', CASE
+            WHEN COUNT(g->'code_range') FILTER (WHERE g->'code_range' <> 'null'::jsonb) > 0 THEN
+                jsonb_build_object(
+                    'min_code', MIN((g->'code_range'->>'min_code')::integer),
+                    'max_code', MAX((g->'code_range'->>'max_code')::integer),
+                    'code_span', MAX((g->'code_range'->>'max_code')::integer) - MIN((g->'code_range'->>'min_code')::integer) + 1
+                )
+            ELSE NULL
+        END
+    )
+    INTO v_summary
+    FROM jsonb_array_elements(COALESCE(v_results, '[]'::jsonb)) g;
+
+    "Data" := jsonb_build_object(
+        'from', v_from,
+        'to', v_to,
+        'state_groups', COALESCE(v_results, '[]'::jsonb),
+        'summary', v_summary
+    );
+
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
@@ -0,0 +1,114 @@
+-- reverts 021_state_code_reservations.up.sql
+-- the version from 005
+
+DROP FUNCTION core.getAvailableStateCodes(uuid, integer, integer, text);
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getAvailableStateCodes
+Version:       1.0.0
+Description:   Returns available state codes for a state group within a range
+Parameters:
+    p_state_group_id UUID               -- Required state group ID
+    p_min_code INTEGER=0                -- Optional minimum code range
+    p_max_code INTEGER=100              -- Optional maximum code range
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Available codes and usage info as JSON
+Change Log:
+    2025-07-28  hunter init
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getAvailableStateCodes(
+    p_state_group_id uuid,
+    p_min_code integer DEFAULT 0,
+    p_max_code integer DEFAULT 100,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_state_group_name varchar(255);
+    v_used_codes integer[];
+    v_available_codes integer[];
+    v_code integer;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Available state codes retrieved successfully';
+    "Data" := NULL;
+    
+    -- p_validation
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+    
+    IF p_min_code < 0 OR p_max_code > 9999 OR p_min_code >= p_max_code THEN
+        "Status" := 'Error';
+        "Message" := 'Invalid code range: min must be >= 0, max must be <= 9999, and min < max';
+        RETURN;
+    END IF;
+    
+    -- state group verfication
+    SELECT state_group_name INTO v_state_group_name
+    FROM core.state_group
+    WHERE state_group_id = p_state_group_id;
+    
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+    
+    -- find me the used
+    SELECT array_agg(state_code ORDER BY state_code) INTO v_used_codes
+    FROM core.state
+    WHERE state_group_id = p_state_group_id
+    AND state_code BETWEEN p_min_code AND p_max_code;
+    
+    -- available codes
+    v_available_codes := ARRAY[]::integer[];
+    FOR v_code IN p_min_code..p_max_code LOOP
+        IF v_used_codes IS NULL OR v_code != ALL(v_used_codes) THEN
+            v_available_codes := array_append(v_available_codes, v_code);
+        END IF;
+    END LOOP;
+    
+    "Data" := jsonb_build_object(
+        'state_group_id', p_state_group_id,
+        'state_group_name', v_state_group_name,
+        'code_range', jsonb_build_object(
+            'min_code', p_min_code,
+            'max_code', p_max_code,
+            'total_range', p_max_code - p_min_code + 1
+        ),
+        'used_codes', COALESCE(v_used_codes, ARRAY[]::integer[]),
+        'available_codes', v_available_codes,
+        'usage_stats', jsonb_build_object(
+            'used_count', COALESCE(array_length(v_used_codes, 1), 0),
+            'available_count', array_length(v_available_codes, 1),
+            'usage_percentage', CASE 
+                WHEN p_max_code - p_min_code + 1 > 0 THEN 
+                    ROUND((COALESCE(array_length(v_used_codes, 1), 0)::numeric / (p_max_code - p_min_code + 1)) * 100, 2)
+                ELSE 0 
+            END
+        )
+    );
+    
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
+
+DROP TABLE core.state_code_reservation;
+
+drop extension if exists btree_gist;
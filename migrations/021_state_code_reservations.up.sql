@@ -0,0 +1,184 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-17
+Schema:        core
+Version:       1.0.0
+Description:   State code ranges reserved per state group, and free codes that respect them
+Change Log:
+    2026-10-17  hunter  init
+===========================================
+*/
+
+-- When a new PLC vendor is onboarded a block of codes in a state group is set aside for
+-- them, so integrators working on different vendors don't hand out the same codes.
+-- Reservations are inclusive ranges and may not overlap within a group. A state can only be
+-- created inside a reservation by the owner of that reservation.
+
+-- for the overlap constraint, gist has no operator class for uuid without it
+create extension if not exists btree_gist;
+
+CREATE TABLE core.state_code_reservation (
+    reservation_id uuid PRIMARY KEY DEFAULT uuid_generate_v1mc(),
+    state_group_id uuid NOT NULL REFERENCES core.state_group(state_group_id) ON DELETE CASCADE,
+    min_code integer NOT NULL,
+    max_code integer NOT NULL,
+    owner text NOT NULL,
+    label text,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz,
+    CONSTRAINT chk_state_code_reservation_range
+        CHECK (min_code >= 0 AND max_code <= 9999 AND min_code <= max_code),
+    CONSTRAINT chk_state_code_reservation_owner CHECK (btrim(owner) <> ''),
+    CONSTRAINT excl_state_code_reservation_overlap EXCLUDE USING gist (
+        state_group_id WITH =,
+        int4range(min_code, max_code, '[]') WITH &&
+    )
+);
+
+SELECT trigger_updated_at('core.state_code_reservation');
+select app.trigger_audit('core.state_code_reservation', 'state_code_reservation', 'reservation_id');
+
+COMMENT ON TABLE core.state_code_reservation IS 'Ranges of state codes in a state group set aside for one owner, e.g. a PLC vendor';
+COMMENT ON COLUMN core.state_code_reservation.owner IS 'Who may create states with codes in the range';
+COMMENT ON COLUMN core.state_code_reservation.label IS 'What the range is for, free text';
+
+-- The parameters changed, so the function from 005 is dropped rather than replaced.
+DROP FUNCTION core.getAvailableStateCodes(uuid, integer, integer);
+
+/*
+===========================================
+Author:        hunter
+Created:       2025-07-28
+Procedure:     core.getAvailableStateCodes
+Version:       1.1.0
+Description:   Returns available state codes for a state group within a range
+Parameters:
+    p_state_group_id UUID               -- Required state group ID
+    p_min_code INTEGER=0                -- Optional minimum code range (null = 0)
+    p_max_code INTEGER=100              -- Optional maximum code range (null = 100)
+    p_owner TEXT                        -- Optional owner, codes reserved for anyone else are not available
+    OUT Status VARCHAR(50)              -- 'Success' or 'Error'
+    OUT Message TEXT                    -- Details or error text
+    OUT Data JSONB                      -- Available codes and usage info as JSON
+Change Log:
+    2025-07-28  hunter  init
+    2026-10-17  hunter  only live rows, reserved codes, optional owner
+===========================================
+*/
+CREATE OR REPLACE FUNCTION core.getAvailableStateCodes(
+    p_state_group_id uuid,
+    p_min_code integer DEFAULT 0,
+    p_max_code integer DEFAULT 100,
+    p_owner text DEFAULT NULL,
+    OUT "Status" varchar(50),
+    OUT "Message" text,
+    OUT "Data" jsonb
+)
+LANGUAGE plpgsql
+SECURITY DEFINER
+AS $$
+DECLARE
+    v_min_code integer := COALESCE(p_min_code, 0);
+    v_max_code integer := COALESCE(p_max_code, 100);
+    v_owner text := NULLIF(btrim(p_owner), '');
+    v_state_group_name varchar(255);
+    v_used_codes integer[];
+    v_reserved_codes integer[];
+    v_available_codes integer[];
+    v_reservations jsonb;
+BEGIN
+    -- init out
+    "Status" := 'Success';
+    "Message" := 'Available state codes retrieved successfully';
+    "Data" := NULL;
+
+    -- p_validation
+    IF p_state_group_id IS NULL THEN
+        "Status" := 'Error';
+        "Message" := 'State group ID cannot be null';
+        RETURN;
+    END IF;
+
+    IF v_min_code < 0 OR v_max_code > 9999 OR v_min_code >= v_max_code THEN
+        "Status" := 'Error';
+        "Message" := 'Invalid code range: min must be >= 0, max must be <= 9999, and min < max';
+        RETURN;
+    END IF;
+
+    -- state group verfication
+    SELECT state_group_name INTO v_state_group_name
+    FROM core.state_group
+    WHERE state_group_id = p_state_group_id
+    AND deleted_at IS NULL;
+
+    IF NOT FOUND THEN
+        "Status" := 'Error';
+        "Message" := 'State group not found';
+        RETURN;
+    END IF;
+
+    SELECT array_agg(state_code ORDER BY state_code) INTO v_used_codes
+    FROM core.state
+    WHERE state_group_id = p_state_group_id
+    AND deleted_at IS NULL
+    AND state_code BETWEEN v_min_code AND v_max_code;
+
+    -- every reservation touching the range
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'reservation_id', reservation_id,
+            'min_code', min_code,
+            'max_code', max_code,
+            'owner', owner,
+            'label', label
+        ) ORDER BY min_code
+    ) INTO v_reservations
+    FROM core.state_code_reservation
+    WHERE state_group_id = p_state_group_id
+    AND min_code <= v_max_code
+    AND max_code >= v_min_code;
+
+    -- the codes in the range held by reservations of other owners
+    SELECT array_agg(c ORDER BY c) INTO v_reserved_codes
+    FROM core.state_code_reservation r
+    CROSS JOIN generate_series(GREATEST(r.min_code, v_min_code), LEAST(r.max_code, v_max_code)) c
+    WHERE r.state_group_id = p_state_group_id
+    AND (v_owner IS NULL OR r.owner <> v_owner);
+
+    -- free when nobody uses it and it isn't reserved for someone else, without an owner
+    -- every reserved code counts as taken
+    SELECT COALESCE(array_agg(c ORDER BY c), ARRAY[]::integer[]) INTO v_available_codes
+    FROM generate_series(v_min_code, v_max_code) c
+    WHERE c <> ALL(COALESCE(v_used_codes, ARRAY[]::integer[]))
+    AND c <> ALL(COALESCE(v_reserved_codes, ARRAY[]::integer[]));
+
+    "Data" := jsonb_build_object(
+        'state_group_id', p_state_group_id,
+        'state_group_name', v_state_group_name,
+        'owner', v_owner,
+        'code_range', jsonb_build_object(
+            'min_code', v_min_code,
+            'max_code', v_max_code,
+            'total_range', v_max_code - v_min_code + 1
+        ),
+        'used_codes', COALESCE(v_used_codes, ARRAY[]::integer[]),
+        'reserved_codes', COALESCE(v_reserved_codes, ARRAY[]::integer[]),
+        'available_codes', v_available_codes,
+        'reservations', COALESCE(v_reservations, '[]'::jsonb),
+        'usage_stats', jsonb_build_object(
+            'used_count', COALESCE(array_length(v_used_codes, 1), 0),
+            'reserved_count', COALESCE(array_length(v_reserved_codes, 1), 0),
+            'available_count', COALESCE(array_length(v_available_codes, 1), 0),
+            'usage_percentage', ROUND(
+                (COALESCE(array_length(v_used_codes, 1), 0)::numeric / (v_max_code - v_min_code + 1)) * 100, 2
+            )
+        )
+    );
+
+EXCEPTION WHEN OTHERS THEN
+    "Status" := 'Error';
+    "Message" := 'Unexpected error: ' || SQLERRM;
+    "Data" := NULL;
+END;
+$$;
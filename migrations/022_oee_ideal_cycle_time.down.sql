@@ -0,0 +1,3 @@
+-- reverts 022_oee_ideal_cycle_time.up.sql
+
+DROP TABLE core.equipment_ideal_cycle_time;
@@ -0,0 +1,25 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-17
+Schema:        core
+Version:       1.0.0
+Description:   Ideal cycle time per equipment for oee performance
+Change Log:
+    2026-10-17  hunter  init
+===========================================
+*/
+
+-- performance is the ideal time for the parts counted over the run time they took,
+-- equipment without a row has no performance
+CREATE TABLE core.equipment_ideal_cycle_time (
+    equipment_id uuid PRIMARY KEY REFERENCES core.equipment(equipment_id) ON DELETE CASCADE,
+    ideal_cycle_seconds double precision NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz,
+    CONSTRAINT chk_ideal_cycle_seconds_positive CHECK (ideal_cycle_seconds > 0)
+);
+
+SELECT trigger_updated_at('core.equipment_ideal_cycle_time');
+
+COMMENT ON TABLE core.equipment_ideal_cycle_time IS 'Fastest time the equipment can make one part, the oee performance baseline';
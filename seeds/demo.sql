@@ -0,0 +1,83 @@
+/*
+===========================================
+Author:        hunter
+Created:       2026-10-16
+Schema:        core, operations
+Version:       1.0.0
+Description:   Demo plant model for development and trying out the api, loaded by `gatherer_mes db seed`.
+               Rows that already exist are left alone so it can be run more than once.
+Change Log:
+    2026-10-16  hunter  init
+===========================================
+*/
+
+-- get or create equipment by name, type and parent, gives back its id
+CREATE OR REPLACE FUNCTION pg_temp.seed_equipment(name text, type_name text, parent_id uuid)
+    RETURNS uuid AS
+$$
+DECLARE
+    found_id uuid;
+BEGIN
+    SELECT e.equipment_id INTO found_id
+    FROM core.equipment e
+    JOIN core.equipment_type t ON t.type_id = e.equipment_type_id
+    WHERE e.equipment_name = name
+      AND t.type_name = seed_equipment.type_name
+      AND e.equipment_parent_id IS NOT DISTINCT FROM parent_id;
+
+    IF found_id IS NULL THEN
+        INSERT INTO core.equipment (equipment_name, equipment_type_id, equipment_parent_id)
+        SELECT name, t.type_id, parent_id
+        FROM core.equipment_type t
+        WHERE t.type_name = seed_equipment.type_name
+        RETURNING equipment_id INTO found_id;
+    END IF;
+
+    RETURN found_id;
+END;
+$$ LANGUAGE plpgsql;
+
+-- enterprise > site > area > line > cells
+DO $$
+DECLARE
+    enterprise_id uuid := pg_temp.seed_equipment('Acme', 'enterprise', NULL);
+    site_id uuid := pg_temp.seed_equipment('Plant 1', 'site', enterprise_id);
+    area_id uuid := pg_temp.seed_equipment('Packaging', 'area', site_id);
+    line_id uuid := pg_temp.seed_equipment('Line 1', 'line', area_id);
+BEGIN
+    PERFORM pg_temp.seed_equipment('Filler', 'cell', line_id);
+    PERFORM pg_temp.seed_equipment('Capper', 'cell', line_id);
+    PERFORM pg_temp.seed_equipment('Labeler', 'cell', line_id);
+END $$;
+
+-- the line and its cells run with the default mode and state groups
+INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+SELECT e.equipment_id, mg.mode_group_id
+FROM core.equipment e
+JOIN core.equipment_type t ON t.type_id = e.equipment_type_id
+CROSS JOIN core.mode_group mg
+WHERE t.type_name IN ('line', 'cell')
+  AND e.equipment_name IN ('Line 1', 'Filler', 'Capper', 'Labeler')
+  AND mg.mode_group_name = 'Default MES Mode Group'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+SELECT e.equipment_id, sg.state_group_id
+FROM core.equipment e
+JOIN core.equipment_type t ON t.type_id = e.equipment_type_id
+CROSS JOIN core.state_group sg
+WHERE t.type_name IN ('line', 'cell')
+  AND e.equipment_name IN ('Line 1', 'Filler', 'Capper', 'Labeler')
+  AND sg.state_group_name = 'Default MES State Group'
+ON CONFLICT DO NOTHING;
+
+-- something to make
+insert into operations.product (product_name, product_description)
+values ('Sparkling Water 500ml', 'Demo product')
+on conflict do nothing;
+
+insert into operations.work_order (work_order_name, product_id, planned_quantity)
+select 'WO-0001', product_id, 10000
+from operations.product
+where product_name = 'Sparkling Water 500ml'
+on conflict do nothing;
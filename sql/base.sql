@@ -34,9 +34,9 @@ END $$;
 DO $$
 BEGIN
     IF NOT EXISTS (
-        SELECT FROM pg_roles WHERE rolname = 'ignition' -- for my container
+        SELECT FROM pg_roles WHERE rolname = 'ignition' -- for my container, login is set up by hand
     ) THEN
-        CREATE ROLE ignition LOGIN PASSWORD 'P@ssword1!';
+        CREATE ROLE ignition NOLOGIN;
     END IF;
 END $$;
 
@@ -0,0 +1,123 @@
+use crate::config::{ApiKeyCommand, DatabaseConfig, DbCommand, MigrateCommand};
+use crate::database::migrations::{MigrationQueries, MigrationState};
+use crate::database::seed::SeedQueries;
+use crate::services::api_key_service::ApiKeyService;
+use crate::services::purge_service::PurgeService;
+use anyhow::Context;
+
+/// `migrate up|status|down`
+pub async fn migrate(database: DatabaseConfig, command: MigrateCommand) -> anyhow::Result<()> {
+    database.validate()?;
+    let db = database.connect().await?;
+
+    match command {
+        MigrateCommand::Up => {
+            MigrationQueries::run(&db)
+                .await
+                .context("failed to apply migrations")?;
+            println!("Database is up to date");
+        }
+        MigrateCommand::Status => {
+            let statuses = MigrationQueries::status(&db)
+                .await
+                .context("failed to read migration status")?;
+            for status in &statuses {
+                println!(
+                    "{:>4}  {:<8}  {}",
+                    status.version,
+                    status.state.as_str(),
+                    status.description
+                );
+            }
+
+            let pending = statuses
+                .iter()
+                .filter(|s| s.state == MigrationState::Pending)
+                .count();
+            println!("{} pending", pending);
+        }
+        MigrateCommand::Down { target } => {
+            let reverted = MigrationQueries::undo(&db, target)
+                .await
+                .context("failed to revert migrations")?;
+            if reverted.is_empty() {
+                println!("Nothing to revert");
+            }
+            for version in reverted {
+                println!("Reverted {}", version);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `db seed|purge`
+pub async fn db(database: DatabaseConfig, command: DbCommand) -> anyhow::Result<()> {
+    database.validate()?;
+    let db = database.connect().await?;
+
+    match command {
+        DbCommand::Seed => {
+            SeedQueries::seed_demo(&db)
+                .await
+                .context("failed to load the demo data")?;
+            println!("Demo data loaded");
+        }
+        DbCommand::Purge { retention } => {
+            let days = retention.soft_delete_retention_days;
+            let purged = PurgeService::new(db, days)
+                .purge()
+                .await
+                .context("failed to purge deleted rows")?;
+            for row in purged {
+                println!("{:<16}  {}", row.entity_type, row.purged);
+            }
+            println!("Purged rows deleted more than {} days ago", days);
+        }
+    }
+
+    Ok(())
+}
+
+/// `api-key issue|list|revoke`, mainly for creating the first admin key
+pub async fn api_key(database: DatabaseConfig, command: ApiKeyCommand) -> anyhow::Result<()> {
+    database.validate()?;
+    let service = ApiKeyService::new(database.connect().await?);
+
+    match command {
+        ApiKeyCommand::Issue { name, scopes } => {
+            let scopes = ApiKeyService::parse_scopes(&scopes)?;
+            let (api_key, key) = service.issue(&name, &scopes).await?;
+            println!(
+                "Issued api key '{}' ({})",
+                api_key.key_name, api_key.api_key_id
+            );
+            println!("{}", key);
+            println!("Store it now, it can't be shown again");
+        }
+        ApiKeyCommand::List => {
+            for api_key in service.get_all().await? {
+                let scopes: Vec<&str> = api_key.scopes.iter().map(|s| s.as_str()).collect();
+                println!(
+                    "{}  {:<12}  {:<18}  {}{}",
+                    api_key.api_key_id,
+                    api_key.key_prefix,
+                    scopes.join(","),
+                    api_key.key_name,
+                    if api_key.revoked_at.is_some() {
+                        "  (revoked)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        ApiKeyCommand::Revoke { api_key_id } => {
+            let api_key = service.revoke(api_key_id).await?;
+            println!("Revoked api key '{}'", api_key.key_name);
+        }
+    }
+
+    Ok(())
+}
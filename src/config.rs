@@ -1,9 +1,102 @@
-use clap::Parser;
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use std::path::PathBuf;
+use std::time::Duration;
 use url::Url;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
-pub struct Config {
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// run the http and grpc servers
+    Serve(Box<Config>),
+
+    /// apply, inspect or revert the database migrations
+    Migrate {
+        #[command(flatten)]
+        database: DatabaseConfig,
+
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+
+    /// database utilities
+    Db {
+        #[command(flatten)]
+        database: DatabaseConfig,
+
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+
+    /// issue, list or revoke the api keys used by http clients
+    ApiKey {
+        #[command(flatten)]
+        database: DatabaseConfig,
+
+        #[command(subcommand)]
+        command: ApiKeyCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MigrateCommand {
+    /// apply every pending migration
+    Up,
+
+    /// list the migrations and whether they are applied
+    Status,
+
+    /// revert the latest migration
+    Down {
+        /// revert every migration newer than this version instead, 0 reverts all of them
+        #[arg(long)]
+        target: Option<i64>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DbCommand {
+    /// load the demo plant model, rows that already exist are left alone
+    Seed,
+
+    /// remove soft deleted rows older than the retention for good, `serve` does this
+    /// periodically as well
+    Purge {
+        #[command(flatten)]
+        retention: RetentionConfig,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ApiKeyCommand {
+    /// create a key, it is printed once and can't be shown again
+    Issue {
+        /// name to tell the key apart, e.g. the client using it
+        #[arg(long)]
+        name: String,
+
+        /// read, write or admin, repeat for more than one
+        #[arg(long = "scope", required = true)]
+        scopes: Vec<String>,
+    },
+
+    /// list every key, revoked ones included
+    List,
+
+    /// stop a key from being accepted
+    Revoke { api_key_id: uuid::Uuid },
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DatabaseConfig {
     /// the connection url for the postgres database
     #[arg(long, env = "DATABASE_URL")]
     pub database_url: Url, // validates URL format automatically
@@ -11,15 +104,112 @@ pub struct Config {
     /// database connection pool size
     #[arg(long, env = "DB_POOL_SIZE", default_value = "50")]
     pub pool_size: u32,
+}
+
+impl DatabaseConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.pool_size == 0 {
+            anyhow::bail!("Pool size must be greater than 0");
+        }
+
+        if self.pool_size > 100 {
+            tracing::warn!(
+                "Pool size {} is quite large. PostgreSQL default max is 100 connections; suggest {}",
+                self.pool_size,
+                "97 max"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// shared connection pool for sqlx shared across the application
+    pub async fn connect(&self) -> anyhow::Result<PgPool> {
+        PgPoolOptions::new()
+            .max_connections(self.pool_size)
+            .connect(self.database_url.as_str())
+            .await
+            .context("could not connect to database_url")
+    }
+}
+
+/// Sign in through an OpenID Connect provider, only api keys are accepted when the issuer
+/// is not set
+#[derive(Args, Debug, Clone, Default)]
+pub struct OidcConfig {
+    /// expected `iss` of the access tokens, e.g. https://login.example.com/realms/plant
+    #[arg(long, env = "OIDC_ISSUER", requires = "oidc_jwks")]
+    pub oidc_issuer: Option<String>,
+
+    /// JWKS with the issuer's signing keys, a http(s) url or a path to a local file
+    #[arg(long, env = "OIDC_JWKS", requires = "oidc_issuer")]
+    pub oidc_jwks: Option<String>,
+
+    /// expected `aud` of the access tokens, not checked when not set
+    #[arg(long, env = "OIDC_AUDIENCE")]
+    pub oidc_audience: Option<String>,
+}
+
+/// How long soft deleted rows of the plant model are kept, they can be restored until then
+#[derive(Args, Debug, Clone)]
+pub struct RetentionConfig {
+    /// days a deleted equipment, type, mode, state or group is kept before it is purged
+    #[arg(long, env = "SOFT_DELETE_RETENTION_DAYS", default_value = "30")]
+    pub soft_delete_retention_days: u16,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct Config {
+    #[command(flatten)]
+    pub database: DatabaseConfig,
+
+    #[command(flatten)]
+    pub oidc: OidcConfig,
+
+    #[command(flatten)]
+    pub retention: RetentionConfig,
+
+    /// hours between purges of soft deleted rows, 0 turns the purge off
+    #[arg(long, env = "PURGE_INTERVAL_HOURS", default_value = "24")]
+    pub purge_interval_hours: u64,
+
+    /// apply pending migrations before starting the servers
+    #[arg(long, env = "AUTO_MIGRATE")]
+    pub auto_migrate: bool,
 
     /// server bind address
     #[arg(long, env = "BIND_ADDRESS", default_value = "0.0.0.0:19080")]
     pub bind_address: String,
 
-    // grpc bind address
-    // TODO: make grpc work
-    // #[clap(long, env = "GRPC_BIND_ADDRESS", default_value = "0.0.0.0:50051")]
-    // pub grpc_bind_address: String,
+    /// certificate chain (PEM) for serving HTTPS, plain HTTP when not set
+    #[arg(long, env = "TLS_CERT_PATH", requires = "tls_key_path")]
+    pub tls_cert_path: Option<PathBuf>,
+
+    /// private key (PEM) matching the TLS certificate
+    #[arg(long, env = "TLS_KEY_PATH", requires = "tls_cert_path")]
+    pub tls_key_path: Option<PathBuf>,
+
+    /// comma separated origins allowed to call the api from a browser, "*" allows any
+    #[arg(long, env = "CORS_ALLOWED_ORIGINS", value_delimiter = ',')]
+    pub cors_allowed_origins: Vec<String>,
+
+    /// seconds a request may take before it is answered with 408
+    #[arg(long, env = "REQUEST_TIMEOUT_SECS", default_value = "30")]
+    pub request_timeout_secs: u64,
+
+    /// largest accepted request body in bytes
+    #[arg(long, env = "BODY_LIMIT_BYTES", default_value = "2097152")]
+    pub body_limit_bytes: usize,
+
+    /// seconds in-flight requests get to finish after SIGTERM/SIGINT, open streams are
+    /// closed after that
+    #[arg(long, env = "SHUTDOWN_TIMEOUT_SECS", default_value = "30")]
+    pub shutdown_timeout_secs: u64,
+
+    /// grpc bind address
+    #[arg(long, env = "GRPC_BIND_ADDRESS", default_value = "0.0.0.0:50051")]
+    pub grpc_bind_address: String,
+
     /// log level
     #[arg(long, env = "RUST_LOG", default_value = "info")]
     pub log_level: String,
@@ -28,18 +218,30 @@ pub struct Config {
 impl Config {
     /// validate the configuration after parsing
     pub fn validate(&self) -> anyhow::Result<()> {
-        if self.pool_size == 0 {
-            anyhow::bail!("Pool size must be greater than 0");
+        self.database.validate()?;
+
+        if self.request_timeout_secs == 0 {
+            anyhow::bail!("Request timeout must be greater than 0");
         }
 
-        if self.pool_size > 100 {
-            tracing::warn!(
-                "Pool size {} is quite large. PostgreSQL default max is 100 connections; suggest {}",
-                self.pool_size,
-                "97 max"
-            );
+        if self.body_limit_bytes == 0 {
+            anyhow::bail!("Body limit must be greater than 0");
         }
 
         Ok(())
     }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    /// None when the purge is turned off
+    pub fn purge_interval(&self) -> Option<Duration> {
+        (self.purge_interval_hours > 0)
+            .then(|| Duration::from_secs(self.purge_interval_hours * 3600))
+    }
 }
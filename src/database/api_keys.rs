@@ -0,0 +1,114 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApiKeyRow {
+    pub api_key_id: Uuid,
+    pub key_name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub last_used_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+pub struct ApiKeyQueries;
+
+impl ApiKeyQueries {
+    pub async fn get_all(db: &PgPool) -> Result<Vec<ApiKeyRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"SELECT api_key_id, key_name, key_prefix, scopes, last_used_at, revoked_at,
+                      created_at, updated_at
+               FROM app.api_key
+               ORDER BY key_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Only keys that have not been revoked
+    pub async fn get_active_by_hash(
+        db: &PgPool,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"SELECT api_key_id, key_name, key_prefix, scopes, last_used_at, revoked_at,
+                      created_at, updated_at
+               FROM app.api_key
+               WHERE key_hash = $1 AND revoked_at IS NULL"#,
+            key_hash
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn create(
+        db: &PgPool,
+        key_name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        scopes: &[String],
+    ) -> Result<ApiKeyRow, sqlx::Error> {
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"INSERT INTO app.api_key (key_name, key_prefix, key_hash, scopes)
+               VALUES ($1, $2, $3, $4)
+               RETURNING api_key_id, key_name, key_prefix, scopes, last_used_at, revoked_at,
+                         created_at, updated_at"#,
+            key_name,
+            key_prefix,
+            key_hash,
+            scopes
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    /// Revoking an already revoked key keeps the original revoked_at
+    pub async fn revoke(db: &PgPool, api_key_id: Uuid) -> Result<Option<ApiKeyRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"UPDATE app.api_key
+               SET revoked_at = coalesce(revoked_at, now())
+               WHERE api_key_id = $1
+               RETURNING api_key_id, key_name, key_prefix, scopes, last_used_at, revoked_at,
+                         created_at, updated_at"#,
+            api_key_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Record that the key was used, at most once a minute so busy clients don't write
+    /// on every request
+    pub async fn touch(db: &PgPool, api_key_id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"UPDATE app.api_key
+               SET last_used_at = now()
+               WHERE api_key_id = $1
+                 AND (last_used_at IS NULL OR last_used_at < now() - interval '1 minute')"#,
+            api_key_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn name_exists(db: &PgPool, key_name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM app.api_key WHERE key_name = $1
+            )"#,
+            key_name
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+}
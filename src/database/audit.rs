@@ -0,0 +1,67 @@
+use crate::models::app::AuditFilter;
+use serde_json::Value;
+use sqlx::{PgPool, Postgres, Transaction};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AuditLogRow {
+    pub audit_id: Uuid,
+    pub occurred_at: OffsetDateTime,
+    pub actor: Option<String>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub transaction_id: i64,
+}
+
+pub struct AuditQueries;
+
+impl AuditQueries {
+    /// Starts a transaction whose changes to core are logged with `actor`. The rows in
+    /// app.audit_log are written by triggers (016) and commit or roll back with the change.
+    pub async fn begin(
+        db: &PgPool,
+        actor: Option<&str>,
+    ) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+        let mut tx = db.begin().await?;
+        sqlx::query_scalar!(
+            "SELECT set_config('app.actor', $1, true)",
+            actor.unwrap_or_default()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Ok(tx)
+    }
+
+    /// Log entries matching the filter, newest first
+    pub async fn get_filtered(
+        db: &PgPool,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditLogRow>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditLogRow,
+            r#"SELECT audit_id, occurred_at, actor, action, entity_type, entity_id,
+                      before, after, transaction_id
+               FROM app.audit_log
+               WHERE ($1::text IS NULL OR entity_type = $1)
+                 AND ($2::uuid IS NULL OR entity_id = $2)
+                 AND ($3::text IS NULL OR actor = $3)
+                 AND ($4::timestamptz IS NULL OR occurred_at >= $4)
+                 AND ($5::timestamptz IS NULL OR occurred_at < $5)
+               ORDER BY occurred_at DESC, audit_id DESC
+               LIMIT $6"#,
+            filter.entity_type.as_deref(),
+            filter.entity_id,
+            filter.actor.as_deref(),
+            filter.from,
+            filter.to,
+            filter.limit
+        )
+        .fetch_all(db)
+        .await
+    }
+}
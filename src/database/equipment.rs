@@ -1,5 +1,5 @@
 use serde_json::Value;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use std::collections::HashMap;
 use time::OffsetDateTime;
 use uuid::Uuid;
@@ -33,6 +33,7 @@ impl EquipmentQueries {
                       equipment_metadata,
                       created_at, updated_at
                FROM core.equipment
+               WHERE deleted_at IS NULL
                ORDER BY equipment_id"#
         )
         .fetch_all(db)
@@ -50,7 +51,7 @@ impl EquipmentQueries {
                       equipment_metadata,
                       created_at, updated_at
                FROM core.equipment
-               WHERE equipment_id = $1"#,
+               WHERE equipment_id = $1 AND deleted_at IS NULL"#,
             equipment_id
         )
         .fetch_optional(db)
@@ -68,7 +69,7 @@ impl EquipmentQueries {
                       equipment_metadata,
                       created_at, updated_at
                FROM core.equipment
-               WHERE equipment_name = $1"#,
+               WHERE equipment_name = $1 AND deleted_at IS NULL"#,
             equipment_name
         )
         .fetch_optional(db)
@@ -86,7 +87,7 @@ impl EquipmentQueries {
                       equipment_metadata,
                       created_at, updated_at
                FROM core.equipment
-               WHERE equipment_type_id = $1
+               WHERE equipment_type_id = $1 AND deleted_at IS NULL
                ORDER BY equipment_name"#,
             equipment_type_id
         )
@@ -105,7 +106,8 @@ impl EquipmentQueries {
                       equipment_metadata,
                       created_at, updated_at
                FROM core.equipment
-               WHERE equipment_parent_id = $1 OR (equipment_parent_id IS NULL AND $1 IS NULL)
+               WHERE (equipment_parent_id = $1 OR (equipment_parent_id IS NULL AND $1 IS NULL))
+                 AND deleted_at IS NULL
                ORDER BY equipment_name"#,
             equipment_parent_id
         )
@@ -121,7 +123,7 @@ impl EquipmentQueries {
                       equipment_metadata,
                       created_at, updated_at
                FROM core.equipment
-               WHERE equipment_enabled = true
+               WHERE equipment_enabled = true AND deleted_at IS NULL
                ORDER BY equipment_name"#
         )
         .fetch_all(db)
@@ -129,7 +131,7 @@ impl EquipmentQueries {
     }
 
     pub async fn create(
-        db: &PgPool,
+        db: &mut PgConnection,
         equipment_name: &str,
         equipment_type_id: Uuid,
         equipment_parent_id: Option<Uuid>,
@@ -154,25 +156,46 @@ impl EquipmentQueries {
             enabled,
             metadata
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
     }
 
-    pub async fn delete(db: &PgPool, equipment_id: Uuid) -> Result<bool, sqlx::Error> {
+    /// Soft delete, the mappings and history are kept until the row is purged
+    pub async fn delete(db: &mut PgConnection, equipment_id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query!(
-            "DELETE FROM core.equipment WHERE equipment_id = $1",
+            "UPDATE core.equipment SET deleted_at = NOW() WHERE equipment_id = $1 AND deleted_at IS NULL",
             equipment_id
         )
-        .execute(db)
+        .execute(&mut *db)
         .await?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// Undo a soft delete, None when there is no deleted equipment with this id
+    pub async fn restore(
+        db: &mut PgConnection,
+        equipment_id: Uuid,
+    ) -> Result<Option<Equipment>, sqlx::Error> {
+        sqlx::query_as!(
+            Equipment,
+            r#"UPDATE core.equipment
+               SET deleted_at = NULL
+               WHERE equipment_id = $1 AND deleted_at IS NOT NULL
+               RETURNING equipment_id, equipment_name, equipment_type_id,
+                         equipment_parent_id, equipment_enabled,
+                         equipment_metadata,
+                         created_at, updated_at"#,
+            equipment_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
     pub async fn exists(db: &PgPool, equipment_id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-                SELECT 1 FROM core.equipment WHERE equipment_id = $1
+                SELECT 1 FROM core.equipment WHERE equipment_id = $1 AND deleted_at IS NULL
             )"#,
             equipment_id
         )
@@ -183,7 +206,7 @@ impl EquipmentQueries {
     }
 
     pub async fn set_enabled(
-        db: &PgPool,
+        db: &mut PgConnection,
         equipment_id: Uuid,
         enabled: bool,
     ) -> Result<Option<Equipment>, sqlx::Error> {
@@ -191,7 +214,7 @@ impl EquipmentQueries {
             Equipment,
             r#"UPDATE core.equipment 
                SET equipment_enabled = $2, updated_at = NOW()
-               WHERE equipment_id = $1
+               WHERE equipment_id = $1 AND deleted_at IS NULL
                RETURNING equipment_id, equipment_name, equipment_type_id, 
                          equipment_parent_id, equipment_enabled, 
                          equipment_metadata,
@@ -199,12 +222,12 @@ impl EquipmentQueries {
             equipment_id,
             enabled
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
     }
 
     pub async fn update_metadata(
-        db: &PgPool,
+        db: &mut PgConnection,
         equipment_id: Uuid,
         metadata: &serde_json::Value,
     ) -> Result<Option<Equipment>, sqlx::Error> {
@@ -212,7 +235,7 @@ impl EquipmentQueries {
             Equipment,
             r#"UPDATE core.equipment 
                SET equipment_metadata = $2, updated_at = NOW()
-               WHERE equipment_id = $1
+               WHERE equipment_id = $1 AND deleted_at IS NULL
                RETURNING equipment_id, equipment_name, equipment_type_id, 
                          equipment_parent_id, equipment_enabled, 
                          equipment_metadata,
@@ -220,7 +243,171 @@ impl EquipmentQueries {
             equipment_id,
             metadata
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
+        .await
+    }
+
+    pub async fn update_name(
+        db: &mut PgConnection,
+        equipment_id: Uuid,
+        equipment_name: &str,
+    ) -> Result<Option<Equipment>, sqlx::Error> {
+        sqlx::query_as!(
+            Equipment,
+            r#"UPDATE core.equipment
+               SET equipment_name = $2, updated_at = NOW()
+               WHERE equipment_id = $1 AND deleted_at IS NULL
+               RETURNING equipment_id, equipment_name, equipment_type_id,
+                         equipment_parent_id, equipment_enabled,
+                         equipment_metadata,
+                         created_at, updated_at"#,
+            equipment_id,
+            equipment_name
+        )
+        .fetch_optional(&mut *db)
+        .await
+    }
+
+    pub async fn update_parent(
+        db: &mut PgConnection,
+        equipment_id: Uuid,
+        equipment_parent_id: Option<Uuid>,
+    ) -> Result<Option<Equipment>, sqlx::Error> {
+        sqlx::query_as!(
+            Equipment,
+            r#"UPDATE core.equipment
+               SET equipment_parent_id = $2, updated_at = NOW()
+               WHERE equipment_id = $1 AND deleted_at IS NULL
+               RETURNING equipment_id, equipment_name, equipment_type_id,
+                         equipment_parent_id, equipment_enabled,
+                         equipment_metadata,
+                         created_at, updated_at"#,
+            equipment_id,
+            equipment_parent_id
+        )
+        .fetch_optional(&mut *db)
+        .await
+    }
+
+    /// Check if a name is already used by equipment of the same type under the same parent,
+    /// mirroring the unique indexes on core.equipment
+    pub async fn name_exists_in_parent(
+        db: &PgPool,
+        equipment_name: &str,
+        equipment_type_id: Uuid,
+        equipment_parent_id: Option<Uuid>,
+        exclude_equipment_id: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM core.equipment
+                WHERE equipment_name = $1
+                  AND equipment_type_id = $2
+                  AND equipment_parent_id IS NOT DISTINCT FROM $3
+                  AND ($4::uuid IS NULL OR equipment_id != $4)
+                  AND deleted_at IS NULL
+            )"#,
+            equipment_name,
+            equipment_type_id,
+            equipment_parent_id,
+            exclude_equipment_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    pub async fn has_children(db: &PgPool, equipment_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM core.equipment WHERE equipment_parent_id = $1 AND deleted_at IS NULL
+            )"#,
+            equipment_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Returns the equipment and every descendant below it.
+    /// UNION (not UNION ALL) keeps the walk finite if a cycle ever slips in.
+    pub async fn get_subtree(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Vec<Equipment>, sqlx::Error> {
+        sqlx::query_as!(
+            Equipment,
+            r#"WITH RECURSIVE subtree AS (
+                   SELECT e.* FROM core.equipment e
+                   WHERE e.equipment_id = $1 AND e.deleted_at IS NULL
+                   UNION
+                   SELECT c.* FROM core.equipment c
+                   JOIN subtree s ON c.equipment_parent_id = s.equipment_id
+                   WHERE c.deleted_at IS NULL
+               )
+               SELECT equipment_id as "equipment_id!", equipment_name as "equipment_name!",
+                      equipment_type_id as "equipment_type_id!", equipment_parent_id,
+                      equipment_enabled as "equipment_enabled!", equipment_metadata,
+                      created_at, updated_at
+               FROM subtree
+               ORDER BY equipment_name"#,
+            equipment_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Check if `equipment_id` sits anywhere in the live subtree below (or at) `ancestor_id`
+    pub async fn is_in_subtree(
+        db: &PgPool,
+        ancestor_id: Uuid,
+        equipment_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"WITH RECURSIVE subtree AS (
+                   SELECT equipment_id FROM core.equipment
+                   WHERE equipment_id = $1 AND deleted_at IS NULL
+                   UNION
+                   SELECT c.equipment_id FROM core.equipment c
+                   JOIN subtree s ON c.equipment_parent_id = s.equipment_id
+                   WHERE c.deleted_at IS NULL
+               )
+               SELECT EXISTS(SELECT 1 FROM subtree WHERE equipment_id = $2)"#,
+            ancestor_id,
+            equipment_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Returns the ancestor chain ordered from the root down to the equipment itself.
+    pub async fn get_ancestors(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Vec<Equipment>, sqlx::Error> {
+        sqlx::query_as!(
+            Equipment,
+            r#"WITH RECURSIVE ancestors AS (
+                   SELECT e.*, 0 AS depth FROM core.equipment e
+                   WHERE e.equipment_id = $1 AND e.deleted_at IS NULL
+                   UNION ALL
+                   SELECT p.*, a.depth + 1 FROM core.equipment p
+                   JOIN ancestors a ON p.equipment_id = a.equipment_parent_id
+                   WHERE a.depth < 64
+               )
+               SELECT equipment_id as "equipment_id!", equipment_name as "equipment_name!",
+                      equipment_type_id as "equipment_type_id!", equipment_parent_id,
+                      equipment_enabled as "equipment_enabled!", equipment_metadata,
+                      created_at, updated_at
+               FROM ancestors
+               ORDER BY depth DESC"#,
+            equipment_id
+        )
+        .fetch_all(db)
         .await
     }
 }
@@ -245,11 +432,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_equipment(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
         let equipment_name = "Test Equipment";
 
         let equipment =
-            EquipmentQueries::create(&pool, equipment_name, type_id, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, equipment_name, type_id, None, None, None).await?;
 
         assert_eq!(equipment.equipment_name, equipment_name);
         assert_eq!(equipment.equipment_type_id, type_id);
@@ -263,15 +451,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_equipment_with_parent(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
 
         // Create parent equipment
         let parent =
-            EquipmentQueries::create(&pool, "Parent Equipment", type_id, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "Parent Equipment", type_id, None, None, None)
+                .await?;
 
         // Create child equipment
         let child = EquipmentQueries::create(
-            &pool,
+            &mut conn,
             "Child Equipment",
             type_id,
             Some(parent.equipment_id),
@@ -287,11 +477,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_equipment_with_metadata(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
         let metadata = json!({"config": "test", "value": 42});
 
         let equipment = EquipmentQueries::create(
-            &pool,
+            &mut conn,
             "Test Equipment",
             type_id,
             None,
@@ -307,10 +498,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_all_equipment(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
 
-        let eq1 = EquipmentQueries::create(&pool, "Equipment 1", type_id, None, None, None).await?;
-        let eq2 = EquipmentQueries::create(&pool, "Equipment 2", type_id, None, None, None).await?;
+        let eq1 =
+            EquipmentQueries::create(&mut conn, "Equipment 1", type_id, None, None, None).await?;
+        let eq2 =
+            EquipmentQueries::create(&mut conn, "Equipment 2", type_id, None, None, None).await?;
 
         let all_equipment = EquipmentQueries::get_all(&pool).await?;
 
@@ -331,9 +525,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_id(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
         let created =
-            EquipmentQueries::create(&pool, "Test Equipment", type_id, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "Test Equipment", type_id, None, None, None)
+                .await?;
 
         let found = EquipmentQueries::get_by_id(&pool, created.equipment_id).await?;
 
@@ -357,15 +553,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_type_id(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id1 = create_test_equipment_type(&pool, "Type 1").await?;
         let type_id2 = create_test_equipment_type(&pool, "Type 2").await?;
 
         let eq1 =
-            EquipmentQueries::create(&pool, "Equipment 1", type_id1, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "Equipment 1", type_id1, None, None, None).await?;
         let _eq2 =
-            EquipmentQueries::create(&pool, "Equipment 2", type_id2, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "Equipment 2", type_id2, None, None, None).await?;
         let eq3 =
-            EquipmentQueries::create(&pool, "Equipment 3", type_id1, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "Equipment 3", type_id1, None, None, None).await?;
 
         let type1_equipment = EquipmentQueries::get_by_type_id(&pool, type_id1).await?;
 
@@ -386,11 +583,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_parent_id(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
 
-        let parent = EquipmentQueries::create(&pool, "Parent", type_id, None, None, None).await?;
+        let parent =
+            EquipmentQueries::create(&mut conn, "Parent", type_id, None, None, None).await?;
         let child1 = EquipmentQueries::create(
-            &pool,
+            &mut conn,
             "Child 1",
             type_id,
             Some(parent.equipment_id),
@@ -399,7 +598,7 @@ mod tests {
         )
         .await?;
         let child2 = EquipmentQueries::create(
-            &pool,
+            &mut conn,
             "Child 2",
             type_id,
             Some(parent.equipment_id),
@@ -407,7 +606,8 @@ mod tests {
             None,
         )
         .await?;
-        let _orphan = EquipmentQueries::create(&pool, "Orphan", type_id, None, None, None).await?;
+        let _orphan =
+            EquipmentQueries::create(&mut conn, "Orphan", type_id, None, None, None).await?;
 
         let children = EquipmentQueries::get_by_parent_id(&pool, Some(parent.equipment_id)).await?;
 
@@ -428,12 +628,14 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_enabled(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
 
         let enabled =
-            EquipmentQueries::create(&pool, "Enabled", type_id, None, Some(true), None).await?;
+            EquipmentQueries::create(&mut conn, "Enabled", type_id, None, Some(true), None).await?;
         let disabled =
-            EquipmentQueries::create(&pool, "Disabled", type_id, None, Some(false), None).await?;
+            EquipmentQueries::create(&mut conn, "Disabled", type_id, None, Some(false), None)
+                .await?;
 
         let enabled_equipment = EquipmentQueries::get_enabled(&pool).await?;
 
@@ -453,12 +655,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_set_enabled(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
         let created =
-            EquipmentQueries::create(&pool, "Test Equipment", type_id, None, Some(true), None)
+            EquipmentQueries::create(&mut conn, "Test Equipment", type_id, None, Some(true), None)
                 .await?;
 
-        let updated = EquipmentQueries::set_enabled(&pool, created.equipment_id, false).await?;
+        let updated = EquipmentQueries::set_enabled(&mut conn, created.equipment_id, false).await?;
 
         assert!(updated.is_some());
         let updated = updated.unwrap();
@@ -470,13 +673,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_metadata(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
         let created =
-            EquipmentQueries::create(&pool, "Test Equipment", type_id, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "Test Equipment", type_id, None, None, None)
+                .await?;
         let new_metadata = json!({"updated": true, "version": 2});
 
         let updated =
-            EquipmentQueries::update_metadata(&pool, created.equipment_id, &new_metadata).await?;
+            EquipmentQueries::update_metadata(&mut conn, created.equipment_id, &new_metadata)
+                .await?;
 
         assert!(updated.is_some());
         let updated = updated.unwrap();
@@ -486,13 +692,163 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn test_update_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+        let type_id = create_test_equipment_type(&pool, "Test Type").await?;
+        let created =
+            EquipmentQueries::create(&mut conn, "Original Name", type_id, None, None, None).await?;
+
+        let updated =
+            EquipmentQueries::update_name(&mut conn, created.equipment_id, "Updated Name").await?;
+
+        assert!(updated.is_some());
+        assert_eq!(updated.unwrap().equipment_name, "Updated Name");
+
+        let missing = EquipmentQueries::update_name(&mut conn, Uuid::new_v4(), "Nope").await?;
+        assert!(missing.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_update_parent(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+        let type_id = create_test_equipment_type(&pool, "Test Type").await?;
+        let parent =
+            EquipmentQueries::create(&mut conn, "Parent", type_id, None, None, None).await?;
+        let child = EquipmentQueries::create(&mut conn, "Child", type_id, None, None, None).await?;
+
+        let moved = EquipmentQueries::update_parent(
+            &mut conn,
+            child.equipment_id,
+            Some(parent.equipment_id),
+        )
+        .await?
+        .unwrap();
+        assert_eq!(moved.equipment_parent_id, Some(parent.equipment_id));
+        assert!(EquipmentQueries::has_children(&pool, parent.equipment_id).await?);
+
+        let detached = EquipmentQueries::update_parent(&mut conn, child.equipment_id, None)
+            .await?
+            .unwrap();
+        assert!(detached.equipment_parent_id.is_none());
+        assert!(!EquipmentQueries::has_children(&pool, parent.equipment_id).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_name_exists_in_parent(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+        let type_id = create_test_equipment_type(&pool, "Test Type").await?;
+        let parent =
+            EquipmentQueries::create(&mut conn, "Parent", type_id, None, None, None).await?;
+        let child = EquipmentQueries::create(
+            &mut conn,
+            "Oven 1",
+            type_id,
+            Some(parent.equipment_id),
+            None,
+            None,
+        )
+        .await?;
+
+        let exists = EquipmentQueries::name_exists_in_parent(
+            &pool,
+            "Oven 1",
+            type_id,
+            Some(parent.equipment_id),
+            None,
+        )
+        .await?;
+        assert!(exists);
+
+        // the equipment itself is excluded when renaming
+        let excluded = EquipmentQueries::name_exists_in_parent(
+            &pool,
+            "Oven 1",
+            type_id,
+            Some(parent.equipment_id),
+            Some(child.equipment_id),
+        )
+        .await?;
+        assert!(!excluded);
+
+        // same name at the root level is a different slot
+        let root =
+            EquipmentQueries::name_exists_in_parent(&pool, "Oven 1", type_id, None, None).await?;
+        assert!(!root);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_subtree_and_ancestors(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+        let type_id = create_test_equipment_type(&pool, "Test Type").await?;
+        let root = EquipmentQueries::create(&mut conn, "Root", type_id, None, None, None).await?;
+        let mid = EquipmentQueries::create(
+            &mut conn,
+            "Mid",
+            type_id,
+            Some(root.equipment_id),
+            None,
+            None,
+        )
+        .await?;
+        let leaf = EquipmentQueries::create(
+            &mut conn,
+            "Leaf",
+            type_id,
+            Some(mid.equipment_id),
+            None,
+            None,
+        )
+        .await?;
+        EquipmentQueries::create(&mut conn, "Other Root", type_id, None, None, None).await?;
+
+        let subtree = EquipmentQueries::get_subtree(&pool, root.equipment_id).await?;
+        assert_eq!(subtree.len(), 3);
+
+        let subtree = EquipmentQueries::get_subtree(&pool, mid.equipment_id).await?;
+        assert_eq!(subtree.len(), 2);
+
+        let ancestors = EquipmentQueries::get_ancestors(&pool, leaf.equipment_id).await?;
+        let names: Vec<&str> = ancestors
+            .iter()
+            .map(|e| e.equipment_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Root", "Mid", "Leaf"]);
+
+        let missing = EquipmentQueries::get_subtree(&pool, Uuid::new_v4()).await?;
+        assert!(missing.is_empty());
+
+        assert!(
+            EquipmentQueries::is_in_subtree(&pool, root.equipment_id, leaf.equipment_id).await?
+        );
+        assert!(
+            !EquipmentQueries::is_in_subtree(&pool, leaf.equipment_id, root.equipment_id).await?
+        );
+
+        // soft-deleted descendants are not part of the subtree
+        EquipmentQueries::delete(&mut conn, leaf.equipment_id).await?;
+        assert!(
+            !EquipmentQueries::is_in_subtree(&pool, root.equipment_id, leaf.equipment_id).await?
+        );
+        assert!(EquipmentQueries::is_in_subtree(&pool, root.equipment_id, mid.equipment_id).await?);
+
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn test_delete_equipment(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
         let created =
-            EquipmentQueries::create(&pool, "To Delete", type_id, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "To Delete", type_id, None, None, None).await?;
 
-        let deleted = EquipmentQueries::delete(&pool, created.equipment_id).await?;
+        let deleted = EquipmentQueries::delete(&mut conn, created.equipment_id).await?;
         assert!(deleted);
 
         // Verify it's gone
@@ -504,8 +860,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_nonexistent(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let deleted = EquipmentQueries::delete(&pool, random_id).await?;
+        let deleted = EquipmentQueries::delete(&mut conn, random_id).await?;
 
         assert!(!deleted);
 
@@ -514,9 +871,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_exists(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_id = create_test_equipment_type(&pool, "Test Type").await?;
         let created =
-            EquipmentQueries::create(&pool, "Exists Test", type_id, None, None, None).await?;
+            EquipmentQueries::create(&mut conn, "Exists Test", type_id, None, None, None).await?;
 
         let exists = EquipmentQueries::exists(&pool, created.equipment_id).await?;
         assert!(exists);
@@ -530,11 +888,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_foreign_key_constraint(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_type_id = Uuid::new_v4();
 
         // Should fail due to foreign key constraint
         let result =
-            EquipmentQueries::create(&pool, "Invalid", random_type_id, None, None, None).await;
+            EquipmentQueries::create(&mut conn, "Invalid", random_type_id, None, None, None).await;
         assert!(result.is_err());
 
         if let Err(sqlx::Error::Database(db_err)) = result {
@@ -0,0 +1,289 @@
+use crate::database::events::{EventQueries, EventType};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// What `set_mode` did with a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeChange {
+    Set(Uuid),
+    /// the equipment is already in the mode, nothing was written
+    Unchanged,
+    /// the equipment was deleted before it could be locked
+    EquipmentMissing,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EquipmentModeHistoryRow {
+    pub history_id: Uuid,
+    pub equipment_id: Uuid,
+    pub mode_id: Uuid,
+    pub mode_description: String,
+    pub mode_group_id: Uuid,
+    pub set_at: OffsetDateTime,
+    pub ended_at: Option<OffsetDateTime>,
+    pub set_by: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CurrentEquipmentModeRow {
+    pub equipment_id: Uuid,
+    pub mode_id: Uuid,
+    pub mode_description: String,
+    pub mode_group_id: Uuid,
+    pub set_at: OffsetDateTime,
+    pub set_by: Option<String>,
+}
+
+pub struct EquipmentModeQueries;
+
+impl EquipmentModeQueries {
+    pub async fn get_current(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Option<CurrentEquipmentModeRow>, sqlx::Error> {
+        sqlx::query_as!(
+            CurrentEquipmentModeRow,
+            r#"SELECT equipment_id as "equipment_id!", mode_id as "mode_id!",
+                      mode_description as "mode_description!",
+                      mode_group_id as "mode_group_id!", set_at as "set_at!", set_by
+               FROM core.current_equipment_mode
+               WHERE equipment_id = $1"#,
+            equipment_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Intervals overlapping the optional [from, to) window, newest first
+    pub async fn get_history(
+        db: &PgPool,
+        equipment_id: Uuid,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> Result<Vec<EquipmentModeHistoryRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentModeHistoryRow,
+            r#"SELECT h.history_id, h.equipment_id, h.mode_id, m.mode_description,
+                      m.mode_group_id, h.set_at, h.ended_at, h.set_by
+               FROM core.equipment_mode_history h
+               JOIN core.mode m ON m.mode_id = h.mode_id
+               WHERE h.equipment_id = $1
+                 AND ($2::timestamptz IS NULL OR h.ended_at IS NULL OR h.ended_at > $2)
+                 AND ($3::timestamptz IS NULL OR h.set_at < $3)
+               ORDER BY h.set_at DESC"#,
+            equipment_id,
+            from,
+            to
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn get_history_entry(
+        db: &PgPool,
+        history_id: Uuid,
+    ) -> Result<Option<EquipmentModeHistoryRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentModeHistoryRow,
+            r#"SELECT h.history_id, h.equipment_id, h.mode_id, m.mode_description,
+                      m.mode_group_id, h.set_at, h.ended_at, h.set_by
+               FROM core.equipment_mode_history h
+               JOIN core.mode m ON m.mode_id = h.mode_id
+               WHERE h.history_id = $1"#,
+            history_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Check if the mode belongs to a mode group mapped to the equipment
+    pub async fn is_mode_assigned(
+        db: &PgPool,
+        equipment_id: Uuid,
+        mode_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM core.mode m
+                JOIN core.equipment_mode_group_mapping emgm
+                  ON emgm.mode_group_id = m.mode_group_id
+                WHERE emgm.equipment_id = $1 AND m.mode_id = $2 AND m.deleted_at IS NULL
+            )"#,
+            equipment_id,
+            mode_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Close the open interval and start a new one in a single transaction. Nothing is
+    /// written when the equipment is already in the mode.
+    pub async fn set_mode(
+        db: &PgPool,
+        equipment_id: Uuid,
+        mode_id: Uuid,
+        set_by: Option<&str>,
+    ) -> Result<ModeChange, sqlx::Error> {
+        let mut tx = db.begin().await?;
+
+        // serialize concurrent mode changes for the same equipment
+        let locked = sqlx::query!(
+            "SELECT equipment_id FROM core.equipment WHERE equipment_id = $1 AND deleted_at IS NULL FOR UPDATE",
+            equipment_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if locked.is_none() {
+            return Ok(ModeChange::EquipmentMissing);
+        }
+
+        // checked under the lock so identical concurrent requests record one interval
+        let current_mode_id = sqlx::query_scalar!(
+            r#"SELECT mode_id FROM core.equipment_mode_history
+               WHERE equipment_id = $1 AND ended_at IS NULL"#,
+            equipment_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if current_mode_id == Some(mode_id) {
+            return Ok(ModeChange::Unchanged);
+        }
+
+        sqlx::query!(
+            r#"UPDATE core.equipment_mode_history
+               SET ended_at = now()
+               WHERE equipment_id = $1 AND ended_at IS NULL"#,
+            equipment_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let history_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment_mode_history (equipment_id, mode_id, set_at, set_by)
+               VALUES ($1, $2, now(), $3)
+               RETURNING history_id"#,
+            equipment_id,
+            mode_id,
+            set_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        EventQueries::notify_runtime_change(
+            &mut tx,
+            EventType::EquipmentMode,
+            equipment_id,
+            history_id,
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(ModeChange::Set(history_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_equipment_with_modes(pool: &PgPool) -> sqlx::Result<(Uuid, Uuid, Uuid)> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+               SELECT $1, mode_group_id FROM core.mode_group
+               WHERE mode_group_name = 'Default MES Mode Group'"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        let production = sqlx::query_scalar!(
+            "SELECT mode_id FROM core.mode WHERE mode_description = 'production'"
+        )
+        .fetch_one(pool)
+        .await?;
+        let idle =
+            sqlx::query_scalar!("SELECT mode_id FROM core.mode WHERE mode_description = 'idle'")
+                .fetch_one(pool)
+                .await?;
+        Ok((equipment_id, production, idle))
+    }
+
+    #[sqlx::test]
+    async fn test_set_mode_and_history(pool: PgPool) -> sqlx::Result<()> {
+        let (equipment_id, production, idle) = seeded_equipment_with_modes(&pool).await?;
+
+        assert!(
+            EquipmentModeQueries::get_current(&pool, equipment_id)
+                .await?
+                .is_none()
+        );
+        assert!(EquipmentModeQueries::is_mode_assigned(&pool, equipment_id, production).await?);
+
+        assert!(matches!(
+            EquipmentModeQueries::set_mode(&pool, equipment_id, production, Some("operator"))
+                .await?,
+            ModeChange::Set(_)
+        ));
+        assert!(matches!(
+            EquipmentModeQueries::set_mode(&pool, equipment_id, idle, None).await?,
+            ModeChange::Set(_)
+        ));
+        assert_eq!(
+            EquipmentModeQueries::set_mode(&pool, equipment_id, idle, Some("operator")).await?,
+            ModeChange::Unchanged
+        );
+
+        let current = EquipmentModeQueries::get_current(&pool, equipment_id)
+            .await?
+            .expect("current mode should be set");
+        assert_eq!(current.mode_id, idle);
+        assert_eq!(current.mode_description, "idle");
+
+        let history = EquipmentModeQueries::get_history(&pool, equipment_id, None, None).await?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].mode_id, idle);
+        assert!(history[0].ended_at.is_none());
+        assert_eq!(history[1].ended_at, Some(history[0].set_at));
+        assert_eq!(history[1].set_by.as_deref(), Some("operator"));
+
+        // a window entirely before the first change is empty
+        let before = history[1].set_at - time::Duration::hours(1);
+        let history =
+            EquipmentModeQueries::get_history(&pool, equipment_id, None, Some(before)).await?;
+        assert!(history.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_set_mode_on_deleted_equipment(pool: PgPool) -> sqlx::Result<()> {
+        let (equipment_id, production, _) = seeded_equipment_with_modes(&pool).await?;
+        sqlx::query!(
+            "UPDATE core.equipment SET deleted_at = now() WHERE equipment_id = $1",
+            equipment_id
+        )
+        .execute(&pool)
+        .await?;
+
+        assert_eq!(
+            EquipmentModeQueries::set_mode(&pool, equipment_id, production, None).await?,
+            ModeChange::EquipmentMissing
+        );
+        assert!(
+            EquipmentModeQueries::get_current(&pool, equipment_id)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+use crate::database::events::{EventQueries, EventType};
+use serde_json::Value;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EquipmentStateHistoryRow {
+    pub history_id: Uuid,
+    pub equipment_id: Uuid,
+    pub state_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    pub state_group_id: Uuid,
+    pub state_value: Option<Value>,
+    pub started_at: OffsetDateTime,
+    pub ended_at: Option<OffsetDateTime>,
+    pub duration_seconds: f64,
+    pub updated_by: Option<String>,
+}
+
+/// What `record_state` did with a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordedState {
+    Recorded(Uuid),
+    /// the equipment is already in the state, nothing was written
+    Unchanged,
+    /// the open interval starts after the report
+    OutOfOrder,
+    /// the equipment was deleted before it could be locked
+    EquipmentMissing,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CurrentEquipmentStateRow {
+    pub equipment_id: Uuid,
+    pub state_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    pub state_group_id: Uuid,
+    pub state_value: Option<Value>,
+    pub started_at: OffsetDateTime,
+    pub duration_seconds: f64,
+    pub updated_by: Option<String>,
+}
+
+pub struct EquipmentStateQueries;
+
+impl EquipmentStateQueries {
+    pub async fn get_current(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Option<CurrentEquipmentStateRow>, sqlx::Error> {
+        sqlx::query_as!(
+            CurrentEquipmentStateRow,
+            r#"SELECT equipment_id as "equipment_id!", state_id as "state_id!",
+                      state_code as "state_code!", state_description as "state_description!",
+                      state_group_id as "state_group_id!", state_value,
+                      started_at as "started_at!",
+                      EXTRACT(EPOCH FROM (now() - started_at))::float8 as "duration_seconds!",
+                      updated_by
+               FROM core.current_equipment_state
+               WHERE equipment_id = $1"#,
+            equipment_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Intervals overlapping the optional [from, to) window, newest first.
+    /// Open intervals are measured up to now.
+    pub async fn get_history(
+        db: &PgPool,
+        equipment_id: Uuid,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> Result<Vec<EquipmentStateHistoryRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentStateHistoryRow,
+            r#"SELECT h.history_id, h.equipment_id, h.state_id, h.state_code,
+                      s.state_description, s.state_group_id, h.state_value,
+                      h.started_at, h.ended_at,
+                      EXTRACT(EPOCH FROM (COALESCE(h.ended_at, now()) - h.started_at))::float8
+                          as "duration_seconds!",
+                      h.updated_by
+               FROM core.equipment_state_history h
+               JOIN core.state s ON s.state_id = h.state_id
+               WHERE h.equipment_id = $1
+                 AND ($2::timestamptz IS NULL OR h.ended_at IS NULL OR h.ended_at > $2)
+                 AND ($3::timestamptz IS NULL OR h.started_at < $3)
+               ORDER BY h.started_at DESC"#,
+            equipment_id,
+            from,
+            to
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn get_history_entry(
+        db: &PgPool,
+        history_id: Uuid,
+    ) -> Result<Option<EquipmentStateHistoryRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentStateHistoryRow,
+            r#"SELECT h.history_id, h.equipment_id, h.state_id, h.state_code,
+                      s.state_description, s.state_group_id, h.state_value,
+                      h.started_at, h.ended_at,
+                      EXTRACT(EPOCH FROM (COALESCE(h.ended_at, now()) - h.started_at))::float8
+                          as "duration_seconds!",
+                      h.updated_by
+               FROM core.equipment_state_history h
+               JOIN core.state s ON s.state_id = h.state_id
+               WHERE h.history_id = $1"#,
+            history_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// State groups mapped to the equipment through core.equipment_state_group_mapping
+    pub async fn get_mapped_state_group_ids(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT state_group_id FROM core.equipment_state_group_mapping
+               WHERE equipment_id = $1
+               ORDER BY state_group_id"#,
+            equipment_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Close the open interval at `started_at` (now when None) and start a new one,
+    /// in a single transaction.
+    pub async fn record_state(
+        db: &PgPool,
+        equipment_id: Uuid,
+        state_id: Uuid,
+        state_code: i32,
+        state_value: Option<&Value>,
+        started_at: Option<OffsetDateTime>,
+        updated_by: Option<&str>,
+    ) -> Result<RecordedState, sqlx::Error> {
+        let mut tx = db.begin().await?;
+
+        // serialize concurrent state changes for the same equipment
+        let Some(started_at) = sqlx::query_scalar!(
+            r#"SELECT COALESCE($2::timestamptz, now()) as "started_at!"
+               FROM core.equipment WHERE equipment_id = $1 AND deleted_at IS NULL FOR UPDATE"#,
+            equipment_id,
+            started_at
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(RecordedState::EquipmentMissing);
+        };
+
+        // checked under the lock so identical concurrent reports record one interval
+        let current_state_id = sqlx::query_scalar!(
+            r#"SELECT state_id FROM core.equipment_state_history
+               WHERE equipment_id = $1 AND ended_at IS NULL"#,
+            equipment_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if current_state_id == Some(state_id) {
+            return Ok(RecordedState::Unchanged);
+        }
+
+        let closed = sqlx::query!(
+            r#"UPDATE core.equipment_state_history
+               SET ended_at = $2
+               WHERE equipment_id = $1 AND ended_at IS NULL AND started_at <= $2"#,
+            equipment_id,
+            started_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if closed.rows_affected() == 0 {
+            let still_open = sqlx::query_scalar!(
+                r#"SELECT EXISTS(
+                    SELECT 1 FROM core.equipment_state_history
+                    WHERE equipment_id = $1 AND ended_at IS NULL
+                )"#,
+                equipment_id
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if still_open.unwrap_or(false) {
+                return Ok(RecordedState::OutOfOrder);
+            }
+        }
+
+        let history_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment_state_history
+                   (equipment_id, state_id, state_code, state_value, started_at, updated_by)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING history_id"#,
+            equipment_id,
+            state_id,
+            state_code,
+            state_value,
+            started_at,
+            updated_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        EventQueries::notify_runtime_change(
+            &mut tx,
+            EventType::EquipmentState,
+            equipment_id,
+            history_id,
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(RecordedState::Recorded(history_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn mapped_equipment(pool: &PgPool) -> sqlx::Result<Uuid> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+               SELECT $1, state_group_id FROM core.state_group
+               WHERE state_group_name = 'Default MES State Group'"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(equipment_id)
+    }
+
+    async fn seeded_state_id(pool: &PgPool, state_code: i32) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT state_id FROM core.state WHERE state_code = $1",
+            state_code
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_record_state_and_history(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = mapped_equipment(&pool).await?;
+        let running = seeded_state_id(&pool, 1).await?;
+        let blocked = seeded_state_id(&pool, 5).await?;
+
+        let groups = EquipmentStateQueries::get_mapped_state_group_ids(&pool, equipment_id).await?;
+        assert_eq!(groups.len(), 1);
+
+        let t0 = OffsetDateTime::now_utc() - time::Duration::minutes(10);
+        let t1 = t0 + time::Duration::seconds(90);
+
+        let recorded = EquipmentStateQueries::record_state(
+            &pool,
+            equipment_id,
+            running,
+            1,
+            None,
+            Some(t0),
+            None,
+        )
+        .await?;
+        assert!(matches!(recorded, RecordedState::Recorded(_)));
+        let recorded = EquipmentStateQueries::record_state(
+            &pool,
+            equipment_id,
+            blocked,
+            5,
+            None,
+            Some(t1),
+            Some("plc"),
+        )
+        .await?;
+        assert!(matches!(recorded, RecordedState::Recorded(_)));
+
+        // a repeat of the current state is not a new interval
+        let recorded =
+            EquipmentStateQueries::record_state(&pool, equipment_id, blocked, 5, None, None, None)
+                .await?;
+        assert_eq!(recorded, RecordedState::Unchanged);
+
+        let history = EquipmentStateQueries::get_history(&pool, equipment_id, None, None).await?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].state_description, "running");
+        assert_eq!(history[1].duration_seconds, 90.0);
+        assert!(history[0].ended_at.is_none());
+
+        let current = EquipmentStateQueries::get_current(&pool, equipment_id)
+            .await?
+            .expect("current state should be set");
+        assert_eq!(current.state_code, 5);
+        assert!(current.duration_seconds >= 8.0 * 60.0);
+
+        // out of order reports do not rewrite history
+        let recorded = EquipmentStateQueries::record_state(
+            &pool,
+            equipment_id,
+            running,
+            1,
+            None,
+            Some(t0),
+            None,
+        )
+        .await?;
+        assert_eq!(recorded, RecordedState::OutOfOrder);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_record_state_on_deleted_equipment(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = mapped_equipment(&pool).await?;
+        let running = seeded_state_id(&pool, 1).await?;
+        sqlx::query!(
+            "UPDATE core.equipment SET deleted_at = now() WHERE equipment_id = $1",
+            equipment_id
+        )
+        .execute(&pool)
+        .await?;
+
+        let recorded =
+            EquipmentStateQueries::record_state(&pool, equipment_id, running, 1, None, None, None)
+                .await?;
+        assert_eq!(recorded, RecordedState::EquipmentMissing);
+        assert!(
+            EquipmentStateQueries::get_current(&pool, equipment_id)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+}
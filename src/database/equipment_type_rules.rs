@@ -0,0 +1,167 @@
+use sqlx::{PgConnection, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EquipmentTypeRuleRow {
+    pub rule_id: Uuid,
+    pub parent_type_id: Option<Uuid>,
+    pub parent_type_name: Option<String>,
+    pub child_type_id: Uuid,
+    pub child_type_name: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+pub struct EquipmentTypeRuleQueries;
+
+impl EquipmentTypeRuleQueries {
+    pub async fn get_all(db: &PgPool) -> Result<Vec<EquipmentTypeRuleRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentTypeRuleRow,
+            r#"SELECT r.rule_id, r.parent_type_id, p.type_name as "parent_type_name?",
+                      r.child_type_id, c.type_name as child_type_name,
+                      r.created_at, r.updated_at
+               FROM core.equipment_type_rule r
+               JOIN core.equipment_type c ON c.type_id = r.child_type_id
+               LEFT JOIN core.equipment_type p ON p.type_id = r.parent_type_id
+               WHERE c.deleted_at IS NULL AND p.deleted_at IS NULL
+               ORDER BY p.type_name NULLS FIRST, c.type_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn get_by_id(
+        db: &PgPool,
+        rule_id: Uuid,
+    ) -> Result<Option<EquipmentTypeRuleRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentTypeRuleRow,
+            r#"SELECT r.rule_id, r.parent_type_id, p.type_name as "parent_type_name?",
+                      r.child_type_id, c.type_name as child_type_name,
+                      r.created_at, r.updated_at
+               FROM core.equipment_type_rule r
+               JOIN core.equipment_type c ON c.type_id = r.child_type_id
+               LEFT JOIN core.equipment_type p ON p.type_id = r.parent_type_id
+               WHERE r.rule_id = $1 AND c.deleted_at IS NULL AND p.deleted_at IS NULL"#,
+            rule_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn create(
+        db: &mut PgConnection,
+        parent_type_id: Option<Uuid>,
+        child_type_id: Uuid,
+    ) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment_type_rule (parent_type_id, child_type_id)
+               VALUES ($1, $2)
+               RETURNING rule_id"#,
+            parent_type_id,
+            child_type_id
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn delete(db: &mut PgConnection, rule_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM core.equipment_type_rule WHERE rule_id = $1",
+            rule_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Check if a child type may be placed under a parent type (None = at the root)
+    pub async fn is_allowed(
+        db: &PgPool,
+        parent_type_id: Option<Uuid>,
+        child_type_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM core.equipment_type_rule
+                WHERE parent_type_id IS NOT DISTINCT FROM $1 AND child_type_id = $2
+            )"#,
+            parent_type_id,
+            child_type_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_type_id(pool: &PgPool, type_name: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = $1",
+            type_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_default_rules(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let site = seeded_type_id(&pool, "site").await?;
+        let cell = seeded_type_id(&pool, "cell").await?;
+
+        let rules = EquipmentTypeRuleQueries::get_all(&pool).await?;
+        assert_eq!(rules.len(), 5);
+        assert!(rules[0].parent_type_id.is_none());
+        assert_eq!(rules[0].child_type_name, "enterprise");
+
+        assert!(EquipmentTypeRuleQueries::is_allowed(&pool, None, enterprise).await?);
+        assert!(EquipmentTypeRuleQueries::is_allowed(&pool, Some(enterprise), site).await?);
+        assert!(!EquipmentTypeRuleQueries::is_allowed(&pool, None, site).await?);
+        assert!(!EquipmentTypeRuleQueries::is_allowed(&pool, Some(cell), site).await?);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_create_and_delete_rule(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+        let site = seeded_type_id(&pool, "site").await?;
+        let line = seeded_type_id(&pool, "line").await?;
+
+        let rule_id = EquipmentTypeRuleQueries::create(&mut conn, Some(site), line).await?;
+        assert!(EquipmentTypeRuleQueries::is_allowed(&pool, Some(site), line).await?);
+
+        let rule = EquipmentTypeRuleQueries::get_by_id(&pool, rule_id)
+            .await?
+            .expect("rule should exist");
+        assert_eq!(rule.parent_type_name.as_deref(), Some("site"));
+        assert_eq!(rule.child_type_name, "line");
+
+        // the same pair cannot be added twice, including root rules
+        assert!(
+            EquipmentTypeRuleQueries::create(&mut conn, Some(site), line)
+                .await
+                .is_err()
+        );
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        assert!(
+            EquipmentTypeRuleQueries::create(&mut conn, None, enterprise)
+                .await
+                .is_err()
+        );
+
+        assert!(EquipmentTypeRuleQueries::delete(&mut conn, rule_id).await?);
+        assert!(!EquipmentTypeRuleQueries::is_allowed(&pool, Some(site), line).await?);
+
+        Ok(())
+    }
+}
@@ -1,16 +1,26 @@
-use anyhow::{Context, anyhow};
-use sqlx::PgPool;
+#[cfg(feature = "stored-procedures")]
+use crate::database::procedures::{Procedure, ProcedureError};
+use crate::error::AppResult;
+#[cfg(not(feature = "stored-procedures"))]
+use crate::error::{AppError, DatabaseContext};
+use sqlx::{PgConnection, PgPool};
 use time::OffsetDateTime;
-use tracing::{debug, error, instrument};
+#[cfg(not(feature = "stored-procedures"))]
+use tracing::error;
+use tracing::{debug, instrument};
 use uuid::Uuid;
 
+#[cfg(not(feature = "stored-procedures"))]
 const MAX_TYPE_NAME_LEN: usize = 255;
 
-#[derive(Debug, Clone, sqlx::FromRow)]
+/// Also read from the "Data" of the equipment type procedures
+#[derive(Debug, Clone, sqlx::FromRow, serde::Deserialize)]
 pub struct EquipmentTypeRow {
     pub type_id: Uuid,
     pub type_name: String,
+    #[serde(default, with = "time::serde::rfc3339::option")]
     pub created_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
     pub updated_at: Option<OffsetDateTime>,
 }
 
@@ -18,18 +28,21 @@ pub struct EquipmentTypeQueries;
 
 impl EquipmentTypeQueries {
     /// Validates and sanitizes type name input
-    fn validate_type_name(type_name: &str) -> anyhow::Result<String> {
+    #[cfg(not(feature = "stored-procedures"))]
+    fn validate_type_name(type_name: &str) -> AppResult<String> {
         let trimmed = type_name.trim().to_string();
 
         if trimmed.is_empty() {
-            return Err(anyhow!("type_name cannot be empty"));
+            return Err(AppError::Validation(
+                "type_name cannot be empty".to_string(),
+            ));
         }
 
         if trimmed.len() > MAX_TYPE_NAME_LEN {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "type_name exceeds max length of {} characters",
                 MAX_TYPE_NAME_LEN
-            ));
+            )));
         }
 
         Ok(trimmed)
@@ -40,12 +53,14 @@ impl EquipmentTypeQueries {
             EquipmentTypeRow,
             r#"SELECT type_id, type_name, created_at, updated_at
                FROM core.equipment_type
+               WHERE deleted_at IS NULL
                ORDER BY type_name"#
         )
         .fetch_all(db)
         .await
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     pub async fn get_by_id(
         db: &PgPool,
         type_id: Uuid,
@@ -54,13 +69,25 @@ impl EquipmentTypeQueries {
             EquipmentTypeRow,
             r#"SELECT type_id, type_name, created_at, updated_at
                FROM core.equipment_type
-               WHERE type_id = $1"#,
+               WHERE type_id = $1 AND deleted_at IS NULL"#,
             type_id
         )
         .fetch_optional(db)
         .await
     }
 
+    #[cfg(feature = "stored-procedures")]
+    pub async fn get_by_id(
+        db: &PgPool,
+        type_id: Uuid,
+    ) -> Result<Option<EquipmentTypeRow>, sqlx::Error> {
+        Ok(Procedure::new("core.getEquipmentTypeById")
+            .bind(type_id)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     pub async fn get_by_name(
         db: &PgPool,
         type_name: &str,
@@ -69,28 +96,43 @@ impl EquipmentTypeQueries {
             EquipmentTypeRow,
             r#"SELECT type_id, type_name, created_at, updated_at
                FROM core.equipment_type
-               WHERE type_name = $1"#,
+               WHERE type_name = $1 AND deleted_at IS NULL"#,
             type_name
         )
         .fetch_optional(db)
         .await
     }
 
+    #[cfg(feature = "stored-procedures")]
+    pub async fn get_by_name(
+        db: &PgPool,
+        type_name: &str,
+    ) -> Result<Option<EquipmentTypeRow>, sqlx::Error> {
+        Ok(Procedure::new("core.getEquipmentTypeByName")
+            .bind(type_name)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(name = %type_name))]
-    pub async fn create(db: &PgPool, type_name: &str) -> anyhow::Result<EquipmentTypeRow> {
+    pub async fn create(db: &mut PgConnection, type_name: &str) -> AppResult<EquipmentTypeRow> {
         let validated_name = Self::validate_type_name(type_name)?;
 
         // Check for duplicate name
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.equipment_type WHERE type_name = $1",
+            "SELECT 1 FROM core.equipment_type WHERE type_name = $1 AND deleted_at IS NULL",
             validated_name
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate type_name")?
         {
             error!("Rejected: duplicate type_name '{}'", validated_name);
-            return Err(anyhow!("type_name '{}' already exists", validated_name));
+            return Err(AppError::Conflict(format!(
+                "type_name '{}' already exists",
+                validated_name
+            )));
         }
 
         debug!("Creating equipment type '{}'", validated_name);
@@ -101,7 +143,7 @@ impl EquipmentTypeQueries {
                RETURNING type_id, type_name, created_at, updated_at"#,
             validated_name
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .with_context(|| {
             format!(
@@ -114,25 +156,42 @@ impl EquipmentTypeQueries {
         Ok(result)
     }
 
+    /// core.insertEquipmentType (019) validates the name and checks for duplicates
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(name = %type_name))]
+    pub async fn create(db: &mut PgConnection, type_name: &str) -> AppResult<EquipmentTypeRow> {
+        let result: EquipmentTypeRow = Procedure::new("core.insertEquipmentType")
+            .bind(type_name)
+            .fetch(db)
+            .await?;
+
+        debug!(?result, "Successfully created equipment type");
+        Ok(result)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(id = %type_id, name = %type_name))]
     pub async fn update(
-        db: &PgPool,
+        db: &mut PgConnection,
         type_id: Uuid,
         type_name: &str,
-    ) -> anyhow::Result<Option<EquipmentTypeRow>> {
+    ) -> AppResult<Option<EquipmentTypeRow>> {
         let validated_name = Self::validate_type_name(type_name)?;
 
         // Check for duplicate name (excluding current record)
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.equipment_type WHERE type_name = $1 AND type_id != $2",
+            "SELECT 1 FROM core.equipment_type WHERE type_name = $1 AND type_id != $2 AND deleted_at IS NULL",
             validated_name,
             type_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate type_name")?
         {
-            return Err(anyhow!("type_name '{}' already exists", validated_name));
+            return Err(AppError::Conflict(format!(
+                "type_name '{}' already exists",
+                validated_name
+            )));
         }
 
         debug!(
@@ -143,12 +202,12 @@ impl EquipmentTypeQueries {
             EquipmentTypeRow,
             r#"UPDATE core.equipment_type 
                SET type_name = $2, updated_at = NOW()
-               WHERE type_id = $1
+               WHERE type_id = $1 AND deleted_at IS NULL
                RETURNING type_id, type_name, created_at, updated_at"#,
             type_id,
             validated_name
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update equipment type for id {}", type_id))?;
 
@@ -161,17 +220,30 @@ impl EquipmentTypeQueries {
         Ok(result)
     }
 
-    #[instrument(skip(db), fields(id = %type_id))]
-    pub async fn delete(db: &PgPool, type_id: Uuid) -> anyhow::Result<bool> {
-        // TODO: Add check to see if the equipment_type is being used anywhere
-        // This would prevent deletion of types that are in use
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(id = %type_id, name = %type_name))]
+    pub async fn update(
+        db: &mut PgConnection,
+        type_id: Uuid,
+        type_name: &str,
+    ) -> AppResult<Option<EquipmentTypeRow>> {
+        Ok(Procedure::new("core.updateEquipmentType")
+            .bind(type_id)
+            .bind(type_name)
+            .fetch_optional(db)
+            .await?)
+    }
 
+    /// Soft delete, the row is kept until it is purged and can be restored until then
+    #[cfg(not(feature = "stored-procedures"))]
+    #[instrument(skip(db), fields(id = %type_id))]
+    pub async fn delete(db: &mut PgConnection, type_id: Uuid) -> AppResult<bool> {
         debug!("Deleting equipment type {}", type_id);
         let result = sqlx::query!(
-            "DELETE FROM core.equipment_type WHERE type_id = $1",
+            "UPDATE core.equipment_type SET deleted_at = NOW() WHERE type_id = $1 AND deleted_at IS NULL",
             type_id
         )
-        .execute(db)
+        .execute(&mut *db)
         .await
         .with_context(|| format!("Failed to delete equipment type with id {}", type_id))?;
 
@@ -185,10 +257,57 @@ impl EquipmentTypeQueries {
         Ok(deleted)
     }
 
+    /// Soft delete with core.deleteEquipmentType (019), which refuses the default types and
+    /// types live equipment still uses
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(id = %type_id))]
+    pub async fn delete(db: &mut PgConnection, type_id: Uuid) -> AppResult<bool> {
+        match Procedure::new("core.deleteEquipmentType")
+            .bind(type_id)
+            .execute(db)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(ProcedureError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Undo a soft delete, None when there is no deleted equipment type with this id
+    pub async fn restore(
+        db: &mut PgConnection,
+        type_id: Uuid,
+    ) -> Result<Option<EquipmentTypeRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentTypeRow,
+            r#"UPDATE core.equipment_type
+               SET deleted_at = NULL
+               WHERE type_id = $1 AND deleted_at IS NOT NULL
+               RETURNING type_id, type_name, created_at, updated_at"#,
+            type_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Check if any live equipment is of this type
+    pub async fn is_in_use(db: &PgPool, type_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM core.equipment WHERE equipment_type_id = $1 AND deleted_at IS NULL
+            )"#,
+            type_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
     pub async fn exists(db: &PgPool, type_id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-                SELECT 1 FROM core.equipment_type WHERE type_id = $1
+                SELECT 1 FROM core.equipment_type WHERE type_id = $1 AND deleted_at IS NULL
             )"#,
             type_id
         )
@@ -202,7 +321,7 @@ impl EquipmentTypeQueries {
     pub async fn name_exists(db: &PgPool, type_name: &str) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-            SELECT 1 FROM core.equipment_type WHERE type_name = $1
+            SELECT 1 FROM core.equipment_type WHERE type_name = $1 AND deleted_at IS NULL
         )"#,
             type_name.trim()
         )
@@ -223,7 +342,7 @@ impl EquipmentTypeQueries {
             EquipmentTypeRow,
             r#"SELECT type_id, type_name, created_at, updated_at
            FROM core.equipment_type
-           WHERE lower(type_name::text COLLATE "C") LIKE $1
+           WHERE lower(type_name::text COLLATE "C") LIKE $1 AND deleted_at IS NULL
            ORDER BY type_name"#,
             pattern
         )
@@ -235,6 +354,8 @@ impl EquipmentTypeQueries {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "stored-procedures")]
+    use crate::error::AppError;
     use uuid::Uuid;
 
     // helper to create equipment type bypassing validation for test setup
@@ -255,9 +376,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_equipment_type(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_name = "Test Equipment Type";
 
-        let equipment_type = EquipmentTypeQueries::create(&pool, type_name)
+        let equipment_type = EquipmentTypeQueries::create(&mut conn, type_name)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -268,21 +390,23 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     #[sqlx::test]
     async fn test_create_validation_errors(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // test empty name
-        let result = EquipmentTypeQueries::create(&pool, "").await;
+        let result = EquipmentTypeQueries::create(&mut conn, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // test whitespace-only name
-        let result = EquipmentTypeQueries::create(&pool, "   ").await;
+        let result = EquipmentTypeQueries::create(&mut conn, "   ").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // test too long name
         let long_name = "a".repeat(300);
-        let result = EquipmentTypeQueries::create(&pool, &long_name).await;
+        let result = EquipmentTypeQueries::create(&mut conn, &long_name).await;
         assert!(result.is_err());
         assert!(
             result
@@ -296,15 +420,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_duplicate_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_name = "Duplicate Test Type";
 
         // first creation should succeed
-        let _first = EquipmentTypeQueries::create(&pool, type_name)
+        let _first = EquipmentTypeQueries::create(&mut conn, type_name)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // second creation with same name should fail
-        let result = EquipmentTypeQueries::create(&pool, type_name).await;
+        let result = EquipmentTypeQueries::create(&mut conn, type_name).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -313,8 +438,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_input_trimming(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test that inputs are properly trimmed
-        let created = EquipmentTypeQueries::create(&pool, "  Trimmed Type  ")
+        let created = EquipmentTypeQueries::create(&mut conn, "  Trimmed Type  ")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -325,11 +451,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_all_equipment_types(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Creating some test data
-        let type1 = EquipmentTypeQueries::create(&pool, "Type 1")
+        let type1 = EquipmentTypeQueries::create(&mut conn, "Type 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let type2 = EquipmentTypeQueries::create(&pool, "Type 2")
+        let type2 = EquipmentTypeQueries::create(&mut conn, "Type 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -344,7 +471,8 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_id(pool: PgPool) -> sqlx::Result<()> {
-        let created = EquipmentTypeQueries::create(&pool, "Test Type")
+        let mut conn = pool.acquire().await?;
+        let created = EquipmentTypeQueries::create(&mut conn, "Test Type")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -370,8 +498,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_name = "Unique Type Name";
-        let created = EquipmentTypeQueries::create(&pool, type_name)
+        let created = EquipmentTypeQueries::create(&mut conn, type_name)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -396,12 +525,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_equipment_type(pool: PgPool) -> sqlx::Result<()> {
-        let created = EquipmentTypeQueries::create(&pool, "Original Name")
+        let mut conn = pool.acquire().await?;
+        let created = EquipmentTypeQueries::create(&mut conn, "Original Name")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let new_name = "Updated Name";
 
-        let updated = EquipmentTypeQueries::update(&pool, created.type_id, new_name)
+        let updated = EquipmentTypeQueries::update(&mut conn, created.type_id, new_name)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -414,25 +544,27 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     #[sqlx::test]
     async fn test_update_validation_errors(pool: PgPool) -> sqlx::Result<()> {
-        let created = EquipmentTypeQueries::create(&pool, "Original Name")
+        let mut conn = pool.acquire().await?;
+        let created = EquipmentTypeQueries::create(&mut conn, "Original Name")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test empty name
-        let result = EquipmentTypeQueries::update(&pool, created.type_id, "").await;
+        let result = EquipmentTypeQueries::update(&mut conn, created.type_id, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test whitespace-only name
-        let result = EquipmentTypeQueries::update(&pool, created.type_id, "   ").await;
+        let result = EquipmentTypeQueries::update(&mut conn, created.type_id, "   ").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test too long name
         let long_name = "a".repeat(300);
-        let result = EquipmentTypeQueries::update(&pool, created.type_id, &long_name).await;
+        let result = EquipmentTypeQueries::update(&mut conn, created.type_id, &long_name).await;
         assert!(result.is_err());
         assert!(
             result
@@ -446,15 +578,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_duplicate_name(pool: PgPool) -> sqlx::Result<()> {
-        let _first = EquipmentTypeQueries::create(&pool, "First Type")
+        let mut conn = pool.acquire().await?;
+        let _first = EquipmentTypeQueries::create(&mut conn, "First Type")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let second = EquipmentTypeQueries::create(&pool, "Second Type")
+        let second = EquipmentTypeQueries::create(&mut conn, "Second Type")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to update second type's name to match first type's name
-        let result = EquipmentTypeQueries::update(&pool, second.type_id, "First Type").await;
+        let result = EquipmentTypeQueries::update(&mut conn, second.type_id, "First Type").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -463,8 +596,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_nonexistent(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let result = EquipmentTypeQueries::update(&pool, random_id, "New Name")
+        let result = EquipmentTypeQueries::update(&mut conn, random_id, "New Name")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -475,7 +609,8 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_preserves_timestamps(pool: PgPool) -> sqlx::Result<()> {
-        let created = EquipmentTypeQueries::create(&pool, "Test Type")
+        let mut conn = pool.acquire().await?;
+        let created = EquipmentTypeQueries::create(&mut conn, "Test Type")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let original_created_at = created.created_at;
@@ -483,7 +618,7 @@ mod tests {
         // Short delay to ensure timestamp difference
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
 
-        let updated = EquipmentTypeQueries::update(&pool, created.type_id, "Updated Type")
+        let updated = EquipmentTypeQueries::update(&mut conn, created.type_id, "Updated Type")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -505,11 +640,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_equipment_type(pool: PgPool) -> sqlx::Result<()> {
-        let created = EquipmentTypeQueries::create(&pool, "To Delete")
+        let mut conn = pool.acquire().await?;
+        let created = EquipmentTypeQueries::create(&mut conn, "To Delete")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
-        let deleted = EquipmentTypeQueries::delete(&pool, created.type_id)
+        let deleted = EquipmentTypeQueries::delete(&mut conn, created.type_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
@@ -523,8 +659,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_nonexistent(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let deleted = EquipmentTypeQueries::delete(&pool, random_id)
+        let deleted = EquipmentTypeQueries::delete(&mut conn, random_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -535,7 +672,8 @@ mod tests {
 
     #[sqlx::test]
     async fn test_exists(pool: PgPool) -> sqlx::Result<()> {
-        let created = EquipmentTypeQueries::create(&pool, "Exists Test")
+        let mut conn = pool.acquire().await?;
+        let created = EquipmentTypeQueries::create(&mut conn, "Exists Test")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -551,8 +689,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_name_exists(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let type_name = "Exists Test Type";
-        let _created = EquipmentTypeQueries::create(&pool, type_name)
+        let _created = EquipmentTypeQueries::create(&mut conn, type_name)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -573,14 +712,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_search_by_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Create test data
-        let _pump = EquipmentTypeQueries::create(&pool, "Water Pump")
+        let _pump = EquipmentTypeQueries::create(&mut conn, "Water Pump")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let _motor = EquipmentTypeQueries::create(&pool, "Electric Motor")
+        let _motor = EquipmentTypeQueries::create(&mut conn, "Electric Motor")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let _valve = EquipmentTypeQueries::create(&pool, "Control Valve")
+        let _valve = EquipmentTypeQueries::create(&mut conn, "Control Valve")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -603,10 +743,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_multiple_operations_sequence(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test a sequence of operations to ensure they work together
 
         // Create
-        let created = EquipmentTypeQueries::create(&pool, "Sequence Test Type")
+        let created = EquipmentTypeQueries::create(&mut conn, "Sequence Test Type")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -619,9 +760,10 @@ mod tests {
         assert!(name_exists);
 
         // Update
-        let updated = EquipmentTypeQueries::update(&pool, created.type_id, "Updated Sequence Type")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let updated =
+            EquipmentTypeQueries::update(&mut conn, created.type_id, "Updated Sequence Type")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(updated.is_some());
 
         // Verify updated name
@@ -634,7 +776,7 @@ mod tests {
         assert!(search_results.iter().any(|t| t.type_id == created.type_id));
 
         // Delete
-        let deleted = EquipmentTypeQueries::delete(&pool, created.type_id)
+        let deleted = EquipmentTypeQueries::delete(&mut conn, created.type_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
@@ -645,4 +787,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "stored-procedures")]
+    #[sqlx::test]
+    async fn test_procedure_rules(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+
+        let result = EquipmentTypeQueries::create(&mut conn, "x").await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let enterprise = EquipmentTypeQueries::get_by_name(&pool, "enterprise")
+            .await?
+            .expect("enterprise is seeded");
+        let result = EquipmentTypeQueries::delete(&mut conn, enterprise.type_id).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        // the duplicate check only looks at live types
+        let created = EquipmentTypeQueries::create(&mut conn, "  Conveyor  ")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(created.type_name, "Conveyor");
+        assert!(created.created_at.is_some());
+
+        let deleted = EquipmentTypeQueries::delete(&mut conn, created.type_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(deleted);
+        let deleted_at = sqlx::query_scalar!(
+            "SELECT deleted_at FROM core.equipment_type WHERE type_id = $1",
+            created.type_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert!(deleted_at.is_some());
+
+        let again = EquipmentTypeQueries::create(&mut conn, "Conveyor")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_ne!(again.type_id, created.type_id);
+
+        Ok(())
+    }
 }
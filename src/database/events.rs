@@ -0,0 +1,202 @@
+use serde::Deserialize;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// NOTIFY channel for the live feed, see migration 014 for the entity triggers
+pub const EVENTS_CHANNEL: &str = "mes_events";
+
+/// What changed. Entity types match the tables, equipment_mode and equipment_state are the
+/// runtime mode and state changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    Equipment,
+    EquipmentType,
+    ModeGroup,
+    Mode,
+    StateGroup,
+    State,
+    EquipmentMode,
+    EquipmentState,
+}
+
+impl EventType {
+    pub const ALL: [EventType; 8] = [
+        EventType::Equipment,
+        EventType::EquipmentType,
+        EventType::ModeGroup,
+        EventType::Mode,
+        EventType::StateGroup,
+        EventType::State,
+        EventType::EquipmentMode,
+        EventType::EquipmentState,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::Equipment => "equipment",
+            EventType::EquipmentType => "equipment_type",
+            EventType::ModeGroup => "mode_group",
+            EventType::Mode => "mode",
+            EventType::StateGroup => "state_group",
+            EventType::State => "state",
+            EventType::EquipmentMode => "equipment_mode",
+            EventType::EquipmentState => "equipment_state",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl EventAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventAction::Created => "created",
+            EventAction::Updated => "updated",
+            EventAction::Deleted => "deleted",
+        }
+    }
+}
+
+/// Notification payload. Only ids are sent, listeners load the rest themselves so large
+/// state values never hit the 8000 byte NOTIFY limit.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventPayload {
+    pub event_type: EventType,
+    pub action: EventAction,
+    /// Id of the entity, or of the history entry for runtime changes
+    pub id: Uuid,
+    pub equipment_id: Option<Uuid>,
+    /// Parent of changed equipment, lets deleted equipment still be placed in the tree
+    pub parent_id: Option<Uuid>,
+}
+
+pub struct EventQueries;
+
+impl EventQueries {
+    /// Queue a runtime change notification on the caller's transaction,
+    /// postgres delivers it on commit
+    pub async fn notify_runtime_change(
+        conn: &mut PgConnection,
+        event_type: EventType,
+        equipment_id: Uuid,
+        history_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT pg_notify(
+                   $1,
+                   json_build_object(
+                       'event_type', $2::text, 'action', 'created',
+                       'id', $4::uuid, 'equipment_id', $3::uuid
+                   )::text
+               )::text"#,
+            EVENTS_CHANNEL,
+            event_type.as_str(),
+            equipment_id,
+            history_id
+        )
+        .fetch_one(conn)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+    use sqlx::postgres::PgListener;
+
+    async fn next_payload(listener: &mut PgListener) -> sqlx::Result<EventPayload> {
+        let notification = listener.recv().await?;
+        serde_json::from_str(notification.payload())
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))
+    }
+
+    #[sqlx::test]
+    async fn test_notify_is_delivered_on_commit(pool: PgPool) -> sqlx::Result<()> {
+        let mut listener = PgListener::connect_with(&pool).await?;
+        listener.listen(EVENTS_CHANNEL).await?;
+
+        let equipment_id = Uuid::new_v4();
+        let history_id = Uuid::new_v4();
+        let mut tx = pool.begin().await?;
+        EventQueries::notify_runtime_change(
+            &mut tx,
+            EventType::EquipmentState,
+            equipment_id,
+            history_id,
+        )
+        .await?;
+        tx.commit().await?;
+
+        let payload = next_payload(&mut listener).await?;
+        assert_eq!(payload.event_type, EventType::EquipmentState);
+        assert_eq!(payload.action, EventAction::Created);
+        assert_eq!(payload.equipment_id, Some(equipment_id));
+        assert_eq!(payload.id, history_id);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_entity_triggers_notify(pool: PgPool) -> sqlx::Result<()> {
+        let mut listener = PgListener::connect_with(&pool).await?;
+        listener.listen(EVENTS_CHANNEL).await?;
+
+        let mode_group_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.mode_group (mode_group_name, mode_group_description)
+               VALUES ('Packaging', 'Packaging line modes')
+               RETURNING mode_group_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        // no-op updates are not sent
+        sqlx::query!(
+            "UPDATE core.mode_group SET mode_group_name = mode_group_name WHERE mode_group_id = $1",
+            mode_group_id
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM core.mode_group WHERE mode_group_id = $1",
+            mode_group_id
+        )
+        .execute(&pool)
+        .await?;
+
+        let created = next_payload(&mut listener).await?;
+        assert_eq!(created.event_type, EventType::ModeGroup);
+        assert_eq!(created.action, EventAction::Created);
+        assert_eq!(created.id, mode_group_id);
+        assert_eq!(created.equipment_id, None);
+
+        let deleted = next_payload(&mut listener).await?;
+        assert_eq!(deleted.action, EventAction::Deleted);
+
+        let parent_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let site = next_payload(&mut listener).await?;
+        assert_eq!(site.event_type, EventType::Equipment);
+        assert_eq!(site.equipment_id, Some(parent_id));
+        assert_eq!(site.parent_id, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct JobStatusHistoryRow {
+    pub history_id: Uuid,
+    pub job_id: Uuid,
+    pub from_status: String,
+    pub to_status: String,
+    pub changed_at: OffsetDateTime,
+    pub changed_by: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct JobRow {
+    pub job_id: Uuid,
+    pub work_order_id: Uuid,
+    pub equipment_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub job_metadata: Value,
+    pub job_status: String,
+    pub released_at: Option<OffsetDateTime>,
+    pub started_at: Option<OffsetDateTime>,
+    pub completed_at: Option<OffsetDateTime>,
+    pub cancelled_at: Option<OffsetDateTime>,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+pub struct JobQueries;
+
+impl JobQueries {
+    pub async fn get_by_id(db: &PgPool, job_id: Uuid) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"SELECT job_id, work_order_id, equipment_id, planned_quantity, job_metadata,
+                      job_status, released_at, started_at, completed_at, cancelled_at,
+                      created_at, updated_at
+               FROM operations.job
+               WHERE job_id = $1"#,
+            job_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Jobs filtered by work order and/or equipment, newest first
+    pub async fn get_filtered(
+        db: &PgPool,
+        work_order_id: Option<Uuid>,
+        equipment_id: Option<Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"SELECT job_id, work_order_id, equipment_id, planned_quantity, job_metadata,
+                      job_status, released_at, started_at, completed_at, cancelled_at,
+                      created_at, updated_at
+               FROM operations.job
+               WHERE ($1::uuid IS NULL OR work_order_id = $1)
+                 AND ($2::uuid IS NULL OR equipment_id = $2)
+               ORDER BY created_at DESC, job_id
+               LIMIT $3 OFFSET $4"#,
+            work_order_id,
+            equipment_id,
+            limit,
+            offset
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// The running or paused job on the equipment, the most recently started one if there
+    /// are several
+    pub async fn get_active_for_equipment(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"SELECT job_id, work_order_id, equipment_id, planned_quantity, job_metadata,
+                      job_status, released_at, started_at, completed_at, cancelled_at,
+                      created_at, updated_at
+               FROM operations.job
+               WHERE equipment_id = $1 AND job_status IN ('running', 'paused')
+               ORDER BY started_at DESC NULLS LAST
+               LIMIT 1"#,
+            equipment_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn count_filtered(
+        db: &PgPool,
+        work_order_id: Option<Uuid>,
+        equipment_id: Option<Uuid>,
+    ) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) FROM operations.job
+               WHERE ($1::uuid IS NULL OR work_order_id = $1)
+                 AND ($2::uuid IS NULL OR equipment_id = $2)"#,
+            work_order_id,
+            equipment_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    pub async fn create(
+        db: &PgPool,
+        work_order_id: Uuid,
+        equipment_id: Uuid,
+        planned_quantity: Option<i32>,
+        job_metadata: Option<&Value>,
+    ) -> Result<JobRow, sqlx::Error> {
+        let default_metadata = serde_json::json!({});
+        let metadata = job_metadata.unwrap_or(&default_metadata);
+
+        sqlx::query_as!(
+            JobRow,
+            r#"INSERT INTO operations.job (work_order_id, equipment_id, planned_quantity, job_metadata)
+               VALUES ($1, $2, $3, $4)
+               RETURNING job_id, work_order_id, equipment_id, planned_quantity, job_metadata,
+                         job_status, released_at, started_at, completed_at, cancelled_at,
+                         created_at, updated_at"#,
+            work_order_id,
+            equipment_id,
+            planned_quantity,
+            metadata
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn update(
+        db: &PgPool,
+        job_id: Uuid,
+        equipment_id: Uuid,
+        planned_quantity: Option<i32>,
+    ) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"UPDATE operations.job
+               SET equipment_id = $2, planned_quantity = $3
+               WHERE job_id = $1
+               RETURNING job_id, work_order_id, equipment_id, planned_quantity, job_metadata,
+                         job_status, released_at, started_at, completed_at, cancelled_at,
+                         created_at, updated_at"#,
+            job_id,
+            equipment_id,
+            planned_quantity
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn update_metadata(
+        db: &PgPool,
+        job_id: Uuid,
+        metadata: &Value,
+    ) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"UPDATE operations.job
+               SET job_metadata = $2
+               WHERE job_id = $1
+               RETURNING job_id, work_order_id, equipment_id, planned_quantity, job_metadata,
+                         job_status, released_at, started_at, completed_at, cancelled_at,
+                         created_at, updated_at"#,
+            job_id,
+            metadata
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Moves the job from `from_status` to `to_status`, stamps the matching timestamp and
+    /// records the change. Returns None if the job is no longer in `from_status`.
+    pub async fn set_status(
+        db: &PgPool,
+        job_id: Uuid,
+        from_status: &str,
+        to_status: &str,
+        changed_by: Option<&str>,
+    ) -> Result<Option<JobRow>, sqlx::Error> {
+        let mut tx = db.begin().await?;
+
+        let row = sqlx::query_as!(
+            JobRow,
+            r#"UPDATE operations.job
+               SET job_status = $3,
+                   released_at = CASE WHEN $3 = 'released' THEN now() ELSE released_at END,
+                   started_at = CASE WHEN $3 = 'running' THEN coalesce(started_at, now())
+                                     ELSE started_at END,
+                   completed_at = CASE WHEN $3 = 'completed' THEN now() ELSE completed_at END,
+                   cancelled_at = CASE WHEN $3 = 'cancelled' THEN now() ELSE cancelled_at END
+               WHERE job_id = $1 AND job_status = $2
+               RETURNING job_id, work_order_id, equipment_id, planned_quantity, job_metadata,
+                         job_status, released_at, started_at, completed_at, cancelled_at,
+                         created_at, updated_at"#,
+            job_id,
+            from_status,
+            to_status
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if row.is_some() {
+            sqlx::query!(
+                r#"INSERT INTO operations.job_status_history (job_id, from_status, to_status, changed_by)
+                   VALUES ($1, $2, $3, $4)"#,
+                job_id,
+                from_status,
+                to_status,
+                changed_by
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(row)
+    }
+
+    /// Status changes of a job, oldest first
+    pub async fn get_status_history(
+        db: &PgPool,
+        job_id: Uuid,
+    ) -> Result<Vec<JobStatusHistoryRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobStatusHistoryRow,
+            r#"SELECT history_id, job_id, from_status, to_status, changed_at, changed_by
+               FROM operations.job_status_history
+               WHERE job_id = $1
+               ORDER BY changed_at, history_id"#,
+            job_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn delete(db: &PgPool, job_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM operations.job WHERE job_id = $1", job_id)
+            .execute(db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn exists(db: &PgPool, job_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.job WHERE job_id = $1
+            )"#,
+            job_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::products::ProductQueries;
+    use crate::database::work_orders::WorkOrderQueries;
+
+    #[sqlx::test]
+    async fn test_job_crud_and_filters(pool: PgPool) -> sqlx::Result<()> {
+        let product = ProductQueries::create(&pool, "Widget", None, None).await?;
+        let work_order =
+            WorkOrderQueries::create(&pool, "WO-1", product.product_id, None, None).await?;
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        let job = JobQueries::create(
+            &pool,
+            work_order.work_order_id,
+            equipment_id,
+            Some(10),
+            None,
+        )
+        .await?;
+        JobQueries::create(&pool, work_order.work_order_id, equipment_id, None, None).await?;
+        assert!(WorkOrderQueries::has_jobs(&pool, work_order.work_order_id).await?);
+
+        let jobs =
+            JobQueries::get_filtered(&pool, Some(work_order.work_order_id), None, 50, 0).await?;
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(
+            JobQueries::count_filtered(&pool, None, Some(equipment_id)).await?,
+            2
+        );
+        assert_eq!(
+            JobQueries::count_filtered(&pool, None, Some(Uuid::new_v4())).await?,
+            0
+        );
+
+        let updated = JobQueries::update(&pool, job.job_id, equipment_id, Some(20))
+            .await?
+            .expect("job should exist");
+        assert_eq!(updated.planned_quantity, Some(20));
+
+        assert_eq!(updated.job_status, "planned");
+
+        let released = JobQueries::set_status(&pool, job.job_id, "planned", "released", None)
+            .await?
+            .expect("job should be planned");
+        assert_eq!(released.job_status, "released");
+        assert!(released.released_at.is_some());
+
+        // a stale from_status does not change anything
+        assert!(
+            JobQueries::set_status(&pool, job.job_id, "planned", "released", None)
+                .await?
+                .is_none()
+        );
+
+        let running = JobQueries::set_status(&pool, job.job_id, "released", "running", Some("op"))
+            .await?
+            .expect("job should be released");
+        assert!(running.started_at.is_some());
+
+        let history = JobQueries::get_status_history(&pool, job.job_id).await?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].from_status, "released");
+        assert_eq!(history[1].changed_by.as_deref(), Some("op"));
+
+        assert!(JobQueries::delete(&pool, job.job_id).await?);
+        assert!(!JobQueries::exists(&pool, job.job_id).await?);
+
+        Ok(())
+    }
+}
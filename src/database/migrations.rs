@@ -0,0 +1,155 @@
+use sqlx::PgPool;
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+
+/// Every migration in /migrations, embedded in the binary at build time
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// applied, but the file has changed since
+    Modified,
+    /// applied, but the file is no longer in this build
+    Missing,
+}
+
+impl MigrationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "modified",
+            MigrationState::Missing => "missing",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+pub struct MigrationQueries;
+
+impl MigrationQueries {
+    pub async fn run(db: &PgPool) -> Result<(), MigrateError> {
+        MIGRATOR.run(db).await
+    }
+
+    /// Every known migration in version order, with the applied ones this build has no
+    /// file for at the end
+    pub async fn status(db: &PgPool) -> Result<Vec<MigrationStatus>, MigrateError> {
+        let mut conn = db.acquire().await?;
+        conn.ensure_migrations_table().await?;
+        let mut applied = conn.list_applied_migrations().await?;
+
+        let mut statuses = Vec::new();
+        for migration in MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+        {
+            let state = match applied.iter().position(|a| a.version == migration.version) {
+                Some(index) => {
+                    let checksum = applied.swap_remove(index).checksum;
+                    if checksum == migration.checksum {
+                        MigrationState::Applied
+                    } else {
+                        MigrationState::Modified
+                    }
+                }
+                None => MigrationState::Pending,
+            };
+            statuses.push(MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                state,
+            });
+        }
+
+        applied.sort_by_key(|a| a.version);
+        statuses.extend(applied.into_iter().map(|a| MigrationStatus {
+            version: a.version,
+            description: String::new(),
+            state: MigrationState::Missing,
+        }));
+
+        Ok(statuses)
+    }
+
+    /// Revert the applied migrations newer than `target`, only the latest one when None.
+    /// Gives back the reverted versions, newest first.
+    pub async fn undo(db: &PgPool, target: Option<i64>) -> Result<Vec<i64>, MigrateError> {
+        let mut applied: Vec<i64> = {
+            let mut conn = db.acquire().await?;
+            conn.ensure_migrations_table().await?;
+            conn.list_applied_migrations()
+                .await?
+                .into_iter()
+                .map(|a| a.version)
+                .collect()
+        };
+        applied.sort_unstable_by(|a, b| b.cmp(a));
+
+        let target = match target {
+            Some(target) => target,
+            None => applied.get(1).copied().unwrap_or(0),
+        };
+
+        MIGRATOR.undo(db, target).await?;
+
+        Ok(applied.into_iter().filter(|v| *v > target).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states(statuses: &[MigrationStatus]) -> Vec<MigrationState> {
+        statuses.iter().map(|s| s.state).collect()
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_migrations_apply_and_revert(pool: PgPool) -> sqlx::Result<()> {
+        let to_sqlx = |e: MigrateError| sqlx::Error::Protocol(e.to_string());
+
+        let pending = MigrationQueries::status(&pool).await.map_err(to_sqlx)?;
+        assert!(!pending.is_empty());
+        assert!(pending.iter().all(|s| s.state == MigrationState::Pending));
+        let latest = pending.last().unwrap().version;
+
+        MigrationQueries::run(&pool).await.map_err(to_sqlx)?;
+        let applied = MigrationQueries::status(&pool).await.map_err(to_sqlx)?;
+        assert!(applied.iter().all(|s| s.state == MigrationState::Applied));
+
+        let reverted = MigrationQueries::undo(&pool, None).await.map_err(to_sqlx)?;
+        assert_eq!(reverted, vec![latest]);
+        let after_undo = MigrationQueries::status(&pool).await.map_err(to_sqlx)?;
+        assert_eq!(
+            after_undo.last().map(|s| s.state),
+            Some(MigrationState::Pending)
+        );
+
+        // every down migration has to run cleanly for this to leave an empty database
+        let reverted = MigrationQueries::undo(&pool, Some(0))
+            .await
+            .map_err(to_sqlx)?;
+        assert_eq!(reverted.len(), pending.len() - 1);
+        let schemas: i64 = sqlx::query_scalar!(
+            r#"SELECT count(*) AS "count!" FROM information_schema.schemata
+               WHERE schema_name IN ('core', 'app', 'operations')"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(schemas, 0);
+
+        MigrationQueries::run(&pool).await.map_err(to_sqlx)?;
+        let reapplied = MigrationQueries::status(&pool).await.map_err(to_sqlx)?;
+        assert_eq!(states(&reapplied), states(&applied));
+
+        Ok(())
+    }
+}
@@ -1,6 +1,23 @@
+pub mod api_keys;
+pub mod audit;
 pub mod equipment;
+pub mod equipment_modes;
+pub mod equipment_states;
+pub mod equipment_type_rules;
 pub mod equipment_types;
+pub mod events;
+pub mod jobs;
+pub mod migrations;
 pub mod mode_groups;
 pub mod modes;
+pub mod oee;
+pub mod plant_model;
+pub mod procedures;
+pub mod production_counts;
+pub mod purge;
+pub mod products;
+pub mod seed;
+pub mod state_code_reservations;
 pub mod state_groups;
 pub mod states;
+pub mod work_orders;
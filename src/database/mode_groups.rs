@@ -1,21 +1,38 @@
-use anyhow::{Context, anyhow};
-use sqlx::PgPool;
+use crate::database::procedures::{Procedure, ProcedureError, ProcedureRow};
+use crate::error::AppResult;
+#[cfg(not(feature = "stored-procedures"))]
+use crate::error::{AppError, DatabaseContext};
+use crate::models::core::{BulkGroupAssignment, GroupEquipment, ModeGroupUsageStats};
+use sqlx::{PgConnection, PgPool};
 use time::OffsetDateTime;
-use tracing::{debug, error, instrument};
+#[cfg(not(feature = "stored-procedures"))]
+use tracing::error;
+use tracing::{debug, instrument};
 use uuid::Uuid;
 
+#[cfg(not(feature = "stored-procedures"))]
 const MAX_NAME_LEN: usize = 255;
+#[cfg(not(feature = "stored-procedures"))]
 const MAX_DESC_LEN: usize = 2048;
 
-#[derive(Debug, Clone, sqlx::FromRow)]
+/// Also read from the "Data" of the mode group procedures
+#[derive(Debug, Clone, sqlx::FromRow, serde::Deserialize)]
 pub struct ModeGroupRow {
     pub mode_group_id: Uuid,
     pub mode_group_name: String,
     pub mode_group_description: String,
+    #[serde(default, with = "time::serde::rfc3339::option")]
     pub created_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
     pub updated_at: Option<OffsetDateTime>,
 }
 
+/// "Data" of core.getEquipmentForModeGroup
+#[derive(serde::Deserialize)]
+struct GroupEquipmentData {
+    equipment: Vec<GroupEquipment>,
+}
+
 pub struct ModeGroupQueries;
 
 impl ModeGroupQueries {
@@ -24,12 +41,13 @@ impl ModeGroupQueries {
             ModeGroupRow,
             r#"SELECT mode_group_id, mode_group_name, 
                 mode_group_description, created_at, updated_at
-	            FROM core.mode_group;"#
+	            FROM core.mode_group WHERE deleted_at IS NULL;"#
         )
         .fetch_all(db)
         .await
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     pub async fn get_by_mode_group_id(
         db: &PgPool,
         mode_group_id: Uuid,
@@ -38,13 +56,25 @@ impl ModeGroupQueries {
             ModeGroupRow,
             r#"SELECT mode_group_id, mode_group_name, mode_group_description, created_at, updated_at
 	            FROM core.mode_group
-                WHERE mode_group_id = $1"#,
+                WHERE mode_group_id = $1 AND deleted_at IS NULL"#,
             mode_group_id
         )
         .fetch_optional(db)
         .await
     }
 
+    #[cfg(feature = "stored-procedures")]
+    pub async fn get_by_mode_group_id(
+        db: &PgPool,
+        mode_group_id: Uuid,
+    ) -> Result<Option<ModeGroupRow>, sqlx::Error> {
+        Ok(Procedure::new("core.getModeGroupById")
+            .bind(mode_group_id)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     pub async fn get_by_mode_group_name(
         db: &PgPool,
         mode_group_name: &str,
@@ -53,13 +83,24 @@ impl ModeGroupQueries {
             ModeGroupRow,
             r#"SELECT mode_group_id, mode_group_name, mode_group_description, created_at, updated_at
 	            FROM core.mode_group
-                WHERE mode_group_name = $1"#,
+                WHERE mode_group_name = $1 AND deleted_at IS NULL"#,
             mode_group_name
         )
         .fetch_optional(db)
         .await
     }
 
+    #[cfg(feature = "stored-procedures")]
+    pub async fn get_by_mode_group_name(
+        db: &PgPool,
+        mode_group_name: &str,
+    ) -> Result<Option<ModeGroupRow>, sqlx::Error> {
+        Ok(Procedure::new("core.getModeGroupByName")
+            .bind(mode_group_name)
+            .fetch_optional(db)
+            .await?)
+    }
+
     pub async fn get_by_mode_group_description(
         db: &PgPool,
         mode_group_description: &str,
@@ -68,7 +109,7 @@ impl ModeGroupQueries {
             ModeGroupRow,
             r#"SELECT mode_group_id, mode_group_name, mode_group_description, created_at, updated_at
 	            FROM core.mode_group
-                WHERE mode_group_description = $1"#,
+                WHERE mode_group_description = $1 AND deleted_at IS NULL"#,
             mode_group_description
         )
         .fetch_optional(db)
@@ -76,73 +117,85 @@ impl ModeGroupQueries {
     }
 
     /// validates input strings for mode group operations
-    fn validate_input(name: &str, description: &str) -> anyhow::Result<(String, String)> {
+    #[cfg(not(feature = "stored-procedures"))]
+    fn validate_input(name: &str, description: &str) -> AppResult<(String, String)> {
         let name = name.trim().to_string();
         let desc = description.trim().to_string();
 
         if name.is_empty() {
-            return Err(anyhow!("mode_group_name cannot be empty"));
+            return Err(AppError::Validation(
+                "mode_group_name cannot be empty".to_string(),
+            ));
         }
 
         if desc.is_empty() {
-            return Err(anyhow!("mode_group_description cannot be empty"));
+            return Err(AppError::Validation(
+                "mode_group_description cannot be empty".to_string(),
+            ));
         }
 
         if name.len() > MAX_NAME_LEN {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "mode_group_name exceeds max length of {} characters",
                 MAX_NAME_LEN
-            ));
+            )));
         }
 
         if desc.len() > MAX_DESC_LEN {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "mode_group_description exceeds max length of {} characters",
                 MAX_DESC_LEN
-            ));
+            )));
         }
 
         Ok((name, desc))
     }
 
     /// validates a single field name or description
-    fn validate_field(field_name: &str, value: &str, max_len: usize) -> anyhow::Result<String> {
+    #[cfg(not(feature = "stored-procedures"))]
+    fn validate_field(field_name: &str, value: &str, max_len: usize) -> AppResult<String> {
         let trimmed = value.trim().to_string();
 
         if trimmed.is_empty() {
-            return Err(anyhow!("{} cannot be empty", field_name));
+            return Err(AppError::Validation(format!(
+                "{} cannot be empty",
+                field_name
+            )));
         }
 
         if trimmed.len() > max_len {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "{} exceeds max length of {} characters",
-                field_name,
-                max_len
-            ));
+                field_name, max_len
+            )));
         }
 
         Ok(trimmed)
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(name = %mode_group_name))]
     pub async fn create_mode_group(
-        db: &PgPool,
+        db: &mut PgConnection,
         mode_group_name: &str,
         mode_group_description: &str,
-    ) -> anyhow::Result<ModeGroupRow> {
+    ) -> AppResult<ModeGroupRow> {
         let (name, desc) = Self::validate_input(mode_group_name, mode_group_description)?;
 
         // check for duplicate name
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.mode_group WHERE mode_group_name = $1",
+            "SELECT 1 FROM core.mode_group WHERE mode_group_name = $1 AND deleted_at IS NULL",
             name
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate mode_group_name")?
         {
             error!("Rejected: duplicate mode_group_name '{}'", name);
-            return Err(anyhow!("mode_group_name '{}' already exists", name));
+            return Err(AppError::Conflict(format!(
+                "mode_group_name '{}' already exists",
+                name
+            )));
         }
 
         debug!("Inserting mode group '{}'", name);
@@ -156,7 +209,7 @@ impl ModeGroupQueries {
             name,
             desc
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .with_context(|| format!("Failed to insert mode group with name '{}'", name))?;
 
@@ -164,49 +217,87 @@ impl ModeGroupQueries {
         Ok(result)
     }
 
+    /// core.insertModeGroup (019) validates the input and checks for duplicates
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(name = %mode_group_name))]
+    pub async fn create_mode_group(
+        db: &mut PgConnection,
+        mode_group_name: &str,
+        mode_group_description: &str,
+    ) -> AppResult<ModeGroupRow> {
+        let result: ModeGroupRow = Procedure::new("core.insertModeGroup")
+            .bind(mode_group_name)
+            .bind(mode_group_description)
+            .fetch(db)
+            .await?;
+
+        debug!(?result, "Successfully inserted mode group");
+        Ok(result)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(id = %mode_group_id, name = %mode_group_name))]
     pub async fn update_mode_group_name(
-        db: &PgPool,
+        db: &mut PgConnection,
         mode_group_id: Uuid,
         mode_group_name: &str,
-    ) -> anyhow::Result<Option<ModeGroupRow>> {
+    ) -> AppResult<Option<ModeGroupRow>> {
         let name = Self::validate_field("mode_group_name", mode_group_name, MAX_NAME_LEN)?;
 
         // check for duplicate name (excluding current record)
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.mode_group WHERE mode_group_name = $1 AND mode_group_id != $2",
+            "SELECT 1 FROM core.mode_group WHERE mode_group_name = $1 AND mode_group_id != $2 AND deleted_at IS NULL",
             name,
             mode_group_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate mode_group_name")?
         {
-            return Err(anyhow!("mode_group_name '{}' already exists", name));
+            return Err(AppError::Conflict(format!(
+                "mode_group_name '{}' already exists",
+                name
+            )));
         }
 
         let result = sqlx::query_as!(
             ModeGroupRow,
             r#"UPDATE core.mode_group 
                SET mode_group_name = $2, updated_at = NOW()
-               WHERE mode_group_id = $1
+               WHERE mode_group_id = $1 AND deleted_at IS NULL
                RETURNING mode_group_id, mode_group_name, mode_group_description, created_at, updated_at"#,
             mode_group_id,
             name
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update mode group name for id {}", mode_group_id))?;
 
         Ok(result)
     }
 
+    /// core.updateModeGroup (019), the description is left as is
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(id = %mode_group_id, name = %mode_group_name))]
+    pub async fn update_mode_group_name(
+        db: &mut PgConnection,
+        mode_group_id: Uuid,
+        mode_group_name: &str,
+    ) -> AppResult<Option<ModeGroupRow>> {
+        Ok(Procedure::new("core.updateModeGroup")
+            .bind(mode_group_id)
+            .bind(mode_group_name)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(id = %mode_group_id))]
     pub async fn update_mode_group_description(
-        db: &PgPool,
+        db: &mut PgConnection,
         mode_group_id: Uuid,
         mode_group_description: &str,
-    ) -> anyhow::Result<Option<ModeGroupRow>> {
+    ) -> AppResult<Option<ModeGroupRow>> {
         let desc = Self::validate_field(
             "mode_group_description",
             mode_group_description,
@@ -217,34 +308,192 @@ impl ModeGroupQueries {
             ModeGroupRow,
             r#"UPDATE core.mode_group 
                SET mode_group_description = $2, updated_at = NOW()
-               WHERE mode_group_id = $1
+               WHERE mode_group_id = $1 AND deleted_at IS NULL
                RETURNING mode_group_id, mode_group_name, mode_group_description, created_at, updated_at"#,
             mode_group_id,
             desc
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update mode group description for id {}", mode_group_id))?;
 
         Ok(result)
     }
 
+    /// core.updateModeGroup (019), a NULL name leaves the name as is
+    #[cfg(feature = "stored-procedures")]
     #[instrument(skip(db), fields(id = %mode_group_id))]
-    pub async fn delete_mode_group(db: &PgPool, mode_group_id: Uuid) -> Result<bool, sqlx::Error> {
+    pub async fn update_mode_group_description(
+        db: &mut PgConnection,
+        mode_group_id: Uuid,
+        mode_group_description: &str,
+    ) -> AppResult<Option<ModeGroupRow>> {
+        Ok(Procedure::new("core.updateModeGroup")
+            .bind(mode_group_id)
+            .bind(None::<&str>)
+            .bind(mode_group_description)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    /// Soft delete, the row is kept until it is purged and can be restored until then
+    #[cfg(not(feature = "stored-procedures"))]
+    #[instrument(skip(db), fields(id = %mode_group_id))]
+    pub async fn delete_mode_group(db: &mut PgConnection, mode_group_id: Uuid) -> AppResult<bool> {
         let result = sqlx::query!(
-            "DELETE FROM core.mode_group WHERE mode_group_id = $1",
+            "UPDATE core.mode_group SET deleted_at = NOW() WHERE mode_group_id = $1 AND deleted_at IS NULL",
             mode_group_id
         )
-        .execute(db)
-        .await?;
+        .execute(&mut *db)
+        .await
+        .with_context(|| format!("Failed to delete mode group {}", mode_group_id))?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// Soft delete with core.deleteModeGroup (019), which refuses the default mode group and
+    /// groups that still have modes or equipment
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(id = %mode_group_id))]
+    pub async fn delete_mode_group(db: &mut PgConnection, mode_group_id: Uuid) -> AppResult<bool> {
+        match Procedure::new("core.deleteModeGroup")
+            .bind(mode_group_id)
+            .execute(db)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(ProcedureError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Undo a soft delete, None when there is no deleted mode group with this id
+    #[instrument(skip(db), fields(id = %mode_group_id))]
+    pub async fn restore_mode_group(
+        db: &mut PgConnection,
+        mode_group_id: Uuid,
+    ) -> Result<Option<ModeGroupRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ModeGroupRow,
+            r#"UPDATE core.mode_group
+               SET deleted_at = NULL
+               WHERE mode_group_id = $1 AND deleted_at IS NOT NULL
+               RETURNING mode_group_id, mode_group_name, mode_group_description, created_at, updated_at"#,
+            mode_group_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// A mode group is in use while it still owns live modes or is mapped to live equipment
+    pub async fn is_in_use(db: &PgPool, mode_group_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM core.mode WHERE mode_group_id = $1 AND deleted_at IS NULL)
+                   OR EXISTS(SELECT 1 FROM core.equipment_mode_group_mapping m
+                             JOIN core.equipment e ON e.equipment_id = m.equipment_id
+                             WHERE m.mode_group_id = $1 AND e.deleted_at IS NULL)"#,
+            mode_group_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Maps equipment to the mode group with core.assignEquipmentToModeGroup (018)
+    #[instrument(skip(db))]
+    pub async fn assign_equipment(
+        db: &mut PgConnection,
+        mode_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> Result<ProcedureRow, ProcedureError> {
+        Procedure::new("core.assignEquipmentToModeGroup")
+            .bind(equipment_id)
+            .bind(mode_group_id)
+            .execute(db)
+            .await
+    }
+
+    /// Removes the mapping with core.unassignEquipmentFromModeGroup (018)
+    #[instrument(skip(db))]
+    pub async fn unassign_equipment(
+        db: &mut PgConnection,
+        mode_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> Result<ProcedureRow, ProcedureError> {
+        Procedure::new("core.unassignEquipmentFromModeGroup")
+            .bind(equipment_id)
+            .bind(mode_group_id)
+            .execute(db)
+            .await
+    }
+
+    /// Maps all the equipment at once with core.bulkAssignEquipmentToModeGroup (018),
+    /// equipment that is already mapped is skipped
+    #[instrument(skip(db, equipment_ids), fields(count = equipment_ids.len()))]
+    pub async fn bulk_assign_equipment(
+        db: &mut PgConnection,
+        mode_group_id: Uuid,
+        equipment_ids: &[Uuid],
+    ) -> Result<BulkGroupAssignment, ProcedureError> {
+        Procedure::new("core.bulkAssignEquipmentToModeGroup")
+            .bind(equipment_ids)
+            .bind(mode_group_id)
+            .fetch(db)
+            .await
+    }
+
+    /// Equipment mapped to the mode group, from core.getEquipmentForModeGroup (018)
+    pub async fn get_equipment(
+        db: &PgPool,
+        mode_group_id: Uuid,
+    ) -> Result<Vec<GroupEquipment>, ProcedureError> {
+        let data: GroupEquipmentData = Procedure::new("core.getEquipmentForModeGroup")
+            .bind(mode_group_id)
+            .fetch(db)
+            .await?;
+        Ok(data.equipment)
+    }
+
+    /// Usage and runtime per live mode group from core.getModeGroupUsageStats (020), one
+    /// group when `mode_group_id` is given. The procedure defaults the range to the last
+    /// 30 days.
+    pub async fn get_usage_stats(
+        db: &PgPool,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+        mode_group_id: Option<Uuid>,
+    ) -> Result<ModeGroupUsageStats, ProcedureError> {
+        Procedure::new("core.getModeGroupUsageStats")
+            .bind(from)
+            .bind(to)
+            .bind(mode_group_id)
+            .fetch(db)
+            .await
+    }
+
+    /// Live mode groups the equipment is mapped to
+    pub async fn get_by_equipment_id(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Vec<ModeGroupRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ModeGroupRow,
+            r#"SELECT g.mode_group_id, g.mode_group_name, g.mode_group_description, g.created_at, g.updated_at
+               FROM core.mode_group g
+               JOIN core.equipment_mode_group_mapping m ON m.mode_group_id = g.mode_group_id
+               WHERE m.equipment_id = $1 AND g.deleted_at IS NULL
+               ORDER BY g.mode_group_name"#,
+            equipment_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
     pub async fn exists(db: &PgPool, mode_group_id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-                SELECT 1 FROM core.mode_group WHERE mode_group_id = $1
+                SELECT 1 FROM core.mode_group WHERE mode_group_id = $1 AND deleted_at IS NULL
             )"#,
             mode_group_id
         )
@@ -258,6 +507,8 @@ impl ModeGroupQueries {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "stored-procedures")]
+    use crate::error::AppError;
     use uuid::Uuid;
 
     // helper to create a test group bypassing validation for test setup
@@ -281,11 +532,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_name = "Test Group";
         let mode_group_description = "Test Group Description";
 
         let new_mode_group =
-            ModeGroupQueries::create_mode_group(&pool, mode_group_name, mode_group_description)
+            ModeGroupQueries::create_mode_group(&mut conn, mode_group_name, mode_group_description)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -301,21 +553,23 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode_group_validation_errors(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // test empty name
-        let result = ModeGroupQueries::create_mode_group(&pool, "", "Valid description").await;
+        let result = ModeGroupQueries::create_mode_group(&mut conn, "", "Valid description").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // test empty description
-        let result = ModeGroupQueries::create_mode_group(&pool, "Valid name", "").await;
+        let result = ModeGroupQueries::create_mode_group(&mut conn, "Valid name", "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // test whitespace-only inputs
-        let result = ModeGroupQueries::create_mode_group(&pool, "   ", "Valid description").await;
+        let result =
+            ModeGroupQueries::create_mode_group(&mut conn, "   ", "Valid description").await;
         assert!(result.is_err());
 
-        let result = ModeGroupQueries::create_mode_group(&pool, "Valid name", "   ").await;
+        let result = ModeGroupQueries::create_mode_group(&mut conn, "Valid name", "   ").await;
         assert!(result.is_err());
 
         Ok(())
@@ -323,15 +577,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode_group_duplicate_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group_name = "Duplicate Test Group";
 
         // first creation should succeed
-        let _first = ModeGroupQueries::create_mode_group(&pool, group_name, "Description 1")
+        let _first = ModeGroupQueries::create_mode_group(&mut conn, group_name, "Description 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // second creation with same name should fail
-        let result = ModeGroupQueries::create_mode_group(&pool, group_name, "Description 2").await;
+        let result =
+            ModeGroupQueries::create_mode_group(&mut conn, group_name, "Description 2").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -340,14 +596,21 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_all_mode_groups(pool: PgPool) -> sqlx::Result<()> {
-        let mode_group_1 =
-            ModeGroupQueries::create_mode_group(&pool, "Test Group 1", "Test Group 1 Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let mode_group_2 =
-            ModeGroupQueries::create_mode_group(&pool, "Test Group 2", "Test Group 2 Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let mode_group_1 = ModeGroupQueries::create_mode_group(
+            &mut conn,
+            "Test Group 1",
+            "Test Group 1 Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mode_group_2 = ModeGroupQueries::create_mode_group(
+            &mut conn,
+            "Test Group 2",
+            "Test Group 2 Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let all_mode_groups = ModeGroupQueries::get_all(&pool).await?;
 
@@ -368,9 +631,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_mode_group_id(pool: PgPool) -> sqlx::Result<()> {
-        let created = ModeGroupQueries::create_mode_group(&pool, "Test Group", "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created =
+            ModeGroupQueries::create_mode_group(&mut conn, "Test Group", "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let found = ModeGroupQueries::get_by_mode_group_id(&pool, created.mode_group_id).await?;
 
@@ -395,10 +660,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_mode_group_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group_name = "Unique Test Group Name";
-        let created = ModeGroupQueries::create_mode_group(&pool, group_name, "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            ModeGroupQueries::create_mode_group(&mut conn, group_name, "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let found = ModeGroupQueries::get_by_mode_group_name(&pool, group_name).await?;
 
@@ -421,8 +688,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_mode_group_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let description = "Unique Test Description";
-        let created = ModeGroupQueries::create_mode_group(&pool, "Test Group", description)
+        let created = ModeGroupQueries::create_mode_group(&mut conn, "Test Group", description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -449,14 +717,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let created =
-            ModeGroupQueries::create_mode_group(&pool, "Original Name", "Test Description")
+            ModeGroupQueries::create_mode_group(&mut conn, "Original Name", "Test Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let new_name = "Updated Name";
 
         let updated =
-            ModeGroupQueries::update_mode_group_name(&pool, created.mode_group_id, new_name)
+            ModeGroupQueries::update_mode_group_name(&mut conn, created.mode_group_id, new_name)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -476,20 +745,21 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_name_validation(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let created =
-            ModeGroupQueries::create_mode_group(&pool, "Original Name", "Test Description")
+            ModeGroupQueries::create_mode_group(&mut conn, "Original Name", "Test Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test empty name
         let result =
-            ModeGroupQueries::update_mode_group_name(&pool, created.mode_group_id, "").await;
+            ModeGroupQueries::update_mode_group_name(&mut conn, created.mode_group_id, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test whitespace-only name
         let result =
-            ModeGroupQueries::update_mode_group_name(&pool, created.mode_group_id, "   ").await;
+            ModeGroupQueries::update_mode_group_name(&mut conn, created.mode_group_id, "   ").await;
         assert!(result.is_err());
 
         Ok(())
@@ -497,18 +767,23 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_name_duplicate(pool: PgPool) -> sqlx::Result<()> {
-        let first = ModeGroupQueries::create_mode_group(&pool, "First Group", "First Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let first =
+            ModeGroupQueries::create_mode_group(&mut conn, "First Group", "First Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let second =
-            ModeGroupQueries::create_mode_group(&pool, "Second Group", "Second Description")
+            ModeGroupQueries::create_mode_group(&mut conn, "Second Group", "Second Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to update second group's name to match first group's name
-        let result =
-            ModeGroupQueries::update_mode_group_name(&pool, second.mode_group_id, "First Group")
-                .await;
+        let result = ModeGroupQueries::update_mode_group_name(
+            &mut conn,
+            second.mode_group_id,
+            "First Group",
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -517,8 +792,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_name_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let result = ModeGroupQueries::update_mode_group_name(&pool, random_id, "New Name")
+        let result = ModeGroupQueries::update_mode_group_name(&mut conn, random_id, "New Name")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -529,14 +805,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let created =
-            ModeGroupQueries::create_mode_group(&pool, "Test Group", "Original Description")
+            ModeGroupQueries::create_mode_group(&mut conn, "Test Group", "Original Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let new_description = "Updated Description";
 
         let updated = ModeGroupQueries::update_mode_group_description(
-            &pool,
+            &mut conn,
             created.mode_group_id,
             new_description,
         )
@@ -555,14 +832,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_description_validation(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let created =
-            ModeGroupQueries::create_mode_group(&pool, "Test Group", "Original Description")
+            ModeGroupQueries::create_mode_group(&mut conn, "Test Group", "Original Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test empty description
         let result =
-            ModeGroupQueries::update_mode_group_description(&pool, created.mode_group_id, "").await;
+            ModeGroupQueries::update_mode_group_description(&mut conn, created.mode_group_id, "")
+                .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
@@ -571,11 +850,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_description_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let result =
-            ModeGroupQueries::update_mode_group_description(&pool, random_id, "New Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let result = ModeGroupQueries::update_mode_group_description(
+            &mut conn,
+            random_id,
+            "New Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert!(result.is_none());
 
@@ -584,11 +867,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_mode_group(pool: PgPool) -> sqlx::Result<()> {
-        let created = ModeGroupQueries::create_mode_group(&pool, "To Delete", "Test Description")
+        let mut conn = pool.acquire().await?;
+        let created =
+            ModeGroupQueries::create_mode_group(&mut conn, "To Delete", "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let deleted = ModeGroupQueries::delete_mode_group(&mut conn, created.mode_group_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-
-        let deleted = ModeGroupQueries::delete_mode_group(&pool, created.mode_group_id).await?;
         assert!(deleted);
 
         // Verify it's gone
@@ -600,8 +887,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_mode_group_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let deleted = ModeGroupQueries::delete_mode_group(&pool, random_id).await?;
+        let deleted = ModeGroupQueries::delete_mode_group(&mut conn, random_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert!(!deleted);
 
@@ -610,9 +900,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_exists(pool: PgPool) -> sqlx::Result<()> {
-        let created = ModeGroupQueries::create_mode_group(&pool, "Exists Test", "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created =
+            ModeGroupQueries::create_mode_group(&mut conn, "Exists Test", "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let exists = ModeGroupQueries::exists(&pool, created.mode_group_id).await?;
         assert!(exists);
@@ -626,9 +918,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_input_trimming(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test that inputs are properly trimmed
         let created = ModeGroupQueries::create_mode_group(
-            &pool,
+            &mut conn,
             "  Trimmed Name  ",
             "  Trimmed Description  ",
         )
@@ -643,18 +936,23 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_preserves_timestamps(pool: PgPool) -> sqlx::Result<()> {
-        let created = ModeGroupQueries::create_mode_group(&pool, "Test Group", "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created =
+            ModeGroupQueries::create_mode_group(&mut conn, "Test Group", "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let original_created_at = created.created_at;
 
         // Short delay to ensure timestamp difference
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
 
-        let updated =
-            ModeGroupQueries::update_mode_group_name(&pool, created.mode_group_id, "Updated Name")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let updated = ModeGroupQueries::update_mode_group_name(
+            &mut conn,
+            created.mode_group_id,
+            "Updated Name",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert!(updated.is_some());
         let updated = updated.unwrap();
@@ -674,11 +972,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_multiple_operations_sequence(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test a sequence of operations to ensure they work together
 
         // Create
         let created =
-            ModeGroupQueries::create_mode_group(&pool, "Sequence Test", "Original Description")
+            ModeGroupQueries::create_mode_group(&mut conn, "Sequence Test", "Original Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -687,15 +986,18 @@ mod tests {
         assert!(exists);
 
         // Update name
-        let updated_name =
-            ModeGroupQueries::update_mode_group_name(&pool, created.mode_group_id, "Updated Name")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let updated_name = ModeGroupQueries::update_mode_group_name(
+            &mut conn,
+            created.mode_group_id,
+            "Updated Name",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(updated_name.is_some());
 
         // Update description
         let updated_desc = ModeGroupQueries::update_mode_group_description(
-            &pool,
+            &mut conn,
             created.mode_group_id,
             "Updated Description",
         )
@@ -712,7 +1014,9 @@ mod tests {
         assert_eq!(final_state.mode_group_description, "Updated Description");
 
         // Delete
-        let deleted = ModeGroupQueries::delete_mode_group(&pool, created.mode_group_id).await?;
+        let deleted = ModeGroupQueries::delete_mode_group(&mut conn, created.mode_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
 
         // Verify it no longer exists
@@ -721,4 +1025,135 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "stored-procedures")]
+    #[sqlx::test]
+    async fn test_procedure_delete_rules(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+
+        let default_group =
+            ModeGroupQueries::get_by_mode_group_name(&pool, "Default MES Mode Group")
+                .await?
+                .expect("the default mode group is seeded");
+        let result =
+            ModeGroupQueries::delete_mode_group(&mut conn, default_group.mode_group_id).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        let created =
+            ModeGroupQueries::create_mode_group(&mut conn, "Packaging", "Packaging modes")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        sqlx::query!(
+            "INSERT INTO core.mode (mode_group_id, mode_description) VALUES ($1, 'Changeover')",
+            created.mode_group_id
+        )
+        .execute(&pool)
+        .await?;
+        let result = ModeGroupQueries::delete_mode_group(&mut conn, created.mode_group_id).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        // a deleted mode no longer holds the group
+        sqlx::query!(
+            "UPDATE core.mode SET deleted_at = now() WHERE mode_group_id = $1",
+            created.mode_group_id
+        )
+        .execute(&pool)
+        .await?;
+        let deleted = ModeGroupQueries::delete_mode_group(&mut conn, created.mode_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(deleted);
+        assert!(
+            ModeGroupQueries::get_by_mode_group_id(&pool, created.mode_group_id)
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_usage_stats_runtime(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let default_group =
+            ModeGroupQueries::get_by_mode_group_name(&pool, "Default MES Mode Group")
+                .await?
+                .expect("the default mode group is seeded");
+        sqlx::query!(
+            "INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id) VALUES ($1, $2)",
+            equipment_id,
+            default_group.mode_group_id
+        )
+        .execute(&pool)
+        .await?;
+
+        // production for an hour, then idle until now
+        let t0 = (OffsetDateTime::now_utc() - time::Duration::hours(3))
+            .replace_nanosecond(0)
+            .unwrap();
+        let t1 = t0 + time::Duration::hours(1);
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_history (equipment_id, mode_id, set_at, ended_at)
+               SELECT $1, mode_id, $2, $3 FROM core.mode WHERE mode_description = 'production'"#,
+            equipment_id,
+            t0,
+            t1
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_history (equipment_id, mode_id, set_at)
+               SELECT $1, mode_id, $2 FROM core.mode WHERE mode_description = 'idle'"#,
+            equipment_id,
+            t1
+        )
+        .execute(&pool)
+        .await?;
+
+        // half an hour of each
+        let from = t0 + time::Duration::minutes(30);
+        let to = t1 + time::Duration::minutes(30);
+        let stats = ModeGroupQueries::get_usage_stats(&pool, Some(from), Some(to), None).await?;
+        assert_eq!(stats.mode_groups.len(), 1);
+        let group = &stats.mode_groups[0];
+        assert_eq!(group.mode_count, 4);
+        assert_eq!(group.unused_mode_count, 2);
+        assert_eq!(group.equipment_count, 1);
+        assert_eq!(group.runtime_seconds, 3600.0);
+        assert_eq!(stats.summary.idle_mode_groups, 0);
+
+        // a range before any history leaves every mode unused
+        let stats = ModeGroupQueries::get_usage_stats(
+            &pool,
+            Some(t0 - time::Duration::days(1)),
+            Some(t0),
+            Some(default_group.mode_group_id),
+        )
+        .await?;
+        assert_eq!(stats.mode_groups[0].unused_mode_count, 4);
+        assert_eq!(stats.summary.idle_mode_groups, 1);
+
+        // deleted groups are left out
+        let deleted = create_test_mode_group_raw(&pool, "Packaging", "Packaging modes").await?;
+        sqlx::query!(
+            "UPDATE core.mode_group SET deleted_at = now() WHERE mode_group_id = $1",
+            deleted.mode_group_id
+        )
+        .execute(&pool)
+        .await?;
+        let result =
+            ModeGroupQueries::get_usage_stats(&pool, None, None, Some(deleted.mode_group_id)).await;
+        assert!(matches!(result, Err(ProcedureError::NotFound(_))));
+
+        let result = ModeGroupQueries::get_usage_stats(&pool, Some(to), Some(from), None).await;
+        assert!(matches!(result, Err(ProcedureError::Invalid(_))));
+
+        Ok(())
+    }
 }
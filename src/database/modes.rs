@@ -1,5 +1,7 @@
-use anyhow::{Context, anyhow};
-use sqlx::PgPool;
+use crate::database::procedures::{Procedure, ProcedureError};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::core::ModeUsageStats;
+use sqlx::{PgConnection, PgPool};
 use time::OffsetDateTime;
 use tracing::{debug, error, instrument};
 use uuid::Uuid;
@@ -19,18 +21,20 @@ pub struct ModeRowQueries;
 
 impl ModeRowQueries {
     /// Validates and sanitizes mode description input
-    fn validate_mode_description(mode_description: &str) -> anyhow::Result<String> {
+    fn validate_mode_description(mode_description: &str) -> AppResult<String> {
         let trimmed = mode_description.trim().to_string();
 
         if trimmed.is_empty() {
-            return Err(anyhow!("mode_description cannot be empty"));
+            return Err(AppError::Validation(
+                "mode_description cannot be empty".to_string(),
+            ));
         }
 
         if trimmed.len() > MAX_DESC_LEN {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "mode_description exceeds max length of {} characters",
                 MAX_DESC_LEN
-            ));
+            )));
         }
 
         Ok(trimmed)
@@ -42,6 +46,7 @@ impl ModeRowQueries {
             r#"SELECT mode_id, mode_group_id, 
                 mode_description, created_at, updated_at
 	            FROM core.mode
+                WHERE deleted_at IS NULL
                 ORDER BY mode_description"#
         )
         .fetch_all(db)
@@ -57,7 +62,7 @@ impl ModeRowQueries {
             r#"SELECT mode_id, mode_group_id, mode_description,
              created_at, updated_at
 	            FROM core.mode
-                WHERE mode_group_id = $1
+                WHERE mode_group_id = $1 AND deleted_at IS NULL
                 ORDER BY mode_description"#,
             mode_group_id
         )
@@ -74,7 +79,7 @@ impl ModeRowQueries {
             r#"SELECT mode_id, mode_group_id, mode_description,
              created_at, updated_at
 	            FROM core.mode
-                WHERE mode_id = $1"#,
+                WHERE mode_id = $1 AND deleted_at IS NULL"#,
             mode_id
         )
         .fetch_optional(db)
@@ -90,7 +95,7 @@ impl ModeRowQueries {
             r#"SELECT mode_id, mode_group_id, mode_description,
              created_at, updated_at
 	            FROM core.mode
-                WHERE mode_description = $1"#,
+                WHERE mode_description = $1 AND deleted_at IS NULL"#,
             mode_description
         )
         .fetch_optional(db)
@@ -99,33 +104,36 @@ impl ModeRowQueries {
 
     #[instrument(skip(db), fields(group_id = %mode_group_id, description = %mode_description))]
     pub async fn create_mode(
-        db: &PgPool,
+        db: &mut PgConnection,
         mode_group_id: Uuid,
         mode_description: &str,
-    ) -> anyhow::Result<ModeRow> {
+    ) -> AppResult<ModeRow> {
         let validated_description = Self::validate_mode_description(mode_description)?;
 
         // Check if mode_group_id exists
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1 AND deleted_at IS NULL)",
             mode_group_id
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .context("Failed to check if mode_group exists")?;
 
         if !group_exists.unwrap_or(false) {
             error!("Rejected: mode_group_id {} does not exist", mode_group_id);
-            return Err(anyhow!("mode_group_id '{}' does not exist", mode_group_id));
+            return Err(AppError::Validation(format!(
+                "mode_group_id '{}' does not exist",
+                mode_group_id
+            )));
         }
 
         // Check for duplicate description within the same mode group
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.mode WHERE mode_group_id = $1 AND mode_description = $2",
+            "SELECT 1 FROM core.mode WHERE mode_group_id = $1 AND mode_description = $2 AND deleted_at IS NULL",
             mode_group_id,
             validated_description
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate mode_description in mode_group")?
         {
@@ -133,10 +141,10 @@ impl ModeRowQueries {
                 "Rejected: duplicate mode_description '{}' in mode_group {}",
                 validated_description, mode_group_id
             );
-            return Err(anyhow!(
+            return Err(AppError::Conflict(format!(
                 "mode_description '{}' already exists in this mode group",
                 validated_description
-            ));
+            )));
         }
 
         debug!(
@@ -152,7 +160,7 @@ impl ModeRowQueries {
             mode_group_id,
             validated_description
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .with_context(|| {
             format!(
@@ -167,37 +175,37 @@ impl ModeRowQueries {
 
     #[instrument(skip(db), fields(id = %mode_id, description = %mode_description))]
     pub async fn update_mode_description(
-        db: &PgPool,
+        db: &mut PgConnection,
         mode_id: Uuid,
         mode_description: &str,
-    ) -> anyhow::Result<Option<ModeRow>> {
+    ) -> AppResult<Option<ModeRow>> {
         let validated_description = Self::validate_mode_description(mode_description)?;
 
         // Get the current mode to check for duplicate in same group
         let current_mode = sqlx::query!(
-            "SELECT mode_group_id FROM core.mode WHERE mode_id = $1",
+            "SELECT mode_group_id FROM core.mode WHERE mode_id = $1 AND deleted_at IS NULL",
             mode_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to fetch current mode")?;
 
         if let Some(current) = current_mode {
             // Check for duplicate description within the same mode group (excluding current record)
             if let Some(_) = sqlx::query_scalar!(
-                "SELECT 1 FROM core.mode WHERE mode_group_id = $1 AND mode_description = $2 AND mode_id != $3",
+                "SELECT 1 FROM core.mode WHERE mode_group_id = $1 AND mode_description = $2 AND mode_id != $3 AND deleted_at IS NULL",
                 current.mode_group_id,
                 validated_description,
                 mode_id
             )
-            .fetch_optional(db)
+            .fetch_optional(&mut *db)
             .await
             .context("Failed to check for duplicate mode_description")?
             {
-                return Err(anyhow!(
+                return Err(AppError::Conflict(format!(
                     "mode_description '{}' already exists in this mode group",
                     validated_description
-                ));
+                )));
             }
         }
 
@@ -209,13 +217,13 @@ impl ModeRowQueries {
             ModeRow,
             r#"UPDATE core.mode 
                SET mode_description = $2, updated_at = NOW()
-               WHERE mode_id = $1
+               WHERE mode_id = $1 AND deleted_at IS NULL
                RETURNING mode_id, mode_group_id, mode_description,
                 created_at, updated_at"#,
             mode_id,
             validated_description
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update mode description for id {}", mode_id))?;
 
@@ -230,47 +238,50 @@ impl ModeRowQueries {
 
     #[instrument(skip(db), fields(id = %mode_id, new_group_id = %mode_group_id))]
     pub async fn update_mode_group(
-        db: &PgPool,
+        db: &mut PgConnection,
         mode_id: Uuid,
         mode_group_id: Uuid,
-    ) -> anyhow::Result<Option<ModeRow>> {
+    ) -> AppResult<Option<ModeRow>> {
         // Check if new mode_group_id exists
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1 AND deleted_at IS NULL)",
             mode_group_id
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .context("Failed to check if mode_group exists")?;
 
         if !group_exists.unwrap_or(false) {
-            return Err(anyhow!("mode_group_id '{}' does not exist", mode_group_id));
+            return Err(AppError::Validation(format!(
+                "mode_group_id '{}' does not exist",
+                mode_group_id
+            )));
         }
 
         // Get current mode to check for conflicts
         let current_mode = sqlx::query!(
-            "SELECT mode_description FROM core.mode WHERE mode_id = $1",
+            "SELECT mode_description FROM core.mode WHERE mode_id = $1 AND deleted_at IS NULL",
             mode_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to fetch current mode")?;
 
         if let Some(current) = current_mode {
             // Check for duplicate description in the new mode group
             if let Some(_) = sqlx::query_scalar!(
-                "SELECT 1 FROM core.mode WHERE mode_group_id = $1 AND mode_description = $2",
+                "SELECT 1 FROM core.mode WHERE mode_group_id = $1 AND mode_description = $2 AND deleted_at IS NULL",
                 mode_group_id,
                 current.mode_description
             )
-            .fetch_optional(db)
+            .fetch_optional(&mut *db)
             .await
             .context("Failed to check for duplicate mode_description in new group")?
             {
-                return Err(anyhow!(
+                return Err(AppError::Conflict(format!(
                     "mode_description '{}' already exists in the target mode group",
                     current.mode_description
-                ));
+                )));
             }
         }
 
@@ -279,13 +290,13 @@ impl ModeRowQueries {
             ModeRow,
             r#"UPDATE core.mode 
                SET mode_group_id = $2, updated_at = NOW()
-               WHERE mode_id = $1
+               WHERE mode_id = $1 AND deleted_at IS NULL
                RETURNING mode_id, mode_group_id, mode_description,
                 created_at, updated_at"#,
             mode_id,
             mode_group_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update mode group for id {}", mode_id))?;
 
@@ -298,16 +309,17 @@ impl ModeRowQueries {
         Ok(result)
     }
 
+    /// Soft delete, the mode history keeps pointing at the row until it is purged
     #[instrument(skip(db), fields(id = %mode_id))]
-    pub async fn delete_mode(db: &PgPool, mode_id: Uuid) -> anyhow::Result<bool> {
-        // TODO: Add check to see if the mode is being used anywhere
-        // This would prevent deletion of modes that are in use
-
+    pub async fn delete_mode(db: &mut PgConnection, mode_id: Uuid) -> AppResult<bool> {
         debug!("Deleting mode {}", mode_id);
-        let result = sqlx::query!("DELETE FROM core.mode WHERE mode_id = $1", mode_id)
-            .execute(db)
-            .await
-            .with_context(|| format!("Failed to delete mode with id {}", mode_id))?;
+        let result = sqlx::query!(
+            "UPDATE core.mode SET deleted_at = NOW() WHERE mode_id = $1 AND deleted_at IS NULL",
+            mode_id
+        )
+        .execute(&mut *db)
+        .await
+        .with_context(|| format!("Failed to delete mode with id {}", mode_id))?;
 
         let deleted = result.rows_affected() > 0;
         if deleted {
@@ -319,10 +331,29 @@ impl ModeRowQueries {
         Ok(deleted)
     }
 
+    /// Undo a soft delete, None when there is no deleted mode with this id
+    #[instrument(skip(db), fields(id = %mode_id))]
+    pub async fn restore_mode(
+        db: &mut PgConnection,
+        mode_id: Uuid,
+    ) -> Result<Option<ModeRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ModeRow,
+            r#"UPDATE core.mode
+               SET deleted_at = NULL
+               WHERE mode_id = $1 AND deleted_at IS NOT NULL
+               RETURNING mode_id, mode_group_id, mode_description,
+                created_at, updated_at"#,
+            mode_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
     pub async fn exists(db: &PgPool, mode_id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-                SELECT 1 FROM core.mode WHERE mode_id = $1
+                SELECT 1 FROM core.mode WHERE mode_id = $1 AND deleted_at IS NULL
             )"#,
             mode_id
         )
@@ -341,7 +372,7 @@ impl ModeRowQueries {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
                 SELECT 1 FROM core.mode 
-                WHERE mode_group_id = $1 AND mode_description = $2
+                WHERE mode_group_id = $1 AND mode_description = $2 AND deleted_at IS NULL
             )"#,
             mode_group_id,
             mode_description.trim()
@@ -363,7 +394,7 @@ impl ModeRowQueries {
             ModeRow,
             r#"SELECT mode_id, mode_group_id, mode_description, created_at, updated_at
                FROM core.mode
-               WHERE lower(mode_description::text COLLATE "C") LIKE $1
+               WHERE lower(mode_description::text COLLATE "C") LIKE $1 AND deleted_at IS NULL
                ORDER BY mode_description"#,
             pattern
         )
@@ -372,13 +403,10 @@ impl ModeRowQueries {
     }
 
     /// Get all modes for a specific mode group with validation
-    pub async fn get_modes_for_group(
-        db: &PgPool,
-        mode_group_id: Uuid,
-    ) -> anyhow::Result<Vec<ModeRow>> {
+    pub async fn get_modes_for_group(db: &PgPool, mode_group_id: Uuid) -> AppResult<Vec<ModeRow>> {
         // Check if mode_group exists first
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1 AND deleted_at IS NULL)",
             mode_group_id
         )
         .fetch_one(db)
@@ -386,7 +414,10 @@ impl ModeRowQueries {
         .context("Failed to check if mode_group exists")?;
 
         if !group_exists.unwrap_or(false) {
-            return Err(anyhow!("mode_group_id '{}' does not exist", mode_group_id));
+            return Err(AppError::Validation(format!(
+                "mode_group_id '{}' does not exist",
+                mode_group_id
+            )));
         }
 
         let modes = Self::get_by_mode_group_id(db, mode_group_id)
@@ -395,6 +426,22 @@ impl ModeRowQueries {
 
         Ok(modes)
     }
+
+    /// Runtime per live mode over a range from core.getModeUsageStats (020), one mode when
+    /// `mode_id` is given
+    pub async fn get_usage_stats(
+        db: &PgPool,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+        mode_id: Option<Uuid>,
+    ) -> Result<ModeUsageStats, ProcedureError> {
+        Procedure::new("core.getModeUsageStats")
+            .bind(from)
+            .bind(to)
+            .bind(mode_id)
+            .fetch(db)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -437,10 +484,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
         let mode_description = "Test Mode Description";
 
-        let mode = ModeRowQueries::create_mode(&pool, mode_group_id, mode_description)
+        let mode = ModeRowQueries::create_mode(&mut conn, mode_group_id, mode_description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -454,21 +502,22 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode_validation_errors(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
 
         // Test empty description
-        let result = ModeRowQueries::create_mode(&pool, mode_group_id, "").await;
+        let result = ModeRowQueries::create_mode(&mut conn, mode_group_id, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test whitespace-only description
-        let result = ModeRowQueries::create_mode(&pool, mode_group_id, "   ").await;
+        let result = ModeRowQueries::create_mode(&mut conn, mode_group_id, "   ").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test too long description
         let long_desc = "a".repeat(3000);
-        let result = ModeRowQueries::create_mode(&pool, mode_group_id, &long_desc).await;
+        let result = ModeRowQueries::create_mode(&mut conn, mode_group_id, &long_desc).await;
         assert!(result.is_err());
         assert!(
             result
@@ -482,10 +531,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode_invalid_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let invalid_group_id = Uuid::new_v4();
 
         let result =
-            ModeRowQueries::create_mode(&pool, invalid_group_id, "Valid Description").await;
+            ModeRowQueries::create_mode(&mut conn, invalid_group_id, "Valid Description").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
 
@@ -494,16 +544,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode_duplicate_description_in_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
         let mode_description = "Duplicate Description";
 
         // First creation should succeed
-        let _first = ModeRowQueries::create_mode(&pool, mode_group_id, mode_description)
+        let _first = ModeRowQueries::create_mode(&mut conn, mode_group_id, mode_description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Second creation with same description in same group should fail
-        let result = ModeRowQueries::create_mode(&pool, mode_group_id, mode_description).await;
+        let result = ModeRowQueries::create_mode(&mut conn, mode_group_id, mode_description).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -512,17 +563,18 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_mode_same_description_different_groups(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group1_id = create_test_mode_group(&pool).await?;
         let group2_id = create_test_mode_group(&pool).await?;
         let mode_description = "Same Description";
 
         // Create mode in first group
-        let _mode1 = ModeRowQueries::create_mode(&pool, group1_id, mode_description)
+        let _mode1 = ModeRowQueries::create_mode(&mut conn, group1_id, mode_description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Create mode with same description in second group - should succeed
-        let _mode2 = ModeRowQueries::create_mode(&pool, group2_id, mode_description)
+        let _mode2 = ModeRowQueries::create_mode(&mut conn, group2_id, mode_description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -531,11 +583,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_input_trimming(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
 
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "  Trimmed Description  ")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            ModeRowQueries::create_mode(&mut conn, mode_group_id, "  Trimmed Description  ")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert_eq!(created.mode_description, "Trimmed Description");
 
@@ -544,12 +598,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_all_modes(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
 
-        let mode1 = ModeRowQueries::create_mode(&pool, mode_group_id, "Mode 1")
+        let mode1 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Mode 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let mode2 = ModeRowQueries::create_mode(&pool, mode_group_id, "Mode 2")
+        let mode2 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Mode 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -564,8 +619,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_mode_id(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "Test Mode")
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Test Mode")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -591,12 +647,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_mode_group_id(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
 
-        let mode1 = ModeRowQueries::create_mode(&pool, mode_group_id, "Mode 1")
+        let mode1 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Mode 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let mode2 = ModeRowQueries::create_mode(&pool, mode_group_id, "Mode 2")
+        let mode2 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Mode 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -611,9 +668,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_mode_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
         let description = "Unique Mode Description";
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, description)
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -629,14 +687,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "Original Description")
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Original Description")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let new_description = "Updated Description";
 
         let updated =
-            ModeRowQueries::update_mode_description(&pool, created.mode_id, new_description)
+            ModeRowQueries::update_mode_description(&mut conn, created.mode_id, new_description)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -651,13 +710,14 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_description_validation(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "Original Description")
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Original Description")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test empty description
-        let result = ModeRowQueries::update_mode_description(&pool, created.mode_id, "").await;
+        let result = ModeRowQueries::update_mode_description(&mut conn, created.mode_id, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
@@ -666,17 +726,18 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_description_duplicate(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let mode1 = ModeRowQueries::create_mode(&pool, mode_group_id, "First Description")
+        let mode1 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "First Description")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let mode2 = ModeRowQueries::create_mode(&pool, mode_group_id, "Second Description")
+        let mode2 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Second Description")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to update mode2 to have same description as mode1
         let result =
-            ModeRowQueries::update_mode_description(&pool, mode2.mode_id, "First Description")
+            ModeRowQueries::update_mode_description(&mut conn, mode2.mode_id, "First Description")
                 .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
@@ -686,13 +747,14 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group1_id = create_test_mode_group(&pool).await?;
         let group2_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, group1_id, "Test Description")
+        let created = ModeRowQueries::create_mode(&mut conn, group1_id, "Test Description")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
-        let updated = ModeRowQueries::update_mode_group(&pool, created.mode_id, group2_id)
+        let updated = ModeRowQueries::update_mode_group(&mut conn, created.mode_id, group2_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -707,14 +769,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_invalid(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "Test Description")
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Test Description")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let invalid_group_id = Uuid::new_v4();
 
         let result =
-            ModeRowQueries::update_mode_group(&pool, created.mode_id, invalid_group_id).await;
+            ModeRowQueries::update_mode_group(&mut conn, created.mode_id, invalid_group_id).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
 
@@ -723,22 +786,23 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_mode_group_conflict(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group1_id = create_test_mode_group(&pool).await?;
         let group2_id = create_test_mode_group(&pool).await?;
         let description = "Same Description";
 
         // Create mode in group1
-        let mode1 = ModeRowQueries::create_mode(&pool, group1_id, description)
+        let mode1 = ModeRowQueries::create_mode(&mut conn, group1_id, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Create mode in group2 with same description
-        let _mode2 = ModeRowQueries::create_mode(&pool, group2_id, description)
+        let _mode2 = ModeRowQueries::create_mode(&mut conn, group2_id, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to move mode1 to group2 - should fail due to description conflict
-        let result = ModeRowQueries::update_mode_group(&pool, mode1.mode_id, group2_id).await;
+        let result = ModeRowQueries::update_mode_group(&mut conn, mode1.mode_id, group2_id).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -747,12 +811,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_mode(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "To Delete")
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, "To Delete")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
-        let deleted = ModeRowQueries::delete_mode(&pool, created.mode_id)
+        let deleted = ModeRowQueries::delete_mode(&mut conn, created.mode_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
@@ -766,8 +831,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_mode_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let deleted = ModeRowQueries::delete_mode(&pool, random_id)
+        let deleted = ModeRowQueries::delete_mode(&mut conn, random_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -778,8 +844,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_exists(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "Exists Test")
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Exists Test")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -795,9 +862,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_description_exists_in_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
         let description = "Exists Test Description";
-        let _created = ModeRowQueries::create_mode(&pool, mode_group_id, description)
+        let _created = ModeRowQueries::create_mode(&mut conn, mode_group_id, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -823,16 +891,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_search_by_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
 
         // Create test data
-        let _pump = ModeRowQueries::create_mode(&pool, mode_group_id, "Water Pump Mode")
+        let _pump = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Water Pump Mode")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let _motor = ModeRowQueries::create_mode(&pool, mode_group_id, "Electric Motor Mode")
+        let _motor = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Electric Motor Mode")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let _valve = ModeRowQueries::create_mode(&pool, mode_group_id, "Control Valve Mode")
+        let _valve = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Control Valve Mode")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -863,12 +932,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_modes_for_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
 
-        let mode1 = ModeRowQueries::create_mode(&pool, mode_group_id, "Mode 1")
+        let mode1 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Mode 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let mode2 = ModeRowQueries::create_mode(&pool, mode_group_id, "Mode 2")
+        let mode2 = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Mode 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -896,8 +966,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_preserves_timestamps(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let mode_group_id = create_test_mode_group(&pool).await?;
-        let created = ModeRowQueries::create_mode(&pool, mode_group_id, "Test Mode")
+        let created = ModeRowQueries::create_mode(&mut conn, mode_group_id, "Test Mode")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let original_created_at = created.created_at;
@@ -906,7 +977,7 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
 
         let updated =
-            ModeRowQueries::update_mode_description(&pool, created.mode_id, "Updated Mode")
+            ModeRowQueries::update_mode_description(&mut conn, created.mode_id, "Updated Mode")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -928,13 +999,14 @@ mod tests {
 
     #[sqlx::test]
     async fn test_multiple_operations_sequence(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test a sequence of operations to ensure they work together
 
         let group1_id = create_test_mode_group(&pool).await?;
         let group2_id = create_test_mode_group(&pool).await?;
 
         // Create
-        let created = ModeRowQueries::create_mode(&pool, group1_id, "Sequence Test Mode")
+        let created = ModeRowQueries::create_mode(&mut conn, group1_id, "Sequence Test Mode")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -950,7 +1022,7 @@ mod tests {
 
         // Update description
         let updated_desc = ModeRowQueries::update_mode_description(
-            &pool,
+            &mut conn,
             created.mode_id,
             "Updated Sequence Mode",
         )
@@ -959,9 +1031,10 @@ mod tests {
         assert!(updated_desc.is_some());
 
         // Update group
-        let updated_group = ModeRowQueries::update_mode_group(&pool, created.mode_id, group2_id)
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let updated_group =
+            ModeRowQueries::update_mode_group(&mut conn, created.mode_id, group2_id)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(updated_group.is_some());
 
         // Verify final state
@@ -976,7 +1049,7 @@ mod tests {
         assert!(search_results.iter().any(|m| m.mode_id == created.mode_id));
 
         // Delete
-        let deleted = ModeRowQueries::delete_mode(&pool, created.mode_id)
+        let deleted = ModeRowQueries::delete_mode(&mut conn, created.mode_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
@@ -0,0 +1,237 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StateClassificationRow {
+    pub classification_id: Uuid,
+    pub state_group_id: Uuid,
+    pub state_code: i32,
+    pub category: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct IdealCycleTimeRow {
+    pub equipment_id: Uuid,
+    pub ideal_cycle_seconds: f64,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+/// A state interval with its oee category (None when the code is not classified)
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ClassifiedStateIntervalRow {
+    pub equipment_id: Uuid,
+    pub started_at: OffsetDateTime,
+    pub ended_at: Option<OffsetDateTime>,
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ModeIntervalRow {
+    pub equipment_id: Uuid,
+    pub mode_description: String,
+    pub set_at: OffsetDateTime,
+    pub ended_at: Option<OffsetDateTime>,
+}
+
+pub struct OeeQueries;
+
+impl OeeQueries {
+    pub async fn get_classifications(
+        db: &PgPool,
+        state_group_id: Uuid,
+    ) -> Result<Vec<StateClassificationRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateClassificationRow,
+            r#"SELECT classification_id, state_group_id, state_code, category,
+                      created_at, updated_at
+               FROM core.state_classification
+               WHERE state_group_id = $1
+               ORDER BY state_code"#,
+            state_group_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Insert or replace the category of a state code
+    pub async fn upsert_classification(
+        db: &PgPool,
+        state_group_id: Uuid,
+        state_code: i32,
+        category: &str,
+    ) -> Result<StateClassificationRow, sqlx::Error> {
+        sqlx::query_as!(
+            StateClassificationRow,
+            r#"INSERT INTO core.state_classification (state_group_id, state_code, category)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (state_group_id, state_code)
+               DO UPDATE SET category = EXCLUDED.category
+               RETURNING classification_id, state_group_id, state_code, category,
+                         created_at, updated_at"#,
+            state_group_id,
+            state_code,
+            category
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn delete_classification(
+        db: &PgPool,
+        state_group_id: Uuid,
+        state_code: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM core.state_classification
+               WHERE state_group_id = $1 AND state_code = $2"#,
+            state_group_id,
+            state_code
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_ideal_cycle_times(
+        db: &PgPool,
+        equipment_ids: &[Uuid],
+    ) -> Result<Vec<IdealCycleTimeRow>, sqlx::Error> {
+        sqlx::query_as!(
+            IdealCycleTimeRow,
+            r#"SELECT equipment_id, ideal_cycle_seconds, created_at, updated_at
+               FROM core.equipment_ideal_cycle_time
+               WHERE equipment_id = ANY($1)"#,
+            equipment_ids
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Insert or replace the ideal cycle time of an equipment
+    pub async fn upsert_ideal_cycle_time(
+        db: &PgPool,
+        equipment_id: Uuid,
+        ideal_cycle_seconds: f64,
+    ) -> Result<IdealCycleTimeRow, sqlx::Error> {
+        sqlx::query_as!(
+            IdealCycleTimeRow,
+            r#"INSERT INTO core.equipment_ideal_cycle_time (equipment_id, ideal_cycle_seconds)
+               VALUES ($1, $2)
+               ON CONFLICT (equipment_id)
+               DO UPDATE SET ideal_cycle_seconds = EXCLUDED.ideal_cycle_seconds
+               RETURNING equipment_id, ideal_cycle_seconds, created_at, updated_at"#,
+            equipment_id,
+            ideal_cycle_seconds
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn delete_ideal_cycle_time(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM core.equipment_ideal_cycle_time WHERE equipment_id = $1",
+            equipment_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// State intervals of the given equipment overlapping [from, to)
+    pub async fn get_state_intervals(
+        db: &PgPool,
+        equipment_ids: &[Uuid],
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<ClassifiedStateIntervalRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ClassifiedStateIntervalRow,
+            r#"SELECT h.equipment_id, h.started_at, h.ended_at, sc.category as "category?"
+               FROM core.equipment_state_history h
+               JOIN core.state s ON s.state_id = h.state_id
+               LEFT JOIN core.state_classification sc
+                 ON sc.state_group_id = s.state_group_id AND sc.state_code = s.state_code
+               WHERE h.equipment_id = ANY($1)
+                 AND (h.ended_at IS NULL OR h.ended_at > $2)
+                 AND h.started_at < $3
+               ORDER BY h.equipment_id, h.started_at"#,
+            equipment_ids,
+            from,
+            to
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Mode intervals of the given equipment overlapping [from, to)
+    pub async fn get_mode_intervals(
+        db: &PgPool,
+        equipment_ids: &[Uuid],
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<ModeIntervalRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ModeIntervalRow,
+            r#"SELECT h.equipment_id, m.mode_description, h.set_at, h.ended_at
+               FROM core.equipment_mode_history h
+               JOIN core.mode m ON m.mode_id = h.mode_id
+               WHERE h.equipment_id = ANY($1)
+                 AND (h.ended_at IS NULL OR h.ended_at > $2)
+                 AND h.set_at < $3
+               ORDER BY h.equipment_id, h.set_at"#,
+            equipment_ids,
+            from,
+            to
+        )
+        .fetch_all(db)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn default_state_group_id(pool: &PgPool) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT state_group_id FROM core.state_group WHERE state_group_name = 'Default MES State Group'"
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_default_and_upserted_classifications(pool: PgPool) -> sqlx::Result<()> {
+        let state_group_id = default_state_group_id(&pool).await?;
+
+        let defaults = OeeQueries::get_classifications(&pool, state_group_id).await?;
+        assert_eq!(defaults.len(), 11);
+        assert_eq!(defaults[1].category, "running");
+
+        let updated =
+            OeeQueries::upsert_classification(&pool, state_group_id, 3, "planned_downtime").await?;
+        assert_eq!(updated.category, "planned_downtime");
+        assert_eq!(updated.classification_id, defaults[3].classification_id);
+
+        // categories are constrained in the database as well
+        assert!(
+            OeeQueries::upsert_classification(&pool, state_group_id, 3, "coffee break")
+                .await
+                .is_err()
+        );
+
+        assert!(OeeQueries::delete_classification(&pool, state_group_id, 3).await?);
+        assert!(!OeeQueries::delete_classification(&pool, state_group_id, 3).await?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use crate::database::equipment::Equipment;
+use crate::database::equipment_types::EquipmentTypeRow;
+use crate::database::mode_groups::ModeGroupRow;
+use crate::database::modes::ModeRow;
+use crate::database::state_groups::StateGroupRow;
+use crate::database::states::StateRow;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Equipment mapped to a mode or state group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GroupMapping {
+    pub equipment_id: Uuid,
+    pub group_id: Uuid,
+}
+
+/// Reads the whole live plant model for an export or an apply. They take a connection so an
+/// apply reads and writes in one transaction.
+pub struct PlantModelQueries;
+
+impl PlantModelQueries {
+    pub async fn equipment_types(
+        db: &mut PgConnection,
+    ) -> Result<Vec<EquipmentTypeRow>, sqlx::Error> {
+        sqlx::query_as!(
+            EquipmentTypeRow,
+            r#"SELECT type_id, type_name, created_at, updated_at
+               FROM core.equipment_type
+               WHERE deleted_at IS NULL
+               ORDER BY type_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn equipment(db: &mut PgConnection) -> Result<Vec<Equipment>, sqlx::Error> {
+        sqlx::query_as!(
+            Equipment,
+            r#"SELECT equipment_id, equipment_name, equipment_type_id,
+                      equipment_parent_id, equipment_enabled, equipment_metadata,
+                      created_at, updated_at
+               FROM core.equipment
+               WHERE deleted_at IS NULL
+               ORDER BY equipment_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn mode_groups(db: &mut PgConnection) -> Result<Vec<ModeGroupRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ModeGroupRow,
+            r#"SELECT mode_group_id, mode_group_name, mode_group_description, created_at, updated_at
+               FROM core.mode_group
+               WHERE deleted_at IS NULL
+               ORDER BY mode_group_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Live modes of live mode groups
+    pub async fn modes(db: &mut PgConnection) -> Result<Vec<ModeRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ModeRow,
+            r#"SELECT m.mode_id, m.mode_group_id, m.mode_description, m.created_at, m.updated_at
+               FROM core.mode m
+               JOIN core.mode_group g ON g.mode_group_id = m.mode_group_id
+               WHERE m.deleted_at IS NULL AND g.deleted_at IS NULL
+               ORDER BY m.created_at, m.mode_description"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn state_groups(db: &mut PgConnection) -> Result<Vec<StateGroupRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateGroupRow,
+            r#"SELECT state_group_id, state_group_name, state_group_description, created_at, updated_at
+               FROM core.state_group
+               WHERE deleted_at IS NULL
+               ORDER BY state_group_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Live states of live state groups
+    pub async fn states(db: &mut PgConnection) -> Result<Vec<StateRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateRow,
+            r#"SELECT s.state_id, s.state_group_id, s.state_code, s.state_description,
+                      s.created_at, s.updated_at
+               FROM core.state s
+               JOIN core.state_group g ON g.state_group_id = s.state_group_id
+               WHERE s.deleted_at IS NULL AND g.deleted_at IS NULL
+               ORDER BY s.state_code"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Mappings between live equipment and live mode groups
+    pub async fn mode_group_mappings(
+        db: &mut PgConnection,
+    ) -> Result<Vec<GroupMapping>, sqlx::Error> {
+        sqlx::query_as!(
+            GroupMapping,
+            r#"SELECT m.equipment_id, m.mode_group_id AS group_id
+               FROM core.equipment_mode_group_mapping m
+               JOIN core.equipment e ON e.equipment_id = m.equipment_id
+               JOIN core.mode_group g ON g.mode_group_id = m.mode_group_id
+               WHERE e.deleted_at IS NULL AND g.deleted_at IS NULL
+               ORDER BY g.mode_group_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Mappings between live equipment and live state groups
+    pub async fn state_group_mappings(
+        db: &mut PgConnection,
+    ) -> Result<Vec<GroupMapping>, sqlx::Error> {
+        sqlx::query_as!(
+            GroupMapping,
+            r#"SELECT m.equipment_id, m.state_group_id AS group_id
+               FROM core.equipment_state_group_mapping m
+               JOIN core.equipment e ON e.equipment_id = m.equipment_id
+               JOIN core.state_group g ON g.state_group_id = m.state_group_id
+               WHERE e.deleted_at IS NULL AND g.deleted_at IS NULL
+               ORDER BY g.state_group_name"#
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Name of a piece of live equipment of the type, None when the type is unused
+    pub async fn equipment_using_type(
+        db: &mut PgConnection,
+        type_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT equipment_name FROM core.equipment
+               WHERE equipment_type_id = $1 AND deleted_at IS NULL
+               ORDER BY equipment_name
+               LIMIT 1"#,
+            type_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Name of a piece of live equipment mapped to the mode group
+    pub async fn equipment_using_mode_group(
+        db: &mut PgConnection,
+        mode_group_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT e.equipment_name
+               FROM core.equipment_mode_group_mapping m
+               JOIN core.equipment e ON e.equipment_id = m.equipment_id
+               WHERE m.mode_group_id = $1 AND e.deleted_at IS NULL
+               ORDER BY e.equipment_name
+               LIMIT 1"#,
+            mode_group_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Name of a piece of live equipment mapped to the state group
+    pub async fn equipment_using_state_group(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT e.equipment_name
+               FROM core.equipment_state_group_mapping m
+               JOIN core.equipment e ON e.equipment_id = m.equipment_id
+               WHERE m.state_group_id = $1 AND e.deleted_at IS NULL
+               ORDER BY e.equipment_name
+               LIMIT 1"#,
+            state_group_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::equipment::EquipmentQueries;
+    use sqlx::PgPool;
+
+    #[sqlx::test]
+    async fn test_mappings_and_usage(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+        let enterprise_id = sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = 'enterprise'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let group_id = sqlx::query_scalar!(
+            "SELECT mode_group_id FROM core.mode_group WHERE mode_group_name = 'Default MES Mode Group'"
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        let equipment =
+            EquipmentQueries::create(&mut conn, "Acme", enterprise_id, None, None, None).await?;
+        sqlx::query!(
+            "INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id) VALUES ($1, $2)",
+            equipment.equipment_id,
+            group_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        let mappings = PlantModelQueries::mode_group_mappings(&mut conn).await?;
+        assert_eq!(
+            mappings,
+            vec![GroupMapping {
+                equipment_id: equipment.equipment_id,
+                group_id
+            }]
+        );
+        assert_eq!(
+            PlantModelQueries::equipment_using_mode_group(&mut conn, group_id).await?,
+            Some("Acme".to_string())
+        );
+        assert_eq!(
+            PlantModelQueries::equipment_using_type(&mut conn, enterprise_id).await?,
+            Some("Acme".to_string())
+        );
+
+        // deleted equipment doesn't count
+        EquipmentQueries::delete(&mut conn, equipment.equipment_id).await?;
+        assert!(
+            PlantModelQueries::mode_group_mappings(&mut conn)
+                .await?
+                .is_empty()
+        );
+        assert_eq!(
+            PlantModelQueries::equipment_using_mode_group(&mut conn, group_id).await?,
+            None
+        );
+
+        Ok(())
+    }
+}
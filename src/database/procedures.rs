@@ -0,0 +1,309 @@
+use crate::error::AppError;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::PgArguments;
+use sqlx::{Arguments, Encode, PgExecutor, Postgres, Type};
+use thiserror::Error;
+use tracing::debug;
+
+/// What the core.* plpgsql functions return: "Status" is 'Success' or 'Error', "Message"
+/// says what happened and "Data" holds the result as json
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ProcedureRow {
+    pub status: String,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl ProcedureRow {
+    pub fn is_success(&self) -> bool {
+        self.status == "Success"
+    }
+}
+
+/// A core.* function that reported 'Error', or a call that failed before it got that far.
+/// The procedures only return a message, the kind of failure is read from its wording.
+#[derive(Debug, Error)]
+pub enum ProcedureError {
+    #[error("{0}")]
+    NotFound(String),
+    /// duplicates and deletes refused because of what still depends on the row
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Invalid(String),
+    /// the procedure caught an unexpected error, the message has the SQL error in it
+    #[error("{function} failed: {message}")]
+    Failed {
+        function: &'static str,
+        message: String,
+    },
+    #[error("Failed to call {function}")]
+    Database {
+        function: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
+    #[error("Failed to read the result of {function}")]
+    Data {
+        function: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+impl ProcedureError {
+    fn from_row(function: &'static str, row: ProcedureRow) -> Self {
+        let message = row.message;
+        if message.starts_with("Unexpected error") {
+            ProcedureError::Failed { function, message }
+        } else if message.contains("not found") {
+            ProcedureError::NotFound(message)
+        } else if message.contains("already") || message.starts_with("Cannot delete") {
+            ProcedureError::Conflict(message)
+        } else {
+            ProcedureError::Invalid(message)
+        }
+    }
+}
+
+impl From<ProcedureError> for AppError {
+    fn from(error: ProcedureError) -> Self {
+        match error {
+            ProcedureError::NotFound(message) => AppError::NotFound(message),
+            ProcedureError::Conflict(message) => AppError::Conflict(message),
+            ProcedureError::Invalid(message) => AppError::Validation(message),
+            ProcedureError::Failed { function, message } => AppError::database(
+                format!("Failed to call {}", function),
+                sqlx::Error::Protocol(message),
+            ),
+            ProcedureError::Database { function, source } => {
+                AppError::database(format!("Failed to call {}", function), source)
+            }
+            ProcedureError::Data { function, source } => AppError::database(
+                format!("Failed to read the result of {}", function),
+                sqlx::Error::Decode(Box::new(source)),
+            ),
+        }
+    }
+}
+
+/// For the queries that return plain sqlx errors, where anything but a failed call is a bug
+impl From<ProcedureError> for sqlx::Error {
+    fn from(error: ProcedureError) -> Self {
+        match error {
+            ProcedureError::Database { source, .. } => source,
+            ProcedureError::Data { source, .. } => sqlx::Error::Decode(Box::new(source)),
+            error => sqlx::Error::Protocol(error.to_string()),
+        }
+    }
+}
+
+/// Call to a core.* function that follows the Status/Message/Data convention
+///
+/// ```ignore
+/// let group: ModeGroupRow = Procedure::new("core.getModeGroupById")
+///     .bind(mode_group_id)
+///     .fetch(&pool)
+///     .await?;
+/// ```
+pub struct Procedure {
+    function: &'static str,
+    arguments: PgArguments,
+    // the first argument that failed to encode, reported when the call is made
+    error: Option<BoxDynError>,
+}
+
+impl Procedure {
+    pub fn new(function: &'static str) -> Self {
+        Self {
+            function,
+            arguments: PgArguments::default(),
+            error: None,
+        }
+    }
+
+    /// Adds the next argument, arguments left off take the function's defaults
+    pub fn bind<'q, T>(mut self, value: T) -> Self
+    where
+        T: Encode<'q, Postgres> + Type<Postgres> + 'q,
+    {
+        if self.error.is_none()
+            && let Err(e) = self.arguments.add(value)
+        {
+            self.error = Some(e);
+        }
+        self
+    }
+
+    /// Runs the function and returns its row as is, 'Error' included
+    pub async fn call<'e, E>(self, db: E) -> Result<ProcedureRow, ProcedureError>
+    where
+        E: PgExecutor<'e>,
+    {
+        let function = self.function;
+        if let Some(e) = self.error {
+            return Err(ProcedureError::Database {
+                function,
+                source: sqlx::Error::Encode(e),
+            });
+        }
+
+        let placeholders = (1..=self.arguments.len())
+            .map(|i| format!("${}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            r#"SELECT "Status" AS status, "Message" AS message, "Data" AS data FROM {}({})"#,
+            function, placeholders
+        );
+
+        debug!("Calling {}", function);
+        sqlx::query_as_with::<_, ProcedureRow, _>(&sql, self.arguments)
+            .fetch_one(db)
+            .await
+            .map_err(|source| ProcedureError::Database { function, source })
+    }
+
+    /// Runs the function, an 'Error' status becomes a `ProcedureError`
+    pub async fn execute<'e, E>(self, db: E) -> Result<ProcedureRow, ProcedureError>
+    where
+        E: PgExecutor<'e>,
+    {
+        let function = self.function;
+        let row = self.call(db).await?;
+        if !row.is_success() {
+            debug!("{} returned an error: {}", function, row.message);
+            return Err(ProcedureError::from_row(function, row));
+        }
+        Ok(row)
+    }
+
+    /// Runs the function and reads "Data" into `T`
+    pub async fn fetch<'e, T, E>(self, db: E) -> Result<T, ProcedureError>
+    where
+        T: DeserializeOwned,
+        E: PgExecutor<'e>,
+    {
+        let function = self.function;
+        let row = self.execute(db).await?;
+        serde_json::from_value(row.data.unwrap_or_default())
+            .map_err(|source| ProcedureError::Data { function, source })
+    }
+
+    /// Like `fetch`, but None when the function reports that something was not found
+    #[cfg_attr(not(feature = "stored-procedures"), allow(dead_code))]
+    pub async fn fetch_optional<'e, T, E>(self, db: E) -> Result<Option<T>, ProcedureError>
+    where
+        T: DeserializeOwned,
+        E: PgExecutor<'e>,
+    {
+        match self.fetch(db).await {
+            Ok(data) => Ok(Some(data)),
+            Err(ProcedureError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    #[derive(Debug, Deserialize)]
+    struct GroupData {
+        mode_group_id: Uuid,
+        mode_group_name: String,
+        mode_count: i64,
+    }
+
+    #[sqlx::test]
+    async fn test_fetch_maps_data(pool: PgPool) -> sqlx::Result<()> {
+        let group: GroupData = Procedure::new("core.insertModeGroup")
+            .bind("  Packaging  ")
+            .bind("Packaging modes")
+            .fetch(&pool)
+            .await?;
+
+        assert_eq!(group.mode_group_name, "Packaging");
+        assert_eq!(group.mode_count, 0);
+
+        let name = sqlx::query_scalar!(
+            "SELECT mode_group_name FROM core.mode_group WHERE mode_group_id = $1",
+            group.mode_group_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(name, "Packaging");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_error_status_is_typed(pool: PgPool) -> sqlx::Result<()> {
+        Procedure::new("core.insertModeGroup")
+            .bind("Packaging")
+            .bind("Packaging modes")
+            .execute(&pool)
+            .await?;
+
+        let result = Procedure::new("core.insertModeGroup")
+            .bind("Packaging")
+            .bind("Packaging modes")
+            .execute(&pool)
+            .await;
+        assert!(matches!(result, Err(ProcedureError::Conflict(_))));
+
+        let result = Procedure::new("core.insertModeGroup")
+            .bind("P")
+            .bind("Packaging modes")
+            .execute(&pool)
+            .await;
+        let Err(ProcedureError::Invalid(message)) = result else {
+            panic!("expected a validation error, got {:?}", result);
+        };
+        assert_eq!(
+            message,
+            "Mode group name must be between 2 and 255 characters"
+        );
+
+        let err: AppError = Procedure::new("core.getModeGroupById")
+            .bind(Uuid::new_v4())
+            .execute(&pool)
+            .await
+            .unwrap_err()
+            .into();
+        assert_eq!(err.code(), "not_found");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_fetch_optional_and_defaults(pool: PgPool) -> sqlx::Result<()> {
+        let missing: Option<GroupData> = Procedure::new("core.getModeGroupById")
+            .bind(Uuid::new_v4())
+            .fetch_optional(&pool)
+            .await?;
+        assert!(missing.is_none());
+
+        let group: GroupData = Procedure::new("core.insertModeGroup")
+            .bind("Packaging")
+            .bind("Packaging modes")
+            .fetch(&pool)
+            .await?;
+
+        // updateModeGroup defaults the description, a NULL name leaves the name as is
+        let row = Procedure::new("core.updateModeGroup")
+            .bind(group.mode_group_id)
+            .bind(None::<&str>)
+            .execute(&pool)
+            .await?;
+        assert_eq!(row.message, "No changes were made to mode group");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+use crate::models::operations::{CountMode, CountValues, counter_delta};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ProductionCountRow {
+    pub count_id: Uuid,
+    pub equipment_id: Uuid,
+    pub job_id: Option<Uuid>,
+    pub recorded_at: OffsetDateTime,
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+    pub good_counter: Option<i64>,
+    pub scrap_counter: Option<i64>,
+    pub rework_counter: Option<i64>,
+    pub rollover: bool,
+    pub recorded_by: Option<String>,
+}
+
+/// Summed quantities of one time bucket
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ProductionBucketRow {
+    pub bucket_start: OffsetDateTime,
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+}
+
+/// Summed quantities of one equipment
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ProductionTotalsRow {
+    pub equipment_id: Uuid,
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewProductionCount<'a> {
+    pub equipment_id: Uuid,
+    pub job_id: Option<Uuid>,
+    /// now() when None
+    pub recorded_at: Option<OffsetDateTime>,
+    pub mode: CountMode,
+    pub values: CountValues,
+    pub counter_max: Option<i64>,
+    pub recorded_by: Option<&'a str>,
+}
+
+pub struct ProductionCountQueries;
+
+impl ProductionCountQueries {
+    /// Record a count report. Absolute readings are turned into deltas against the previous
+    /// reading of each counter. Returns None when an absolute reading is older than the
+    /// latest counter reading of the equipment.
+    pub async fn record(
+        db: &PgPool,
+        count: &NewProductionCount<'_>,
+    ) -> Result<Option<ProductionCountRow>, sqlx::Error> {
+        let mut tx = db.begin().await?;
+
+        // serialize reports for the same equipment so counter readings stay in order
+        let recorded_at = sqlx::query_scalar!(
+            r#"SELECT COALESCE($2::timestamptz, now()) as "recorded_at!"
+               FROM core.equipment WHERE equipment_id = $1 AND deleted_at IS NULL FOR UPDATE"#,
+            count.equipment_id,
+            count.recorded_at
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (quantities, counters, rollover) = match count.mode {
+            CountMode::Delta => (count.values, CountValues::default(), false),
+            CountMode::Absolute => {
+                let previous = sqlx::query!(
+                    r#"SELECT
+                         (SELECT max(recorded_at) FROM operations.production_count
+                          WHERE equipment_id = $1
+                            AND (good_counter IS NOT NULL OR scrap_counter IS NOT NULL
+                                 OR rework_counter IS NOT NULL)) as last_reading_at,
+                         (SELECT good_counter FROM operations.production_count
+                          WHERE equipment_id = $1 AND good_counter IS NOT NULL
+                          ORDER BY recorded_at DESC, created_at DESC LIMIT 1) as good_counter,
+                         (SELECT scrap_counter FROM operations.production_count
+                          WHERE equipment_id = $1 AND scrap_counter IS NOT NULL
+                          ORDER BY recorded_at DESC, created_at DESC LIMIT 1) as scrap_counter,
+                         (SELECT rework_counter FROM operations.production_count
+                          WHERE equipment_id = $1 AND rework_counter IS NOT NULL
+                          ORDER BY recorded_at DESC, created_at DESC LIMIT 1) as rework_counter"#,
+                    count.equipment_id
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+
+                if previous
+                    .last_reading_at
+                    .is_some_and(|last| recorded_at < last)
+                {
+                    return Ok(None);
+                }
+
+                let mut rollover = false;
+                let mut delta = |previous: Option<i64>, current: Option<i64>| {
+                    current.map(|current| {
+                        let (delta, rolled) = counter_delta(previous, current, count.counter_max);
+                        rollover |= rolled;
+                        delta
+                    })
+                };
+                let quantities = CountValues {
+                    good: delta(previous.good_counter, count.values.good),
+                    scrap: delta(previous.scrap_counter, count.values.scrap),
+                    rework: delta(previous.rework_counter, count.values.rework),
+                };
+                (quantities, count.values, rollover)
+            }
+        };
+
+        let row = sqlx::query_as!(
+            ProductionCountRow,
+            r#"INSERT INTO operations.production_count
+                   (equipment_id, job_id, recorded_at, good_quantity, scrap_quantity,
+                    rework_quantity, good_counter, scrap_counter, rework_counter, rollover,
+                    recorded_by)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+               RETURNING count_id, equipment_id, job_id, recorded_at, good_quantity,
+                         scrap_quantity, rework_quantity, good_counter, scrap_counter,
+                         rework_counter, rollover, recorded_by"#,
+            count.equipment_id,
+            count.job_id,
+            recorded_at,
+            quantities.good.unwrap_or(0),
+            quantities.scrap.unwrap_or(0),
+            quantities.rework.unwrap_or(0),
+            counters.good,
+            counters.scrap,
+            counters.rework,
+            rollover,
+            count.recorded_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(row))
+    }
+
+    /// Quantities of the equipment summed per `bucket` ('minute', 'hour', 'day', 'week' or
+    /// 'month', truncated in utc) over [from, to)
+    pub async fn get_buckets(
+        db: &PgPool,
+        equipment_ids: &[Uuid],
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+        bucket: &str,
+        job_id: Option<Uuid>,
+    ) -> Result<Vec<ProductionBucketRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductionBucketRow,
+            r#"SELECT date_trunc($4, recorded_at, 'UTC') as "bucket_start!",
+                      sum(good_quantity)::bigint as "good_quantity!",
+                      sum(scrap_quantity)::bigint as "scrap_quantity!",
+                      sum(rework_quantity)::bigint as "rework_quantity!"
+               FROM operations.production_count
+               WHERE equipment_id = ANY($1)
+                 AND recorded_at >= $2 AND recorded_at < $3
+                 AND ($5::uuid IS NULL OR job_id = $5)
+               GROUP BY 1
+               ORDER BY 1"#,
+            equipment_ids,
+            from,
+            to,
+            bucket,
+            job_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    /// Quantities summed per equipment over [from, to)
+    pub async fn get_totals_by_equipment(
+        db: &PgPool,
+        equipment_ids: &[Uuid],
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<ProductionTotalsRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductionTotalsRow,
+            r#"SELECT equipment_id,
+                      sum(good_quantity)::bigint as "good_quantity!",
+                      sum(scrap_quantity)::bigint as "scrap_quantity!",
+                      sum(rework_quantity)::bigint as "rework_quantity!"
+               FROM operations.production_count
+               WHERE equipment_id = ANY($1)
+                 AND recorded_at >= $2 AND recorded_at < $3
+               GROUP BY equipment_id"#,
+            equipment_ids,
+            from,
+            to
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn job_has_counts(db: &PgPool, job_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.production_count WHERE job_id = $1
+            )"#,
+            job_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    async fn create_equipment(pool: &PgPool) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    fn reading(equipment_id: Uuid, at: OffsetDateTime, good: i64) -> NewProductionCount<'static> {
+        NewProductionCount {
+            equipment_id,
+            job_id: None,
+            recorded_at: Some(at),
+            mode: CountMode::Absolute,
+            values: CountValues {
+                good: Some(good),
+                ..Default::default()
+            },
+            counter_max: Some(999),
+            recorded_by: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_record_absolute_readings(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool).await?;
+        let t0 = (OffsetDateTime::now_utc() - Duration::hours(1))
+            .replace_nanosecond(0)
+            .unwrap();
+
+        let first = ProductionCountQueries::record(&pool, &reading(equipment_id, t0, 990))
+            .await?
+            .expect("first reading should be recorded");
+        assert_eq!(first.good_quantity, 0);
+        assert_eq!(first.good_counter, Some(990));
+
+        let wrapped = ProductionCountQueries::record(
+            &pool,
+            &reading(equipment_id, t0 + Duration::minutes(1), 5),
+        )
+        .await?
+        .expect("second reading should be recorded");
+        assert_eq!(wrapped.good_quantity, 15);
+        assert!(wrapped.rollover);
+
+        // readings older than the latest one are rejected
+        let stale = ProductionCountQueries::record(&pool, &reading(equipment_id, t0, 10)).await?;
+        assert!(stale.is_none());
+
+        let delta = NewProductionCount {
+            mode: CountMode::Delta,
+            values: CountValues {
+                good: Some(3),
+                scrap: Some(1),
+                rework: None,
+            },
+            ..reading(equipment_id, t0 + Duration::minutes(2), 0)
+        };
+        ProductionCountQueries::record(&pool, &delta).await?;
+
+        let totals = ProductionCountQueries::get_totals_by_equipment(
+            &pool,
+            &[equipment_id],
+            t0,
+            t0 + Duration::hours(1),
+        )
+        .await?;
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].good_quantity, 18);
+        assert_eq!(totals[0].scrap_quantity, 1);
+
+        let buckets = ProductionCountQueries::get_buckets(
+            &pool,
+            &[equipment_id],
+            t0,
+            t0 + Duration::hours(1),
+            "minute",
+            None,
+        )
+        .await?;
+        assert_eq!(buckets.len(), 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_record_counter_reset(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool).await?;
+        let t0 = (OffsetDateTime::now_utc() - Duration::hours(1))
+            .replace_nanosecond(0)
+            .unwrap();
+
+        ProductionCountQueries::record(&pool, &reading(equipment_id, t0, 500)).await?;
+
+        // the plc reset its counter on a job change, far below the max of 999
+        let reset = ProductionCountQueries::record(
+            &pool,
+            &reading(equipment_id, t0 + Duration::minutes(1), 0),
+        )
+        .await?
+        .expect("reset reading should be recorded");
+        assert_eq!(reset.good_quantity, 0);
+        assert!(reset.rollover);
+
+        let next = ProductionCountQueries::record(
+            &pool,
+            &reading(equipment_id, t0 + Duration::minutes(2), 7),
+        )
+        .await?
+        .expect("reading after the reset should be recorded");
+        assert_eq!(next.good_quantity, 7);
+        assert!(!next.rollover);
+
+        Ok(())
+    }
+}
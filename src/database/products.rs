@@ -0,0 +1,195 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ProductRow {
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub product_description: Option<String>,
+    pub product_metadata: Value,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+pub struct ProductQueries;
+
+impl ProductQueries {
+    pub async fn get_by_id(
+        db: &PgPool,
+        product_id: Uuid,
+    ) -> Result<Option<ProductRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductRow,
+            r#"SELECT product_id, product_name, product_description, product_metadata,
+                      created_at, updated_at
+               FROM operations.product
+               WHERE product_id = $1"#,
+            product_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn create(
+        db: &PgPool,
+        product_name: &str,
+        product_description: Option<&str>,
+        product_metadata: Option<&Value>,
+    ) -> Result<ProductRow, sqlx::Error> {
+        let default_metadata = serde_json::json!({});
+        let metadata = product_metadata.unwrap_or(&default_metadata);
+
+        sqlx::query_as!(
+            ProductRow,
+            r#"INSERT INTO operations.product (product_name, product_description, product_metadata)
+               VALUES ($1, $2, $3)
+               RETURNING product_id, product_name, product_description, product_metadata,
+                         created_at, updated_at"#,
+            product_name,
+            product_description,
+            metadata
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn update(
+        db: &PgPool,
+        product_id: Uuid,
+        product_name: &str,
+        product_description: Option<&str>,
+    ) -> Result<Option<ProductRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductRow,
+            r#"UPDATE operations.product
+               SET product_name = $2, product_description = $3
+               WHERE product_id = $1
+               RETURNING product_id, product_name, product_description, product_metadata,
+                         created_at, updated_at"#,
+            product_id,
+            product_name,
+            product_description
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn update_metadata(
+        db: &PgPool,
+        product_id: Uuid,
+        metadata: &Value,
+    ) -> Result<Option<ProductRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ProductRow,
+            r#"UPDATE operations.product
+               SET product_metadata = $2
+               WHERE product_id = $1
+               RETURNING product_id, product_name, product_description, product_metadata,
+                         created_at, updated_at"#,
+            product_id,
+            metadata
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn delete(db: &PgPool, product_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM operations.product WHERE product_id = $1",
+            product_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn exists(db: &PgPool, product_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.product WHERE product_id = $1
+            )"#,
+            product_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Check for a product with the same (case insensitive) name, optionally ignoring one product
+    pub async fn name_exists(
+        db: &PgPool,
+        product_name: &str,
+        exclude_product_id: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.product
+                WHERE product_name = $1
+                  AND ($2::uuid IS NULL OR product_id <> $2)
+            )"#,
+            product_name,
+            exclude_product_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Check if any work order references the product
+    pub async fn is_in_use(db: &PgPool, product_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.work_order WHERE product_id = $1
+            )"#,
+            product_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[sqlx::test]
+    async fn test_product_crud(pool: PgPool) -> sqlx::Result<()> {
+        let created = ProductQueries::create(&pool, "Widget", Some("A widget"), None).await?;
+        assert_eq!(created.product_metadata, json!({}));
+
+        let found = ProductQueries::get_by_id(&pool, created.product_id)
+            .await?
+            .expect("product should exist");
+        assert_eq!(found.product_description.as_deref(), Some("A widget"));
+
+        // names are case insensitive
+        assert!(ProductQueries::name_exists(&pool, "WIDGET", None).await?);
+        assert!(!ProductQueries::name_exists(&pool, "widget", Some(created.product_id)).await?);
+
+        let updated = ProductQueries::update(&pool, created.product_id, "Gadget", None)
+            .await?
+            .expect("product should exist");
+        assert_eq!(updated.product_name, "Gadget");
+        assert!(updated.product_description.is_none());
+
+        let updated =
+            ProductQueries::update_metadata(&pool, created.product_id, &json!({"sku": "G-1"}))
+                .await?
+                .expect("product should exist");
+        assert_eq!(updated.product_metadata["sku"], "G-1");
+
+        assert!(!ProductQueries::is_in_use(&pool, created.product_id).await?);
+        assert!(ProductQueries::delete(&pool, created.product_id).await?);
+        assert!(!ProductQueries::exists(&pool, created.product_id).await?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use sqlx::PgConnection;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PurgedRow {
+    pub entity_type: String,
+    pub purged: i64,
+}
+
+pub struct PurgeQueries;
+
+impl PurgeQueries {
+    /// Removes rows soft deleted more than `retention_days` ago for good, see
+    /// core.purge_deleted (017). Returns the number of purged rows per entity type.
+    pub async fn purge_deleted(
+        db: &mut PgConnection,
+        retention_days: i32,
+    ) -> Result<Vec<PurgedRow>, sqlx::Error> {
+        sqlx::query_as!(
+            PurgedRow,
+            r#"SELECT entity_type as "entity_type!", purged as "purged!"
+               FROM core.purge_deleted(make_interval(days => $1))"#,
+            retention_days
+        )
+        .fetch_all(db)
+        .await
+    }
+}
@@ -0,0 +1,45 @@
+use sqlx::PgPool;
+
+/// Demo plant model, see /seeds for what it contains
+const DEMO_SEED: &str = include_str!("../../seeds/demo.sql");
+
+pub struct SeedQueries;
+
+impl SeedQueries {
+    /// Load the demo data in one transaction, rows that already exist are left alone
+    pub async fn seed_demo(db: &PgPool) -> Result<(), sqlx::Error> {
+        let mut tx = db.begin().await?;
+        sqlx::raw_sql(DEMO_SEED).execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_seed_demo_can_run_twice(pool: PgPool) -> sqlx::Result<()> {
+        SeedQueries::seed_demo(&pool).await?;
+        SeedQueries::seed_demo(&pool).await?;
+
+        let cells = sqlx::query_scalar!(
+            r#"SELECT e.equipment_name FROM core.equipment e
+               JOIN core.equipment p ON p.equipment_id = e.equipment_parent_id
+               WHERE p.equipment_name = 'Line 1'
+               ORDER BY e.equipment_name"#
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(cells, vec!["Capper", "Filler", "Labeler"]);
+
+        let total: i64 = sqlx::query_scalar!(r#"SELECT count(*) AS "count!" FROM core.equipment"#)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(total, 7);
+
+        Ok(())
+    }
+}
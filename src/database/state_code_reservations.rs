@@ -0,0 +1,166 @@
+use sqlx::{PgConnection, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StateCodeReservationRow {
+    pub reservation_id: Uuid,
+    pub state_group_id: Uuid,
+    pub min_code: i32,
+    pub max_code: i32,
+    pub owner: String,
+    pub label: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+pub struct StateCodeReservationQueries;
+
+impl StateCodeReservationQueries {
+    pub async fn get_by_state_group(
+        db: &PgPool,
+        state_group_id: Uuid,
+    ) -> Result<Vec<StateCodeReservationRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateCodeReservationRow,
+            r#"SELECT reservation_id, state_group_id, min_code, max_code, owner, label,
+                      created_at, updated_at
+               FROM core.state_code_reservation
+               WHERE state_group_id = $1
+               ORDER BY min_code"#,
+            state_group_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
+    pub async fn get_by_id(
+        db: &PgPool,
+        reservation_id: Uuid,
+    ) -> Result<Option<StateCodeReservationRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateCodeReservationRow,
+            r#"SELECT reservation_id, state_group_id, min_code, max_code, owner, label,
+                      created_at, updated_at
+               FROM core.state_code_reservation
+               WHERE reservation_id = $1"#,
+            reservation_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// The reservation in the group that shares a code with `min_code..=max_code`, there is
+    /// at most one since reservations can't overlap
+    pub async fn find_overlapping(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        min_code: i32,
+        max_code: i32,
+    ) -> Result<Option<StateCodeReservationRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateCodeReservationRow,
+            r#"SELECT reservation_id, state_group_id, min_code, max_code, owner, label,
+                      created_at, updated_at
+               FROM core.state_code_reservation
+               WHERE state_group_id = $1 AND min_code <= $3 AND max_code >= $2
+               ORDER BY min_code
+               LIMIT 1"#,
+            state_group_id,
+            min_code,
+            max_code
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn create(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        min_code: i32,
+        max_code: i32,
+        owner: &str,
+        label: Option<&str>,
+    ) -> Result<Uuid, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"INSERT INTO core.state_code_reservation (state_group_id, min_code, max_code, owner, label)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING reservation_id"#,
+            state_group_id,
+            min_code,
+            max_code,
+            owner,
+            label
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn delete(db: &mut PgConnection, reservation_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM core.state_code_reservation WHERE reservation_id = $1",
+            reservation_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn default_state_group_id(pool: &PgPool) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT state_group_id FROM core.state_group WHERE state_group_name = 'Default MES State Group'"
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_create_and_find_overlapping(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
+        let group_id = default_state_group_id(&pool).await?;
+
+        let reservation_id = StateCodeReservationQueries::create(
+            &mut conn,
+            group_id,
+            100,
+            199,
+            "Rockwell",
+            Some("AB PLCs on line 3"),
+        )
+        .await?;
+
+        let found = StateCodeReservationQueries::find_overlapping(&mut conn, group_id, 150, 150)
+            .await?
+            .expect("code 150 should be reserved");
+        assert_eq!(found.reservation_id, reservation_id);
+        assert_eq!(found.owner, "Rockwell");
+        assert!(
+            StateCodeReservationQueries::find_overlapping(&mut conn, group_id, 200, 299)
+                .await?
+                .is_none()
+        );
+
+        // ranges that share a code are refused by the exclusion constraint, touching is fine
+        assert!(
+            StateCodeReservationQueries::create(&mut conn, group_id, 199, 250, "Siemens", None)
+                .await
+                .is_err()
+        );
+        StateCodeReservationQueries::create(&mut conn, group_id, 200, 250, "Siemens", None).await?;
+
+        let reservations = StateCodeReservationQueries::get_by_state_group(&pool, group_id).await?;
+        assert_eq!(reservations.len(), 2);
+        assert_eq!(reservations[1].owner, "Siemens");
+
+        assert!(StateCodeReservationQueries::delete(&mut conn, reservation_id).await?);
+        assert!(!StateCodeReservationQueries::delete(&mut conn, reservation_id).await?);
+
+        Ok(())
+    }
+}
@@ -1,21 +1,40 @@
-use anyhow::{Context, anyhow};
-use sqlx::PgPool;
+use crate::database::procedures::{Procedure, ProcedureError, ProcedureRow};
+use crate::error::AppResult;
+#[cfg(not(feature = "stored-procedures"))]
+use crate::error::{AppError, DatabaseContext};
+use crate::models::core::{
+    AvailableStateCodes, BulkGroupAssignment, GroupEquipment, StateGroupUsageStats,
+};
+use sqlx::{PgConnection, PgPool};
 use time::OffsetDateTime;
-use tracing::{debug, error, instrument};
+#[cfg(not(feature = "stored-procedures"))]
+use tracing::error;
+use tracing::{debug, instrument};
 use uuid::Uuid;
 
+#[cfg(not(feature = "stored-procedures"))]
 const MAX_NAME_LEN: usize = 255;
+#[cfg(not(feature = "stored-procedures"))]
 const MAX_DESC_LEN: usize = 2048;
 
-#[derive(Debug, Clone, sqlx::FromRow)]
+/// Also read from the "Data" of the state group procedures
+#[derive(Debug, Clone, sqlx::FromRow, serde::Deserialize)]
 pub struct StateGroupRow {
     pub state_group_id: Uuid,
     pub state_group_name: String,
     pub state_group_description: String,
+    #[serde(default, with = "time::serde::rfc3339::option")]
     pub created_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
     pub updated_at: Option<OffsetDateTime>,
 }
 
+/// "Data" of core.getEquipmentForStateGroup
+#[derive(serde::Deserialize)]
+struct GroupEquipmentData {
+    equipment: Vec<GroupEquipment>,
+}
+
 pub struct StateGroupQueries;
 
 impl StateGroupQueries {
@@ -25,12 +44,14 @@ impl StateGroupQueries {
             r#"SELECT state_group_id, state_group_name, 
                 state_group_description, created_at, updated_at
 	            FROM core.state_group
+                WHERE deleted_at IS NULL
                 ORDER BY state_group_name"#
         )
         .fetch_all(db)
         .await
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     pub async fn get_by_state_group_id(
         db: &PgPool,
         state_group_id: Uuid,
@@ -39,13 +60,25 @@ impl StateGroupQueries {
             StateGroupRow,
             r#"SELECT state_group_id, state_group_name, state_group_description, created_at, updated_at
 	            FROM core.state_group
-                WHERE state_group_id = $1"#,
+                WHERE state_group_id = $1 AND deleted_at IS NULL"#,
             state_group_id
         )
         .fetch_optional(db)
         .await
     }
 
+    #[cfg(feature = "stored-procedures")]
+    pub async fn get_by_state_group_id(
+        db: &PgPool,
+        state_group_id: Uuid,
+    ) -> Result<Option<StateGroupRow>, sqlx::Error> {
+        Ok(Procedure::new("core.getStateGroupById")
+            .bind(state_group_id)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     pub async fn get_by_state_group_name(
         db: &PgPool,
         state_group_name: &str,
@@ -54,13 +87,24 @@ impl StateGroupQueries {
             StateGroupRow,
             r#"SELECT state_group_id, state_group_name, state_group_description, created_at, updated_at
 	            FROM core.state_group
-                WHERE state_group_name = $1"#,
+                WHERE state_group_name = $1 AND deleted_at IS NULL"#,
             state_group_name
         )
         .fetch_optional(db)
         .await
     }
 
+    #[cfg(feature = "stored-procedures")]
+    pub async fn get_by_state_group_name(
+        db: &PgPool,
+        state_group_name: &str,
+    ) -> Result<Option<StateGroupRow>, sqlx::Error> {
+        Ok(Procedure::new("core.getStateGroupByName")
+            .bind(state_group_name)
+            .fetch_optional(db)
+            .await?)
+    }
+
     pub async fn get_by_state_group_description(
         db: &PgPool,
         state_group_description: &str,
@@ -69,7 +113,7 @@ impl StateGroupQueries {
             StateGroupRow,
             r#"SELECT state_group_id, state_group_name, state_group_description, created_at, updated_at
 	            FROM core.state_group
-                WHERE state_group_description = $1"#,
+                WHERE state_group_description = $1 AND deleted_at IS NULL"#,
             state_group_description
         )
         .fetch_optional(db)
@@ -77,73 +121,85 @@ impl StateGroupQueries {
     }
 
     /// validates input strings for state group operations
-    fn validate_input(name: &str, description: &str) -> anyhow::Result<(String, String)> {
+    #[cfg(not(feature = "stored-procedures"))]
+    fn validate_input(name: &str, description: &str) -> AppResult<(String, String)> {
         let name = name.trim().to_string();
         let desc = description.trim().to_string();
 
         if name.is_empty() {
-            return Err(anyhow!("state_group_name cannot be empty"));
+            return Err(AppError::Validation(
+                "state_group_name cannot be empty".to_string(),
+            ));
         }
 
         if desc.is_empty() {
-            return Err(anyhow!("state_group_description cannot be empty"));
+            return Err(AppError::Validation(
+                "state_group_description cannot be empty".to_string(),
+            ));
         }
 
         if name.len() > MAX_NAME_LEN {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "state_group_name exceeds max length of {} characters",
                 MAX_NAME_LEN
-            ));
+            )));
         }
 
         if desc.len() > MAX_DESC_LEN {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "state_group_description exceeds max length of {} characters",
                 MAX_DESC_LEN
-            ));
+            )));
         }
 
         Ok((name, desc))
     }
 
     /// validates a single field name or description
-    fn validate_field(field_name: &str, value: &str, max_len: usize) -> anyhow::Result<String> {
+    #[cfg(not(feature = "stored-procedures"))]
+    fn validate_field(field_name: &str, value: &str, max_len: usize) -> AppResult<String> {
         let trimmed = value.trim().to_string();
 
         if trimmed.is_empty() {
-            return Err(anyhow!("{} cannot be empty", field_name));
+            return Err(AppError::Validation(format!(
+                "{} cannot be empty",
+                field_name
+            )));
         }
 
         if trimmed.len() > max_len {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "{} exceeds max length of {} characters",
-                field_name,
-                max_len
-            ));
+                field_name, max_len
+            )));
         }
 
         Ok(trimmed)
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(name = %state_group_name))]
     pub async fn create_state_group(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_group_name: &str,
         state_group_description: &str,
-    ) -> anyhow::Result<StateGroupRow> {
+    ) -> AppResult<StateGroupRow> {
         let (name, desc) = Self::validate_input(state_group_name, state_group_description)?;
 
         // check for duplicate name
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.state_group WHERE state_group_name = $1",
+            "SELECT 1 FROM core.state_group WHERE state_group_name = $1 AND deleted_at IS NULL",
             name
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate state_group_name")?
         {
             error!("Rejected: duplicate state_group_name '{}'", name);
-            return Err(anyhow!("state_group_name '{}' already exists", name));
+            return Err(AppError::Conflict(format!(
+                "state_group_name '{}' already exists",
+                name
+            )));
         }
 
         debug!("Inserting state group '{}'", name);
@@ -157,7 +213,7 @@ impl StateGroupQueries {
             name,
             desc
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .with_context(|| format!("Failed to insert state group with name '{}'", name))?;
 
@@ -165,49 +221,87 @@ impl StateGroupQueries {
         Ok(result)
     }
 
+    /// core.insertStateGroup (019) validates the input and checks for duplicates
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(name = %state_group_name))]
+    pub async fn create_state_group(
+        db: &mut PgConnection,
+        state_group_name: &str,
+        state_group_description: &str,
+    ) -> AppResult<StateGroupRow> {
+        let result: StateGroupRow = Procedure::new("core.insertStateGroup")
+            .bind(state_group_name)
+            .bind(state_group_description)
+            .fetch(db)
+            .await?;
+
+        debug!(?result, "Successfully inserted state group");
+        Ok(result)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(id = %state_group_id, name = %state_group_name))]
     pub async fn update_state_group_name(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_group_id: Uuid,
         state_group_name: &str,
-    ) -> anyhow::Result<Option<StateGroupRow>> {
+    ) -> AppResult<Option<StateGroupRow>> {
         let name = Self::validate_field("state_group_name", state_group_name, MAX_NAME_LEN)?;
 
         // check for duplicate name (excluding current record)
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.state_group WHERE state_group_name = $1 AND state_group_id != $2",
+            "SELECT 1 FROM core.state_group WHERE state_group_name = $1 AND state_group_id != $2 AND deleted_at IS NULL",
             name,
             state_group_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate state_group_name")?
         {
-            return Err(anyhow!("state_group_name '{}' already exists", name));
+            return Err(AppError::Conflict(format!(
+                "state_group_name '{}' already exists",
+                name
+            )));
         }
 
         let result = sqlx::query_as!(
             StateGroupRow,
             r#"UPDATE core.state_group 
                SET state_group_name = $2, updated_at = NOW()
-               WHERE state_group_id = $1
+               WHERE state_group_id = $1 AND deleted_at IS NULL
                RETURNING state_group_id, state_group_name, state_group_description, created_at, updated_at"#,
             state_group_id,
             name
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update state group name for id {}", state_group_id))?;
 
         Ok(result)
     }
 
+    /// core.updateStateGroup (019), the description is left as is
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(id = %state_group_id, name = %state_group_name))]
+    pub async fn update_state_group_name(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        state_group_name: &str,
+    ) -> AppResult<Option<StateGroupRow>> {
+        Ok(Procedure::new("core.updateStateGroup")
+            .bind(state_group_id)
+            .bind(state_group_name)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(id = %state_group_id))]
     pub async fn update_state_group_description(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_group_id: Uuid,
         state_group_description: &str,
-    ) -> anyhow::Result<Option<StateGroupRow>> {
+    ) -> AppResult<Option<StateGroupRow>> {
         let desc = Self::validate_field(
             "state_group_description",
             state_group_description,
@@ -218,37 +312,201 @@ impl StateGroupQueries {
             StateGroupRow,
             r#"UPDATE core.state_group 
                SET state_group_description = $2, updated_at = NOW()
-               WHERE state_group_id = $1
+               WHERE state_group_id = $1 AND deleted_at IS NULL
                RETURNING state_group_id, state_group_name, state_group_description, created_at, updated_at"#,
             state_group_id,
             desc
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update state group description for id {}", state_group_id))?;
 
         Ok(result)
     }
 
+    /// core.updateStateGroup (019), a NULL name leaves the name as is
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(id = %state_group_id))]
+    pub async fn update_state_group_description(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        state_group_description: &str,
+    ) -> AppResult<Option<StateGroupRow>> {
+        Ok(Procedure::new("core.updateStateGroup")
+            .bind(state_group_id)
+            .bind(None::<&str>)
+            .bind(state_group_description)
+            .fetch_optional(db)
+            .await?)
+    }
+
+    /// Soft delete, the row is kept until it is purged and can be restored until then
+    #[cfg(not(feature = "stored-procedures"))]
     #[instrument(skip(db), fields(id = %state_group_id))]
     pub async fn delete_state_group(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_group_id: Uuid,
-    ) -> Result<bool, sqlx::Error> {
+    ) -> AppResult<bool> {
         let result = sqlx::query!(
-            "DELETE FROM core.state_group WHERE state_group_id = $1",
+            "UPDATE core.state_group SET deleted_at = NOW() WHERE state_group_id = $1 AND deleted_at IS NULL",
             state_group_id
         )
-        .execute(db)
-        .await?;
+        .execute(&mut *db)
+        .await
+        .with_context(|| format!("Failed to delete state group {}", state_group_id))?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// Soft delete with core.deleteStateGroup (019), which refuses the default state group
+    /// and groups that still have states or equipment
+    #[cfg(feature = "stored-procedures")]
+    #[instrument(skip(db), fields(id = %state_group_id))]
+    pub async fn delete_state_group(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+    ) -> AppResult<bool> {
+        match Procedure::new("core.deleteStateGroup")
+            .bind(state_group_id)
+            .execute(db)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(ProcedureError::NotFound(_)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Undo a soft delete, None when there is no deleted state group with this id
+    #[instrument(skip(db), fields(id = %state_group_id))]
+    pub async fn restore_state_group(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+    ) -> Result<Option<StateGroupRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateGroupRow,
+            r#"UPDATE core.state_group
+               SET deleted_at = NULL
+               WHERE state_group_id = $1 AND deleted_at IS NOT NULL
+               RETURNING state_group_id, state_group_name, state_group_description, created_at, updated_at"#,
+            state_group_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Maps equipment to the state group with core.assignEquipmentToStateGroup (018)
+    #[instrument(skip(db))]
+    pub async fn assign_equipment(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> Result<ProcedureRow, ProcedureError> {
+        Procedure::new("core.assignEquipmentToStateGroup")
+            .bind(equipment_id)
+            .bind(state_group_id)
+            .execute(db)
+            .await
+    }
+
+    /// Removes the mapping with core.unassignEquipmentFromStateGroup (018)
+    #[instrument(skip(db))]
+    pub async fn unassign_equipment(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> Result<ProcedureRow, ProcedureError> {
+        Procedure::new("core.unassignEquipmentFromStateGroup")
+            .bind(equipment_id)
+            .bind(state_group_id)
+            .execute(db)
+            .await
+    }
+
+    /// Maps all the equipment at once with core.bulkAssignEquipmentToStateGroup (018),
+    /// equipment that is already mapped is skipped
+    #[instrument(skip(db, equipment_ids), fields(count = equipment_ids.len()))]
+    pub async fn bulk_assign_equipment(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        equipment_ids: &[Uuid],
+    ) -> Result<BulkGroupAssignment, ProcedureError> {
+        Procedure::new("core.bulkAssignEquipmentToStateGroup")
+            .bind(equipment_ids)
+            .bind(state_group_id)
+            .fetch(db)
+            .await
+    }
+
+    /// Equipment mapped to the state group, from core.getEquipmentForStateGroup (018)
+    pub async fn get_equipment(
+        db: &PgPool,
+        state_group_id: Uuid,
+    ) -> Result<Vec<GroupEquipment>, ProcedureError> {
+        let data: GroupEquipmentData = Procedure::new("core.getEquipmentForStateGroup")
+            .bind(state_group_id)
+            .fetch(db)
+            .await?;
+        Ok(data.equipment)
+    }
+
+    /// Usage and runtime per live state group from core.getStateGroupUsageStats (020), one
+    /// group when `state_group_id` is given
+    pub async fn get_usage_stats(
+        db: &PgPool,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+        state_group_id: Option<Uuid>,
+    ) -> Result<StateGroupUsageStats, ProcedureError> {
+        Procedure::new("core.getStateGroupUsageStats")
+            .bind(from)
+            .bind(to)
+            .bind(state_group_id)
+            .fetch(db)
+            .await
+    }
+
+    /// Codes between `min_code` and `max_code` (0 and 100 when None) that no live state uses
+    /// and no other owner has reserved, from core.getAvailableStateCodes (021). Without an
+    /// owner every reserved code is taken.
+    pub async fn get_available_codes(
+        db: &PgPool,
+        state_group_id: Uuid,
+        min_code: Option<i32>,
+        max_code: Option<i32>,
+        owner: Option<&str>,
+    ) -> Result<AvailableStateCodes, ProcedureError> {
+        Procedure::new("core.getAvailableStateCodes")
+            .bind(state_group_id)
+            .bind(min_code)
+            .bind(max_code)
+            .bind(owner)
+            .fetch(db)
+            .await
+    }
+
+    /// Live state groups the equipment is mapped to
+    pub async fn get_by_equipment_id(
+        db: &PgPool,
+        equipment_id: Uuid,
+    ) -> Result<Vec<StateGroupRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateGroupRow,
+            r#"SELECT g.state_group_id, g.state_group_name, g.state_group_description, g.created_at, g.updated_at
+               FROM core.state_group g
+               JOIN core.equipment_state_group_mapping m ON m.state_group_id = g.state_group_id
+               WHERE m.equipment_id = $1 AND g.deleted_at IS NULL
+               ORDER BY g.state_group_name"#,
+            equipment_id
+        )
+        .fetch_all(db)
+        .await
+    }
+
     pub async fn exists(db: &PgPool, state_group_id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-                SELECT 1 FROM core.state_group WHERE state_group_id = $1
+                SELECT 1 FROM core.state_group WHERE state_group_id = $1 AND deleted_at IS NULL
             )"#,
             state_group_id
         )
@@ -262,7 +520,7 @@ impl StateGroupQueries {
     pub async fn name_exists(db: &PgPool, state_group_name: &str) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-                SELECT 1 FROM core.state_group WHERE state_group_name = $1
+                SELECT 1 FROM core.state_group WHERE state_group_name = $1 AND deleted_at IS NULL
             )"#,
             state_group_name.trim()
         )
@@ -283,7 +541,7 @@ impl StateGroupQueries {
             StateGroupRow,
             r#"SELECT state_group_id, state_group_name, state_group_description, created_at, updated_at
                FROM core.state_group
-               WHERE lower(state_group_name::text COLLATE "C") LIKE $1
+               WHERE lower(state_group_name::text COLLATE "C") LIKE $1 AND deleted_at IS NULL
                ORDER BY state_group_name"#,
             pattern
         )
@@ -302,7 +560,7 @@ impl StateGroupQueries {
             StateGroupRow,
             r#"SELECT state_group_id, state_group_name, state_group_description, created_at, updated_at
                FROM core.state_group
-               WHERE lower(state_group_description::text COLLATE "C") LIKE $1
+               WHERE lower(state_group_description::text COLLATE "C") LIKE $1 AND deleted_at IS NULL
                ORDER BY state_group_name"#,
             pattern
         )
@@ -337,13 +595,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_name = "Test State Group";
         let state_group_description = "Test State Group Description";
 
-        let new_state_group =
-            StateGroupQueries::create_state_group(&pool, state_group_name, state_group_description)
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let new_state_group = StateGroupQueries::create_state_group(
+            &mut conn,
+            state_group_name,
+            state_group_description,
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert_eq!(new_state_group.state_group_name, state_group_name);
         assert_eq!(
@@ -355,29 +617,33 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     #[sqlx::test]
     async fn test_create_state_group_validation_errors(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // test empty name
-        let result = StateGroupQueries::create_state_group(&pool, "", "Valid description").await;
+        let result =
+            StateGroupQueries::create_state_group(&mut conn, "", "Valid description").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // test empty description
-        let result = StateGroupQueries::create_state_group(&pool, "Valid name", "").await;
+        let result = StateGroupQueries::create_state_group(&mut conn, "Valid name", "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // test whitespace-only inputs
-        let result = StateGroupQueries::create_state_group(&pool, "   ", "Valid description").await;
+        let result =
+            StateGroupQueries::create_state_group(&mut conn, "   ", "Valid description").await;
         assert!(result.is_err());
 
-        let result = StateGroupQueries::create_state_group(&pool, "Valid name", "   ").await;
+        let result = StateGroupQueries::create_state_group(&mut conn, "Valid name", "   ").await;
         assert!(result.is_err());
 
         // test too long name
         let long_name = "a".repeat(300);
         let result =
-            StateGroupQueries::create_state_group(&pool, &long_name, "Valid description").await;
+            StateGroupQueries::create_state_group(&mut conn, &long_name, "Valid description").await;
         assert!(result.is_err());
         assert!(
             result
@@ -388,7 +654,8 @@ mod tests {
 
         // test too long description
         let long_desc = "a".repeat(3000);
-        let result = StateGroupQueries::create_state_group(&pool, "Valid name", &long_desc).await;
+        let result =
+            StateGroupQueries::create_state_group(&mut conn, "Valid name", &long_desc).await;
         assert!(result.is_err());
         assert!(
             result
@@ -402,16 +669,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state_group_duplicate_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group_name = "Duplicate Test State Group";
 
         // first creation should succeed
-        let _first = StateGroupQueries::create_state_group(&pool, group_name, "Description 1")
+        let _first = StateGroupQueries::create_state_group(&mut conn, group_name, "Description 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // second creation with same name should fail
         let result =
-            StateGroupQueries::create_state_group(&pool, group_name, "Description 2").await;
+            StateGroupQueries::create_state_group(&mut conn, group_name, "Description 2").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -420,15 +688,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_all_state_groups(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_1 = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Test State Group 1",
             "Test State Group 1 Description",
         )
         .await
         .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let state_group_2 = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Test State Group 2",
             "Test State Group 2 Description",
         )
@@ -454,10 +723,14 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_state_group_id(pool: PgPool) -> sqlx::Result<()> {
-        let created =
-            StateGroupQueries::create_state_group(&pool, "Test State Group", "Test Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created = StateGroupQueries::create_state_group(
+            &mut conn,
+            "Test State Group",
+            "Test Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let found = StateGroupQueries::get_by_state_group_id(&pool, created.state_group_id).await?;
 
@@ -482,10 +755,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_state_group_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group_name = "Unique Test State Group Name";
-        let created = StateGroupQueries::create_state_group(&pool, group_name, "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            StateGroupQueries::create_state_group(&mut conn, group_name, "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let found = StateGroupQueries::get_by_state_group_name(&pool, group_name).await?;
 
@@ -509,10 +784,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_state_group_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let description = "Unique Test State Description";
-        let created = StateGroupQueries::create_state_group(&pool, "Test State Group", description)
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            StateGroupQueries::create_state_group(&mut conn, "Test State Group", description)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let found = StateGroupQueries::get_by_state_group_description(&pool, description).await?;
 
@@ -539,14 +816,18 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_name(pool: PgPool) -> sqlx::Result<()> {
-        let created =
-            StateGroupQueries::create_state_group(&pool, "Original State Name", "Test Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created = StateGroupQueries::create_state_group(
+            &mut conn,
+            "Original State Name",
+            "Test Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let new_name = "Updated State Name";
 
         let updated =
-            StateGroupQueries::update_state_group_name(&pool, created.state_group_id, new_name)
+            StateGroupQueries::update_state_group_name(&mut conn, created.state_group_id, new_name)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -564,29 +845,38 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     #[sqlx::test]
     async fn test_update_state_group_name_validation(pool: PgPool) -> sqlx::Result<()> {
-        let created =
-            StateGroupQueries::create_state_group(&pool, "Original State Name", "Test Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created = StateGroupQueries::create_state_group(
+            &mut conn,
+            "Original State Name",
+            "Test Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test empty name
         let result =
-            StateGroupQueries::update_state_group_name(&pool, created.state_group_id, "").await;
+            StateGroupQueries::update_state_group_name(&mut conn, created.state_group_id, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test whitespace-only name
         let result =
-            StateGroupQueries::update_state_group_name(&pool, created.state_group_id, "   ").await;
+            StateGroupQueries::update_state_group_name(&mut conn, created.state_group_id, "   ")
+                .await;
         assert!(result.is_err());
 
         // Test too long name
         let long_name = "a".repeat(300);
-        let result =
-            StateGroupQueries::update_state_group_name(&pool, created.state_group_id, &long_name)
-                .await;
+        let result = StateGroupQueries::update_state_group_name(
+            &mut conn,
+            created.state_group_id,
+            &long_name,
+        )
+        .await;
         assert!(result.is_err());
         assert!(
             result
@@ -600,12 +890,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_name_duplicate(pool: PgPool) -> sqlx::Result<()> {
-        let first =
-            StateGroupQueries::create_state_group(&pool, "First State Group", "First Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let first = StateGroupQueries::create_state_group(
+            &mut conn,
+            "First State Group",
+            "First Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let second = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Second State Group",
             "Second Description",
         )
@@ -614,7 +908,7 @@ mod tests {
 
         // Try to update second group's name to match first group's name
         let result = StateGroupQueries::update_state_group_name(
-            &pool,
+            &mut conn,
             second.state_group_id,
             "First State Group",
         )
@@ -627,8 +921,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_name_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let result = StateGroupQueries::update_state_group_name(&pool, random_id, "New Name")
+        let result = StateGroupQueries::update_state_group_name(&mut conn, random_id, "New Name")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -639,8 +934,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let created = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Test State Group",
             "Original Description",
         )
@@ -649,7 +945,7 @@ mod tests {
         let new_description = "Updated State Description";
 
         let updated = StateGroupQueries::update_state_group_description(
-            &pool,
+            &mut conn,
             created.state_group_id,
             new_description,
         )
@@ -666,10 +962,12 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(not(feature = "stored-procedures"))]
     #[sqlx::test]
     async fn test_update_state_group_description_validation(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let created = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Test State Group",
             "Original Description",
         )
@@ -677,22 +975,28 @@ mod tests {
         .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test empty description
-        let result =
-            StateGroupQueries::update_state_group_description(&pool, created.state_group_id, "")
-                .await;
+        let result = StateGroupQueries::update_state_group_description(
+            &mut conn,
+            created.state_group_id,
+            "",
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test whitespace-only description
-        let result =
-            StateGroupQueries::update_state_group_description(&pool, created.state_group_id, "   ")
-                .await;
+        let result = StateGroupQueries::update_state_group_description(
+            &mut conn,
+            created.state_group_id,
+            "   ",
+        )
+        .await;
         assert!(result.is_err());
 
         // Test too long description
         let long_desc = "a".repeat(3000);
         let result = StateGroupQueries::update_state_group_description(
-            &pool,
+            &mut conn,
             created.state_group_id,
             &long_desc,
         )
@@ -710,11 +1014,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_description_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let result =
-            StateGroupQueries::update_state_group_description(&pool, random_id, "New Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let result = StateGroupQueries::update_state_group_description(
+            &mut conn,
+            random_id,
+            "New Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert!(result.is_none());
 
@@ -723,12 +1031,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_state_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let created =
-            StateGroupQueries::create_state_group(&pool, "To Delete State", "Test Description")
+            StateGroupQueries::create_state_group(&mut conn, "To Delete State", "Test Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
-        let deleted = StateGroupQueries::delete_state_group(&pool, created.state_group_id).await?;
+        let deleted = StateGroupQueries::delete_state_group(&mut conn, created.state_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
 
         // Verify it's gone
@@ -740,8 +1051,11 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_state_group_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let deleted = StateGroupQueries::delete_state_group(&pool, random_id).await?;
+        let deleted = StateGroupQueries::delete_state_group(&mut conn, random_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert!(!deleted);
 
@@ -750,10 +1064,14 @@ mod tests {
 
     #[sqlx::test]
     async fn test_exists(pool: PgPool) -> sqlx::Result<()> {
-        let created =
-            StateGroupQueries::create_state_group(&pool, "Exists Test State", "Test Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created = StateGroupQueries::create_state_group(
+            &mut conn,
+            "Exists Test State",
+            "Test Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let exists = StateGroupQueries::exists(&pool, created.state_group_id).await?;
         assert!(exists);
@@ -767,9 +1085,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_name_exists(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_name = "Exists Test State Group";
         let _created =
-            StateGroupQueries::create_state_group(&pool, state_group_name, "Test Description")
+            StateGroupQueries::create_state_group(&mut conn, state_group_name, "Test Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -786,20 +1105,24 @@ mod tests {
 
     #[sqlx::test]
     async fn test_search_by_name(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Create test data
-        let _pump =
-            StateGroupQueries::create_state_group(&pool, "Pump State Group", "Pump related states")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let _pump = StateGroupQueries::create_state_group(
+            &mut conn,
+            "Pump State Group",
+            "Pump related states",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let _motor = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Motor State Group",
             "Motor related states",
         )
         .await
         .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let _valve = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Valve State Group",
             "Valve related states",
         )
@@ -833,19 +1156,23 @@ mod tests {
 
     #[sqlx::test]
     async fn test_search_by_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Create test data
         let _pump =
-            StateGroupQueries::create_state_group(&pool, "Group A", "Pump related operations")
+            StateGroupQueries::create_state_group(&mut conn, "Group A", "Pump related operations")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let _motor =
-            StateGroupQueries::create_state_group(&pool, "Group B", "Motor control operations")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let _valve =
-            StateGroupQueries::create_state_group(&pool, "Group C", "Valve positioning operations")
+            StateGroupQueries::create_state_group(&mut conn, "Group B", "Motor control operations")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let _valve = StateGroupQueries::create_state_group(
+            &mut conn,
+            "Group C",
+            "Valve positioning operations",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Search for "operations"
         let operations_results =
@@ -870,9 +1197,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_input_trimming(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test that inputs are properly trimmed
         let created = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "  Trimmed State Name  ",
             "  Trimmed State Description  ",
         )
@@ -887,17 +1215,21 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_preserves_timestamps(pool: PgPool) -> sqlx::Result<()> {
-        let created =
-            StateGroupQueries::create_state_group(&pool, "Test State Group", "Test Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mut conn = pool.acquire().await?;
+        let created = StateGroupQueries::create_state_group(
+            &mut conn,
+            "Test State Group",
+            "Test Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let original_created_at = created.created_at;
 
         // Short delay to ensure timestamp difference
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
 
         let updated = StateGroupQueries::update_state_group_name(
-            &pool,
+            &mut conn,
             created.state_group_id,
             "Updated State Name",
         )
@@ -922,11 +1254,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_multiple_operations_sequence(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test a sequence of operations to ensure they work together
 
         // Create
         let created = StateGroupQueries::create_state_group(
-            &pool,
+            &mut conn,
             "Sequence Test State",
             "Original State Description",
         )
@@ -943,7 +1276,7 @@ mod tests {
 
         // Update name
         let updated_name = StateGroupQueries::update_state_group_name(
-            &pool,
+            &mut conn,
             created.state_group_id,
             "Updated State Name",
         )
@@ -953,7 +1286,7 @@ mod tests {
 
         // Update description
         let updated_desc = StateGroupQueries::update_state_group_description(
-            &pool,
+            &mut conn,
             created.state_group_id,
             "Updated State Description",
         )
@@ -981,7 +1314,9 @@ mod tests {
         );
 
         // Delete
-        let deleted = StateGroupQueries::delete_state_group(&pool, created.state_group_id).await?;
+        let deleted = StateGroupQueries::delete_state_group(&mut conn, created.state_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
 
         // Verify it no longer exists
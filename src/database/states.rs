@@ -1,5 +1,7 @@
-use anyhow::{Context, anyhow};
-use sqlx::PgPool;
+use crate::database::procedures::{Procedure, ProcedureError};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::core::StateUsageStats;
+use sqlx::{PgConnection, PgPool};
 use time::OffsetDateTime;
 use tracing::{debug, error, instrument};
 use uuid::Uuid;
@@ -20,27 +22,31 @@ pub struct StateRowQueries;
 
 impl StateRowQueries {
     /// Validates and sanitizes state description input
-    fn validate_state_description(state_description: &str) -> anyhow::Result<String> {
+    fn validate_state_description(state_description: &str) -> AppResult<String> {
         let trimmed = state_description.trim().to_string();
 
         if trimmed.is_empty() {
-            return Err(anyhow!("state_description cannot be empty"));
+            return Err(AppError::Validation(
+                "state_description cannot be empty".to_string(),
+            ));
         }
 
         if trimmed.len() > MAX_DESC_LEN {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "state_description exceeds max length of {} characters",
                 MAX_DESC_LEN
-            ));
+            )));
         }
 
         Ok(trimmed)
     }
 
     /// Validates state code input
-    fn validate_state_code(state_code: i32) -> anyhow::Result<i32> {
+    fn validate_state_code(state_code: i32) -> AppResult<i32> {
         if state_code < 0 {
-            return Err(anyhow!("state_code cannot be negative"));
+            return Err(AppError::Validation(
+                "state_code cannot be negative".to_string(),
+            ));
         }
 
         Ok(state_code)
@@ -52,6 +58,7 @@ impl StateRowQueries {
             r#"SELECT state_id, state_group_id, state_code,
                 state_description, created_at, updated_at
 	            FROM core.state
+                WHERE deleted_at IS NULL
                 ORDER BY state_code, state_description"#
         )
         .fetch_all(db)
@@ -67,7 +74,7 @@ impl StateRowQueries {
             r#"SELECT state_id, state_group_id, state_code, 
              state_description, created_at, updated_at
 	            FROM core.state
-                WHERE state_group_id = $1
+                WHERE state_group_id = $1 AND deleted_at IS NULL
                 ORDER BY state_code, state_description"#,
             state_group_id
         )
@@ -84,7 +91,7 @@ impl StateRowQueries {
             r#"SELECT state_id, state_group_id, state_code,
              state_description, created_at, updated_at
 	            FROM core.state
-                WHERE state_id = $1"#,
+                WHERE state_id = $1 AND deleted_at IS NULL"#,
             state_id
         )
         .fetch_optional(db)
@@ -100,7 +107,7 @@ impl StateRowQueries {
             r#"SELECT state_id, state_group_id, state_code,
              state_description, created_at, updated_at
 	            FROM core.state
-                WHERE state_description = $1"#,
+                WHERE state_description = $1 AND deleted_at IS NULL"#,
             state_description
         )
         .fetch_optional(db)
@@ -117,7 +124,7 @@ impl StateRowQueries {
             r#"SELECT state_id, state_group_id, state_code,
              state_description, created_at, updated_at
 	            FROM core.state
-                WHERE state_group_id = $1 AND state_code = $2"#,
+                WHERE state_group_id = $1 AND state_code = $2 AND deleted_at IS NULL"#,
             state_group_id,
             state_code
         )
@@ -127,38 +134,38 @@ impl StateRowQueries {
 
     #[instrument(skip(db), fields(group_id = %state_group_id, code = %state_code, description = %state_description))]
     pub async fn create_state(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_group_id: Uuid,
         state_code: i32,
         state_description: &str,
-    ) -> anyhow::Result<StateRow> {
+    ) -> AppResult<StateRow> {
         let validated_description = Self::validate_state_description(state_description)?;
         let validated_code = Self::validate_state_code(state_code)?;
 
         // Check if state_group_id exists
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1 AND deleted_at IS NULL)",
             state_group_id
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .context("Failed to check if state_group exists")?;
 
         if !group_exists.unwrap_or(false) {
             error!("Rejected: state_group_id {} does not exist", state_group_id);
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "state_group_id '{}' does not exist",
                 state_group_id
-            ));
+            )));
         }
 
         // Check for duplicate state_code within the same state group
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_code = $2",
+            "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_code = $2 AND deleted_at IS NULL",
             state_group_id,
             validated_code
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate state_code in state_group")?
         {
@@ -166,19 +173,19 @@ impl StateRowQueries {
                 "Rejected: duplicate state_code '{}' in state_group {}",
                 validated_code, state_group_id
             );
-            return Err(anyhow!(
+            return Err(AppError::Conflict(format!(
                 "state_code '{}' already exists in this state group",
                 validated_code
-            ));
+            )));
         }
 
         // Check for duplicate description within the same state group
         if let Some(_) = sqlx::query_scalar!(
-            "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_description = $2",
+            "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_description = $2 AND deleted_at IS NULL",
             state_group_id,
             validated_description
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to check for duplicate state_description in state_group")?
         {
@@ -186,10 +193,10 @@ impl StateRowQueries {
                 "Rejected: duplicate state_description '{}' in state_group {}",
                 validated_description, state_group_id
             );
-            return Err(anyhow!(
+            return Err(AppError::Conflict(format!(
                 "state_description '{}' already exists in this state group",
                 validated_description
-            ));
+            )));
         }
 
         debug!(
@@ -206,7 +213,7 @@ impl StateRowQueries {
             validated_code,
             validated_description
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .with_context(|| {
             format!(
@@ -221,37 +228,37 @@ impl StateRowQueries {
 
     #[instrument(skip(db), fields(id = %state_id, description = %state_description))]
     pub async fn update_state_description(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_id: Uuid,
         state_description: &str,
-    ) -> anyhow::Result<Option<StateRow>> {
+    ) -> AppResult<Option<StateRow>> {
         let validated_description = Self::validate_state_description(state_description)?;
 
         // Get the current state to check for duplicate in same group
         let current_state = sqlx::query!(
-            "SELECT state_group_id FROM core.state WHERE state_id = $1",
+            "SELECT state_group_id FROM core.state WHERE state_id = $1 AND deleted_at IS NULL",
             state_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to fetch current state")?;
 
         if let Some(current) = current_state {
             // Check for duplicate description within the same state group (excluding current record)
             if let Some(_) = sqlx::query_scalar!(
-                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_description = $2 AND state_id != $3",
+                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_description = $2 AND state_id != $3 AND deleted_at IS NULL",
                 current.state_group_id,
                 validated_description,
                 state_id
             )
-            .fetch_optional(db)
+            .fetch_optional(&mut *db)
             .await
             .context("Failed to check for duplicate state_description")?
             {
-                return Err(anyhow!(
+                return Err(AppError::Conflict(format!(
                     "state_description '{}' already exists in this state group",
                     validated_description
-                ));
+                )));
             }
         }
 
@@ -263,13 +270,13 @@ impl StateRowQueries {
             StateRow,
             r#"UPDATE core.state 
                SET state_description = $2, updated_at = NOW()
-               WHERE state_id = $1
+               WHERE state_id = $1 AND deleted_at IS NULL
                RETURNING state_id, state_group_id, state_code,
              state_description, created_at, updated_at"#,
             state_id,
             validated_description
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update state description for id {}", state_id))?;
 
@@ -284,37 +291,37 @@ impl StateRowQueries {
 
     #[instrument(skip(db), fields(id = %state_id, code = %state_code))]
     pub async fn update_state_code(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_id: Uuid,
         state_code: i32,
-    ) -> anyhow::Result<Option<StateRow>> {
+    ) -> AppResult<Option<StateRow>> {
         let validated_code = Self::validate_state_code(state_code)?;
 
         // Get the current state to check for duplicate in same group
         let current_state = sqlx::query!(
-            "SELECT state_group_id FROM core.state WHERE state_id = $1",
+            "SELECT state_group_id FROM core.state WHERE state_id = $1 AND deleted_at IS NULL",
             state_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to fetch current state")?;
 
         if let Some(current) = current_state {
             // Check for duplicate code within the same state group (excluding current record)
             if let Some(_) = sqlx::query_scalar!(
-                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_code = $2 AND state_id != $3",
+                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_code = $2 AND state_id != $3 AND deleted_at IS NULL",
                 current.state_group_id,
                 validated_code,
                 state_id
             )
-            .fetch_optional(db)
+            .fetch_optional(&mut *db)
             .await
             .context("Failed to check for duplicate state_code")?
             {
-                return Err(anyhow!(
+                return Err(AppError::Conflict(format!(
                     "state_code '{}' already exists in this state group",
                     validated_code
-                ));
+                )));
             }
         }
 
@@ -323,13 +330,13 @@ impl StateRowQueries {
             StateRow,
             r#"UPDATE core.state 
                SET state_code = $2, updated_at = NOW()
-               WHERE state_id = $1
+               WHERE state_id = $1 AND deleted_at IS NULL
                RETURNING state_id, state_group_id, state_code,
              state_description, created_at, updated_at"#,
             state_id,
             validated_code
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update state code for id {}", state_id))?;
 
@@ -344,66 +351,66 @@ impl StateRowQueries {
 
     #[instrument(skip(db), fields(id = %state_id, new_group_id = %state_group_id))]
     pub async fn update_state_group(
-        db: &PgPool,
+        db: &mut PgConnection,
         state_id: Uuid,
         state_group_id: Uuid,
-    ) -> anyhow::Result<Option<StateRow>> {
+    ) -> AppResult<Option<StateRow>> {
         // Check if new state_group_id exists
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1 AND deleted_at IS NULL)",
             state_group_id
         )
-        .fetch_one(db)
+        .fetch_one(&mut *db)
         .await
         .context("Failed to check if state_group exists")?;
 
         if !group_exists.unwrap_or(false) {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "state_group_id '{}' does not exist",
                 state_group_id
-            ));
+            )));
         }
 
         // Get current state to check for conflicts
         let current_state = sqlx::query!(
-            "SELECT state_code, state_description FROM core.state WHERE state_id = $1",
+            "SELECT state_code, state_description FROM core.state WHERE state_id = $1 AND deleted_at IS NULL",
             state_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .context("Failed to fetch current state")?;
 
         if let Some(current) = current_state {
             // Check for duplicate code in the new state group
             if let Some(_) = sqlx::query_scalar!(
-                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_code = $2",
+                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_code = $2 AND deleted_at IS NULL",
                 state_group_id,
                 current.state_code
             )
-            .fetch_optional(db)
+            .fetch_optional(&mut *db)
             .await
             .context("Failed to check for duplicate state_code in new group")?
             {
-                return Err(anyhow!(
+                return Err(AppError::Conflict(format!(
                     "state_code '{}' already exists in the target state group",
                     current.state_code
-                ));
+                )));
             }
 
             // Check for duplicate description in the new state group
             if let Some(_) = sqlx::query_scalar!(
-                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_description = $2",
+                "SELECT 1 FROM core.state WHERE state_group_id = $1 AND state_description = $2 AND deleted_at IS NULL",
                 state_group_id,
                 current.state_description
             )
-            .fetch_optional(db)
+            .fetch_optional(&mut *db)
             .await
             .context("Failed to check for duplicate state_description in new group")?
             {
-                return Err(anyhow!(
+                return Err(AppError::Conflict(format!(
                     "state_description '{}' already exists in the target state group",
                     current.state_description
-                ));
+                )));
             }
         }
 
@@ -412,13 +419,13 @@ impl StateRowQueries {
             StateRow,
             r#"UPDATE core.state 
                SET state_group_id = $2, updated_at = NOW()
-               WHERE state_id = $1
+               WHERE state_id = $1 AND deleted_at IS NULL
                RETURNING state_id, state_group_id, state_code,
              state_description, created_at, updated_at"#,
             state_id,
             state_group_id
         )
-        .fetch_optional(db)
+        .fetch_optional(&mut *db)
         .await
         .with_context(|| format!("Failed to update state group for id {}", state_id))?;
 
@@ -431,16 +438,17 @@ impl StateRowQueries {
         Ok(result)
     }
 
+    /// Soft delete, the state history keeps pointing at the row until it is purged
     #[instrument(skip(db), fields(id = %state_id))]
-    pub async fn delete_state(db: &PgPool, state_id: Uuid) -> anyhow::Result<bool> {
-        // TODO: Add check to see if the state is being used anywhere
-        // This would prevent deletion of states that are in use
-
+    pub async fn delete_state(db: &mut PgConnection, state_id: Uuid) -> AppResult<bool> {
         debug!("Deleting state {}", state_id);
-        let result = sqlx::query!("DELETE FROM core.state WHERE state_id = $1", state_id)
-            .execute(db)
-            .await
-            .with_context(|| format!("Failed to delete state with id {}", state_id))?;
+        let result = sqlx::query!(
+            "UPDATE core.state SET deleted_at = NOW() WHERE state_id = $1 AND deleted_at IS NULL",
+            state_id
+        )
+        .execute(&mut *db)
+        .await
+        .with_context(|| format!("Failed to delete state with id {}", state_id))?;
 
         let deleted = result.rows_affected() > 0;
         if deleted {
@@ -452,10 +460,29 @@ impl StateRowQueries {
         Ok(deleted)
     }
 
+    /// Undo a soft delete, None when there is no deleted state with this id
+    #[instrument(skip(db), fields(id = %state_id))]
+    pub async fn restore_state(
+        db: &mut PgConnection,
+        state_id: Uuid,
+    ) -> Result<Option<StateRow>, sqlx::Error> {
+        sqlx::query_as!(
+            StateRow,
+            r#"UPDATE core.state
+               SET deleted_at = NULL
+               WHERE state_id = $1 AND deleted_at IS NOT NULL
+               RETURNING state_id, state_group_id, state_code, state_description,
+                created_at, updated_at"#,
+            state_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
     pub async fn exists(db: &PgPool, state_id: Uuid) -> Result<bool, sqlx::Error> {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
-                SELECT 1 FROM core.state WHERE state_id = $1
+                SELECT 1 FROM core.state WHERE state_id = $1 AND deleted_at IS NULL
             )"#,
             state_id
         )
@@ -474,7 +501,7 @@ impl StateRowQueries {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
                 SELECT 1 FROM core.state 
-                WHERE state_group_id = $1 AND state_code = $2
+                WHERE state_group_id = $1 AND state_code = $2 AND deleted_at IS NULL
             )"#,
             state_group_id,
             state_code
@@ -494,7 +521,7 @@ impl StateRowQueries {
         let result = sqlx::query_scalar!(
             r#"SELECT EXISTS(
                 SELECT 1 FROM core.state 
-                WHERE state_group_id = $1 AND state_description = $2
+                WHERE state_group_id = $1 AND state_description = $2 AND deleted_at IS NULL
             )"#,
             state_group_id,
             state_description.trim()
@@ -516,7 +543,7 @@ impl StateRowQueries {
             StateRow,
             r#"SELECT state_id, state_group_id, state_code, state_description, created_at, updated_at
                FROM core.state
-               WHERE lower(state_description::text COLLATE "C") LIKE $1
+               WHERE lower(state_description::text COLLATE "C") LIKE $1 AND deleted_at IS NULL
                ORDER BY state_code, state_description"#,
             pattern
         )
@@ -528,10 +555,10 @@ impl StateRowQueries {
     pub async fn get_states_for_group(
         db: &PgPool,
         state_group_id: Uuid,
-    ) -> anyhow::Result<Vec<StateRow>> {
+    ) -> AppResult<Vec<StateRow>> {
         // Check if state_group exists first
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1 AND deleted_at IS NULL)",
             state_group_id
         )
         .fetch_one(db)
@@ -539,10 +566,10 @@ impl StateRowQueries {
         .context("Failed to check if state_group exists")?;
 
         if !group_exists.unwrap_or(false) {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "state_group_id '{}' does not exist",
                 state_group_id
-            ));
+            )));
         }
 
         let states = Self::get_by_state_group_id(db, state_group_id)
@@ -558,14 +585,16 @@ impl StateRowQueries {
         state_group_id: Uuid,
         min_code: i32,
         max_code: i32,
-    ) -> anyhow::Result<Vec<StateRow>> {
+    ) -> AppResult<Vec<StateRow>> {
         if min_code > max_code {
-            return Err(anyhow!("min_code cannot be greater than max_code"));
+            return Err(AppError::Validation(
+                "min_code cannot be greater than max_code".to_string(),
+            ));
         }
 
         // Check if state_group exists first
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.state_group WHERE state_group_id = $1 AND deleted_at IS NULL)",
             state_group_id
         )
         .fetch_one(db)
@@ -573,17 +602,17 @@ impl StateRowQueries {
         .context("Failed to check if state_group exists")?;
 
         if !group_exists.unwrap_or(false) {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "state_group_id '{}' does not exist",
                 state_group_id
-            ));
+            )));
         }
 
         let states = sqlx::query_as!(
             StateRow,
             r#"SELECT state_id, state_group_id, state_code, state_description, created_at, updated_at
                FROM core.state
-               WHERE state_group_id = $1 AND state_code >= $2 AND state_code <= $3
+               WHERE state_group_id = $1 AND state_code >= $2 AND state_code <= $3 AND deleted_at IS NULL
                ORDER BY state_code"#,
             state_group_id,
             min_code,
@@ -595,6 +624,22 @@ impl StateRowQueries {
 
         Ok(states)
     }
+
+    /// Runtime per live state over a range from core.getStateUsageStats (020), listed per
+    /// state group. Only the state and its group when `state_id` is given.
+    pub async fn get_usage_stats(
+        db: &PgPool,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+        state_id: Option<Uuid>,
+    ) -> Result<StateUsageStats, ProcedureError> {
+        Procedure::new("core.getStateUsageStats")
+            .bind(from)
+            .bind(to)
+            .bind(state_id)
+            .fetch(db)
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -639,12 +684,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let state_code = 100;
         let state_description = "Test State Description";
 
         let state =
-            StateRowQueries::create_state(&pool, state_group_id, state_code, state_description)
+            StateRowQueries::create_state(&mut conn, state_group_id, state_code, state_description)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -659,21 +705,23 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state_validation_errors(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
 
         // Test empty description
-        let result = StateRowQueries::create_state(&pool, state_group_id, 100, "").await;
+        let result = StateRowQueries::create_state(&mut conn, state_group_id, 100, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test whitespace-only description
-        let result = StateRowQueries::create_state(&pool, state_group_id, 100, "   ").await;
+        let result = StateRowQueries::create_state(&mut conn, state_group_id, 100, "   ").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
         // Test too long description
         let long_desc = "a".repeat(3000);
-        let result = StateRowQueries::create_state(&pool, state_group_id, 100, &long_desc).await;
+        let result =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, &long_desc).await;
         assert!(result.is_err());
         assert!(
             result
@@ -684,7 +732,7 @@ mod tests {
 
         // Test negative state code
         let result =
-            StateRowQueries::create_state(&pool, state_group_id, -1, "Valid Description").await;
+            StateRowQueries::create_state(&mut conn, state_group_id, -1, "Valid Description").await;
         assert!(result.is_err());
         assert!(
             result
@@ -698,10 +746,12 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state_invalid_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let invalid_group_id = Uuid::new_v4();
 
         let result =
-            StateRowQueries::create_state(&pool, invalid_group_id, 100, "Valid Description").await;
+            StateRowQueries::create_state(&mut conn, invalid_group_id, 100, "Valid Description")
+                .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
 
@@ -710,19 +760,28 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state_duplicate_code_in_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let state_code = 100;
 
         // First creation should succeed
-        let _first =
-            StateRowQueries::create_state(&pool, state_group_id, state_code, "First Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let _first = StateRowQueries::create_state(
+            &mut conn,
+            state_group_id,
+            state_code,
+            "First Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Second creation with same code in same group should fail
-        let result =
-            StateRowQueries::create_state(&pool, state_group_id, state_code, "Second Description")
-                .await;
+        let result = StateRowQueries::create_state(
+            &mut conn,
+            state_group_id,
+            state_code,
+            "Second Description",
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -731,17 +790,19 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state_duplicate_description_in_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let state_description = "Duplicate Description";
 
         // First creation should succeed
-        let _first = StateRowQueries::create_state(&pool, state_group_id, 100, state_description)
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let _first =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, state_description)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Second creation with same description in same group should fail
         let result =
-            StateRowQueries::create_state(&pool, state_group_id, 200, state_description).await;
+            StateRowQueries::create_state(&mut conn, state_group_id, 200, state_description).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -750,6 +811,7 @@ mod tests {
 
     #[sqlx::test]
     async fn test_create_state_same_values_different_groups(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group1_id = create_test_state_group(&pool).await?;
         let group2_id = create_test_state_group(&pool).await?;
         let state_code = 100;
@@ -757,13 +819,13 @@ mod tests {
 
         // Create state in first group
         let _state1 =
-            StateRowQueries::create_state(&pool, group1_id, state_code, state_description)
+            StateRowQueries::create_state(&mut conn, group1_id, state_code, state_description)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Create state with same code and description in second group - should succeed
         let _state2 =
-            StateRowQueries::create_state(&pool, group2_id, state_code, state_description)
+            StateRowQueries::create_state(&mut conn, group2_id, state_code, state_description)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -772,12 +834,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_input_trimming(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
 
-        let created =
-            StateRowQueries::create_state(&pool, state_group_id, 100, "  Trimmed Description  ")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created = StateRowQueries::create_state(
+            &mut conn,
+            state_group_id,
+            100,
+            "  Trimmed Description  ",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         assert_eq!(created.state_description, "Trimmed Description");
 
@@ -786,12 +853,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_all_states(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
 
-        let state1 = StateRowQueries::create_state(&pool, state_group_id, 100, "State 1")
+        let state1 = StateRowQueries::create_state(&mut conn, state_group_id, 100, "State 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let state2 = StateRowQueries::create_state(&pool, state_group_id, 200, "State 2")
+        let state2 = StateRowQueries::create_state(&mut conn, state_group_id, 200, "State 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -806,8 +874,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_state_id(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, "Test State")
+        let created = StateRowQueries::create_state(&mut conn, state_group_id, 100, "Test State")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -834,12 +903,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_state_group_id(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
 
-        let state1 = StateRowQueries::create_state(&pool, state_group_id, 100, "State 1")
+        let state1 = StateRowQueries::create_state(&mut conn, state_group_id, 100, "State 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let state2 = StateRowQueries::create_state(&pool, state_group_id, 200, "State 2")
+        let state2 = StateRowQueries::create_state(&mut conn, state_group_id, 200, "State 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -854,9 +924,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_state_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let description = "Unique State Description";
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, description)
+        let created = StateRowQueries::create_state(&mut conn, state_group_id, 100, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -872,12 +943,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_by_state_code_and_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let state_code = 100;
-        let created =
-            StateRowQueries::create_state(&pool, state_group_id, state_code, "Test Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created = StateRowQueries::create_state(
+            &mut conn,
+            state_group_id,
+            state_code,
+            "Test Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         let found =
             StateRowQueries::get_by_state_code_and_group(&pool, state_group_id, state_code).await?;
@@ -892,15 +968,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let created =
-            StateRowQueries::create_state(&pool, state_group_id, 100, "Original Description")
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "Original Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let new_description = "Updated Description";
 
         let updated =
-            StateRowQueries::update_state_description(&pool, created.state_id, new_description)
+            StateRowQueries::update_state_description(&mut conn, created.state_id, new_description)
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -915,14 +992,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_description_validation(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let created =
-            StateRowQueries::create_state(&pool, state_group_id, 100, "Original Description")
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "Original Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test empty description
-        let result = StateRowQueries::update_state_description(&pool, created.state_id, "").await;
+        let result =
+            StateRowQueries::update_state_description(&mut conn, created.state_id, "").await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
 
@@ -931,19 +1010,24 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_description_duplicate(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let state1 = StateRowQueries::create_state(&pool, state_group_id, 100, "First Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let state1 =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "First Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let state2 =
-            StateRowQueries::create_state(&pool, state_group_id, 200, "Second Description")
+            StateRowQueries::create_state(&mut conn, state_group_id, 200, "Second Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to update state2 to have same description as state1
-        let result =
-            StateRowQueries::update_state_description(&pool, state2.state_id, "First Description")
-                .await;
+        let result = StateRowQueries::update_state_description(
+            &mut conn,
+            state2.state_id,
+            "First Description",
+        )
+        .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -952,13 +1036,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_code(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let new_code = 200;
 
-        let updated = StateRowQueries::update_state_code(&pool, created.state_id, new_code)
+        let updated = StateRowQueries::update_state_code(&mut conn, created.state_id, new_code)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -973,13 +1059,15 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_code_validation(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test negative code
-        let result = StateRowQueries::update_state_code(&pool, created.state_id, -1).await;
+        let result = StateRowQueries::update_state_code(&mut conn, created.state_id, -1).await;
         assert!(result.is_err());
         assert!(
             result
@@ -993,17 +1081,19 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_code_duplicate(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let state1 = StateRowQueries::create_state(&pool, state_group_id, 100, "First Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let state1 =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "First Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let state2 =
-            StateRowQueries::create_state(&pool, state_group_id, 200, "Second Description")
+            StateRowQueries::create_state(&mut conn, state_group_id, 200, "Second Description")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to update state2 to have same code as state1
-        let result = StateRowQueries::update_state_code(&pool, state2.state_id, 100).await;
+        let result = StateRowQueries::update_state_code(&mut conn, state2.state_id, 100).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -1012,13 +1102,14 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group1_id = create_test_state_group(&pool).await?;
         let group2_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, group1_id, 100, "Test Description")
+        let created = StateRowQueries::create_state(&mut conn, group1_id, 100, "Test Description")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
-        let updated = StateRowQueries::update_state_group(&pool, created.state_id, group2_id)
+        let updated = StateRowQueries::update_state_group(&mut conn, created.state_id, group2_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1033,14 +1124,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_invalid(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, "Test Description")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "Test Description")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let invalid_group_id = Uuid::new_v4();
 
         let result =
-            StateRowQueries::update_state_group(&pool, created.state_id, invalid_group_id).await;
+            StateRowQueries::update_state_group(&mut conn, created.state_id, invalid_group_id)
+                .await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("does not exist"));
 
@@ -1049,22 +1143,26 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_code_conflict(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group1_id = create_test_state_group(&pool).await?;
         let group2_id = create_test_state_group(&pool).await?;
         let state_code = 100;
 
         // Create state in group1
-        let state1 = StateRowQueries::create_state(&pool, group1_id, state_code, "Description 1")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let state1 =
+            StateRowQueries::create_state(&mut conn, group1_id, state_code, "Description 1")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Create state in group2 with same code
-        let _state2 = StateRowQueries::create_state(&pool, group2_id, state_code, "Description 2")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let _state2 =
+            StateRowQueries::create_state(&mut conn, group2_id, state_code, "Description 2")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to move state1 to group2 - should fail due to code conflict
-        let result = StateRowQueries::update_state_group(&pool, state1.state_id, group2_id).await;
+        let result =
+            StateRowQueries::update_state_group(&mut conn, state1.state_id, group2_id).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -1073,22 +1171,24 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_state_group_description_conflict(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let group1_id = create_test_state_group(&pool).await?;
         let group2_id = create_test_state_group(&pool).await?;
         let description = "Same Description";
 
         // Create state in group1
-        let state1 = StateRowQueries::create_state(&pool, group1_id, 100, description)
+        let state1 = StateRowQueries::create_state(&mut conn, group1_id, 100, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Create state in group2 with same description
-        let _state2 = StateRowQueries::create_state(&pool, group2_id, 200, description)
+        let _state2 = StateRowQueries::create_state(&mut conn, group2_id, 200, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Try to move state1 to group2 - should fail due to description conflict
-        let result = StateRowQueries::update_state_group(&pool, state1.state_id, group2_id).await;
+        let result =
+            StateRowQueries::update_state_group(&mut conn, state1.state_id, group2_id).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("already exists"));
 
@@ -1097,12 +1197,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_state(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, "To Delete")
+        let created = StateRowQueries::create_state(&mut conn, state_group_id, 100, "To Delete")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
-        let deleted = StateRowQueries::delete_state(&pool, created.state_id)
+        let deleted = StateRowQueries::delete_state(&mut conn, created.state_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
@@ -1116,8 +1217,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_delete_state_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let random_id = Uuid::new_v4();
-        let deleted = StateRowQueries::delete_state(&pool, random_id)
+        let deleted = StateRowQueries::delete_state(&mut conn, random_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1128,8 +1230,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_exists(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, "Exists Test")
+        let created = StateRowQueries::create_state(&mut conn, state_group_id, 100, "Exists Test")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1145,12 +1248,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_code_exists_in_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let state_code = 100;
-        let _created =
-            StateRowQueries::create_state(&pool, state_group_id, state_code, "Test Description")
-                .await
-                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let _created = StateRowQueries::create_state(
+            &mut conn,
+            state_group_id,
+            state_code,
+            "Test Description",
+        )
+        .await
+        .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Test exact match
         let exists =
@@ -1172,9 +1280,10 @@ mod tests {
 
     #[sqlx::test]
     async fn test_description_exists_in_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
         let description = "Exists Test Description";
-        let _created = StateRowQueries::create_state(&pool, state_group_id, 100, description)
+        let _created = StateRowQueries::create_state(&mut conn, state_group_id, 100, description)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1202,18 +1311,20 @@ mod tests {
 
     #[sqlx::test]
     async fn test_search_by_description(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
 
         // Create test data
-        let _pump = StateRowQueries::create_state(&pool, state_group_id, 100, "Water Pump State")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let _pump =
+            StateRowQueries::create_state(&mut conn, state_group_id, 100, "Water Pump State")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let _motor =
-            StateRowQueries::create_state(&pool, state_group_id, 200, "Electric Motor State")
+            StateRowQueries::create_state(&mut conn, state_group_id, 200, "Electric Motor State")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let _valve =
-            StateRowQueries::create_state(&pool, state_group_id, 300, "Control Valve State")
+            StateRowQueries::create_state(&mut conn, state_group_id, 300, "Control Valve State")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1244,12 +1355,13 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_states_for_group(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
 
-        let state1 = StateRowQueries::create_state(&pool, state_group_id, 100, "State 1")
+        let state1 = StateRowQueries::create_state(&mut conn, state_group_id, 100, "State 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let state2 = StateRowQueries::create_state(&pool, state_group_id, 200, "State 2")
+        let state2 = StateRowQueries::create_state(&mut conn, state_group_id, 200, "State 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1277,15 +1389,16 @@ mod tests {
 
     #[sqlx::test]
     async fn test_get_states_by_code_range(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
 
-        let _state1 = StateRowQueries::create_state(&pool, state_group_id, 100, "State 1")
+        let _state1 = StateRowQueries::create_state(&mut conn, state_group_id, 100, "State 1")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let _state2 = StateRowQueries::create_state(&pool, state_group_id, 200, "State 2")
+        let _state2 = StateRowQueries::create_state(&mut conn, state_group_id, 200, "State 2")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
-        let _state3 = StateRowQueries::create_state(&pool, state_group_id, 300, "State 3")
+        let _state3 = StateRowQueries::create_state(&mut conn, state_group_id, 300, "State 3")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1320,8 +1433,9 @@ mod tests {
 
     #[sqlx::test]
     async fn test_update_preserves_timestamps(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         let state_group_id = create_test_state_group(&pool).await?;
-        let created = StateRowQueries::create_state(&pool, state_group_id, 100, "Test State")
+        let created = StateRowQueries::create_state(&mut conn, state_group_id, 100, "Test State")
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         let original_created_at = created.created_at;
@@ -1330,7 +1444,7 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
 
         let updated =
-            StateRowQueries::update_state_description(&pool, created.state_id, "Updated State")
+            StateRowQueries::update_state_description(&mut conn, created.state_id, "Updated State")
                 .await
                 .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
@@ -1352,15 +1466,17 @@ mod tests {
 
     #[sqlx::test]
     async fn test_multiple_operations_sequence(pool: PgPool) -> sqlx::Result<()> {
+        let mut conn = pool.acquire().await?;
         // Test a sequence of operations to ensure they work together
 
         let group1_id = create_test_state_group(&pool).await?;
         let group2_id = create_test_state_group(&pool).await?;
 
         // Create
-        let created = StateRowQueries::create_state(&pool, group1_id, 100, "Sequence Test State")
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let created =
+            StateRowQueries::create_state(&mut conn, group1_id, 100, "Sequence Test State")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
 
         // Verify it exists
         let exists = StateRowQueries::exists(&pool, created.state_id).await?;
@@ -1378,7 +1494,7 @@ mod tests {
 
         // Update description
         let updated_desc = StateRowQueries::update_state_description(
-            &pool,
+            &mut conn,
             created.state_id,
             "Updated Sequence State",
         )
@@ -1387,15 +1503,16 @@ mod tests {
         assert!(updated_desc.is_some());
 
         // Update code
-        let updated_code = StateRowQueries::update_state_code(&pool, created.state_id, 200)
+        let updated_code = StateRowQueries::update_state_code(&mut conn, created.state_id, 200)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(updated_code.is_some());
 
         // Update group
-        let updated_group = StateRowQueries::update_state_group(&pool, created.state_id, group2_id)
-            .await
-            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let updated_group =
+            StateRowQueries::update_state_group(&mut conn, created.state_id, group2_id)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(updated_group.is_some());
 
         // Verify final state
@@ -1415,7 +1532,7 @@ mod tests {
         );
 
         // Delete
-        let deleted = StateRowQueries::delete_state(&pool, created.state_id)
+        let deleted = StateRowQueries::delete_state(&mut conn, created.state_id)
             .await
             .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
         assert!(deleted);
@@ -1426,4 +1543,83 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn test_usage_stats_runtime(pool: PgPool) -> sqlx::Result<()> {
+        let state_group_id = create_test_state_group(&pool).await?;
+        let running = create_state_raw(&pool, state_group_id, 1, "Running").await?;
+        let starved = create_state_raw(&pool, state_group_id, 2, "Starved").await?;
+        let retired = create_state_raw(&pool, state_group_id, 9, "Retired").await?;
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        // running for 40 minutes, then starved for 20
+        let t0 = (OffsetDateTime::now_utc() - time::Duration::hours(2))
+            .replace_nanosecond(0)
+            .unwrap();
+        for (state, started_at, ended_at) in [
+            (&running, t0, t0 + time::Duration::minutes(40)),
+            (
+                &starved,
+                t0 + time::Duration::minutes(40),
+                t0 + time::Duration::minutes(60),
+            ),
+        ] {
+            sqlx::query!(
+                r#"INSERT INTO core.equipment_state_history
+                       (equipment_id, state_id, state_code, started_at, ended_at)
+                   VALUES ($1, $2, $3, $4, $5)"#,
+                equipment_id,
+                state.state_id,
+                state.state_code,
+                started_at,
+                ended_at
+            )
+            .execute(&pool)
+            .await?;
+        }
+        // deleted states are not listed
+        StateRowQueries::delete_state(&mut *pool.acquire().await?, retired.state_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let from = Some(t0);
+        let to = Some(t0 + time::Duration::hours(1));
+        let stats = StateRowQueries::get_usage_stats(&pool, from, to, None).await?;
+        let group = stats
+            .state_groups
+            .iter()
+            .find(|g| g.state_group_id == state_group_id)
+            .expect("the test group is listed");
+        assert_eq!(group.state_count, 2);
+        assert_eq!(group.runtime_seconds, 3600.0);
+        assert_eq!(group.states[0].state_code, 1);
+        assert_eq!(group.states[0].runtime_seconds, 2400.0);
+        assert_eq!(group.states[0].equipment_count, 1);
+        assert_eq!(group.states[1].runtime_seconds, 1200.0);
+        assert_eq!(
+            group.states[1].last_used_at.map(|t| t.timestamp()),
+            Some((t0 + time::Duration::minutes(60)).unix_timestamp())
+        );
+        // the seeded default group has no history
+        assert_eq!(stats.summary.unused_states, 11);
+
+        // one state brings only its own group
+        let stats =
+            StateRowQueries::get_usage_stats(&pool, from, to, Some(starved.state_id)).await?;
+        assert_eq!(stats.state_groups.len(), 1);
+        assert_eq!(stats.state_groups[0].states.len(), 1);
+        assert_eq!(stats.state_groups[0].runtime_seconds, 1200.0);
+
+        let result =
+            StateRowQueries::get_usage_stats(&pool, None, None, Some(retired.state_id)).await;
+        assert!(matches!(result, Err(ProcedureError::NotFound(_))));
+
+        Ok(())
+    }
 }
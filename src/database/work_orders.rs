@@ -0,0 +1,196 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WorkOrderRow {
+    pub work_order_id: Uuid,
+    pub work_order_name: String,
+    pub product_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub work_order_metadata: Value,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+pub struct WorkOrderQueries;
+
+impl WorkOrderQueries {
+    pub async fn get_by_id(
+        db: &PgPool,
+        work_order_id: Uuid,
+    ) -> Result<Option<WorkOrderRow>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkOrderRow,
+            r#"SELECT work_order_id, work_order_name, product_id, planned_quantity,
+                      work_order_metadata, created_at, updated_at
+               FROM operations.work_order
+               WHERE work_order_id = $1"#,
+            work_order_id
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn create(
+        db: &PgPool,
+        work_order_name: &str,
+        product_id: Uuid,
+        planned_quantity: Option<i32>,
+        work_order_metadata: Option<&Value>,
+    ) -> Result<WorkOrderRow, sqlx::Error> {
+        let default_metadata = serde_json::json!({});
+        let metadata = work_order_metadata.unwrap_or(&default_metadata);
+
+        sqlx::query_as!(
+            WorkOrderRow,
+            r#"INSERT INTO operations.work_order
+                   (work_order_name, product_id, planned_quantity, work_order_metadata)
+               VALUES ($1, $2, $3, $4)
+               RETURNING work_order_id, work_order_name, product_id, planned_quantity,
+                         work_order_metadata, created_at, updated_at"#,
+            work_order_name,
+            product_id,
+            planned_quantity,
+            metadata
+        )
+        .fetch_one(db)
+        .await
+    }
+
+    pub async fn update(
+        db: &PgPool,
+        work_order_id: Uuid,
+        work_order_name: &str,
+        planned_quantity: Option<i32>,
+    ) -> Result<Option<WorkOrderRow>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkOrderRow,
+            r#"UPDATE operations.work_order
+               SET work_order_name = $2, planned_quantity = $3
+               WHERE work_order_id = $1
+               RETURNING work_order_id, work_order_name, product_id, planned_quantity,
+                         work_order_metadata, created_at, updated_at"#,
+            work_order_id,
+            work_order_name,
+            planned_quantity
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn update_metadata(
+        db: &PgPool,
+        work_order_id: Uuid,
+        metadata: &Value,
+    ) -> Result<Option<WorkOrderRow>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkOrderRow,
+            r#"UPDATE operations.work_order
+               SET work_order_metadata = $2
+               WHERE work_order_id = $1
+               RETURNING work_order_id, work_order_name, product_id, planned_quantity,
+                         work_order_metadata, created_at, updated_at"#,
+            work_order_id,
+            metadata
+        )
+        .fetch_optional(db)
+        .await
+    }
+
+    pub async fn delete(db: &PgPool, work_order_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM operations.work_order WHERE work_order_id = $1",
+            work_order_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn exists(db: &PgPool, work_order_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.work_order WHERE work_order_id = $1
+            )"#,
+            work_order_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Check for a work order with the same (case insensitive) name, optionally ignoring one
+    pub async fn name_exists(
+        db: &PgPool,
+        work_order_name: &str,
+        exclude_work_order_id: Option<Uuid>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.work_order
+                WHERE work_order_name = $1
+                  AND ($2::uuid IS NULL OR work_order_id <> $2)
+            )"#,
+            work_order_name,
+            exclude_work_order_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+    /// Check if any job belongs to the work order
+    pub async fn has_jobs(db: &PgPool, work_order_id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM operations.job WHERE work_order_id = $1
+            )"#,
+            work_order_id
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::products::ProductQueries;
+
+    #[sqlx::test]
+    async fn test_work_order_crud(pool: PgPool) -> sqlx::Result<()> {
+        let product = ProductQueries::create(&pool, "Widget", None, None).await?;
+
+        let created =
+            WorkOrderQueries::create(&pool, "WO-1", product.product_id, Some(100), None).await?;
+        assert_eq!(created.planned_quantity, Some(100));
+        assert!(ProductQueries::is_in_use(&pool, product.product_id).await?);
+        assert!(WorkOrderQueries::name_exists(&pool, "wo-1", None).await?);
+
+        // quantities must be positive
+        assert!(
+            WorkOrderQueries::create(&pool, "WO-2", product.product_id, Some(0), None)
+                .await
+                .is_err()
+        );
+
+        let updated = WorkOrderQueries::update(&pool, created.work_order_id, "WO-1A", None)
+            .await?
+            .expect("work order should exist");
+        assert_eq!(updated.work_order_name, "WO-1A");
+        assert!(updated.planned_quantity.is_none());
+
+        assert!(!WorkOrderQueries::has_jobs(&pool, created.work_order_id).await?);
+        assert!(WorkOrderQueries::delete(&pool, created.work_order_id).await?);
+        assert!(!WorkOrderQueries::exists(&pool, created.work_order_id).await?);
+
+        Ok(())
+    }
+}
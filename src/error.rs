@@ -0,0 +1,106 @@
+use thiserror::Error;
+
+/// Errors returned by the services so the http and gRPC layers can tell the failure
+/// cases apart without matching on messages
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{0}")]
+    NotFound(String),
+    /// duplicate names and similar clashes with existing data
+    #[error("{0}")]
+    Conflict(String),
+    /// bad input, including references to entities that don't exist
+    #[error("{0}")]
+    Validation(String),
+    /// missing, unknown or revoked credentials
+    #[error("{0}")]
+    Unauthorized(String),
+    /// valid credentials without the scope the request needs
+    #[error("{0}")]
+    Forbidden(String),
+    /// the message is safe to show to clients, the source is only logged
+    #[error("{message}")]
+    Database {
+        message: String,
+        #[source]
+        source: sqlx::Error,
+    },
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn database(message: impl Into<String>, source: sqlx::Error) -> Self {
+        AppError::Database {
+            message: message.into(),
+            source,
+        }
+    }
+
+    /// Stable machine readable code sent to clients along with the message
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Validation(_) => "validation_error",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::Database { .. } => "database_error",
+        }
+    }
+}
+
+/// `context` for sqlx results, works like anyhow's but keeps the error typed
+pub trait DatabaseContext<T> {
+    fn context(self, message: &str) -> AppResult<T>;
+
+    fn with_context<F>(self, message: F) -> AppResult<T>
+    where
+        F: FnOnce() -> String;
+}
+
+impl<T> DatabaseContext<T> for Result<T, sqlx::Error> {
+    fn context(self, message: &str) -> AppResult<T> {
+        self.with_context(|| message.to_string())
+    }
+
+    fn with_context<F>(self, message: F) -> AppResult<T>
+    where
+        F: FnOnce() -> String,
+    {
+        self.map_err(|source| AppError::database(message(), source))
+    }
+}
+
+/// True when a query failed on a unique constraint or index, for clashes the queries can't
+/// check for up front such as restoring a row whose name has been taken since
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|error| error.is_unique_violation())
+}
+
+/// True when a query failed on an exclusion constraint (sqlstate 23P01), which sqlx doesn't
+/// have its own kind for
+pub fn is_exclusion_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|error| error.code())
+        .is_some_and(|code| code == "23P01")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_database_context_keeps_source() {
+        let result: Result<(), sqlx::Error> = Err(sqlx::Error::RowNotFound);
+        let err = result.context("Failed to fetch mode").unwrap_err();
+
+        assert_eq!(err.code(), "database_error");
+        assert_eq!(err.to_string(), "Failed to fetch mode");
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::grpc::to_status;
+use crate::http;
+use crate::models::app::Scope;
+use crate::services::api_key_service::ApiKeyService;
+use crate::services::jwt_service::JwtService;
+use axum::{
+    extract::{Extension, Request},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+
+/// Reflection only describes the protos, clients need it before they have a token
+const REFLECTION_PREFIX: &str = "/grpc.reflection.";
+
+/// Rpcs that only look, everything else changes data
+const READ_RPC_PREFIXES: [&str; 3] = ["Get", "List", "Watch"];
+
+/// Scope an rpc needs, mapped onto the http method it corresponds to so both servers
+/// share `http::auth::required_scope`
+pub fn required_scope(path: &str) -> Scope {
+    let rpc = path.rsplit('/').next().unwrap_or_default();
+    let method = if READ_RPC_PREFIXES
+        .iter()
+        .any(|prefix| rpc.starts_with(prefix))
+    {
+        Method::GET
+    } else {
+        Method::POST
+    };
+    http::auth::required_scope(&method, path)
+}
+
+/// Middleware for every gRPC service. Takes the same bearer tokens as the http api and
+/// adds the principal to the request extensions, see `grpc::actor`. Rejections are sent
+/// as a grpc status.
+pub async fn authenticate(
+    Extension(api_keys): Extension<ApiKeyService>,
+    jwt: Option<Extension<JwtService>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    if path.starts_with(REFLECTION_PREFIX) {
+        return next.run(request).await;
+    }
+    let required = required_scope(path);
+
+    let token = http::auth::bearer_token(request.headers()).map(str::to_string);
+    match http::auth::authorize(&api_keys, jwt.as_deref(), token.as_deref(), required).await {
+        Ok(principal) => {
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        Err(e) => to_status(e, "authenticate").into_http(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use crate::grpc::equipment_types::{
+        EquipmentTypesGrpcService,
+        equipment_type_proto::{
+            CreateEquipmentTypeRequest, equipment_types_server::EquipmentTypesServer,
+        },
+    };
+    use crate::services::equipment_type_service::EquipmentTypeService;
+    use axum::{Router, body::Body, middleware};
+    use prost::Message;
+    use sqlx::PgPool;
+    use tonic::{Code, Status, service::Routes};
+    use tower::ServiceExt;
+
+    const CREATE: &str = "/equipment_types.EquipmentTypes/CreateEquipmentType";
+
+    fn app(pool: PgPool, api_keys: ApiKeyService) -> Router {
+        Routes::new(EquipmentTypesServer::new(EquipmentTypesGrpcService::new(
+            EquipmentTypeService::new(pool),
+        )))
+        .into_axum_router()
+        .layer(middleware::from_fn(authenticate))
+        .layer(Extension(api_keys))
+    }
+
+    // a unary call, the message behind the 5 byte grpc frame header
+    fn call(path: &str, token: Option<&str>, message: impl Message) -> Request {
+        let message = message.encode_to_vec();
+        let mut body = vec![0];
+        body.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        body.extend_from_slice(&message);
+
+        let mut request = Request::builder()
+            .method("POST")
+            .uri(path)
+            .header("content-type", "application/grpc")
+            .header("te", "trailers");
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        request.body(Body::from(body)).unwrap()
+    }
+
+    fn code(response: &Response) -> Option<Code> {
+        Status::from_header_map(response.headers()).map(|status| status.code())
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(
+            required_scope("/equipment_types.EquipmentTypes/ListEquipmentTypes"),
+            Scope::Read
+        );
+        assert_eq!(
+            required_scope("/equipment_events.EquipmentEvents/WatchEquipment"),
+            Scope::Read
+        );
+        assert_eq!(required_scope("/modes.Modes/DeleteMode"), Scope::Write);
+    }
+
+    #[sqlx::test]
+    async fn test_grpc_calls_need_a_token_with_scope(pool: PgPool) -> sqlx::Result<()> {
+        let to_sqlx = |e: AppError| sqlx::Error::Protocol(e.to_string());
+        let api_keys = ApiKeyService::new(pool.clone());
+        let (_, reader) = api_keys
+            .issue("Line 1 HMI", &[Scope::Read])
+            .await
+            .map_err(to_sqlx)?;
+        let (_, writer) = api_keys
+            .issue("Engineering", &[Scope::Write])
+            .await
+            .map_err(to_sqlx)?;
+        let app = app(pool.clone(), api_keys);
+        let create = || CreateEquipmentTypeRequest {
+            type_name: "Filler".to_string(),
+        };
+
+        let response = app
+            .clone()
+            .oneshot(call(CREATE, None, create()))
+            .await
+            .unwrap();
+        assert_eq!(code(&response), Some(Code::Unauthenticated));
+
+        let response = app
+            .clone()
+            .oneshot(call(CREATE, Some(&reader), create()))
+            .await
+            .unwrap();
+        assert_eq!(code(&response), Some(Code::PermissionDenied));
+
+        let response = app
+            .oneshot(call(CREATE, Some(&writer), create()))
+            .await
+            .unwrap();
+        assert_eq!(code(&response), None);
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        // the change is recorded as made by the key
+        let actor = sqlx::query_scalar!(
+            r#"SELECT actor as "actor!" FROM app.audit_log
+               WHERE entity_type = 'equipment_type' AND after->>'type_name' = 'Filler'"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(actor, "api-key:Engineering");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use crate::database::events::EventType;
+use crate::grpc::{format_timestamp, parse_uuid, to_status};
+use crate::services::event_service::{Event, EventData, EventFilter, EventService};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::{debug, info, warn};
+
+pub mod equipment_event_proto {
+    tonic::include_proto!("equipment_events");
+}
+
+use equipment_event_proto::{
+    ChangeKind, ModeChange, StateChange, WatchEquipmentRequest, equipment_event,
+    equipment_events_server::EquipmentEvents,
+};
+
+/// Events buffered per stream before a slow client holds up its own forwarding task
+const STREAM_BUFFER: usize = 64;
+
+/// Only mode and state changes have a proto form, entity changes give None
+fn to_proto(event: Event) -> Option<equipment_event_proto::EquipmentEvent> {
+    let equipment_id = event.equipment_id?;
+    let (history_id, changed_at, changed_by, change) = match event.data {
+        EventData::EquipmentMode(mode) => (
+            mode.history_id,
+            mode.set_at,
+            mode.set_by,
+            equipment_event::Change::Mode(ModeChange {
+                mode_id: mode.mode_id.to_string(),
+                mode_description: mode.mode_description,
+                mode_group_id: mode.mode_group_id.to_string(),
+            }),
+        ),
+        EventData::EquipmentState(state) => (
+            state.history_id,
+            state.started_at,
+            state.updated_by,
+            equipment_event::Change::State(StateChange {
+                state_id: state.state_id.to_string(),
+                state_code: state.state_code,
+                state_description: state.state_description,
+                state_group_id: state.state_group_id.to_string(),
+                state_value: state.state_value.map(|v| v.to_string()),
+            }),
+        ),
+        EventData::None => return None,
+    };
+
+    Some(equipment_event_proto::EquipmentEvent {
+        equipment_id: equipment_id.to_string(),
+        path: event.path.iter().map(ToString::to_string).collect(),
+        history_id: history_id.to_string(),
+        changed_at: format_timestamp(Some(changed_at)).unwrap_or_default(),
+        changed_by,
+        change: Some(change),
+    })
+}
+
+fn parse_filter(request: &WatchEquipmentRequest) -> Result<EventFilter, Status> {
+    let equipment_id = request
+        .equipment_id
+        .as_deref()
+        .map(|id| parse_uuid(id, "equipment_id"))
+        .transpose()?;
+
+    let mut event_types = Vec::new();
+    for kind in &request.kinds {
+        match ChangeKind::try_from(*kind) {
+            Ok(ChangeKind::Mode) => event_types.push(EventType::EquipmentMode),
+            Ok(ChangeKind::State) => event_types.push(EventType::EquipmentState),
+            _ => {
+                return Err(Status::invalid_argument(format!(
+                    "invalid change kind {}",
+                    kind
+                )));
+            }
+        }
+    }
+
+    // this stream only carries runtime changes, no kinds means both of them
+    if event_types.is_empty() {
+        event_types = vec![EventType::EquipmentMode, EventType::EquipmentState];
+    }
+
+    Ok(EventFilter {
+        equipment_id,
+        event_types,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentEventsGrpcService {
+    service: EventService,
+}
+
+impl EquipmentEventsGrpcService {
+    pub fn new(service: EventService) -> Self {
+        Self { service }
+    }
+}
+
+#[tonic::async_trait]
+impl EquipmentEvents for EquipmentEventsGrpcService {
+    type WatchEquipmentStream =
+        ReceiverStream<Result<equipment_event_proto::EquipmentEvent, Status>>;
+
+    async fn watch_equipment(
+        &self,
+        request: Request<WatchEquipmentRequest>,
+    ) -> Result<Response<Self::WatchEquipmentStream>, Status> {
+        let filter = parse_filter(request.get_ref())?;
+        let mut events = self
+            .service
+            .subscribe(&filter)
+            .await
+            .map_err(|e| to_status(e, "watch equipment"))?;
+        info!("Client watching equipment events: {:?}", filter);
+
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                let message = match events.recv().await {
+                    Ok(event) if filter.matches(&event) => match to_proto(event) {
+                        Some(event) => Ok(event),
+                        None => continue,
+                    },
+                    Ok(_) => continue,
+                    // the client fell too far behind, end the stream so it resubscribes and
+                    // reads the current state again instead of silently missing changes
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Equipment event stream lagged by {} events", missed);
+                        Err(Status::data_loss(format!(
+                            "stream fell behind and missed {} events",
+                            missed
+                        )))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let end = message.is_err();
+                if tx.send(message).await.is_err() || end {
+                    break;
+                }
+            }
+            debug!("Equipment event stream closed");
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::equipment_mode_service::EquipmentModeService;
+    use sqlx::PgPool;
+    use std::time::Duration;
+    use tokio_stream::StreamExt;
+    use tonic::Code;
+    use uuid::Uuid;
+
+    #[sqlx::test]
+    async fn test_watch_equipment_streams_mode_changes(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+               SELECT $1, mode_group_id FROM core.mode_group
+               WHERE mode_group_name = 'Default MES Mode Group'"#,
+            equipment_id
+        )
+        .execute(&pool)
+        .await?;
+        let production = sqlx::query_scalar!(
+            "SELECT mode_id FROM core.mode WHERE mode_description = 'production'"
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        let events = EventService::new(pool.clone());
+        let listener = events.clone();
+        tokio::spawn(async move { listener.listen().await });
+        let grpc = EquipmentEventsGrpcService::new(events);
+
+        let invalid = grpc
+            .watch_equipment(Request::new(WatchEquipmentRequest {
+                equipment_id: Some(Uuid::new_v4().to_string()),
+                kinds: vec![],
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(invalid.code(), Code::NotFound);
+
+        let mut stream = grpc
+            .watch_equipment(Request::new(WatchEquipmentRequest {
+                equipment_id: Some(equipment_id.to_string()),
+                kinds: vec![ChangeKind::Mode as i32],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // the listener connects in the background, keep setting the mode until it is up
+        let modes = EquipmentModeService::new(pool);
+        let mut received = None;
+        for _ in 0..25 {
+            modes
+                .set_mode(equipment_id, production, Some("operator"))
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+            if let Ok(Some(event)) =
+                tokio::time::timeout(Duration::from_millis(200), stream.next()).await
+            {
+                received = Some(event.unwrap());
+                break;
+            }
+        }
+        let event = received.expect("mode change should be streamed");
+
+        assert_eq!(event.equipment_id, equipment_id.to_string());
+        assert_eq!(event.changed_by.as_deref(), Some("operator"));
+        match event.change {
+            Some(equipment_event::Change::Mode(mode)) => {
+                assert_eq!(mode.mode_description, "production")
+            }
+            other => panic!("expected a mode change, got {:?}", other),
+        }
+
+        Ok(())
+    }
+}
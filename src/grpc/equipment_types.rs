@@ -0,0 +1,187 @@
+use crate::grpc::{actor, format_timestamp, pagination, parse_uuid, to_status};
+use crate::services::equipment_type_service::{EquipmentType, EquipmentTypeService};
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+pub mod equipment_type_proto {
+    tonic::include_proto!("equipment_types");
+}
+
+use equipment_type_proto::{
+    CreateEquipmentTypeRequest, DeleteEquipmentTypeRequest, DeleteEquipmentTypeResponse,
+    GetEquipmentTypeRequest, ListEquipmentTypesRequest, ListEquipmentTypesResponse,
+    UpdateEquipmentTypeRequest, equipment_types_server::EquipmentTypes,
+};
+
+impl From<EquipmentType> for equipment_type_proto::EquipmentType {
+    fn from(equipment_type: EquipmentType) -> Self {
+        Self {
+            type_id: equipment_type.type_id.to_string(),
+            type_name: equipment_type.type_name,
+            created_at: format_timestamp(equipment_type.created_at),
+            updated_at: format_timestamp(equipment_type.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentTypesGrpcService {
+    service: EquipmentTypeService,
+}
+
+impl EquipmentTypesGrpcService {
+    pub fn new(service: EquipmentTypeService) -> Self {
+        Self { service }
+    }
+}
+
+#[tonic::async_trait]
+impl EquipmentTypes for EquipmentTypesGrpcService {
+    async fn get_equipment_type(
+        &self,
+        request: Request<GetEquipmentTypeRequest>,
+    ) -> Result<Response<equipment_type_proto::EquipmentType>, Status> {
+        let type_id = parse_uuid(&request.get_ref().type_id, "type_id")?;
+        let equipment_type = self
+            .service
+            .get_by_id(type_id)
+            .await
+            .map_err(|e| to_status(e, "retrieve equipment type"))?;
+        Ok(Response::new(equipment_type.into()))
+    }
+
+    async fn list_equipment_types(
+        &self,
+        request: Request<ListEquipmentTypesRequest>,
+    ) -> Result<Response<ListEquipmentTypesResponse>, Status> {
+        let request = request.into_inner();
+        let (offset, limit) = pagination(request.offset, request.limit);
+        let (equipment_types, total) = self
+            .service
+            .get_paginated(offset, limit)
+            .await
+            .map_err(|e| to_status(e, "retrieve equipment types"))?;
+
+        Ok(Response::new(ListEquipmentTypesResponse {
+            equipment_types: equipment_types.into_iter().map(Into::into).collect(),
+            total,
+            offset,
+            limit,
+        }))
+    }
+
+    async fn create_equipment_type(
+        &self,
+        request: Request<CreateEquipmentTypeRequest>,
+    ) -> Result<Response<equipment_type_proto::EquipmentType>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let equipment_type = service
+            .create(&request.get_ref().type_name)
+            .await
+            .map_err(|e| to_status(e, "create equipment type"))?;
+        info!("Created equipment type: {}", equipment_type.type_name);
+        Ok(Response::new(equipment_type.into()))
+    }
+
+    async fn update_equipment_type(
+        &self,
+        request: Request<UpdateEquipmentTypeRequest>,
+    ) -> Result<Response<equipment_type_proto::EquipmentType>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let request = request.into_inner();
+        let type_id = parse_uuid(&request.type_id, "type_id")?;
+        let equipment_type = service
+            .update(type_id, &request.type_name)
+            .await
+            .map_err(|e| to_status(e, "update equipment type"))?;
+        info!("Updated equipment type: {}", equipment_type.type_name);
+        Ok(Response::new(equipment_type.into()))
+    }
+
+    async fn delete_equipment_type(
+        &self,
+        request: Request<DeleteEquipmentTypeRequest>,
+    ) -> Result<Response<DeleteEquipmentTypeResponse>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let type_id = parse_uuid(&request.get_ref().type_id, "type_id")?;
+        service
+            .delete(type_id)
+            .await
+            .map_err(|e| to_status(e, "delete equipment type"))?;
+        info!("Deleted equipment type: {}", type_id);
+        Ok(Response::new(DeleteEquipmentTypeResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+    use tonic::Code;
+
+    #[sqlx::test]
+    async fn test_equipment_types_rpcs(pool: PgPool) -> sqlx::Result<()> {
+        let grpc = EquipmentTypesGrpcService::new(EquipmentTypeService::new(pool));
+
+        let created = grpc
+            .create_equipment_type(Request::new(CreateEquipmentTypeRequest {
+                type_name: "Press".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(created.type_name, "Press");
+        assert!(created.created_at.is_some());
+
+        let fetched = grpc
+            .get_equipment_type(Request::new(GetEquipmentTypeRequest {
+                type_id: created.type_id.clone(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(fetched, created);
+
+        let duplicate = grpc
+            .create_equipment_type(Request::new(CreateEquipmentTypeRequest {
+                type_name: "Press".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(duplicate.code(), Code::AlreadyExists);
+
+        let list = grpc
+            .list_equipment_types(Request::new(ListEquipmentTypesRequest {
+                offset: None,
+                limit: Some(2),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(list.equipment_types.len(), 2);
+        assert!(list.total > 2);
+
+        let invalid = grpc
+            .get_equipment_type(Request::new(GetEquipmentTypeRequest {
+                type_id: "not-a-uuid".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(invalid.code(), Code::InvalidArgument);
+
+        grpc.delete_equipment_type(Request::new(DeleteEquipmentTypeRequest {
+            type_id: created.type_id.clone(),
+        }))
+        .await
+        .unwrap();
+        let missing = grpc
+            .get_equipment_type(Request::new(GetEquipmentTypeRequest {
+                type_id: created.type_id,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), Code::NotFound);
+
+        Ok(())
+    }
+}
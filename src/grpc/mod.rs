@@ -0,0 +1,143 @@
+// every rpc returns tonic::Status, boxing it in the helpers would only add noise
+#![allow(clippy::result_large_err)]
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::models::app::Principal;
+use crate::services::api_key_service::ApiKeyService;
+use crate::services::equipment_type_service::EquipmentTypeService;
+use crate::services::event_service::EventService;
+use crate::services::jwt_service::JwtService;
+use crate::services::mode_group_service::ModeGroupService;
+use crate::services::mode_service::ModeService;
+use crate::shutdown::Shutdown;
+use anyhow::Context;
+use axum::{Extension, middleware};
+use sqlx::PgPool;
+use std::net::SocketAddr;
+use time::OffsetDateTime;
+use tonic::service::Routes;
+use tonic::transport::Server;
+use tonic::{Request, Status};
+use tower::ServiceBuilder;
+use tracing::error;
+use uuid::Uuid;
+
+pub mod auth;
+pub mod equipment_events;
+pub mod equipment_types;
+pub mod mode_groups;
+pub mod modes;
+
+use equipment_events::{
+    EquipmentEventsGrpcService,
+    equipment_event_proto::equipment_events_server::EquipmentEventsServer,
+};
+use equipment_types::{
+    EquipmentTypesGrpcService, equipment_type_proto::equipment_types_server::EquipmentTypesServer,
+};
+use mode_groups::{ModeGroupsGrpcService, mode_group_proto::mode_groups_server::ModeGroupsServer};
+use modes::{ModesGrpcService, mode_proto::modes_server::ModesServer};
+
+/// Encoded descriptors of every proto, served through reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("gatherer_descriptor");
+
+const DEFAULT_LIMIT: i64 = 50;
+
+pub async fn serve(
+    config: Config,
+    db: PgPool,
+    events: EventService,
+    shutdown: Shutdown,
+) -> anyhow::Result<()> {
+    let addr: SocketAddr = config
+        .grpc_bind_address
+        .parse()
+        .with_context(|| format!("invalid grpc bind address '{}'", config.grpc_bind_address))?;
+
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .build_v1()
+        .context("failed to build gRPC reflection service")?;
+
+    let api_key_service = ApiKeyService::new(db.clone());
+    let jwt_service = JwtService::from_config(&config.oidc).await?;
+
+    let mut routes = Routes::builder();
+    routes
+        .add_service(reflection)
+        .add_service(EquipmentEventsServer::new(EquipmentEventsGrpcService::new(
+            events,
+        )))
+        .add_service(EquipmentTypesServer::new(EquipmentTypesGrpcService::new(
+            EquipmentTypeService::new(db.clone()),
+        )))
+        .add_service(ModeGroupsServer::new(ModeGroupsGrpcService::new(
+            ModeGroupService::new(db.clone()),
+        )))
+        .add_service(ModesServer::new(ModesGrpcService::new(ModeService::new(
+            db,
+        ))));
+    // same api keys and tokens as the http api
+    let router = routes
+        .routes()
+        .into_axum_router()
+        .layer(middleware::from_fn(auth::authenticate))
+        .layer(
+            ServiceBuilder::new()
+                .layer(Extension(api_key_service))
+                .option_layer(jwt_service.map(Extension)),
+        );
+
+    println!("gRPC server listening on http://{}", addr);
+
+    let server = Server::builder()
+        .add_routes(Routes::from(router))
+        .serve_with_shutdown(addr, shutdown.clone().wait());
+
+    match shutdown.drain(server, config.shutdown_timeout()).await {
+        Some(result) => result.context("error running gRPC server"),
+        None => Ok(()),
+    }
+}
+
+fn parse_uuid(value: &str, field: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(value.trim())
+        .map_err(|_| Status::invalid_argument(format!("{} '{}' is not a valid uuid", field, value)))
+}
+
+/// Audit actor of the principal the auth middleware attached to the request
+fn actor<T>(request: &Request<T>) -> Option<String> {
+    request
+        .extensions()
+        .get::<Principal>()
+        .map(Principal::actor)
+}
+
+/// offset/limit with the same defaults as the http pagination
+fn pagination(offset: Option<i64>, limit: Option<i64>) -> (i64, i64) {
+    (offset.unwrap_or(0), limit.unwrap_or(DEFAULT_LIMIT))
+}
+
+fn format_timestamp(value: Option<OffsetDateTime>) -> Option<String> {
+    value.and_then(|dt| {
+        dt.format(&time::format_description::well_known::Rfc3339)
+            .ok()
+    })
+}
+
+/// Map a service error onto a grpc status. Database errors are logged and not passed on
+/// to the client.
+fn to_status(e: AppError, action: &str) -> Status {
+    match e {
+        AppError::NotFound(message) => Status::not_found(message),
+        AppError::Conflict(message) => Status::already_exists(message),
+        AppError::Validation(message) => Status::invalid_argument(message),
+        AppError::Unauthorized(message) => Status::unauthenticated(message),
+        AppError::Forbidden(message) => Status::permission_denied(message),
+        AppError::Database { message, source } => {
+            error!("Failed to {}: {}: {}", action, message, source);
+            Status::internal(format!("Failed to {}", action))
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::grpc::{actor, format_timestamp, pagination, parse_uuid, to_status};
+use crate::services::mode_group_service::{ModeGroup, ModeGroupService};
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+pub mod mode_group_proto {
+    tonic::include_proto!("mode_groups");
+}
+
+use mode_group_proto::{
+    CreateModeGroupRequest, DeleteModeGroupRequest, DeleteModeGroupResponse, GetModeGroupRequest,
+    ListModeGroupsRequest, ListModeGroupsResponse, UpdateModeGroupDescriptionRequest,
+    UpdateModeGroupNameRequest, mode_groups_server::ModeGroups,
+};
+
+impl From<ModeGroup> for mode_group_proto::ModeGroup {
+    fn from(mode_group: ModeGroup) -> Self {
+        Self {
+            mode_group_id: mode_group.mode_group_id.to_string(),
+            mode_group_name: mode_group.mode_group_name,
+            mode_group_description: mode_group.mode_group_description,
+            created_at: format_timestamp(mode_group.created_at),
+            updated_at: format_timestamp(mode_group.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModeGroupsGrpcService {
+    service: ModeGroupService,
+}
+
+impl ModeGroupsGrpcService {
+    pub fn new(service: ModeGroupService) -> Self {
+        Self { service }
+    }
+}
+
+#[tonic::async_trait]
+impl ModeGroups for ModeGroupsGrpcService {
+    async fn get_mode_group(
+        &self,
+        request: Request<GetModeGroupRequest>,
+    ) -> Result<Response<mode_group_proto::ModeGroup>, Status> {
+        let mode_group_id = parse_uuid(&request.get_ref().mode_group_id, "mode_group_id")?;
+        let mode_group = self
+            .service
+            .get_by_id(mode_group_id)
+            .await
+            .map_err(|e| to_status(e, "retrieve mode group"))?;
+        Ok(Response::new(mode_group.into()))
+    }
+
+    async fn list_mode_groups(
+        &self,
+        request: Request<ListModeGroupsRequest>,
+    ) -> Result<Response<ListModeGroupsResponse>, Status> {
+        let request = request.into_inner();
+        let (offset, limit) = pagination(request.offset, request.limit);
+        let (mode_groups, total) = self
+            .service
+            .get_paginated(offset, limit)
+            .await
+            .map_err(|e| to_status(e, "retrieve mode groups"))?;
+
+        Ok(Response::new(ListModeGroupsResponse {
+            mode_groups: mode_groups.into_iter().map(Into::into).collect(),
+            total,
+            offset,
+            limit,
+        }))
+    }
+
+    async fn create_mode_group(
+        &self,
+        request: Request<CreateModeGroupRequest>,
+    ) -> Result<Response<mode_group_proto::ModeGroup>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let request = request.into_inner();
+        let mode_group = service
+            .create(&request.mode_group_name, &request.mode_group_description)
+            .await
+            .map_err(|e| to_status(e, "create mode group"))?;
+        info!("Created mode group: {}", mode_group.mode_group_name);
+        Ok(Response::new(mode_group.into()))
+    }
+
+    async fn update_mode_group_name(
+        &self,
+        request: Request<UpdateModeGroupNameRequest>,
+    ) -> Result<Response<mode_group_proto::ModeGroup>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let request = request.into_inner();
+        let mode_group_id = parse_uuid(&request.mode_group_id, "mode_group_id")?;
+        let mode_group = service
+            .update_name(mode_group_id, &request.mode_group_name)
+            .await
+            .map_err(|e| to_status(e, "update mode group name"))?;
+        info!("Updated mode group name: {}", mode_group.mode_group_name);
+        Ok(Response::new(mode_group.into()))
+    }
+
+    async fn update_mode_group_description(
+        &self,
+        request: Request<UpdateModeGroupDescriptionRequest>,
+    ) -> Result<Response<mode_group_proto::ModeGroup>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let request = request.into_inner();
+        let mode_group_id = parse_uuid(&request.mode_group_id, "mode_group_id")?;
+        let mode_group = service
+            .update_description(mode_group_id, &request.mode_group_description)
+            .await
+            .map_err(|e| to_status(e, "update mode group description"))?;
+        info!("Updated mode group description: {}", mode_group_id);
+        Ok(Response::new(mode_group.into()))
+    }
+
+    async fn delete_mode_group(
+        &self,
+        request: Request<DeleteModeGroupRequest>,
+    ) -> Result<Response<DeleteModeGroupResponse>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let mode_group_id = parse_uuid(&request.get_ref().mode_group_id, "mode_group_id")?;
+        service
+            .delete(mode_group_id)
+            .await
+            .map_err(|e| to_status(e, "delete mode group"))?;
+        info!("Deleted mode group: {}", mode_group_id);
+        Ok(Response::new(DeleteModeGroupResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+    use tonic::Code;
+
+    #[sqlx::test]
+    async fn test_mode_groups_rpcs(pool: PgPool) -> sqlx::Result<()> {
+        let grpc = ModeGroupsGrpcService::new(ModeGroupService::new(pool));
+
+        let created = grpc
+            .create_mode_group(Request::new(CreateModeGroupRequest {
+                mode_group_name: "Packaging".to_string(),
+                mode_group_description: "Packaging line modes".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(created.mode_group_name, "Packaging");
+
+        let renamed = grpc
+            .update_mode_group_name(Request::new(UpdateModeGroupNameRequest {
+                mode_group_id: created.mode_group_id.clone(),
+                mode_group_name: "Packing".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(renamed.mode_group_name, "Packing");
+        assert_eq!(renamed.mode_group_description, "Packaging line modes");
+
+        let list = grpc
+            .list_mode_groups(Request::new(ListModeGroupsRequest {
+                offset: Some(0),
+                limit: Some(1000),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(list.total, list.mode_groups.len() as i64);
+        assert!(
+            list.mode_groups
+                .iter()
+                .any(|g| g.mode_group_id == created.mode_group_id)
+        );
+
+        let invalid = grpc
+            .list_mode_groups(Request::new(ListModeGroupsRequest {
+                offset: Some(-1),
+                limit: None,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(invalid.code(), Code::InvalidArgument);
+
+        let missing = grpc
+            .delete_mode_group(Request::new(DeleteModeGroupRequest {
+                mode_group_id: uuid::Uuid::new_v4().to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), Code::NotFound);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use crate::grpc::{actor, format_timestamp, pagination, parse_uuid, to_status};
+use crate::services::mode_service::{Mode, ModeService};
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+pub mod mode_proto {
+    tonic::include_proto!("modes");
+}
+
+use mode_proto::{
+    CreateModeRequest, DeleteModeRequest, DeleteModeResponse, GetModeRequest, ListModesRequest,
+    ListModesResponse, UpdateModeDescriptionRequest, UpdateModeGroupRequest, modes_server::Modes,
+};
+
+impl From<Mode> for mode_proto::Mode {
+    fn from(mode: Mode) -> Self {
+        Self {
+            mode_id: mode.mode_id.to_string(),
+            mode_group_id: mode.mode_group_id.to_string(),
+            mode_description: mode.mode_description,
+            created_at: format_timestamp(mode.created_at),
+            updated_at: format_timestamp(mode.updated_at),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ModesGrpcService {
+    service: ModeService,
+}
+
+impl ModesGrpcService {
+    pub fn new(service: ModeService) -> Self {
+        Self { service }
+    }
+}
+
+#[tonic::async_trait]
+impl Modes for ModesGrpcService {
+    async fn get_mode(
+        &self,
+        request: Request<GetModeRequest>,
+    ) -> Result<Response<mode_proto::Mode>, Status> {
+        let mode_id = parse_uuid(&request.get_ref().mode_id, "mode_id")?;
+        let mode = self
+            .service
+            .get_by_id(mode_id)
+            .await
+            .map_err(|e| to_status(e, "retrieve mode"))?;
+        Ok(Response::new(mode.into()))
+    }
+
+    async fn list_modes(
+        &self,
+        request: Request<ListModesRequest>,
+    ) -> Result<Response<ListModesResponse>, Status> {
+        let request = request.into_inner();
+        let (offset, limit) = pagination(request.offset, request.limit);
+        let result = match request.mode_group_id {
+            Some(mode_group_id) => {
+                let mode_group_id = parse_uuid(&mode_group_id, "mode_group_id")?;
+                self.service
+                    .get_paginated_by_mode_group(mode_group_id, offset, limit)
+                    .await
+            }
+            None => self.service.get_paginated(offset, limit).await,
+        };
+        let (modes, total) = result.map_err(|e| to_status(e, "retrieve modes"))?;
+
+        Ok(Response::new(ListModesResponse {
+            modes: modes.into_iter().map(Into::into).collect(),
+            total,
+            offset,
+            limit,
+        }))
+    }
+
+    async fn create_mode(
+        &self,
+        request: Request<CreateModeRequest>,
+    ) -> Result<Response<mode_proto::Mode>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let request = request.into_inner();
+        let mode_group_id = parse_uuid(&request.mode_group_id, "mode_group_id")?;
+        let mode = service
+            .create(mode_group_id, &request.mode_description)
+            .await
+            .map_err(|e| to_status(e, "create mode"))?;
+        info!("Created mode: {}", mode.mode_description);
+        Ok(Response::new(mode.into()))
+    }
+
+    async fn update_mode_description(
+        &self,
+        request: Request<UpdateModeDescriptionRequest>,
+    ) -> Result<Response<mode_proto::Mode>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let request = request.into_inner();
+        let mode_id = parse_uuid(&request.mode_id, "mode_id")?;
+        let mode = service
+            .update_description(mode_id, &request.mode_description)
+            .await
+            .map_err(|e| to_status(e, "update mode description"))?;
+        info!("Updated mode description: {}", mode.mode_description);
+        Ok(Response::new(mode.into()))
+    }
+
+    async fn update_mode_group(
+        &self,
+        request: Request<UpdateModeGroupRequest>,
+    ) -> Result<Response<mode_proto::Mode>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let request = request.into_inner();
+        let mode_id = parse_uuid(&request.mode_id, "mode_id")?;
+        let mode_group_id = parse_uuid(&request.mode_group_id, "mode_group_id")?;
+        let mode = service
+            .update_mode_group(mode_id, mode_group_id)
+            .await
+            .map_err(|e| to_status(e, "update mode group"))?;
+        info!("Moved mode {} to mode group {}", mode_id, mode_group_id);
+        Ok(Response::new(mode.into()))
+    }
+
+    async fn delete_mode(
+        &self,
+        request: Request<DeleteModeRequest>,
+    ) -> Result<Response<DeleteModeResponse>, Status> {
+        let service = self.service.acting_as(actor(&request));
+        let mode_id = parse_uuid(&request.get_ref().mode_id, "mode_id")?;
+        service
+            .delete(mode_id)
+            .await
+            .map_err(|e| to_status(e, "delete mode"))?;
+        info!("Deleted mode: {}", mode_id);
+        Ok(Response::new(DeleteModeResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+    use tonic::Code;
+
+    #[sqlx::test]
+    async fn test_modes_rpcs(pool: PgPool) -> sqlx::Result<()> {
+        let mode_group_id = sqlx::query_scalar!(
+            "SELECT mode_group_id FROM core.mode_group WHERE mode_group_name = 'Default MES Mode Group'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let grpc = ModesGrpcService::new(ModeService::new(pool));
+
+        let created = grpc
+            .create_mode(Request::new(CreateModeRequest {
+                mode_group_id: mode_group_id.to_string(),
+                mode_description: "changeover".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(created.mode_description, "changeover");
+
+        let duplicate = grpc
+            .create_mode(Request::new(CreateModeRequest {
+                mode_group_id: mode_group_id.to_string(),
+                mode_description: "changeover".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(duplicate.code(), Code::AlreadyExists);
+
+        let unknown_group = grpc
+            .create_mode(Request::new(CreateModeRequest {
+                mode_group_id: uuid::Uuid::new_v4().to_string(),
+                mode_description: "changeover".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(unknown_group.code(), Code::InvalidArgument);
+
+        let list = grpc
+            .list_modes(Request::new(ListModesRequest {
+                mode_group_id: Some(mode_group_id.to_string()),
+                offset: None,
+                limit: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(list.limit, 50);
+        assert!(
+            list.modes
+                .iter()
+                .all(|m| m.mode_group_id == created.mode_group_id)
+        );
+        assert!(list.modes.iter().any(|m| m.mode_id == created.mode_id));
+
+        let updated = grpc
+            .update_mode_description(Request::new(UpdateModeDescriptionRequest {
+                mode_id: created.mode_id.clone(),
+                mode_description: "setup".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(updated.mode_description, "setup");
+
+        grpc.delete_mode(Request::new(DeleteModeRequest {
+            mode_id: created.mode_id.clone(),
+        }))
+        .await
+        .unwrap();
+        let missing = grpc
+            .get_mode(Request::new(GetModeRequest {
+                mode_id: created.mode_id,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(missing.code(), Code::NotFound);
+
+        Ok(())
+    }
+}
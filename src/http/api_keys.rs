@@ -0,0 +1,211 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::{ApiKey, Principal, Scope};
+use crate::services::api_key_service::ApiKeyService;
+use axum::{
+    Json, Router,
+    extract::{Extension, Path},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// api key administration, needs the admin scope
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/admin/api-keys",
+            get(get_all_api_keys).post(issue_api_key),
+        )
+        .route("/api/v1/admin/api-keys/revoke/{id}", post(revoke_api_key))
+}
+
+// request/response dtos
+#[derive(Serialize)]
+pub struct ApiKeyResponse {
+    pub api_key_id: Uuid,
+    pub key_name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<Scope>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_used_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub revoked_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+/// The key is only ever sent in this response
+#[derive(Serialize)]
+pub struct IssuedApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+    pub key: String,
+}
+
+#[derive(Deserialize)]
+pub struct IssueApiKeyRequest {
+    pub key_name: String,
+    pub scopes: Vec<String>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(api_key: ApiKey) -> Self {
+        Self {
+            api_key_id: api_key.api_key_id,
+            key_name: api_key.key_name,
+            key_prefix: api_key.key_prefix,
+            scopes: api_key.scopes,
+            last_used_at: api_key.last_used_at,
+            revoked_at: api_key.revoked_at,
+            created_at: api_key.created_at,
+            updated_at: api_key.updated_at,
+        }
+    }
+}
+
+// handlers
+async fn get_all_api_keys(
+    Extension(service): Extension<ApiKeyService>,
+) -> Result<Json<ApiResponse<Vec<ApiKeyResponse>>>, AppError> {
+    let api_keys = service.get_all().await?;
+    let response: Vec<ApiKeyResponse> = api_keys.into_iter().map(ApiKeyResponse::from).collect();
+    Ok(Json(ApiResponse::success(response)))
+}
+
+async fn issue_api_key(
+    Extension(service): Extension<ApiKeyService>,
+    principal: Principal,
+    Json(request): Json<IssueApiKeyRequest>,
+) -> Result<Json<ApiResponse<IssuedApiKeyResponse>>, AppError> {
+    let scopes = ApiKeyService::parse_scopes(&request.scopes)?;
+    let (api_key, key) = service.issue(&request.key_name, &scopes).await?;
+    info!("{} issued api key: {}", principal.actor(), api_key.key_name);
+    Ok(Json(ApiResponse::success(IssuedApiKeyResponse {
+        api_key: api_key.into(),
+        key,
+    })))
+}
+
+async fn revoke_api_key(
+    Extension(service): Extension<ApiKeyService>,
+    principal: Principal,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ApiKeyResponse>>, AppError> {
+    let api_key = service.revoke(id).await?;
+    info!(
+        "{} revoked api key: {}",
+        principal.actor(),
+        api_key.key_name
+    );
+    Ok(Json(ApiResponse::success(api_key.into())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::auth;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        middleware,
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_api_key_admin_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let service = ApiKeyService::new(pool);
+        let (_, admin) = service
+            .issue("Bootstrap", &[Scope::Admin])
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let app = router()
+            .route_layer(middleware::from_fn(auth::authenticate))
+            .layer(Extension(service));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/admin/api-keys")
+            .header("authorization", format!("Bearer {}", admin))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"key_name": "Line 1 HMI", "scopes": ["read"]}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let issued = body_json(response).await["data"].clone();
+        assert_eq!(issued["scopes"], json!(["read"]));
+        let reader = issued["key"].as_str().unwrap().to_string();
+        let reader_id = issued["api_key_id"].as_str().unwrap().to_string();
+
+        // a read key can't manage keys
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/admin/api-keys")
+            .header("authorization", format!("Bearer {}", reader))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/admin/api-keys")
+            .header("authorization", format!("Bearer {}", admin))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let listed = body_json(response).await["data"].clone();
+        assert_eq!(listed.as_array().unwrap().len(), 2);
+        assert!(listed[0].get("key").is_none());
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/admin/api-keys/revoke/{}", reader_id))
+            .header("authorization", format!("Bearer {}", admin))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body_json(response).await["data"]["revoked_at"].is_string());
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/admin/api-keys")
+            .header("authorization", format!("Bearer {}", reader))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        Ok(())
+    }
+}
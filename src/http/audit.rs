@@ -0,0 +1,164 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::{AuditEntry, AuditFilter};
+use crate::services::audit_service::AuditService;
+use axum::{
+    Json, Router,
+    extract::{Extension, Query},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+pub fn router() -> Router {
+    Router::new().route("/api/v1/audit", get(get_audit_log))
+}
+
+// request/response dtos
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<Uuid>,
+    pub actor: Option<String>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub to: Option<OffsetDateTime>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    100
+}
+
+#[derive(Serialize)]
+pub struct AuditEntryResponse {
+    pub audit_id: Uuid,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub occurred_at: Option<OffsetDateTime>,
+    pub actor: Option<String>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub transaction_id: i64,
+}
+
+impl From<AuditQuery> for AuditFilter {
+    fn from(query: AuditQuery) -> Self {
+        Self {
+            entity_type: query.entity_type,
+            entity_id: query.entity_id,
+            actor: query.actor,
+            from: query.from,
+            to: query.to,
+            limit: query.limit,
+        }
+    }
+}
+
+impl From<AuditEntry> for AuditEntryResponse {
+    fn from(entry: AuditEntry) -> Self {
+        Self {
+            audit_id: entry.audit_id,
+            occurred_at: Some(entry.occurred_at),
+            actor: entry.actor,
+            action: entry.action,
+            entity_type: entry.entity_type,
+            entity_id: entry.entity_id,
+            before: entry.before,
+            after: entry.after,
+            transaction_id: entry.transaction_id,
+        }
+    }
+}
+
+// handlers
+async fn get_audit_log(
+    Extension(service): Extension<AuditService>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<ApiResponse<Vec<AuditEntryResponse>>>, AppError> {
+    let entries = service.search(&query.into()).await?;
+    info!("Retrieved {} audit log entries", entries.len());
+    let response: Vec<AuditEntryResponse> =
+        entries.into_iter().map(AuditEntryResponse::from).collect();
+    Ok(Json(ApiResponse::success(response)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::equipment_type_service::EquipmentTypeService;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn get(app: &Router, uri: &str) -> (StatusCode, Value) {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[sqlx::test]
+    async fn test_audit_log_filters(pool: PgPool) -> sqlx::Result<()> {
+        let to_sqlx = |e: AppError| sqlx::Error::Protocol(e.to_string());
+        let types = EquipmentTypeService::new(pool.clone());
+        let filler = types
+            .acting_as(Some("jdoe".to_string()))
+            .create("Filler")
+            .await
+            .map_err(to_sqlx)?;
+        types
+            .acting_as(Some("asmith".to_string()))
+            .update(filler.type_id, "Bottle Filler")
+            .await
+            .map_err(to_sqlx)?;
+
+        let app = router().layer(Extension(AuditService::new(pool)));
+
+        let (status, body) = get(
+            &app,
+            &format!(
+                "/api/v1/audit?entity_type=equipment_type&entity_id={}",
+                filler.type_id
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let entries = body["data"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["actor"], "asmith");
+        assert_eq!(entries[0]["before"]["type_name"], "Filler");
+        assert_eq!(entries[0]["after"]["type_name"], "Bottle Filler");
+        assert_eq!(entries[1]["actor"], "jdoe");
+
+        let (_, body) = get(&app, "/api/v1/audit?actor=jdoe").await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+        let (_, body) = get(&app, "/api/v1/audit?to=2000-01-01T00:00:00Z").await;
+        assert!(body["data"].as_array().unwrap().is_empty());
+
+        let (status, body) = get(
+            &app,
+            "/api/v1/audit?from=2030-01-01T00:00:00Z&to=2020-01-01T00:00:00Z",
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "validation_error");
+
+        Ok(())
+    }
+}
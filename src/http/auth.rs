@@ -0,0 +1,339 @@
+use crate::error::AppError;
+use crate::models::app::{Principal, Scope};
+use crate::services::api_key_service::{ApiKeyService, KEY_PREFIX};
+use crate::services::jwt_service::JwtService;
+use axum::{
+    extract::{Extension, FromRequestParts, OptionalFromRequestParts, Request},
+    http::{HeaderMap, Method, Uri, header, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use std::convert::Infallible;
+use tracing::debug;
+
+/// Routes that also take the token as `?access_token=`, browsers can't set headers on
+/// an EventSource or WebSocket
+const QUERY_TOKEN_PREFIX: &str = "/api/v1/events/";
+const QUERY_TOKEN_PARAM: &str = "access_token";
+
+/// Scope a request needs: admin for the admin routes, read for anything that only
+/// looks, write for everything that changes data
+pub fn required_scope(method: &Method, path: &str) -> Scope {
+    if path.starts_with("/api/v1/admin/") {
+        Scope::Admin
+    } else if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        Scope::Read
+    } else {
+        Scope::Write
+    }
+}
+
+/// Middleware for the api routes. Requires `Authorization: Bearer <token>` with a
+/// scope that covers the request, anything outside /api is let through. The event feeds
+/// also accept the token in the query string. The token is
+/// an api key or, when an issuer is configured, an access token from the identity
+/// provider. The principal is added to the request extensions for handlers to use.
+pub async fn authenticate(
+    Extension(api_keys): Extension<ApiKeyService>,
+    jwt: Option<Extension<JwtService>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let path = request.uri().path();
+    if !path.starts_with("/api/") {
+        return Ok(next.run(request).await);
+    }
+    let required = required_scope(request.method(), path);
+    let token = match bearer_token(request.headers()) {
+        Some(token) => Some(token.to_string()),
+        None if path.starts_with(QUERY_TOKEN_PREFIX) => query_token(request.uri()),
+        None => None,
+    };
+
+    let principal = authorize(&api_keys, jwt.as_deref(), token.as_deref(), required).await?;
+    request.extensions_mut().insert(principal);
+    Ok(next.run(request).await)
+}
+
+/// Token from the `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+fn query_token(uri: &Uri) -> Option<String> {
+    url::form_urlencoded::parse(uri.query()?.as_bytes())
+        .find(|(key, _)| key == QUERY_TOKEN_PARAM)
+        .map(|(_, token)| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Principal for `token`, which must have the `required` scope. Shared with the gRPC
+/// server, see `grpc::auth`.
+pub async fn authorize(
+    api_keys: &ApiKeyService,
+    jwt: Option<&JwtService>,
+    token: Option<&str>,
+    required: Scope,
+) -> Result<Principal, AppError> {
+    let token = token.ok_or_else(|| AppError::Unauthorized("missing bearer token".to_string()))?;
+
+    let principal = match jwt {
+        Some(jwt) if !token.starts_with(KEY_PREFIX) => jwt.verify(token).await?,
+        _ => Principal::from(api_keys.authenticate(token).await?),
+    };
+
+    if !principal.has_scope(required) {
+        return Err(AppError::Forbidden(format!(
+            "'{}' needs the {} scope for this request",
+            principal.name, required
+        )));
+    }
+
+    debug!(
+        "Authenticated {:?} '{}' ({})",
+        principal.kind, principal.name, principal.subject
+    );
+    Ok(principal)
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Principal {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Principal>()
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized("not authenticated".to_string()))
+    }
+}
+
+/// `Option<Principal>` for handlers that record who made a change, None when the route
+/// is served without `authenticate`
+impl<S: Send + Sync> OptionalFromRequestParts<S> for Principal {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<Principal>().cloned())
+    }
+}
+
+/// Who to record for a change, see `Principal::attribute`. Without a principal the
+/// name the client sent is kept as is.
+pub fn attribute(principal: Option<&Principal>, on_behalf_of: Option<&str>) -> Option<String> {
+    match principal {
+        Some(principal) => Some(principal.attribute(on_behalf_of)),
+        None => on_behalf_of.map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{equipment_types, events};
+    use crate::services::equipment_type_service::EquipmentTypeService;
+    use crate::services::event_service::EventService;
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+        middleware,
+    };
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    fn send(method: &str, uri: &str, token: Option<&str>) -> Request<Body> {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(
+            required_scope(&Method::GET, "/api/v1/equipment-types"),
+            Scope::Read
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/api/v1/equipment-types/delete/1"),
+            Scope::Write
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/api/v1/admin/api-keys"),
+            Scope::Admin
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/api/v1/admin/plant-model/apply"),
+            Scope::Admin
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/api/v1/plant-model/export"),
+            Scope::Read
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_plant_floor_key_can_read_but_not_delete(pool: PgPool) -> sqlx::Result<()> {
+        let to_sqlx = |e: AppError| sqlx::Error::Protocol(e.to_string());
+        let api_keys = ApiKeyService::new(pool.clone());
+        let (_, reader) = api_keys
+            .issue("Line 1 HMI", &[Scope::Read])
+            .await
+            .map_err(to_sqlx)?;
+        let (_, writer) = api_keys
+            .issue("Engineering", &[Scope::Write])
+            .await
+            .map_err(to_sqlx)?;
+
+        let type_id: uuid::Uuid = sqlx::query_scalar!(
+            "INSERT INTO core.equipment_type (type_name) VALUES ('Filler') RETURNING type_id"
+        )
+        .fetch_one(&pool)
+        .await?;
+
+        let app = Router::new()
+            .merge(equipment_types::router())
+            .route_layer(middleware::from_fn(authenticate))
+            .layer(Extension(EquipmentTypeService::new(pool.clone())))
+            .layer(Extension(api_keys));
+        let delete = format!("/api/v1/equipment-types/delete/{}", type_id);
+
+        let response = app
+            .clone()
+            .oneshot(send("GET", "/api/v1/equipment-types", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()["www-authenticate"], "Bearer");
+        assert_eq!(body_json(response).await["code"], "unauthorized");
+
+        let response = app
+            .clone()
+            .oneshot(send("GET", "/api/v1/equipment-types", Some("gmes_wrong")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(send("GET", "/api/v1/equipment-types", Some(&reader)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(send("POST", &delete, Some(&reader)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(body_json(response).await["code"], "forbidden");
+
+        let response = app
+            .clone()
+            .oneshot(send("POST", &delete, Some(&writer)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_event_feed_takes_the_token_from_the_query(pool: PgPool) -> sqlx::Result<()> {
+        let api_keys = ApiKeyService::new(pool.clone());
+        let (_, reader) = api_keys
+            .issue("Line 1 HMI", &[Scope::Read])
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = Router::new()
+            .merge(events::router())
+            .merge(equipment_types::router())
+            .route_layer(middleware::from_fn(authenticate))
+            .layer(Extension(EventService::new(pool.clone())))
+            .layer(Extension(EquipmentTypeService::new(pool.clone())))
+            .layer(Extension(api_keys));
+
+        let response = app
+            .clone()
+            .oneshot(send("GET", "/api/v1/events/stream", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(send(
+                "GET",
+                "/api/v1/events/stream?types=equipment&access_token=gmes_wrong",
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(send(
+                "GET",
+                &format!(
+                    "/api/v1/events/stream?types=equipment&access_token={}",
+                    reader
+                ),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        // the websocket feed authenticates the same way, the upgrade itself needs a real
+        // connection so a request that got past authentication ends at 426
+        let upgrade = |token: &str| {
+            Request::builder()
+                .uri(format!("/api/v1/events/ws?access_token={}", token))
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-version", "13")
+                .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(upgrade("gmes_wrong")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.clone().oneshot(upgrade(&reader)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UPGRADE_REQUIRED);
+
+        // only the event feeds read the query string
+        let response = app
+            .oneshot(send(
+                "GET",
+                &format!("/api/v1/equipment-types?access_token={}", reader),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        Ok(())
+    }
+}
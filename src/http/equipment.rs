@@ -0,0 +1,555 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::models::core::{EquipmentPath, EquipmentWithRelations};
+use crate::services::equipment_service::{Equipment, EquipmentService};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// equipment endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/equipment",
+            get(get_all_equipment).post(create_equipment),
+        )
+        .route("/api/v1/equipment/count", get(get_equipment_count))
+        .route("/api/v1/equipment/{id}", get(get_equipment_by_id))
+        .route("/api/v1/equipment/{id}/tree", get(get_equipment_tree))
+        .route("/api/v1/equipment/{id}/path", get(get_equipment_path))
+        .route("/api/v1/equipment/update-name/{id}", post(rename_equipment))
+        .route(
+            "/api/v1/equipment/update-parent/{id}",
+            post(reparent_equipment),
+        )
+        .route(
+            "/api/v1/equipment/update-metadata/{id}",
+            post(update_equipment_metadata),
+        )
+        .route("/api/v1/equipment/enable/{id}", post(enable_equipment))
+        .route("/api/v1/equipment/disable/{id}", post(disable_equipment))
+        .route("/api/v1/equipment/delete/{id}", post(delete_equipment))
+        .route("/api/v1/equipment/restore/{id}", post(restore_equipment))
+        .route("/api/v1/equipment/exists/{id}", get(check_equipment_exists))
+}
+
+// request/response dtos
+#[derive(Serialize)]
+pub struct EquipmentResponse {
+    pub equipment_id: Uuid,
+    pub equipment_name: String,
+    pub equipment_type_id: Uuid,
+    pub equipment_parent_id: Option<Uuid>,
+    pub equipment_enabled: bool,
+    pub equipment_metadata: Option<Value>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateEquipmentRequest {
+    pub equipment_name: String,
+    pub equipment_type_id: Uuid,
+    pub equipment_parent_id: Option<Uuid>,
+    pub equipment_enabled: Option<bool>,
+    pub equipment_metadata: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEquipmentNameRequest {
+    pub equipment_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEquipmentParentRequest {
+    pub equipment_parent_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEquipmentMetadataRequest {
+    pub equipment_metadata: Value,
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: i64,
+}
+
+#[derive(Serialize)]
+pub struct ExistsResponse {
+    pub exists: bool,
+}
+
+#[derive(Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub total_count: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PaginationQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+fn default_page() -> i64 {
+    1
+}
+fn default_per_page() -> i64 {
+    50
+}
+
+// service model -> response model
+impl From<Equipment> for EquipmentResponse {
+    fn from(equipment: Equipment) -> Self {
+        Self {
+            equipment_id: equipment.equipment_id,
+            equipment_name: equipment.equipment_name,
+            equipment_type_id: equipment.equipment_type_id,
+            equipment_parent_id: equipment.equipment_parent_id,
+            equipment_enabled: equipment.equipment_enabled,
+            equipment_metadata: equipment.equipment_metadata,
+            created_at: equipment.created_at,
+            updated_at: equipment.updated_at,
+        }
+    }
+}
+
+// handler functions for http endpoints
+async fn get_all_equipment(
+    Extension(service): Extension<EquipmentService>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<EquipmentResponse>>>, AppError> {
+    // Convert 1-based page to 0-based offset
+    let offset = (pagination.page - 1) * pagination.per_page;
+
+    let (equipment, total_count) = service.get_paginated(offset, pagination.per_page).await?;
+    let response: Vec<EquipmentResponse> =
+        equipment.into_iter().map(EquipmentResponse::from).collect();
+
+    let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
+
+    let paginated_response = PaginatedResponse {
+        data: response,
+        total_count,
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total_pages,
+    };
+
+    info!(
+        "Retrieved {} equipment (page {}/{}, total: {})",
+        paginated_response.data.len(),
+        pagination.page,
+        total_pages,
+        total_count
+    );
+
+    Ok(Json(ApiResponse::success(paginated_response)))
+}
+
+async fn get_equipment_by_id(
+    Extension(service): Extension<EquipmentService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let equipment = service.get_by_id(id).await?;
+    info!("Retrieved equipment: {}", equipment.equipment_name);
+    Ok(Json(ApiResponse::success(EquipmentResponse::from(
+        equipment,
+    ))))
+}
+
+async fn get_equipment_tree(
+    Extension(service): Extension<EquipmentService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentWithRelations>>, AppError> {
+    let tree = service.get_tree(id).await?;
+    info!("Retrieved equipment tree: {}", tree.equipment_name);
+    Ok(Json(ApiResponse::success(tree)))
+}
+
+async fn get_equipment_path(
+    Extension(service): Extension<EquipmentService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentPath>>, AppError> {
+    let path = service.get_path(id).await?;
+    info!("Retrieved equipment path {} (depth {})", id, path.depth);
+    Ok(Json(ApiResponse::success(path)))
+}
+
+async fn create_equipment(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Json(request): Json<CreateEquipmentRequest>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment = service
+        .create(
+            &request.equipment_name,
+            request.equipment_type_id,
+            request.equipment_parent_id,
+            request.equipment_enabled,
+            request.equipment_metadata.as_ref(),
+        )
+        .await?;
+    info!("Created equipment: {}", equipment.equipment_name);
+    Ok(Json(ApiResponse::success(EquipmentResponse::from(
+        equipment,
+    ))))
+}
+
+async fn rename_equipment(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateEquipmentNameRequest>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment = service.rename(id, &request.equipment_name).await?;
+    info!("Renamed equipment {}: {}", id, equipment.equipment_name);
+    Ok(Json(ApiResponse::success(EquipmentResponse::from(
+        equipment,
+    ))))
+}
+
+async fn reparent_equipment(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateEquipmentParentRequest>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment = service.reparent(id, request.equipment_parent_id).await?;
+    info!(
+        "Moved equipment {} to parent {:?}",
+        id, equipment.equipment_parent_id
+    );
+    Ok(Json(ApiResponse::success(EquipmentResponse::from(
+        equipment,
+    ))))
+}
+
+async fn update_equipment_metadata(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateEquipmentMetadataRequest>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment = service
+        .update_metadata(id, &request.equipment_metadata)
+        .await?;
+    info!("Updated equipment metadata {}", id);
+    Ok(Json(ApiResponse::success(EquipmentResponse::from(
+        equipment,
+    ))))
+}
+
+async fn enable_equipment(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    set_equipment_enabled(service, id, true).await
+}
+
+async fn disable_equipment(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    set_equipment_enabled(service, id, false).await
+}
+
+async fn set_equipment_enabled(
+    service: EquipmentService,
+    id: Uuid,
+    enabled: bool,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let equipment = service.set_enabled(id, enabled).await?;
+    info!("Set equipment {} enabled: {}", id, enabled);
+    Ok(Json(ApiResponse::success(EquipmentResponse::from(
+        equipment,
+    ))))
+}
+
+async fn delete_equipment(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted equipment: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn restore_equipment(
+    Extension(service): Extension<EquipmentService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment = service.restore(id).await?;
+    info!("Restored equipment: {}", equipment.equipment_name);
+    Ok(Json(ApiResponse::success(EquipmentResponse::from(
+        equipment,
+    ))))
+}
+
+async fn get_equipment_count(
+    Extension(service): Extension<EquipmentService>,
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    info!("Total equipment count: {}", count);
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn check_equipment_exists(
+    Extension(service): Extension<EquipmentService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn enterprise_type_id(pool: &PgPool) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = 'enterprise'"
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_create_equipment_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let type_id = enterprise_type_id(&pool).await?;
+        let app = router().layer(Extension(EquipmentService::new(pool)));
+
+        let request_body = json!({
+            "equipment_name": "Acme",
+            "equipment_type_id": type_id,
+            "equipment_metadata": {"site_code": "ACM"}
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/equipment")
+            .header("content-type", "application/json")
+            .body(Body::from(request_body.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["equipment_name"], "Acme");
+        assert_eq!(body["data"]["equipment_metadata"]["site_code"], "ACM");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_equipment_lifecycle_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let type_id = enterprise_type_id(&pool).await?;
+        let service = EquipmentService::new(pool);
+        let created = service
+            .create("Acme", type_id, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/equipment/update-name/{}",
+                created.equipment_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"equipment_name": "Acme Corp"}).to_string(),
+            ))
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["equipment_name"], "Acme Corp");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/equipment/disable/{}",
+                created.equipment_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["equipment_enabled"], false);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/equipment/delete/{}", created.equipment_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/equipment/{}", created.equipment_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = body_json(response).await;
+        assert_eq!(
+            body["error"],
+            format!("Equipment with ID {} not found", created.equipment_id)
+        );
+
+        let restore = || {
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/equipment/restore/{}",
+                    created.equipment_id
+                ))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let body = body_json(app.clone().oneshot(restore()).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["equipment_name"], "Acme Corp");
+
+        // it is live again, there is nothing left to restore
+        let response = app.oneshot(restore()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_pagination_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let type_id = enterprise_type_id(&pool).await?;
+        let service = EquipmentService::new(pool);
+
+        for i in 1..=5 {
+            service
+                .create(&format!("Enterprise {}", i), type_id, None, None, None)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/equipment?page=2&per_page=3")
+            .body(Body::empty())
+            .unwrap();
+
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["total_count"], 5);
+        assert_eq!(body["data"]["total_pages"], 2);
+        assert_eq!(body["data"]["data"].as_array().unwrap().len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_tree_and_path_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let type_id = enterprise_type_id(&pool).await?;
+        let site_type_id =
+            sqlx::query_scalar!("SELECT type_id FROM core.equipment_type WHERE type_name = 'site'")
+                .fetch_one(&pool)
+                .await?;
+        let service = EquipmentService::new(pool);
+        let enterprise = service
+            .create("Acme", type_id, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let site = service
+            .create(
+                "Plant 1",
+                site_type_id,
+                Some(enterprise.equipment_id),
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/equipment/{}/tree",
+                enterprise.equipment_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["equipment_type"]["type_name"], "enterprise");
+        assert_eq!(
+            body["data"]["child_equipment"][0]["equipment_name"],
+            "Plant 1"
+        );
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/equipment/{}/path", site.equipment_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["depth"], 1);
+        assert_eq!(body["data"]["path"][0]["equipment_name"], "Acme");
+
+        Ok(())
+    }
+}
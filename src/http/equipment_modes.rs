@@ -0,0 +1,303 @@
+use crate::error::AppError;
+use crate::http::auth;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::services::equipment_mode_service::{
+    EquipmentCurrentMode, EquipmentModeChange, EquipmentModeService,
+};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// live equipment mode endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/equipment/{id}/mode",
+            get(get_current_mode).post(set_mode),
+        )
+        .route("/api/v1/equipment/{id}/mode/history", get(get_mode_history))
+}
+
+#[derive(Deserialize)]
+pub struct SetModeRequest {
+    pub mode_id: Uuid,
+    pub set_by: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct CurrentModeResponse {
+    pub equipment_id: Uuid,
+    pub mode_id: Uuid,
+    pub mode_description: String,
+    pub mode_group_id: Uuid,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub set_at: Option<OffsetDateTime>,
+    pub set_by: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ModeHistoryResponse {
+    pub history_id: Uuid,
+    pub equipment_id: Uuid,
+    pub mode_id: Uuid,
+    pub mode_description: String,
+    pub mode_group_id: Uuid,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub set_at: Option<OffsetDateTime>,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub ended_at: Option<OffsetDateTime>,
+    pub set_by: Option<String>,
+}
+
+impl From<EquipmentCurrentMode> for CurrentModeResponse {
+    fn from(current: EquipmentCurrentMode) -> Self {
+        Self {
+            equipment_id: current.equipment_id,
+            mode_id: current.mode_id,
+            mode_description: current.mode_description,
+            mode_group_id: current.mode_group_id,
+            set_at: Some(current.set_at),
+            set_by: current.set_by,
+        }
+    }
+}
+
+impl From<EquipmentModeChange> for ModeHistoryResponse {
+    fn from(change: EquipmentModeChange) -> Self {
+        Self {
+            history_id: change.history_id,
+            equipment_id: change.equipment_id,
+            mode_id: change.mode_id,
+            mode_description: change.mode_description,
+            mode_group_id: change.mode_group_id,
+            set_at: Some(change.set_at),
+            ended_at: change.ended_at,
+            set_by: change.set_by,
+        }
+    }
+}
+
+async fn get_current_mode(
+    Extension(service): Extension<EquipmentModeService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Option<CurrentModeResponse>>>, AppError> {
+    let current = service.get_current(id).await?;
+    Ok(Json(ApiResponse::success(
+        current.map(CurrentModeResponse::from),
+    )))
+}
+
+async fn set_mode(
+    Extension(service): Extension<EquipmentModeService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SetModeRequest>,
+) -> Result<Json<ApiResponse<CurrentModeResponse>>, AppError> {
+    let set_by = auth::attribute(principal.as_ref(), request.set_by.as_deref());
+    let current = service
+        .set_mode(id, request.mode_id, set_by.as_deref())
+        .await?;
+    info!("Set equipment {} mode to {}", id, current.mode_description);
+    Ok(Json(ApiResponse::success(CurrentModeResponse::from(
+        current,
+    ))))
+}
+
+async fn get_mode_history(
+    Extension(service): Extension<EquipmentModeService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<ApiResponse<Vec<ModeHistoryResponse>>>, AppError> {
+    let history = service.get_history(id, query.from, query.to).await?;
+    info!(
+        "Retrieved {} mode history entries for equipment {}",
+        history.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(
+        history.into_iter().map(ModeHistoryResponse::from).collect(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::app::Scope;
+    use crate::services::api_key_service::ApiKeyService;
+    use crate::services::jwt_service::testing::TestIssuer;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+        middleware,
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn mapped_equipment(pool: &PgPool) -> sqlx::Result<Uuid> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+               SELECT $1, mode_group_id FROM core.mode_group
+               WHERE mode_group_name = 'Default MES Mode Group'"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(equipment_id)
+    }
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_set_and_get_mode_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = mapped_equipment(&pool).await?;
+        let production = sqlx::query_scalar!(
+            "SELECT mode_id FROM core.mode WHERE mode_description = 'production'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(EquipmentModeService::new(pool)));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/equipment/{}/mode", equipment_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], true);
+        assert!(body["data"].is_null());
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/equipment/{}/mode", equipment_id))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"mode_id": production, "set_by": "operator"}).to_string(),
+            ))
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["mode_description"], "production");
+
+        // unknown modes are rejected as invalid input
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/equipment/{}/mode", equipment_id))
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"mode_id": Uuid::new_v4()}).to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/equipment/{}/mode/history?from=2000-01-01T00:00:00Z",
+                equipment_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        let history = body["data"].as_array().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0]["set_by"], "operator");
+        assert!(history[0]["ended_at"].is_null());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_set_by_comes_from_the_principal(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = mapped_equipment(&pool).await?;
+        let mode = |description: &'static str| {
+            sqlx::query_scalar!(
+                "SELECT mode_id FROM core.mode WHERE mode_description = $1",
+                description
+            )
+            .fetch_one(&pool)
+        };
+        let (production, idle) = (mode("production").await?, mode("idle").await?);
+        let issuer = TestIssuer::new();
+        let api_keys = ApiKeyService::new(pool.clone());
+        let (_, hmi) = api_keys
+            .issue("Line 1 HMI", &[Scope::Write])
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let app = router()
+            .route_layer(middleware::from_fn(auth::authenticate))
+            .layer(Extension(EquipmentModeService::new(pool.clone())))
+            .layer(Extension(api_keys))
+            .layer(Extension(issuer.service().await));
+
+        let user = issuer.token(json!({"preferred_username": "jdoe", "scope": "write"}));
+        for (token, mode_id, set_by) in [
+            // users can't claim to be someone else
+            (&user, production, Some("operator")),
+            // shared keys may say who they act for
+            (&hmi, idle, Some("operator")),
+            (&hmi, production, None),
+        ] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/equipment/{}/mode", equipment_id))
+                .header("authorization", format!("Bearer {}", token))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"mode_id": mode_id, "set_by": set_by}).to_string(),
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let recorded = sqlx::query_scalar!(
+            r#"SELECT set_by AS "set_by!" FROM core.equipment_mode_history
+               WHERE equipment_id = $1 ORDER BY set_at, history_id"#,
+            equipment_id
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(
+            recorded,
+            vec![
+                "jdoe",
+                "api-key:Line 1 HMI for operator",
+                "api-key:Line 1 HMI"
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use crate::error::AppError;
+use crate::http::auth;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::services::equipment_state_service::{
+    EquipmentCurrentState, EquipmentStateChange, EquipmentStateService, StateReport,
+};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// live equipment state endpoints, fed by plc/data collection
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/equipment/{id}/state",
+            get(get_current_state).post(ingest_state),
+        )
+        .route(
+            "/api/v1/equipment/{id}/state/history",
+            get(get_state_history),
+        )
+}
+
+#[derive(Deserialize)]
+pub struct IngestStateRequest {
+    pub state_code: i32,
+    /// only needed when the code exists in more than one of the equipment's state groups
+    pub state_group_id: Option<Uuid>,
+    pub state_value: Option<Value>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub timestamp: Option<OffsetDateTime>,
+    pub updated_by: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct CurrentStateResponse {
+    pub equipment_id: Uuid,
+    pub state_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    pub state_group_id: Uuid,
+    pub state_value: Option<Value>,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub started_at: Option<OffsetDateTime>,
+    pub duration_seconds: f64,
+    pub updated_by: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct StateHistoryResponse {
+    pub history_id: Uuid,
+    pub equipment_id: Uuid,
+    pub state_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    pub state_group_id: Uuid,
+    pub state_value: Option<Value>,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub started_at: Option<OffsetDateTime>,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub ended_at: Option<OffsetDateTime>,
+    pub duration_seconds: f64,
+    pub updated_by: Option<String>,
+}
+
+impl From<EquipmentCurrentState> for CurrentStateResponse {
+    fn from(current: EquipmentCurrentState) -> Self {
+        Self {
+            equipment_id: current.equipment_id,
+            state_id: current.state_id,
+            state_code: current.state_code,
+            state_description: current.state_description,
+            state_group_id: current.state_group_id,
+            state_value: current.state_value,
+            started_at: Some(current.started_at),
+            duration_seconds: current.duration_seconds,
+            updated_by: current.updated_by,
+        }
+    }
+}
+
+impl From<EquipmentStateChange> for StateHistoryResponse {
+    fn from(change: EquipmentStateChange) -> Self {
+        Self {
+            history_id: change.history_id,
+            equipment_id: change.equipment_id,
+            state_id: change.state_id,
+            state_code: change.state_code,
+            state_description: change.state_description,
+            state_group_id: change.state_group_id,
+            state_value: change.state_value,
+            started_at: Some(change.started_at),
+            ended_at: change.ended_at,
+            duration_seconds: change.duration_seconds,
+            updated_by: change.updated_by,
+        }
+    }
+}
+
+async fn get_current_state(
+    Extension(service): Extension<EquipmentStateService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Option<CurrentStateResponse>>>, AppError> {
+    let current = service.get_current(id).await?;
+    Ok(Json(ApiResponse::success(
+        current.map(CurrentStateResponse::from),
+    )))
+}
+
+async fn ingest_state(
+    Extension(service): Extension<EquipmentStateService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<IngestStateRequest>,
+) -> Result<Json<ApiResponse<CurrentStateResponse>>, AppError> {
+    let updated_by = auth::attribute(principal.as_ref(), request.updated_by.as_deref());
+    let report = StateReport {
+        state_code: request.state_code,
+        state_group_id: request.state_group_id,
+        state_value: request.state_value.as_ref(),
+        timestamp: request.timestamp,
+        updated_by: updated_by.as_deref(),
+    };
+
+    let current = service.ingest(id, report).await?;
+    info!(
+        "Equipment {} state is {} ({})",
+        id, current.state_description, current.state_code
+    );
+    Ok(Json(ApiResponse::success(CurrentStateResponse::from(
+        current,
+    ))))
+}
+
+async fn get_state_history(
+    Extension(service): Extension<EquipmentStateService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<ApiResponse<Vec<StateHistoryResponse>>>, AppError> {
+    let history = service.get_history(id, query.from, query.to).await?;
+    info!(
+        "Retrieved {} state history entries for equipment {}",
+        history.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(
+        history
+            .into_iter()
+            .map(StateHistoryResponse::from)
+            .collect(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn mapped_equipment(pool: &PgPool) -> sqlx::Result<Uuid> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+               SELECT $1, state_group_id FROM core.state_group
+               WHERE state_group_name = 'Default MES State Group'"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(equipment_id)
+    }
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn ingest_request(equipment_id: Uuid, body: Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/equipment/{}/state", equipment_id))
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_ingest_state_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = mapped_equipment(&pool).await?;
+        let app = router().layer(Extension(EquipmentStateService::new(pool)));
+
+        let response = app
+            .clone()
+            .oneshot(ingest_request(
+                equipment_id,
+                json!({"state_code": 1, "timestamp": "2024-01-01T08:00:00Z"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["state_description"], "running");
+
+        let body = body_json(
+            app.clone()
+                .oneshot(ingest_request(
+                    equipment_id,
+                    json!({"state_code": 7, "timestamp": "2024-01-01T08:30:00Z"}),
+                ))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(body["data"]["state_description"], "planned downtime");
+
+        let response = app
+            .clone()
+            .oneshot(ingest_request(equipment_id, json!({"state_code": 4242})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/equipment/{}/state/history?from=2024-01-01T00:00:00Z&to=2024-01-02T00:00:00Z",
+                equipment_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        let history = body["data"].as_array().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1]["duration_seconds"], 1800.0);
+        assert_eq!(history[1]["ended_at"], "2024-01-01T08:30:00Z");
+
+        Ok(())
+    }
+}
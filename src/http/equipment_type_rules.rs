@@ -0,0 +1,173 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::services::equipment_type_rule_service::{EquipmentTypeRule, EquipmentTypeRuleService};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// equipment type hierarchy rule endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/equipment-types/rules",
+            get(get_all_rules).post(create_rule),
+        )
+        .route("/api/v1/equipment-types/rules/{id}", get(get_rule_by_id))
+        .route(
+            "/api/v1/equipment-types/rules/delete/{id}",
+            post(delete_rule),
+        )
+}
+
+#[derive(Deserialize)]
+pub struct CreateEquipmentTypeRuleRequest {
+    /// leave empty to allow the child type at the root of the hierarchy
+    pub parent_type_id: Option<Uuid>,
+    pub child_type_id: Uuid,
+}
+
+#[derive(Serialize)]
+pub struct EquipmentTypeRuleResponse {
+    pub rule_id: Uuid,
+    pub parent_type_id: Option<Uuid>,
+    pub parent_type_name: Option<String>,
+    pub child_type_id: Uuid,
+    pub child_type_name: String,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<EquipmentTypeRule> for EquipmentTypeRuleResponse {
+    fn from(rule: EquipmentTypeRule) -> Self {
+        Self {
+            rule_id: rule.rule_id,
+            parent_type_id: rule.parent_type_id,
+            parent_type_name: rule.parent_type_name,
+            child_type_id: rule.child_type_id,
+            child_type_name: rule.child_type_name,
+            created_at: rule.created_at,
+            updated_at: rule.updated_at,
+        }
+    }
+}
+
+async fn get_all_rules(
+    Extension(service): Extension<EquipmentTypeRuleService>,
+) -> Result<Json<ApiResponse<Vec<EquipmentTypeRuleResponse>>>, AppError> {
+    let rules = service.get_all().await?;
+    info!("Retrieved {} equipment type rules", rules.len());
+    Ok(Json(ApiResponse::success(
+        rules
+            .into_iter()
+            .map(EquipmentTypeRuleResponse::from)
+            .collect(),
+    )))
+}
+
+async fn get_rule_by_id(
+    Extension(service): Extension<EquipmentTypeRuleService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentTypeRuleResponse>>, AppError> {
+    let rule = service.get_by_id(id).await?;
+    Ok(Json(ApiResponse::success(EquipmentTypeRuleResponse::from(
+        rule,
+    ))))
+}
+
+async fn create_rule(
+    Extension(service): Extension<EquipmentTypeRuleService>,
+    principal: Option<Principal>,
+    Json(request): Json<CreateEquipmentTypeRuleRequest>,
+) -> Result<Json<ApiResponse<EquipmentTypeRuleResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let rule = service
+        .create(request.parent_type_id, request.child_type_id)
+        .await?;
+    info!(
+        "Created equipment type rule: {:?} > {}",
+        rule.parent_type_name, rule.child_type_name
+    );
+    Ok(Json(ApiResponse::success(EquipmentTypeRuleResponse::from(
+        rule,
+    ))))
+}
+
+async fn delete_rule(
+    Extension(service): Extension<EquipmentTypeRuleService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted equipment type rule: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    #[sqlx::test]
+    async fn test_list_and_create_rules_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let cell_type_id =
+            sqlx::query_scalar!("SELECT type_id FROM core.equipment_type WHERE type_name = 'cell'")
+                .fetch_one(&pool)
+                .await?;
+        let app = router().layer(Extension(EquipmentTypeRuleService::new(pool)));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/equipment-types/rules")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"].as_array().unwrap().len(), 5);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/equipment-types/rules")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"parent_type_id": null, "child_type_id": cell_type_id}).to_string(),
+            ))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"]["child_type_name"], "cell");
+
+        Ok(())
+    }
+}
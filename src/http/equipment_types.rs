@@ -1,5 +1,7 @@
+use crate::error::AppError;
 use crate::http::date_format;
 use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
 use crate::services::equipment_type_service::EquipmentTypeService;
 use axum::{
     Json, Router,
@@ -8,7 +10,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
-use tracing::{error, info};
+use tracing::info;
 use uuid::Uuid;
 
 // equipment type endpoints
@@ -44,6 +46,10 @@ pub fn router() -> Router {
             "/api/v1/equipment-types/delete/{id}",
             post(delete_equipment_type),
         )
+        .route(
+            "/api/v1/equipment-types/restore/{id}",
+            post(restore_equipment_type),
+        )
         .route(
             "/api/v1/equipment-types/exists/{id}",
             get(check_equipment_type_exists),
@@ -155,272 +161,195 @@ impl From<crate::services::equipment_type_service::EquipmentType> for EquipmentT
 async fn get_all_equipment_types(
     Extension(service): Extension<EquipmentTypeService>,
     Query(pagination): Query<PaginationQuery>,
-) -> Json<ApiResponse<PaginatedResponse<EquipmentTypeResponse>>> {
+) -> Result<Json<ApiResponse<PaginatedResponse<EquipmentTypeResponse>>>, AppError> {
     // Convert 1-based page to 0-based offset
     let offset = (pagination.page - 1) * pagination.per_page;
 
-    match service.get_paginated(offset, pagination.per_page).await {
-        Ok((equipment_types, total_count)) => {
-            let response: Vec<EquipmentTypeResponse> = equipment_types
-                .into_iter()
-                .map(EquipmentTypeResponse::from)
-                .collect();
-
-            let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
-
-            let paginated_response = PaginatedResponse {
-                data: response,
-                total_count,
-                page: pagination.page,
-                per_page: pagination.per_page,
-                total_pages,
-            };
-
-            info!(
-                "Retrieved {} equipment types (page {}/{}, total: {})",
-                paginated_response.data.len(),
-                pagination.page,
-                total_pages,
-                total_count
-            );
-
-            Json(ApiResponse::success(paginated_response))
-        }
-        Err(e) => {
-            error!("Failed to get equipment types: {}", e);
-            Json(ApiResponse::error_str("Failed to retrieve equipment types"))
-        }
-    }
+    let (equipment_types, total_count) = service.get_paginated(offset, pagination.per_page).await?;
+    let response: Vec<EquipmentTypeResponse> = equipment_types
+        .into_iter()
+        .map(EquipmentTypeResponse::from)
+        .collect();
+
+    let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
+
+    let paginated_response = PaginatedResponse {
+        data: response,
+        total_count,
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total_pages,
+    };
+
+    info!(
+        "Retrieved {} equipment types (page {}/{}, total: {})",
+        paginated_response.data.len(),
+        pagination.page,
+        total_pages,
+        total_count
+    );
+
+    Ok(Json(ApiResponse::success(paginated_response)))
 }
 
 async fn get_equipment_type_by_id(
     Extension(service): Extension<EquipmentTypeService>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<EquipmentTypeResponse>> {
-    match service.get_by_id(id).await {
-        Ok(equipment_type) => {
-            info!("Retrieved equipment type: {}", equipment_type.type_name);
-            Json(ApiResponse::success(EquipmentTypeResponse::from(
-                equipment_type,
-            )))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("not found") {
-                Json(ApiResponse::error_str("Equipment type not found"))
-            } else {
-                error!("Failed to get equipment type {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to retrieve equipment type"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<EquipmentTypeResponse>>, AppError> {
+    let equipment_type = service.get_by_id(id).await?;
+    info!("Retrieved equipment type: {}", equipment_type.type_name);
+    Ok(Json(ApiResponse::success(EquipmentTypeResponse::from(
+        equipment_type,
+    ))))
 }
 
 async fn create_equipment_type(
     Extension(service): Extension<EquipmentTypeService>,
+    principal: Option<Principal>,
     Json(request): Json<CreateEquipmentTypeRequest>,
-) -> Json<ApiResponse<EquipmentTypeResponse>> {
-    match service.create(&request.type_name).await {
-        Ok(equipment_type) => {
-            info!("Created equipment type: {}", equipment_type.type_name);
-            Json(ApiResponse::success(EquipmentTypeResponse::from(
-                equipment_type,
-            )))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("already exists") {
-                Json(ApiResponse::error_str("Equipment type name already exists"))
-            } else if error_msg.contains("cannot be empty") || error_msg.contains("cannot exceed") {
-                Json(ApiResponse::error(format!("Invalid input: {}", error_msg)))
-            } else {
-                error!("Failed to create equipment type: {}", e);
-                Json(ApiResponse::error_str("Failed to create equipment type"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<EquipmentTypeResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment_type = service.create(&request.type_name).await?;
+    info!("Created equipment type: {}", equipment_type.type_name);
+    Ok(Json(ApiResponse::success(EquipmentTypeResponse::from(
+        equipment_type,
+    ))))
 }
 
 async fn update_equipment_type(
     Extension(service): Extension<EquipmentTypeService>,
+    principal: Option<Principal>,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateEquipmentTypeRequest>,
-) -> Json<ApiResponse<EquipmentTypeResponse>> {
-    match service.update(id, &request.type_name).await {
-        Ok(equipment_type) => {
-            info!(
-                "Updated equipment type {}: {}",
-                id, equipment_type.type_name
-            );
-            Json(ApiResponse::success(EquipmentTypeResponse::from(
-                equipment_type,
-            )))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("not found") {
-                Json(ApiResponse::error_str("Equipment type not found"))
-            } else if error_msg.contains("already exists") {
-                Json(ApiResponse::error_str("Equipment type name already exists"))
-            } else if error_msg.contains("cannot be empty") || error_msg.contains("cannot exceed") {
-                Json(ApiResponse::error(format!("Invalid input: {}", error_msg)))
-            } else {
-                error!("Failed to update equipment type {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to update equipment type"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<EquipmentTypeResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment_type = service.update(id, &request.type_name).await?;
+    info!(
+        "Updated equipment type {}: {}",
+        id, equipment_type.type_name
+    );
+    Ok(Json(ApiResponse::success(EquipmentTypeResponse::from(
+        equipment_type,
+    ))))
 }
 
 async fn delete_equipment_type(
     Extension(service): Extension<EquipmentTypeService>,
+    principal: Option<Principal>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<()>> {
-    match service.delete(id).await {
-        Ok(()) => {
-            info!("Deleted equipment type: {}", id);
-            Json(ApiResponse::success(()))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("not found") {
-                Json(ApiResponse::error_str("Equipment type not found"))
-            } else if error_msg.contains("in use") {
-                Json(ApiResponse::error_str(
-                    "Equipment type is in use and cannot be deleted",
-                ))
-            } else {
-                error!("Failed to delete equipment type {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to delete equipment type"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted equipment type: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn restore_equipment_type(
+    Extension(service): Extension<EquipmentTypeService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<EquipmentTypeResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let equipment_type = service.restore(id).await?;
+    info!("Restored equipment type: {}", equipment_type.type_name);
+    Ok(Json(ApiResponse::success(EquipmentTypeResponse::from(
+        equipment_type,
+    ))))
 }
 
 async fn search_equipment_types(
     Extension(service): Extension<EquipmentTypeService>,
     Query(search_query): Query<SearchQuery>,
-) -> Json<ApiResponse<Vec<EquipmentTypeResponse>>> {
+) -> Result<Json<ApiResponse<Vec<EquipmentTypeResponse>>>, AppError> {
     if search_query.q.trim().is_empty() {
-        return Json(ApiResponse::error_str("Search query cannot be empty"));
+        return Err(AppError::Validation(
+            "Search query cannot be empty".to_string(),
+        ));
     }
 
-    match service.search_by_name(&search_query.q).await {
-        Ok(equipment_types) => {
-            let response: Vec<EquipmentTypeResponse> = equipment_types
-                .into_iter()
-                .map(EquipmentTypeResponse::from)
-                .collect();
+    let equipment_types = service.search_by_name(&search_query.q).await?;
+    let response: Vec<EquipmentTypeResponse> = equipment_types
+        .into_iter()
+        .map(EquipmentTypeResponse::from)
+        .collect();
 
-            info!(
-                "Found {} equipment types matching search '{}'",
-                response.len(),
-                search_query.q
-            );
+    info!(
+        "Found {} equipment types matching search '{}'",
+        response.len(),
+        search_query.q
+    );
 
-            Json(ApiResponse::success(response))
-        }
-        Err(e) => {
-            error!("Failed to search equipment types: {}", e);
-            Json(ApiResponse::error_str("Failed to search equipment types"))
-        }
-    }
+    Ok(Json(ApiResponse::success(response)))
 }
 
 async fn bulk_create_equipment_types(
     Extension(service): Extension<EquipmentTypeService>,
+    principal: Option<Principal>,
     Json(request): Json<BulkCreateEquipmentTypeRequest>,
-) -> Json<ApiResponse<BulkCreateEquipmentTypeResponse>> {
+) -> Result<Json<ApiResponse<BulkCreateEquipmentTypeResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
     if request.type_names.is_empty() {
-        return Json(ApiResponse::error_str("No equipment type names provided"));
+        return Err(AppError::Validation(
+            "No equipment type names provided".to_string(),
+        ));
     }
 
     if request.type_names.len() > 100 {
-        return Json(ApiResponse::error_str(
-            "Cannot create more than 100 equipment types at once",
+        return Err(AppError::Validation(
+            "Cannot create more than 100 equipment types at once".to_string(),
         ));
     }
 
     let type_name_refs: Vec<&str> = request.type_names.iter().map(|s| s.as_str()).collect();
 
-    match service.bulk_create(type_name_refs).await {
-        Ok(equipment_types) => {
-            let response: Vec<EquipmentTypeResponse> = equipment_types
-                .into_iter()
-                .map(EquipmentTypeResponse::from)
-                .collect();
-
-            let bulk_response = BulkCreateEquipmentTypeResponse {
-                created_count: response.len(),
-                total_requested: request.type_names.len(),
-                created: response,
-            };
-
-            info!(
-                "Bulk created {}/{} equipment types",
-                bulk_response.created_count, bulk_response.total_requested
-            );
-
-            Json(ApiResponse::success(bulk_response))
-        }
-        Err(e) => {
-            error!("Failed to bulk create equipment types: {}", e);
-            Json(ApiResponse::error_str("Failed to create equipment types"))
-        }
-    }
+    let equipment_types = service.bulk_create(type_name_refs).await?;
+    let response: Vec<EquipmentTypeResponse> = equipment_types
+        .into_iter()
+        .map(EquipmentTypeResponse::from)
+        .collect();
+
+    let bulk_response = BulkCreateEquipmentTypeResponse {
+        created_count: response.len(),
+        total_requested: request.type_names.len(),
+        created: response,
+    };
+
+    info!(
+        "Bulk created {}/{} equipment types",
+        bulk_response.created_count, bulk_response.total_requested
+    );
+
+    Ok(Json(ApiResponse::success(bulk_response)))
 }
 
 async fn get_equipment_types_count(
     Extension(service): Extension<EquipmentTypeService>,
-) -> Json<ApiResponse<CountResponse>> {
-    match service.count().await {
-        Ok(count) => {
-            info!("Total equipment types count: {}", count);
-            Json(ApiResponse::success(CountResponse { count }))
-        }
-        Err(e) => {
-            error!("Failed to get equipment types count: {}", e);
-            Json(ApiResponse::error_str(
-                "Failed to get equipment types count",
-            ))
-        }
-    }
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    info!("Total equipment types count: {}", count);
+    Ok(Json(ApiResponse::success(CountResponse { count })))
 }
 
 async fn check_equipment_type_exists(
     Extension(service): Extension<EquipmentTypeService>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<ExistsResponse>> {
-    match service.exists(id).await {
-        Ok(exists) => Json(ApiResponse::success(ExistsResponse { exists })),
-        Err(e) => {
-            error!("Failed to check if equipment type {} exists: {}", id, e);
-            Json(ApiResponse::error_str(
-                "Failed to check equipment type existence",
-            ))
-        }
-    }
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
 }
 
 async fn check_equipment_type_name_exists(
     Extension(service): Extension<EquipmentTypeService>,
     Query(query): Query<NameExistsQuery>,
-) -> Json<ApiResponse<ExistsResponse>> {
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
     if query.name.trim().is_empty() {
-        return Json(ApiResponse::error_str(
-            "Equipment type name cannot be empty",
+        return Err(AppError::Validation(
+            "Equipment type name cannot be empty".to_string(),
         ));
     }
 
-    match service.name_exists(&query.name).await {
-        Ok(exists) => Json(ApiResponse::success(ExistsResponse { exists })),
-        Err(e) => {
-            error!("Failed to check if equipment type name exists: {}", e);
-            Json(ApiResponse::error_str(
-                "Failed to check equipment type name existence",
-            ))
-        }
-    }
+    let exists = service.name_exists(&query.name).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
 }
 
 // Note: Tests removed due to unknown ApiResponse structure
@@ -0,0 +1,303 @@
+use crate::error::AppError;
+use crate::http::equipment_modes::ModeHistoryResponse;
+use crate::http::equipment_states::StateHistoryResponse;
+use crate::services::event_service::{
+    Event, EventData, EventFilter, EventService, parse_event_types,
+};
+use axum::{
+    Router,
+    extract::{
+        Extension, Query,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{
+        IntoResponse, Response,
+        sse::{self, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+/// Events buffered per client before a slow client holds up its own forwarding task
+const STREAM_BUFFER: usize = 64;
+
+// live feed of entity changes and runtime mode/state changes
+pub fn router() -> Router {
+    Router::new()
+        .route("/api/v1/events/stream", get(stream_events))
+        .route("/api/v1/events/ws", get(websocket_events))
+}
+
+#[derive(Deserialize)]
+pub struct EventStreamQuery {
+    /// comma separated event types, e.g. "equipment,equipment_state". All types when missing
+    pub types: Option<String>,
+    /// only events for this equipment and its subtree
+    pub equipment_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum EventDataResponse {
+    EquipmentMode(ModeHistoryResponse),
+    EquipmentState(StateHistoryResponse),
+}
+
+#[derive(Serialize)]
+pub struct EventResponse {
+    pub event_type: &'static str,
+    pub action: &'static str,
+    pub id: Uuid,
+    pub equipment_id: Option<Uuid>,
+    pub path: Vec<Uuid>,
+    /// the history entry for mode and state changes, entity changes only carry the id
+    pub data: Option<EventDataResponse>,
+}
+
+/// Sent as the last message when a client falls too far behind, it should reload
+/// whatever it shows and subscribe again
+#[derive(Serialize)]
+pub struct LaggedResponse {
+    pub event_type: &'static str,
+    pub missed: u64,
+}
+
+impl From<Event> for EventResponse {
+    fn from(event: Event) -> Self {
+        let data = match event.data {
+            EventData::None => None,
+            EventData::EquipmentMode(mode) => Some(EventDataResponse::EquipmentMode(mode.into())),
+            EventData::EquipmentState(state) => {
+                Some(EventDataResponse::EquipmentState(state.into()))
+            }
+        };
+
+        Self {
+            event_type: event.event_type.as_str(),
+            action: event.action.as_str(),
+            id: event.id,
+            equipment_id: event.equipment_id,
+            path: event.path,
+            data,
+        }
+    }
+}
+
+impl LaggedResponse {
+    fn new(missed: u64) -> Self {
+        Self {
+            event_type: "lagged",
+            missed,
+        }
+    }
+}
+
+async fn subscribe(
+    service: &EventService,
+    query: EventStreamQuery,
+) -> Result<mpsc::Receiver<Result<EventResponse, u64>>, AppError> {
+    let event_types = query
+        .types
+        .as_deref()
+        .map(parse_event_types)
+        .transpose()?
+        .unwrap_or_default();
+    let filter = EventFilter {
+        equipment_id: query.equipment_id,
+        event_types,
+    };
+
+    let events = service.subscribe(&filter).await?;
+    info!("Client subscribed to events: {:?}", filter);
+    Ok(forward(events, filter))
+}
+
+/// Pass the matching events on to one client. Ends with Err(missed) when the client
+/// fell too far behind, so it reloads instead of silently missing changes.
+fn forward(
+    mut events: broadcast::Receiver<Event>,
+    filter: EventFilter,
+) -> mpsc::Receiver<Result<EventResponse, u64>> {
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(async move {
+        loop {
+            let message = match events.recv().await {
+                Ok(event) if filter.matches(&event) => Ok(EventResponse::from(event)),
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("Event stream lagged by {} events", missed);
+                    Err(missed)
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            let end = message.is_err();
+            if tx.send(message).await.is_err() || end {
+                break;
+            }
+        }
+        debug!("Event stream closed");
+    });
+    rx
+}
+
+fn to_sse(message: Result<EventResponse, u64>) -> Result<sse::Event, Infallible> {
+    let event = match message {
+        Ok(event) => sse::Event::default()
+            .event(event.event_type)
+            .json_data(&event),
+        Err(missed) => sse::Event::default()
+            .event("lagged")
+            .json_data(LaggedResponse::new(missed)),
+    };
+    // serializing these types can't fail, an empty event is only a fallback
+    Ok(event.unwrap_or_default())
+}
+
+async fn stream_events(
+    Extension(service): Extension<EventService>,
+    Query(query): Query<EventStreamQuery>,
+) -> Result<Response, AppError> {
+    let rx = subscribe(&service, query).await?;
+    let stream = tokio_stream::StreamExt::map(ReceiverStream::new(rx), to_sse);
+    Ok(Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
+
+async fn websocket_events(
+    Extension(service): Extension<EventService>,
+    Query(query): Query<EventStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let rx = subscribe(&service, query).await?;
+    Ok(ws.on_upgrade(move |socket| send_events(socket, rx)))
+}
+
+async fn send_events(mut socket: WebSocket, mut rx: mpsc::Receiver<Result<EventResponse, u64>>) {
+    loop {
+        tokio::select! {
+            message = rx.recv() => {
+                let (json, end) = match message {
+                    Some(Ok(event)) => (serde_json::to_string(&event), false),
+                    Some(Err(missed)) => (serde_json::to_string(&LaggedResponse::new(missed)), true),
+                    None => break,
+                };
+                let sent = match json {
+                    Ok(json) => socket.send(Message::Text(json.into())).await.is_ok(),
+                    Err(e) => {
+                        error!("Failed to serialize event: {}", e);
+                        true
+                    }
+                };
+                if !sent || end {
+                    break;
+                }
+            }
+            // the feed is one way, only watch for the client going away
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!("WebSocket event feed closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use std::time::Duration;
+    use tokio_stream::StreamExt;
+    use tower::ServiceExt;
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_stream_events_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let events = EventService::new(pool.clone());
+        let listener = events.clone();
+        tokio::spawn(async move { listener.listen().await });
+        let app = router().layer(Extension(events));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/events/stream?types=equipment,widgets")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["code"], "validation_error");
+        assert!(body["error"].as_str().unwrap().contains("widgets"));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/events/stream?equipment_id={}",
+                Uuid::new_v4()
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["code"], "not_found");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/events/stream?types=mode_group")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let mut body = response.into_body().into_data_stream();
+
+        // the listener connects in the background, keep creating groups until it is up
+        let mut received = None;
+        for i in 0..25 {
+            sqlx::query!(
+                "INSERT INTO core.mode_group (mode_group_name, mode_group_description) VALUES ($1, 'Packaging line modes')",
+                format!("Packaging {}", i)
+            )
+            .execute(&pool)
+            .await?;
+            if let Ok(Some(chunk)) =
+                tokio::time::timeout(Duration::from_millis(200), body.next()).await
+            {
+                received = Some(String::from_utf8(chunk.unwrap().to_vec()).unwrap());
+                break;
+            }
+        }
+        let frame = received.expect("mode group event should be streamed");
+
+        assert!(frame.starts_with("event: mode_group\n"));
+        let data: Value = serde_json::from_str(
+            frame
+                .lines()
+                .find_map(|line| line.strip_prefix("data: "))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(data["action"], "created");
+        assert!(data["data"].is_null());
+
+        Ok(())
+    }
+}
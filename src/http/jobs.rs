@@ -0,0 +1,594 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::models::operations::{Job, JobStatus, JobStatusChange, JobTransition};
+use crate::services::job_service::JobService;
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// job endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route("/api/v1/jobs", get(get_all_jobs).post(create_job))
+        .route("/api/v1/jobs/count", get(get_job_count))
+        .route("/api/v1/jobs/{id}", get(get_job_by_id))
+        .route("/api/v1/jobs/{id}/history", get(get_job_status_history))
+        .route("/api/v1/jobs/{id}/release", post(release_job))
+        .route("/api/v1/jobs/{id}/start", post(start_job))
+        .route("/api/v1/jobs/{id}/pause", post(pause_job))
+        .route("/api/v1/jobs/{id}/resume", post(resume_job))
+        .route("/api/v1/jobs/{id}/complete", post(complete_job))
+        .route("/api/v1/jobs/{id}/cancel", post(cancel_job))
+        .route("/api/v1/jobs/update/{id}", post(update_job))
+        .route(
+            "/api/v1/jobs/update-metadata/{id}",
+            post(update_job_metadata),
+        )
+        .route("/api/v1/jobs/delete/{id}", post(delete_job))
+        .route("/api/v1/jobs/exists/{id}", get(check_job_exists))
+}
+
+// request/response dtos
+#[derive(Serialize)]
+pub struct JobResponse {
+    pub job_id: Uuid,
+    pub work_order_id: Uuid,
+    pub equipment_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub job_metadata: Value,
+    pub job_status: JobStatus,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub released_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub started_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub completed_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cancelled_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct JobStatusChangeResponse {
+    pub history_id: Uuid,
+    pub job_id: Uuid,
+    pub from_status: JobStatus,
+    pub to_status: JobStatus,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub changed_at: Option<OffsetDateTime>,
+    pub changed_by: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateJobRequest {
+    pub work_order_id: Uuid,
+    pub equipment_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub job_metadata: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateJobRequest {
+    pub equipment_id: Uuid,
+    pub planned_quantity: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateJobMetadataRequest {
+    pub job_metadata: Value,
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: i64,
+}
+
+#[derive(Serialize)]
+pub struct ExistsResponse {
+    pub exists: bool,
+}
+
+#[derive(Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub total_count: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Deserialize)]
+pub struct JobListQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+    pub work_order_id: Option<Uuid>,
+    pub equipment_id: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct JobFilterQuery {
+    pub work_order_id: Option<Uuid>,
+    pub equipment_id: Option<Uuid>,
+}
+
+fn default_page() -> i64 {
+    1
+}
+fn default_per_page() -> i64 {
+    50
+}
+
+// service model -> response model
+impl From<Job> for JobResponse {
+    fn from(job: Job) -> Self {
+        Self {
+            job_id: job.job_id,
+            work_order_id: job.work_order_id,
+            equipment_id: job.equipment_id,
+            planned_quantity: job.planned_quantity,
+            job_metadata: job.job_metadata,
+            job_status: job.job_status,
+            released_at: job.released_at,
+            started_at: job.started_at,
+            completed_at: job.completed_at,
+            cancelled_at: job.cancelled_at,
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+        }
+    }
+}
+
+impl From<JobStatusChange> for JobStatusChangeResponse {
+    fn from(change: JobStatusChange) -> Self {
+        Self {
+            history_id: change.history_id,
+            job_id: change.job_id,
+            from_status: change.from_status,
+            to_status: change.to_status,
+            changed_at: Some(change.changed_at),
+            changed_by: change.changed_by,
+        }
+    }
+}
+
+// handler functions for http endpoints
+async fn get_all_jobs(
+    Extension(service): Extension<JobService>,
+    Query(query): Query<JobListQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<JobResponse>>>, AppError> {
+    // Convert 1-based page to 0-based offset
+    let offset = (query.page - 1) * query.per_page;
+
+    let (jobs, total_count) = service
+        .get_paginated(
+            query.work_order_id,
+            query.equipment_id,
+            offset,
+            query.per_page,
+        )
+        .await?;
+    let response: Vec<JobResponse> = jobs.into_iter().map(JobResponse::from).collect();
+
+    let total_pages = (total_count + query.per_page - 1) / query.per_page;
+
+    let paginated_response = PaginatedResponse {
+        data: response,
+        total_count,
+        page: query.page,
+        per_page: query.per_page,
+        total_pages,
+    };
+
+    info!(
+        "Retrieved {} jobs (page {}/{}, total: {})",
+        paginated_response.data.len(),
+        query.page,
+        total_pages,
+        total_count
+    );
+
+    Ok(Json(ApiResponse::success(paginated_response)))
+}
+
+async fn get_job_by_id(
+    Extension(service): Extension<JobService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    let job = service.get_by_id(id).await?;
+    info!("Retrieved job: {}", job.job_id);
+    Ok(Json(ApiResponse::success(JobResponse::from(job))))
+}
+
+async fn create_job(
+    Extension(service): Extension<JobService>,
+    Json(request): Json<CreateJobRequest>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    let job = service
+        .create(
+            request.work_order_id,
+            request.equipment_id,
+            request.planned_quantity,
+            request.job_metadata.as_ref(),
+        )
+        .await?;
+    info!("Created job: {}", job.job_id);
+    Ok(Json(ApiResponse::success(JobResponse::from(job))))
+}
+
+async fn update_job(
+    Extension(service): Extension<JobService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateJobRequest>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    let job = service
+        .update(id, request.equipment_id, request.planned_quantity)
+        .await?;
+    info!("Updated job {}", id);
+    Ok(Json(ApiResponse::success(JobResponse::from(job))))
+}
+
+async fn update_job_metadata(
+    Extension(service): Extension<JobService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateJobMetadataRequest>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    let job = service.update_metadata(id, &request.job_metadata).await?;
+    info!("Updated job metadata {}", id);
+    Ok(Json(ApiResponse::success(JobResponse::from(job))))
+}
+
+async fn get_job_status_history(
+    Extension(service): Extension<JobService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<JobStatusChangeResponse>>>, AppError> {
+    let history = service.get_status_history(id).await?;
+    info!("Retrieved {} status changes for job {}", history.len(), id);
+    Ok(Json(ApiResponse::success(
+        history
+            .into_iter()
+            .map(JobStatusChangeResponse::from)
+            .collect(),
+    )))
+}
+
+async fn release_job(
+    Extension(service): Extension<JobService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    transition_job(service, principal, id, JobTransition::Release).await
+}
+
+async fn start_job(
+    Extension(service): Extension<JobService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    transition_job(service, principal, id, JobTransition::Start).await
+}
+
+async fn pause_job(
+    Extension(service): Extension<JobService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    transition_job(service, principal, id, JobTransition::Pause).await
+}
+
+async fn resume_job(
+    Extension(service): Extension<JobService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    transition_job(service, principal, id, JobTransition::Resume).await
+}
+
+async fn complete_job(
+    Extension(service): Extension<JobService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    transition_job(service, principal, id, JobTransition::Complete).await
+}
+
+async fn cancel_job(
+    Extension(service): Extension<JobService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    transition_job(service, principal, id, JobTransition::Cancel).await
+}
+
+// transitions the job's status does not allow come back as 409
+async fn transition_job(
+    service: JobService,
+    principal: Option<Principal>,
+    id: Uuid,
+    transition: JobTransition,
+) -> Result<Json<ApiResponse<JobResponse>>, AppError> {
+    let changed_by = principal.map(|principal| principal.actor());
+    let job = service
+        .transition(id, transition, changed_by.as_deref())
+        .await?;
+    info!("Job {} is now {}", id, job.job_status);
+    Ok(Json(ApiResponse::success(JobResponse::from(job))))
+}
+
+async fn delete_job(
+    Extension(service): Extension<JobService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    service.delete(id).await?;
+    info!("Deleted job: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn get_job_count(
+    Extension(service): Extension<JobService>,
+    Query(filter): Query<JobFilterQuery>,
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service
+        .count(filter.work_order_id, filter.equipment_id)
+        .await?;
+    info!("Total job count: {}", count);
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn check_job_exists(
+    Extension(service): Extension<JobService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_job_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let work_order_id = sqlx::query_scalar!(
+            r#"WITH p AS (
+                   INSERT INTO operations.product (product_name) VALUES ('Widget')
+                   RETURNING product_id
+               )
+               INSERT INTO operations.work_order (work_order_name, product_id)
+               SELECT 'WO-1', product_id FROM p
+               RETURNING work_order_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(JobService::new(pool)));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/jobs")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "work_order_id": work_order_id,
+                    "equipment_id": equipment_id,
+                    "planned_quantity": 25
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["equipment_id"], equipment_id.to_string());
+        let job_id = body["data"]["job_id"].as_str().unwrap().to_string();
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/jobs?equipment_id={}", equipment_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["total_count"], 1);
+        assert_eq!(body["data"]["data"][0]["job_id"], job_id);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/jobs/count?work_order_id={}",
+                Uuid::new_v4()
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["count"], 0);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/jobs/update-metadata/{}", job_id))
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"job_metadata": "shift A"}).to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("json object"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_job_transition_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let job_id = sqlx::query_scalar!(
+            r#"WITH p AS (
+                   INSERT INTO operations.product (product_name) VALUES ('Widget')
+                   RETURNING product_id
+               ), wo AS (
+                   INSERT INTO operations.work_order (work_order_name, product_id)
+                   SELECT 'WO-1', product_id FROM p
+                   RETURNING work_order_id
+               ), eq AS (
+                   INSERT INTO core.equipment (equipment_name, equipment_type_id)
+                   SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+                   RETURNING equipment_id
+               )
+               INSERT INTO operations.job (work_order_id, equipment_id)
+               SELECT work_order_id, equipment_id FROM wo, eq
+               RETURNING job_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(JobService::new(pool)));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/jobs/{}/release", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["job_status"], "released");
+        assert!(body["data"]["released_at"].is_string());
+
+        // the equipment has no mode yet so it cannot start
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/jobs/{}/start", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert!(
+            body["error"]
+                .as_str()
+                .unwrap()
+                .contains("jobs can only run")
+        );
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/jobs/{}/pause", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = body_json(response).await;
+        assert_eq!(
+            body["error"],
+            "invalid transition: cannot pause a job that is released"
+        );
+        assert_eq!(body["code"], "conflict");
+
+        // a job that was never started cannot be completed
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/jobs/{}/complete", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = body_json(response).await;
+        assert_eq!(
+            body["error"],
+            "invalid transition: cannot complete a job that is released"
+        );
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/jobs/{}/cancel", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["job_status"], "cancelled");
+
+        // cancelled is final
+        for transition in ["release", "resume", "cancel"] {
+            let request = Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/jobs/{}/{}", job_id, transition))
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+        }
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/jobs/{}/start", Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/jobs/{}/history", job_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+        assert_eq!(body["data"][1]["from_status"], "released");
+        assert_eq!(body["data"][1]["to_status"], "cancelled");
+
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
+use crate::error::AppError;
 use crate::http::date_format;
+use crate::http::mode_groups::UsageStatsQuery;
 use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::models::core::ModeUsageStats;
 use crate::services::mode_service::{Mode, ModeService};
 use axum::{
     Json, Router,
@@ -8,15 +12,18 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
-use tracing::{error, info};
+use tracing::info;
 use uuid::Uuid;
 
 pub fn router() -> Router {
     Router::new()
         .route("/api/v1/modes", get(get_all_modes).post(create_mode))
         .route("/api/v1/modes/count", get(get_modes_count))
+        .route("/api/v1/modes/stats", get(get_modes_usage_stats))
         .route("/api/v1/modes/{id}", get(get_mode_by_id))
+        .route("/api/v1/modes/{id}/stats", get(get_mode_usage_stats))
         .route("/api/v1/modes/delete/{id}", post(delete_mode))
+        .route("/api/v1/modes/restore/{id}", post(restore_mode))
 }
 
 #[derive(Deserialize)]
@@ -88,108 +95,103 @@ impl From<Mode> for ModeResponse {
 async fn get_all_modes(
     Extension(service): Extension<ModeService>,
     Query(query): Query<PaginationQuery>,
-) -> Json<ApiResponse<PaginatedResponse<ModeResponse>>> {
+) -> Result<Json<ApiResponse<PaginatedResponse<ModeResponse>>>, AppError> {
     let offset = (query.page - 1) * query.per_page;
 
-    let result = service
+    let (modes, total_count) = service
         .search_with_filters(
             query.mode_group_id,
             query.search.as_deref(),
             offset,
             query.per_page,
         )
-        .await;
-
-    match result {
-        Ok((modes, total_count)) => {
-            let response = PaginatedResponse {
-                data: modes.into_iter().map(ModeResponse::from).collect(),
-                total_count,
-                page: query.page,
-                per_page: query.per_page,
-                total_pages: (total_count + query.per_page - 1) / query.per_page,
-            };
-
-            info!(
-                "Retrieved {} modes (page {}/{})",
-                response.data.len(),
-                response.page,
-                response.total_pages
-            );
-
-            Json(ApiResponse::success(response))
-        }
-        Err(e) => {
-            error!("Failed to get modes: {}", e);
-            Json(ApiResponse::error_str("Failed to retrieve modes"))
-        }
-    }
+        .await?;
+
+    let response = PaginatedResponse {
+        data: modes.into_iter().map(ModeResponse::from).collect(),
+        total_count,
+        page: query.page,
+        per_page: query.per_page,
+        total_pages: (total_count + query.per_page - 1) / query.per_page,
+    };
+
+    info!(
+        "Retrieved {} modes (page {}/{})",
+        response.data.len(),
+        response.page,
+        response.total_pages
+    );
+
+    Ok(Json(ApiResponse::success(response)))
 }
 
 async fn get_mode_by_id(
     Extension(service): Extension<ModeService>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<ModeResponse>> {
-    match service.get_by_id(id).await {
-        Ok(mode) => Json(ApiResponse::success(ModeResponse::from(mode))),
-        Err(e) => {
-            if e.to_string().contains("not found") {
-                Json(ApiResponse::error_str("Mode not found"))
-            } else {
-                error!("Failed to fetch mode {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to retrieve mode"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<ModeResponse>>, AppError> {
+    let mode = service.get_by_id(id).await?;
+    Ok(Json(ApiResponse::success(ModeResponse::from(mode))))
 }
 
 async fn create_mode(
     Extension(service): Extension<ModeService>,
+    principal: Option<Principal>,
     Json(payload): Json<CreateModeRequest>,
-) -> Json<ApiResponse<ModeResponse>> {
-    match service
+) -> Result<Json<ApiResponse<ModeResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mode = service
         .create(payload.mode_group_id, &payload.mode_description)
-        .await
-    {
-        Ok(mode) => {
-            info!("Created mode: {}", mode.mode_description);
-            Json(ApiResponse::success(ModeResponse::from(mode)))
-        }
-        Err(e) => {
-            error!("Failed to create mode: {}", e);
-            Json(ApiResponse::error_str("Failed to create mode"))
-        }
-    }
+        .await?;
+    info!("Created mode: {}", mode.mode_description);
+    Ok(Json(ApiResponse::success(ModeResponse::from(mode))))
 }
 
 async fn delete_mode(
     Extension(service): Extension<ModeService>,
+    principal: Option<Principal>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<()>> {
-    match service.delete(id).await {
-        Ok(_) => {
-            info!("Deleted mode: {}", id);
-            Json(ApiResponse::success(()))
-        }
-        Err(e) => {
-            if e.to_string().contains("not found") {
-                Json(ApiResponse::error_str("Mode not found"))
-            } else {
-                error!("Failed to delete mode {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to delete mode"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted mode: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn restore_mode(
+    Extension(service): Extension<ModeService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ModeResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mode = service.restore(id).await?;
+    info!("Restored mode: {}", mode.mode_description);
+    Ok(Json(ApiResponse::success(ModeResponse::from(mode))))
 }
 
 async fn get_modes_count(
     Extension(service): Extension<ModeService>,
-) -> Json<ApiResponse<CountResponse>> {
-    match service.count().await {
-        Ok(count) => Json(ApiResponse::success(CountResponse { count })),
-        Err(e) => {
-            error!("Failed to get modes count: {}", e);
-            Json(ApiResponse::error_str("Failed to get modes count"))
-        }
-    }
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn get_modes_usage_stats(
+    Extension(service): Extension<ModeService>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<ModeUsageStats>>, AppError> {
+    let stats = service.usage_stats(None, query.from, query.to).await?;
+    info!(
+        "Mode usage stats: {} modes, {} unused",
+        stats.summary.total_modes, stats.summary.unused_modes
+    );
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+async fn get_mode_usage_stats(
+    Extension(service): Extension<ModeService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<ModeUsageStats>>, AppError> {
+    let stats = service.usage_stats(Some(id), query.from, query.to).await?;
+    Ok(Json(ApiResponse::success(stats)))
 }
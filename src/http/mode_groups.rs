@@ -1,5 +1,10 @@
+use crate::error::AppError;
 use crate::http::date_format;
 use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::models::core::{
+    BulkGroupAssignment, EquipmentModeGroupMapping, GroupEquipment, ModeGroupUsageStats,
+};
 use crate::services::mode_group_service::ModeGroupService;
 use axum::{
     Json, Router,
@@ -8,7 +13,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
-use tracing::{error, info};
+use tracing::info;
 use uuid::Uuid;
 
 // mode group endpoints
@@ -20,6 +25,10 @@ pub fn router() -> Router {
         )
         .route("/api/v1/mode-groups/bulk", post(bulk_create_mode_groups))
         .route("/api/v1/mode-groups/count", get(get_mode_groups_count))
+        .route(
+            "/api/v1/mode-groups/stats",
+            get(get_mode_groups_usage_stats),
+        )
         .route("/api/v1/mode-groups/{id}", get(get_mode_group_by_id))
         .route(
             "/api/v1/mode-groups/update-name/{id}",
@@ -30,6 +39,28 @@ pub fn router() -> Router {
             post(update_mode_group_description),
         )
         .route("/api/v1/mode-groups/delete/{id}", post(delete_mode_group))
+        .route("/api/v1/mode-groups/restore/{id}", post(restore_mode_group))
+        .route(
+            "/api/v1/mode-groups/{id}/equipment",
+            get(get_mode_group_equipment),
+        )
+        .route(
+            "/api/v1/mode-groups/{id}/stats",
+            get(get_mode_group_usage_stats),
+        )
+        .route("/api/v1/mode-groups/assign/{id}", post(assign_equipment))
+        .route(
+            "/api/v1/mode-groups/unassign/{id}",
+            post(unassign_equipment),
+        )
+        .route(
+            "/api/v1/mode-groups/bulk-assign/{id}",
+            post(bulk_assign_equipment),
+        )
+        .route(
+            "/api/v1/equipment/{id}/mode-groups",
+            get(get_equipment_mode_groups),
+        )
         .route(
             "/api/v1/mode-groups/exists/{id}",
             get(check_mode_group_exists),
@@ -93,6 +124,25 @@ pub struct BulkCreateModeGroupResponse {
     pub total_requested: usize,
 }
 
+#[derive(Deserialize)]
+pub struct AssignEquipmentRequest {
+    pub equipment_id: Uuid,
+}
+
+/// Range the runtime in the usage stats covers, the last 30 days when left out
+#[derive(Deserialize)]
+pub struct UsageStatsQuery {
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkAssignEquipmentRequest {
+    pub equipment_ids: Vec<Uuid>,
+}
+
 #[derive(Serialize)]
 pub struct CountResponse {
     pub count: i64,
@@ -154,235 +204,179 @@ impl From<crate::services::mode_group_service::ModeGroup> for ModeGroupResponse
 async fn get_all_mode_groups(
     Extension(service): Extension<ModeGroupService>,
     Query(pagination): Query<PaginationQuery>,
-) -> Json<ApiResponse<PaginatedResponse<ModeGroupResponse>>> {
+) -> Result<Json<ApiResponse<PaginatedResponse<ModeGroupResponse>>>, AppError> {
     // Convert 1-based page to 0-based offset
     let offset = (pagination.page - 1) * pagination.per_page;
 
-    match service.get_paginated(offset, pagination.per_page).await {
-        Ok((mode_groups, total_count)) => {
-            let response: Vec<ModeGroupResponse> = mode_groups
-                .into_iter()
-                .map(ModeGroupResponse::from)
-                .collect();
-
-            let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
-
-            let paginated_response = PaginatedResponse {
-                data: response,
-                total_count,
-                page: pagination.page,
-                per_page: pagination.per_page,
-                total_pages,
-            };
-
-            info!(
-                "Retrieved {} mode groups (page {}/{}, total: {})",
-                paginated_response.data.len(),
-                pagination.page,
-                total_pages,
-                total_count
-            );
-
-            Json(ApiResponse::success(paginated_response))
-        }
-        Err(e) => {
-            error!("Failed to get mode groups: {}", e);
-            Json(ApiResponse::error_str("Failed to retrieve mode groups"))
-        }
-    }
+    let (mode_groups, total_count) = service.get_paginated(offset, pagination.per_page).await?;
+    let response: Vec<ModeGroupResponse> = mode_groups
+        .into_iter()
+        .map(ModeGroupResponse::from)
+        .collect();
+
+    let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
+
+    let paginated_response = PaginatedResponse {
+        data: response,
+        total_count,
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total_pages,
+    };
+
+    info!(
+        "Retrieved {} mode groups (page {}/{}, total: {})",
+        paginated_response.data.len(),
+        pagination.page,
+        total_pages,
+        total_count
+    );
+
+    Ok(Json(ApiResponse::success(paginated_response)))
 }
 
 async fn get_mode_group_by_id(
     Extension(service): Extension<ModeGroupService>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<ModeGroupResponse>> {
-    match service.get_by_id(id).await {
-        Ok(mode_group) => {
-            info!("Retrieved mode group: {}", mode_group.mode_group_name);
-            Json(ApiResponse::success(ModeGroupResponse::from(mode_group)))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("not found") {
-                Json(ApiResponse::error_str("Mode group not found"))
-            } else {
-                error!("Failed to get mode group {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to retrieve mode group"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<ModeGroupResponse>>, AppError> {
+    let mode_group = service.get_by_id(id).await?;
+    info!("Retrieved mode group: {}", mode_group.mode_group_name);
+    Ok(Json(ApiResponse::success(ModeGroupResponse::from(
+        mode_group,
+    ))))
 }
 
 async fn get_mode_group_by_name(
     Extension(service): Extension<ModeGroupService>,
     Query(query): Query<NameQuery>,
-) -> Json<ApiResponse<ModeGroupResponse>> {
+) -> Result<Json<ApiResponse<ModeGroupResponse>>, AppError> {
     if query.name.trim().is_empty() {
-        return Json(ApiResponse::error_str("Mode group name cannot be empty"));
+        return Err(AppError::Validation(
+            "Mode group name cannot be empty".to_string(),
+        ));
     }
 
-    match service.get_by_name(&query.name).await {
-        Ok(Some(mode_group)) => {
-            info!(
-                "Retrieved mode group by name: {}",
-                mode_group.mode_group_name
-            );
-            Json(ApiResponse::success(ModeGroupResponse::from(mode_group)))
-        }
-        Ok(None) => Json(ApiResponse::error_str("Mode group not found")),
-        Err(e) => {
-            error!("Failed to get mode group by name '{}': {}", query.name, e);
-            Json(ApiResponse::error_str("Failed to retrieve mode group"))
-        }
-    }
+    let mode_group = service
+        .get_by_name(&query.name)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Mode group not found".to_string()))?;
+    info!(
+        "Retrieved mode group by name: {}",
+        mode_group.mode_group_name
+    );
+    Ok(Json(ApiResponse::success(ModeGroupResponse::from(
+        mode_group,
+    ))))
 }
 
 async fn get_mode_group_by_description(
     Extension(service): Extension<ModeGroupService>,
     Query(query): Query<DescriptionQuery>,
-) -> Json<ApiResponse<ModeGroupResponse>> {
+) -> Result<Json<ApiResponse<ModeGroupResponse>>, AppError> {
     if query.description.trim().is_empty() {
-        return Json(ApiResponse::error_str(
-            "Mode group description cannot be empty",
+        return Err(AppError::Validation(
+            "Mode group description cannot be empty".to_string(),
         ));
     }
 
-    match service.get_by_description(&query.description).await {
-        Ok(Some(mode_group)) => {
-            info!(
-                "Retrieved mode group by description: {}",
-                mode_group.mode_group_name
-            );
-            Json(ApiResponse::success(ModeGroupResponse::from(mode_group)))
-        }
-        Ok(None) => Json(ApiResponse::error_str("Mode group not found")),
-        Err(e) => {
-            error!("Failed to get mode group by description: {}", e);
-            Json(ApiResponse::error_str("Failed to retrieve mode group"))
-        }
-    }
+    let mode_group = service
+        .get_by_description(&query.description)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Mode group not found".to_string()))?;
+    info!(
+        "Retrieved mode group by description: {}",
+        mode_group.mode_group_name
+    );
+    Ok(Json(ApiResponse::success(ModeGroupResponse::from(
+        mode_group,
+    ))))
 }
 
 async fn create_mode_group(
     Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
     Json(request): Json<CreateModeGroupRequest>,
-) -> Json<ApiResponse<ModeGroupResponse>> {
-    match service
+) -> Result<Json<ApiResponse<ModeGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mode_group = service
         .create(&request.mode_group_name, &request.mode_group_description)
-        .await
-    {
-        Ok(mode_group) => {
-            info!("Created mode group: {}", mode_group.mode_group_name);
-            Json(ApiResponse::success(ModeGroupResponse::from(mode_group)))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("already exists") {
-                Json(ApiResponse::error_str("Mode group name already exists"))
-            } else if error_msg.contains("cannot be empty") || error_msg.contains("cannot exceed") {
-                Json(ApiResponse::error(format!("Invalid input: {}", error_msg)))
-            } else {
-                error!("Failed to create mode group: {}", e);
-                Json(ApiResponse::error_str("Failed to create mode group"))
-            }
-        }
-    }
+        .await?;
+    info!("Created mode group: {}", mode_group.mode_group_name);
+    Ok(Json(ApiResponse::success(ModeGroupResponse::from(
+        mode_group,
+    ))))
 }
 
 async fn update_mode_group_name(
     Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateModeGroupNameRequest>,
-) -> Json<ApiResponse<ModeGroupResponse>> {
-    match service.update_name(id, &request.mode_group_name).await {
-        Ok(mode_group) => {
-            info!(
-                "Updated mode group name {}: {}",
-                id, mode_group.mode_group_name
-            );
-            Json(ApiResponse::success(ModeGroupResponse::from(mode_group)))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("not found") {
-                Json(ApiResponse::error_str("Mode group not found"))
-            } else if error_msg.contains("already exists") {
-                Json(ApiResponse::error_str("Mode group name already exists"))
-            } else if error_msg.contains("cannot be empty") || error_msg.contains("cannot exceed") {
-                Json(ApiResponse::error(format!("Invalid input: {}", error_msg)))
-            } else {
-                error!("Failed to update mode group name {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to update mode group name"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<ModeGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mode_group = service.update_name(id, &request.mode_group_name).await?;
+    info!(
+        "Updated mode group name {}: {}",
+        id, mode_group.mode_group_name
+    );
+    Ok(Json(ApiResponse::success(ModeGroupResponse::from(
+        mode_group,
+    ))))
 }
 
 async fn update_mode_group_description(
     Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
     Path(id): Path<Uuid>,
     Json(request): Json<UpdateModeGroupDescriptionRequest>,
-) -> Json<ApiResponse<ModeGroupResponse>> {
-    match service
+) -> Result<Json<ApiResponse<ModeGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mode_group = service
         .update_description(id, &request.mode_group_description)
-        .await
-    {
-        Ok(mode_group) => {
-            info!("Updated mode group description {}", id);
-            Json(ApiResponse::success(ModeGroupResponse::from(mode_group)))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("not found") {
-                Json(ApiResponse::error_str("Mode group not found"))
-            } else if error_msg.contains("cannot be empty") || error_msg.contains("cannot exceed") {
-                Json(ApiResponse::error(format!("Invalid input: {}", error_msg)))
-            } else {
-                error!("Failed to update mode group description {}: {}", id, e);
-                Json(ApiResponse::error_str(
-                    "Failed to update mode group description",
-                ))
-            }
-        }
-    }
+        .await?;
+    info!("Updated mode group description {}", id);
+    Ok(Json(ApiResponse::success(ModeGroupResponse::from(
+        mode_group,
+    ))))
 }
 
 async fn delete_mode_group(
     Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<()>> {
-    match service.delete(id).await {
-        Ok(()) => {
-            info!("Deleted mode group: {}", id);
-            Json(ApiResponse::success(()))
-        }
-        Err(e) => {
-            let error_msg = e.to_string();
-            if error_msg.contains("not found") {
-                Json(ApiResponse::error_str("Mode group not found"))
-            } else if error_msg.contains("in use") {
-                Json(ApiResponse::error_str(
-                    "Mode group is in use and cannot be deleted",
-                ))
-            } else {
-                error!("Failed to delete mode group {}: {}", id, e);
-                Json(ApiResponse::error_str("Failed to delete mode group"))
-            }
-        }
-    }
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted mode group: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn restore_mode_group(
+    Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ModeGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mode_group = service.restore(id).await?;
+    info!("Restored mode group: {}", mode_group.mode_group_name);
+    Ok(Json(ApiResponse::success(ModeGroupResponse::from(
+        mode_group,
+    ))))
 }
 
 async fn bulk_create_mode_groups(
     Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
     Json(request): Json<BulkCreateModeGroupRequest>,
-) -> Json<ApiResponse<BulkCreateModeGroupResponse>> {
+) -> Result<Json<ApiResponse<BulkCreateModeGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
     if request.mode_groups.is_empty() {
-        return Json(ApiResponse::error_str("No mode groups provided"));
+        return Err(AppError::Validation("No mode groups provided".to_string()));
     }
 
     if request.mode_groups.len() > 100 {
-        return Json(ApiResponse::error_str(
-            "Cannot create more than 100 mode groups at once",
+        return Err(AppError::Validation(
+            "Cannot create more than 100 mode groups at once".to_string(),
         ));
     }
 
@@ -397,61 +391,153 @@ async fn bulk_create_mode_groups(
         })
         .collect();
 
-    match service.bulk_create(mode_group_tuples).await {
-        Ok(mode_groups) => {
-            let response: Vec<ModeGroupResponse> = mode_groups
-                .into_iter()
-                .map(ModeGroupResponse::from)
-                .collect();
-
-            let bulk_response = BulkCreateModeGroupResponse {
-                created_count: response.len(),
-                total_requested: request.mode_groups.len(),
-                created: response,
-            };
-
-            info!(
-                "Bulk created {}/{} mode groups",
-                bulk_response.created_count, bulk_response.total_requested
-            );
-
-            Json(ApiResponse::success(bulk_response))
-        }
-        Err(e) => {
-            error!("Failed to bulk create mode groups: {}", e);
-            Json(ApiResponse::error_str("Failed to create mode groups"))
-        }
+    let mode_groups = service.bulk_create(mode_group_tuples).await?;
+    let response: Vec<ModeGroupResponse> = mode_groups
+        .into_iter()
+        .map(ModeGroupResponse::from)
+        .collect();
+
+    let bulk_response = BulkCreateModeGroupResponse {
+        created_count: response.len(),
+        total_requested: request.mode_groups.len(),
+        created: response,
+    };
+
+    info!(
+        "Bulk created {}/{} mode groups",
+        bulk_response.created_count, bulk_response.total_requested
+    );
+
+    Ok(Json(ApiResponse::success(bulk_response)))
+}
+
+async fn get_mode_group_equipment(
+    Extension(service): Extension<ModeGroupService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<GroupEquipment>>>, AppError> {
+    let equipment = service.get_equipment(id).await?;
+    info!(
+        "Retrieved {} equipment for mode group {}",
+        equipment.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(equipment)))
+}
+
+async fn assign_equipment(
+    Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AssignEquipmentRequest>,
+) -> Result<Json<ApiResponse<EquipmentModeGroupMapping>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mapping = service.assign_equipment(id, request.equipment_id).await?;
+    info!(
+        "Assigned equipment {} to mode group {}",
+        request.equipment_id, id
+    );
+    Ok(Json(ApiResponse::success(mapping)))
+}
+
+async fn unassign_equipment(
+    Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AssignEquipmentRequest>,
+) -> Result<Json<ApiResponse<EquipmentModeGroupMapping>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mapping = service.unassign_equipment(id, request.equipment_id).await?;
+    info!(
+        "Unassigned equipment {} from mode group {}",
+        request.equipment_id, id
+    );
+    Ok(Json(ApiResponse::success(mapping)))
+}
+
+async fn bulk_assign_equipment(
+    Extension(service): Extension<ModeGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<BulkAssignEquipmentRequest>,
+) -> Result<Json<ApiResponse<BulkGroupAssignment>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    if request.equipment_ids.is_empty() {
+        return Err(AppError::Validation(
+            "No equipment IDs provided".to_string(),
+        ));
     }
+
+    if request.equipment_ids.len() > 1000 {
+        return Err(AppError::Validation(
+            "Cannot assign more than 1000 equipment at once".to_string(),
+        ));
+    }
+
+    let assignment = service
+        .bulk_assign_equipment(id, &request.equipment_ids)
+        .await?;
+    info!(
+        "Bulk assigned {}/{} equipment to mode group {}",
+        assignment.assigned_equipment_ids.len(),
+        request.equipment_ids.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(assignment)))
+}
+
+async fn get_equipment_mode_groups(
+    Extension(service): Extension<ModeGroupService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<ModeGroupResponse>>>, AppError> {
+    let mode_groups = service.get_by_equipment_id(id).await?;
+    info!(
+        "Retrieved {} mode groups for equipment {}",
+        mode_groups.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(
+        mode_groups
+            .into_iter()
+            .map(ModeGroupResponse::from)
+            .collect(),
+    )))
 }
 
 async fn get_mode_groups_count(
     Extension(service): Extension<ModeGroupService>,
-) -> Json<ApiResponse<CountResponse>> {
-    match service.count().await {
-        Ok(count) => {
-            info!("Total mode groups count: {}", count);
-            Json(ApiResponse::success(CountResponse { count }))
-        }
-        Err(e) => {
-            error!("Failed to get mode groups count: {}", e);
-            Json(ApiResponse::error_str("Failed to get mode groups count"))
-        }
-    }
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    info!("Total mode groups count: {}", count);
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn get_mode_groups_usage_stats(
+    Extension(service): Extension<ModeGroupService>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<ModeGroupUsageStats>>, AppError> {
+    let stats = service.usage_stats(None, query.from, query.to).await?;
+    info!(
+        "Mode group usage stats: {} groups, {} idle",
+        stats.summary.total_mode_groups, stats.summary.idle_mode_groups
+    );
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+async fn get_mode_group_usage_stats(
+    Extension(service): Extension<ModeGroupService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<ModeGroupUsageStats>>, AppError> {
+    let stats = service.usage_stats(Some(id), query.from, query.to).await?;
+    Ok(Json(ApiResponse::success(stats)))
 }
 
 async fn check_mode_group_exists(
     Extension(service): Extension<ModeGroupService>,
     Path(id): Path<Uuid>,
-) -> Json<ApiResponse<ExistsResponse>> {
-    match service.exists(id).await {
-        Ok(exists) => Json(ApiResponse::success(ExistsResponse { exists })),
-        Err(e) => {
-            error!("Failed to check if mode group {} exists: {}", id, e);
-            Json(ApiResponse::error_str(
-                "Failed to check mode group existence",
-            ))
-        }
-    }
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
 }
 
 #[cfg(test)]
@@ -653,4 +739,111 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn test_error_status_codes(pool: PgPool) -> sqlx::Result<()> {
+        let service = create_test_service(pool);
+        service
+            .create("Existing Group", "Existing Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let cases = [
+            (
+                "POST",
+                "/api/v1/mode-groups".to_string(),
+                json!({
+                    "mode_group_name": "Existing Group",
+                    "mode_group_description": "Another Description"
+                }),
+                StatusCode::CONFLICT,
+                "conflict",
+            ),
+            (
+                "POST",
+                "/api/v1/mode-groups".to_string(),
+                json!({
+                    "mode_group_name": "  ",
+                    "mode_group_description": "Description"
+                }),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_error",
+            ),
+            (
+                "GET",
+                format!("/api/v1/mode-groups/{}", Uuid::new_v4()),
+                json!(null),
+                StatusCode::NOT_FOUND,
+                "not_found",
+            ),
+        ];
+
+        for (method, uri, body, status, code) in cases {
+            let request = Request::builder()
+                .method(method)
+                .uri(&uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), status, "{} {}", method, uri);
+
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            assert_eq!(body["code"], code);
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_usage_stats_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let app = router().layer(Extension(create_test_service(pool)));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/mode-groups/stats")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let group = &body["data"]["mode_groups"][0];
+        assert_eq!(group["mode_group_name"], "Default MES Mode Group");
+        assert_eq!(group["mode_count"], 4);
+        assert_eq!(group["runtime_seconds"], 0.0);
+        assert_eq!(body["data"]["summary"]["idle_mode_groups"], 1);
+
+        let cases = [
+            (
+                format!("/api/v1/mode-groups/{}/stats", Uuid::new_v4()),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                "/api/v1/mode-groups/stats?from=2026-01-02T00:00:00Z&to=2026-01-01T00:00:00Z"
+                    .to_string(),
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+        ];
+        for (uri, status) in cases {
+            let request = Request::builder()
+                .method("GET")
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), status, "{}", uri);
+        }
+
+        Ok(())
+    }
 }
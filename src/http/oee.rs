@@ -0,0 +1,435 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::services::oee_service::{
+    IdealCycleTime, OeeNode, OeeReport, OeeService, StateClassification,
+};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// oee endpoints, the per state group downtime classification and the per equipment ideal
+// cycle time they rely on
+pub fn router() -> Router {
+    Router::new()
+        .route("/api/v1/equipment/{id}/oee", get(get_equipment_oee))
+        .route(
+            "/api/v1/equipment/{id}/ideal-cycle-time",
+            post(set_ideal_cycle_time),
+        )
+        .route(
+            "/api/v1/equipment/{id}/ideal-cycle-time/delete",
+            post(delete_ideal_cycle_time),
+        )
+        .route(
+            "/api/v1/state-groups/{id}/classifications",
+            get(get_classifications).post(set_classification),
+        )
+        .route(
+            "/api/v1/state-groups/{id}/classifications/delete/{state_code}",
+            post(delete_classification),
+        )
+}
+
+#[derive(Deserialize)]
+pub struct OeeQuery {
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct SetClassificationRequest {
+    pub state_code: i32,
+    /// running, performance_loss, unplanned_downtime or planned_downtime
+    pub category: String,
+}
+
+#[derive(Deserialize)]
+pub struct SetIdealCycleTimeRequest {
+    /// fastest time the equipment makes one part in
+    pub ideal_cycle_seconds: f64,
+}
+
+#[derive(Serialize)]
+pub struct OeeNodeResponse {
+    pub equipment_id: Uuid,
+    pub equipment_name: String,
+    pub running_seconds: f64,
+    pub performance_loss_seconds: f64,
+    pub unplanned_downtime_seconds: f64,
+    pub planned_downtime_seconds: f64,
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+    pub ideal_cycle_seconds: Option<f64>,
+    pub availability: Option<f64>,
+    pub performance: Option<f64>,
+    pub quality: Option<f64>,
+    pub oee: Option<f64>,
+    pub children: Vec<OeeNodeResponse>,
+}
+
+#[derive(Serialize)]
+pub struct OeeResponse {
+    #[serde(serialize_with = "date_format::serialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub to: Option<OffsetDateTime>,
+    #[serde(flatten)]
+    pub equipment: OeeNodeResponse,
+}
+
+#[derive(Serialize)]
+pub struct StateClassificationResponse {
+    pub classification_id: Uuid,
+    pub state_group_id: Uuid,
+    pub state_code: i32,
+    pub category: String,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct IdealCycleTimeResponse {
+    pub equipment_id: Uuid,
+    pub ideal_cycle_seconds: f64,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<OeeNode> for OeeNodeResponse {
+    fn from(node: OeeNode) -> Self {
+        Self {
+            equipment_id: node.equipment_id,
+            equipment_name: node.equipment_name,
+            running_seconds: node.durations.running_seconds,
+            performance_loss_seconds: node.durations.performance_loss_seconds,
+            unplanned_downtime_seconds: node.durations.unplanned_downtime_seconds,
+            planned_downtime_seconds: node.durations.planned_downtime_seconds,
+            good_quantity: node.production.good_quantity,
+            scrap_quantity: node.production.scrap_quantity,
+            rework_quantity: node.production.rework_quantity,
+            ideal_cycle_seconds: node.ideal_cycle_seconds,
+            availability: node.availability,
+            performance: node.performance,
+            quality: node.quality,
+            oee: node.oee,
+            children: node
+                .children
+                .into_iter()
+                .map(OeeNodeResponse::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<OeeReport> for OeeResponse {
+    fn from(report: OeeReport) -> Self {
+        Self {
+            from: Some(report.from),
+            to: Some(report.to),
+            equipment: OeeNodeResponse::from(report.equipment),
+        }
+    }
+}
+
+impl From<StateClassification> for StateClassificationResponse {
+    fn from(classification: StateClassification) -> Self {
+        Self {
+            classification_id: classification.classification_id,
+            state_group_id: classification.state_group_id,
+            state_code: classification.state_code,
+            category: classification.category,
+            created_at: classification.created_at,
+            updated_at: classification.updated_at,
+        }
+    }
+}
+
+impl From<IdealCycleTime> for IdealCycleTimeResponse {
+    fn from(ideal: IdealCycleTime) -> Self {
+        Self {
+            equipment_id: ideal.equipment_id,
+            ideal_cycle_seconds: ideal.ideal_cycle_seconds,
+            created_at: ideal.created_at,
+            updated_at: ideal.updated_at,
+        }
+    }
+}
+
+async fn get_equipment_oee(
+    Extension(service): Extension<OeeService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<OeeQuery>,
+) -> Result<Json<ApiResponse<OeeResponse>>, AppError> {
+    let report = service.calculate(id, query.from, query.to).await?;
+    info!(
+        "Calculated oee for {}: availability {:?}, performance {:?}",
+        report.equipment.equipment_name,
+        report.equipment.availability,
+        report.equipment.performance
+    );
+    Ok(Json(ApiResponse::success(OeeResponse::from(report))))
+}
+
+async fn set_ideal_cycle_time(
+    Extension(service): Extension<OeeService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SetIdealCycleTimeRequest>,
+) -> Result<Json<ApiResponse<IdealCycleTimeResponse>>, AppError> {
+    let ideal = service
+        .set_ideal_cycle_time(id, request.ideal_cycle_seconds)
+        .await?;
+    info!(
+        "Set ideal cycle time of equipment {} to {}s",
+        id, ideal.ideal_cycle_seconds
+    );
+    Ok(Json(ApiResponse::success(IdealCycleTimeResponse::from(
+        ideal,
+    ))))
+}
+
+async fn delete_ideal_cycle_time(
+    Extension(service): Extension<OeeService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    service.delete_ideal_cycle_time(id).await?;
+    info!("Deleted ideal cycle time of equipment {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn get_classifications(
+    Extension(service): Extension<OeeService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<StateClassificationResponse>>>, AppError> {
+    let classifications = service.get_classifications(id).await?;
+    Ok(Json(ApiResponse::success(
+        classifications
+            .into_iter()
+            .map(StateClassificationResponse::from)
+            .collect(),
+    )))
+}
+
+async fn set_classification(
+    Extension(service): Extension<OeeService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<SetClassificationRequest>,
+) -> Result<Json<ApiResponse<StateClassificationResponse>>, AppError> {
+    let classification = service
+        .set_classification(id, request.state_code, &request.category)
+        .await?;
+    info!(
+        "Classified state_code {} of state group {} as {}",
+        classification.state_code, id, classification.category
+    );
+    Ok(Json(ApiResponse::success(
+        StateClassificationResponse::from(classification),
+    )))
+}
+
+async fn delete_classification(
+    Extension(service): Extension<OeeService>,
+    Path((id, state_code)): Path<(Uuid, i32)>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    service.delete_classification(id, state_code).await?;
+    info!(
+        "Deleted classification for state_code {} of state group {}",
+        state_code, id
+    );
+    Ok(Json(ApiResponse::success(())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_oee_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(OeeService::new(pool)));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/equipment/{}/oee?from=2024-01-01T00:00:00Z&to=2024-01-02T00:00:00Z",
+                equipment_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["equipment_name"], "Acme");
+        assert_eq!(body["data"]["from"], "2024-01-01T00:00:00Z");
+        assert!(body["data"]["availability"].is_null());
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/equipment/{}/oee?from=2024-01-02T00:00:00Z&to=2024-01-01T00:00:00Z",
+                equipment_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/equipment/{}/oee", Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let set_ideal = |seconds: f64| {
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/equipment/{}/ideal-cycle-time",
+                    equipment_id
+                ))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"ideal_cycle_seconds": seconds}).to_string(),
+                ))
+                .unwrap()
+        };
+        let body = body_json(app.clone().oneshot(set_ideal(1.5)).await.unwrap()).await;
+        assert_eq!(body["data"]["ideal_cycle_seconds"], 1.5);
+        let response = app.clone().oneshot(set_ideal(0.0)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/equipment/{}/oee", equipment_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["ideal_cycle_seconds"], 1.5);
+
+        let delete = || {
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/equipment/{}/ideal-cycle-time/delete",
+                    equipment_id
+                ))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_classification_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let state_group_id = sqlx::query_scalar!(
+            "SELECT state_group_id FROM core.state_group WHERE state_group_name = 'Default MES State Group'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(OeeService::new(pool)));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/state-groups/{}/classifications",
+                state_group_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"state_code": 3, "category": "planned_downtime"}).to_string(),
+            ))
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["category"], "planned_downtime");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/state-groups/{}/classifications",
+                state_group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"][3]["category"], "planned_downtime");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/state-groups/{}/classifications/delete/3",
+                state_group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/state-groups/{}/classifications/delete/3",
+                state_group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::AppError;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::models::plant_model::{PlantModel, PlantModelDiff};
+use crate::services::plant_model_service::PlantModelService;
+use axum::{
+    Json, Router,
+    extract::{Extension, Query},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use tracing::info;
+
+// plant model import/export endpoints, applying rewrites the whole plant so it needs admin
+pub fn router() -> Router {
+    Router::new()
+        .route("/api/v1/plant-model/export", get(export_plant_model))
+        .route("/api/v1/admin/plant-model/apply", post(apply_plant_model))
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlantModelFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+#[derive(Deserialize)]
+pub struct ExportPlantModelQuery {
+    #[serde(default)]
+    pub format: PlantModelFormat,
+}
+
+#[derive(Deserialize)]
+pub struct ApplyPlantModelQuery {
+    /// report the changes without keeping them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// JSON in the usual response envelope, YAML as a bare document that can be applied as is
+async fn export_plant_model(
+    Extension(service): Extension<PlantModelService>,
+    Query(query): Query<ExportPlantModelQuery>,
+) -> Result<Response, AppError> {
+    let model = service.export().await?;
+    Ok(match query.format {
+        PlantModelFormat::Json => Json(ApiResponse::success(model)).into_response(),
+        PlantModelFormat::Yaml => {
+            // strings, numbers and string keyed maps always serialize
+            let body = serde_yaml::to_string(&model).unwrap_or_default();
+            ([(header::CONTENT_TYPE, "application/yaml")], body).into_response()
+        }
+    })
+}
+
+async fn apply_plant_model(
+    Extension(service): Extension<PlantModelService>,
+    principal: Option<Principal>,
+    Query(query): Query<ApplyPlantModelQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<ApiResponse<PlantModelDiff>>, AppError> {
+    let model = parse_plant_model(&headers, &body)?;
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let diff = service.apply(&model, query.dry_run).await?;
+    info!(
+        "{} plant model: {} created, {} updated, {} deleted",
+        if diff.dry_run {
+            "Dry run of"
+        } else {
+            "Applied"
+        },
+        diff.summary.created,
+        diff.summary.updated,
+        diff.summary.deleted
+    );
+    Ok(Json(ApiResponse::success(diff)))
+}
+
+/// YAML when the content type says so, JSON otherwise
+fn parse_plant_model(headers: &HeaderMap, body: &str) -> Result<PlantModel, AppError> {
+    let is_yaml = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("yaml"));
+    let model = if is_yaml {
+        serde_yaml::from_str(body).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(body).map_err(|e| e.to_string())
+    };
+    model.map_err(|e| AppError::Validation(format!("Invalid plant model: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    #[sqlx::test]
+    async fn test_apply_yaml_and_export(pool: PgPool) -> sqlx::Result<()> {
+        let app = router().layer(Extension(PlantModelService::new(pool)));
+        let document = r#"
+equipment:
+  - name: Acme
+    type: enterprise
+    children:
+      - name: Plant 1
+        type: site
+        metadata:
+          timezone: Europe/Berlin
+        mode_groups: [Default MES Mode Group]
+"#;
+
+        let apply = |dry_run: bool| {
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/admin/plant-model/apply?dry_run={}",
+                    dry_run
+                ))
+                .header("content-type", "application/yaml")
+                .body(Body::from(document))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(apply(true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"]["dry_run"], true);
+        assert_eq!(body["data"]["summary"]["created"], 3);
+        assert_eq!(body["data"]["changes"][1]["key"], "Acme/Plant 1");
+
+        let response = app.clone().oneshot(apply(false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/plant-model/export?format=yaml")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/yaml");
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let exported: PlantModel = serde_yaml::from_slice(&bytes).unwrap();
+        let roots = exported.equipment.unwrap();
+        assert_eq!(roots[0].name, "Acme");
+        assert_eq!(roots[0].children[0].metadata["timezone"], "Europe/Berlin");
+
+        // applying the export again has nothing to do
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/admin/plant-model/apply")
+            .header("content-type", "application/yaml")
+            .body(Body::from(bytes))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"]["changes"].as_array().unwrap().len(), 0);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/admin/plant-model/apply")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"equipment": [{"name": "Acme"}]}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        Ok(())
+    }
+}
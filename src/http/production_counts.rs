@@ -0,0 +1,316 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::operations::{CountMode, CountValues};
+use crate::services::production_count_service::{
+    CountReport, ProductionBucket, ProductionCount, ProductionCountService, ProductionReport,
+    ProductionTotals,
+};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// good/scrap/rework counts per equipment, fed by plc/data collection or operators
+pub fn router() -> Router {
+    Router::new().route(
+        "/api/v1/equipment/{id}/production",
+        get(get_production).post(ingest_production),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct IngestProductionRequest {
+    /// delta (default) for quantities produced since the last report, absolute for raw
+    /// plc counter values
+    #[serde(default)]
+    pub mode: CountMode,
+    pub good: Option<i64>,
+    pub scrap: Option<i64>,
+    pub rework: Option<i64>,
+    /// defaults to the job running on the equipment
+    pub job_id: Option<Uuid>,
+    /// highest plc counter value, used to detect rollover in absolute mode
+    pub counter_max: Option<i64>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub timestamp: Option<OffsetDateTime>,
+    pub recorded_by: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ProductionQuery {
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub to: Option<OffsetDateTime>,
+    /// minute, hour (default), day, week or month
+    pub bucket: Option<String>,
+    pub job_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct ProductionCountResponse {
+    pub count_id: Uuid,
+    pub equipment_id: Uuid,
+    pub job_id: Option<Uuid>,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub recorded_at: Option<OffsetDateTime>,
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+    pub good_counter: Option<i64>,
+    pub scrap_counter: Option<i64>,
+    pub rework_counter: Option<i64>,
+    pub rollover: bool,
+    pub recorded_by: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProductionTotalsResponse {
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+    pub total_quantity: i64,
+    pub quality: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct ProductionBucketResponse {
+    #[serde(serialize_with = "date_format::serialize")]
+    pub bucket_start: Option<OffsetDateTime>,
+    #[serde(flatten)]
+    pub totals: ProductionTotalsResponse,
+}
+
+#[derive(Serialize)]
+pub struct ProductionReportResponse {
+    pub equipment_id: Uuid,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(serialize_with = "date_format::serialize")]
+    pub to: Option<OffsetDateTime>,
+    pub bucket: String,
+    pub totals: ProductionTotalsResponse,
+    pub buckets: Vec<ProductionBucketResponse>,
+}
+
+impl From<ProductionCount> for ProductionCountResponse {
+    fn from(count: ProductionCount) -> Self {
+        Self {
+            count_id: count.count_id,
+            equipment_id: count.equipment_id,
+            job_id: count.job_id,
+            recorded_at: Some(count.recorded_at),
+            good_quantity: count.good_quantity,
+            scrap_quantity: count.scrap_quantity,
+            rework_quantity: count.rework_quantity,
+            good_counter: count.good_counter,
+            scrap_counter: count.scrap_counter,
+            rework_counter: count.rework_counter,
+            rollover: count.rollover,
+            recorded_by: count.recorded_by,
+        }
+    }
+}
+
+impl From<ProductionTotals> for ProductionTotalsResponse {
+    fn from(totals: ProductionTotals) -> Self {
+        Self {
+            good_quantity: totals.good_quantity,
+            scrap_quantity: totals.scrap_quantity,
+            rework_quantity: totals.rework_quantity,
+            total_quantity: totals.total_quantity(),
+            quality: totals.quality(),
+        }
+    }
+}
+
+impl From<ProductionBucket> for ProductionBucketResponse {
+    fn from(bucket: ProductionBucket) -> Self {
+        Self {
+            bucket_start: Some(bucket.bucket_start),
+            totals: ProductionTotalsResponse::from(bucket.totals),
+        }
+    }
+}
+
+impl From<ProductionReport> for ProductionReportResponse {
+    fn from(report: ProductionReport) -> Self {
+        Self {
+            equipment_id: report.equipment_id,
+            from: Some(report.from),
+            to: Some(report.to),
+            bucket: report.bucket,
+            totals: ProductionTotalsResponse::from(report.totals),
+            buckets: report
+                .buckets
+                .into_iter()
+                .map(ProductionBucketResponse::from)
+                .collect(),
+        }
+    }
+}
+
+async fn ingest_production(
+    Extension(service): Extension<ProductionCountService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<IngestProductionRequest>,
+) -> Result<Json<ApiResponse<ProductionCountResponse>>, AppError> {
+    let report = CountReport {
+        mode: request.mode,
+        values: CountValues {
+            good: request.good,
+            scrap: request.scrap,
+            rework: request.rework,
+        },
+        job_id: request.job_id,
+        counter_max: request.counter_max,
+        timestamp: request.timestamp,
+        recorded_by: request.recorded_by.as_deref(),
+    };
+
+    let count = service.ingest(id, report).await?;
+    info!(
+        "Equipment {} produced {} good, {} scrap, {} rework",
+        id, count.good_quantity, count.scrap_quantity, count.rework_quantity
+    );
+    Ok(Json(ApiResponse::success(ProductionCountResponse::from(
+        count,
+    ))))
+}
+
+async fn get_production(
+    Extension(service): Extension<ProductionCountService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ProductionQuery>,
+) -> Result<Json<ApiResponse<ProductionReportResponse>>, AppError> {
+    let report = service
+        .aggregate(
+            id,
+            query.from,
+            query.to,
+            query.bucket.as_deref(),
+            query.job_id,
+        )
+        .await?;
+    info!(
+        "Aggregated {} production buckets for equipment {}",
+        report.buckets.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(ProductionReportResponse::from(
+        report,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn ingest_request(equipment_id: Uuid, body: Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/equipment/{}/production", equipment_id))
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_production_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT 'Acme', type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(ProductionCountService::new(pool)));
+
+        let request = ingest_request(
+            equipment_id,
+            json!({
+                "mode": "absolute",
+                "good": 65530,
+                "counter_max": 65535,
+                "timestamp": "2024-01-01T08:00:00Z"
+            }),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["good_quantity"], 0);
+        assert_eq!(body["data"]["good_counter"], 65530);
+
+        let request = ingest_request(
+            equipment_id,
+            json!({
+                "mode": "absolute",
+                "good": 4,
+                "counter_max": 65535,
+                "timestamp": "2024-01-01T08:30:00Z"
+            }),
+        );
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["good_quantity"], 10);
+        assert_eq!(body["data"]["rollover"], true);
+
+        let request = ingest_request(
+            equipment_id,
+            json!({"scrap": 2, "timestamp": "2024-01-01T09:15:00Z"}),
+        );
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["scrap_quantity"], 2);
+
+        let request = ingest_request(equipment_id, json!({"good": -1}));
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"], "good cannot be negative");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/equipment/{}/production?from=2024-01-01T00:00:00Z&to=2024-01-02T00:00:00Z",
+                equipment_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["bucket"], "hour");
+        assert_eq!(body["data"]["totals"]["good_quantity"], 10);
+        assert_eq!(body["data"]["totals"]["scrap_quantity"], 2);
+        assert_eq!(body["data"]["buckets"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            body["data"]["buckets"][0]["bucket_start"],
+            "2024-01-01T08:00:00Z"
+        );
+
+        let request = ingest_request(Uuid::new_v4(), json!({"good": 1}));
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::operations::Product;
+use crate::services::product_service::ProductService;
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// product endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/products",
+            get(get_all_products).post(create_product),
+        )
+        .route("/api/v1/products/count", get(get_product_count))
+        .route("/api/v1/products/{id}", get(get_product_by_id))
+        .route("/api/v1/products/update/{id}", post(update_product))
+        .route(
+            "/api/v1/products/update-metadata/{id}",
+            post(update_product_metadata),
+        )
+        .route("/api/v1/products/delete/{id}", post(delete_product))
+        .route("/api/v1/products/exists/{id}", get(check_product_exists))
+}
+
+// request/response dtos
+#[derive(Serialize)]
+pub struct ProductResponse {
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub product_description: Option<String>,
+    pub product_metadata: Value,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateProductRequest {
+    pub product_name: String,
+    pub product_description: Option<String>,
+    pub product_metadata: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProductRequest {
+    pub product_name: String,
+    pub product_description: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProductMetadataRequest {
+    pub product_metadata: Value,
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: i64,
+}
+
+#[derive(Serialize)]
+pub struct ExistsResponse {
+    pub exists: bool,
+}
+
+#[derive(Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub total_count: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PaginationQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+fn default_page() -> i64 {
+    1
+}
+fn default_per_page() -> i64 {
+    50
+}
+
+// service model -> response model
+impl From<Product> for ProductResponse {
+    fn from(product: Product) -> Self {
+        Self {
+            product_id: product.product_id,
+            product_name: product.product_name,
+            product_description: product.product_description,
+            product_metadata: product.product_metadata,
+            created_at: product.created_at,
+            updated_at: product.updated_at,
+        }
+    }
+}
+
+// handler functions for http endpoints
+async fn get_all_products(
+    Extension(service): Extension<ProductService>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<ProductResponse>>>, AppError> {
+    // Convert 1-based page to 0-based offset
+    let offset = (pagination.page - 1) * pagination.per_page;
+
+    let (products, total_count) = service.get_paginated(offset, pagination.per_page).await?;
+    let response: Vec<ProductResponse> = products.into_iter().map(ProductResponse::from).collect();
+
+    let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
+
+    let paginated_response = PaginatedResponse {
+        data: response,
+        total_count,
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total_pages,
+    };
+
+    info!(
+        "Retrieved {} products (page {}/{}, total: {})",
+        paginated_response.data.len(),
+        pagination.page,
+        total_pages,
+        total_count
+    );
+
+    Ok(Json(ApiResponse::success(paginated_response)))
+}
+
+async fn get_product_by_id(
+    Extension(service): Extension<ProductService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ProductResponse>>, AppError> {
+    let product = service.get_by_id(id).await?;
+    info!("Retrieved product: {}", product.product_name);
+    Ok(Json(ApiResponse::success(ProductResponse::from(product))))
+}
+
+async fn create_product(
+    Extension(service): Extension<ProductService>,
+    Json(request): Json<CreateProductRequest>,
+) -> Result<Json<ApiResponse<ProductResponse>>, AppError> {
+    let product = service
+        .create(
+            &request.product_name,
+            request.product_description.as_deref(),
+            request.product_metadata.as_ref(),
+        )
+        .await?;
+    info!("Created product: {}", product.product_name);
+    Ok(Json(ApiResponse::success(ProductResponse::from(product))))
+}
+
+async fn update_product(
+    Extension(service): Extension<ProductService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateProductRequest>,
+) -> Result<Json<ApiResponse<ProductResponse>>, AppError> {
+    let product = service
+        .update(
+            id,
+            &request.product_name,
+            request.product_description.as_deref(),
+        )
+        .await?;
+    info!("Updated product {}: {}", id, product.product_name);
+    Ok(Json(ApiResponse::success(ProductResponse::from(product))))
+}
+
+async fn update_product_metadata(
+    Extension(service): Extension<ProductService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateProductMetadataRequest>,
+) -> Result<Json<ApiResponse<ProductResponse>>, AppError> {
+    let product = service
+        .update_metadata(id, &request.product_metadata)
+        .await?;
+    info!("Updated product metadata {}", id);
+    Ok(Json(ApiResponse::success(ProductResponse::from(product))))
+}
+
+async fn delete_product(
+    Extension(service): Extension<ProductService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    service.delete(id).await?;
+    info!("Deleted product: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn get_product_count(
+    Extension(service): Extension<ProductService>,
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    info!("Total product count: {}", count);
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn check_product_exists(
+    Extension(service): Extension<ProductService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_product_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let app = router().layer(Extension(ProductService::new(pool)));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/products")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"product_name": "Widget", "product_metadata": {"sku": "W-1"}}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["product_metadata"]["sku"], "W-1");
+        let product_id = body["data"]["product_id"].as_str().unwrap().to_string();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/products")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"product_name": ""}).to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"], "product_name cannot be empty");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/products")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"product_name": "Widget"}).to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/products/update/{}", product_id))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"product_name": "Gadget", "product_description": "Renamed"}).to_string(),
+            ))
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["product_name"], "Gadget");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/products?page=1&per_page=10")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["total_count"], 1);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/products/delete/{}", product_id))
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/products/{}", product_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
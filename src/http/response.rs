@@ -1,10 +1,12 @@
+use crate::error::AppError;
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use tracing::error;
 
 #[derive(Serialize)]
 pub struct SuccessResponse<T> {
@@ -18,6 +20,9 @@ pub struct ErrorResponse {
     pub success: bool,
     pub timestamp: DateTime<Utc>,
     pub error: String,
+    /// machine readable error code, see `AppError::code`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
 }
 
 impl<T> SuccessResponse<T> {
@@ -36,12 +41,20 @@ impl ErrorResponse {
             success: false,
             timestamp: Utc::now(),
             error,
+            code: None,
         }
     }
 
     pub fn from_str(error: &str) -> Self {
         Self::new(error.to_string())
     }
+
+    pub fn with_code(code: &'static str, error: String) -> Self {
+        Self {
+            code: Some(code),
+            ..Self::new(error)
+        }
+    }
 }
 
 // unified response enum
@@ -57,10 +70,6 @@ impl<T> ApiResponse<T> {
         Self::Success(SuccessResponse::new(data))
     }
 
-    pub fn error(error: String) -> Self {
-        Self::Error(ErrorResponse::new(error))
-    }
-
     pub fn error_str(error: &str) -> Self {
         Self::Error(ErrorResponse::from_str(error))
     }
@@ -75,8 +84,30 @@ pub async fn not_implemented() -> Json<ApiResponse<ErrorResponse>> {
 }
 
 pub async fn handler_404() -> Response {
-    let body = ApiResponse::<ErrorResponse>::error_str("route not found");
-    (StatusCode::NOT_FOUND, Json(body)).into_response()
+    AppError::NotFound("route not found".to_string()).into_response()
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Database { message, source } => {
+                error!("{}: {}", message, source);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        let body =
+            ApiResponse::<()>::Error(ErrorResponse::with_code(self.code(), self.to_string()));
+        if status == StatusCode::UNAUTHORIZED {
+            return (status, [(header::WWW_AUTHENTICATE, "Bearer")], Json(body)).into_response();
+        }
+        (status, Json(body)).into_response()
+    }
 }
 
 // TODO: claude tests, need to review
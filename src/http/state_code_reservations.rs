@@ -0,0 +1,251 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::services::state_code_reservation_service::{
+    StateCodeReservation, StateCodeReservationService,
+};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// state code reservation endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/state-groups/{id}/reservations",
+            get(get_reservations).post(create_reservation),
+        )
+        .route(
+            "/api/v1/state-groups/reservations/{id}",
+            get(get_reservation_by_id),
+        )
+        .route(
+            "/api/v1/state-groups/reservations/delete/{id}",
+            post(delete_reservation),
+        )
+}
+
+#[derive(Deserialize)]
+pub struct CreateStateCodeReservationRequest {
+    pub min_code: i32,
+    pub max_code: i32,
+    /// who may create states in the range, e.g. the PLC vendor or integrator
+    pub owner: String,
+    pub label: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct StateCodeReservationResponse {
+    pub reservation_id: Uuid,
+    pub state_group_id: Uuid,
+    pub min_code: i32,
+    pub max_code: i32,
+    pub owner: String,
+    pub label: Option<String>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<StateCodeReservation> for StateCodeReservationResponse {
+    fn from(reservation: StateCodeReservation) -> Self {
+        Self {
+            reservation_id: reservation.reservation_id,
+            state_group_id: reservation.state_group_id,
+            min_code: reservation.min_code,
+            max_code: reservation.max_code,
+            owner: reservation.owner,
+            label: reservation.label,
+            created_at: reservation.created_at,
+            updated_at: reservation.updated_at,
+        }
+    }
+}
+
+async fn get_reservations(
+    Extension(service): Extension<StateCodeReservationService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<StateCodeReservationResponse>>>, AppError> {
+    let reservations = service.get_by_state_group(id).await?;
+    info!(
+        "Retrieved {} state code reservations for state group {}",
+        reservations.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(
+        reservations
+            .into_iter()
+            .map(StateCodeReservationResponse::from)
+            .collect(),
+    )))
+}
+
+async fn get_reservation_by_id(
+    Extension(service): Extension<StateCodeReservationService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<StateCodeReservationResponse>>, AppError> {
+    let reservation = service.get_by_id(id).await?;
+    Ok(Json(ApiResponse::success(
+        StateCodeReservationResponse::from(reservation),
+    )))
+}
+
+async fn create_reservation(
+    Extension(service): Extension<StateCodeReservationService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateStateCodeReservationRequest>,
+) -> Result<Json<ApiResponse<StateCodeReservationResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let reservation = service
+        .create(
+            id,
+            request.min_code,
+            request.max_code,
+            &request.owner,
+            request.label.as_deref(),
+        )
+        .await?;
+    info!(
+        "Reserved state codes {}-{} in state group {} for '{}'",
+        reservation.min_code, reservation.max_code, id, reservation.owner
+    );
+    Ok(Json(ApiResponse::success(
+        StateCodeReservationResponse::from(reservation),
+    )))
+}
+
+async fn delete_reservation(
+    Extension(service): Extension<StateCodeReservationService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted state code reservation: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    #[sqlx::test]
+    async fn test_create_and_list_reservations_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let group_id = sqlx::query_scalar!(
+            "SELECT state_group_id FROM core.state_group WHERE state_group_name = 'Default MES State Group'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(StateCodeReservationService::new(pool)));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/state-groups/{}/reservations", group_id))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"min_code": 100, "max_code": 199, "owner": "Rockwell", "label": "AB PLCs"})
+                    .to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"]["owner"], "Rockwell");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/state-groups/{}/reservations", group_id))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"min_code": 190, "max_code": 250, "owner": "Siemens"}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["success"], false);
+        assert!(
+            body["error"]
+                .as_str()
+                .unwrap()
+                .contains("already reserved for 'Rockwell'")
+        );
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/state-groups/{}/reservations", group_id))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"min_code": 300, "max_code": 200, "owner": "Siemens"}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/state-groups/{}/reservations",
+                Uuid::new_v4()
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/state-groups/reservations/delete/{}",
+                Uuid::new_v4()
+            ))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/state-groups/{}/reservations", group_id))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+        assert_eq!(body["data"][0]["min_code"], 100);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1034 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::app::Principal;
+use crate::models::core::{
+    AvailableStateCodes, BulkGroupAssignment, EquipmentStateGroupMapping, GroupEquipment,
+    StateGroupUsageStats,
+};
+use crate::services::state_group_service::{StateGroup, StateGroupService};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// state group endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/state-groups",
+            get(get_all_state_groups).post(create_state_group),
+        )
+        .route("/api/v1/state-groups/bulk", post(bulk_create_state_groups))
+        .route("/api/v1/state-groups/count", get(get_state_groups_count))
+        .route(
+            "/api/v1/state-groups/stats",
+            get(get_state_groups_usage_stats),
+        )
+        .route("/api/v1/state-groups/search", get(search_state_groups))
+        .route("/api/v1/state-groups/{id}", get(get_state_group_by_id))
+        .route(
+            "/api/v1/state-groups/update-name/{id}",
+            post(update_state_group_name),
+        )
+        .route(
+            "/api/v1/state-groups/update-description/{id}",
+            post(update_state_group_description),
+        )
+        .route("/api/v1/state-groups/delete/{id}", post(delete_state_group))
+        .route(
+            "/api/v1/state-groups/restore/{id}",
+            post(restore_state_group),
+        )
+        .route(
+            "/api/v1/state-groups/{id}/equipment",
+            get(get_state_group_equipment),
+        )
+        .route(
+            "/api/v1/state-groups/{id}/stats",
+            get(get_state_group_usage_stats),
+        )
+        .route(
+            "/api/v1/state-groups/{id}/available-codes",
+            get(get_available_state_codes),
+        )
+        .route("/api/v1/state-groups/assign/{id}", post(assign_equipment))
+        .route(
+            "/api/v1/state-groups/unassign/{id}",
+            post(unassign_equipment),
+        )
+        .route(
+            "/api/v1/state-groups/bulk-assign/{id}",
+            post(bulk_assign_equipment),
+        )
+        .route(
+            "/api/v1/equipment/{id}/state-groups",
+            get(get_equipment_state_groups),
+        )
+        .route(
+            "/api/v1/state-groups/exists/{id}",
+            get(check_state_group_exists),
+        )
+        .route(
+            "/api/v1/state-groups/name-exists",
+            get(check_state_group_name_exists),
+        )
+        .route("/api/v1/state-groups/by-name", get(get_state_group_by_name))
+        .route(
+            "/api/v1/state-groups/by-description",
+            get(get_state_group_by_description),
+        )
+}
+
+// request/response dtos
+#[derive(Serialize, Deserialize)]
+pub struct StateGroupResponse {
+    pub state_group_id: Uuid,
+    pub state_group_name: String,
+    pub state_group_description: String,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateStateGroupRequest {
+    pub state_group_name: String,
+    pub state_group_description: String,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateStateGroupNameRequest {
+    pub state_group_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateStateGroupDescriptionRequest {
+    pub state_group_description: String,
+}
+
+#[derive(Deserialize)]
+pub struct BulkCreateStateGroupRequest {
+    pub state_groups: Vec<BulkStateGroupItem>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkStateGroupItem {
+    pub state_group_name: String,
+    pub state_group_description: String,
+}
+
+#[derive(Serialize)]
+pub struct BulkCreateStateGroupResponse {
+    pub created: Vec<StateGroupResponse>,
+    pub created_count: usize,
+    pub total_requested: usize,
+}
+
+#[derive(Deserialize)]
+pub struct AssignEquipmentRequest {
+    pub equipment_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct BulkAssignEquipmentRequest {
+    pub equipment_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: i64,
+}
+
+#[derive(Serialize)]
+pub struct ExistsResponse {
+    pub exists: bool,
+}
+
+#[derive(Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub total_count: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PaginationQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+#[derive(Deserialize)]
+pub struct NameQuery {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct DescriptionQuery {
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+}
+
+/// Range the runtime in the usage stats covers, the last 30 days when left out
+#[derive(Deserialize)]
+pub struct UsageStatsQuery {
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub from: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "date_format::deserialize")]
+    pub to: Option<OffsetDateTime>,
+}
+
+/// Range to look for free codes in (0 to 100 when left out), and whose reservations to
+/// treat as free
+#[derive(Deserialize)]
+pub struct AvailableCodesQuery {
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+    pub owner: Option<String>,
+}
+
+fn default_page() -> i64 {
+    1
+}
+fn default_per_page() -> i64 {
+    50
+}
+
+// service model -> response model
+impl From<StateGroup> for StateGroupResponse {
+    fn from(state_group: StateGroup) -> Self {
+        Self {
+            state_group_id: state_group.state_group_id,
+            state_group_name: state_group.state_group_name,
+            state_group_description: state_group.state_group_description,
+            created_at: state_group.created_at,
+            updated_at: state_group.updated_at,
+        }
+    }
+}
+
+// handler functions for http endpoints
+async fn get_all_state_groups(
+    Extension(service): Extension<StateGroupService>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<StateGroupResponse>>>, AppError> {
+    // Convert 1-based page to 0-based offset
+    let offset = (pagination.page - 1) * pagination.per_page;
+
+    let (state_groups, total_count) = service.get_paginated(offset, pagination.per_page).await?;
+    let response: Vec<StateGroupResponse> = state_groups
+        .into_iter()
+        .map(StateGroupResponse::from)
+        .collect();
+
+    let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
+
+    let paginated_response = PaginatedResponse {
+        data: response,
+        total_count,
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total_pages,
+    };
+
+    info!(
+        "Retrieved {} state groups (page {}/{}, total: {})",
+        paginated_response.data.len(),
+        pagination.page,
+        total_pages,
+        total_count
+    );
+
+    Ok(Json(ApiResponse::success(paginated_response)))
+}
+
+async fn get_state_group_by_id(
+    Extension(service): Extension<StateGroupService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<StateGroupResponse>>, AppError> {
+    let state_group = service.get_by_id(id).await?;
+    info!("Retrieved state group: {}", state_group.state_group_name);
+    Ok(Json(ApiResponse::success(StateGroupResponse::from(
+        state_group,
+    ))))
+}
+
+async fn get_state_group_by_name(
+    Extension(service): Extension<StateGroupService>,
+    Query(query): Query<NameQuery>,
+) -> Result<Json<ApiResponse<StateGroupResponse>>, AppError> {
+    if query.name.trim().is_empty() {
+        return Err(AppError::Validation(
+            "State group name cannot be empty".to_string(),
+        ));
+    }
+
+    let state_group = service
+        .get_by_name(&query.name)
+        .await?
+        .ok_or_else(|| AppError::NotFound("State group not found".to_string()))?;
+    info!(
+        "Retrieved state group by name: {}",
+        state_group.state_group_name
+    );
+    Ok(Json(ApiResponse::success(StateGroupResponse::from(
+        state_group,
+    ))))
+}
+
+async fn get_state_group_by_description(
+    Extension(service): Extension<StateGroupService>,
+    Query(query): Query<DescriptionQuery>,
+) -> Result<Json<ApiResponse<StateGroupResponse>>, AppError> {
+    if query.description.trim().is_empty() {
+        return Err(AppError::Validation(
+            "State group description cannot be empty".to_string(),
+        ));
+    }
+
+    let state_group = service
+        .get_by_description(&query.description)
+        .await?
+        .ok_or_else(|| AppError::NotFound("State group not found".to_string()))?;
+    info!(
+        "Retrieved state group by description: {}",
+        state_group.state_group_name
+    );
+    Ok(Json(ApiResponse::success(StateGroupResponse::from(
+        state_group,
+    ))))
+}
+
+async fn search_state_groups(
+    Extension(service): Extension<StateGroupService>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<ApiResponse<Vec<StateGroupResponse>>>, AppError> {
+    let state_groups = service.search(&query.q).await?;
+    info!(
+        "Found {} state groups matching '{}'",
+        state_groups.len(),
+        query.q
+    );
+    Ok(Json(ApiResponse::success(
+        state_groups
+            .into_iter()
+            .map(StateGroupResponse::from)
+            .collect(),
+    )))
+}
+
+async fn create_state_group(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Json(request): Json<CreateStateGroupRequest>,
+) -> Result<Json<ApiResponse<StateGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state_group = service
+        .create(&request.state_group_name, &request.state_group_description)
+        .await?;
+    info!("Created state group: {}", state_group.state_group_name);
+    Ok(Json(ApiResponse::success(StateGroupResponse::from(
+        state_group,
+    ))))
+}
+
+async fn update_state_group_name(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateStateGroupNameRequest>,
+) -> Result<Json<ApiResponse<StateGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state_group = service.update_name(id, &request.state_group_name).await?;
+    info!(
+        "Updated state group name {}: {}",
+        id, state_group.state_group_name
+    );
+    Ok(Json(ApiResponse::success(StateGroupResponse::from(
+        state_group,
+    ))))
+}
+
+async fn update_state_group_description(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateStateGroupDescriptionRequest>,
+) -> Result<Json<ApiResponse<StateGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state_group = service
+        .update_description(id, &request.state_group_description)
+        .await?;
+    info!("Updated state group description {}", id);
+    Ok(Json(ApiResponse::success(StateGroupResponse::from(
+        state_group,
+    ))))
+}
+
+async fn delete_state_group(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted state group: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn restore_state_group(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<StateGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state_group = service.restore(id).await?;
+    info!("Restored state group: {}", state_group.state_group_name);
+    Ok(Json(ApiResponse::success(StateGroupResponse::from(
+        state_group,
+    ))))
+}
+
+async fn get_state_group_equipment(
+    Extension(service): Extension<StateGroupService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<GroupEquipment>>>, AppError> {
+    let equipment = service.get_equipment(id).await?;
+    info!(
+        "Retrieved {} equipment for state group {}",
+        equipment.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(equipment)))
+}
+
+async fn assign_equipment(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AssignEquipmentRequest>,
+) -> Result<Json<ApiResponse<EquipmentStateGroupMapping>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mapping = service.assign_equipment(id, request.equipment_id).await?;
+    info!(
+        "Assigned equipment {} to state group {}",
+        request.equipment_id, id
+    );
+    Ok(Json(ApiResponse::success(mapping)))
+}
+
+async fn unassign_equipment(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AssignEquipmentRequest>,
+) -> Result<Json<ApiResponse<EquipmentStateGroupMapping>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let mapping = service.unassign_equipment(id, request.equipment_id).await?;
+    info!(
+        "Unassigned equipment {} from state group {}",
+        request.equipment_id, id
+    );
+    Ok(Json(ApiResponse::success(mapping)))
+}
+
+async fn bulk_assign_equipment(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<BulkAssignEquipmentRequest>,
+) -> Result<Json<ApiResponse<BulkGroupAssignment>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    if request.equipment_ids.is_empty() {
+        return Err(AppError::Validation(
+            "No equipment IDs provided".to_string(),
+        ));
+    }
+
+    if request.equipment_ids.len() > 1000 {
+        return Err(AppError::Validation(
+            "Cannot assign more than 1000 equipment at once".to_string(),
+        ));
+    }
+
+    let assignment = service
+        .bulk_assign_equipment(id, &request.equipment_ids)
+        .await?;
+    info!(
+        "Bulk assigned {}/{} equipment to state group {}",
+        assignment.assigned_equipment_ids.len(),
+        request.equipment_ids.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(assignment)))
+}
+
+async fn get_equipment_state_groups(
+    Extension(service): Extension<StateGroupService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<StateGroupResponse>>>, AppError> {
+    let state_groups = service.get_by_equipment_id(id).await?;
+    info!(
+        "Retrieved {} state groups for equipment {}",
+        state_groups.len(),
+        id
+    );
+    Ok(Json(ApiResponse::success(
+        state_groups
+            .into_iter()
+            .map(StateGroupResponse::from)
+            .collect(),
+    )))
+}
+
+async fn bulk_create_state_groups(
+    Extension(service): Extension<StateGroupService>,
+    principal: Option<Principal>,
+    Json(request): Json<BulkCreateStateGroupRequest>,
+) -> Result<Json<ApiResponse<BulkCreateStateGroupResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    if request.state_groups.is_empty() {
+        return Err(AppError::Validation("No state groups provided".to_string()));
+    }
+
+    if request.state_groups.len() > 100 {
+        return Err(AppError::Validation(
+            "Cannot create more than 100 state groups at once".to_string(),
+        ));
+    }
+
+    let state_group_tuples: Vec<(&str, &str)> = request
+        .state_groups
+        .iter()
+        .map(|item| {
+            (
+                item.state_group_name.as_str(),
+                item.state_group_description.as_str(),
+            )
+        })
+        .collect();
+
+    let state_groups = service.bulk_create(state_group_tuples).await?;
+    let response: Vec<StateGroupResponse> = state_groups
+        .into_iter()
+        .map(StateGroupResponse::from)
+        .collect();
+
+    let bulk_response = BulkCreateStateGroupResponse {
+        created_count: response.len(),
+        total_requested: request.state_groups.len(),
+        created: response,
+    };
+
+    info!(
+        "Bulk created {}/{} state groups",
+        bulk_response.created_count, bulk_response.total_requested
+    );
+
+    Ok(Json(ApiResponse::success(bulk_response)))
+}
+
+async fn get_state_groups_count(
+    Extension(service): Extension<StateGroupService>,
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    info!("Total state groups count: {}", count);
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn get_state_groups_usage_stats(
+    Extension(service): Extension<StateGroupService>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<StateGroupUsageStats>>, AppError> {
+    let stats = service.usage_stats(None, query.from, query.to).await?;
+    info!(
+        "State group usage stats: {} groups, {} idle",
+        stats.summary.total_state_groups, stats.summary.idle_state_groups
+    );
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+async fn get_state_group_usage_stats(
+    Extension(service): Extension<StateGroupService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<StateGroupUsageStats>>, AppError> {
+    let stats = service.usage_stats(Some(id), query.from, query.to).await?;
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+async fn get_available_state_codes(
+    Extension(service): Extension<StateGroupService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AvailableCodesQuery>,
+) -> Result<Json<ApiResponse<AvailableStateCodes>>, AppError> {
+    let codes = service
+        .available_codes(id, query.min, query.max, query.owner.as_deref())
+        .await?;
+    Ok(Json(ApiResponse::success(codes)))
+}
+
+async fn check_state_group_exists(
+    Extension(service): Extension<StateGroupService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+async fn check_state_group_name_exists(
+    Extension(service): Extension<StateGroupService>,
+    Query(query): Query<NameQuery>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.name_exists(&query.name).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    // Helper function to create test service
+    fn create_test_service(pool: PgPool) -> StateGroupService {
+        StateGroupService::new(pool)
+    }
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_create_state_group_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let app = router().layer(Extension(create_test_service(pool)));
+
+        let request_body = json!({
+            "state_group_name": "Test HTTP Group",
+            "state_group_description": "Test HTTP Description"
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/state-groups")
+            .header("content-type", "application/json")
+            .body(Body::from(request_body.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["state_group_name"], "Test HTTP Group");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_get_state_group_by_id_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let service = create_test_service(pool);
+
+        let created = service
+            .create("Test Get Group", "Test Get Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/state-groups/{}", created.state_group_id))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["state_group_name"], "Test Get Group");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_state_group_exists_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let service = create_test_service(pool);
+
+        let created = service
+            .create("Test Exists Group", "Test Exists Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let cases = [
+            (
+                format!("/api/v1/state-groups/exists/{}", created.state_group_id),
+                true,
+            ),
+            (
+                format!("/api/v1/state-groups/exists/{}", Uuid::new_v4()),
+                false,
+            ),
+            (
+                "/api/v1/state-groups/name-exists?name=Test%20Exists%20Group".to_string(),
+                true,
+            ),
+            (
+                "/api/v1/state-groups/name-exists?name=Missing%20Group".to_string(),
+                false,
+            ),
+        ];
+        for (uri, expected) in cases {
+            let request = Request::builder()
+                .method("GET")
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = body_json(response).await;
+            assert_eq!(body["data"]["exists"], expected, "{}", uri);
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_update_state_group_name_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let service = create_test_service(pool);
+
+        let created = service
+            .create("Original Name", "Original Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/state-groups/update-name/{}",
+                created.state_group_id
+            ))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"state_group_name": "Updated Name"}).to_string(),
+            ))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["state_group_name"], "Updated Name");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_bulk_create_state_groups_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let app = router().layer(Extension(create_test_service(pool)));
+
+        let request_body = json!({
+            "state_groups": [
+                {
+                    "state_group_name": "Bulk Group 1",
+                    "state_group_description": "Bulk Description 1"
+                },
+                {
+                    "state_group_name": "Bulk Group 2",
+                    "state_group_description": "Bulk Description 2"
+                }
+            ]
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/state-groups/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(request_body.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["created_count"], 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_search_state_groups_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let app = router().layer(Extension(create_test_service(pool)));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/state-groups/search?q=default")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delete_state_group_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let service = create_test_service(pool);
+
+        let created = service
+            .create("To Delete", "Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!(
+                "/api/v1/state-groups/delete/{}",
+                created.state_group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["success"], true);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_equipment_assignment_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let service = create_test_service(pool.clone());
+        let equipment = crate::services::equipment_service::EquipmentService::new(pool.clone());
+
+        let enterprise_type = sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = 'enterprise'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let acme = equipment
+            .create("Acme", enterprise_type, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let globex = equipment
+            .create("Globex", enterprise_type, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let group = service
+            .create("Filler", "Filler states")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let app = router().layer(Extension(service));
+
+        let post = |uri: String, body: Value| {
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let get = |uri: String| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let request = post(
+            format!("/api/v1/state-groups/assign/{}", group.state_group_id),
+            json!({"equipment_id": acme.equipment_id}),
+        );
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(
+            body["data"]["state_group_id"],
+            group.state_group_id.to_string()
+        );
+
+        let request = post(
+            format!("/api/v1/state-groups/assign/{}", group.state_group_id),
+            json!({"equipment_id": acme.equipment_id}),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(
+            body["error"],
+            "Equipment is already assigned to this state group"
+        );
+
+        let request = post(
+            format!("/api/v1/state-groups/bulk-assign/{}", group.state_group_id),
+            json!({"equipment_ids": [acme.equipment_id, globex.equipment_id]}),
+        );
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(
+            body["data"]["assigned_equipment_ids"],
+            json!([globex.equipment_id])
+        );
+        assert_eq!(
+            body["data"]["skipped_equipment_ids"],
+            json!([acme.equipment_id])
+        );
+
+        let request = get(format!(
+            "/api/v1/state-groups/{}/equipment",
+            group.state_group_id
+        ));
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+        assert_eq!(body["data"][0]["equipment_name"], "Acme");
+
+        let request = post(
+            format!("/api/v1/state-groups/unassign/{}", group.state_group_id),
+            json!({"equipment_id": acme.equipment_id}),
+        );
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+
+        let request = get(format!(
+            "/api/v1/equipment/{}/state-groups",
+            acme.equipment_id
+        ));
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"], json!([]));
+
+        let request = get(format!(
+            "/api/v1/equipment/{}/state-groups",
+            globex.equipment_id
+        ));
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"][0]["state_group_name"], "Filler");
+
+        let request = get(format!("/api/v1/equipment/{}/state-groups", Uuid::new_v4()));
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_available_codes_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let service = create_test_service(pool.clone());
+        let group = service
+            .create("Filler", "Filler states")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        sqlx::query!(
+            r#"INSERT INTO core.state (state_group_id, state_code, state_description)
+               VALUES ($1, 1, 'running'), ($1, 2, 'idle')"#,
+            group.state_group_id
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.state_code_reservation (state_group_id, min_code, max_code, owner, label)
+               VALUES ($1, 5, 7, 'Rockwell', 'AB PLCs')"#,
+            group.state_group_id
+        )
+        .execute(&pool)
+        .await?;
+        let app = router().layer(Extension(service));
+
+        let get = |uri: String| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let request = get(format!(
+            "/api/v1/state-groups/{}/available-codes?min=0&max=9",
+            group.state_group_id
+        ));
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["available_codes"], json!([0, 3, 4, 8, 9]));
+        assert_eq!(body["data"]["used_codes"], json!([1, 2]));
+        assert_eq!(body["data"]["reserved_codes"], json!([5, 6, 7]));
+        assert_eq!(body["data"]["reservations"][0]["owner"], "Rockwell");
+
+        // the owner's own reservation counts as free
+        let request = get(format!(
+            "/api/v1/state-groups/{}/available-codes?min=0&max=9&owner=Rockwell",
+            group.state_group_id
+        ));
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(
+            body["data"]["available_codes"],
+            json!([0, 3, 4, 5, 6, 7, 8, 9])
+        );
+
+        let request = get(format!(
+            "/api/v1/state-groups/{}/available-codes?min=9&max=0",
+            group.state_group_id
+        ));
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert!(
+            body["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid code range")
+        );
+
+        let request = get(format!(
+            "/api/v1/state-groups/{}/available-codes",
+            Uuid::new_v4()
+        ));
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(body["error"], "State group not found");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,716 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::http::state_groups::UsageStatsQuery;
+use crate::models::app::Principal;
+use crate::models::core::StateUsageStats;
+use crate::services::state_service::{State, StateService};
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/api/v1/states", get(get_all_states).post(create_state))
+        .route("/api/v1/states/bulk", post(bulk_create_states))
+        .route("/api/v1/states/count", get(get_states_count))
+        .route("/api/v1/states/stats", get(get_states_usage_stats))
+        .route("/api/v1/states/code-range", get(get_states_by_code_range))
+        .route("/api/v1/states/by-code", get(get_state_by_code))
+        .route("/api/v1/states/code-exists", get(check_code_exists))
+        .route(
+            "/api/v1/states/description-exists",
+            get(check_description_exists),
+        )
+        .route("/api/v1/states/{id}", get(get_state_by_id))
+        .route("/api/v1/states/{id}/stats", get(get_state_usage_stats))
+        .route(
+            "/api/v1/states/update-description/{id}",
+            post(update_state_description),
+        )
+        .route("/api/v1/states/update-code/{id}", post(update_state_code))
+        .route(
+            "/api/v1/states/update-state-group/{id}",
+            post(update_state_group),
+        )
+        .route("/api/v1/states/delete/{id}", post(delete_state))
+        .route("/api/v1/states/restore/{id}", post(restore_state))
+        .route("/api/v1/states/exists/{id}", get(check_state_exists))
+}
+
+#[derive(Deserialize)]
+pub struct CreateStateRequest {
+    pub state_group_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    /// needed for codes inside a reserved range, see /api/v1/state-groups/{id}/reservations
+    pub owner: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkCreateStateRequest {
+    pub states: Vec<CreateStateRequest>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateStateDescriptionRequest {
+    pub state_description: String,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateStateCodeRequest {
+    pub state_code: i32,
+    pub owner: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateStateGroupRequest {
+    pub state_group_id: Uuid,
+    pub owner: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RestoreStateQuery {
+    pub owner: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct StateResponse {
+    pub state_id: Uuid,
+    pub state_group_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize)]
+pub struct BulkCreateStateResponse {
+    pub created: Vec<StateResponse>,
+    pub created_count: usize,
+    pub total_requested: usize,
+}
+
+#[derive(Deserialize)]
+pub struct PaginationQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+    pub state_group_id: Option<Uuid>,
+    pub search: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CodeRangeQuery {
+    pub state_group_id: Uuid,
+    pub min: i32,
+    pub max: i32,
+}
+
+#[derive(Deserialize)]
+pub struct CodeQuery {
+    pub state_group_id: Uuid,
+    pub code: i32,
+}
+
+#[derive(Deserialize)]
+pub struct DescriptionQuery {
+    pub state_group_id: Uuid,
+    pub description: String,
+}
+
+#[derive(Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub total_count: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: i64,
+}
+
+#[derive(Serialize)]
+pub struct ExistsResponse {
+    pub exists: bool,
+}
+
+fn default_page() -> i64 {
+    1
+}
+fn default_per_page() -> i64 {
+    50
+}
+
+impl From<State> for StateResponse {
+    fn from(state: State) -> Self {
+        Self {
+            state_id: state.state_id,
+            state_group_id: state.state_group_id,
+            state_code: state.state_code,
+            state_description: state.state_description,
+            created_at: state.created_at,
+            updated_at: state.updated_at,
+        }
+    }
+}
+
+async fn get_all_states(
+    Extension(service): Extension<StateService>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<StateResponse>>>, AppError> {
+    let offset = (query.page - 1) * query.per_page;
+
+    let (states, total_count) = service
+        .search_with_filters(
+            query.state_group_id,
+            query.search.as_deref(),
+            offset,
+            query.per_page,
+        )
+        .await?;
+
+    let response = PaginatedResponse {
+        data: states.into_iter().map(StateResponse::from).collect(),
+        total_count,
+        page: query.page,
+        per_page: query.per_page,
+        total_pages: (total_count + query.per_page - 1) / query.per_page,
+    };
+
+    info!(
+        "Retrieved {} states (page {}/{})",
+        response.data.len(),
+        response.page,
+        response.total_pages
+    );
+
+    Ok(Json(ApiResponse::success(response)))
+}
+
+async fn get_state_by_id(
+    Extension(service): Extension<StateService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<StateResponse>>, AppError> {
+    let state = service.get_by_id(id).await?;
+    Ok(Json(ApiResponse::success(StateResponse::from(state))))
+}
+
+async fn get_state_by_code(
+    Extension(service): Extension<StateService>,
+    Query(query): Query<CodeQuery>,
+) -> Result<Json<ApiResponse<StateResponse>>, AppError> {
+    let state = service
+        .get_by_code(query.state_group_id, query.code)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "State with code {} not found in state group {}",
+                query.code, query.state_group_id
+            ))
+        })?;
+    Ok(Json(ApiResponse::success(StateResponse::from(state))))
+}
+
+async fn get_states_by_code_range(
+    Extension(service): Extension<StateService>,
+    Query(query): Query<CodeRangeQuery>,
+) -> Result<Json<ApiResponse<Vec<StateResponse>>>, AppError> {
+    let states = service
+        .get_by_code_range(query.state_group_id, query.min, query.max)
+        .await?;
+    info!(
+        "Retrieved {} states in code range {}..={}",
+        states.len(),
+        query.min,
+        query.max
+    );
+    Ok(Json(ApiResponse::success(
+        states.into_iter().map(StateResponse::from).collect(),
+    )))
+}
+
+async fn create_state(
+    Extension(service): Extension<StateService>,
+    principal: Option<Principal>,
+    Json(payload): Json<CreateStateRequest>,
+) -> Result<Json<ApiResponse<StateResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state = service
+        .create(
+            payload.state_group_id,
+            payload.state_code,
+            &payload.state_description,
+            payload.owner.as_deref(),
+        )
+        .await?;
+    info!(
+        "Created state {}: {}",
+        state.state_code, state.state_description
+    );
+    Ok(Json(ApiResponse::success(StateResponse::from(state))))
+}
+
+async fn bulk_create_states(
+    Extension(service): Extension<StateService>,
+    principal: Option<Principal>,
+    Json(request): Json<BulkCreateStateRequest>,
+) -> Result<Json<ApiResponse<BulkCreateStateResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state_data: Vec<(Uuid, i32, &str, Option<&str>)> = request
+        .states
+        .iter()
+        .map(|item| {
+            (
+                item.state_group_id,
+                item.state_code,
+                item.state_description.as_str(),
+                item.owner.as_deref(),
+            )
+        })
+        .collect();
+
+    let states = service.bulk_create(state_data).await?;
+    let created: Vec<StateResponse> = states.into_iter().map(StateResponse::from).collect();
+
+    let bulk_response = BulkCreateStateResponse {
+        created_count: created.len(),
+        total_requested: request.states.len(),
+        created,
+    };
+
+    info!(
+        "Bulk created {}/{} states",
+        bulk_response.created_count, bulk_response.total_requested
+    );
+
+    Ok(Json(ApiResponse::success(bulk_response)))
+}
+
+async fn update_state_description(
+    Extension(service): Extension<StateService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateStateDescriptionRequest>,
+) -> Result<Json<ApiResponse<StateResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state = service
+        .update_description(id, &request.state_description)
+        .await?;
+    info!("Updated state description {}", id);
+    Ok(Json(ApiResponse::success(StateResponse::from(state))))
+}
+
+async fn update_state_code(
+    Extension(service): Extension<StateService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateStateCodeRequest>,
+) -> Result<Json<ApiResponse<StateResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state = service
+        .update_code(id, request.state_code, request.owner.as_deref())
+        .await?;
+    info!("Updated state code {}: {}", id, state.state_code);
+    Ok(Json(ApiResponse::success(StateResponse::from(state))))
+}
+
+async fn update_state_group(
+    Extension(service): Extension<StateService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateStateGroupRequest>,
+) -> Result<Json<ApiResponse<StateResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state = service
+        .update_state_group(id, request.state_group_id, request.owner.as_deref())
+        .await?;
+    info!("Moved state {} to group {}", id, state.state_group_id);
+    Ok(Json(ApiResponse::success(StateResponse::from(state))))
+}
+
+async fn delete_state(
+    Extension(service): Extension<StateService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    service.delete(id).await?;
+    info!("Deleted state: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn restore_state(
+    Extension(service): Extension<StateService>,
+    principal: Option<Principal>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RestoreStateQuery>,
+) -> Result<Json<ApiResponse<StateResponse>>, AppError> {
+    let service = service.acting_as(principal.map(|p| p.actor()));
+    let state = service.restore(id, query.owner.as_deref()).await?;
+    info!("Restored state: {}", id);
+    Ok(Json(ApiResponse::success(StateResponse::from(state))))
+}
+
+async fn get_states_count(
+    Extension(service): Extension<StateService>,
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn get_states_usage_stats(
+    Extension(service): Extension<StateService>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<StateUsageStats>>, AppError> {
+    let stats = service.usage_stats(None, query.from, query.to).await?;
+    info!(
+        "State usage stats: {} states, {} unused",
+        stats.summary.total_states, stats.summary.unused_states
+    );
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+async fn get_state_usage_stats(
+    Extension(service): Extension<StateService>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<UsageStatsQuery>,
+) -> Result<Json<ApiResponse<StateUsageStats>>, AppError> {
+    let stats = service.usage_stats(Some(id), query.from, query.to).await?;
+    Ok(Json(ApiResponse::success(stats)))
+}
+
+async fn check_state_exists(
+    Extension(service): Extension<StateService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+async fn check_code_exists(
+    Extension(service): Extension<StateService>,
+    Query(query): Query<CodeQuery>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service
+        .code_exists_in_group(query.state_group_id, query.code)
+        .await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+async fn check_description_exists(
+    Extension(service): Extension<StateService>,
+    Query(query): Query<DescriptionQuery>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service
+        .description_exists_in_group(query.state_group_id, &query.description)
+        .await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::{Value, json};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn create_test_state_group(pool: &PgPool) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            r#"INSERT INTO core.state_group (state_group_name, state_group_description)
+               VALUES ('HTTP Test Group', 'HTTP Test Description')
+               RETURNING state_group_id"#
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_create_state_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let group_id = create_test_state_group(&pool).await?;
+        let app = router().layer(Extension(StateService::new(pool)));
+
+        let request_body = json!({
+            "state_group_id": group_id,
+            "state_code": 1,
+            "state_description": "running"
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/states")
+            .header("content-type", "application/json")
+            .body(Body::from(request_body.to_string()))
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["data"]["state_code"], 1);
+
+        // same code again is rejected
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/states")
+            .header("content-type", "application/json")
+            .body(Body::from(request_body.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_code_range_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let group_id = create_test_state_group(&pool).await?;
+        let service = StateService::new(pool);
+
+        service
+            .bulk_create(vec![
+                (group_id, 1, "running", None),
+                (group_id, 10, "idle", None),
+                (group_id, 20, "blocked", None),
+            ])
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/states/code-range?state_group_id={}&min=0&max=10",
+                group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/states/code-range?state_group_id={}&min=10&max=0",
+                group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_bulk_create_endpoint_rolls_back(pool: PgPool) -> sqlx::Result<()> {
+        let group_id = create_test_state_group(&pool).await?;
+        let service = StateService::new(pool);
+        let app = router().layer(Extension(service.clone()));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/states/bulk")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"states": [
+                    {"state_group_id": group_id, "state_code": 1, "state_description": "running"},
+                    {"state_group_id": group_id, "state_code": 2, "state_description": "idle"},
+                    {"state_group_id": group_id, "state_code": 1, "state_description": "faulted"}
+                ]})
+                .to_string(),
+            ))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let states = service
+            .get_by_state_group_id(group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(states.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_code_exists_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let group_id = create_test_state_group(&pool).await?;
+        let service = StateService::new(pool);
+
+        service
+            .create(group_id, 7, "planned downtime", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/states/code-exists?state_group_id={}&code=7",
+                group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["exists"], true);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/states/code-exists?state_group_id={}&code=8",
+                group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["exists"], false);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_description_exists_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let group_id = create_test_state_group(&pool).await?;
+        let service = StateService::new(pool);
+
+        service
+            .create(group_id, 7, "planned downtime", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/states/description-exists?state_group_id={}&description=planned%20downtime",
+                group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["exists"], true);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/states/description-exists?state_group_id={}&description=starved",
+                group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["exists"], false);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_list_states_by_group_endpoint(pool: PgPool) -> sqlx::Result<()> {
+        let group_id = create_test_state_group(&pool).await?;
+        let service = StateService::new(pool);
+
+        service
+            .bulk_create(vec![
+                (group_id, 1, "running", None),
+                (group_id, 2, "idle", None),
+            ])
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let app = router().layer(Extension(service));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/states?state_group_id={}&per_page=1",
+                group_id
+            ))
+            .body(Body::empty())
+            .unwrap();
+
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["total_count"], 2);
+        assert_eq!(body["data"]["total_pages"], 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_usage_stats_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let app = router().layer(Extension(StateService::new(pool)));
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/states/stats?from=2026-01-01T00:00:00Z")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.clone().oneshot(request).await.unwrap()).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["from"], "2026-01-01T00:00:00Z");
+        assert_eq!(body["data"]["summary"]["total_states"], 11);
+        assert_eq!(body["data"]["summary"]["unused_states"], 11);
+        assert_eq!(
+            body["data"]["state_groups"][0]["states"][1]["state_code"],
+            1
+        );
+
+        let request = Request::builder()
+            .method("GET")
+            .uri(format!("/api/v1/states/{}/stats", Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"], "State not found");
+
+        Ok(())
+    }
+}
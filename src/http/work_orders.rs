@@ -0,0 +1,340 @@
+use crate::error::AppError;
+use crate::http::date_format;
+use crate::http::response::ApiResponse;
+use crate::models::operations::WorkOrder;
+use crate::services::work_order_service::WorkOrderService;
+use axum::{
+    Json, Router,
+    extract::{Extension, Path, Query},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+// work order endpoints
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/api/v1/work-orders",
+            get(get_all_work_orders).post(create_work_order),
+        )
+        .route("/api/v1/work-orders/count", get(get_work_order_count))
+        .route("/api/v1/work-orders/{id}", get(get_work_order_by_id))
+        .route("/api/v1/work-orders/update/{id}", post(update_work_order))
+        .route(
+            "/api/v1/work-orders/update-metadata/{id}",
+            post(update_work_order_metadata),
+        )
+        .route("/api/v1/work-orders/delete/{id}", post(delete_work_order))
+        .route(
+            "/api/v1/work-orders/exists/{id}",
+            get(check_work_order_exists),
+        )
+}
+
+// request/response dtos
+#[derive(Serialize)]
+pub struct WorkOrderResponse {
+    pub work_order_id: Uuid,
+    pub work_order_name: String,
+    pub product_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub work_order_metadata: Value,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(
+        serialize_with = "date_format::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateWorkOrderRequest {
+    pub work_order_name: String,
+    pub product_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub work_order_metadata: Option<Value>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateWorkOrderRequest {
+    pub work_order_name: String,
+    pub planned_quantity: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateWorkOrderMetadataRequest {
+    pub work_order_metadata: Value,
+}
+
+#[derive(Serialize)]
+pub struct CountResponse {
+    pub count: i64,
+}
+
+#[derive(Serialize)]
+pub struct ExistsResponse {
+    pub exists: bool,
+}
+
+#[derive(Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    pub total_count: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PaginationQuery {
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_per_page")]
+    pub per_page: i64,
+}
+
+fn default_page() -> i64 {
+    1
+}
+fn default_per_page() -> i64 {
+    50
+}
+
+// service model -> response model
+impl From<WorkOrder> for WorkOrderResponse {
+    fn from(work_order: WorkOrder) -> Self {
+        Self {
+            work_order_id: work_order.work_order_id,
+            work_order_name: work_order.work_order_name,
+            product_id: work_order.product_id,
+            planned_quantity: work_order.planned_quantity,
+            work_order_metadata: work_order.work_order_metadata,
+            created_at: work_order.created_at,
+            updated_at: work_order.updated_at,
+        }
+    }
+}
+
+// handler functions for http endpoints
+async fn get_all_work_orders(
+    Extension(service): Extension<WorkOrderService>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<PaginatedResponse<WorkOrderResponse>>>, AppError> {
+    // Convert 1-based page to 0-based offset
+    let offset = (pagination.page - 1) * pagination.per_page;
+
+    let (work_orders, total_count) = service.get_paginated(offset, pagination.per_page).await?;
+    let response: Vec<WorkOrderResponse> = work_orders
+        .into_iter()
+        .map(WorkOrderResponse::from)
+        .collect();
+
+    let total_pages = (total_count + pagination.per_page - 1) / pagination.per_page;
+
+    let paginated_response = PaginatedResponse {
+        data: response,
+        total_count,
+        page: pagination.page,
+        per_page: pagination.per_page,
+        total_pages,
+    };
+
+    info!(
+        "Retrieved {} work orders (page {}/{}, total: {})",
+        paginated_response.data.len(),
+        pagination.page,
+        total_pages,
+        total_count
+    );
+
+    Ok(Json(ApiResponse::success(paginated_response)))
+}
+
+async fn get_work_order_by_id(
+    Extension(service): Extension<WorkOrderService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WorkOrderResponse>>, AppError> {
+    let work_order = service.get_by_id(id).await?;
+    info!("Retrieved work order: {}", work_order.work_order_name);
+    Ok(Json(ApiResponse::success(WorkOrderResponse::from(
+        work_order,
+    ))))
+}
+
+async fn create_work_order(
+    Extension(service): Extension<WorkOrderService>,
+    Json(request): Json<CreateWorkOrderRequest>,
+) -> Result<Json<ApiResponse<WorkOrderResponse>>, AppError> {
+    let work_order = service
+        .create(
+            &request.work_order_name,
+            request.product_id,
+            request.planned_quantity,
+            request.work_order_metadata.as_ref(),
+        )
+        .await?;
+    info!("Created work order: {}", work_order.work_order_name);
+    Ok(Json(ApiResponse::success(WorkOrderResponse::from(
+        work_order,
+    ))))
+}
+
+async fn update_work_order(
+    Extension(service): Extension<WorkOrderService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateWorkOrderRequest>,
+) -> Result<Json<ApiResponse<WorkOrderResponse>>, AppError> {
+    let work_order = service
+        .update(id, &request.work_order_name, request.planned_quantity)
+        .await?;
+    info!("Updated work order {}: {}", id, work_order.work_order_name);
+    Ok(Json(ApiResponse::success(WorkOrderResponse::from(
+        work_order,
+    ))))
+}
+
+async fn update_work_order_metadata(
+    Extension(service): Extension<WorkOrderService>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateWorkOrderMetadataRequest>,
+) -> Result<Json<ApiResponse<WorkOrderResponse>>, AppError> {
+    let work_order = service
+        .update_metadata(id, &request.work_order_metadata)
+        .await?;
+    info!("Updated work order metadata {}", id);
+    Ok(Json(ApiResponse::success(WorkOrderResponse::from(
+        work_order,
+    ))))
+}
+
+async fn delete_work_order(
+    Extension(service): Extension<WorkOrderService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    service.delete(id).await?;
+    info!("Deleted work order: {}", id);
+    Ok(Json(ApiResponse::success(())))
+}
+
+async fn get_work_order_count(
+    Extension(service): Extension<WorkOrderService>,
+) -> Result<Json<ApiResponse<CountResponse>>, AppError> {
+    let count = service.count().await?;
+    info!("Total work order count: {}", count);
+    Ok(Json(ApiResponse::success(CountResponse { count })))
+}
+
+async fn check_work_order_exists(
+    Extension(service): Extension<WorkOrderService>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ExistsResponse>>, AppError> {
+    let exists = service.exists(id).await?;
+    Ok(Json(ApiResponse::success(ExistsResponse { exists })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::json;
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    async fn body_json(response: axum::response::Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_work_order_endpoints(pool: PgPool) -> sqlx::Result<()> {
+        let product_id = sqlx::query_scalar!(
+            "INSERT INTO operations.product (product_name) VALUES ('Widget') RETURNING product_id"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let app = router().layer(Extension(WorkOrderService::new(pool)));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/work-orders")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "work_order_name": "WO-1",
+                    "product_id": product_id,
+                    "planned_quantity": 100
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body_json(response).await;
+        assert_eq!(body["success"], true);
+        assert_eq!(body["data"]["planned_quantity"], 100);
+        let work_order_id = body["data"]["work_order_id"].as_str().unwrap().to_string();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/work-orders")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"work_order_name": "WO-2", "product_id": Uuid::new_v4()}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("does not exist"));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/work-orders/update/{}", work_order_id))
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"work_order_name": "WO-1", "planned_quantity": 0}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["error"], "planned_quantity must be greater than 0");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/work-orders")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"work_order_name": "WO-1", "product_id": product_id}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/v1/work-orders/count")
+            .body(Body::empty())
+            .unwrap();
+        let body = body_json(app.oneshot(request).await.unwrap()).await;
+        assert_eq!(body["data"]["count"], 1);
+
+        Ok(())
+    }
+}
@@ -1,14 +1,21 @@
 use anyhow::Context;
 use clap::Parser;
-use sqlx::postgres::PgPoolOptions;
 
+mod commands;
 mod config;
 mod database;
+mod error;
+mod grpc;
 mod http;
 mod models;
 mod services;
+mod shutdown;
 
-use config::Config;
+use config::{Cli, Command, Config};
+use database::migrations::MigrationQueries;
+use services::event_service::EventService;
+use services::purge_service::PurgeService;
+use shutdown::Shutdown;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -19,51 +26,70 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
     // parse configuration from cli args and environment
-    let config: Config = Config::parse();
+    match Cli::parse().command {
+        Command::Serve(config) => serve(*config).await?,
+        Command::Migrate { database, command } => commands::migrate(database, command).await?,
+        Command::Db { database, command } => commands::db(database, command).await?,
+        Command::ApiKey { database, command } => {
+            commands::api_key(database, command).await?
+        }
+    }
+
+    Ok(())
+}
+
+async fn serve(config: Config) -> anyhow::Result<()> {
     config.validate()?;
 
-    // shared connection pool for sqlx shared across the application
-    let db = PgPoolOptions::new()
-        .max_connections(config.pool_size)
-        .connect(&config.database_url.to_string())
-        .await
-        .context("could not connect to database_url")?;
+    let db = config.database.connect().await?;
+
+    // opt in, deployments that manage the schema themselves run `migrate up` instead
+    if config.auto_migrate {
+        MigrationQueries::run(&db)
+            .await
+            .context("failed to apply migrations")?;
+        println!("Database migrations applied");
+    }
 
-    // run migrations (idk if we need this, keeping in for now)
-    // sqlx::migrate!().run(&db).await?;
+    // relay entity, mode and state changes from postgres to the streaming subscribers
+    let events = EventService::new(db.clone());
+    let listener = events.clone();
+    tokio::spawn(async move { listener.listen().await });
 
-    // create services
-    // let equipment_type_service = EquipmentTypeService::new(db.clone());
+    // soft deleted rows can be restored until they are past the retention
+    if let Some(interval) = config.purge_interval() {
+        let purge = PurgeService::new(db.clone(), config.retention.soft_delete_retention_days);
+        tokio::spawn(async move { purge.run_every(interval).await });
+    }
+
+    // both servers drain and stop on SIGTERM/SIGINT
+    let shutdown = Shutdown::on_signal();
 
     // start both http and gRPC servers concurrently or in parallel
     tokio::try_join!(
-        start_http_server(config, db.clone()),
-        // start_grpc_server(equipment_type_service)
+        start_http_server(config.clone(), db.clone(), events.clone(), shutdown.clone()),
+        start_grpc_server(config, db, events, shutdown)
     )?;
 
     Ok(())
 }
 
-async fn start_http_server(config: Config, db: sqlx::PgPool) -> anyhow::Result<()> {
+async fn start_http_server(
+    config: Config,
+    db: sqlx::PgPool,
+    events: EventService,
+    shutdown: Shutdown,
+) -> anyhow::Result<()> {
     println!("Starting HTTP server...");
-    http::serve(config, db).await
+    http::serve(config, db, events, shutdown).await
 }
 
-// async fn start_grpc_server(equipment_type_service: EquipmentTypeService) -> anyhow::Result<()> {
-//     use crate::grpc::equipment_types::{
-//         EquipmentTypesGrpcService,
-//         equipment_type_proto::equipment_types_server::EquipmentTypesServer,
-//     };
-//     use tonic::transport::Server;
-
-//     let equipment_types_service = EquipmentTypesGrpcService::new(equipment_type_service);
-
-//     let addr = "0.0.0.0:50051".parse()?;
-//     println!("gRPC server listening on http://{}", addr);
-
-//     Server::builder()
-//         .add_service(EquipmentTypesServer::new(equipment_types_service))
-//         .serve(addr)
-//         .await
-//         .context("gRPC server error")
-// }
+async fn start_grpc_server(
+    config: Config,
+    db: sqlx::PgPool,
+    events: EventService,
+    shutdown: Shutdown,
+) -> anyhow::Result<()> {
+    println!("Starting gRPC server...");
+    grpc::serve(config, db, events, shutdown).await
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub api_key_id: Uuid,
+    pub key_name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<Scope>,
+    pub last_used_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+/// Who a request was made by, a user signed in through the identity provider or an
+/// api key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// `sub` claim for users, the key id for api keys
+    pub subject: String,
+    /// preferred_username, email or name for users, the key name for api keys
+    pub name: String,
+    pub kind: PrincipalKind,
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrincipalKind {
+    User,
+    ApiKey,
+}
+
+/// A change to a core configuration row, recorded by the audit triggers
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub audit_id: Uuid,
+    pub occurred_at: OffsetDateTime,
+    /// None for changes made directly in the database
+    pub actor: Option<String>,
+    /// created, updated, deleted, restored or purged
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    /// The row before the change, None when it was created
+    pub before: Option<Value>,
+    /// The row after the change, None when it was purged
+    pub after: Option<Value>,
+    pub transaction_id: i64,
+}
+
+/// Which audit entries to return, unset fields match everything. `from` is inclusive,
+/// `to` exclusive.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub from: Option<OffsetDateTime>,
+    pub to: Option<OffsetDateTime>,
+    pub limit: i64,
+}
+
+/// What a principal is allowed to do. Each scope includes the ones below it, so a
+/// write key can also read and an admin key can do anything.
+///
+/// ```text
+/// admin -> write -> read
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        }
+    }
+
+    /// True when holding this scope is enough for something that needs `required`
+    pub fn grants(self, required: Scope) -> bool {
+        self >= required
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Scope::Read),
+            "write" => Ok(Scope::Write),
+            "admin" => Ok(Scope::Admin),
+            other => Err(format!("unknown scope '{}'", other)),
+        }
+    }
+}
+
+impl Principal {
+    pub fn has_scope(&self, required: Scope) -> bool {
+        self.scopes.iter().any(|scope| scope.grants(required))
+    }
+
+    /// Name recorded in set_by, changed_by and similar columns
+    pub fn actor(&self) -> String {
+        match self.kind {
+            PrincipalKind::User => self.name.clone(),
+            PrincipalKind::ApiKey => format!("api-key:{}", self.name),
+        }
+    }
+
+    /// Name to record for a change a client says it made for `on_behalf_of`. Users are
+    /// always recorded as themselves. Api keys are shared by machines such as HMIs and
+    /// PLC gateways, so they may name the operator or device they act for, recorded next
+    /// to the key since the name itself is not checked.
+    pub fn attribute(&self, on_behalf_of: Option<&str>) -> String {
+        let on_behalf_of = on_behalf_of.map(str::trim).filter(|s| !s.is_empty());
+        match (self.kind, on_behalf_of) {
+            (PrincipalKind::ApiKey, Some(name)) => format!("{} for {}", self.actor(), name),
+            _ => self.actor(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_grants() {
+        assert!(Scope::Admin.grants(Scope::Write));
+        assert!(Scope::Write.grants(Scope::Read));
+        assert!(Scope::Read.grants(Scope::Read));
+        assert!(!Scope::Read.grants(Scope::Write));
+        assert!(!Scope::Write.grants(Scope::Admin));
+
+        assert_eq!("write".parse::<Scope>(), Ok(Scope::Write));
+        assert!("delete".parse::<Scope>().is_err());
+    }
+
+    #[test]
+    fn test_principal_attribution() {
+        let user = Principal {
+            subject: "0f3c".to_string(),
+            name: "jdoe".to_string(),
+            kind: PrincipalKind::User,
+            scopes: vec![Scope::Write],
+        };
+        assert!(user.has_scope(Scope::Read));
+        assert!(!user.has_scope(Scope::Admin));
+        assert_eq!(user.attribute(Some("someone else")), "jdoe");
+
+        let hmi = Principal {
+            name: "Line 1 HMI".to_string(),
+            kind: PrincipalKind::ApiKey,
+            ..user
+        };
+        assert_eq!(hmi.attribute(None), "api-key:Line 1 HMI");
+        assert_eq!(
+            hmi.attribute(Some(" operator ")),
+            "api-key:Line 1 HMI for operator"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Clone, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct EquipmentType {
     pub type_id: Uuid, 
     pub type_name: String,
@@ -12,13 +12,13 @@ pub struct EquipmentType {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EquipmentMetadata {
     #[serde(flatten)]
     pub extra: HashMap<String, Value>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Equipment {
     pub equipment_id: Uuid, 
     pub equipment_name: String,
@@ -30,40 +30,63 @@ pub struct Equipment {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EquipmentModeGroupMapping {
     pub equipment_id: Uuid,  
     pub mode_group_id: Uuid, 
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ModeGroups {
     pub mode_group_id: Uuid, 
     pub mode_group_name: String,
     pub mode_group_description: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Modes {
     pub mode_id: Uuid,       
     pub mode_group_id: Uuid, 
     pub mode_description: String,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EquipmentStateGroupMapping {
     pub equipment_id: Uuid,   
     pub state_group_id: Uuid, 
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+/// Equipment mapped to a mode or state group, as listed by core.getEquipmentForModeGroup
+/// and core.getEquipmentForStateGroup
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GroupEquipment {
+    pub equipment_id: Uuid,
+    pub equipment_name: String,
+    pub equipment_type_id: Uuid,
+    pub equipment_type_name: String,
+    pub equipment_parent_id: Option<Uuid>,
+    pub equipment_enabled: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of assigning many equipment to a group at once. Ids that don't belong to live
+/// equipment are in neither list.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BulkGroupAssignment {
+    pub assigned_equipment_ids: Vec<Uuid>,
+    /// already assigned before
+    pub skipped_equipment_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StateGroup {
     pub state_group_id: Uuid, 
     pub state_group_name: String,
     pub state_group_description: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct State {
     pub state_id: Uuid,       
     pub state_group_id: Uuid, 
@@ -71,7 +94,7 @@ pub struct State {
     pub state_description: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EquipmentWithRelations {
     pub equipment_id: Uuid,
     pub equipment_name: String,
@@ -84,7 +107,7 @@ pub struct EquipmentWithRelations {
 
     // Related data
     pub equipment_type: EquipmentType,
-    pub child_equipment: Vec<Equipment>,
+    pub child_equipment: Vec<EquipmentWithRelations>,
 
     // Only present for certain equipment types
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -94,7 +117,7 @@ pub struct EquipmentWithRelations {
     pub state_groups: Vec<StateGroupWithStates>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ModeGroupWithModes {
     pub mode_group_id: Uuid,
     pub mode_group_name: String,
@@ -102,7 +125,7 @@ pub struct ModeGroupWithModes {
     pub modes: Vec<Modes>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StateGroupWithStates {
     pub state_group_id: Uuid,
     pub state_group_name: String,
@@ -110,26 +133,219 @@ pub struct StateGroupWithStates {
     pub states: Vec<State>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct CurrentEquipmentMode {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EquipmentPath {
     pub equipment_id: Uuid,
+    pub path: Vec<Equipment>,
+    pub depth: i32,
+}
+/// core.getModeGroupUsageStats: counts per mode group plus the time equipment spent in
+/// its modes between `from` and `to`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeGroupUsageStats {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub mode_groups: Vec<ModeGroupUsage>,
+    pub summary: ModeGroupUsageSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeGroupUsage {
+    pub mode_group_id: Uuid,
+    pub mode_group_name: String,
+    pub mode_group_description: String,
+    pub mode_count: i64,
+    /// modes no equipment was in during the range
+    pub unused_mode_count: i64,
+    pub equipment_count: i64,
+    pub enabled_equipment_count: i64,
+    pub runtime_seconds: f64,
+    /// end of the latest interval in any of its modes, None if never used
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub is_default_group: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeGroupUsageSummary {
+    pub total_mode_groups: i64,
+    /// assigned to at least one equipment
+    pub active_mode_groups: i64,
+    pub unused_mode_groups: i64,
+    /// no runtime in the range
+    pub idle_mode_groups: i64,
+}
+
+/// core.getModeUsageStats
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeUsageStats {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub modes: Vec<ModeUsage>,
+    pub summary: ModeUsageSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeUsage {
     pub mode_id: Uuid,
-    pub set_at: DateTime<Utc>,
-    pub set_by: Option<String>,
+    pub mode_description: String,
+    pub mode_group: ModeUsageGroup,
+    pub is_default_mode: bool,
+    pub runtime_seconds: f64,
+    /// equipment that were in the mode during the range
+    pub equipment_count: i64,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct CurrentEquipmentState {
-    pub equipment_id: Uuid,
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeUsageGroup {
+    pub mode_group_id: Uuid,
+    pub mode_group_name: String,
+    pub mode_group_description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeUsageSummary {
+    pub total_modes: i64,
+    pub default_modes: i64,
+    pub custom_modes: i64,
+    pub unused_modes: i64,
+    pub mode_groups_with_modes: i64,
+    pub total_mode_groups: i64,
+}
+
+/// core.getStateGroupUsageStats: counts per state group plus the time equipment spent in
+/// its states between `from` and `to`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateGroupUsageStats {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub state_groups: Vec<StateGroupUsage>,
+    pub summary: StateGroupUsageSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateGroupUsage {
+    pub state_group_id: Uuid,
+    pub state_group_name: String,
+    pub state_group_description: String,
+    pub state_count: i64,
+    /// states no equipment reported during the range
+    pub unused_state_count: i64,
+    pub equipment_count: i64,
+    pub enabled_equipment_count: i64,
+    pub state_code_range: Option<StateCodeRange>,
+    pub runtime_seconds: f64,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub is_default_group: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateGroupUsageSummary {
+    pub total_state_groups: i64,
+    pub active_state_groups: i64,
+    pub unused_state_groups: i64,
+    pub idle_state_groups: i64,
+}
+
+/// Lowest and highest state code, None when there are no states
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateCodeRange {
+    pub min_code: i32,
+    pub max_code: i32,
+    pub code_span: i32,
+}
+
+/// core.getStateUsageStats: states with their runtime, listed per state group
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateUsageStats {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub state_groups: Vec<StateGroupStateUsage>,
+    pub summary: StateUsageSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateGroupStateUsage {
+    pub state_group_id: Uuid,
+    pub state_group_name: String,
+    pub state_group_description: String,
+    pub state_count: i64,
+    pub unused_state_count: i64,
+    pub code_range: Option<StateCodeRange>,
+    pub runtime_seconds: f64,
+    pub states: Vec<StateUsage>,
+    pub is_default_group: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateUsage {
     pub state_id: Uuid,
-    pub state_value: Option<Value>,
-    pub updated_at: DateTime<Utc>,
-    pub updated_by: Option<String>,
+    pub state_code: i32,
+    pub state_description: String,
+    pub runtime_seconds: f64,
+    /// equipment that reported the state during the range
+    pub equipment_count: i64,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct EquipmentPath {
-    pub equipment_id: Uuid,
-    pub path: Vec<Equipment>,
-    pub depth: i32,
-}
\ No newline at end of file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StateUsageSummary {
+    pub total_states: i64,
+    pub total_state_groups: i64,
+    pub state_groups_with_states: i64,
+    pub default_states: i64,
+    pub custom_states: i64,
+    pub unused_states: i64,
+    pub code_range_overall: Option<StateCodeRange>,
+}
+
+/// core.getAvailableStateCodes: codes in a range of a state group that are neither used nor
+/// reserved for another owner
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailableStateCodes {
+    pub state_group_id: Uuid,
+    pub state_group_name: String,
+    /// owner the codes were looked up for, reservations of this owner count as free
+    pub owner: Option<String>,
+    pub code_range: AvailableCodeRange,
+    pub used_codes: Vec<i32>,
+    pub reserved_codes: Vec<i32>,
+    pub available_codes: Vec<i32>,
+    /// reservations that overlap the range, including the owner's own
+    pub reservations: Vec<ReservedCodeRange>,
+    pub usage_stats: AvailableCodeUsage,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailableCodeRange {
+    pub min_code: i32,
+    pub max_code: i32,
+    pub total_range: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReservedCodeRange {
+    pub reservation_id: Uuid,
+    pub min_code: i32,
+    pub max_code: i32,
+    pub owner: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailableCodeUsage {
+    pub used_count: i64,
+    pub reserved_count: i64,
+    pub available_count: i64,
+    pub usage_percentage: f64,
+}
@@ -1 +1,4 @@
+pub mod app;
 pub mod core;
+pub mod operations;
+pub mod plant_model;
@@ -1,3 +1,265 @@
-pub struct Products {
-    future_use: String,
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct Product {
+    pub product_id: Uuid,
+    pub product_name: String,
+    pub product_description: Option<String>,
+    pub product_metadata: Value,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkOrder {
+    pub work_order_id: Uuid,
+    pub work_order_name: String,
+    pub product_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub work_order_metadata: Value,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub job_id: Uuid,
+    pub work_order_id: Uuid,
+    pub equipment_id: Uuid,
+    pub planned_quantity: Option<i32>,
+    pub job_metadata: Value,
+    pub job_status: JobStatus,
+    pub released_at: Option<OffsetDateTime>,
+    pub started_at: Option<OffsetDateTime>,
+    pub completed_at: Option<OffsetDateTime>,
+    pub cancelled_at: Option<OffsetDateTime>,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JobStatusChange {
+    pub history_id: Uuid,
+    pub job_id: Uuid,
+    pub from_status: JobStatus,
+    pub to_status: JobStatus,
+    pub changed_at: OffsetDateTime,
+    pub changed_by: Option<String>,
+}
+
+/// Job lifecycle
+///
+/// ```text
+/// planned -> released -> running <-> paused
+///                           |          |
+///                           +----------+--> completed
+/// cancelled is reachable from every status that is not final
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Planned,
+    Released,
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobTransition {
+    Release,
+    Start,
+    Pause,
+    Resume,
+    Complete,
+    Cancel,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Planned => "planned",
+            JobStatus::Released => "released",
+            JobStatus::Running => "running",
+            JobStatus::Paused => "paused",
+            JobStatus::Completed => "completed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Completed and cancelled jobs cannot change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Cancelled)
+    }
+
+    /// The status after applying the transition, None if the transition is not legal
+    pub fn apply(self, transition: JobTransition) -> Option<JobStatus> {
+        match (self, transition) {
+            (JobStatus::Planned, JobTransition::Release) => Some(JobStatus::Released),
+            (JobStatus::Released, JobTransition::Start) => Some(JobStatus::Running),
+            (JobStatus::Running, JobTransition::Pause) => Some(JobStatus::Paused),
+            (JobStatus::Paused, JobTransition::Resume) => Some(JobStatus::Running),
+            (JobStatus::Running | JobStatus::Paused, JobTransition::Complete) => {
+                Some(JobStatus::Completed)
+            }
+            (status, JobTransition::Cancel) if !status.is_final() => Some(JobStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "planned" => Ok(JobStatus::Planned),
+            "released" => Ok(JobStatus::Released),
+            "running" => Ok(JobStatus::Running),
+            "paused" => Ok(JobStatus::Paused),
+            "completed" => Ok(JobStatus::Completed),
+            "cancelled" => Ok(JobStatus::Cancelled),
+            other => Err(format!("unknown job_status '{}'", other)),
+        }
+    }
+}
+
+impl JobTransition {
+    /// Verb used in routes and error messages
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobTransition::Release => "release",
+            JobTransition::Start => "start",
+            JobTransition::Pause => "pause",
+            JobTransition::Resume => "resume",
+            JobTransition::Complete => "complete",
+            JobTransition::Cancel => "cancel",
+        }
+    }
+}
+
+/// How the quantities of a count report are meant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CountMode {
+    /// Produced since the previous report
+    #[default]
+    Delta,
+    /// Raw plc counter values, turned into deltas against the previous reading
+    Absolute,
+}
+
+/// Good, scrap and rework values of a count report, None when not reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountValues {
+    pub good: Option<i64>,
+    pub scrap: Option<i64>,
+    pub rework: Option<i64>,
+}
+
+/// Share of the counter range a rollover can skip: a wrap is only assumed when the previous
+/// reading was in the last tenth of the range before `counter_max`
+const ROLLOVER_WINDOW: i64 = 10;
+
+/// Increase of a plc counter since the previous reading and whether it went backwards.
+///
+/// The first reading only sets the baseline. A reading lower than the previous one means the
+/// counter wrapped at `counter_max` when the previous reading was close to the max, otherwise
+/// it was reset, e.g. on a job change, and counted up from zero.
+pub fn counter_delta(previous: Option<i64>, current: i64, counter_max: Option<i64>) -> (i64, bool) {
+    match previous {
+        None => (0, false),
+        Some(previous) if current >= previous => (current - previous, false),
+        Some(previous) => match counter_max {
+            Some(max) if previous <= max && max - previous < (max + 1) / ROLLOVER_WINDOW => {
+                (max - previous + current + 1, true)
+            }
+            _ => (current, true),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_happy_path() {
+        let status = JobStatus::Planned
+            .apply(JobTransition::Release)
+            .and_then(|s| s.apply(JobTransition::Start))
+            .and_then(|s| s.apply(JobTransition::Pause))
+            .and_then(|s| s.apply(JobTransition::Resume))
+            .and_then(|s| s.apply(JobTransition::Complete));
+
+        assert_eq!(status, Some(JobStatus::Completed));
+    }
+
+    #[test]
+    fn test_job_illegal_transitions() {
+        // jobs have to be released before they can start
+        assert_eq!(JobStatus::Planned.apply(JobTransition::Start), None);
+        assert_eq!(JobStatus::Released.apply(JobTransition::Complete), None);
+        assert_eq!(JobStatus::Running.apply(JobTransition::Resume), None);
+        assert_eq!(JobStatus::Paused.apply(JobTransition::Pause), None);
+
+        for status in [JobStatus::Completed, JobStatus::Cancelled] {
+            assert!(status.is_final());
+            assert_eq!(status.apply(JobTransition::Cancel), None);
+            assert_eq!(status.apply(JobTransition::Start), None);
+        }
+    }
+
+    #[test]
+    fn test_job_cancel_from_open_statuses() {
+        for status in [
+            JobStatus::Planned,
+            JobStatus::Released,
+            JobStatus::Running,
+            JobStatus::Paused,
+        ] {
+            assert_eq!(
+                status.apply(JobTransition::Cancel),
+                Some(JobStatus::Cancelled)
+            );
+        }
+    }
+
+    #[test]
+    fn test_job_status_round_trip() {
+        for status in [JobStatus::Planned, JobStatus::Paused, JobStatus::Cancelled] {
+            assert_eq!(status.as_str().parse::<JobStatus>(), Ok(status));
+        }
+        assert!("done".parse::<JobStatus>().is_err());
+    }
+
+    #[test]
+    fn test_counter_delta() {
+        // first reading is the baseline
+        assert_eq!(counter_delta(None, 500, None), (0, false));
+        assert_eq!(counter_delta(Some(500), 520, None), (20, false));
+        assert_eq!(counter_delta(Some(520), 520, Some(65535)), (0, false));
+
+        // 65530 -> 65535 -> 0 -> 4 is 10 parts on a 16 bit counter
+        assert_eq!(counter_delta(Some(65530), 4, Some(65535)), (10, true));
+
+        // without a max the drop is treated as a reset to zero
+        assert_eq!(counter_delta(Some(900), 15, None), (15, true));
+
+        // far from the max it is a reset too, not 65,036 parts
+        assert_eq!(counter_delta(Some(500), 0, Some(65535)), (0, true));
+        assert_eq!(counter_delta(Some(500), 12, Some(65535)), (12, true));
+    }
 }
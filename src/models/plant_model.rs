@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The plant model as one document, what `GET /api/v1/plant-model/export` returns and
+/// `POST /api/v1/admin/plant-model/apply` takes, as JSON or YAML.
+///
+/// Everything is matched by name: equipment types by name, groups by name, modes by
+/// description, states by code and equipment by its name and type under its parent. A
+/// section that is left out is not touched, a section that is there is the complete list
+/// and anything missing from it is deleted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlantModel {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equipment_types: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode_groups: Option<Vec<PlantModeGroup>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_groups: Option<Vec<PlantStateGroup>>,
+    /// the roots of the equipment tree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equipment: Option<Vec<PlantEquipment>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlantModeGroup {
+    pub name: String,
+    pub description: String,
+    /// mode descriptions
+    #[serde(default)]
+    pub modes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlantStateGroup {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub states: Vec<PlantState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlantState {
+    pub code: i32,
+    pub description: String,
+    /// only read when the state is created, for codes inside a reserved range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlantEquipment {
+    pub name: String,
+    /// equipment type name
+    #[serde(rename = "type")]
+    pub equipment_type: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub metadata: Map<String, Value>,
+    /// names of the mode groups the equipment is assigned to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mode_groups: Vec<String>,
+    /// names of the state groups the equipment is assigned to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub state_groups: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PlantEquipment>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// What an apply changed, or would change when it is a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlantModelDiff {
+    pub dry_run: bool,
+    pub changes: Vec<PlantModelChange>,
+    pub summary: PlantModelDiffSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlantModelChange {
+    pub action: PlantModelAction,
+    /// same names as the audit log, e.g. "state" or "equipment_mode_group"
+    pub entity_type: String,
+    /// what the document matches the entity on, e.g. "Filler states/10" for a state or
+    /// "Acme/Plant 1/Line 1" for equipment
+    pub key: String,
+    /// the fields that changed, before and after
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlantModelAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlantModelDiffSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+}
@@ -0,0 +1,236 @@
+use crate::database::api_keys::{ApiKeyQueries, ApiKeyRow};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::app::{ApiKey, Principal, PrincipalKind, Scope};
+use rand::{Rng, distributions::Alphanumeric, rngs::OsRng};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+const MAX_NAME_LEN: usize = 255;
+
+/// Every issued key starts with this, makes them easy to spot in configs and logs
+pub const KEY_PREFIX: &str = "gmes_";
+const KEY_RANDOM_LEN: usize = 40;
+/// Characters of the key kept in the clear so people can tell their keys apart
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+impl From<ApiKeyRow> for ApiKey {
+    fn from(row: ApiKeyRow) -> Self {
+        Self {
+            api_key_id: row.api_key_id,
+            key_name: row.key_name,
+            key_prefix: row.key_prefix,
+            // the table only allows known scopes
+            scopes: row.scopes.iter().filter_map(|s| s.parse().ok()).collect(),
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl From<ApiKey> for Principal {
+    fn from(api_key: ApiKey) -> Self {
+        Self {
+            subject: api_key.api_key_id.to_string(),
+            name: api_key.key_name,
+            kind: PrincipalKind::ApiKey,
+            scopes: api_key.scopes,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyService {
+    db: PgPool,
+}
+
+impl ApiKeyService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    fn validate_name(key_name: &str) -> AppResult<String> {
+        let trimmed = key_name.trim().to_string();
+
+        if trimmed.is_empty() {
+            return Err(AppError::Validation("key_name cannot be empty".to_string()));
+        }
+
+        if trimmed.len() > MAX_NAME_LEN {
+            return Err(AppError::Validation(format!(
+                "key_name exceeds max length of {} characters",
+                MAX_NAME_LEN
+            )));
+        }
+
+        Ok(trimmed)
+    }
+
+    /// Parses scope names such as "read" or "write"
+    pub fn parse_scopes<S: AsRef<str>>(scopes: &[S]) -> AppResult<Vec<Scope>> {
+        scopes
+            .iter()
+            .map(|s| s.as_ref().trim().parse::<Scope>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::Validation)
+    }
+
+    fn validate_scopes(scopes: &[Scope]) -> AppResult<Vec<String>> {
+        let mut scopes = scopes.to_vec();
+        scopes.sort();
+        scopes.dedup();
+
+        if scopes.is_empty() {
+            return Err(AppError::Validation(
+                "at least one scope is required".to_string(),
+            ));
+        }
+
+        Ok(scopes.iter().map(|s| s.as_str().to_string()).collect())
+    }
+
+    fn generate_key() -> String {
+        let random: String = OsRng
+            .sample_iter(&Alphanumeric)
+            .take(KEY_RANDOM_LEN)
+            .map(char::from)
+            .collect();
+        format!("{}{}", KEY_PREFIX, random)
+    }
+
+    fn hash_key(key: &str) -> String {
+        format!("{:x}", Sha256::digest(key.as_bytes()))
+    }
+
+    /// Create a key with the given scopes. The key itself is only returned here, the
+    /// database keeps a hash of it.
+    #[instrument(skip(self), fields(key_name = %key_name))]
+    pub async fn issue(&self, key_name: &str, scopes: &[Scope]) -> AppResult<(ApiKey, String)> {
+        debug!("Issuing api key");
+        let name = Self::validate_name(key_name)?;
+        let scopes = Self::validate_scopes(scopes)?;
+
+        if ApiKeyQueries::name_exists(&self.db, &name)
+            .await
+            .context("Failed to check for duplicate key_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "key_name '{}' already exists",
+                name
+            )));
+        }
+
+        let key = Self::generate_key();
+        let row = ApiKeyQueries::create(
+            &self.db,
+            &name,
+            &key[..DISPLAY_PREFIX_LEN],
+            &Self::hash_key(&key),
+            &scopes,
+        )
+        .await
+        .with_context(|| format!("Failed to create api key '{}'", name))?;
+
+        debug!("Successfully issued api key: {}", row.key_name);
+        Ok((ApiKey::from(row), key))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_all(&self) -> AppResult<Vec<ApiKey>> {
+        debug!("Fetching all api keys");
+        let rows = ApiKeyQueries::get_all(&self.db)
+            .await
+            .context("Failed to fetch api keys")?;
+        Ok(rows.into_iter().map(ApiKey::from).collect())
+    }
+
+    #[instrument(skip(self), fields(api_key_id = %api_key_id))]
+    pub async fn revoke(&self, api_key_id: Uuid) -> AppResult<ApiKey> {
+        debug!("Revoking api key");
+        let row = ApiKeyQueries::revoke(&self.db, api_key_id)
+            .await
+            .with_context(|| format!("Failed to revoke api key {}", api_key_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Api key with ID {} not found", api_key_id))
+            })?;
+
+        debug!("Successfully revoked api key: {}", row.key_name);
+        Ok(ApiKey::from(row))
+    }
+
+    /// The active key matching a bearer token
+    #[instrument(skip_all)]
+    pub async fn authenticate(&self, key: &str) -> AppResult<ApiKey> {
+        let row = ApiKeyQueries::get_active_by_hash(&self.db, &Self::hash_key(key))
+            .await
+            .context("Failed to look up api key")?
+            .ok_or_else(|| AppError::Unauthorized("invalid or revoked api key".to_string()))?;
+
+        // a failed usage update shouldn't turn the request away
+        if let Err(e) = ApiKeyQueries::touch(&self.db, row.api_key_id).await {
+            warn!("Failed to record api key usage: {}", e);
+        }
+
+        Ok(ApiKey::from(row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_issue_authenticate_and_revoke(pool: PgPool) -> sqlx::Result<()> {
+        let to_sqlx = |e: AppError| sqlx::Error::Protocol(e.to_string());
+        let service = ApiKeyService::new(pool.clone());
+
+        let (issued, key) = service
+            .issue(" Line 1 HMI ", &[Scope::Read])
+            .await
+            .map_err(to_sqlx)?;
+        assert_eq!(issued.key_name, "Line 1 HMI");
+        assert_eq!(issued.scopes, vec![Scope::Read]);
+        assert!(key.starts_with(&issued.key_prefix));
+
+        // only the hash is stored
+        let stored: String = sqlx::query_scalar!(
+            "SELECT key_hash FROM app.api_key WHERE api_key_id = $1",
+            issued.api_key_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_ne!(stored, key);
+
+        let found = service.authenticate(&key).await.map_err(to_sqlx)?;
+        assert_eq!(found.api_key_id, issued.api_key_id);
+        assert!(matches!(
+            service.authenticate("gmes_not_a_key").await,
+            Err(AppError::Unauthorized(_))
+        ));
+
+        assert!(matches!(
+            service.issue("line 1 hmi", &[Scope::Write]).await,
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            service.issue("Empty", &[]).await,
+            Err(AppError::Validation(_))
+        ));
+
+        let revoked = service.revoke(issued.api_key_id).await.map_err(to_sqlx)?;
+        assert!(revoked.revoked_at.is_some());
+        assert!(matches!(
+            service.authenticate(&key).await,
+            Err(AppError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            service.revoke(Uuid::new_v4()).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use crate::database::audit::{AuditLogRow, AuditQueries};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::app::{AuditEntry, AuditFilter};
+use sqlx::PgPool;
+use tracing::{debug, instrument};
+
+/// Most entries one search returns
+pub const MAX_LIMIT: i64 = 1000;
+
+impl From<AuditLogRow> for AuditEntry {
+    fn from(row: AuditLogRow) -> Self {
+        Self {
+            audit_id: row.audit_id,
+            occurred_at: row.occurred_at,
+            actor: row.actor,
+            action: row.action,
+            entity_type: row.entity_type,
+            entity_id: row.entity_id,
+            before: row.before,
+            after: row.after,
+            transaction_id: row.transaction_id,
+        }
+    }
+}
+
+/// Reads the audit log, the entries themselves are written by triggers in the
+/// transactions the other services open with `AuditQueries::begin`
+#[derive(Debug, Clone)]
+pub struct AuditService {
+    db: PgPool,
+}
+
+impl AuditService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    #[instrument(skip(self))]
+    pub async fn search(&self, filter: &AuditFilter) -> AppResult<Vec<AuditEntry>> {
+        if !(1..=MAX_LIMIT).contains(&filter.limit) {
+            return Err(AppError::Validation(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+        if let (Some(from), Some(to)) = (filter.from, filter.to)
+            && from >= to
+        {
+            return Err(AppError::Validation("from must be before to".to_string()));
+        }
+
+        let rows = AuditQueries::get_filtered(&self.db, filter)
+            .await
+            .context("Failed to search the audit log")?;
+        debug!("Found {} audit log entries", rows.len());
+        Ok(rows.into_iter().map(AuditEntry::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::mode_group_service::ModeGroupService;
+    use crate::services::state_group_service::StateGroupService;
+    use crate::services::state_service::StateService;
+
+    #[sqlx::test]
+    async fn test_changes_are_logged_with_their_actor(pool: PgPool) -> sqlx::Result<()> {
+        let to_sqlx = |e: AppError| sqlx::Error::Protocol(e.to_string());
+        let audit = AuditService::new(pool.clone());
+        let mode_groups = ModeGroupService::new(pool.clone()).acting_as(Some("jdoe".to_string()));
+
+        let group = mode_groups
+            .create("Packaging", "Packaging line modes")
+            .await
+            .map_err(to_sqlx)?;
+        mode_groups
+            .update_name(group.mode_group_id, "Packing")
+            .await
+            .map_err(to_sqlx)?;
+        // a rejected change leaves nothing behind
+        assert!(
+            mode_groups
+                .update_name(group.mode_group_id, "")
+                .await
+                .is_err()
+        );
+
+        let filter = AuditFilter {
+            entity_type: Some("mode_group".to_string()),
+            entity_id: Some(group.mode_group_id),
+            limit: 100,
+            ..Default::default()
+        };
+        let entries = audit.search(&filter).await.map_err(to_sqlx)?;
+        assert_eq!(entries.len(), 2);
+        let renamed = &entries[0];
+        assert_eq!(renamed.action, "updated");
+        assert_eq!(renamed.actor.as_deref(), Some("jdoe"));
+        assert_eq!(
+            renamed.before.as_ref().unwrap()["mode_group_name"],
+            "Packaging"
+        );
+        assert_eq!(
+            renamed.after.as_ref().unwrap()["mode_group_name"],
+            "Packing"
+        );
+        assert_eq!(entries[1].action, "created");
+        assert!(entries[1].before.is_none());
+
+        // services without an actor, like the grpc ones, log a null actor
+        let state_group = StateGroupService::new(pool.clone())
+            .create("Filler states", "States of the fillers")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let states =
+            StateService::new(pool.clone()).acting_as(Some("api-key:Line 1 HMI".to_string()));
+        let state = states
+            .create(state_group.state_group_id, 10, "Running", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        states
+            .delete(state.state_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let deleted = audit
+            .search(&AuditFilter {
+                actor: Some("api-key:Line 1 HMI".to_string()),
+                limit: 100,
+                ..Default::default()
+            })
+            .await
+            .map_err(to_sqlx)?;
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(deleted[0].action, "deleted");
+        assert_eq!(deleted[0].entity_id, state.state_id);
+        // soft deleted, the row is still there with deleted_at set
+        assert!(deleted[0].after.as_ref().unwrap()["deleted_at"].is_string());
+        assert_eq!(deleted[0].before.as_ref().unwrap()["state_code"], 10);
+
+        let created_group = audit
+            .search(&AuditFilter {
+                entity_id: Some(state_group.state_group_id),
+                limit: 100,
+                ..Default::default()
+            })
+            .await
+            .map_err(to_sqlx)?;
+        assert_eq!(created_group.len(), 1);
+        assert!(created_group[0].actor.is_none());
+
+        assert!(matches!(
+            audit
+                .search(&AuditFilter {
+                    limit: 0,
+                    ..Default::default()
+                })
+                .await,
+            Err(AppError::Validation(_))
+        ));
+
+        Ok(())
+    }
+}
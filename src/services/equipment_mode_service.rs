@@ -0,0 +1,336 @@
+use crate::database::equipment::EquipmentQueries;
+use crate::database::equipment_modes::{
+    CurrentEquipmentModeRow, EquipmentModeHistoryRow, EquipmentModeQueries, ModeChange,
+};
+use crate::database::modes::ModeRowQueries;
+use crate::error::{AppError, AppResult, DatabaseContext};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+const MAX_SET_BY_LEN: usize = 255;
+
+/// Mode description the seeded mode group uses for scheduled production time
+pub const PRODUCTION_MODE: &str = "production";
+
+#[derive(Debug, Clone)]
+pub struct EquipmentModeChange {
+    pub history_id: Uuid,
+    pub equipment_id: Uuid,
+    pub mode_id: Uuid,
+    pub mode_description: String,
+    pub mode_group_id: Uuid,
+    pub set_at: OffsetDateTime,
+    pub ended_at: Option<OffsetDateTime>,
+    pub set_by: Option<String>,
+}
+
+impl From<EquipmentModeHistoryRow> for EquipmentModeChange {
+    fn from(row: EquipmentModeHistoryRow) -> Self {
+        Self {
+            history_id: row.history_id,
+            equipment_id: row.equipment_id,
+            mode_id: row.mode_id,
+            mode_description: row.mode_description,
+            mode_group_id: row.mode_group_id,
+            set_at: row.set_at,
+            ended_at: row.ended_at,
+            set_by: row.set_by,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentCurrentMode {
+    pub equipment_id: Uuid,
+    pub mode_id: Uuid,
+    pub mode_description: String,
+    pub mode_group_id: Uuid,
+    pub set_at: OffsetDateTime,
+    pub set_by: Option<String>,
+}
+
+impl From<CurrentEquipmentModeRow> for EquipmentCurrentMode {
+    fn from(row: CurrentEquipmentModeRow) -> Self {
+        Self {
+            equipment_id: row.equipment_id,
+            mode_id: row.mode_id,
+            mode_description: row.mode_description,
+            mode_group_id: row.mode_group_id,
+            set_at: row.set_at,
+            set_by: row.set_by,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentModeService {
+    db: PgPool,
+}
+
+impl EquipmentModeService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    async fn validate_equipment_exists(&self, equipment_id: Uuid) -> AppResult<()> {
+        if !EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check if equipment exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_current(&self, equipment_id: Uuid) -> AppResult<Option<EquipmentCurrentMode>> {
+        debug!("Fetching current mode");
+        self.validate_equipment_exists(equipment_id).await?;
+
+        let row = EquipmentModeQueries::get_current(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch current equipment mode")?;
+        Ok(row.map(EquipmentCurrentMode::from))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_history(
+        &self,
+        equipment_id: Uuid,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> AppResult<Vec<EquipmentModeChange>> {
+        debug!("Fetching mode history");
+        if let (Some(from), Some(to)) = (from, to)
+            && from >= to
+        {
+            return Err(AppError::Validation(
+                "invalid time range: 'from' must be before 'to'".to_string(),
+            ));
+        }
+        self.validate_equipment_exists(equipment_id).await?;
+
+        let rows = EquipmentModeQueries::get_history(&self.db, equipment_id, from, to)
+            .await
+            .context("Failed to fetch equipment mode history")?;
+        let history: Vec<EquipmentModeChange> =
+            rows.into_iter().map(EquipmentModeChange::from).collect();
+        debug!("Found {} mode history entries", history.len());
+        Ok(history)
+    }
+
+    /// Set the equipment's mode; the mode must belong to a mode group mapped to the equipment.
+    /// Setting the mode the equipment is already in is a no-op.
+    #[instrument(skip(self), fields(equipment_id = %equipment_id, mode_id = %mode_id))]
+    pub async fn set_mode(
+        &self,
+        equipment_id: Uuid,
+        mode_id: Uuid,
+        set_by: Option<&str>,
+    ) -> AppResult<EquipmentCurrentMode> {
+        debug!("Setting equipment mode");
+        let set_by = set_by.map(str::trim).filter(|s| !s.is_empty());
+        if set_by.is_some_and(|s| s.len() > MAX_SET_BY_LEN) {
+            return Err(AppError::Validation(format!(
+                "set_by exceeds max length of {} characters",
+                MAX_SET_BY_LEN
+            )));
+        }
+
+        self.validate_equipment_exists(equipment_id).await?;
+
+        if !ModeRowQueries::exists(&self.db, mode_id)
+            .await
+            .context("Failed to check if mode exists")?
+        {
+            return Err(AppError::Validation(format!(
+                "mode_id '{}' does not exist",
+                mode_id
+            )));
+        }
+
+        if !EquipmentModeQueries::is_mode_assigned(&self.db, equipment_id, mode_id)
+            .await
+            .context("Failed to check equipment mode group mapping")?
+        {
+            return Err(AppError::Validation(format!(
+                "mode '{}' is not in a mode group assigned to equipment {}",
+                mode_id, equipment_id
+            )));
+        }
+
+        let change = EquipmentModeQueries::set_mode(&self.db, equipment_id, mode_id, set_by)
+            .await
+            .context("Failed to set equipment mode")?;
+
+        match change {
+            ModeChange::Set(_) => debug!("Successfully set equipment mode"),
+            ModeChange::Unchanged => debug!("Equipment is already in the requested mode"),
+            ModeChange::EquipmentMissing => {
+                return Err(AppError::NotFound(format!(
+                    "Equipment with ID {} not found",
+                    equipment_id
+                )));
+            }
+        }
+        self.get_current(equipment_id).await?.ok_or_else(|| {
+            AppError::database(
+                format!("Failed to read back mode for equipment {}", equipment_id),
+                sqlx::Error::RowNotFound,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::equipment_service::EquipmentService;
+
+    async fn seeded_type_id(pool: &PgPool, type_name: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = $1",
+            type_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn seeded_mode_id(pool: &PgPool, mode_description: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT mode_id FROM core.mode WHERE mode_description = $1",
+            mode_description
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn create_equipment(pool: &PgPool, mapped: bool) -> sqlx::Result<Uuid> {
+        let enterprise = seeded_type_id(pool, "enterprise").await?;
+        let equipment = EquipmentService::new(pool.clone())
+            .create("Acme", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        if mapped {
+            sqlx::query!(
+                r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+                   SELECT $1, mode_group_id FROM core.mode_group
+                   WHERE mode_group_name = 'Default MES Mode Group'"#,
+                equipment.equipment_id
+            )
+            .execute(pool)
+            .await?;
+        }
+        Ok(equipment.equipment_id)
+    }
+
+    #[sqlx::test]
+    async fn test_service_set_mode(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool, true).await?;
+        let production = seeded_mode_id(&pool, "production").await?;
+        let idle = seeded_mode_id(&pool, "idle").await?;
+        let service = EquipmentModeService::new(pool);
+
+        let current = service
+            .get_current(equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(current.is_none());
+
+        let current = service
+            .set_mode(equipment_id, production, Some("  operator "))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(current.mode_description, "production");
+        assert_eq!(current.set_by.as_deref(), Some("operator"));
+
+        // re-setting the same mode does not open a new interval
+        let same = service
+            .set_mode(equipment_id, production, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(same.set_at, current.set_at);
+
+        service
+            .set_mode(equipment_id, idle, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let history = service
+            .get_history(equipment_id, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].mode_description, "idle");
+
+        let current = service
+            .get_current(equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+            .expect("mode should be set");
+        assert_eq!(current.mode_id, idle);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_set_mode_concurrent_duplicates(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool, true).await?;
+        let production = seeded_mode_id(&pool, "production").await?;
+        let service = EquipmentModeService::new(pool);
+
+        let results = tokio::join!(
+            service.set_mode(equipment_id, production, None),
+            service.set_mode(equipment_id, production, None),
+            service.set_mode(equipment_id, production, None),
+        );
+        for result in [results.0, results.1, results.2] {
+            result.map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let history = service
+            .get_history(equipment_id, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(history.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_set_mode_validation(pool: PgPool) -> sqlx::Result<()> {
+        let unmapped = create_equipment(&pool, false).await?;
+        let production = seeded_mode_id(&pool, "production").await?;
+        let service = EquipmentModeService::new(pool);
+
+        let result = service.set_mode(unmapped, production, None).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("not in a mode group")
+        );
+
+        let result = service.set_mode(unmapped, Uuid::new_v4(), None).await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+
+        let result = service.set_mode(Uuid::new_v4(), production, None).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        let now = OffsetDateTime::now_utc();
+        let result = service.get_history(unmapped, Some(now), Some(now)).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("invalid time range")
+        );
+
+        Ok(())
+    }
+}
@@ -1 +1,1127 @@
+use crate::database::audit::AuditQueries;
+use crate::database::equipment::{Equipment as EquipmentRow, EquipmentQueries};
+use crate::database::equipment_type_rules::EquipmentTypeRuleQueries;
+use crate::database::equipment_types::EquipmentTypeQueries;
+use crate::error::{AppError, AppResult, DatabaseContext, is_unique_violation};
+use crate::models::core as model;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
 
+const MAX_NAME_LEN: usize = 255;
+
+#[derive(Debug, Clone)]
+pub struct Equipment {
+    pub equipment_id: Uuid,
+    pub equipment_name: String,
+    pub equipment_type_id: Uuid,
+    pub equipment_parent_id: Option<Uuid>,
+    pub equipment_enabled: bool,
+    pub equipment_metadata: Option<Value>,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<EquipmentRow> for Equipment {
+    fn from(row: EquipmentRow) -> Self {
+        Self {
+            equipment_id: row.equipment_id,
+            equipment_name: row.equipment_name,
+            equipment_type_id: row.equipment_type_id,
+            equipment_parent_id: row.equipment_parent_id,
+            equipment_enabled: row.equipment_enabled,
+            equipment_metadata: row.equipment_metadata,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+fn to_utc(value: Option<OffsetDateTime>) -> Option<DateTime<Utc>> {
+    value.and_then(|t| DateTime::from_timestamp(t.unix_timestamp(), t.nanosecond()))
+}
+
+fn to_metadata(value: Option<Value>) -> model::EquipmentMetadata {
+    let extra = match value {
+        Some(Value::Object(map)) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    model::EquipmentMetadata { extra }
+}
+
+impl From<EquipmentRow> for model::Equipment {
+    fn from(row: EquipmentRow) -> Self {
+        Self {
+            equipment_id: row.equipment_id,
+            equipment_name: row.equipment_name,
+            equipment_type_id: row.equipment_type_id,
+            equipment_parent_id: row.equipment_parent_id,
+            equipment_enabled: row.equipment_enabled,
+            equipment_metadata: row.equipment_metadata.map(|m| to_metadata(Some(m))),
+            created_at: to_utc(row.created_at),
+            updated_at: to_utc(row.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentService {
+    db: PgPool,
+    actor: Option<String>,
+}
+
+impl EquipmentService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
+    }
+
+    /// Validates and sanitizes equipment name input
+    fn validate_name(equipment_name: &str) -> AppResult<String> {
+        let trimmed = equipment_name.trim().to_string();
+
+        if trimmed.is_empty() {
+            return Err(AppError::Validation(
+                "equipment_name cannot be empty".to_string(),
+            ));
+        }
+
+        if trimmed.len() > MAX_NAME_LEN {
+            return Err(AppError::Validation(format!(
+                "equipment_name exceeds max length of {} characters",
+                MAX_NAME_LEN
+            )));
+        }
+
+        Ok(trimmed)
+    }
+
+    /// Equipment metadata is stored as a json object, anything else is rejected
+    fn validate_metadata(metadata: &Value) -> AppResult<()> {
+        if !metadata.is_object() {
+            return Err(AppError::Validation(
+                "equipment_metadata must be a json object".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_by_id(&self, equipment_id: Uuid) -> AppResult<Equipment> {
+        debug!("Fetching equipment by ID");
+        let row = EquipmentQueries::get_by_id(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment by ID")?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment with ID {} not found", equipment_id))
+            })?;
+
+        debug!("Found equipment: {}", row.equipment_name);
+        Ok(Equipment::from(row))
+    }
+
+    #[instrument(skip(self, equipment_metadata), fields(equipment_name = %equipment_name))]
+    pub async fn create(
+        &self,
+        equipment_name: &str,
+        equipment_type_id: Uuid,
+        equipment_parent_id: Option<Uuid>,
+        equipment_enabled: Option<bool>,
+        equipment_metadata: Option<&Value>,
+    ) -> AppResult<Equipment> {
+        debug!("Creating new equipment");
+        let name = Self::validate_name(equipment_name)?;
+
+        if let Some(metadata) = equipment_metadata {
+            Self::validate_metadata(metadata)?;
+        }
+
+        self.validate_hierarchy(equipment_type_id, equipment_parent_id)
+            .await?;
+
+        if EquipmentQueries::name_exists_in_parent(
+            &self.db,
+            &name,
+            equipment_type_id,
+            equipment_parent_id,
+            None,
+        )
+        .await
+        .context("Failed to check for duplicate equipment_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "equipment_name '{}' already exists at this level of the hierarchy",
+                name
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        let row = EquipmentQueries::create(
+            &mut tx,
+            &name,
+            equipment_type_id,
+            equipment_parent_id,
+            equipment_enabled,
+            equipment_metadata,
+        )
+        .await
+        .with_context(|| format!("Failed to create equipment '{}'", name))?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully created equipment: {}", row.equipment_name);
+        Ok(Equipment::from(row))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id, equipment_name = %equipment_name))]
+    pub async fn rename(&self, equipment_id: Uuid, equipment_name: &str) -> AppResult<Equipment> {
+        debug!("Renaming equipment");
+        let name = Self::validate_name(equipment_name)?;
+        let current = self.get_by_id(equipment_id).await?;
+
+        if EquipmentQueries::name_exists_in_parent(
+            &self.db,
+            &name,
+            current.equipment_type_id,
+            current.equipment_parent_id,
+            Some(equipment_id),
+        )
+        .await
+        .context("Failed to check for duplicate equipment_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "equipment_name '{}' already exists at this level of the hierarchy",
+                name
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        let row = EquipmentQueries::update_name(&mut tx, equipment_id, &name)
+            .await
+            .with_context(|| format!("Failed to rename equipment {}", equipment_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment with ID {} not found", equipment_id))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully renamed equipment: {}", row.equipment_name);
+        Ok(Equipment::from(row))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn reparent(
+        &self,
+        equipment_id: Uuid,
+        equipment_parent_id: Option<Uuid>,
+    ) -> AppResult<Equipment> {
+        debug!("Moving equipment to parent {:?}", equipment_parent_id);
+        let current = self.get_by_id(equipment_id).await?;
+
+        if let Some(parent_id) = equipment_parent_id {
+            if parent_id == equipment_id {
+                return Err(AppError::Validation(
+                    "equipment cannot be its own parent".to_string(),
+                ));
+            }
+
+            if EquipmentQueries::is_in_subtree(&self.db, equipment_id, parent_id)
+                .await
+                .context("Failed to check for hierarchy cycles")?
+            {
+                return Err(AppError::Validation(
+                    "invalid hierarchy: equipment cannot be moved under one of its own descendants"
+                        .to_string(),
+                ));
+            }
+        }
+
+        self.validate_hierarchy(current.equipment_type_id, equipment_parent_id)
+            .await?;
+
+        if EquipmentQueries::name_exists_in_parent(
+            &self.db,
+            &current.equipment_name,
+            current.equipment_type_id,
+            equipment_parent_id,
+            Some(equipment_id),
+        )
+        .await
+        .context("Failed to check for duplicate equipment_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "equipment_name '{}' already exists under the target parent",
+                current.equipment_name
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        let row = EquipmentQueries::update_parent(&mut tx, equipment_id, equipment_parent_id)
+            .await
+            .with_context(|| format!("Failed to move equipment {}", equipment_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment with ID {} not found", equipment_id))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully moved equipment: {}", row.equipment_name);
+        Ok(Equipment::from(row))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id, enabled = %enabled))]
+    pub async fn set_enabled(&self, equipment_id: Uuid, enabled: bool) -> AppResult<Equipment> {
+        debug!("Setting equipment enabled flag");
+        let mut tx = self.begin().await?;
+        let row = EquipmentQueries::set_enabled(&mut tx, equipment_id, enabled)
+            .await
+            .with_context(|| format!("Failed to set enabled for equipment {}", equipment_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment with ID {} not found", equipment_id))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!(
+            "Equipment {} enabled: {}",
+            row.equipment_name, row.equipment_enabled
+        );
+        Ok(Equipment::from(row))
+    }
+
+    #[instrument(skip(self, metadata), fields(equipment_id = %equipment_id))]
+    pub async fn update_metadata(
+        &self,
+        equipment_id: Uuid,
+        metadata: &Value,
+    ) -> AppResult<Equipment> {
+        debug!("Updating equipment metadata");
+        Self::validate_metadata(metadata)?;
+
+        let mut tx = self.begin().await?;
+        let row = EquipmentQueries::update_metadata(&mut tx, equipment_id, metadata)
+            .await
+            .with_context(|| format!("Failed to update metadata for equipment {}", equipment_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment with ID {} not found", equipment_id))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully updated equipment metadata");
+        Ok(Equipment::from(row))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn delete(&self, equipment_id: Uuid) -> AppResult<()> {
+        debug!("Deleting equipment");
+
+        if EquipmentQueries::has_children(&self.db, equipment_id)
+            .await
+            .context("Failed to check for child equipment")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Equipment {} is in use by child equipment and cannot be deleted",
+                equipment_id
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        let deleted = EquipmentQueries::delete(&mut tx, equipment_id)
+            .await
+            .with_context(|| format!("Failed to delete equipment {}", equipment_id))?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+
+        debug!("Successfully deleted equipment");
+        Ok(())
+    }
+
+    /// Brings back deleted equipment with its mode and state group mappings. Its parent
+    /// and type have to be restored first.
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn restore(&self, equipment_id: Uuid) -> AppResult<Equipment> {
+        debug!("Restoring equipment");
+
+        let mut tx = self.begin().await?;
+        let row = match EquipmentQueries::restore(&mut tx, equipment_id).await {
+            Err(e) if is_unique_violation(&e) => {
+                return Err(AppError::Conflict(format!(
+                    "Equipment {} can't be restored, equipment with its name already exists at this level of the hierarchy",
+                    equipment_id
+                )));
+            }
+            result => result
+                .with_context(|| format!("Failed to restore equipment {}", equipment_id))?
+                .ok_or_else(|| {
+                    AppError::NotFound(format!(
+                        "Deleted equipment with ID {} not found",
+                        equipment_id
+                    ))
+                })?,
+        };
+
+        if let Some(parent_id) = row.equipment_parent_id
+            && !EquipmentQueries::exists(&self.db, parent_id)
+                .await
+                .context("Failed to check if parent equipment exists")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Equipment {} can't be restored while its parent {} is deleted",
+                equipment_id, parent_id
+            )));
+        }
+        if !EquipmentTypeQueries::exists(&self.db, row.equipment_type_id)
+            .await
+            .context("Failed to check if equipment type exists")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Equipment {} can't be restored while its equipment type {} is deleted",
+                equipment_id, row.equipment_type_id
+            )));
+        }
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully restored equipment: {}", row.equipment_name);
+        Ok(Equipment::from(row))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn exists(&self, equipment_id: Uuid) -> AppResult<bool> {
+        let exists = EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check if equipment exists")?;
+        debug!("Equipment {} exists: {}", equipment_id, exists);
+        Ok(exists)
+    }
+
+    /// Get equipment with pagination
+    #[instrument(skip(self))]
+    pub async fn get_paginated(&self, offset: i64, limit: i64) -> AppResult<(Vec<Equipment>, i64)> {
+        debug!(
+            "Fetching equipment with pagination: offset={}, limit={}",
+            offset, limit
+        );
+
+        if offset < 0 {
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
+        }
+
+        if limit <= 0 || limit > 1000 {
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
+        }
+
+        let total_count = self.count().await?;
+
+        let rows = sqlx::query_as!(
+            EquipmentRow,
+            r#"SELECT equipment_id, equipment_name, equipment_type_id,
+                      equipment_parent_id, equipment_enabled,
+                      equipment_metadata,
+                      created_at, updated_at
+               FROM core.equipment
+               WHERE deleted_at IS NULL
+               ORDER BY equipment_name, equipment_id
+               LIMIT $1 OFFSET $2"#,
+            limit,
+            offset
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch paginated equipment")?;
+
+        let equipment: Vec<Equipment> = rows.into_iter().map(Equipment::from).collect();
+        debug!(
+            "Found {} equipment (total: {})",
+            equipment.len(),
+            total_count
+        );
+
+        Ok((equipment, total_count))
+    }
+
+    /// Get count of all equipment
+    #[instrument(skip(self))]
+    pub async fn count(&self) -> AppResult<i64> {
+        let count =
+            sqlx::query_scalar!("SELECT COUNT(*) FROM core.equipment WHERE deleted_at IS NULL")
+                .fetch_one(&self.db)
+                .await
+                .context("Failed to count equipment")?
+                .unwrap_or(0);
+
+        debug!("Total equipment count: {}", count);
+        Ok(count)
+    }
+
+    /// Builds the nested subtree below the equipment, with the equipment type and the
+    /// mode/state groups mapped to every node
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_tree(&self, equipment_id: Uuid) -> AppResult<model::EquipmentWithRelations> {
+        debug!("Fetching equipment tree");
+
+        let rows = EquipmentQueries::get_subtree(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment subtree")?;
+
+        let ids: Vec<Uuid> = rows.iter().map(|r| r.equipment_id).collect();
+
+        let types: HashMap<Uuid, model::EquipmentType> = EquipmentTypeQueries::get_all(&self.db)
+            .await
+            .context("Failed to fetch equipment types")?
+            .into_iter()
+            .map(|t| {
+                (
+                    t.type_id,
+                    model::EquipmentType {
+                        type_id: t.type_id,
+                        type_name: t.type_name,
+                        created_at: to_utc(t.created_at),
+                        updated_at: to_utc(t.updated_at),
+                    },
+                )
+            })
+            .collect();
+
+        let mut mode_groups = self.mode_groups_for(&ids).await?;
+        let mut state_groups = self.state_groups_for(&ids).await?;
+
+        let mut root = None;
+        let mut children: HashMap<Uuid, Vec<EquipmentRow>> = HashMap::new();
+        for row in rows {
+            if row.equipment_id == equipment_id {
+                root = Some(row);
+            } else if let Some(parent_id) = row.equipment_parent_id {
+                children.entry(parent_id).or_default().push(row);
+            }
+        }
+
+        let root = root.ok_or_else(|| {
+            AppError::NotFound(format!("Equipment with ID {} not found", equipment_id))
+        })?;
+
+        let tree = Self::build_node(
+            root,
+            &mut children,
+            &types,
+            &mut mode_groups,
+            &mut state_groups,
+        )?;
+
+        debug!("Built equipment tree for {}", tree.equipment_name);
+        Ok(tree)
+    }
+
+    fn build_node(
+        row: EquipmentRow,
+        children: &mut HashMap<Uuid, Vec<EquipmentRow>>,
+        types: &HashMap<Uuid, model::EquipmentType>,
+        mode_groups: &mut HashMap<Uuid, Vec<model::ModeGroupWithModes>>,
+        state_groups: &mut HashMap<Uuid, Vec<model::StateGroupWithStates>>,
+    ) -> AppResult<model::EquipmentWithRelations> {
+        let equipment_type = types.get(&row.equipment_type_id).cloned().ok_or_else(|| {
+            AppError::Validation(format!(
+                "equipment_type_id '{}' does not exist",
+                row.equipment_type_id
+            ))
+        })?;
+
+        let child_equipment = children
+            .remove(&row.equipment_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::build_node(child, children, types, mode_groups, state_groups))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(model::EquipmentWithRelations {
+            equipment_id: row.equipment_id,
+            equipment_name: row.equipment_name,
+            equipment_type_id: row.equipment_type_id,
+            equipment_parent_id: row.equipment_parent_id,
+            equipment_enabled: row.equipment_enabled,
+            equipment_metadata: to_metadata(row.equipment_metadata),
+            created_at: to_utc(row.created_at),
+            updated_at: to_utc(row.updated_at),
+            equipment_type,
+            child_equipment,
+            mode_groups: mode_groups.remove(&row.equipment_id).unwrap_or_default(),
+            state_groups: state_groups.remove(&row.equipment_id).unwrap_or_default(),
+        })
+    }
+
+    async fn mode_groups_for(
+        &self,
+        equipment_ids: &[Uuid],
+    ) -> AppResult<HashMap<Uuid, Vec<model::ModeGroupWithModes>>> {
+        let rows = sqlx::query!(
+            r#"SELECT m.equipment_id, g.mode_group_id, g.mode_group_name, g.mode_group_description,
+                      mo.mode_id as "mode_id?", mo.mode_description as "mode_description?"
+               FROM core.equipment_mode_group_mapping m
+               JOIN core.mode_group g ON g.mode_group_id = m.mode_group_id
+               LEFT JOIN core.mode mo
+                 ON mo.mode_group_id = g.mode_group_id AND mo.deleted_at IS NULL
+               WHERE m.equipment_id = ANY($1) AND g.deleted_at IS NULL
+               ORDER BY m.equipment_id, g.mode_group_name, g.mode_group_id, mo.mode_description"#,
+            equipment_ids
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch mode groups for equipment")?;
+
+        let mut result: HashMap<Uuid, Vec<model::ModeGroupWithModes>> = HashMap::new();
+        for row in rows {
+            let groups = result.entry(row.equipment_id).or_default();
+            if groups.last().map(|g| g.mode_group_id) != Some(row.mode_group_id) {
+                groups.push(model::ModeGroupWithModes {
+                    mode_group_id: row.mode_group_id,
+                    mode_group_name: row.mode_group_name,
+                    mode_group_description: row.mode_group_description,
+                    modes: Vec::new(),
+                });
+            }
+            if let (Some(group), Some(mode_id), Some(mode_description)) =
+                (groups.last_mut(), row.mode_id, row.mode_description)
+            {
+                group.modes.push(model::Modes {
+                    mode_id,
+                    mode_group_id: row.mode_group_id,
+                    mode_description,
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn state_groups_for(
+        &self,
+        equipment_ids: &[Uuid],
+    ) -> AppResult<HashMap<Uuid, Vec<model::StateGroupWithStates>>> {
+        let rows = sqlx::query!(
+            r#"SELECT m.equipment_id, g.state_group_id, g.state_group_name, g.state_group_description,
+                      s.state_id as "state_id?", s.state_code as "state_code?",
+                      s.state_description as "state_description?"
+               FROM core.equipment_state_group_mapping m
+               JOIN core.state_group g ON g.state_group_id = m.state_group_id
+               LEFT JOIN core.state s
+                 ON s.state_group_id = g.state_group_id AND s.deleted_at IS NULL
+               WHERE m.equipment_id = ANY($1) AND g.deleted_at IS NULL
+               ORDER BY m.equipment_id, g.state_group_name, g.state_group_id, s.state_code"#,
+            equipment_ids
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch state groups for equipment")?;
+
+        let mut result: HashMap<Uuid, Vec<model::StateGroupWithStates>> = HashMap::new();
+        for row in rows {
+            let groups = result.entry(row.equipment_id).or_default();
+            if groups.last().map(|g| g.state_group_id) != Some(row.state_group_id) {
+                groups.push(model::StateGroupWithStates {
+                    state_group_id: row.state_group_id,
+                    state_group_name: row.state_group_name,
+                    state_group_description: row.state_group_description,
+                    states: Vec::new(),
+                });
+            }
+            if let (Some(group), Some(state_id), Some(state_code)) =
+                (groups.last_mut(), row.state_id, row.state_code)
+            {
+                group.states.push(model::State {
+                    state_id,
+                    state_group_id: row.state_group_id,
+                    state_code,
+                    state_description: row.state_description,
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the ancestor chain from the root (enterprise) down to the equipment.
+    /// depth is 0 for a root and counts the levels above the equipment otherwise
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_path(&self, equipment_id: Uuid) -> AppResult<model::EquipmentPath> {
+        debug!("Fetching equipment path");
+
+        let rows = EquipmentQueries::get_ancestors(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment ancestors")?;
+
+        if rows.is_empty() {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+
+        let depth = rows.len() as i32 - 1;
+        let path: Vec<model::Equipment> = rows.into_iter().map(model::Equipment::from).collect();
+
+        debug!("Equipment {} is at depth {}", equipment_id, depth);
+        Ok(model::EquipmentPath {
+            equipment_id,
+            path,
+            depth,
+        })
+    }
+
+    /// Validates the type/parent pairing against core.equipment_type_rule,
+    /// a None parent means the equipment sits at the root of the hierarchy
+    async fn validate_hierarchy(
+        &self,
+        equipment_type_id: Uuid,
+        equipment_parent_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        let child_type = EquipmentTypeQueries::get_by_id(&self.db, equipment_type_id)
+            .await
+            .context("Failed to fetch equipment type")?
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "equipment_type_id '{}' does not exist",
+                    equipment_type_id
+                ))
+            })?;
+
+        let parent_type = match equipment_parent_id {
+            Some(parent_id) => {
+                let parent = EquipmentQueries::get_by_id(&self.db, parent_id)
+                    .await
+                    .context("Failed to fetch parent equipment")?
+                    .ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "equipment_parent_id '{}' does not exist",
+                            parent_id
+                        ))
+                    })?;
+
+                EquipmentTypeQueries::get_by_id(&self.db, parent.equipment_type_id)
+                    .await
+                    .context("Failed to fetch parent equipment type")?
+            }
+            None => None,
+        };
+
+        let allowed = EquipmentTypeRuleQueries::is_allowed(
+            &self.db,
+            parent_type.as_ref().map(|t| t.type_id),
+            equipment_type_id,
+        )
+        .await
+        .context("Failed to check equipment type rules")?;
+
+        if !allowed {
+            return Err(match parent_type {
+                Some(parent_type) => AppError::Validation(format!(
+                    "invalid hierarchy: equipment type '{}' cannot be placed under '{}'",
+                    child_type.type_name, parent_type.type_name
+                )),
+                None => AppError::Validation(format!(
+                    "invalid hierarchy: equipment type '{}' cannot be at the root of the hierarchy",
+                    child_type.type_name
+                )),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    // the seeded isa-95 types from 002_core.sql
+    async fn seeded_type_id(pool: &PgPool, type_name: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = $1",
+            type_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_service_create_and_get(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let service = EquipmentService::new(pool);
+
+        let created = service
+            .create("  Acme  ", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(created.equipment_name, "Acme");
+        assert!(created.equipment_enabled);
+
+        let found = service
+            .get_by_id(created.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(found.equipment_id, created.equipment_id);
+        assert_eq!(found.equipment_type_id, enterprise);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_validation(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let service = EquipmentService::new(pool);
+
+        let result = service.create("   ", enterprise, None, None, None).await;
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+
+        let result = service
+            .create("Acme", Uuid::new_v4(), None, None, None)
+            .await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+
+        let result = service
+            .create("Acme", enterprise, Some(Uuid::new_v4()), None, None)
+            .await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+
+        let result = service
+            .create("Acme", enterprise, None, None, Some(&json!([1, 2])))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("json object"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_duplicate_handling(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let service = EquipmentService::new(pool);
+
+        service
+            .create("Acme", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service.create("Acme", enterprise, None, None, None).await;
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_rename_and_reparent(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let site = seeded_type_id(&pool, "site").await?;
+        let service = EquipmentService::new(pool);
+
+        let acme = service
+            .create("Acme", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let other = service
+            .create("Other Co", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let plant = service
+            .create("Plant 1", site, Some(acme.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let renamed = service
+            .rename(plant.equipment_id, "Plant A")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(renamed.equipment_name, "Plant A");
+
+        let moved = service
+            .reparent(plant.equipment_id, Some(other.equipment_id))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(moved.equipment_parent_id, Some(other.equipment_id));
+
+        let result = service
+            .reparent(plant.equipment_id, Some(plant.equipment_id))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("own parent"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_enable_and_metadata(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let service = EquipmentService::new(pool);
+
+        let created = service
+            .create("Acme", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let disabled = service
+            .set_enabled(created.equipment_id, false)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(!disabled.equipment_enabled);
+
+        let metadata = json!({"tag_path": "[default]Acme"});
+        let updated = service
+            .update_metadata(created.equipment_id, &metadata)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated.equipment_metadata, Some(metadata));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_delete(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let site = seeded_type_id(&pool, "site").await?;
+        let service = EquipmentService::new(pool);
+
+        let acme = service
+            .create("Acme", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let plant = service
+            .create("Plant 1", site, Some(acme.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        // parents with children are in use
+        let result = service.delete(acme.equipment_id).await;
+        assert!(result.unwrap_err().to_string().contains("in use"));
+
+        service
+            .delete(plant.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        service
+            .delete(acme.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service.delete(acme.equipment_id).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_pagination(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = seeded_type_id(&pool, "enterprise").await?;
+        let service = EquipmentService::new(pool);
+
+        for i in 1..=5 {
+            service
+                .create(&format!("Enterprise {}", i), enterprise, None, None, None)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let (page, total) = service
+            .get_paginated(0, 3)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(page.len(), 3);
+        assert_eq!(total, 5);
+
+        let result = service.get_paginated(-1, 10).await;
+        assert!(result.unwrap_err().to_string().contains("negative"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_tree_and_path(pool: PgPool) -> sqlx::Result<()> {
+        let service = EquipmentService::new(pool.clone());
+        let enterprise_type = seeded_type_id(&pool, "enterprise").await?;
+        let site_type = seeded_type_id(&pool, "site").await?;
+        let area_type = seeded_type_id(&pool, "area").await?;
+
+        let enterprise = service
+            .create("Acme", enterprise_type, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let site = service
+            .create(
+                "Plant 1",
+                site_type,
+                Some(enterprise.equipment_id),
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let area = service
+            .create("Packaging", area_type, Some(site.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+               SELECT $1, mode_group_id FROM core.mode_group
+               WHERE mode_group_name = 'Default MES Mode Group'"#,
+            area.equipment_id
+        )
+        .execute(&pool)
+        .await?;
+
+        let tree = service
+            .get_tree(enterprise.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(tree.equipment_type.type_name, "enterprise");
+        assert_eq!(tree.child_equipment.len(), 1);
+        let site_node = &tree.child_equipment[0];
+        assert_eq!(site_node.equipment_name, "Plant 1");
+        let area_node = &site_node.child_equipment[0];
+        assert_eq!(area_node.equipment_name, "Packaging");
+        assert_eq!(area_node.mode_groups.len(), 1);
+        assert_eq!(area_node.mode_groups[0].modes.len(), 4);
+
+        let path = service
+            .get_path(area.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(path.depth, 2);
+        let names: Vec<&str> = path
+            .path
+            .iter()
+            .map(|e| e.equipment_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Acme", "Plant 1", "Packaging"]);
+
+        let result = service.get_tree(Uuid::new_v4()).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        let result = service.get_path(Uuid::new_v4()).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_hierarchy_rules(pool: PgPool) -> sqlx::Result<()> {
+        let service = EquipmentService::new(pool.clone());
+        let enterprise_type = seeded_type_id(&pool, "enterprise").await?;
+        let site_type = seeded_type_id(&pool, "site").await?;
+        let cell_type = seeded_type_id(&pool, "cell").await?;
+
+        // only enterprise may be a root
+        let result = service.create("Plant 1", site_type, None, None, None).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("cannot be at the root")
+        );
+
+        let acme = service
+            .create("Acme", enterprise_type, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        // a cell cannot sit directly under an enterprise
+        let result = service
+            .create("Cell 1", cell_type, Some(acme.equipment_id), None, None)
+            .await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("'cell' cannot be placed under 'enterprise'")
+        );
+
+        let plant = service
+            .create("Plant 1", site_type, Some(acme.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        // moving a site to the root breaks the rules
+        let result = service.reparent(plant.equipment_id, None).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("invalid hierarchy")
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_reparent_rejects_cycles(pool: PgPool) -> sqlx::Result<()> {
+        let service = EquipmentService::new(pool.clone());
+        let enterprise_type = seeded_type_id(&pool, "enterprise").await?;
+        let site_type = seeded_type_id(&pool, "site").await?;
+
+        // allow sites under sites so only the cycle check can reject the move
+        sqlx::query!(
+            "INSERT INTO core.equipment_type_rule (parent_type_id, child_type_id) VALUES ($1, $1)",
+            site_type
+        )
+        .execute(&pool)
+        .await?;
+
+        let acme = service
+            .create("Acme", enterprise_type, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let outer = service
+            .create("Outer", site_type, Some(acme.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let inner = service
+            .create("Inner", site_type, Some(outer.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service
+            .reparent(outer.equipment_id, Some(inner.equipment_id))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("descendants"));
+
+        // a soft-deleted former child is no longer part of the subtree, the move is refused
+        // because the parent is gone rather than as a cycle
+        service
+            .delete(inner.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let result = service
+            .reparent(outer.equipment_id, Some(inner.equipment_id))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+
+        Ok(())
+    }
+}
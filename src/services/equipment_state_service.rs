@@ -0,0 +1,446 @@
+use crate::database::equipment::EquipmentQueries;
+use crate::database::equipment_states::{
+    CurrentEquipmentStateRow, EquipmentStateHistoryRow, EquipmentStateQueries, RecordedState,
+};
+use crate::database::states::{StateRow, StateRowQueries};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use serde_json::Value;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+const MAX_UPDATED_BY_LEN: usize = 255;
+
+#[derive(Debug, Clone)]
+pub struct EquipmentStateChange {
+    pub history_id: Uuid,
+    pub equipment_id: Uuid,
+    pub state_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    pub state_group_id: Uuid,
+    pub state_value: Option<Value>,
+    pub started_at: OffsetDateTime,
+    pub ended_at: Option<OffsetDateTime>,
+    pub duration_seconds: f64,
+    pub updated_by: Option<String>,
+}
+
+impl From<EquipmentStateHistoryRow> for EquipmentStateChange {
+    fn from(row: EquipmentStateHistoryRow) -> Self {
+        Self {
+            history_id: row.history_id,
+            equipment_id: row.equipment_id,
+            state_id: row.state_id,
+            state_code: row.state_code,
+            state_description: row.state_description,
+            state_group_id: row.state_group_id,
+            state_value: row.state_value,
+            started_at: row.started_at,
+            ended_at: row.ended_at,
+            duration_seconds: row.duration_seconds,
+            updated_by: row.updated_by,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentCurrentState {
+    pub equipment_id: Uuid,
+    pub state_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    pub state_group_id: Uuid,
+    pub state_value: Option<Value>,
+    pub started_at: OffsetDateTime,
+    pub duration_seconds: f64,
+    pub updated_by: Option<String>,
+}
+
+impl From<CurrentEquipmentStateRow> for EquipmentCurrentState {
+    fn from(row: CurrentEquipmentStateRow) -> Self {
+        Self {
+            equipment_id: row.equipment_id,
+            state_id: row.state_id,
+            state_code: row.state_code,
+            state_description: row.state_description,
+            state_group_id: row.state_group_id,
+            state_value: row.state_value,
+            started_at: row.started_at,
+            duration_seconds: row.duration_seconds,
+            updated_by: row.updated_by,
+        }
+    }
+}
+
+/// A raw state report from a PLC or other data source
+#[derive(Debug, Clone, Default)]
+pub struct StateReport<'a> {
+    pub state_code: i32,
+    /// Only needed when the code is defined in more than one mapped state group
+    pub state_group_id: Option<Uuid>,
+    pub state_value: Option<&'a Value>,
+    /// When the state was entered at the source, defaults to now
+    pub timestamp: Option<OffsetDateTime>,
+    pub updated_by: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentStateService {
+    db: PgPool,
+}
+
+impl EquipmentStateService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    async fn validate_equipment_exists(&self, equipment_id: Uuid) -> AppResult<()> {
+        if !EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check if equipment exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Resolve a raw state code against the state groups mapped to the equipment
+    async fn resolve_state(
+        &self,
+        equipment_id: Uuid,
+        state_code: i32,
+        state_group_id: Option<Uuid>,
+    ) -> AppResult<StateRow> {
+        let mapped = EquipmentStateQueries::get_mapped_state_group_ids(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment state group mapping")?;
+
+        if mapped.is_empty() {
+            return Err(AppError::Validation(format!(
+                "no state group is assigned to equipment {}",
+                equipment_id
+            )));
+        }
+
+        let candidates = match state_group_id {
+            Some(group_id) if mapped.contains(&group_id) => vec![group_id],
+            Some(group_id) => {
+                return Err(AppError::Validation(format!(
+                    "state group '{}' is not assigned to equipment {}",
+                    group_id, equipment_id
+                )));
+            }
+            None => mapped,
+        };
+
+        let mut matches = Vec::new();
+        for group_id in candidates {
+            if let Some(state) =
+                StateRowQueries::get_by_state_code_and_group(&self.db, group_id, state_code)
+                    .await
+                    .context("Failed to resolve state code")?
+            {
+                matches.push(state);
+            }
+        }
+
+        match matches.len() {
+            0 => Err(AppError::Validation(format!(
+                "state_code {} is not defined in the state groups assigned to equipment {}",
+                state_code, equipment_id
+            ))),
+            1 => Ok(matches.remove(0)),
+            _ => Err(AppError::Validation(format!(
+                "state_code {} is ambiguous for equipment {}, specify state_group_id",
+                state_code, equipment_id
+            ))),
+        }
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_current(
+        &self,
+        equipment_id: Uuid,
+    ) -> AppResult<Option<EquipmentCurrentState>> {
+        debug!("Fetching current state");
+        self.validate_equipment_exists(equipment_id).await?;
+
+        let row = EquipmentStateQueries::get_current(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch current equipment state")?;
+        Ok(row.map(EquipmentCurrentState::from))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_history(
+        &self,
+        equipment_id: Uuid,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> AppResult<Vec<EquipmentStateChange>> {
+        debug!("Fetching state history");
+        if let (Some(from), Some(to)) = (from, to)
+            && from >= to
+        {
+            return Err(AppError::Validation(
+                "invalid time range: 'from' must be before 'to'".to_string(),
+            ));
+        }
+        self.validate_equipment_exists(equipment_id).await?;
+
+        let rows = EquipmentStateQueries::get_history(&self.db, equipment_id, from, to)
+            .await
+            .context("Failed to fetch equipment state history")?;
+        let history: Vec<EquipmentStateChange> =
+            rows.into_iter().map(EquipmentStateChange::from).collect();
+        debug!("Found {} state history entries", history.len());
+        Ok(history)
+    }
+
+    /// Ingest a raw state code. A change of state closes the open interval and starts a new
+    /// one; reporting the state the equipment is already in leaves the history untouched.
+    #[instrument(skip(self, report), fields(equipment_id = %equipment_id, state_code = report.state_code))]
+    pub async fn ingest(
+        &self,
+        equipment_id: Uuid,
+        report: StateReport<'_>,
+    ) -> AppResult<EquipmentCurrentState> {
+        debug!("Ingesting equipment state");
+        let updated_by = report.updated_by.map(str::trim).filter(|s| !s.is_empty());
+        if updated_by.is_some_and(|s| s.len() > MAX_UPDATED_BY_LEN) {
+            return Err(AppError::Validation(format!(
+                "updated_by exceeds max length of {} characters",
+                MAX_UPDATED_BY_LEN
+            )));
+        }
+        if report
+            .timestamp
+            .is_some_and(|t| t > OffsetDateTime::now_utc())
+        {
+            return Err(AppError::Validation(
+                "invalid timestamp: cannot be in the future".to_string(),
+            ));
+        }
+
+        self.validate_equipment_exists(equipment_id).await?;
+
+        let state = self
+            .resolve_state(equipment_id, report.state_code, report.state_group_id)
+            .await?;
+
+        let recorded = EquipmentStateQueries::record_state(
+            &self.db,
+            equipment_id,
+            state.state_id,
+            report.state_code,
+            report.state_value,
+            report.timestamp,
+            updated_by,
+        )
+        .await
+        .context("Failed to record equipment state")?;
+
+        match recorded {
+            RecordedState::Recorded(_) => {
+                debug!("Successfully recorded state {}", state.state_description)
+            }
+            RecordedState::Unchanged => debug!("Equipment is already in the reported state"),
+            RecordedState::OutOfOrder => {
+                return Err(AppError::Validation(
+                    "invalid timestamp: earlier than the start of the current state".to_string(),
+                ));
+            }
+            RecordedState::EquipmentMissing => {
+                return Err(AppError::NotFound(format!(
+                    "Equipment with ID {} not found",
+                    equipment_id
+                )));
+            }
+        }
+        self.get_current(equipment_id).await?.ok_or_else(|| {
+            AppError::database(
+                format!("Failed to read back state for equipment {}", equipment_id),
+                sqlx::Error::RowNotFound,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::equipment_service::EquipmentService;
+
+    async fn create_equipment(pool: &PgPool, mapped: bool) -> sqlx::Result<Uuid> {
+        let enterprise = sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = 'enterprise'"
+        )
+        .fetch_one(pool)
+        .await?;
+        let equipment = EquipmentService::new(pool.clone())
+            .create("Acme", enterprise, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        if mapped {
+            sqlx::query!(
+                r#"INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+                   SELECT $1, state_group_id FROM core.state_group
+                   WHERE state_group_name = 'Default MES State Group'"#,
+                equipment.equipment_id
+            )
+            .execute(pool)
+            .await?;
+        }
+        Ok(equipment.equipment_id)
+    }
+
+    fn report(state_code: i32) -> StateReport<'static> {
+        StateReport {
+            state_code,
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_service_ingest(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool, true).await?;
+        let service = EquipmentStateService::new(pool);
+
+        let current = service
+            .ingest(equipment_id, report(1))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(current.state_description, "running");
+
+        // repeated reports of the same code keep the interval open
+        let same = service
+            .ingest(equipment_id, report(1))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(same.started_at, current.started_at);
+
+        let value = serde_json::json!({"fault": "jam"});
+        service
+            .ingest(
+                equipment_id,
+                StateReport {
+                    state_code: 8,
+                    state_value: Some(&value),
+                    updated_by: Some("plc-01"),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let history = service
+            .get_history(equipment_id, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].state_description, "unplanned downtime");
+        assert_eq!(history[0].state_value, Some(value));
+        assert_eq!(history[1].ended_at, Some(history[0].started_at));
+
+        let current = service
+            .get_current(equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+            .expect("state should be set");
+        assert_eq!(current.updated_by.as_deref(), Some("plc-01"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_ingest_concurrent_duplicates(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool, true).await?;
+        let service = EquipmentStateService::new(pool);
+
+        let results = tokio::join!(
+            service.ingest(equipment_id, report(1)),
+            service.ingest(equipment_id, report(1)),
+            service.ingest(equipment_id, report(1)),
+        );
+        for result in [results.0, results.1, results.2] {
+            result.map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let history = service
+            .get_history(equipment_id, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(history.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_ingest_validation(pool: PgPool) -> sqlx::Result<()> {
+        let unmapped = create_equipment(&pool, false).await?;
+        let service = EquipmentStateService::new(pool.clone());
+
+        let result = service.ingest(unmapped, report(1)).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("no state group is assigned")
+        );
+
+        let result = service.ingest(Uuid::new_v4(), report(1)).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+               SELECT $1, state_group_id FROM core.state_group
+               WHERE state_group_name = 'Default MES State Group'"#,
+            unmapped
+        )
+        .execute(&pool)
+        .await?;
+
+        let result = service.ingest(unmapped, report(999)).await;
+        assert!(result.unwrap_err().to_string().contains("not defined"));
+
+        let result = service
+            .ingest(
+                unmapped,
+                StateReport {
+                    state_code: 1,
+                    state_group_id: Some(Uuid::new_v4()),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(result.unwrap_err().to_string().contains("is not assigned"));
+
+        service
+            .ingest(unmapped, report(1))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let result = service
+            .ingest(
+                unmapped,
+                StateReport {
+                    state_code: 3,
+                    timestamp: Some(OffsetDateTime::now_utc() - time::Duration::hours(1)),
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("invalid timestamp")
+        );
+
+        Ok(())
+    }
+}
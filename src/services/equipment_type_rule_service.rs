@@ -0,0 +1,186 @@
+use crate::database::audit::AuditQueries;
+use crate::database::equipment_type_rules::{EquipmentTypeRuleQueries, EquipmentTypeRuleRow};
+use crate::database::equipment_types::EquipmentTypeQueries;
+use crate::error::{AppError, AppResult, DatabaseContext};
+use sqlx::{PgPool, Postgres, Transaction};
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct EquipmentTypeRule {
+    pub rule_id: Uuid,
+    pub parent_type_id: Option<Uuid>,
+    pub parent_type_name: Option<String>,
+    pub child_type_id: Uuid,
+    pub child_type_name: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<EquipmentTypeRuleRow> for EquipmentTypeRule {
+    fn from(row: EquipmentTypeRuleRow) -> Self {
+        Self {
+            rule_id: row.rule_id,
+            parent_type_id: row.parent_type_id,
+            parent_type_name: row.parent_type_name,
+            child_type_id: row.child_type_id,
+            child_type_name: row.child_type_name,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentTypeRuleService {
+    db: PgPool,
+    actor: Option<String>,
+}
+
+impl EquipmentTypeRuleService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_all(&self) -> AppResult<Vec<EquipmentTypeRule>> {
+        debug!("Fetching all equipment type rules");
+        let rows = EquipmentTypeRuleQueries::get_all(&self.db)
+            .await
+            .context("Failed to fetch equipment type rules")?;
+        let rules: Vec<EquipmentTypeRule> = rows.into_iter().map(EquipmentTypeRule::from).collect();
+        debug!("Found {} equipment type rules", rules.len());
+        Ok(rules)
+    }
+
+    #[instrument(skip(self), fields(rule_id = %rule_id))]
+    pub async fn get_by_id(&self, rule_id: Uuid) -> AppResult<EquipmentTypeRule> {
+        let row = EquipmentTypeRuleQueries::get_by_id(&self.db, rule_id)
+            .await
+            .context("Failed to fetch equipment type rule")?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment type rule with ID {} not found", rule_id))
+            })?;
+        Ok(EquipmentTypeRule::from(row))
+    }
+
+    /// Allow `child_type_id` under `parent_type_id`, or at the root when the parent is None
+    #[instrument(skip(self))]
+    pub async fn create(
+        &self,
+        parent_type_id: Option<Uuid>,
+        child_type_id: Uuid,
+    ) -> AppResult<EquipmentTypeRule> {
+        debug!("Creating equipment type rule");
+
+        for type_id in parent_type_id.iter().chain(std::iter::once(&child_type_id)) {
+            if !EquipmentTypeQueries::exists(&self.db, *type_id)
+                .await
+                .context("Failed to check if equipment type exists")?
+            {
+                return Err(AppError::Validation(format!(
+                    "type_id '{}' does not exist",
+                    type_id
+                )));
+            }
+        }
+
+        if EquipmentTypeRuleQueries::is_allowed(&self.db, parent_type_id, child_type_id)
+            .await
+            .context("Failed to check for duplicate equipment type rule")?
+        {
+            return Err(AppError::Conflict(
+                "equipment type rule already exists".to_string(),
+            ));
+        }
+
+        let mut tx = self.begin().await?;
+        let rule_id = EquipmentTypeRuleQueries::create(&mut tx, parent_type_id, child_type_id)
+            .await
+            .context("Failed to create equipment type rule")?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully created equipment type rule {}", rule_id);
+        self.get_by_id(rule_id).await
+    }
+
+    #[instrument(skip(self), fields(rule_id = %rule_id))]
+    pub async fn delete(&self, rule_id: Uuid) -> AppResult<()> {
+        debug!("Deleting equipment type rule");
+        let mut tx = self.begin().await?;
+        let deleted = EquipmentTypeRuleQueries::delete(&mut tx, rule_id)
+            .await
+            .with_context(|| format!("Failed to delete equipment type rule {}", rule_id))?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "Equipment type rule with ID {} not found",
+                rule_id
+            )));
+        }
+
+        debug!("Successfully deleted equipment type rule");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seeded_type_id(pool: &PgPool, type_name: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = $1",
+            type_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_service_create_and_delete(pool: PgPool) -> sqlx::Result<()> {
+        let service = EquipmentTypeRuleService::new(pool.clone());
+        let site = seeded_type_id(&pool, "site").await?;
+
+        // allow sites at the root for single-site installs
+        let rule = service
+            .create(None, site)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(rule.parent_type_id.is_none());
+        assert_eq!(rule.child_type_name, "site");
+
+        let result = service.create(None, site).await;
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+
+        let result = service.create(Some(Uuid::new_v4()), site).await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+
+        service
+            .delete(rule.rule_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service.delete(rule.rule_id).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+}
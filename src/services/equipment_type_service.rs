@@ -1,6 +1,7 @@
+use crate::database::audit::AuditQueries;
 use crate::database::equipment_types::{EquipmentTypeQueries, EquipmentTypeRow};
-use anyhow::{Context, Result, anyhow};
-use sqlx::PgPool;
+use crate::error::{AppError, AppResult, DatabaseContext, is_unique_violation};
+use sqlx::{PgPool, Postgres, Transaction};
 use time::OffsetDateTime;
 use tracing::{debug, instrument};
 use uuid::Uuid;
@@ -27,15 +28,31 @@ impl From<EquipmentTypeRow> for EquipmentType {
 #[derive(Debug, Clone)]
 pub struct EquipmentTypeService {
     db: PgPool,
+    actor: Option<String>,
 }
 
 impl EquipmentTypeService {
     pub fn new(db: PgPool) -> Self {
-        Self { db }
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
     }
 
     #[instrument(skip(self))]
-    pub async fn get_all(&self) -> Result<Vec<EquipmentType>> {
+    pub async fn get_all(&self) -> AppResult<Vec<EquipmentType>> {
         debug!("Fetching all equipment types");
         let rows = EquipmentTypeQueries::get_all(&self.db)
             .await
@@ -46,19 +63,21 @@ impl EquipmentTypeService {
     }
 
     #[instrument(skip(self), fields(type_id = %type_id))]
-    pub async fn get_by_id(&self, type_id: Uuid) -> Result<EquipmentType> {
+    pub async fn get_by_id(&self, type_id: Uuid) -> AppResult<EquipmentType> {
         debug!("Fetching equipment type by ID");
         let row = EquipmentTypeQueries::get_by_id(&self.db, type_id)
             .await
             .context("Failed to fetch equipment type by ID")?
-            .ok_or_else(|| anyhow!("Equipment type with ID {} not found", type_id))?;
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment type with ID {} not found", type_id))
+            })?;
 
         debug!("Found equipment type: {}", row.type_name);
         Ok(EquipmentType::from(row))
     }
 
     #[instrument(skip(self), fields(type_name = %type_name))]
-    pub async fn get_by_name(&self, type_name: &str) -> Result<Option<EquipmentType>> {
+    pub async fn get_by_name(&self, type_name: &str) -> AppResult<Option<EquipmentType>> {
         debug!("Fetching equipment type by name");
         let row = EquipmentTypeQueries::get_by_name(&self.db, type_name)
             .await
@@ -77,55 +96,56 @@ impl EquipmentTypeService {
     }
 
     #[instrument(skip(self), fields(type_name = %type_name))]
-    pub async fn create(&self, type_name: &str) -> Result<EquipmentType> {
+    pub async fn create(&self, type_name: &str) -> AppResult<EquipmentType> {
         debug!("Creating new equipment type");
 
-        let row = EquipmentTypeQueries::create(&self.db, type_name)
-            .await
-            .map_err(|e| {
-                // Check if it's a duplicate error and preserve the original message
-                if e.to_string().contains("already exists") {
-                    e // Return the original error to preserve "already exists" message
-                } else {
-                    // For other errors, add context
-                    e.context(format!("Failed to create equipment type '{}'", type_name))
-                }
-            })?;
+        let mut tx = self.begin().await?;
+        let row = EquipmentTypeQueries::create(&mut tx, type_name).await?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         debug!("Successfully created equipment type: {}", row.type_name);
         Ok(EquipmentType::from(row))
     }
 
     #[instrument(skip(self), fields(type_id = %type_id, type_name = %type_name))]
-    pub async fn update(&self, type_id: Uuid, type_name: &str) -> Result<EquipmentType> {
+    pub async fn update(&self, type_id: Uuid, type_name: &str) -> AppResult<EquipmentType> {
         debug!("Updating equipment type");
 
-        let row = EquipmentTypeQueries::update(&self.db, type_id, type_name)
-            .await
-            .with_context(|| format!("Failed to update equipment type {}", type_id))?
-            .ok_or_else(|| anyhow!("Equipment type with ID {} not found", type_id))?;
+        let mut tx = self.begin().await?;
+        let row = EquipmentTypeQueries::update(&mut tx, type_id, type_name)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment type with ID {} not found", type_id))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         debug!("Successfully updated equipment type: {}", row.type_name);
         Ok(EquipmentType::from(row))
     }
 
     #[instrument(skip(self), fields(type_id = %type_id))]
-    pub async fn delete(&self, type_id: Uuid) -> Result<()> {
+    pub async fn delete(&self, type_id: Uuid) -> AppResult<()> {
         debug!("Deleting equipment type");
 
-        // TODO: Add check to see if equipment type is in use
-        // This would prevent deletion of types that are actively used
-        // Example:
-        // if self.is_in_use(type_id).await? {
-        //     return Err(anyhow!("Equipment type is in use and cannot be deleted"));
-        // }
-
-        let deleted = EquipmentTypeQueries::delete(&self.db, type_id)
+        if EquipmentTypeQueries::is_in_use(&self.db, type_id)
             .await
-            .with_context(|| format!("Failed to delete equipment type {}", type_id))?;
+            .context("Failed to check equipment type usage")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Equipment type {} is in use by equipment and cannot be deleted",
+                type_id
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        let deleted = EquipmentTypeQueries::delete(&mut tx, type_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         if !deleted {
-            return Err(anyhow!("Equipment type with ID {} not found", type_id));
+            return Err(AppError::NotFound(format!(
+                "Equipment type with ID {} not found",
+                type_id
+            )));
         }
 
         debug!("Successfully deleted equipment type");
@@ -133,7 +153,36 @@ impl EquipmentTypeService {
     }
 
     #[instrument(skip(self), fields(type_id = %type_id))]
-    pub async fn exists(&self, type_id: Uuid) -> Result<bool> {
+    pub async fn restore(&self, type_id: Uuid) -> AppResult<EquipmentType> {
+        debug!("Restoring equipment type");
+
+        let mut tx = self.begin().await?;
+        let row = EquipmentTypeQueries::restore(&mut tx, type_id)
+            .await
+            .map_err(|e| {
+                if is_unique_violation(&e) {
+                    AppError::Conflict(format!(
+                        "Equipment type {} can't be restored, its name is taken",
+                        type_id
+                    ))
+                } else {
+                    AppError::database(format!("Failed to restore equipment type {}", type_id), e)
+                }
+            })?
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Deleted equipment type with ID {} not found",
+                    type_id
+                ))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully restored equipment type: {}", row.type_name);
+        Ok(EquipmentType::from(row))
+    }
+
+    #[instrument(skip(self), fields(type_id = %type_id))]
+    pub async fn exists(&self, type_id: Uuid) -> AppResult<bool> {
         let exists = EquipmentTypeQueries::exists(&self.db, type_id)
             .await
             .context("Failed to check if equipment type exists")?;
@@ -142,7 +191,7 @@ impl EquipmentTypeService {
     }
 
     #[instrument(skip(self), fields(type_name = %type_name))]
-    pub async fn name_exists(&self, type_name: &str) -> Result<bool> {
+    pub async fn name_exists(&self, type_name: &str) -> AppResult<bool> {
         let exists = EquipmentTypeQueries::name_exists(&self.db, type_name)
             .await
             .context("Failed to check if equipment type name exists")?;
@@ -151,7 +200,7 @@ impl EquipmentTypeService {
     }
 
     #[instrument(skip(self), fields(search_term = %search_term))]
-    pub async fn search_by_name(&self, search_term: &str) -> Result<Vec<EquipmentType>> {
+    pub async fn search_by_name(&self, search_term: &str) -> AppResult<Vec<EquipmentType>> {
         debug!("Searching equipment types by name");
         let rows = EquipmentTypeQueries::search_by_name(&self.db, search_term)
             .await
@@ -163,7 +212,7 @@ impl EquipmentTypeService {
 
     // TODO: Implement this method to check if an equipment type is in use
     // #[instrument(skip(self), fields(type_id = %type_id))]
-    // pub async fn is_in_use(&self, type_id: Uuid) -> Result<bool> {
+    // pub async fn is_in_use(&self, type_id: Uuid) -> AppResult<bool> {
     //     // This would check if the equipment type is referenced in other tables
     //     // Example: equipment, maintenance_records, etc.
     //     // let count = sqlx::query_scalar!(
@@ -180,7 +229,7 @@ impl EquipmentTypeService {
 
     /// Bulk create equipment types (useful for initial setup or imports)
     #[instrument(skip(self, type_names))]
-    pub async fn bulk_create(&self, type_names: Vec<&str>) -> Result<Vec<EquipmentType>> {
+    pub async fn bulk_create(&self, type_names: Vec<&str>) -> AppResult<Vec<EquipmentType>> {
         debug!("Bulk creating {} equipment types", type_names.len());
         let mut created_types = Vec::new();
         let mut errors = Vec::new();
@@ -190,7 +239,7 @@ impl EquipmentTypeService {
                 Ok(equipment_type) => {
                     created_types.push(equipment_type);
                 }
-                Err(e) if e.to_string().contains("already exists") => {
+                Err(AppError::Conflict(_)) => {
                     // Skip duplicates, optionally log warning
                     debug!("Skipping duplicate equipment type: {}", type_name);
                 }
@@ -201,10 +250,10 @@ impl EquipmentTypeService {
         }
 
         if !errors.is_empty() {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "Bulk create failed with errors: {}",
                 errors.join(", ")
-            ));
+            )));
         }
 
         debug!(
@@ -220,32 +269,39 @@ impl EquipmentTypeService {
         &self,
         offset: i64,
         limit: i64,
-    ) -> Result<(Vec<EquipmentType>, i64)> {
+    ) -> AppResult<(Vec<EquipmentType>, i64)> {
         debug!(
             "Fetching equipment types with pagination: offset={}, limit={}",
             offset, limit
         );
 
         if offset < 0 {
-            return Err(anyhow!("Offset cannot be negative"));
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
         }
 
         if limit <= 0 || limit > 1000 {
-            return Err(anyhow!("Limit must be between 1 and 1000"));
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
         }
 
         // Get total count
-        let total_count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.equipment_type")
-            .fetch_one(&self.db)
-            .await
-            .context("Failed to get total count of equipment types")?
-            .unwrap_or(0);
+        let total_count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM core.equipment_type WHERE deleted_at IS NULL"
+        )
+        .fetch_one(&self.db)
+        .await
+        .context("Failed to get total count of equipment types")?
+        .unwrap_or(0);
 
         // Get paginated results
         let rows = sqlx::query_as!(
             EquipmentTypeRow,
             r#"SELECT type_id, type_name, created_at, updated_at
                FROM core.equipment_type
+               WHERE deleted_at IS NULL
                ORDER BY type_name
                LIMIT $1 OFFSET $2"#,
             limit,
@@ -271,18 +327,20 @@ impl EquipmentTypeService {
         &self,
         start_date: OffsetDateTime,
         end_date: OffsetDateTime,
-    ) -> Result<Vec<EquipmentType>> {
+    ) -> AppResult<Vec<EquipmentType>> {
         debug!("Fetching equipment types by date range");
 
         if start_date > end_date {
-            return Err(anyhow!("Start date cannot be after end date"));
+            return Err(AppError::Validation(
+                "Start date cannot be after end date".to_string(),
+            ));
         }
 
         let rows = sqlx::query_as!(
             EquipmentTypeRow,
             r#"SELECT type_id, type_name, created_at, updated_at
                FROM core.equipment_type
-               WHERE created_at >= $1 AND created_at <= $2
+               WHERE created_at >= $1 AND created_at <= $2 AND deleted_at IS NULL
                ORDER BY created_at DESC"#,
             start_date,
             end_date
@@ -298,12 +356,14 @@ impl EquipmentTypeService {
 
     /// Get count of all equipment types
     #[instrument(skip(self))]
-    pub async fn count(&self) -> Result<i64> {
-        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.equipment_type")
-            .fetch_one(&self.db)
-            .await
-            .context("Failed to count equipment types")?
-            .unwrap_or(0);
+    pub async fn count(&self) -> AppResult<i64> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM core.equipment_type WHERE deleted_at IS NULL"
+        )
+        .fetch_one(&self.db)
+        .await
+        .context("Failed to count equipment types")?
+        .unwrap_or(0);
 
         debug!("Total equipment types count: {}", count);
         Ok(count)
@@ -0,0 +1,342 @@
+use crate::database::equipment::EquipmentQueries;
+use crate::database::equipment_modes::EquipmentModeQueries;
+use crate::database::equipment_states::EquipmentStateQueries;
+use crate::database::events::{EVENTS_CHANNEL, EventAction, EventPayload, EventType};
+use crate::error::{AppError, AppResult};
+use crate::services::equipment_mode_service::EquipmentModeChange;
+use crate::services::equipment_state_service::EquipmentStateChange;
+use anyhow::{Context, Result};
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, error, instrument, warn};
+use uuid::Uuid;
+
+/// Events a slow subscriber can fall behind before it starts missing them
+const EVENT_BUFFER: usize = 1024;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub enum EventData {
+    /// Entity changes only carry the id, clients fetch the entity if they need it
+    None,
+    EquipmentMode(EquipmentModeChange),
+    EquipmentState(EquipmentStateChange),
+}
+
+/// A created, updated or deleted entity, or a recorded mode or state change
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub event_type: EventType,
+    pub action: EventAction,
+    /// Id of the entity, or of the history entry for mode and state changes
+    pub id: Uuid,
+    pub equipment_id: Option<Uuid>,
+    /// The equipment and all of its ancestors, root first. Empty when the event is not
+    /// about equipment.
+    pub path: Vec<Uuid>,
+    pub data: EventData,
+}
+
+/// Which events a subscriber wants
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Only events for this equipment and everything below it, which leaves out events
+    /// that are not about equipment. Everything when None.
+    pub equipment_id: Option<Uuid>,
+    /// Only these event types, all of them when empty
+    pub event_types: Vec<EventType>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        let in_subtree = self
+            .equipment_id
+            .is_none_or(|root| event.path.contains(&root));
+        let wanted_type =
+            self.event_types.is_empty() || self.event_types.contains(&event.event_type);
+        in_subtree && wanted_type
+    }
+}
+
+/// Parse a comma separated list of event types, e.g. "equipment,equipment_state"
+pub fn parse_event_types(value: &str) -> AppResult<Vec<EventType>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            EventType::parse(s).ok_or_else(|| {
+                AppError::Validation(format!(
+                    "invalid event type '{}', expected one of: {}",
+                    s,
+                    EventType::ALL.map(|t| t.as_str()).join(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Fans entity and runtime changes out to subscribers. Changes arrive through postgres
+/// LISTEN/NOTIFY, so every server instance sees the changes made through all of them.
+#[derive(Debug, Clone)]
+pub struct EventService {
+    db: PgPool,
+    sender: broadcast::Sender<Event>,
+}
+
+impl EventService {
+    pub fn new(db: PgPool) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { db, sender }
+    }
+
+    /// Check the filter and start receiving the events from now on
+    #[instrument(skip(self))]
+    pub async fn subscribe(&self, filter: &EventFilter) -> AppResult<broadcast::Receiver<Event>> {
+        debug!("Subscribing to events");
+        if let Some(equipment_id) = filter.equipment_id
+            && !EquipmentQueries::exists(&self.db, equipment_id)
+                .await
+                .map_err(|e| AppError::database("Failed to check if equipment exists", e))?
+        {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+
+        Ok(self.sender.subscribe())
+    }
+
+    /// Forward notifications to subscribers for as long as the server runs,
+    /// reconnecting when the listener connection is lost
+    pub async fn listen(&self) {
+        loop {
+            match self.connect().await {
+                Ok(listener) => {
+                    if let Err(e) = self.forward(listener).await {
+                        error!("Event listener stopped: {:#}", e);
+                    }
+                }
+                Err(e) => error!("Failed to start event listener: {:#}", e),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn connect(&self) -> Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.db)
+            .await
+            .context("Failed to connect event listener")?;
+        listener
+            .listen(EVENTS_CHANNEL)
+            .await
+            .context("Failed to listen for events")?;
+        Ok(listener)
+    }
+
+    async fn forward(&self, mut listener: PgListener) -> Result<()> {
+        loop {
+            let notification = listener.recv().await.context("Failed to receive event")?;
+
+            // nobody is watching, skip loading the change
+            if self.sender.receiver_count() == 0 {
+                continue;
+            }
+
+            let payload: EventPayload = match serde_json::from_str(notification.payload()) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!("Ignoring malformed event: {}", e);
+                    continue;
+                }
+            };
+
+            match self.load_event(&payload).await {
+                // a send error only means the last subscriber just left
+                Ok(Some(event)) => drop(self.sender.send(event)),
+                Ok(None) => debug!("History entry {} is already gone", payload.id),
+                Err(e) => warn!("Failed to load event: {:#}", e),
+            }
+        }
+    }
+
+    async fn load_event(&self, payload: &EventPayload) -> Result<Option<Event>> {
+        let data = match payload.event_type {
+            EventType::EquipmentMode => {
+                match EquipmentModeQueries::get_history_entry(&self.db, payload.id)
+                    .await
+                    .context("Failed to fetch mode history entry")?
+                {
+                    Some(row) => EventData::EquipmentMode(row.into()),
+                    None => return Ok(None),
+                }
+            }
+            EventType::EquipmentState => {
+                match EquipmentStateQueries::get_history_entry(&self.db, payload.id)
+                    .await
+                    .context("Failed to fetch state history entry")?
+                {
+                    Some(row) => EventData::EquipmentState(row.into()),
+                    None => return Ok(None),
+                }
+            }
+            _ => EventData::None,
+        };
+
+        // deleted equipment is gone from the tree, place it below its old parent instead
+        let path = match (payload.equipment_id, payload.action) {
+            (Some(equipment_id), EventAction::Deleted) => {
+                let mut path = match payload.parent_id {
+                    Some(parent_id) => self.get_path(parent_id).await?,
+                    None => Vec::new(),
+                };
+                path.push(equipment_id);
+                path
+            }
+            (Some(equipment_id), _) => self.get_path(equipment_id).await?,
+            (None, _) => Vec::new(),
+        };
+
+        Ok(Some(Event {
+            event_type: payload.event_type,
+            action: payload.action,
+            id: payload.id,
+            equipment_id: payload.equipment_id,
+            path,
+            data,
+        }))
+    }
+
+    async fn get_path(&self, equipment_id: Uuid) -> Result<Vec<Uuid>> {
+        let ancestors = EquipmentQueries::get_ancestors(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment ancestors")?;
+        Ok(ancestors.into_iter().map(|e| e.equipment_id).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::equipment_state_service::{EquipmentStateService, StateReport};
+
+    async fn create_equipment(
+        pool: &PgPool,
+        equipment_name: &str,
+        type_name: &str,
+        parent_id: Option<Uuid>,
+    ) -> sqlx::Result<Uuid> {
+        let equipment_id = sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id, equipment_parent_id)
+               SELECT $1, type_id, $3 FROM core.equipment_type WHERE type_name = $2
+               RETURNING equipment_id"#,
+            equipment_name,
+            type_name,
+            parent_id
+        )
+        .fetch_one(pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+               SELECT $1, state_group_id FROM core.state_group
+               WHERE state_group_name = 'Default MES State Group'"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(equipment_id)
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<Event>) -> sqlx::Result<Event> {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("event should arrive")
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))
+    }
+
+    #[test]
+    fn test_parse_event_types() {
+        assert_eq!(
+            parse_event_types("equipment, equipment_state,").unwrap(),
+            vec![EventType::Equipment, EventType::EquipmentState]
+        );
+        assert!(parse_event_types("").unwrap().is_empty());
+        let err = parse_event_types("equipment,widgets").unwrap_err();
+        assert!(err.to_string().contains("invalid event type 'widgets'"));
+    }
+
+    #[sqlx::test]
+    async fn test_events_reach_subscribers(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = create_equipment(&pool, "Acme", "enterprise", None).await?;
+
+        let service = EventService::new(pool.clone());
+        let filter = EventFilter {
+            equipment_id: Some(enterprise),
+            event_types: vec![],
+        };
+        let mut events = service
+            .subscribe(&filter)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let listener = service
+            .connect()
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let forwarder = service.clone();
+        tokio::spawn(async move { forwarder.forward(listener).await });
+
+        let site = create_equipment(&pool, "Plant 1", "site", Some(enterprise)).await?;
+        let created = next_event(&mut events).await?;
+        assert_eq!(created.event_type, EventType::Equipment);
+        assert_eq!(created.action, EventAction::Created);
+        assert_eq!(created.path, vec![enterprise, site]);
+        assert!(filter.matches(&created));
+
+        let states = EquipmentStateService::new(pool.clone());
+        states
+            .ingest(
+                site,
+                StateReport {
+                    state_code: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let state = next_event(&mut events).await?;
+        assert_eq!(state.event_type, EventType::EquipmentState);
+        assert!(matches!(&state.data, EventData::EquipmentState(s) if s.state_code == 1));
+
+        sqlx::query!("DELETE FROM core.equipment WHERE equipment_id = $1", site)
+            .execute(&pool)
+            .await?;
+        let deleted = next_event(&mut events).await?;
+        assert_eq!(deleted.action, EventAction::Deleted);
+        assert_eq!(deleted.path, vec![enterprise, site]);
+        assert!(filter.matches(&deleted));
+
+        sqlx::query!(
+            "INSERT INTO core.mode_group (mode_group_name, mode_group_description) VALUES ('Packaging', 'Packaging line modes')"
+        )
+        .execute(&pool)
+        .await?;
+        let mode_group = next_event(&mut events).await?;
+        assert_eq!(mode_group.event_type, EventType::ModeGroup);
+        assert!(mode_group.path.is_empty());
+        assert!(!filter.matches(&mode_group));
+        assert!(EventFilter::default().matches(&mode_group));
+
+        let result = service
+            .subscribe(&EventFilter {
+                equipment_id: Some(Uuid::new_v4()),
+                event_types: vec![],
+            })
+            .await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,635 @@
+use crate::database::equipment::EquipmentQueries;
+use crate::database::equipment_modes::EquipmentModeQueries;
+use crate::database::jobs::{JobQueries, JobRow, JobStatusHistoryRow};
+use crate::database::production_counts::ProductionCountQueries;
+use crate::database::work_orders::WorkOrderQueries;
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::operations::{Job, JobStatus, JobStatusChange, JobTransition};
+use crate::services::equipment_mode_service::PRODUCTION_MODE;
+use crate::services::work_order_service::validate_planned_quantity;
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+// job_status is guarded by a check constraint, an unknown value means the schema and the
+// state machine are out of sync
+fn invalid_status(message: String) -> AppError {
+    AppError::database(
+        "Invalid job_status read from the database",
+        sqlx::Error::Decode(message.into()),
+    )
+}
+
+impl TryFrom<JobRow> for Job {
+    type Error = AppError;
+
+    fn try_from(row: JobRow) -> AppResult<Self> {
+        Ok(Self {
+            job_id: row.job_id,
+            work_order_id: row.work_order_id,
+            equipment_id: row.equipment_id,
+            planned_quantity: row.planned_quantity,
+            job_metadata: row.job_metadata,
+            job_status: row.job_status.parse().map_err(invalid_status)?,
+            released_at: row.released_at,
+            started_at: row.started_at,
+            completed_at: row.completed_at,
+            cancelled_at: row.cancelled_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+impl TryFrom<JobStatusHistoryRow> for JobStatusChange {
+    type Error = AppError;
+
+    fn try_from(row: JobStatusHistoryRow) -> AppResult<Self> {
+        Ok(Self {
+            history_id: row.history_id,
+            job_id: row.job_id,
+            from_status: row.from_status.parse().map_err(invalid_status)?,
+            to_status: row.to_status.parse().map_err(invalid_status)?,
+            changed_at: row.changed_at,
+            changed_by: row.changed_by,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JobService {
+    db: PgPool,
+}
+
+impl JobService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Job metadata is stored as a json object, anything else is rejected
+    fn validate_metadata(metadata: &Value) -> AppResult<()> {
+        if !metadata.is_object() {
+            return Err(AppError::Validation(
+                "job_metadata must be a json object".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn validate_equipment(&self, equipment_id: Uuid) -> AppResult<()> {
+        if !EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check equipment")?
+        {
+            return Err(AppError::Validation(format!(
+                "equipment_id '{}' does not exist",
+                equipment_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub async fn get_by_id(&self, job_id: Uuid) -> AppResult<Job> {
+        debug!("Fetching job by ID");
+        let row = JobQueries::get_by_id(&self.db, job_id)
+            .await
+            .context("Failed to fetch job by ID")?
+            .ok_or_else(|| AppError::NotFound(format!("Job with ID {} not found", job_id)))?;
+
+        Job::try_from(row)
+    }
+
+    #[instrument(skip(self, job_metadata), fields(work_order_id = %work_order_id, equipment_id = %equipment_id))]
+    pub async fn create(
+        &self,
+        work_order_id: Uuid,
+        equipment_id: Uuid,
+        planned_quantity: Option<i32>,
+        job_metadata: Option<&Value>,
+    ) -> AppResult<Job> {
+        debug!("Creating new job");
+        validate_planned_quantity(planned_quantity)?;
+
+        if let Some(metadata) = job_metadata {
+            Self::validate_metadata(metadata)?;
+        }
+
+        if !WorkOrderQueries::exists(&self.db, work_order_id)
+            .await
+            .context("Failed to check work order")?
+        {
+            return Err(AppError::Validation(format!(
+                "work_order_id '{}' does not exist",
+                work_order_id
+            )));
+        }
+
+        self.validate_equipment(equipment_id).await?;
+
+        let row = JobQueries::create(
+            &self.db,
+            work_order_id,
+            equipment_id,
+            planned_quantity,
+            job_metadata,
+        )
+        .await
+        .with_context(|| format!("Failed to create job for work order {}", work_order_id))?;
+
+        debug!("Successfully created job: {}", row.job_id);
+        Job::try_from(row)
+    }
+
+    #[instrument(skip(self), fields(job_id = %job_id, equipment_id = %equipment_id))]
+    pub async fn update(
+        &self,
+        job_id: Uuid,
+        equipment_id: Uuid,
+        planned_quantity: Option<i32>,
+    ) -> AppResult<Job> {
+        debug!("Updating job");
+        validate_planned_quantity(planned_quantity)?;
+        self.validate_equipment(equipment_id).await?;
+
+        let current = self.get_by_id(job_id).await?;
+        if !matches!(current.job_status, JobStatus::Planned | JobStatus::Released) {
+            return Err(AppError::Conflict(format!(
+                "invalid transition: a job that is {} cannot be updated",
+                current.job_status
+            )));
+        }
+
+        let row = JobQueries::update(&self.db, job_id, equipment_id, planned_quantity)
+            .await
+            .with_context(|| format!("Failed to update job {}", job_id))?
+            .ok_or_else(|| AppError::NotFound(format!("Job with ID {} not found", job_id)))?;
+
+        debug!("Successfully updated job");
+        Job::try_from(row)
+    }
+
+    #[instrument(skip(self, metadata), fields(job_id = %job_id))]
+    pub async fn update_metadata(&self, job_id: Uuid, metadata: &Value) -> AppResult<Job> {
+        debug!("Updating job metadata");
+        Self::validate_metadata(metadata)?;
+
+        let row = JobQueries::update_metadata(&self.db, job_id, metadata)
+            .await
+            .with_context(|| format!("Failed to update metadata for job {}", job_id))?
+            .ok_or_else(|| AppError::NotFound(format!("Job with ID {} not found", job_id)))?;
+
+        debug!("Successfully updated job metadata");
+        Job::try_from(row)
+    }
+
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub async fn delete(&self, job_id: Uuid) -> AppResult<()> {
+        debug!("Deleting job");
+
+        if ProductionCountQueries::job_has_counts(&self.db, job_id)
+            .await
+            .context("Failed to check if job has production counts")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Job with ID {} is in use by production counts and cannot be deleted",
+                job_id
+            )));
+        }
+
+        let deleted = JobQueries::delete(&self.db, job_id)
+            .await
+            .with_context(|| format!("Failed to delete job {}", job_id))?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "Job with ID {} not found",
+                job_id
+            )));
+        }
+
+        debug!("Successfully deleted job");
+        Ok(())
+    }
+
+    /// A job can only run on equipment that is enabled and in the production mode
+    async fn validate_equipment_can_run(&self, equipment_id: Uuid) -> AppResult<()> {
+        let equipment = EquipmentQueries::get_by_id(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment")?
+            .ok_or_else(|| {
+                AppError::Validation(format!("equipment_id '{}' does not exist", equipment_id))
+            })?;
+
+        if !equipment.equipment_enabled {
+            return Err(AppError::Conflict(format!(
+                "equipment '{}' is disabled and cannot run jobs",
+                equipment.equipment_name
+            )));
+        }
+
+        let mode = EquipmentModeQueries::get_current(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch current equipment mode")?;
+
+        match mode {
+            Some(mode) if mode.mode_description == PRODUCTION_MODE => Ok(()),
+            Some(mode) => Err(AppError::Conflict(format!(
+                "equipment '{}' is in mode '{}', jobs can only run in '{}'",
+                equipment.equipment_name, mode.mode_description, PRODUCTION_MODE
+            ))),
+            None => Err(AppError::Conflict(format!(
+                "equipment '{}' has no mode set, jobs can only run in '{}'",
+                equipment.equipment_name, PRODUCTION_MODE
+            ))),
+        }
+    }
+
+    /// Applies a lifecycle transition to the job. Starting and resuming check that the
+    /// equipment can run the job.
+    #[instrument(skip(self), fields(job_id = %job_id, transition = ?transition))]
+    pub async fn transition(
+        &self,
+        job_id: Uuid,
+        transition: JobTransition,
+        changed_by: Option<&str>,
+    ) -> AppResult<Job> {
+        debug!("Applying job transition");
+        let current = self.get_by_id(job_id).await?;
+
+        let next = current.job_status.apply(transition).ok_or_else(|| {
+            AppError::Conflict(format!(
+                "invalid transition: cannot {} a job that is {}",
+                transition.as_str(),
+                current.job_status
+            ))
+        })?;
+
+        if next == JobStatus::Running {
+            self.validate_equipment_can_run(current.equipment_id)
+                .await?;
+        }
+
+        let row = JobQueries::set_status(
+            &self.db,
+            job_id,
+            current.job_status.as_str(),
+            next.as_str(),
+            changed_by,
+        )
+        .await
+        .with_context(|| format!("Failed to {} job {}", transition.as_str(), job_id))?
+        .ok_or_else(|| {
+            AppError::Conflict(format!(
+                "invalid transition: job {} changed status while it was being updated",
+                job_id
+            ))
+        })?;
+
+        debug!("Job {} is now {}", job_id, next);
+        Job::try_from(row)
+    }
+
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub async fn get_status_history(&self, job_id: Uuid) -> AppResult<Vec<JobStatusChange>> {
+        debug!("Fetching job status history");
+        if !self.exists(job_id).await? {
+            return Err(AppError::NotFound(format!(
+                "Job with ID {} not found",
+                job_id
+            )));
+        }
+
+        let rows = JobQueries::get_status_history(&self.db, job_id)
+            .await
+            .context("Failed to fetch job status history")?;
+
+        rows.into_iter().map(JobStatusChange::try_from).collect()
+    }
+
+    #[instrument(skip(self), fields(job_id = %job_id))]
+    pub async fn exists(&self, job_id: Uuid) -> AppResult<bool> {
+        let exists = JobQueries::exists(&self.db, job_id)
+            .await
+            .context("Failed to check if job exists")?;
+        debug!("Job {} exists: {}", job_id, exists);
+        Ok(exists)
+    }
+
+    /// Get jobs with pagination, optionally filtered by work order and/or equipment
+    #[instrument(skip(self))]
+    pub async fn get_paginated(
+        &self,
+        work_order_id: Option<Uuid>,
+        equipment_id: Option<Uuid>,
+        offset: i64,
+        limit: i64,
+    ) -> AppResult<(Vec<Job>, i64)> {
+        debug!(
+            "Fetching jobs with pagination: offset={}, limit={}",
+            offset, limit
+        );
+
+        if offset < 0 {
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
+        }
+
+        if limit <= 0 || limit > 1000 {
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
+        }
+
+        let total_count = self.count(work_order_id, equipment_id).await?;
+
+        let rows = JobQueries::get_filtered(&self.db, work_order_id, equipment_id, limit, offset)
+            .await
+            .context("Failed to fetch paginated jobs")?;
+
+        let jobs = rows
+            .into_iter()
+            .map(Job::try_from)
+            .collect::<AppResult<Vec<Job>>>()?;
+        debug!("Found {} jobs (total: {})", jobs.len(), total_count);
+
+        Ok((jobs, total_count))
+    }
+
+    /// Get count of jobs, optionally filtered by work order and/or equipment
+    #[instrument(skip(self))]
+    pub async fn count(
+        &self,
+        work_order_id: Option<Uuid>,
+        equipment_id: Option<Uuid>,
+    ) -> AppResult<i64> {
+        let count = JobQueries::count_filtered(&self.db, work_order_id, equipment_id)
+            .await
+            .context("Failed to count jobs")?;
+
+        debug!("Total job count: {}", count);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::equipment_mode_service::EquipmentModeService;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    async fn create_work_order(pool: &PgPool) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            r#"WITH p AS (
+                   INSERT INTO operations.product (product_name) VALUES ('Widget')
+                   RETURNING product_id
+               )
+               INSERT INTO operations.work_order (work_order_name, product_id)
+               SELECT 'WO-1', product_id FROM p
+               RETURNING work_order_id"#
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn create_equipment(pool: &PgPool, equipment_name: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id)
+               SELECT $1, type_id FROM core.equipment_type WHERE type_name = 'enterprise'
+               RETURNING equipment_id"#,
+            equipment_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_service_create_and_validation(pool: PgPool) -> sqlx::Result<()> {
+        let work_order_id = create_work_order(&pool).await?;
+        let equipment_id = create_equipment(&pool, "Acme").await?;
+        let service = JobService::new(pool);
+
+        let job = service
+            .create(
+                work_order_id,
+                equipment_id,
+                Some(10),
+                Some(&json!({"shift": "A"})),
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(job.work_order_id, work_order_id);
+        assert_eq!(job.job_metadata["shift"], "A");
+
+        let err = service
+            .create(Uuid::new_v4(), equipment_id, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("work_order_id"));
+
+        let err = service
+            .create(work_order_id, Uuid::new_v4(), None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("equipment_id"));
+
+        let err = service
+            .create(work_order_id, equipment_id, Some(-1), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("greater than 0"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_filters_and_update(pool: PgPool) -> sqlx::Result<()> {
+        let work_order_id = create_work_order(&pool).await?;
+        let first = create_equipment(&pool, "Acme").await?;
+        let second = create_equipment(&pool, "Globex").await?;
+        let service = JobService::new(pool);
+
+        let job = service
+            .create(work_order_id, first, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        service
+            .create(work_order_id, second, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let (jobs, total) = service
+            .get_paginated(Some(work_order_id), Some(first), 0, 50)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(total, 1);
+        assert_eq!(jobs[0].job_id, job.job_id);
+
+        let moved = service
+            .update(job.job_id, second, Some(5))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(moved.equipment_id, second);
+
+        let total = service
+            .count(None, Some(second))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(total, 2);
+
+        Ok(())
+    }
+
+    async fn set_seeded_mode(
+        pool: &PgPool,
+        equipment_id: Uuid,
+        mode_description: &str,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+               SELECT $1, mode_group_id FROM core.mode_group
+               WHERE mode_group_name = 'Default MES Mode Group'
+               ON CONFLICT DO NOTHING"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        let mode_id = sqlx::query_scalar!(
+            "SELECT mode_id FROM core.mode WHERE mode_description = $1",
+            mode_description
+        )
+        .fetch_one(pool)
+        .await?;
+
+        EquipmentModeService::new(pool.clone())
+            .set_mode(equipment_id, mode_id, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_lifecycle(pool: PgPool) -> sqlx::Result<()> {
+        let work_order_id = create_work_order(&pool).await?;
+        let equipment_id = create_equipment(&pool, "Acme").await?;
+        set_seeded_mode(&pool, equipment_id, PRODUCTION_MODE).await?;
+        let service = JobService::new(pool);
+
+        let job = service
+            .create(work_order_id, equipment_id, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(job.job_status, JobStatus::Planned);
+
+        let err = service
+            .transition(job.job_id, JobTransition::Start, None)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("cannot start a job that is planned")
+        );
+
+        for transition in [
+            JobTransition::Release,
+            JobTransition::Start,
+            JobTransition::Pause,
+            JobTransition::Resume,
+            JobTransition::Complete,
+        ] {
+            service
+                .transition(job.job_id, transition, Some("operator"))
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let job = service
+            .get_by_id(job.job_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(job.job_status, JobStatus::Completed);
+        assert!(job.released_at.is_some());
+        assert!(job.started_at.is_some());
+        assert!(job.completed_at.is_some());
+        assert!(job.cancelled_at.is_none());
+
+        let history = service
+            .get_status_history(job.job_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[2].from_status, JobStatus::Running);
+        assert_eq!(history[2].to_status, JobStatus::Paused);
+
+        // final jobs cannot move or be updated anymore
+        let err = service
+            .transition(job.job_id, JobTransition::Cancel, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid transition"));
+        let err = service
+            .update(job.job_id, equipment_id, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be updated"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_start_requires_running_equipment(pool: PgPool) -> sqlx::Result<()> {
+        let work_order_id = create_work_order(&pool).await?;
+        let equipment_id = create_equipment(&pool, "Acme").await?;
+        let service = JobService::new(pool.clone());
+
+        let job = service
+            .create(work_order_id, equipment_id, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        service
+            .transition(job.job_id, JobTransition::Release, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let err = service
+            .transition(job.job_id, JobTransition::Start, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("has no mode set"));
+
+        set_seeded_mode(&pool, equipment_id, "idle").await?;
+        let err = service
+            .transition(job.job_id, JobTransition::Start, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("in mode 'idle'"));
+
+        set_seeded_mode(&pool, equipment_id, PRODUCTION_MODE).await?;
+        sqlx::query!(
+            "UPDATE core.equipment SET equipment_enabled = false WHERE equipment_id = $1",
+            equipment_id
+        )
+        .execute(&pool)
+        .await?;
+        let err = service
+            .transition(job.job_id, JobTransition::Start, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("is disabled"));
+
+        // nothing was recorded for the rejected starts
+        let job = service
+            .get_by_id(job.job_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(job.job_status, JobStatus::Released);
+        assert!(job.started_at.is_none());
+
+        Ok(())
+    }
+}
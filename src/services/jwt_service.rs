@@ -0,0 +1,368 @@
+use crate::config::OidcConfig;
+use crate::error::{AppError, AppResult};
+use crate::models::app::{Principal, PrincipalKind, Scope};
+use anyhow::Context;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, instrument, warn};
+use url::Url;
+
+/// Shortest time between two JWKS downloads, tokens with an unknown `kid` trigger one
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub enum JwksSource {
+    File(PathBuf),
+    Url(Url),
+}
+
+impl JwksSource {
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            let url =
+                Url::parse(source).with_context(|| format!("invalid JWKS url '{}'", source))?;
+            Ok(JwksSource::Url(url))
+        } else {
+            Ok(JwksSource::File(PathBuf::from(source)))
+        }
+    }
+}
+
+/// The claims we read from an access token, the registered ones are checked by
+/// jsonwebtoken
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    preferred_username: Option<String>,
+    email: Option<String>,
+    name: Option<String>,
+    /// space separated, the usual OAuth form
+    scope: Option<String>,
+    /// list form used by some providers
+    scp: Option<Vec<String>>,
+    roles: Option<Vec<String>>,
+}
+
+impl Claims {
+    /// Only read, write and admin mean something here, other scopes and roles are ignored
+    fn scopes(&self) -> Vec<Scope> {
+        let mut scopes: Vec<Scope> = self
+            .scope
+            .iter()
+            .flat_map(|scope| scope.split_whitespace())
+            .chain(self.scp.iter().flatten().map(String::as_str))
+            .chain(self.roles.iter().flatten().map(String::as_str))
+            .filter_map(|s| s.parse().ok())
+            .collect();
+        scopes.sort();
+        scopes.dedup();
+        scopes
+    }
+}
+
+/// Verifies access tokens issued by the configured OpenID Connect provider
+#[derive(Debug, Clone)]
+pub struct JwtService {
+    inner: Arc<JwtInner>,
+}
+
+#[derive(Debug)]
+struct JwtInner {
+    issuer: String,
+    audience: Option<String>,
+    source: JwksSource,
+    keys: RwLock<JwkSet>,
+    last_refresh: Mutex<Instant>,
+    client: reqwest::Client,
+}
+
+impl JwtService {
+    /// Loads the signing keys, None when no issuer is configured
+    pub async fn from_config(config: &OidcConfig) -> anyhow::Result<Option<Self>> {
+        let (Some(issuer), Some(jwks)) = (&config.oidc_issuer, &config.oidc_jwks) else {
+            return Ok(None);
+        };
+
+        let service = Self::new(
+            issuer,
+            config.oidc_audience.as_deref(),
+            JwksSource::parse(jwks)?,
+        )
+        .await?;
+        Ok(Some(service))
+    }
+
+    pub async fn new(
+        issuer: &str,
+        audience: Option<&str>,
+        source: JwksSource,
+    ) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("failed to create the JWKS http client")?;
+        let keys = Self::fetch_keys(&client, &source).await?;
+        debug!("Loaded {} signing keys for {}", keys.keys.len(), issuer);
+
+        Ok(Self {
+            inner: Arc::new(JwtInner {
+                issuer: issuer.to_string(),
+                audience: audience.map(str::to_string),
+                source,
+                keys: RwLock::new(keys),
+                last_refresh: Mutex::new(Instant::now()),
+                client,
+            }),
+        })
+    }
+
+    async fn fetch_keys(client: &reqwest::Client, source: &JwksSource) -> anyhow::Result<JwkSet> {
+        match source {
+            JwksSource::File(path) => {
+                let json = tokio::fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("failed to read JWKS file {}", path.display()))?;
+                serde_json::from_str(&json)
+                    .with_context(|| format!("invalid JWKS file {}", path.display()))
+            }
+            JwksSource::Url(url) => client
+                .get(url.clone())
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .with_context(|| format!("failed to download JWKS from {}", url))?
+                .json()
+                .await
+                .with_context(|| format!("invalid JWKS at {}", url)),
+        }
+    }
+
+    /// Providers rotate their keys, so a `kid` we don't know yet is looked up again.
+    /// At most once per JWKS_REFRESH_INTERVAL, unknown kids are cheap to make up.
+    async fn refresh_keys(&self) -> bool {
+        let mut last_refresh = self.inner.last_refresh.lock().await;
+        if last_refresh.elapsed() < JWKS_REFRESH_INTERVAL {
+            return false;
+        }
+        *last_refresh = Instant::now();
+
+        match Self::fetch_keys(&self.inner.client, &self.inner.source).await {
+            Ok(keys) => {
+                debug!("Reloaded {} signing keys", keys.keys.len());
+                *self.inner.keys.write().await = keys;
+                true
+            }
+            Err(e) => {
+                warn!("Failed to reload the signing keys: {:#}", e);
+                false
+            }
+        }
+    }
+
+    async fn find_key(&self, kid: Option<&str>) -> Option<Jwk> {
+        let keys = self.inner.keys.read().await;
+        match kid {
+            Some(kid) => keys.find(kid).cloned(),
+            // without a kid the key has to be unambiguous
+            None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+            None => None,
+        }
+    }
+
+    /// The principal for a valid access token
+    #[instrument(skip_all)]
+    pub async fn verify(&self, token: &str) -> AppResult<Principal> {
+        let header = decode_header(token).map_err(|e| Self::rejected(e.kind()))?;
+
+        // only asymmetric algorithms, a public key must never be usable as an hmac secret
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(AppError::Unauthorized(
+                "token is signed with an unsupported algorithm".to_string(),
+            ));
+        }
+
+        let kid = header.kid.as_deref();
+        let jwk = match self.find_key(kid).await {
+            Some(jwk) => Some(jwk),
+            None if self.refresh_keys().await => self.find_key(kid).await,
+            None => None,
+        }
+        .ok_or_else(|| AppError::Unauthorized("token is signed with an unknown key".to_string()))?;
+        if matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)) {
+            return Err(AppError::Unauthorized(
+                "token is signed with an unsupported key".to_string(),
+            ));
+        }
+        let key = DecodingKey::from_jwk(&jwk).map_err(|e| Self::rejected(e.kind()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.inner.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+        match &self.inner.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = decode::<Claims>(token, &key, &validation)
+            .map_err(|e| Self::rejected(e.kind()))?
+            .claims;
+
+        let scopes = claims.scopes();
+        let name = claims
+            .preferred_username
+            .or(claims.email)
+            .or(claims.name)
+            .unwrap_or_else(|| claims.sub.clone());
+        Ok(Principal {
+            subject: claims.sub,
+            name,
+            kind: PrincipalKind::User,
+            scopes,
+        })
+    }
+
+    fn rejected(kind: &ErrorKind) -> AppError {
+        debug!("Rejected token: {:?}", kind);
+        let message = match kind {
+            ErrorKind::ExpiredSignature => "token has expired",
+            ErrorKind::ImmatureSignature => "token is not valid yet",
+            ErrorKind::InvalidIssuer => "token is from an unknown issuer",
+            ErrorKind::InvalidAudience => "token is meant for another audience",
+            ErrorKind::MissingRequiredClaim(_) => "token is missing a required claim",
+            _ => "invalid token",
+        };
+        AppError::Unauthorized(message.to_string())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use serde_json::{Value, json};
+    use tempfile::NamedTempFile;
+
+    pub const ISSUER: &str = "https://login.example.com/realms/plant";
+
+    /// An Ed25519 key generated for the test, with its public half in a JWKS file
+    pub struct TestIssuer {
+        pub jwks: NamedTempFile,
+        key: EncodingKey,
+    }
+
+    impl TestIssuer {
+        pub fn new() -> Self {
+            let rng = ring::rand::SystemRandom::new();
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+            let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+
+            let jwks = NamedTempFile::new().unwrap();
+            let set = json!({"keys": [{
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "use": "sig",
+                "alg": "EdDSA",
+                "kid": "test",
+                "x": URL_SAFE_NO_PAD.encode(pair.public_key().as_ref()),
+            }]});
+            std::fs::write(jwks.path(), set.to_string()).unwrap();
+
+            Self {
+                jwks,
+                key: EncodingKey::from_ed_der(pkcs8.as_ref()),
+            }
+        }
+
+        pub async fn service(&self) -> JwtService {
+            JwtService::new(
+                ISSUER,
+                Some("gatherer"),
+                JwksSource::File(self.jwks.path().to_path_buf()),
+            )
+            .await
+            .unwrap()
+        }
+
+        /// A token valid for an hour, `claims` are added to or override the defaults
+        pub fn token(&self, claims: Value) -> String {
+            let exp = jsonwebtoken::get_current_timestamp() + 3600;
+            let mut all = json!({
+                "iss": ISSUER,
+                "aud": "gatherer",
+                "sub": "3b241101-e2bb-4255-8caf-4136c566a962",
+                "exp": exp,
+            });
+            for (name, value) in claims.as_object().unwrap() {
+                all[name] = value.clone();
+            }
+
+            let mut header = Header::new(Algorithm::EdDSA);
+            header.kid = Some("test".to_string());
+            encode(&header, &all, &self.key).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::TestIssuer;
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_verify_access_tokens() {
+        let issuer = TestIssuer::new();
+        let service = issuer.service().await;
+
+        let token = issuer.token(json!({
+            "preferred_username": "jdoe",
+            "scope": "openid profile write",
+            "roles": ["offline_access", "read"],
+        }));
+        let principal = service.verify(&token).await.unwrap();
+        assert_eq!(principal.name, "jdoe");
+        assert_eq!(principal.kind, PrincipalKind::User);
+        assert_eq!(principal.scopes, vec![Scope::Read, Scope::Write]);
+
+        let rejected = [
+            (json!({"exp": 1_000_000}), "token has expired"),
+            (
+                json!({"iss": "https://evil.example.com"}),
+                "token is from an unknown issuer",
+            ),
+            (
+                json!({"aud": "someone-else"}),
+                "token is meant for another audience",
+            ),
+        ];
+        for (claims, message) in rejected {
+            match service.verify(&issuer.token(claims)).await {
+                Err(AppError::Unauthorized(m)) => assert_eq!(m, message),
+                other => panic!("expected {}, got {:?}", message, other),
+            }
+        }
+
+        // signed by a key the issuer doesn't publish
+        let other = TestIssuer::new();
+        assert!(matches!(
+            service.verify(&other.token(json!({}))).await,
+            Err(AppError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            service.verify("not.a.token").await,
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+}
@@ -1,3 +1,21 @@
+pub mod api_key_service;
+pub mod audit_service;
+pub mod equipment_mode_service;
+pub mod equipment_service;
+pub mod equipment_state_service;
+pub mod equipment_type_rule_service;
 pub mod equipment_type_service;
+pub mod event_service;
+pub mod job_service;
+pub mod jwt_service;
 pub mod mode_group_service;
 pub mod mode_service;
+pub mod oee_service;
+pub mod plant_model_service;
+pub mod product_service;
+pub mod production_count_service;
+pub mod purge_service;
+pub mod state_code_reservation_service;
+pub mod state_group_service;
+pub mod state_service;
+pub mod work_order_service;
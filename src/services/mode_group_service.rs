@@ -1,6 +1,11 @@
+use crate::database::audit::AuditQueries;
+use crate::database::equipment::EquipmentQueries;
 use crate::database::mode_groups::{ModeGroupQueries, ModeGroupRow};
-use anyhow::{Context, Result, anyhow};
-use sqlx::PgPool;
+use crate::error::{AppError, AppResult, DatabaseContext, is_unique_violation};
+use crate::models::core::{
+    BulkGroupAssignment, EquipmentModeGroupMapping, GroupEquipment, ModeGroupUsageStats,
+};
+use sqlx::{PgPool, Postgres, Transaction};
 use time::OffsetDateTime;
 use tracing::{debug, instrument};
 use uuid::Uuid;
@@ -29,15 +34,31 @@ impl From<ModeGroupRow> for ModeGroup {
 #[derive(Debug, Clone)]
 pub struct ModeGroupService {
     db: PgPool,
+    actor: Option<String>,
 }
 
 impl ModeGroupService {
     pub fn new(db: PgPool) -> Self {
-        Self { db }
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
     }
 
     #[instrument(skip(self))]
-    pub async fn get_all(&self) -> Result<Vec<ModeGroup>> {
+    pub async fn get_all(&self) -> AppResult<Vec<ModeGroup>> {
         debug!("Fetching all mode groups");
         let rows = ModeGroupQueries::get_all(&self.db)
             .await
@@ -48,19 +69,21 @@ impl ModeGroupService {
     }
 
     #[instrument(skip(self), fields(mode_group_id = %mode_group_id))]
-    pub async fn get_by_id(&self, mode_group_id: Uuid) -> Result<ModeGroup> {
+    pub async fn get_by_id(&self, mode_group_id: Uuid) -> AppResult<ModeGroup> {
         debug!("Fetching mode group by ID");
         let row = ModeGroupQueries::get_by_mode_group_id(&self.db, mode_group_id)
             .await
             .context("Failed to fetch mode group by ID")?
-            .ok_or_else(|| anyhow!("Mode group with ID {} not found", mode_group_id))?;
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Mode group with ID {} not found", mode_group_id))
+            })?;
 
         debug!("Found mode group: {}", row.mode_group_name);
         Ok(ModeGroup::from(row))
     }
 
     #[instrument(skip(self), fields(mode_group_name = %mode_group_name))]
-    pub async fn get_by_name(&self, mode_group_name: &str) -> Result<Option<ModeGroup>> {
+    pub async fn get_by_name(&self, mode_group_name: &str) -> AppResult<Option<ModeGroup>> {
         debug!("Fetching mode group by name");
         let row = ModeGroupQueries::get_by_mode_group_name(&self.db, mode_group_name)
             .await
@@ -82,7 +105,7 @@ impl ModeGroupService {
     pub async fn get_by_description(
         &self,
         mode_group_description: &str,
-    ) -> Result<Option<ModeGroup>> {
+    ) -> AppResult<Option<ModeGroup>> {
         debug!("Fetching mode group by description");
         let row = ModeGroupQueries::get_by_mode_group_description(&self.db, mode_group_description)
             .await
@@ -105,21 +128,14 @@ impl ModeGroupService {
         &self,
         mode_group_name: &str,
         mode_group_description: &str,
-    ) -> Result<ModeGroup> {
+    ) -> AppResult<ModeGroup> {
         debug!("Creating new mode group");
 
+        let mut tx = self.begin().await?;
         let row =
-            ModeGroupQueries::create_mode_group(&self.db, mode_group_name, mode_group_description)
-                .await
-                .map_err(|e| {
-                    // Check if it's a duplicate error and preserve the original message
-                    if e.to_string().contains("already exists") {
-                        e // Return the original error to preserve "already exists" message
-                    } else {
-                        // For other errors, add context
-                        e.context(format!("Failed to create mode group '{}'", mode_group_name))
-                    }
-                })?;
+            ModeGroupQueries::create_mode_group(&mut tx, mode_group_name, mode_group_description)
+                .await?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         debug!("Successfully created mode group: {}", row.mode_group_name);
         Ok(ModeGroup::from(row))
@@ -130,14 +146,16 @@ impl ModeGroupService {
         &self,
         mode_group_id: Uuid,
         mode_group_name: &str,
-    ) -> Result<ModeGroup> {
+    ) -> AppResult<ModeGroup> {
         debug!("Updating mode group name");
 
-        let row =
-            ModeGroupQueries::update_mode_group_name(&self.db, mode_group_id, mode_group_name)
-                .await
-                .with_context(|| format!("Failed to update mode group name for {}", mode_group_id))?
-                .ok_or_else(|| anyhow!("Mode group with ID {} not found", mode_group_id))?;
+        let mut tx = self.begin().await?;
+        let row = ModeGroupQueries::update_mode_group_name(&mut tx, mode_group_id, mode_group_name)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Mode group with ID {} not found", mode_group_id))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         debug!(
             "Successfully updated mode group name: {}",
@@ -151,44 +169,48 @@ impl ModeGroupService {
         &self,
         mode_group_id: Uuid,
         mode_group_description: &str,
-    ) -> Result<ModeGroup> {
+    ) -> AppResult<ModeGroup> {
         debug!("Updating mode group description");
 
+        let mut tx = self.begin().await?;
         let row = ModeGroupQueries::update_mode_group_description(
-            &self.db,
+            &mut tx,
             mode_group_id,
             mode_group_description,
         )
-        .await
-        .with_context(|| {
-            format!(
-                "Failed to update mode group description for {}",
-                mode_group_id
-            )
-        })?
-        .ok_or_else(|| anyhow!("Mode group with ID {} not found", mode_group_id))?;
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("Mode group with ID {} not found", mode_group_id))
+        })?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         debug!("Successfully updated mode group description");
         Ok(ModeGroup::from(row))
     }
 
     #[instrument(skip(self), fields(mode_group_id = %mode_group_id))]
-    pub async fn delete(&self, mode_group_id: Uuid) -> Result<()> {
+    pub async fn delete(&self, mode_group_id: Uuid) -> AppResult<()> {
         debug!("Deleting mode group");
 
-        // TODO: Add check to see if mode group is in use
-        // This would prevent deletion of groups that are actively used
-        // Example:
-        // if self.is_in_use(mode_group_id).await? {
-        //     return Err(anyhow!("Mode group is in use and cannot be deleted"));
-        // }
-
-        let deleted = ModeGroupQueries::delete_mode_group(&self.db, mode_group_id)
+        if ModeGroupQueries::is_in_use(&self.db, mode_group_id)
             .await
-            .with_context(|| format!("Failed to delete mode group {}", mode_group_id))?;
+            .context("Failed to check mode group usage")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Mode group {} is in use and cannot be deleted",
+                mode_group_id
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        let deleted = ModeGroupQueries::delete_mode_group(&mut tx, mode_group_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         if !deleted {
-            return Err(anyhow!("Mode group with ID {} not found", mode_group_id));
+            return Err(AppError::NotFound(format!(
+                "Mode group with ID {} not found",
+                mode_group_id
+            )));
         }
 
         debug!("Successfully deleted mode group");
@@ -196,7 +218,152 @@ impl ModeGroupService {
     }
 
     #[instrument(skip(self), fields(mode_group_id = %mode_group_id))]
-    pub async fn exists(&self, mode_group_id: Uuid) -> Result<bool> {
+    pub async fn restore(&self, mode_group_id: Uuid) -> AppResult<ModeGroup> {
+        debug!("Restoring mode group");
+
+        let mut tx = self.begin().await?;
+        let row = ModeGroupQueries::restore_mode_group(&mut tx, mode_group_id)
+            .await
+            .map_err(|e| {
+                if is_unique_violation(&e) {
+                    AppError::Conflict(format!(
+                        "Mode group {} can't be restored, its name is taken",
+                        mode_group_id
+                    ))
+                } else {
+                    AppError::database(format!("Failed to restore mode group {}", mode_group_id), e)
+                }
+            })?
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Deleted mode group with ID {} not found",
+                    mode_group_id
+                ))
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully restored mode group: {}", row.mode_group_name);
+        Ok(ModeGroup::from(row))
+    }
+
+    /// Maps the equipment to the mode group, its modes can then be set on the equipment
+    #[instrument(skip(self), fields(mode_group_id = %mode_group_id, equipment_id = %equipment_id))]
+    pub async fn assign_equipment(
+        &self,
+        mode_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> AppResult<EquipmentModeGroupMapping> {
+        debug!("Assigning equipment to mode group");
+
+        let mut tx = self.begin().await?;
+        ModeGroupQueries::assign_equipment(&mut tx, mode_group_id, equipment_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully assigned equipment to mode group");
+        Ok(EquipmentModeGroupMapping {
+            equipment_id,
+            mode_group_id,
+        })
+    }
+
+    #[instrument(skip(self), fields(mode_group_id = %mode_group_id, equipment_id = %equipment_id))]
+    pub async fn unassign_equipment(
+        &self,
+        mode_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> AppResult<EquipmentModeGroupMapping> {
+        debug!("Unassigning equipment from mode group");
+
+        let mut tx = self.begin().await?;
+        ModeGroupQueries::unassign_equipment(&mut tx, mode_group_id, equipment_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully unassigned equipment from mode group");
+        Ok(EquipmentModeGroupMapping {
+            equipment_id,
+            mode_group_id,
+        })
+    }
+
+    /// Maps all the equipment to the mode group, it is a conflict when every one of them
+    /// already is
+    #[instrument(skip(self, equipment_ids), fields(mode_group_id = %mode_group_id, count = equipment_ids.len()))]
+    pub async fn bulk_assign_equipment(
+        &self,
+        mode_group_id: Uuid,
+        equipment_ids: &[Uuid],
+    ) -> AppResult<BulkGroupAssignment> {
+        debug!("Bulk assigning equipment to mode group");
+
+        let mut tx = self.begin().await?;
+        let assignment =
+            ModeGroupQueries::bulk_assign_equipment(&mut tx, mode_group_id, equipment_ids).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!(
+            "Successfully assigned {} equipment to mode group, {} already were",
+            assignment.assigned_equipment_ids.len(),
+            assignment.skipped_equipment_ids.len()
+        );
+        Ok(assignment)
+    }
+
+    /// Live equipment mapped to the mode group
+    #[instrument(skip(self), fields(mode_group_id = %mode_group_id))]
+    pub async fn get_equipment(&self, mode_group_id: Uuid) -> AppResult<Vec<GroupEquipment>> {
+        debug!("Fetching equipment for mode group");
+
+        let equipment = ModeGroupQueries::get_equipment(&self.db, mode_group_id).await?;
+
+        debug!("Found {} equipment for mode group", equipment.len());
+        Ok(equipment)
+    }
+
+    /// Mode and equipment counts per group and the time spent in its modes between `from`
+    /// and `to`, for one group when `mode_group_id` is given
+    #[instrument(skip(self))]
+    pub async fn usage_stats(
+        &self,
+        mode_group_id: Option<Uuid>,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> AppResult<ModeGroupUsageStats> {
+        debug!("Fetching mode group usage stats");
+
+        let stats = ModeGroupQueries::get_usage_stats(&self.db, from, to, mode_group_id).await?;
+
+        debug!(
+            "{} of {} mode groups were idle",
+            stats.summary.idle_mode_groups, stats.summary.total_mode_groups
+        );
+        Ok(stats)
+    }
+
+    /// Live mode groups the equipment is mapped to
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_by_equipment_id(&self, equipment_id: Uuid) -> AppResult<Vec<ModeGroup>> {
+        debug!("Fetching mode groups for equipment");
+
+        if !EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check if equipment exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+
+        let rows = ModeGroupQueries::get_by_equipment_id(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch mode groups for equipment")?;
+        let groups: Vec<ModeGroup> = rows.into_iter().map(ModeGroup::from).collect();
+        debug!("Found {} mode groups for equipment", groups.len());
+        Ok(groups)
+    }
+
+    #[instrument(skip(self), fields(mode_group_id = %mode_group_id))]
+    pub async fn exists(&self, mode_group_id: Uuid) -> AppResult<bool> {
         let exists = ModeGroupQueries::exists(&self.db, mode_group_id)
             .await
             .context("Failed to check if mode group exists")?;
@@ -206,32 +373,38 @@ impl ModeGroupService {
 
     /// Get mode groups with pagination
     #[instrument(skip(self))]
-    pub async fn get_paginated(&self, offset: i64, limit: i64) -> Result<(Vec<ModeGroup>, i64)> {
+    pub async fn get_paginated(&self, offset: i64, limit: i64) -> AppResult<(Vec<ModeGroup>, i64)> {
         debug!(
             "Fetching mode groups with pagination: offset={}, limit={}",
             offset, limit
         );
 
         if offset < 0 {
-            return Err(anyhow!("Offset cannot be negative"));
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
         }
 
         if limit <= 0 || limit > 1000 {
-            return Err(anyhow!("Limit must be between 1 and 1000"));
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
         }
 
         // Get total count
-        let total_count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode_group")
-            .fetch_one(&self.db)
-            .await
-            .context("Failed to get total count of mode groups")?
-            .unwrap_or(0);
+        let total_count =
+            sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode_group WHERE deleted_at IS NULL")
+                .fetch_one(&self.db)
+                .await
+                .context("Failed to get total count of mode groups")?
+                .unwrap_or(0);
 
         // Get paginated results
         let rows = sqlx::query_as!(
             ModeGroupRow,
             r#"SELECT mode_group_id, mode_group_name, mode_group_description, created_at, updated_at
                FROM core.mode_group
+               WHERE deleted_at IS NULL
                ORDER BY mode_group_name
                LIMIT $1 OFFSET $2"#,
             limit,
@@ -257,18 +430,20 @@ impl ModeGroupService {
         &self,
         start_date: OffsetDateTime,
         end_date: OffsetDateTime,
-    ) -> Result<Vec<ModeGroup>> {
+    ) -> AppResult<Vec<ModeGroup>> {
         debug!("Fetching mode groups by date range");
 
         if start_date > end_date {
-            return Err(anyhow!("Start date cannot be after end date"));
+            return Err(AppError::Validation(
+                "Start date cannot be after end date".to_string(),
+            ));
         }
 
         let rows = sqlx::query_as!(
             ModeGroupRow,
             r#"SELECT mode_group_id, mode_group_name, mode_group_description, created_at, updated_at
                FROM core.mode_group
-               WHERE created_at >= $1 AND created_at <= $2
+               WHERE created_at >= $1 AND created_at <= $2 AND deleted_at IS NULL
                ORDER BY created_at DESC"#,
             start_date,
             end_date
@@ -284,12 +459,13 @@ impl ModeGroupService {
 
     /// Get count of all mode groups
     #[instrument(skip(self))]
-    pub async fn count(&self) -> Result<i64> {
-        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode_group")
-            .fetch_one(&self.db)
-            .await
-            .context("Failed to count mode groups")?
-            .unwrap_or(0);
+    pub async fn count(&self) -> AppResult<i64> {
+        let count =
+            sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode_group WHERE deleted_at IS NULL")
+                .fetch_one(&self.db)
+                .await
+                .context("Failed to count mode groups")?
+                .unwrap_or(0);
 
         debug!("Total mode groups count: {}", count);
         Ok(count)
@@ -297,7 +473,7 @@ impl ModeGroupService {
 
     /// Bulk create mode groups (useful for initial setup or imports)
     #[instrument(skip(self, mode_groups))]
-    pub async fn bulk_create(&self, mode_groups: Vec<(&str, &str)>) -> Result<Vec<ModeGroup>> {
+    pub async fn bulk_create(&self, mode_groups: Vec<(&str, &str)>) -> AppResult<Vec<ModeGroup>> {
         debug!("Bulk creating {} mode groups", mode_groups.len());
         let mut created_groups = Vec::new();
         let mut errors = Vec::new();
@@ -307,7 +483,7 @@ impl ModeGroupService {
                 Ok(mode_group) => {
                     created_groups.push(mode_group);
                 }
-                Err(e) if e.to_string().contains("already exists") => {
+                Err(AppError::Conflict(_)) => {
                     // Skip duplicates, optionally log warning
                     debug!("Skipping duplicate mode group: {}", name);
                 }
@@ -318,32 +494,15 @@ impl ModeGroupService {
         }
 
         if !errors.is_empty() {
-            return Err(anyhow!(
+            return Err(AppError::Validation(format!(
                 "Bulk create failed with errors: {}",
                 errors.join(", ")
-            ));
+            )));
         }
 
         debug!("Successfully created {} mode groups", created_groups.len());
         Ok(created_groups)
     }
-
-    // TODO: Implement this method to check if a mode group is in use
-    // #[instrument(skip(self), fields(mode_group_id = %mode_group_id))]
-    // pub async fn is_in_use(&self, mode_group_id: Uuid) -> Result<bool> {
-    //     // This would check if the mode group is referenced in other tables
-    //     // Example: modes, equipment_modes, etc.
-    //     // let count = sqlx::query_scalar!(
-    //     //     "SELECT COUNT(*) FROM modes WHERE mode_group_id = $1",
-    //     //     mode_group_id
-    //     // )
-    //     // .fetch_one(&self.db)
-    //     // .await
-    //     // .context("Failed to check mode group usage")?;
-    //     //
-    //     // Ok(count.unwrap_or(0) > 0)
-    //     Ok(false)
-    // }
 }
 
 #[cfg(test)]
@@ -498,4 +657,169 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn test_service_equipment_assignment(pool: PgPool) -> sqlx::Result<()> {
+        let service = ModeGroupService::new(pool.clone()).acting_as(Some("jdoe".to_string()));
+        let equipment = crate::services::equipment_service::EquipmentService::new(pool.clone());
+
+        let enterprise_type = sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = 'enterprise'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let acme = equipment
+            .create("Acme", enterprise_type, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let globex = equipment
+            .create("Globex", enterprise_type, None, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let group = service
+            .create("Packaging", "Packaging modes")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let mapping = service
+            .assign_equipment(group.mode_group_id, acme.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(mapping.equipment_id, acme.equipment_id);
+
+        let result = service
+            .assign_equipment(group.mode_group_id, acme.equipment_id)
+            .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        let result = service
+            .assign_equipment(Uuid::new_v4(), acme.equipment_id)
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        let assignment = service
+            .bulk_assign_equipment(
+                group.mode_group_id,
+                &[acme.equipment_id, globex.equipment_id, Uuid::new_v4()],
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(assignment.assigned_equipment_ids, vec![globex.equipment_id]);
+        assert_eq!(assignment.skipped_equipment_ids, vec![acme.equipment_id]);
+
+        let listed = service
+            .get_equipment(group.mode_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let names: Vec<&str> = listed.iter().map(|e| e.equipment_name.as_str()).collect();
+        assert_eq!(names, vec!["Acme", "Globex"]);
+        assert_eq!(listed[0].equipment_type_name, "enterprise");
+
+        let groups = service
+            .get_by_equipment_id(globex.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].mode_group_id, group.mode_group_id);
+
+        // deleted equipment drops out of the list and can't be assigned
+        equipment
+            .delete(globex.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let listed = service
+            .get_equipment(group.mode_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(listed.len(), 1);
+        let result = service
+            .unassign_equipment(group.mode_group_id, globex.equipment_id)
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        service
+            .unassign_equipment(group.mode_group_id, acme.equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let result = service
+            .unassign_equipment(group.mode_group_id, acme.equipment_id)
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        let actors = sqlx::query_scalar!(
+            r#"SELECT actor as "actor!" FROM app.audit_log
+               WHERE entity_type = 'equipment_mode_group' AND entity_id = $1
+               ORDER BY occurred_at"#,
+            acme.equipment_id
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(actors, vec!["jdoe", "jdoe"]);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_delete_and_restore(pool: PgPool) -> sqlx::Result<()> {
+        let service = ModeGroupService::new(pool.clone());
+        let modes = crate::services::mode_service::ModeService::new(pool);
+
+        let group = service
+            .create("Packaging", "Packaging modes")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let mode = modes
+            .create(group.mode_group_id, "Changeover")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        // a group with live modes is in use
+        let result = service.delete(group.mode_group_id).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        modes
+            .delete(mode.mode_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        service
+            .delete(group.mode_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(matches!(
+            service.get_by_id(group.mode_group_id).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        // the mode can't come back before its group
+        let result = modes.restore(mode.mode_id).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        // the name of a deleted group can be reused, which blocks restoring it
+        let replacement = service
+            .create("Packaging", "Replacement")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let result = service.restore(group.mode_group_id).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        service
+            .update_name(replacement.mode_group_id, "Packaging (new)")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let restored = service
+            .restore(group.mode_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(restored.mode_group_name, "Packaging");
+        modes
+            .restore(mode.mode_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        // nothing left to restore
+        let result = service.restore(group.mode_group_id).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
 }
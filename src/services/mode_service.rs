@@ -1,5 +1,8 @@
-use anyhow::{Context, anyhow};
-use sqlx::PgPool;
+use crate::database::audit::AuditQueries;
+use crate::database::mode_groups::ModeGroupQueries;
+use crate::error::{AppError, AppResult, DatabaseContext, is_unique_violation};
+use crate::models::core::ModeUsageStats;
+use sqlx::{PgPool, Postgres, Transaction};
 use time::OffsetDateTime;
 use tracing::{debug, error, instrument};
 use uuid::Uuid;
@@ -30,15 +33,31 @@ impl From<ModeRow> for Mode {
 #[derive(Clone)]
 pub struct ModeService {
     db: PgPool,
+    actor: Option<String>,
 }
 
 impl ModeService {
     pub fn new(db: PgPool) -> Self {
-        Self { db }
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
     }
 
     #[instrument(skip(self))]
-    pub async fn get_all(&self) -> anyhow::Result<Vec<Mode>> {
+    pub async fn get_all(&self) -> AppResult<Vec<Mode>> {
         debug!("Fetching all modes");
 
         let rows = ModeRowQueries::get_all(&self.db)
@@ -52,7 +71,7 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(offset = %offset, limit = %limit))]
-    pub async fn get_paginated(&self, offset: i64, limit: i64) -> anyhow::Result<(Vec<Mode>, i64)> {
+    pub async fn get_paginated(&self, offset: i64, limit: i64) -> AppResult<(Vec<Mode>, i64)> {
         debug!(
             "Fetching paginated modes: offset={}, limit={}",
             offset, limit
@@ -60,24 +79,30 @@ impl ModeService {
 
         // Validate pagination parameters
         if offset < 0 {
-            return Err(anyhow!("Offset cannot be negative"));
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
         }
         if limit <= 0 || limit > 1000 {
-            return Err(anyhow!("Limit must be between 1 and 1000"));
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
         }
 
         // Get total count
-        let total_count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode")
-            .fetch_one(&self.db)
-            .await
-            .context("Failed to get total mode count")?
-            .unwrap_or(0);
+        let total_count =
+            sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode WHERE deleted_at IS NULL")
+                .fetch_one(&self.db)
+                .await
+                .context("Failed to get total mode count")?
+                .unwrap_or(0);
 
         // Get paginated results
         let rows = sqlx::query_as!(
             ModeRow,
             r#"SELECT mode_id, mode_group_id, mode_description, created_at, updated_at
                FROM core.mode
+               WHERE deleted_at IS NULL
                ORDER BY mode_description
                LIMIT $1 OFFSET $2"#,
             limit,
@@ -94,13 +119,13 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(id = %mode_id))]
-    pub async fn get_by_id(&self, mode_id: Uuid) -> anyhow::Result<Mode> {
+    pub async fn get_by_id(&self, mode_id: Uuid) -> AppResult<Mode> {
         debug!("Fetching mode by id: {}", mode_id);
 
         let row = ModeRowQueries::get_by_mode_id(&self.db, mode_id)
             .await
             .context("Failed to fetch mode by id")?
-            .ok_or_else(|| anyhow!("Mode with id '{}' not found", mode_id))?;
+            .ok_or_else(|| AppError::NotFound(format!("Mode with id '{}' not found", mode_id)))?;
 
         let mode = Mode::from(row);
         debug!("Retrieved mode: {}", mode.mode_description);
@@ -108,7 +133,7 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(description = %mode_description))]
-    pub async fn get_by_description(&self, mode_description: &str) -> anyhow::Result<Option<Mode>> {
+    pub async fn get_by_description(&self, mode_description: &str) -> AppResult<Option<Mode>> {
         debug!("Fetching mode by description: {}", mode_description);
 
         let row = ModeRowQueries::get_by_mode_description(&self.db, mode_description)
@@ -129,12 +154,10 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(group_id = %mode_group_id))]
-    pub async fn get_by_mode_group_id(&self, mode_group_id: Uuid) -> anyhow::Result<Vec<Mode>> {
+    pub async fn get_by_mode_group_id(&self, mode_group_id: Uuid) -> AppResult<Vec<Mode>> {
         debug!("Fetching modes by mode_group_id: {}", mode_group_id);
 
-        let rows = ModeRowQueries::get_modes_for_group(&self.db, mode_group_id)
-            .await
-            .context("Failed to fetch modes by mode_group_id")?;
+        let rows = ModeRowQueries::get_modes_for_group(&self.db, mode_group_id).await?;
 
         let modes: Vec<Mode> = rows.into_iter().map(Mode::from).collect();
 
@@ -147,11 +170,13 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(search_term = %search_term))]
-    pub async fn search_by_description(&self, search_term: &str) -> anyhow::Result<Vec<Mode>> {
+    pub async fn search_by_description(&self, search_term: &str) -> AppResult<Vec<Mode>> {
         debug!("Searching modes by description: {}", search_term);
 
         if search_term.trim().is_empty() {
-            return Err(anyhow!("Search term cannot be empty"));
+            return Err(AppError::Validation(
+                "Search term cannot be empty".to_string(),
+            ));
         }
 
         let rows = ModeRowQueries::search_by_description(&self.db, search_term)
@@ -169,16 +194,12 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(group_id = %mode_group_id, description = %mode_description))]
-    pub async fn create(
-        &self,
-        mode_group_id: Uuid,
-        mode_description: &str,
-    ) -> anyhow::Result<Mode> {
+    pub async fn create(&self, mode_group_id: Uuid, mode_description: &str) -> AppResult<Mode> {
         debug!("Creating mode with description: {}", mode_description);
 
-        let row = ModeRowQueries::create_mode(&self.db, mode_group_id, mode_description)
-            .await
-            .context("Failed to create mode")?;
+        let mut tx = self.begin().await?;
+        let row = ModeRowQueries::create_mode(&mut tx, mode_group_id, mode_description).await?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         let mode = Mode::from(row);
         debug!("Successfully created mode: {}", mode.mode_description);
@@ -186,24 +207,27 @@ impl ModeService {
     }
 
     #[instrument(skip(self))]
-    pub async fn bulk_create(&self, mode_data: Vec<(Uuid, &str)>) -> anyhow::Result<Vec<Mode>> {
+    pub async fn bulk_create(&self, mode_data: Vec<(Uuid, &str)>) -> AppResult<Vec<Mode>> {
         debug!("Bulk creating {} modes", mode_data.len());
 
         if mode_data.is_empty() {
-            return Err(anyhow!("No modes provided for bulk creation"));
+            return Err(AppError::Validation(
+                "No modes provided for bulk creation".to_string(),
+            ));
         }
 
         if mode_data.len() > 100 {
-            return Err(anyhow!("Cannot create more than 100 modes at once"));
+            return Err(AppError::Validation(
+                "Cannot create more than 100 modes at once".to_string(),
+            ));
         }
 
         let mut created_modes = Vec::new();
 
-        // Use the pool directly for bulk operations since our queries expect &PgPool
         for (mode_group_id, mode_description) in mode_data {
-            match ModeRowQueries::create_mode(&self.db, mode_group_id, mode_description).await {
-                Ok(row) => {
-                    created_modes.push(Mode::from(row));
+            match self.create(mode_group_id, mode_description).await {
+                Ok(mode) => {
+                    created_modes.push(mode);
                 }
                 Err(e) => {
                     error!("Failed to create mode '{}': {}", mode_description, e);
@@ -221,13 +245,14 @@ impl ModeService {
         &self,
         mode_id: Uuid,
         mode_description: &str,
-    ) -> anyhow::Result<Mode> {
+    ) -> AppResult<Mode> {
         debug!("Updating mode description for id: {}", mode_id);
 
-        let row = ModeRowQueries::update_mode_description(&self.db, mode_id, mode_description)
-            .await
-            .context("Failed to update mode description")?
-            .ok_or_else(|| anyhow!("Mode with id '{}' not found", mode_id))?;
+        let mut tx = self.begin().await?;
+        let row = ModeRowQueries::update_mode_description(&mut tx, mode_id, mode_description)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Mode with id '{}' not found", mode_id)))?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         let mode = Mode::from(row);
         debug!(
@@ -238,17 +263,14 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(id = %mode_id, new_group_id = %mode_group_id))]
-    pub async fn update_mode_group(
-        &self,
-        mode_id: Uuid,
-        mode_group_id: Uuid,
-    ) -> anyhow::Result<Mode> {
+    pub async fn update_mode_group(&self, mode_id: Uuid, mode_group_id: Uuid) -> AppResult<Mode> {
         debug!("Updating mode group for id: {}", mode_id);
 
-        let row = ModeRowQueries::update_mode_group(&self.db, mode_id, mode_group_id)
-            .await
-            .context("Failed to update mode group")?
-            .ok_or_else(|| anyhow!("Mode with id '{}' not found", mode_id))?;
+        let mut tx = self.begin().await?;
+        let row = ModeRowQueries::update_mode_group(&mut tx, mode_id, mode_group_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Mode with id '{}' not found", mode_id)))?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         let mode = Mode::from(row);
         debug!(
@@ -259,26 +281,66 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(id = %mode_id))]
-    pub async fn delete(&self, mode_id: Uuid) -> anyhow::Result<()> {
+    pub async fn delete(&self, mode_id: Uuid) -> AppResult<()> {
         debug!("Deleting mode: {}", mode_id);
 
-        let deleted = ModeRowQueries::delete_mode(&self.db, mode_id)
-            .await
-            .context("Failed to delete mode")?;
+        let mut tx = self.begin().await?;
+        let deleted = ModeRowQueries::delete_mode(&mut tx, mode_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
 
         if !deleted {
-            return Err(anyhow!("Mode with id '{}' not found", mode_id));
+            return Err(AppError::NotFound(format!(
+                "Mode with id '{}' not found",
+                mode_id
+            )));
         }
 
         debug!("Successfully deleted mode: {}", mode_id);
         Ok(())
     }
 
+    /// A mode can only be restored into a mode group that is not deleted itself
+    #[instrument(skip(self), fields(id = %mode_id))]
+    pub async fn restore(&self, mode_id: Uuid) -> AppResult<Mode> {
+        debug!("Restoring mode: {}", mode_id);
+
+        let mut tx = self.begin().await?;
+        let row = ModeRowQueries::restore_mode(&mut tx, mode_id)
+            .await
+            .map_err(|e| {
+                if is_unique_violation(&e) {
+                    AppError::Conflict(format!(
+                        "Mode {} can't be restored, its description is taken in its mode group",
+                        mode_id
+                    ))
+                } else {
+                    AppError::database(format!("Failed to restore mode {}", mode_id), e)
+                }
+            })?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Deleted mode with id '{}' not found", mode_id))
+            })?;
+
+        if !ModeGroupQueries::exists(&self.db, row.mode_group_id)
+            .await
+            .context("Failed to check if mode group exists")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Mode {} can't be restored while its mode group {} is deleted",
+                mode_id, row.mode_group_id
+            )));
+        }
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully restored mode: {}", mode_id);
+        Ok(Mode::from(row))
+    }
+
     #[instrument(skip(self))]
-    pub async fn count(&self) -> anyhow::Result<i64> {
+    pub async fn count(&self) -> AppResult<i64> {
         debug!("Getting total mode count");
 
-        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode")
+        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.mode WHERE deleted_at IS NULL")
             .fetch_one(&self.db)
             .await
             .context("Failed to get mode count")?
@@ -289,7 +351,7 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(id = %mode_id))]
-    pub async fn exists(&self, mode_id: Uuid) -> anyhow::Result<bool> {
+    pub async fn exists(&self, mode_id: Uuid) -> AppResult<bool> {
         debug!("Checking if mode exists: {}", mode_id);
 
         let exists = ModeRowQueries::exists(&self.db, mode_id)
@@ -300,12 +362,32 @@ impl ModeService {
         Ok(exists)
     }
 
+    /// Time equipment spent in each mode between `from` and `to`, modes at 0 are candidates
+    /// for cleanup
+    #[instrument(skip(self))]
+    pub async fn usage_stats(
+        &self,
+        mode_id: Option<Uuid>,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> AppResult<ModeUsageStats> {
+        debug!("Fetching mode usage stats");
+
+        let stats = ModeRowQueries::get_usage_stats(&self.db, from, to, mode_id).await?;
+
+        debug!(
+            "{} of {} modes were unused",
+            stats.summary.unused_modes, stats.summary.total_modes
+        );
+        Ok(stats)
+    }
+
     #[instrument(skip(self), fields(group_id = %mode_group_id, description = %mode_description))]
     pub async fn description_exists_in_group(
         &self,
         mode_group_id: Uuid,
         mode_description: &str,
-    ) -> anyhow::Result<bool> {
+    ) -> AppResult<bool> {
         debug!(
             "Checking if description exists in group: {} - {}",
             mode_group_id, mode_description
@@ -324,11 +406,11 @@ impl ModeService {
     }
 
     #[instrument(skip(self), fields(group_id = %mode_group_id))]
-    pub async fn count_by_mode_group(&self, mode_group_id: Uuid) -> anyhow::Result<i64> {
+    pub async fn count_by_mode_group(&self, mode_group_id: Uuid) -> AppResult<i64> {
         debug!("Getting mode count for group: {}", mode_group_id);
 
         let count = sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM core.mode WHERE mode_group_id = $1",
+            "SELECT COUNT(*) FROM core.mode WHERE mode_group_id = $1 AND deleted_at IS NULL",
             mode_group_id
         )
         .fetch_one(&self.db)
@@ -346,7 +428,7 @@ impl ModeService {
         mode_group_id: Uuid,
         offset: i64,
         limit: i64,
-    ) -> anyhow::Result<(Vec<Mode>, i64)> {
+    ) -> AppResult<(Vec<Mode>, i64)> {
         debug!(
             "Fetching paginated modes for group: {}, offset={}, limit={}",
             mode_group_id, offset, limit
@@ -354,15 +436,19 @@ impl ModeService {
 
         // Validate pagination parameters
         if offset < 0 {
-            return Err(anyhow!("Offset cannot be negative"));
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
         }
         if limit <= 0 || limit > 1000 {
-            return Err(anyhow!("Limit must be between 1 and 1000"));
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
         }
 
         // Check if mode group exists
         let group_exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1 AND deleted_at IS NULL)",
             mode_group_id
         )
         .fetch_one(&self.db)
@@ -370,7 +456,10 @@ impl ModeService {
         .context("Failed to check if mode_group exists")?;
 
         if !group_exists.unwrap_or(false) {
-            return Err(anyhow!("mode_group_id '{}' does not exist", mode_group_id));
+            return Err(AppError::Validation(format!(
+                "mode_group_id '{}' does not exist",
+                mode_group_id
+            )));
         }
 
         // Get total count for this group
@@ -381,7 +470,7 @@ impl ModeService {
             ModeRow,
             r#"SELECT mode_id, mode_group_id, mode_description, created_at, updated_at
                FROM core.mode
-               WHERE mode_group_id = $1
+               WHERE mode_group_id = $1 AND deleted_at IS NULL
                ORDER BY mode_description
                LIMIT $2 OFFSET $3"#,
             mode_group_id,
@@ -405,11 +494,11 @@ impl ModeService {
 
     /// Validates that a mode group exists before performing operations
     #[instrument(skip(self), fields(group_id = %mode_group_id))]
-    pub async fn validate_mode_group_exists(&self, mode_group_id: Uuid) -> anyhow::Result<()> {
+    pub async fn validate_mode_group_exists(&self, mode_group_id: Uuid) -> AppResult<()> {
         debug!("Validating mode group exists: {}", mode_group_id);
 
         let exists = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1)",
+            "SELECT EXISTS(SELECT 1 FROM core.mode_group WHERE mode_group_id = $1 AND deleted_at IS NULL)",
             mode_group_id
         )
         .fetch_one(&self.db)
@@ -417,7 +506,10 @@ impl ModeService {
         .context("Failed to check if mode_group exists")?;
 
         if !exists.unwrap_or(false) {
-            return Err(anyhow!("mode_group_id '{}' does not exist", mode_group_id));
+            return Err(AppError::Validation(format!(
+                "mode_group_id '{}' does not exist",
+                mode_group_id
+            )));
         }
 
         Ok(())
@@ -431,21 +523,25 @@ impl ModeService {
         description_search: Option<&str>,
         offset: i64,
         limit: i64,
-    ) -> anyhow::Result<(Vec<Mode>, i64)> {
+    ) -> AppResult<(Vec<Mode>, i64)> {
         debug!(
             "Searching modes with filters: group_id={:?}, search={:?}",
             mode_group_id, description_search
         );
 
         if offset < 0 {
-            return Err(anyhow!("Offset cannot be negative"));
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
         }
         if limit <= 0 || limit > 1000 {
-            return Err(anyhow!("Limit must be between 1 and 1000"));
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
         }
 
         // Build dynamic query with parameter index tracking
-        let mut where_conditions = Vec::new();
+        let mut where_conditions = vec!["deleted_at IS NULL".to_string()];
         let mut param_index = 0;
         let mut bind_group_id = None;
         let mut bind_description = None;
@@ -467,11 +563,7 @@ impl ModeService {
             }
         }
 
-        let where_clause = if where_conditions.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", where_conditions.join(" AND "))
-        };
+        let where_clause = format!("WHERE {}", where_conditions.join(" AND "));
 
         // --- Count query ---
         let count_query = format!("SELECT COUNT(*) FROM core.mode {}", &where_clause);
@@ -484,7 +576,10 @@ impl ModeService {
             count_stmt = count_stmt.bind(desc);
         }
 
-        let total_count = count_stmt.fetch_one(&self.db).await?;
+        let total_count = count_stmt
+            .fetch_one(&self.db)
+            .await
+            .context("Failed to count filtered modes")?;
 
         // --- Data query ---
         param_index += 1;
@@ -508,7 +603,10 @@ impl ModeService {
         }
         data_stmt = data_stmt.bind(limit).bind(offset);
 
-        let rows = data_stmt.fetch_all(&self.db).await?;
+        let rows = data_stmt
+            .fetch_all(&self.db)
+            .await
+            .context("Failed to fetch filtered modes")?;
         let modes: Vec<Mode> = rows.into_iter().map(Mode::from).collect();
 
         debug!(
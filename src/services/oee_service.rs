@@ -0,0 +1,847 @@
+use crate::database::equipment::{Equipment as EquipmentRow, EquipmentQueries};
+use crate::database::oee::{
+    ClassifiedStateIntervalRow, IdealCycleTimeRow, ModeIntervalRow, OeeQueries,
+    StateClassificationRow,
+};
+use crate::database::production_counts::ProductionCountQueries;
+use crate::database::state_groups::StateGroupQueries;
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::services::equipment_mode_service::PRODUCTION_MODE;
+use crate::services::production_count_service::ProductionTotals;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+/// How time spent in a state counts towards oee
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OeeCategory {
+    Running,
+    PerformanceLoss,
+    UnplannedDowntime,
+    PlannedDowntime,
+}
+
+impl OeeCategory {
+    pub const ALL: [OeeCategory; 4] = [
+        OeeCategory::Running,
+        OeeCategory::PerformanceLoss,
+        OeeCategory::UnplannedDowntime,
+        OeeCategory::PlannedDowntime,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OeeCategory::Running => "running",
+            OeeCategory::PerformanceLoss => "performance_loss",
+            OeeCategory::UnplannedDowntime => "unplanned_downtime",
+            OeeCategory::PlannedDowntime => "planned_downtime",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StateClassification {
+    pub classification_id: Uuid,
+    pub state_group_id: Uuid,
+    pub state_code: i32,
+    pub category: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<StateClassificationRow> for StateClassification {
+    fn from(row: StateClassificationRow) -> Self {
+        Self {
+            classification_id: row.classification_id,
+            state_group_id: row.state_group_id,
+            state_code: row.state_code,
+            category: row.category,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IdealCycleTime {
+    pub equipment_id: Uuid,
+    pub ideal_cycle_seconds: f64,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<IdealCycleTimeRow> for IdealCycleTime {
+    fn from(row: IdealCycleTimeRow) -> Self {
+        Self {
+            equipment_id: row.equipment_id,
+            ideal_cycle_seconds: row.ideal_cycle_seconds,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Seconds spent in each oee category
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OeeDurations {
+    pub running_seconds: f64,
+    pub performance_loss_seconds: f64,
+    pub unplanned_downtime_seconds: f64,
+    pub planned_downtime_seconds: f64,
+}
+
+impl OeeDurations {
+    fn add(&mut self, category: OeeCategory, seconds: f64) {
+        match category {
+            OeeCategory::Running => self.running_seconds += seconds,
+            OeeCategory::PerformanceLoss => self.performance_loss_seconds += seconds,
+            OeeCategory::UnplannedDowntime => self.unplanned_downtime_seconds += seconds,
+            OeeCategory::PlannedDowntime => self.planned_downtime_seconds += seconds,
+        }
+    }
+
+    fn merge(&mut self, other: &OeeDurations) {
+        self.running_seconds += other.running_seconds;
+        self.performance_loss_seconds += other.performance_loss_seconds;
+        self.unplanned_downtime_seconds += other.unplanned_downtime_seconds;
+        self.planned_downtime_seconds += other.planned_downtime_seconds;
+    }
+
+    /// Planned production time minus planned downtime
+    pub fn planned_production_seconds(&self) -> f64 {
+        self.running_seconds + self.performance_loss_seconds + self.unplanned_downtime_seconds
+    }
+
+    /// Time the equipment ran, minor stops included
+    pub fn run_seconds(&self) -> f64 {
+        self.running_seconds + self.performance_loss_seconds
+    }
+
+    /// Run time over planned production time
+    pub fn availability(&self) -> Option<f64> {
+        ratio(self.run_seconds(), self.planned_production_seconds())
+    }
+}
+
+/// Ideal time for the parts counted and the run time they took, summed over the equipment
+/// that has an ideal cycle time
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IdealRun {
+    pub ideal_seconds: f64,
+    pub run_seconds: f64,
+}
+
+impl IdealRun {
+    fn new(
+        ideal_cycle_seconds: f64,
+        durations: &OeeDurations,
+        production: &ProductionTotals,
+    ) -> Self {
+        Self {
+            ideal_seconds: ideal_cycle_seconds * production.total_quantity() as f64,
+            run_seconds: durations.run_seconds(),
+        }
+    }
+
+    fn merge(&mut self, other: &IdealRun) {
+        self.ideal_seconds += other.ideal_seconds;
+        self.run_seconds += other.run_seconds;
+    }
+
+    /// Total count times the ideal cycle time over run time, slow cycles and minor stops
+    /// both lower it
+    pub fn performance(&self) -> Option<f64> {
+        ratio(self.ideal_seconds, self.run_seconds)
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> Option<f64> {
+    (denominator > 0.0).then(|| numerator / denominator)
+}
+
+/// OEE for one equipment, rolled up over everything below it
+#[derive(Debug, Clone)]
+pub struct OeeNode {
+    pub equipment_id: Uuid,
+    pub equipment_name: String,
+    pub durations: OeeDurations,
+    pub production: ProductionTotals,
+    /// None when no ideal cycle time is set for the equipment
+    pub ideal_cycle_seconds: Option<f64>,
+    pub ideal_run: IdealRun,
+    pub availability: Option<f64>,
+    /// None until an ideal cycle time is set here or below
+    pub performance: Option<f64>,
+    /// None until production counts are recorded
+    pub quality: Option<f64>,
+    pub oee: Option<f64>,
+    pub children: Vec<OeeNode>,
+}
+
+impl OeeNode {
+    fn new(
+        row: &EquipmentRow,
+        durations: OeeDurations,
+        production: ProductionTotals,
+        ideal_cycle_seconds: Option<f64>,
+        ideal_run: IdealRun,
+        children: Vec<OeeNode>,
+    ) -> Self {
+        let availability = durations.availability();
+        let performance = ideal_run.performance();
+        let quality = production.quality();
+        let oee = match (availability, performance, quality) {
+            (Some(a), Some(p), Some(q)) => Some(a * p * q),
+            _ => None,
+        };
+
+        Self {
+            equipment_id: row.equipment_id,
+            equipment_name: row.equipment_name.clone(),
+            durations,
+            production,
+            ideal_cycle_seconds,
+            ideal_run,
+            availability,
+            performance,
+            quality,
+            oee,
+            children,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OeeReport {
+    pub from: OffsetDateTime,
+    pub to: OffsetDateTime,
+    pub equipment: OeeNode,
+}
+
+fn overlap_seconds(
+    start: OffsetDateTime,
+    end: OffsetDateTime,
+    other_start: OffsetDateTime,
+    other_end: OffsetDateTime,
+) -> f64 {
+    let start = start.max(other_start);
+    let end = end.min(other_end);
+    if end > start {
+        (end - start).as_seconds_f64()
+    } else {
+        0.0
+    }
+}
+
+/// Sum the state time of one equipment inside the window. When the equipment has mode
+/// history in the window only time in production mode counts, otherwise all of it does.
+fn equipment_durations(
+    states: &[&ClassifiedStateIntervalRow],
+    modes: &[&ModeIntervalRow],
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+    now: OffsetDateTime,
+) -> OeeDurations {
+    let production: Vec<(OffsetDateTime, OffsetDateTime)> = modes
+        .iter()
+        .filter(|m| m.mode_description == PRODUCTION_MODE)
+        .map(|m| (m.set_at.max(from), m.ended_at.unwrap_or(now).min(to)))
+        .collect();
+
+    let mut durations = OeeDurations::default();
+    for state in states {
+        let category = state
+            .category
+            .as_deref()
+            .and_then(OeeCategory::parse)
+            .unwrap_or(OeeCategory::UnplannedDowntime);
+        let end = state.ended_at.unwrap_or(now);
+
+        let seconds = if modes.is_empty() {
+            overlap_seconds(state.started_at, end, from, to)
+        } else {
+            production
+                .iter()
+                .map(|(start, stop)| overlap_seconds(state.started_at, end, *start, *stop))
+                .sum()
+        };
+        durations.add(category, seconds);
+    }
+    durations
+}
+
+#[derive(Debug, Clone)]
+pub struct OeeService {
+    db: PgPool,
+}
+
+impl OeeService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    async fn validate_state_group_exists(&self, state_group_id: Uuid) -> AppResult<()> {
+        if !StateGroupQueries::exists(&self.db, state_group_id)
+            .await
+            .context("Failed to check if state group exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "State group with ID {} not found",
+                state_group_id
+            )));
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn get_classifications(
+        &self,
+        state_group_id: Uuid,
+    ) -> AppResult<Vec<StateClassification>> {
+        debug!("Fetching state classifications");
+        self.validate_state_group_exists(state_group_id).await?;
+
+        let rows = OeeQueries::get_classifications(&self.db, state_group_id)
+            .await
+            .context("Failed to fetch state classifications")?;
+        Ok(rows.into_iter().map(StateClassification::from).collect())
+    }
+
+    /// Set how a state code of the group counts towards oee
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn set_classification(
+        &self,
+        state_group_id: Uuid,
+        state_code: i32,
+        category: &str,
+    ) -> AppResult<StateClassification> {
+        debug!("Setting state classification");
+        let category = OeeCategory::parse(category.trim()).ok_or_else(|| {
+            AppError::Validation(format!(
+                "invalid category '{}', expected one of: {}",
+                category,
+                OeeCategory::ALL.map(|c| c.as_str()).join(", ")
+            ))
+        })?;
+        self.validate_state_group_exists(state_group_id).await?;
+
+        let row = OeeQueries::upsert_classification(
+            &self.db,
+            state_group_id,
+            state_code,
+            category.as_str(),
+        )
+        .await
+        .context("Failed to set state classification")?;
+        Ok(StateClassification::from(row))
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn delete_classification(
+        &self,
+        state_group_id: Uuid,
+        state_code: i32,
+    ) -> AppResult<()> {
+        debug!("Deleting state classification");
+        let deleted = OeeQueries::delete_classification(&self.db, state_group_id, state_code)
+            .await
+            .context("Failed to delete state classification")?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "Classification for state_code {} in state group {} not found",
+                state_code, state_group_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Set the fastest time the equipment makes one part in, the baseline for performance
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn set_ideal_cycle_time(
+        &self,
+        equipment_id: Uuid,
+        ideal_cycle_seconds: f64,
+    ) -> AppResult<IdealCycleTime> {
+        debug!("Setting ideal cycle time");
+        if !(ideal_cycle_seconds.is_finite() && ideal_cycle_seconds > 0.0) {
+            return Err(AppError::Validation(
+                "ideal_cycle_seconds must be greater than 0".to_string(),
+            ));
+        }
+        if !EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check if equipment exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+
+        let row = OeeQueries::upsert_ideal_cycle_time(&self.db, equipment_id, ideal_cycle_seconds)
+            .await
+            .context("Failed to set ideal cycle time")?;
+        Ok(IdealCycleTime::from(row))
+    }
+
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn delete_ideal_cycle_time(&self, equipment_id: Uuid) -> AppResult<()> {
+        debug!("Deleting ideal cycle time");
+        let deleted = OeeQueries::delete_ideal_cycle_time(&self.db, equipment_id)
+            .await
+            .context("Failed to delete ideal cycle time")?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "Ideal cycle time for equipment {} not found",
+                equipment_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// OEE of the equipment and everything below it over [from, to).
+    /// Defaults to the last 24 hours.
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn calculate(
+        &self,
+        equipment_id: Uuid,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> AppResult<OeeReport> {
+        debug!("Calculating oee");
+        let now = OffsetDateTime::now_utc();
+        let to = to.unwrap_or(now);
+        let from = from.unwrap_or(to - Duration::days(1));
+        if from >= to {
+            return Err(AppError::Validation(
+                "invalid time range: 'from' must be before 'to'".to_string(),
+            ));
+        }
+
+        let subtree = EquipmentQueries::get_subtree(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment subtree")?;
+        let root = subtree
+            .iter()
+            .find(|e| e.equipment_id == equipment_id)
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Equipment with ID {} not found", equipment_id))
+            })?;
+
+        let ids: Vec<Uuid> = subtree.iter().map(|e| e.equipment_id).collect();
+        let states = OeeQueries::get_state_intervals(&self.db, &ids, from, to)
+            .await
+            .context("Failed to fetch state intervals")?;
+        let modes = OeeQueries::get_mode_intervals(&self.db, &ids, from, to)
+            .await
+            .context("Failed to fetch mode intervals")?;
+        let counts: HashMap<Uuid, ProductionTotals> =
+            ProductionCountQueries::get_totals_by_equipment(&self.db, &ids, from, to)
+                .await
+                .context("Failed to fetch production counts")?
+                .iter()
+                .map(|row| (row.equipment_id, ProductionTotals::from(row)))
+                .collect();
+        let ideal_cycle_times: HashMap<Uuid, f64> =
+            OeeQueries::get_ideal_cycle_times(&self.db, &ids)
+                .await
+                .context("Failed to fetch ideal cycle times")?
+                .into_iter()
+                .map(|row| (row.equipment_id, row.ideal_cycle_seconds))
+                .collect();
+
+        let mut states_by_equipment: HashMap<Uuid, Vec<&ClassifiedStateIntervalRow>> =
+            HashMap::new();
+        for state in &states {
+            states_by_equipment
+                .entry(state.equipment_id)
+                .or_default()
+                .push(state);
+        }
+        let mut modes_by_equipment: HashMap<Uuid, Vec<&ModeIntervalRow>> = HashMap::new();
+        for mode in &modes {
+            modes_by_equipment
+                .entry(mode.equipment_id)
+                .or_default()
+                .push(mode);
+        }
+
+        let own: HashMap<Uuid, OeeDurations> = ids
+            .iter()
+            .map(|id| {
+                let durations = equipment_durations(
+                    states_by_equipment
+                        .get(id)
+                        .map(Vec::as_slice)
+                        .unwrap_or(&[]),
+                    modes_by_equipment.get(id).map(Vec::as_slice).unwrap_or(&[]),
+                    from,
+                    to,
+                    now,
+                );
+                (*id, durations)
+            })
+            .collect();
+
+        let mut children: HashMap<Uuid, Vec<&EquipmentRow>> = HashMap::new();
+        for row in &subtree {
+            if let Some(parent_id) = row.equipment_parent_id
+                && row.equipment_id != equipment_id
+            {
+                children.entry(parent_id).or_default().push(row);
+            }
+        }
+
+        let equipment = Self::build_node(root, &own, &counts, &ideal_cycle_times, &children);
+        debug!(
+            "OEE for {}: availability {:?}, performance {:?}, quality {:?}",
+            equipment.equipment_name,
+            equipment.availability,
+            equipment.performance,
+            equipment.quality
+        );
+
+        Ok(OeeReport {
+            from,
+            to,
+            equipment,
+        })
+    }
+
+    fn build_node(
+        row: &EquipmentRow,
+        own: &HashMap<Uuid, OeeDurations>,
+        counts: &HashMap<Uuid, ProductionTotals>,
+        ideal_cycle_times: &HashMap<Uuid, f64>,
+        children: &HashMap<Uuid, Vec<&EquipmentRow>>,
+    ) -> OeeNode {
+        let child_nodes: Vec<OeeNode> = children
+            .get(&row.equipment_id)
+            .map(|rows| {
+                rows.iter()
+                    .map(|child| Self::build_node(child, own, counts, ideal_cycle_times, children))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut durations = own.get(&row.equipment_id).copied().unwrap_or_default();
+        let mut production = counts.get(&row.equipment_id).copied().unwrap_or_default();
+        let ideal_cycle_seconds = ideal_cycle_times.get(&row.equipment_id).copied();
+        let mut ideal_run = ideal_cycle_seconds
+            .map(|seconds| IdealRun::new(seconds, &durations, &production))
+            .unwrap_or_default();
+        for child in &child_nodes {
+            durations.merge(&child.durations);
+            production.merge(&child.production);
+            ideal_run.merge(&child.ideal_run);
+        }
+
+        OeeNode::new(
+            row,
+            durations,
+            production,
+            ideal_cycle_seconds,
+            ideal_run,
+            child_nodes,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::operations::CountValues;
+    use crate::services::equipment_mode_service::EquipmentModeService;
+    use crate::services::equipment_service::EquipmentService;
+    use crate::services::equipment_state_service::{EquipmentStateService, StateReport};
+    use crate::services::production_count_service::{CountReport, ProductionCountService};
+
+    async fn seeded_type_id(pool: &PgPool, type_name: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = $1",
+            type_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    async fn map_default_groups(pool: &PgPool, equipment_id: Uuid) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_state_group_mapping (equipment_id, state_group_id)
+               SELECT $1, state_group_id FROM core.state_group
+               WHERE state_group_name = 'Default MES State Group'"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+               SELECT $1, mode_group_id FROM core.mode_group
+               WHERE mode_group_name = 'Default MES Mode Group'"#,
+            equipment_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn report(
+        service: &EquipmentStateService,
+        equipment_id: Uuid,
+        state_code: i32,
+        at: OffsetDateTime,
+    ) -> sqlx::Result<()> {
+        service
+            .ingest(
+                equipment_id,
+                StateReport {
+                    state_code,
+                    timestamp: Some(at),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        Ok(())
+    }
+
+    fn minutes(value: i64) -> Duration {
+        Duration::minutes(value)
+    }
+
+    #[test]
+    fn test_durations_ratios() {
+        let mut durations = OeeDurations::default();
+        assert_eq!(durations.availability(), None);
+
+        durations.add(OeeCategory::Running, 60.0);
+        durations.add(OeeCategory::PerformanceLoss, 20.0);
+        durations.add(OeeCategory::UnplannedDowntime, 20.0);
+        durations.add(OeeCategory::PlannedDowntime, 500.0);
+
+        assert_eq!(durations.availability(), Some(0.8));
+
+        // 100 parts at an ideal 0.6s each in 80s of run time
+        let production = ProductionTotals {
+            good_quantity: 90,
+            scrap_quantity: 10,
+            rework_quantity: 0,
+        };
+        assert_eq!(IdealRun::default().performance(), None);
+        assert_eq!(
+            IdealRun::new(0.6, &durations, &production).performance(),
+            Some(0.75)
+        );
+        assert_eq!(
+            OeeCategory::parse("planned_downtime"),
+            Some(OeeCategory::PlannedDowntime)
+        );
+        assert_eq!(OeeCategory::parse("lunch"), None);
+    }
+
+    #[sqlx::test]
+    async fn test_oee_rolls_up_the_tree(pool: PgPool) -> sqlx::Result<()> {
+        let equipment = EquipmentService::new(pool.clone());
+        let site = equipment
+            .create(
+                "Plant",
+                seeded_type_id(&pool, "enterprise").await?,
+                None,
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let site_type = seeded_type_id(&pool, "site").await?;
+        let line_a = equipment
+            .create("A", site_type, Some(site.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let line_b = equipment
+            .create("B", site_type, Some(site.equipment_id), None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        map_default_groups(&pool, line_a.equipment_id).await?;
+        map_default_groups(&pool, line_b.equipment_id).await?;
+
+        let states = EquipmentStateService::new(pool.clone());
+        // whole seconds so the window lines up with the stored microsecond timestamps
+        let t0 = (OffsetDateTime::now_utc() - Duration::hours(2))
+            .replace_nanosecond(0)
+            .unwrap();
+
+        // A: 60 running, 20 blocked, 20 unplanned stop, 20 planned downtime
+        report(&states, line_a.equipment_id, 1, t0).await?;
+        report(&states, line_a.equipment_id, 5, t0 + minutes(60)).await?;
+        report(&states, line_a.equipment_id, 8, t0 + minutes(80)).await?;
+        report(&states, line_a.equipment_id, 7, t0 + minutes(100)).await?;
+        report(&states, line_a.equipment_id, 1, t0 + minutes(120)).await?;
+
+        // B: 100 running, 20 unplanned stop
+        report(&states, line_b.equipment_id, 1, t0).await?;
+        report(&states, line_b.equipment_id, 8, t0 + minutes(100)).await?;
+        report(&states, line_b.equipment_id, 1, t0 + minutes(120)).await?;
+
+        let service = OeeService::new(pool.clone());
+        let report = service
+            .calculate(site.equipment_id, Some(t0), Some(t0 + minutes(120)))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let a = &report.equipment.children[0];
+        assert_eq!(a.equipment_name, "A");
+        assert_eq!(a.durations.planned_downtime_seconds, 1200.0);
+        assert_eq!(a.availability, Some(0.8));
+        // no ideal cycle time yet
+        assert_eq!(a.performance, None);
+        assert_eq!(a.oee, None);
+
+        // the site sums its children: 160 running, 20 blocked, 40 unplanned
+        let totals = report.equipment.durations;
+        assert_eq!(totals.running_seconds, 160.0 * 60.0);
+        assert_eq!(report.equipment.availability, Some(180.0 / 220.0));
+
+        // quality comes from the production counts in the window, performance from the
+        // counts at the ideal cycle time
+        service
+            .set_ideal_cycle_time(line_a.equipment_id, 36.0)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let counts = ProductionCountService::new(pool.clone());
+        for (equipment_id, good, scrap) in
+            [(line_a.equipment_id, 90, 10), (line_b.equipment_id, 50, 0)]
+        {
+            counts
+                .ingest(
+                    equipment_id,
+                    CountReport {
+                        values: CountValues {
+                            good: Some(good),
+                            scrap: Some(scrap),
+                            rework: None,
+                        },
+                        timestamp: Some(t0 + minutes(30)),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+        let report = service
+            .calculate(site.equipment_id, Some(t0), Some(t0 + minutes(120)))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let a = &report.equipment.children[0];
+        assert_eq!(a.quality, Some(0.9));
+        // 100 parts at 36s is 60 minutes of the 80 A ran
+        assert_eq!(a.performance, Some(0.75));
+        assert_eq!(a.oee, Some(0.8 * 0.75 * 0.9));
+        // B has no ideal cycle time and is left out of the site's performance
+        assert_eq!(report.equipment.performance, Some(0.75));
+        assert_eq!(report.equipment.production.good_quantity, 140);
+        assert_eq!(report.equipment.quality, Some(140.0 / 150.0));
+
+        // only production mode counts once modes are recorded
+        let modes = EquipmentModeService::new(pool.clone());
+        let idle =
+            sqlx::query_scalar!("SELECT mode_id FROM core.mode WHERE mode_description = 'idle'")
+                .fetch_one(&pool)
+                .await?;
+        modes
+            .set_mode(line_b.equipment_id, idle, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let report = service
+            .calculate(line_b.equipment_id, Some(t0), None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(report.equipment.durations, OeeDurations::default());
+
+        let result = service.calculate(Uuid::new_v4(), None, None).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_ideal_cycle_time(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = EquipmentService::new(pool.clone())
+            .create(
+                "Plant",
+                seeded_type_id(&pool, "enterprise").await?,
+                None,
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+            .equipment_id;
+        let service = OeeService::new(pool);
+
+        let set = service
+            .set_ideal_cycle_time(equipment_id, 2.5)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(set.ideal_cycle_seconds, 2.5);
+
+        for seconds in [0.0, -1.0, f64::NAN] {
+            let result = service.set_ideal_cycle_time(equipment_id, seconds).await;
+            assert!(matches!(result, Err(AppError::Validation(_))));
+        }
+        let result = service.set_ideal_cycle_time(Uuid::new_v4(), 2.5).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        service
+            .delete_ideal_cycle_time(equipment_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let result = service.delete_ideal_cycle_time(equipment_id).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_classifications(pool: PgPool) -> sqlx::Result<()> {
+        let state_group_id = sqlx::query_scalar!(
+            "SELECT state_group_id FROM core.state_group WHERE state_group_name = 'Default MES State Group'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let service = OeeService::new(pool);
+
+        let updated = service
+            .set_classification(state_group_id, 2, "unplanned_downtime")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated.category, "unplanned_downtime");
+
+        let result = service.set_classification(state_group_id, 2, "lunch").await;
+        assert!(result.unwrap_err().to_string().contains("invalid category"));
+
+        let result = service
+            .set_classification(Uuid::new_v4(), 2, "running")
+            .await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        service
+            .delete_classification(state_group_id, 2)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let classifications = service
+            .get_classifications(state_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(classifications.len(), 10);
+
+        Ok(())
+    }
+}
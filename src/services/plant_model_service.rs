@@ -0,0 +1,1479 @@
+use crate::database::audit::AuditQueries;
+use crate::database::equipment::{Equipment, EquipmentQueries};
+use crate::database::equipment_type_rules::EquipmentTypeRuleQueries;
+use crate::database::equipment_types::{EquipmentTypeQueries, EquipmentTypeRow};
+use crate::database::mode_groups::{ModeGroupQueries, ModeGroupRow};
+use crate::database::modes::{ModeRow, ModeRowQueries};
+use crate::database::plant_model::{GroupMapping, PlantModelQueries};
+use crate::database::state_groups::{StateGroupQueries, StateGroupRow};
+use crate::database::states::{StateRow, StateRowQueries};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::plant_model::{
+    PlantEquipment, PlantModeGroup, PlantModel, PlantModelAction, PlantModelChange, PlantModelDiff,
+    PlantModelDiffSummary, PlantState, PlantStateGroup,
+};
+use crate::services::state_service::StateService;
+use serde_json::{Map, Value, json};
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+const MAX_EQUIPMENT_NAME_LEN: usize = 255;
+
+#[derive(Debug, Clone)]
+pub struct PlantModelService {
+    db: PgPool,
+    actor: Option<String>,
+}
+
+impl PlantModelService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
+    }
+
+    /// The live plant model with every section filled in, applying it changes nothing
+    #[instrument(skip(self))]
+    pub async fn export(&self) -> AppResult<PlantModel> {
+        debug!("Exporting the plant model");
+        let mut conn = self
+            .db
+            .acquire()
+            .await
+            .context("Failed to acquire a connection")?;
+        let snapshot = Snapshot::load(&mut conn).await?;
+        Ok(snapshot.to_model())
+    }
+
+    /// Make the database match `model` in one transaction. A dry run makes the same changes
+    /// and rolls them back, so it fails the same way the real apply would.
+    #[instrument(skip(self, model))]
+    pub async fn apply(&self, model: &PlantModel, dry_run: bool) -> AppResult<PlantModelDiff> {
+        debug!("Applying the plant model");
+        let model = normalize(model)?;
+
+        let mut tx = self.begin().await?;
+        let current = Snapshot::load(&mut tx).await?;
+        let mut apply = Apply::new(&self.db, &current);
+        apply.run(&mut tx, &model).await?;
+        let changes = apply.changes;
+
+        if dry_run {
+            tx.rollback()
+                .await
+                .context("Failed to roll back the dry run")?;
+        } else {
+            tx.commit().await.context("Failed to commit the change")?;
+        }
+
+        let mut summary = PlantModelDiffSummary::default();
+        for change in &changes {
+            match change.action {
+                PlantModelAction::Create => summary.created += 1,
+                PlantModelAction::Update => summary.updated += 1,
+                PlantModelAction::Delete => summary.deleted += 1,
+            }
+        }
+        debug!(
+            "Plant model {}: {} created, {} updated, {} deleted",
+            if dry_run { "dry run" } else { "applied" },
+            summary.created,
+            summary.updated,
+            summary.deleted
+        );
+        Ok(PlantModelDiff {
+            dry_run,
+            changes,
+            summary,
+        })
+    }
+}
+
+/// Trims every name and checks the document on its own, before anything is read
+fn normalize(model: &PlantModel) -> AppResult<PlantModel> {
+    let mut model = model.clone();
+
+    if let Some(types) = &mut model.equipment_types {
+        let mut seen = HashSet::new();
+        for name in types.iter_mut() {
+            *name = name.trim().to_string();
+            if name.is_empty() {
+                return Err(AppError::Validation(
+                    "equipment type names cannot be empty".to_string(),
+                ));
+            }
+            if !seen.insert(name.to_lowercase()) {
+                return Err(AppError::Validation(format!(
+                    "equipment type '{}' is listed more than once",
+                    name
+                )));
+            }
+        }
+    }
+
+    if let Some(groups) = &mut model.mode_groups {
+        let mut seen = HashSet::new();
+        for group in groups.iter_mut() {
+            group.name = group.name.trim().to_string();
+            group.description = group.description.trim().to_string();
+            if group.name.is_empty() {
+                return Err(AppError::Validation(
+                    "mode group names cannot be empty".to_string(),
+                ));
+            }
+            if !seen.insert(group.name.clone()) {
+                return Err(AppError::Validation(format!(
+                    "mode group '{}' is listed more than once",
+                    group.name
+                )));
+            }
+            let mut modes = HashSet::new();
+            for mode in group.modes.iter_mut() {
+                *mode = mode.trim().to_string();
+                if mode.is_empty() {
+                    return Err(AppError::Validation(format!(
+                        "{}: mode descriptions cannot be empty",
+                        group.name
+                    )));
+                }
+                if !modes.insert(mode.clone()) {
+                    return Err(AppError::Validation(format!(
+                        "{}: mode '{}' is listed more than once",
+                        group.name, mode
+                    )));
+                }
+            }
+        }
+    }
+
+    if let Some(groups) = &mut model.state_groups {
+        let mut seen = HashSet::new();
+        for group in groups.iter_mut() {
+            group.name = group.name.trim().to_string();
+            group.description = group.description.trim().to_string();
+            if group.name.is_empty() {
+                return Err(AppError::Validation(
+                    "state group names cannot be empty".to_string(),
+                ));
+            }
+            if !seen.insert(group.name.clone()) {
+                return Err(AppError::Validation(format!(
+                    "state group '{}' is listed more than once",
+                    group.name
+                )));
+            }
+            let mut codes = HashSet::new();
+            let mut descriptions = HashSet::new();
+            for state in group.states.iter_mut() {
+                state.description = state.description.trim().to_string();
+                if state.description.is_empty() {
+                    return Err(AppError::Validation(format!(
+                        "{}/{}: state descriptions cannot be empty",
+                        group.name, state.code
+                    )));
+                }
+                if !codes.insert(state.code) {
+                    return Err(AppError::Validation(format!(
+                        "{}: state code {} is listed more than once",
+                        group.name, state.code
+                    )));
+                }
+                if !descriptions.insert(state.description.clone()) {
+                    return Err(AppError::Validation(format!(
+                        "{}: state '{}' is listed more than once",
+                        group.name, state.description
+                    )));
+                }
+            }
+        }
+    }
+
+    if let Some(roots) = &mut model.equipment {
+        normalize_equipment(roots, "")?;
+    }
+
+    Ok(model)
+}
+
+fn normalize_equipment(nodes: &mut [PlantEquipment], parent_path: &str) -> AppResult<()> {
+    let mut siblings = HashSet::new();
+    for node in nodes.iter_mut() {
+        node.name = node.name.trim().to_string();
+        node.equipment_type = node.equipment_type.trim().to_string();
+        let path = equipment_path(parent_path, &node.name);
+        if node.name.is_empty() {
+            return Err(AppError::Validation(format!(
+                "{}: equipment_name cannot be empty",
+                path
+            )));
+        }
+        if node.name.len() > MAX_EQUIPMENT_NAME_LEN {
+            return Err(AppError::Validation(format!(
+                "{}: equipment_name cannot be longer than {} characters",
+                path, MAX_EQUIPMENT_NAME_LEN
+            )));
+        }
+        if !siblings.insert((node.name.clone(), node.equipment_type.to_lowercase())) {
+            return Err(AppError::Validation(format!(
+                "{}: equipment '{}' of type '{}' is listed more than once",
+                parent_path_or_root(parent_path),
+                node.name,
+                node.equipment_type
+            )));
+        }
+        for groups in [&mut node.mode_groups, &mut node.state_groups] {
+            let mut seen = HashSet::new();
+            for name in groups.iter_mut() {
+                *name = name.trim().to_string();
+                if !seen.insert(name.clone()) {
+                    return Err(AppError::Validation(format!(
+                        "{}: group '{}' is listed more than once",
+                        path, name
+                    )));
+                }
+            }
+        }
+        normalize_equipment(&mut node.children, &path)?;
+    }
+    Ok(())
+}
+
+fn equipment_path(parent_path: &str, name: &str) -> String {
+    if parent_path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent_path, name)
+    }
+}
+
+fn parent_path_or_root(parent_path: &str) -> &str {
+    if parent_path.is_empty() {
+        "equipment"
+    } else {
+        parent_path
+    }
+}
+
+/// Keeps the kind of error and says which entry of the document it came from
+fn failed_on(key: &str, error: AppError) -> AppError {
+    match error {
+        AppError::NotFound(message) => AppError::NotFound(format!("{}: {}", key, message)),
+        AppError::Conflict(message) => AppError::Conflict(format!("{}: {}", key, message)),
+        AppError::Validation(message) => AppError::Validation(format!("{}: {}", key, message)),
+        AppError::Database { message, source } => {
+            AppError::database(format!("{}: {}", key, message), source)
+        }
+        other => other,
+    }
+}
+
+fn metadata_of(equipment: &Equipment) -> Map<String, Value> {
+    match &equipment.equipment_metadata {
+        Some(Value::Object(metadata)) => metadata.clone(),
+        _ => Map::new(),
+    }
+}
+
+/// Everything live the plant model covers, read in one go
+struct Snapshot {
+    equipment_types: Vec<EquipmentTypeRow>,
+    equipment: Vec<Equipment>,
+    mode_groups: Vec<ModeGroupRow>,
+    modes: Vec<ModeRow>,
+    state_groups: Vec<StateGroupRow>,
+    states: Vec<StateRow>,
+    mode_group_mappings: Vec<GroupMapping>,
+    state_group_mappings: Vec<GroupMapping>,
+}
+
+impl Snapshot {
+    async fn load(db: &mut PgConnection) -> AppResult<Self> {
+        Ok(Self {
+            equipment_types: PlantModelQueries::equipment_types(db)
+                .await
+                .context("Failed to fetch equipment types")?,
+            equipment: PlantModelQueries::equipment(db)
+                .await
+                .context("Failed to fetch equipment")?,
+            mode_groups: PlantModelQueries::mode_groups(db)
+                .await
+                .context("Failed to fetch mode groups")?,
+            modes: PlantModelQueries::modes(db)
+                .await
+                .context("Failed to fetch modes")?,
+            state_groups: PlantModelQueries::state_groups(db)
+                .await
+                .context("Failed to fetch state groups")?,
+            states: PlantModelQueries::states(db)
+                .await
+                .context("Failed to fetch states")?,
+            mode_group_mappings: PlantModelQueries::mode_group_mappings(db)
+                .await
+                .context("Failed to fetch mode group mappings")?,
+            state_group_mappings: PlantModelQueries::state_group_mappings(db)
+                .await
+                .context("Failed to fetch state group mappings")?,
+        })
+    }
+
+    /// Equipment by parent, roots under None. Equipment under a deleted parent is left out
+    /// like the parent is.
+    fn children(&self) -> HashMap<Option<Uuid>, Vec<&Equipment>> {
+        let live: HashSet<Uuid> = self.equipment.iter().map(|e| e.equipment_id).collect();
+        let mut children: HashMap<Option<Uuid>, Vec<&Equipment>> = HashMap::new();
+        for equipment in &self.equipment {
+            match equipment.equipment_parent_id {
+                Some(parent_id) if !live.contains(&parent_id) => {}
+                parent_id => children.entry(parent_id).or_default().push(equipment),
+            }
+        }
+        children
+    }
+
+    fn to_model(&self) -> PlantModel {
+        let type_names: HashMap<Uuid, &str> = self
+            .equipment_types
+            .iter()
+            .map(|t| (t.type_id, t.type_name.as_str()))
+            .collect();
+        let mode_group_names: HashMap<Uuid, &str> = self
+            .mode_groups
+            .iter()
+            .map(|g| (g.mode_group_id, g.mode_group_name.as_str()))
+            .collect();
+        let state_group_names: HashMap<Uuid, &str> = self
+            .state_groups
+            .iter()
+            .map(|g| (g.state_group_id, g.state_group_name.as_str()))
+            .collect();
+
+        let mut mode_groups_of: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for mapping in &self.mode_group_mappings {
+            mode_groups_of
+                .entry(mapping.equipment_id)
+                .or_default()
+                .push(mode_group_names[&mapping.group_id].to_string());
+        }
+        let mut state_groups_of: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for mapping in &self.state_group_mappings {
+            state_groups_of
+                .entry(mapping.equipment_id)
+                .or_default()
+                .push(state_group_names[&mapping.group_id].to_string());
+        }
+
+        let children = self.children();
+        let build = |equipment: &Equipment| PlantEquipment {
+            name: equipment.equipment_name.clone(),
+            equipment_type: type_names
+                .get(&equipment.equipment_type_id)
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            enabled: equipment.equipment_enabled,
+            metadata: metadata_of(equipment),
+            mode_groups: mode_groups_of
+                .get(&equipment.equipment_id)
+                .cloned()
+                .unwrap_or_default(),
+            state_groups: state_groups_of
+                .get(&equipment.equipment_id)
+                .cloned()
+                .unwrap_or_default(),
+            children: Vec::new(),
+        };
+        let roots = tree(&children, None, &build);
+
+        PlantModel {
+            equipment_types: Some(
+                self.equipment_types
+                    .iter()
+                    .map(|t| t.type_name.clone())
+                    .collect(),
+            ),
+            mode_groups: Some(
+                self.mode_groups
+                    .iter()
+                    .map(|g| PlantModeGroup {
+                        name: g.mode_group_name.clone(),
+                        description: g.mode_group_description.clone(),
+                        modes: self
+                            .modes
+                            .iter()
+                            .filter(|m| m.mode_group_id == g.mode_group_id)
+                            .map(|m| m.mode_description.clone())
+                            .collect(),
+                    })
+                    .collect(),
+            ),
+            state_groups: Some(
+                self.state_groups
+                    .iter()
+                    .map(|g| PlantStateGroup {
+                        name: g.state_group_name.clone(),
+                        description: g.state_group_description.clone(),
+                        states: self
+                            .states
+                            .iter()
+                            .filter(|s| s.state_group_id == g.state_group_id)
+                            .map(|s| PlantState {
+                                code: s.state_code,
+                                description: s.state_description.clone(),
+                                owner: None,
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            ),
+            equipment: Some(roots),
+        }
+    }
+}
+
+fn tree(
+    children: &HashMap<Option<Uuid>, Vec<&Equipment>>,
+    parent_id: Option<Uuid>,
+    build: &impl Fn(&Equipment) -> PlantEquipment,
+) -> Vec<PlantEquipment> {
+    children
+        .get(&parent_id)
+        .map(|nodes| {
+            nodes
+                .iter()
+                .map(|equipment| PlantEquipment {
+                    children: tree(children, Some(equipment.equipment_id), build),
+                    ..build(equipment)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Equipment of the document still to match, all under the same parent
+struct Level<'n> {
+    parent_id: Option<Uuid>,
+    parent_type_id: Option<Uuid>,
+    parent_path: String,
+    nodes: &'n [PlantEquipment],
+}
+
+/// One apply, from the snapshot taken in its transaction to the changes it made
+struct Apply<'a> {
+    db: &'a PgPool,
+    current: &'a Snapshot,
+    changes: Vec<PlantModelChange>,
+    /// lower case names of the types equipment can have once the apply is done
+    type_ids: HashMap<String, Uuid>,
+    type_names: HashMap<Uuid, String>,
+    mode_group_ids: HashMap<String, Uuid>,
+    mode_group_names: HashMap<Uuid, String>,
+    state_group_ids: HashMap<String, Uuid>,
+    state_group_names: HashMap<Uuid, String>,
+}
+
+impl<'a> Apply<'a> {
+    fn new(db: &'a PgPool, current: &'a Snapshot) -> Self {
+        Self {
+            db,
+            current,
+            changes: Vec::new(),
+            type_ids: HashMap::new(),
+            type_names: current
+                .equipment_types
+                .iter()
+                .map(|t| (t.type_id, t.type_name.clone()))
+                .collect(),
+            mode_group_ids: HashMap::new(),
+            mode_group_names: current
+                .mode_groups
+                .iter()
+                .map(|g| (g.mode_group_id, g.mode_group_name.clone()))
+                .collect(),
+            state_group_ids: HashMap::new(),
+            state_group_names: current
+                .state_groups
+                .iter()
+                .map(|g| (g.state_group_id, g.state_group_name.clone()))
+                .collect(),
+        }
+    }
+
+    fn record(
+        &mut self,
+        action: PlantModelAction,
+        entity_type: &str,
+        key: impl Into<String>,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) {
+        self.changes.push(PlantModelChange {
+            action,
+            entity_type: entity_type.to_string(),
+            key: key.into(),
+            before,
+            after,
+        });
+    }
+
+    /// Creates and updates go first so equipment can use what the document adds, deletes of
+    /// types and groups go last once nothing uses them
+    async fn run(&mut self, tx: &mut PgConnection, model: &PlantModel) -> AppResult<()> {
+        let removed_types = self
+            .equipment_types(tx, model.equipment_types.as_deref())
+            .await?;
+        let removed_mode_groups = self.mode_groups(tx, model.mode_groups.as_deref()).await?;
+        let removed_state_groups = self.state_groups(tx, model.state_groups.as_deref()).await?;
+
+        if let Some(roots) = &model.equipment {
+            self.equipment(tx, roots).await?;
+        }
+
+        for group in removed_mode_groups {
+            self.delete_mode_group(tx, group).await?;
+        }
+        for group in removed_state_groups {
+            self.delete_state_group(tx, group).await?;
+        }
+        for equipment_type in removed_types {
+            self.delete_equipment_type(tx, equipment_type).await?;
+        }
+        Ok(())
+    }
+
+    /// Creates the missing types and returns the ones the document dropped
+    async fn equipment_types(
+        &mut self,
+        tx: &mut PgConnection,
+        wanted: Option<&[String]>,
+    ) -> AppResult<Vec<&'a EquipmentTypeRow>> {
+        let current = self.current;
+        let Some(wanted) = wanted else {
+            self.type_ids = current
+                .equipment_types
+                .iter()
+                .map(|t| (t.type_name.to_lowercase(), t.type_id))
+                .collect();
+            return Ok(Vec::new());
+        };
+
+        let existing: HashMap<String, &EquipmentTypeRow> = current
+            .equipment_types
+            .iter()
+            .map(|t| (t.type_name.to_lowercase(), t))
+            .collect();
+        for name in wanted {
+            let type_id = match existing.get(&name.to_lowercase()) {
+                Some(row) => row.type_id,
+                None => {
+                    let row = EquipmentTypeQueries::create(tx, name)
+                        .await
+                        .map_err(|e| failed_on(name, e))?;
+                    self.record(
+                        PlantModelAction::Create,
+                        "equipment_type",
+                        name.as_str(),
+                        None,
+                        Some(json!({ "name": row.type_name })),
+                    );
+                    self.type_names.insert(row.type_id, row.type_name);
+                    row.type_id
+                }
+            };
+            self.type_ids.insert(name.to_lowercase(), type_id);
+        }
+
+        Ok(current
+            .equipment_types
+            .iter()
+            .filter(|t| !self.type_ids.contains_key(&t.type_name.to_lowercase()))
+            .collect())
+    }
+
+    /// Creates and updates the groups and their modes, returns the groups the document dropped
+    async fn mode_groups(
+        &mut self,
+        tx: &mut PgConnection,
+        wanted: Option<&[PlantModeGroup]>,
+    ) -> AppResult<Vec<&'a ModeGroupRow>> {
+        let current = self.current;
+        let Some(wanted) = wanted else {
+            self.mode_group_ids = current
+                .mode_groups
+                .iter()
+                .map(|g| (g.mode_group_name.clone(), g.mode_group_id))
+                .collect();
+            return Ok(Vec::new());
+        };
+
+        let existing: HashMap<&str, &ModeGroupRow> = current
+            .mode_groups
+            .iter()
+            .map(|g| (g.mode_group_name.as_str(), g))
+            .collect();
+        for group in wanted {
+            let mode_group_id = match existing.get(group.name.as_str()) {
+                Some(row) => {
+                    if row.mode_group_description != group.description {
+                        ModeGroupQueries::update_mode_group_description(
+                            tx,
+                            row.mode_group_id,
+                            &group.description,
+                        )
+                        .await
+                        .map_err(|e| failed_on(&group.name, e))?;
+                        self.record(
+                            PlantModelAction::Update,
+                            "mode_group",
+                            group.name.as_str(),
+                            Some(json!({ "description": row.mode_group_description })),
+                            Some(json!({ "description": group.description })),
+                        );
+                    }
+                    row.mode_group_id
+                }
+                None => {
+                    let row =
+                        ModeGroupQueries::create_mode_group(tx, &group.name, &group.description)
+                            .await
+                            .map_err(|e| failed_on(&group.name, e))?;
+                    self.record(
+                        PlantModelAction::Create,
+                        "mode_group",
+                        group.name.as_str(),
+                        None,
+                        Some(json!({ "description": row.mode_group_description })),
+                    );
+                    self.mode_group_names
+                        .insert(row.mode_group_id, row.mode_group_name);
+                    row.mode_group_id
+                }
+            };
+            self.mode_group_ids
+                .insert(group.name.clone(), mode_group_id);
+            self.modes(tx, mode_group_id, group).await?;
+        }
+
+        Ok(current
+            .mode_groups
+            .iter()
+            .filter(|g| !self.mode_group_ids.contains_key(&g.mode_group_name))
+            .collect())
+    }
+
+    async fn modes(
+        &mut self,
+        tx: &mut PgConnection,
+        mode_group_id: Uuid,
+        group: &PlantModeGroup,
+    ) -> AppResult<()> {
+        let current: Vec<&ModeRow> = self
+            .current
+            .modes
+            .iter()
+            .filter(|m| m.mode_group_id == mode_group_id)
+            .collect();
+
+        for mode in &current {
+            if !group.modes.contains(&mode.mode_description) {
+                self.delete_mode(tx, &group.name, mode).await?;
+            }
+        }
+        for description in &group.modes {
+            if current.iter().any(|m| &m.mode_description == description) {
+                continue;
+            }
+            let key = format!("{}/{}", group.name, description);
+            ModeRowQueries::create_mode(tx, mode_group_id, description)
+                .await
+                .map_err(|e| failed_on(&key, e))?;
+            self.record(
+                PlantModelAction::Create,
+                "mode",
+                key,
+                None,
+                Some(json!({ "description": description })),
+            );
+        }
+        Ok(())
+    }
+
+    async fn delete_mode(
+        &mut self,
+        tx: &mut PgConnection,
+        group_name: &str,
+        mode: &ModeRow,
+    ) -> AppResult<()> {
+        let key = format!("{}/{}", group_name, mode.mode_description);
+        ModeRowQueries::delete_mode(tx, mode.mode_id)
+            .await
+            .map_err(|e| failed_on(&key, e))?;
+        self.record(
+            PlantModelAction::Delete,
+            "mode",
+            key,
+            Some(json!({ "description": mode.mode_description })),
+            None,
+        );
+        Ok(())
+    }
+
+    /// Creates and updates the groups and their states, returns the groups the document
+    /// dropped
+    async fn state_groups(
+        &mut self,
+        tx: &mut PgConnection,
+        wanted: Option<&[PlantStateGroup]>,
+    ) -> AppResult<Vec<&'a StateGroupRow>> {
+        let current = self.current;
+        let Some(wanted) = wanted else {
+            self.state_group_ids = current
+                .state_groups
+                .iter()
+                .map(|g| (g.state_group_name.clone(), g.state_group_id))
+                .collect();
+            return Ok(Vec::new());
+        };
+
+        let existing: HashMap<&str, &StateGroupRow> = current
+            .state_groups
+            .iter()
+            .map(|g| (g.state_group_name.as_str(), g))
+            .collect();
+        for group in wanted {
+            let state_group_id = match existing.get(group.name.as_str()) {
+                Some(row) => {
+                    if row.state_group_description != group.description {
+                        StateGroupQueries::update_state_group_description(
+                            tx,
+                            row.state_group_id,
+                            &group.description,
+                        )
+                        .await
+                        .map_err(|e| failed_on(&group.name, e))?;
+                        self.record(
+                            PlantModelAction::Update,
+                            "state_group",
+                            group.name.as_str(),
+                            Some(json!({ "description": row.state_group_description })),
+                            Some(json!({ "description": group.description })),
+                        );
+                    }
+                    row.state_group_id
+                }
+                None => {
+                    let row =
+                        StateGroupQueries::create_state_group(tx, &group.name, &group.description)
+                            .await
+                            .map_err(|e| failed_on(&group.name, e))?;
+                    self.record(
+                        PlantModelAction::Create,
+                        "state_group",
+                        group.name.as_str(),
+                        None,
+                        Some(json!({ "description": row.state_group_description })),
+                    );
+                    self.state_group_names
+                        .insert(row.state_group_id, row.state_group_name);
+                    row.state_group_id
+                }
+            };
+            self.state_group_ids
+                .insert(group.name.clone(), state_group_id);
+            self.states(tx, state_group_id, group).await?;
+        }
+
+        Ok(current
+            .state_groups
+            .iter()
+            .filter(|g| !self.state_group_ids.contains_key(&g.state_group_name))
+            .collect())
+    }
+
+    /// States are matched on their code. Removed codes go first so a description can move
+    /// from one code to another.
+    async fn states(
+        &mut self,
+        tx: &mut PgConnection,
+        state_group_id: Uuid,
+        group: &PlantStateGroup,
+    ) -> AppResult<()> {
+        let current: Vec<&StateRow> = self
+            .current
+            .states
+            .iter()
+            .filter(|s| s.state_group_id == state_group_id)
+            .collect();
+        let wanted: HashMap<i32, &PlantState> = group.states.iter().map(|s| (s.code, s)).collect();
+
+        for state in &current {
+            if !wanted.contains_key(&state.state_code) {
+                self.delete_state(tx, &group.name, state).await?;
+            }
+        }
+        for state in &current {
+            let Some(wanted) = wanted.get(&state.state_code) else {
+                continue;
+            };
+            if state.state_description == wanted.description {
+                continue;
+            }
+            let key = format!("{}/{}", group.name, state.state_code);
+            StateRowQueries::update_state_description(tx, state.state_id, &wanted.description)
+                .await
+                .map_err(|e| failed_on(&key, e))?;
+            self.record(
+                PlantModelAction::Update,
+                "state",
+                key,
+                Some(json!({ "description": state.state_description })),
+                Some(json!({ "description": wanted.description })),
+            );
+        }
+        for state in &group.states {
+            if current.iter().any(|s| s.state_code == state.code) {
+                continue;
+            }
+            let key = format!("{}/{}", group.name, state.code);
+            StateService::check_reservation(tx, state_group_id, state.code, state.owner.as_deref())
+                .await
+                .map_err(|e| failed_on(&key, e))?;
+            StateRowQueries::create_state(tx, state_group_id, state.code, &state.description)
+                .await
+                .map_err(|e| failed_on(&key, e))?;
+            self.record(
+                PlantModelAction::Create,
+                "state",
+                key,
+                None,
+                Some(json!({ "code": state.code, "description": state.description })),
+            );
+        }
+        Ok(())
+    }
+
+    async fn delete_state(
+        &mut self,
+        tx: &mut PgConnection,
+        group_name: &str,
+        state: &StateRow,
+    ) -> AppResult<()> {
+        let key = format!("{}/{}", group_name, state.state_code);
+        StateRowQueries::delete_state(tx, state.state_id)
+            .await
+            .map_err(|e| failed_on(&key, e))?;
+        self.record(
+            PlantModelAction::Delete,
+            "state",
+            key,
+            Some(json!({ "code": state.state_code, "description": state.state_description })),
+            None,
+        );
+        Ok(())
+    }
+
+    /// Walks the document tree from the roots down. Equipment is matched on its name and type
+    /// under the same parent, anything under a parent that the document doesn't list is
+    /// deleted with everything below it.
+    async fn equipment(
+        &mut self,
+        tx: &mut PgConnection,
+        roots: &[PlantEquipment],
+    ) -> AppResult<()> {
+        let current = self.current;
+        let children = current.children();
+        let mut mode_groups_of: HashMap<Uuid, HashSet<Uuid>> = HashMap::new();
+        for mapping in &current.mode_group_mappings {
+            mode_groups_of
+                .entry(mapping.equipment_id)
+                .or_default()
+                .insert(mapping.group_id);
+        }
+        let mut state_groups_of: HashMap<Uuid, HashSet<Uuid>> = HashMap::new();
+        for mapping in &current.state_group_mappings {
+            state_groups_of
+                .entry(mapping.equipment_id)
+                .or_default()
+                .insert(mapping.group_id);
+        }
+
+        let mut levels = vec![Level {
+            parent_id: None,
+            parent_type_id: None,
+            parent_path: String::new(),
+            nodes: roots,
+        }];
+        while let Some(Level {
+            parent_id,
+            parent_type_id,
+            parent_path,
+            nodes,
+        }) = levels.pop()
+        {
+            let existing = children.get(&parent_id).cloned().unwrap_or_default();
+            let mut matched = HashSet::new();
+
+            for node in nodes {
+                let path = equipment_path(&parent_path, &node.name);
+                let type_id = *self
+                    .type_ids
+                    .get(&node.equipment_type.to_lowercase())
+                    .ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "{}: equipment type '{}' does not exist",
+                            path, node.equipment_type
+                        ))
+                    })?;
+
+                let found = existing
+                    .iter()
+                    .find(|e| e.equipment_name == node.name && e.equipment_type_id == type_id);
+                let equipment_id = match found {
+                    Some(equipment) => {
+                        matched.insert(equipment.equipment_id);
+                        self.update_equipment(tx, &path, equipment, node).await?;
+                        equipment.equipment_id
+                    }
+                    None => {
+                        self.create_equipment(tx, &path, parent_id, parent_type_id, type_id, node)
+                            .await?
+                    }
+                };
+
+                let mode_groups = mode_groups_of.remove(&equipment_id).unwrap_or_default();
+                self.equipment_mode_groups(tx, &path, equipment_id, &mode_groups, node)
+                    .await?;
+                let state_groups = state_groups_of.remove(&equipment_id).unwrap_or_default();
+                self.equipment_state_groups(tx, &path, equipment_id, &state_groups, node)
+                    .await?;
+
+                levels.push(Level {
+                    parent_id: Some(equipment_id),
+                    parent_type_id: Some(type_id),
+                    parent_path: path,
+                    nodes: &node.children,
+                });
+            }
+
+            for equipment in existing {
+                if !matched.contains(&equipment.equipment_id) {
+                    self.delete_equipment_tree(tx, &children, &parent_path, equipment)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_equipment(
+        &mut self,
+        tx: &mut PgConnection,
+        path: &str,
+        parent_id: Option<Uuid>,
+        parent_type_id: Option<Uuid>,
+        type_id: Uuid,
+        node: &PlantEquipment,
+    ) -> AppResult<Uuid> {
+        let allowed = EquipmentTypeRuleQueries::is_allowed(self.db, parent_type_id, type_id)
+            .await
+            .context("Failed to check the equipment type rules")?;
+        if !allowed {
+            let type_name = &self.type_names[&type_id];
+            return Err(AppError::Validation(match parent_type_id {
+                Some(parent_type_id) => format!(
+                    "{}: invalid hierarchy: equipment type '{}' cannot be placed under '{}'",
+                    path, type_name, self.type_names[&parent_type_id]
+                ),
+                None => format!(
+                    "{}: invalid hierarchy: equipment type '{}' cannot be at the root of the hierarchy",
+                    path, type_name
+                ),
+            }));
+        }
+
+        let metadata = Value::Object(node.metadata.clone());
+        let equipment = EquipmentQueries::create(
+            tx,
+            &node.name,
+            type_id,
+            parent_id,
+            Some(node.enabled),
+            Some(&metadata),
+        )
+        .await
+        .with_context(|| format!("{}: Failed to create equipment", path))?;
+        self.record(
+            PlantModelAction::Create,
+            "equipment",
+            path,
+            None,
+            Some(json!({
+                "type": self.type_names[&type_id],
+                "enabled": node.enabled,
+                "metadata": metadata,
+            })),
+        );
+        Ok(equipment.equipment_id)
+    }
+
+    /// One update with only the fields that changed, nothing when none did
+    async fn update_equipment(
+        &mut self,
+        tx: &mut PgConnection,
+        path: &str,
+        equipment: &Equipment,
+        node: &PlantEquipment,
+    ) -> AppResult<()> {
+        let mut before = Map::new();
+        let mut after = Map::new();
+
+        if equipment.equipment_enabled != node.enabled {
+            EquipmentQueries::set_enabled(tx, equipment.equipment_id, node.enabled)
+                .await
+                .with_context(|| format!("{}: Failed to update equipment", path))?;
+            before.insert("enabled".into(), json!(equipment.equipment_enabled));
+            after.insert("enabled".into(), json!(node.enabled));
+        }
+
+        let metadata = metadata_of(equipment);
+        if metadata != node.metadata {
+            let wanted = Value::Object(node.metadata.clone());
+            EquipmentQueries::update_metadata(tx, equipment.equipment_id, &wanted)
+                .await
+                .with_context(|| format!("{}: Failed to update equipment", path))?;
+            before.insert("metadata".into(), Value::Object(metadata));
+            after.insert("metadata".into(), wanted);
+        }
+
+        if !after.is_empty() {
+            self.record(
+                PlantModelAction::Update,
+                "equipment",
+                path,
+                Some(Value::Object(before)),
+                Some(Value::Object(after)),
+            );
+        }
+        Ok(())
+    }
+
+    async fn equipment_mode_groups(
+        &mut self,
+        tx: &mut PgConnection,
+        path: &str,
+        equipment_id: Uuid,
+        current: &HashSet<Uuid>,
+        node: &PlantEquipment,
+    ) -> AppResult<()> {
+        let mut wanted = HashSet::new();
+        for name in &node.mode_groups {
+            let key = format!("{} -> {}", path, name);
+            let mode_group_id = *self.mode_group_ids.get(name).ok_or_else(|| {
+                AppError::Validation(format!("{}: mode group does not exist", key))
+            })?;
+            wanted.insert(mode_group_id);
+            if current.contains(&mode_group_id) {
+                continue;
+            }
+            ModeGroupQueries::assign_equipment(tx, mode_group_id, equipment_id)
+                .await
+                .map_err(|e| failed_on(&key, e.into()))?;
+            self.record(
+                PlantModelAction::Create,
+                "equipment_mode_group",
+                key,
+                None,
+                None,
+            );
+        }
+
+        for mode_group_id in current.difference(&wanted) {
+            let key = format!("{} -> {}", path, self.mode_group_names[mode_group_id]);
+            ModeGroupQueries::unassign_equipment(tx, *mode_group_id, equipment_id)
+                .await
+                .map_err(|e| failed_on(&key, e.into()))?;
+            self.record(
+                PlantModelAction::Delete,
+                "equipment_mode_group",
+                key,
+                None,
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    async fn equipment_state_groups(
+        &mut self,
+        tx: &mut PgConnection,
+        path: &str,
+        equipment_id: Uuid,
+        current: &HashSet<Uuid>,
+        node: &PlantEquipment,
+    ) -> AppResult<()> {
+        let mut wanted = HashSet::new();
+        for name in &node.state_groups {
+            let key = format!("{} -> {}", path, name);
+            let state_group_id = *self.state_group_ids.get(name).ok_or_else(|| {
+                AppError::Validation(format!("{}: state group does not exist", key))
+            })?;
+            wanted.insert(state_group_id);
+            if current.contains(&state_group_id) {
+                continue;
+            }
+            StateGroupQueries::assign_equipment(tx, state_group_id, equipment_id)
+                .await
+                .map_err(|e| failed_on(&key, e.into()))?;
+            self.record(
+                PlantModelAction::Create,
+                "equipment_state_group",
+                key,
+                None,
+                None,
+            );
+        }
+
+        for state_group_id in current.difference(&wanted) {
+            let key = format!("{} -> {}", path, self.state_group_names[state_group_id]);
+            StateGroupQueries::unassign_equipment(tx, *state_group_id, equipment_id)
+                .await
+                .map_err(|e| failed_on(&key, e.into()))?;
+            self.record(
+                PlantModelAction::Delete,
+                "equipment_state_group",
+                key,
+                None,
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    /// Soft deletes the equipment and everything below it, children first. Their group
+    /// mappings stay so a restore brings them back as they were.
+    async fn delete_equipment_tree(
+        &mut self,
+        tx: &mut PgConnection,
+        children: &HashMap<Option<Uuid>, Vec<&Equipment>>,
+        parent_path: &str,
+        root: &Equipment,
+    ) -> AppResult<()> {
+        let mut subtree = Vec::new();
+        let mut stack = vec![(equipment_path(parent_path, &root.equipment_name), root)];
+        while let Some((path, equipment)) = stack.pop() {
+            for child in children
+                .get(&Some(equipment.equipment_id))
+                .into_iter()
+                .flatten()
+            {
+                stack.push((equipment_path(&path, &child.equipment_name), *child));
+            }
+            subtree.push((path, equipment));
+        }
+
+        // every node was pushed before its children
+        for (path, equipment) in subtree.into_iter().rev() {
+            EquipmentQueries::delete(tx, equipment.equipment_id)
+                .await
+                .with_context(|| format!("{}: Failed to delete equipment", path))?;
+            self.record(
+                PlantModelAction::Delete,
+                "equipment",
+                path,
+                Some(json!({
+                    "type": self.type_names[&equipment.equipment_type_id],
+                    "enabled": equipment.equipment_enabled,
+                })),
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    async fn delete_mode_group(
+        &mut self,
+        tx: &mut PgConnection,
+        group: &ModeGroupRow,
+    ) -> AppResult<()> {
+        let name = &group.mode_group_name;
+        if let Some(equipment) =
+            PlantModelQueries::equipment_using_mode_group(tx, group.mode_group_id)
+                .await
+                .context("Failed to check mode group mappings")?
+        {
+            return Err(AppError::Conflict(format!(
+                "mode group '{}' is still assigned to equipment '{}'",
+                name, equipment
+            )));
+        }
+
+        let current = self.current;
+        for mode in current
+            .modes
+            .iter()
+            .filter(|m| m.mode_group_id == group.mode_group_id)
+        {
+            self.delete_mode(tx, name, mode).await?;
+        }
+        ModeGroupQueries::delete_mode_group(tx, group.mode_group_id)
+            .await
+            .map_err(|e| failed_on(name, e))?;
+        self.record(
+            PlantModelAction::Delete,
+            "mode_group",
+            name.as_str(),
+            Some(json!({ "description": group.mode_group_description })),
+            None,
+        );
+        Ok(())
+    }
+
+    async fn delete_state_group(
+        &mut self,
+        tx: &mut PgConnection,
+        group: &StateGroupRow,
+    ) -> AppResult<()> {
+        let name = &group.state_group_name;
+        if let Some(equipment) =
+            PlantModelQueries::equipment_using_state_group(tx, group.state_group_id)
+                .await
+                .context("Failed to check state group mappings")?
+        {
+            return Err(AppError::Conflict(format!(
+                "state group '{}' is still assigned to equipment '{}'",
+                name, equipment
+            )));
+        }
+
+        let current = self.current;
+        for state in current
+            .states
+            .iter()
+            .filter(|s| s.state_group_id == group.state_group_id)
+        {
+            self.delete_state(tx, name, state).await?;
+        }
+        StateGroupQueries::delete_state_group(tx, group.state_group_id)
+            .await
+            .map_err(|e| failed_on(name, e))?;
+        self.record(
+            PlantModelAction::Delete,
+            "state_group",
+            name.as_str(),
+            Some(json!({ "description": group.state_group_description })),
+            None,
+        );
+        Ok(())
+    }
+
+    async fn delete_equipment_type(
+        &mut self,
+        tx: &mut PgConnection,
+        equipment_type: &EquipmentTypeRow,
+    ) -> AppResult<()> {
+        let name = &equipment_type.type_name;
+        if let Some(equipment) = PlantModelQueries::equipment_using_type(tx, equipment_type.type_id)
+            .await
+            .context("Failed to check equipment of the type")?
+        {
+            return Err(AppError::Conflict(format!(
+                "equipment type '{}' is still used by equipment '{}'",
+                name, equipment
+            )));
+        }
+
+        EquipmentTypeQueries::delete(tx, equipment_type.type_id)
+            .await
+            .map_err(|e| failed_on(name, e))?;
+        self.record(
+            PlantModelAction::Delete,
+            "equipment_type",
+            name.as_str(),
+            Some(json!({ "name": name })),
+            None,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_sqlx(e: AppError) -> sqlx::Error {
+        sqlx::Error::Protocol(e.to_string())
+    }
+
+    fn line(mode_groups: Vec<String>) -> PlantModel {
+        PlantModel {
+            mode_groups: Some(vec![
+                PlantModeGroup {
+                    name: "Default MES Mode Group".to_string(),
+                    description: "Default modes".to_string(),
+                    modes: vec!["disabled".into(), "production".into(), "idle".into()],
+                },
+                PlantModeGroup {
+                    name: "Packaging modes".to_string(),
+                    description: "Modes of the packaging lines".to_string(),
+                    modes: vec!["running".into(), "cleaning".into()],
+                },
+            ]),
+            equipment: Some(vec![PlantEquipment {
+                name: "Acme".to_string(),
+                equipment_type: "Enterprise".to_string(),
+                enabled: true,
+                metadata: Map::new(),
+                mode_groups: Vec::new(),
+                state_groups: Vec::new(),
+                children: vec![PlantEquipment {
+                    name: "Plant 1".to_string(),
+                    equipment_type: "site".to_string(),
+                    enabled: true,
+                    metadata: Map::new(),
+                    mode_groups,
+                    state_groups: vec!["Default MES State Group".to_string()],
+                    children: Vec::new(),
+                }],
+            }]),
+            ..PlantModel::default()
+        }
+    }
+
+    fn keys(diff: &PlantModelDiff, action: PlantModelAction) -> Vec<&str> {
+        diff.changes
+            .iter()
+            .filter(|c| c.action == action)
+            .map(|c| c.key.as_str())
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn test_apply_dry_run_and_export(pool: PgPool) -> sqlx::Result<()> {
+        let service = PlantModelService::new(pool.clone());
+        let model = line(vec!["Packaging modes".to_string()]);
+
+        let diff = service.apply(&model, true).await.map_err(to_sqlx)?;
+        assert!(diff.dry_run);
+        assert_eq!(
+            keys(&diff, PlantModelAction::Delete),
+            vec!["Default MES Mode Group/change over"]
+        );
+        assert_eq!(
+            keys(&diff, PlantModelAction::Update),
+            vec!["Default MES Mode Group"]
+        );
+        assert_eq!(
+            keys(&diff, PlantModelAction::Create),
+            vec![
+                "Packaging modes",
+                "Packaging modes/running",
+                "Packaging modes/cleaning",
+                "Acme",
+                "Acme/Plant 1",
+                "Acme/Plant 1 -> Packaging modes",
+                "Acme/Plant 1 -> Default MES State Group",
+            ]
+        );
+
+        // nothing of the dry run was kept
+        let export = service.export().await.map_err(to_sqlx)?;
+        assert!(export.equipment.unwrap().is_empty());
+        assert_eq!(export.mode_groups.unwrap().len(), 1);
+
+        let applied = service.apply(&model, false).await.map_err(to_sqlx)?;
+        assert_eq!(applied.summary.created, diff.summary.created);
+        assert_eq!(applied.summary.deleted, 1);
+
+        let export = service.export().await.map_err(to_sqlx)?;
+        let plant = &export.equipment.as_ref().unwrap()[0].children[0];
+        assert_eq!(plant.equipment_type, "site");
+        assert_eq!(plant.mode_groups, vec!["Packaging modes"]);
+        assert_eq!(plant.state_groups, vec!["Default MES State Group"]);
+
+        // the export round trips
+        let again = service.apply(&export, false).await.map_err(to_sqlx)?;
+        assert!(again.changes.is_empty());
+
+        // dropping the plant deletes it and keeps the group it used
+        let mut model = line(Vec::new());
+        model.equipment.as_mut().unwrap()[0].children.clear();
+        let diff = service.apply(&model, false).await.map_err(to_sqlx)?;
+        assert_eq!(keys(&diff, PlantModelAction::Delete), vec!["Acme/Plant 1"]);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_apply_is_all_or_nothing(pool: PgPool) -> sqlx::Result<()> {
+        let service = PlantModelService::new(pool.clone());
+        service
+            .apply(&line(vec!["Packaging modes".to_string()]), false)
+            .await
+            .map_err(to_sqlx)?;
+
+        // the group is dropped while the plant, left out of the document, still uses it
+        let mut model = line(Vec::new());
+        model.mode_groups.as_mut().unwrap().pop();
+        model.equipment = None;
+        let result = service.apply(&model, true).await;
+        let Err(AppError::Conflict(message)) = result else {
+            panic!("expected a conflict, got {:?}", result);
+        };
+        assert_eq!(
+            message,
+            "mode group 'Packaging modes' is still assigned to equipment 'Plant 1'"
+        );
+
+        // unassigning the plant in the same document lets the group go
+        let mut model = line(Vec::new());
+        model.mode_groups.as_mut().unwrap().pop();
+        let diff = service.apply(&model, true).await.map_err(to_sqlx)?;
+        assert!(keys(&diff, PlantModelAction::Delete).contains(&"Packaging modes"));
+
+        // a cell can't sit right under the enterprise, the whole apply is rolled back
+        let mut model = line(Vec::new());
+        model.equipment.as_mut().unwrap()[0].children[0].equipment_type = "cell".to_string();
+        let result = service.apply(&model, false).await;
+        let Err(AppError::Validation(message)) = result else {
+            panic!("expected a validation error, got {:?}", result);
+        };
+        assert!(message.contains("cannot be placed under 'enterprise'"));
+        let export = service.export().await.map_err(to_sqlx)?;
+        assert_eq!(export.equipment.unwrap()[0].children[0].name, "Plant 1");
+        assert_eq!(export.mode_groups.unwrap().len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_apply_rejects_bad_documents(pool: PgPool) -> sqlx::Result<()> {
+        let service = PlantModelService::new(pool);
+
+        let mut model = line(Vec::new());
+        model.equipment_types = Some(vec!["line".to_string(), " Line ".to_string()]);
+        let result = service.apply(&model, true).await;
+        assert!(matches!(result, Err(AppError::Validation(m)) if m.contains("more than once")));
+
+        let mut model = line(vec!["No such group".to_string()]);
+        model.mode_groups = None;
+        let result = service.apply(&model, true).await;
+        assert!(
+            matches!(result, Err(AppError::Validation(m)) if m == "Acme/Plant 1 -> No such group: mode group does not exist")
+        );
+
+        Ok(())
+    }
+}
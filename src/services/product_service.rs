@@ -0,0 +1,333 @@
+use crate::database::products::{ProductQueries, ProductRow};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::operations::Product;
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+const MAX_NAME_LEN: usize = 255;
+
+impl From<ProductRow> for Product {
+    fn from(row: ProductRow) -> Self {
+        Self {
+            product_id: row.product_id,
+            product_name: row.product_name,
+            product_description: row.product_description,
+            product_metadata: row.product_metadata,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductService {
+    db: PgPool,
+}
+
+impl ProductService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Validates and sanitizes product name input
+    fn validate_name(product_name: &str) -> AppResult<String> {
+        let trimmed = product_name.trim().to_string();
+
+        if trimmed.is_empty() {
+            return Err(AppError::Validation(
+                "product_name cannot be empty".to_string(),
+            ));
+        }
+
+        if trimmed.len() > MAX_NAME_LEN {
+            return Err(AppError::Validation(format!(
+                "product_name exceeds max length of {} characters",
+                MAX_NAME_LEN
+            )));
+        }
+
+        Ok(trimmed)
+    }
+
+    /// Product metadata is stored as a json object, anything else is rejected
+    fn validate_metadata(metadata: &Value) -> AppResult<()> {
+        if !metadata.is_object() {
+            return Err(AppError::Validation(
+                "product_metadata must be a json object".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(product_id = %product_id))]
+    pub async fn get_by_id(&self, product_id: Uuid) -> AppResult<Product> {
+        debug!("Fetching product by ID");
+        let row = ProductQueries::get_by_id(&self.db, product_id)
+            .await
+            .context("Failed to fetch product by ID")?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Product with ID {} not found", product_id))
+            })?;
+
+        debug!("Found product: {}", row.product_name);
+        Ok(Product::from(row))
+    }
+
+    #[instrument(skip(self, product_metadata), fields(product_name = %product_name))]
+    pub async fn create(
+        &self,
+        product_name: &str,
+        product_description: Option<&str>,
+        product_metadata: Option<&Value>,
+    ) -> AppResult<Product> {
+        debug!("Creating new product");
+        let name = Self::validate_name(product_name)?;
+
+        if let Some(metadata) = product_metadata {
+            Self::validate_metadata(metadata)?;
+        }
+
+        if ProductQueries::name_exists(&self.db, &name, None)
+            .await
+            .context("Failed to check for duplicate product_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "product_name '{}' already exists",
+                name
+            )));
+        }
+
+        let row = ProductQueries::create(&self.db, &name, product_description, product_metadata)
+            .await
+            .with_context(|| format!("Failed to create product '{}'", name))?;
+
+        debug!("Successfully created product: {}", row.product_name);
+        Ok(Product::from(row))
+    }
+
+    #[instrument(skip(self), fields(product_id = %product_id, product_name = %product_name))]
+    pub async fn update(
+        &self,
+        product_id: Uuid,
+        product_name: &str,
+        product_description: Option<&str>,
+    ) -> AppResult<Product> {
+        debug!("Updating product");
+        let name = Self::validate_name(product_name)?;
+
+        if ProductQueries::name_exists(&self.db, &name, Some(product_id))
+            .await
+            .context("Failed to check for duplicate product_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "product_name '{}' already exists",
+                name
+            )));
+        }
+
+        let row = ProductQueries::update(&self.db, product_id, &name, product_description)
+            .await
+            .with_context(|| format!("Failed to update product {}", product_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Product with ID {} not found", product_id))
+            })?;
+
+        debug!("Successfully updated product: {}", row.product_name);
+        Ok(Product::from(row))
+    }
+
+    #[instrument(skip(self, metadata), fields(product_id = %product_id))]
+    pub async fn update_metadata(&self, product_id: Uuid, metadata: &Value) -> AppResult<Product> {
+        debug!("Updating product metadata");
+        Self::validate_metadata(metadata)?;
+
+        let row = ProductQueries::update_metadata(&self.db, product_id, metadata)
+            .await
+            .with_context(|| format!("Failed to update metadata for product {}", product_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Product with ID {} not found", product_id))
+            })?;
+
+        debug!("Successfully updated product metadata");
+        Ok(Product::from(row))
+    }
+
+    #[instrument(skip(self), fields(product_id = %product_id))]
+    pub async fn delete(&self, product_id: Uuid) -> AppResult<()> {
+        debug!("Deleting product");
+
+        if ProductQueries::is_in_use(&self.db, product_id)
+            .await
+            .context("Failed to check product usage")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Product {} is in use by work orders and cannot be deleted",
+                product_id
+            )));
+        }
+
+        let deleted = ProductQueries::delete(&self.db, product_id)
+            .await
+            .with_context(|| format!("Failed to delete product {}", product_id))?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "Product with ID {} not found",
+                product_id
+            )));
+        }
+
+        debug!("Successfully deleted product");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(product_id = %product_id))]
+    pub async fn exists(&self, product_id: Uuid) -> AppResult<bool> {
+        let exists = ProductQueries::exists(&self.db, product_id)
+            .await
+            .context("Failed to check if product exists")?;
+        debug!("Product {} exists: {}", product_id, exists);
+        Ok(exists)
+    }
+
+    /// Get products with pagination
+    #[instrument(skip(self))]
+    pub async fn get_paginated(&self, offset: i64, limit: i64) -> AppResult<(Vec<Product>, i64)> {
+        debug!(
+            "Fetching products with pagination: offset={}, limit={}",
+            offset, limit
+        );
+
+        if offset < 0 {
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
+        }
+
+        if limit <= 0 || limit > 1000 {
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
+        }
+
+        let total_count = self.count().await?;
+
+        let rows = sqlx::query_as!(
+            ProductRow,
+            r#"SELECT product_id, product_name, product_description, product_metadata,
+                      created_at, updated_at
+               FROM operations.product
+               ORDER BY product_name, product_id
+               LIMIT $1 OFFSET $2"#,
+            limit,
+            offset
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch paginated products")?;
+
+        let products: Vec<Product> = rows.into_iter().map(Product::from).collect();
+        debug!("Found {} products (total: {})", products.len(), total_count);
+
+        Ok((products, total_count))
+    }
+
+    /// Get count of all products
+    #[instrument(skip(self))]
+    pub async fn count(&self) -> AppResult<i64> {
+        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM operations.product")
+            .fetch_one(&self.db)
+            .await
+            .context("Failed to count products")?
+            .unwrap_or(0);
+
+        debug!("Total product count: {}", count);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    #[sqlx::test]
+    async fn test_service_create_and_validation(pool: PgPool) -> sqlx::Result<()> {
+        let service = ProductService::new(pool);
+
+        let created = service
+            .create("  Widget  ", Some("A widget"), Some(&json!({"sku": "W-1"})))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(created.product_name, "Widget");
+        assert_eq!(created.product_metadata["sku"], "W-1");
+
+        let err = service.create("   ", None, None).await.unwrap_err();
+        assert!(err.to_string().contains("cannot be empty"));
+
+        let err = service
+            .create("Other", None, Some(&json!([1, 2])))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("must be a json object"));
+
+        let err = service.create("widget", None, None).await.unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_update_and_delete(pool: PgPool) -> sqlx::Result<()> {
+        let service = ProductService::new(pool.clone());
+
+        let widget = service
+            .create("Widget", None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        service
+            .create("Gadget", None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let err = service
+            .update(widget.product_id, "GADGET", None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        // renaming to a different case of its own name is allowed
+        let updated = service
+            .update(widget.product_id, "WIDGET", Some("Renamed"))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated.product_name, "WIDGET");
+        assert_eq!(updated.product_description.as_deref(), Some("Renamed"));
+
+        sqlx::query!(
+            "INSERT INTO operations.work_order (work_order_name, product_id) VALUES ('WO-1', $1)",
+            widget.product_id
+        )
+        .execute(&pool)
+        .await?;
+
+        let err = service.delete(widget.product_id).await.unwrap_err();
+        assert!(err.to_string().contains("in use"));
+
+        let err = service.delete(Uuid::new_v4()).await.unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        let (page, total) = service
+            .get_paginated(0, 1)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(page.len(), 1);
+        assert_eq!(total, 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,518 @@
+use crate::database::equipment::EquipmentQueries;
+use crate::database::jobs::JobQueries;
+use crate::database::production_counts::{
+    NewProductionCount, ProductionBucketRow, ProductionCountQueries, ProductionCountRow,
+    ProductionTotalsRow,
+};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::operations::{CountMode, CountValues};
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+const MAX_RECORDED_BY_LEN: usize = 255;
+
+/// Buckets the aggregate endpoint accepts, in postgres `date_trunc` units
+pub const BUCKETS: [&str; 5] = ["minute", "hour", "day", "week", "month"];
+
+#[derive(Debug, Clone)]
+pub struct ProductionCount {
+    pub count_id: Uuid,
+    pub equipment_id: Uuid,
+    pub job_id: Option<Uuid>,
+    pub recorded_at: OffsetDateTime,
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+    pub good_counter: Option<i64>,
+    pub scrap_counter: Option<i64>,
+    pub rework_counter: Option<i64>,
+    pub rollover: bool,
+    pub recorded_by: Option<String>,
+}
+
+impl From<ProductionCountRow> for ProductionCount {
+    fn from(row: ProductionCountRow) -> Self {
+        Self {
+            count_id: row.count_id,
+            equipment_id: row.equipment_id,
+            job_id: row.job_id,
+            recorded_at: row.recorded_at,
+            good_quantity: row.good_quantity,
+            scrap_quantity: row.scrap_quantity,
+            rework_quantity: row.rework_quantity,
+            good_counter: row.good_counter,
+            scrap_counter: row.scrap_counter,
+            rework_counter: row.rework_counter,
+            rollover: row.rollover,
+            recorded_by: row.recorded_by,
+        }
+    }
+}
+
+/// Summed good, scrap and rework quantities
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProductionTotals {
+    pub good_quantity: i64,
+    pub scrap_quantity: i64,
+    pub rework_quantity: i64,
+}
+
+impl ProductionTotals {
+    pub fn merge(&mut self, other: &ProductionTotals) {
+        self.good_quantity += other.good_quantity;
+        self.scrap_quantity += other.scrap_quantity;
+        self.rework_quantity += other.rework_quantity;
+    }
+
+    pub fn total_quantity(&self) -> i64 {
+        self.good_quantity + self.scrap_quantity + self.rework_quantity
+    }
+
+    /// Good parts over all parts, rework counts as not right first time
+    pub fn quality(&self) -> Option<f64> {
+        let total = self.total_quantity();
+        (total > 0).then(|| self.good_quantity as f64 / total as f64)
+    }
+}
+
+impl From<&ProductionTotalsRow> for ProductionTotals {
+    fn from(row: &ProductionTotalsRow) -> Self {
+        Self {
+            good_quantity: row.good_quantity,
+            scrap_quantity: row.scrap_quantity,
+            rework_quantity: row.rework_quantity,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductionBucket {
+    pub bucket_start: OffsetDateTime,
+    pub totals: ProductionTotals,
+}
+
+impl From<ProductionBucketRow> for ProductionBucket {
+    fn from(row: ProductionBucketRow) -> Self {
+        Self {
+            bucket_start: row.bucket_start,
+            totals: ProductionTotals {
+                good_quantity: row.good_quantity,
+                scrap_quantity: row.scrap_quantity,
+                rework_quantity: row.rework_quantity,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductionReport {
+    pub equipment_id: Uuid,
+    pub from: OffsetDateTime,
+    pub to: OffsetDateTime,
+    pub bucket: String,
+    pub totals: ProductionTotals,
+    pub buckets: Vec<ProductionBucket>,
+}
+
+/// A count report from the plc or an operator
+#[derive(Debug, Clone, Default)]
+pub struct CountReport<'a> {
+    pub mode: CountMode,
+    pub values: CountValues,
+    /// Defaults to the job running on the equipment
+    pub job_id: Option<Uuid>,
+    /// Highest value of the plc counters, used to detect rollover in absolute mode
+    pub counter_max: Option<i64>,
+    pub timestamp: Option<OffsetDateTime>,
+    pub recorded_by: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductionCountService {
+    db: PgPool,
+}
+
+impl ProductionCountService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    fn validate_report(report: &CountReport<'_>) -> AppResult<()> {
+        let values = [
+            ("good", report.values.good),
+            ("scrap", report.values.scrap),
+            ("rework", report.values.rework),
+        ];
+
+        if values.iter().all(|(_, value)| value.is_none()) {
+            return Err(AppError::Validation(
+                "at least one of good, scrap or rework is required".to_string(),
+            ));
+        }
+
+        for (name, value) in values {
+            if value.is_some_and(|v| v < 0) {
+                return Err(AppError::Validation(format!("{} cannot be negative", name)));
+            }
+            if let (Some(value), Some(max)) = (value, report.counter_max)
+                && value > max
+            {
+                return Err(AppError::Validation(format!(
+                    "{} exceeds counter_max of {}",
+                    name, max
+                )));
+            }
+        }
+
+        if report.counter_max.is_some_and(|max| max <= 0) {
+            return Err(AppError::Validation(
+                "counter_max must be greater than 0".to_string(),
+            ));
+        }
+
+        if report
+            .recorded_by
+            .is_some_and(|s| s.len() > MAX_RECORDED_BY_LEN)
+        {
+            return Err(AppError::Validation(format!(
+                "recorded_by exceeds max length of {} characters",
+                MAX_RECORDED_BY_LEN
+            )));
+        }
+
+        if report
+            .timestamp
+            .is_some_and(|t| t > OffsetDateTime::now_utc())
+        {
+            return Err(AppError::Validation(
+                "invalid timestamp: cannot be in the future".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn validate_equipment_exists(&self, equipment_id: Uuid) -> AppResult<()> {
+        if !EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check if equipment exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// The job the counts belong to. A given job has to run on the equipment, without one
+    /// the counts go to the active job of the equipment if there is one.
+    async fn resolve_job(
+        &self,
+        equipment_id: Uuid,
+        job_id: Option<Uuid>,
+    ) -> AppResult<Option<Uuid>> {
+        match job_id {
+            Some(job_id) => {
+                let job = JobQueries::get_by_id(&self.db, job_id)
+                    .await
+                    .context("Failed to fetch job")?
+                    .ok_or_else(|| {
+                        AppError::Validation(format!("job_id '{}' does not exist", job_id))
+                    })?;
+
+                if job.equipment_id != equipment_id {
+                    return Err(AppError::Validation(format!(
+                        "job_id '{}' is not assigned to this equipment",
+                        job_id
+                    )));
+                }
+                Ok(Some(job_id))
+            }
+            None => {
+                let job = JobQueries::get_active_for_equipment(&self.db, equipment_id)
+                    .await
+                    .context("Failed to fetch active job")?;
+                Ok(job.map(|j| j.job_id))
+            }
+        }
+    }
+
+    /// Record produced quantities, either as deltas or as raw plc counter values
+    #[instrument(skip(self, report), fields(equipment_id = %equipment_id, mode = ?report.mode))]
+    pub async fn ingest(
+        &self,
+        equipment_id: Uuid,
+        report: CountReport<'_>,
+    ) -> AppResult<ProductionCount> {
+        debug!("Ingesting production count");
+        let recorded_by = report.recorded_by.map(str::trim).filter(|s| !s.is_empty());
+        Self::validate_report(&report)?;
+        self.validate_equipment_exists(equipment_id).await?;
+
+        let job_id = self.resolve_job(equipment_id, report.job_id).await?;
+
+        let row = ProductionCountQueries::record(
+            &self.db,
+            &NewProductionCount {
+                equipment_id,
+                job_id,
+                recorded_at: report.timestamp,
+                mode: report.mode,
+                values: report.values,
+                counter_max: report.counter_max,
+                recorded_by,
+            },
+        )
+        .await
+        .context("Failed to record production count")?
+        .ok_or_else(|| {
+            AppError::Validation(
+                "invalid timestamp: earlier than the latest counter reading".to_string(),
+            )
+        })?;
+
+        if row.rollover {
+            debug!("Counter rollover detected for equipment {}", equipment_id);
+        }
+        Ok(ProductionCount::from(row))
+    }
+
+    /// Quantities of the equipment and everything below it over [from, to), summed per
+    /// bucket. Defaults to the last 24 hours in hourly buckets.
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn aggregate(
+        &self,
+        equipment_id: Uuid,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+        bucket: Option<&str>,
+        job_id: Option<Uuid>,
+    ) -> AppResult<ProductionReport> {
+        debug!("Aggregating production counts");
+        let bucket = bucket.map(str::trim).unwrap_or("hour").to_lowercase();
+        if !BUCKETS.contains(&bucket.as_str()) {
+            return Err(AppError::Validation(format!(
+                "invalid bucket '{}', expected one of: {}",
+                bucket,
+                BUCKETS.join(", ")
+            )));
+        }
+
+        let to = to.unwrap_or_else(OffsetDateTime::now_utc);
+        let from = from.unwrap_or(to - Duration::days(1));
+        if from >= to {
+            return Err(AppError::Validation(
+                "invalid time range: 'from' must be before 'to'".to_string(),
+            ));
+        }
+
+        let ids: Vec<Uuid> = EquipmentQueries::get_subtree(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch equipment subtree")?
+            .into_iter()
+            .map(|e| e.equipment_id)
+            .collect();
+        if ids.is_empty() {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+
+        let buckets: Vec<ProductionBucket> =
+            ProductionCountQueries::get_buckets(&self.db, &ids, from, to, &bucket, job_id)
+                .await
+                .context("Failed to aggregate production counts")?
+                .into_iter()
+                .map(ProductionBucket::from)
+                .collect();
+
+        let mut totals = ProductionTotals::default();
+        for b in &buckets {
+            totals.merge(&b.totals);
+        }
+        debug!(
+            "Found {} buckets, {} parts in total",
+            buckets.len(),
+            totals.total_quantity()
+        );
+
+        Ok(ProductionReport {
+            equipment_id,
+            from,
+            to,
+            bucket,
+            totals,
+            buckets,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::job_service::JobService;
+
+    async fn create_equipment(
+        pool: &PgPool,
+        equipment_name: &str,
+        type_name: &str,
+        parent_id: Option<Uuid>,
+    ) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            r#"INSERT INTO core.equipment (equipment_name, equipment_type_id, equipment_parent_id)
+               SELECT $1, type_id, $3 FROM core.equipment_type WHERE type_name = $2
+               RETURNING equipment_id"#,
+            equipment_name,
+            type_name,
+            parent_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    fn delta(good: i64, scrap: i64) -> CountReport<'static> {
+        CountReport {
+            values: CountValues {
+                good: Some(good),
+                scrap: Some(scrap),
+                rework: None,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_service_validation(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool, "Acme", "enterprise", None).await?;
+        let service = ProductionCountService::new(pool);
+
+        let err = service
+            .ingest(equipment_id, CountReport::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("at least one of"));
+
+        let err = service
+            .ingest(equipment_id, delta(-1, 0))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be negative"));
+
+        let report = CountReport {
+            mode: CountMode::Absolute,
+            counter_max: Some(100),
+            ..delta(101, 0)
+        };
+        let err = service.ingest(equipment_id, report).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds counter_max"));
+
+        let report = CountReport {
+            job_id: Some(Uuid::new_v4()),
+            ..delta(1, 0)
+        };
+        let err = service.ingest(equipment_id, report).await.unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+
+        let err = service
+            .aggregate(equipment_id, None, None, Some("fortnight"), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid bucket"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_ingest_attaches_active_job(pool: PgPool) -> sqlx::Result<()> {
+        let equipment_id = create_equipment(&pool, "Acme", "enterprise", None).await?;
+        let job_id = sqlx::query_scalar!(
+            r#"WITH p AS (
+                   INSERT INTO operations.product (product_name) VALUES ('Widget')
+                   RETURNING product_id
+               ), wo AS (
+                   INSERT INTO operations.work_order (work_order_name, product_id)
+                   SELECT 'WO-1', product_id FROM p
+                   RETURNING work_order_id
+               )
+               INSERT INTO operations.job (work_order_id, equipment_id, job_status)
+               SELECT work_order_id, $1, 'released' FROM wo
+               RETURNING job_id"#,
+            equipment_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        let service = ProductionCountService::new(pool.clone());
+
+        let count = service
+            .ingest(equipment_id, delta(5, 0))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(count.job_id.is_none());
+
+        // skip the equipment mode check of start, only the status matters here
+        sqlx::query!(
+            "UPDATE operations.job SET job_status = 'running', started_at = now() WHERE job_id = $1",
+            job_id
+        )
+        .execute(&pool)
+        .await?;
+        let count = service
+            .ingest(equipment_id, delta(7, 1))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(count.job_id, Some(job_id));
+
+        let err = JobService::new(pool).delete(job_id).await.unwrap_err();
+        assert!(err.to_string().contains("in use"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_aggregate_subtree(pool: PgPool) -> sqlx::Result<()> {
+        let enterprise = create_equipment(&pool, "Acme", "enterprise", None).await?;
+        let site = create_equipment(&pool, "Plant 1", "site", Some(enterprise)).await?;
+        let other = create_equipment(&pool, "Globex", "enterprise", None).await?;
+        let service = ProductionCountService::new(pool);
+
+        let t0 = (OffsetDateTime::now_utc() - Duration::hours(3))
+            .replace_nanosecond(0)
+            .unwrap();
+        for (equipment_id, at, good, scrap) in [
+            (enterprise, t0, 10, 0),
+            (site, t0 + Duration::minutes(5), 20, 5),
+            (site, t0 + Duration::hours(1), 15, 0),
+            (other, t0, 100, 0),
+        ] {
+            let report = CountReport {
+                timestamp: Some(at),
+                ..delta(good, scrap)
+            };
+            service
+                .ingest(equipment_id, report)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let report = service
+            .aggregate(enterprise, Some(t0), None, Some("hour"), None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(report.totals.good_quantity, 45);
+        assert_eq!(report.totals.scrap_quantity, 5);
+        assert!(report.buckets.len() >= 2);
+        assert_eq!(report.totals.quality(), Some(0.9));
+
+        let report = service
+            .aggregate(site, Some(t0), None, Some("day"), None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(report.totals.good_quantity, 35);
+
+        Ok(())
+    }
+}
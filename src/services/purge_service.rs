@@ -0,0 +1,213 @@
+use crate::database::audit::AuditQueries;
+use crate::database::purge::{PurgeQueries, PurgedRow};
+use crate::error::{AppResult, DatabaseContext};
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info, instrument};
+
+/// Actor the purged rows are logged with in the audit log
+const PURGE_ACTOR: &str = "purge";
+
+/// Permanently removes plant model rows that were soft deleted longer ago than the
+/// retention, until then they can be restored
+#[derive(Debug, Clone)]
+pub struct PurgeService {
+    db: PgPool,
+    retention_days: u16,
+}
+
+impl PurgeService {
+    pub fn new(db: PgPool, retention_days: u16) -> Self {
+        Self { db, retention_days }
+    }
+
+    /// Rows still referenced, e.g. a mode with history, are kept and tried again next time
+    #[instrument(skip(self), fields(retention_days = self.retention_days))]
+    pub async fn purge(&self) -> AppResult<Vec<PurgedRow>> {
+        let mut tx = AuditQueries::begin(&self.db, Some(PURGE_ACTOR))
+            .await
+            .context("Failed to start a transaction")?;
+        let purged = PurgeQueries::purge_deleted(&mut tx, i32::from(self.retention_days))
+            .await
+            .context("Failed to purge deleted rows")?;
+        tx.commit().await.context("Failed to commit the purge")?;
+
+        debug!("Purged {:?}", purged);
+        Ok(purged)
+    }
+
+    /// Purge every `period` for as long as the server runs, starting right away
+    pub async fn run_every(&self, period: Duration) {
+        let mut ticker = tokio::time::interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match self.purge().await {
+                Ok(purged) => {
+                    let total: i64 = purged.iter().map(|row| row.purged).sum();
+                    if total > 0 {
+                        info!(
+                            "Purged {} rows deleted more than {} days ago",
+                            total, self.retention_days
+                        );
+                    }
+                }
+                Err(e) => error!("Failed to purge deleted rows: {:#}", anyhow::Error::from(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::equipment_mode_service::{EquipmentModeService, PRODUCTION_MODE};
+    use crate::services::equipment_service::EquipmentService;
+    use crate::services::mode_group_service::ModeGroupService;
+    use crate::services::mode_service::ModeService;
+
+    fn purged(rows: &[PurgedRow], entity_type: &str) -> i64 {
+        rows.iter()
+            .find(|row| row.entity_type == entity_type)
+            .map(|row| row.purged)
+            .unwrap_or_default()
+    }
+
+    fn to_sqlx(e: impl std::fmt::Display) -> sqlx::Error {
+        sqlx::Error::Protocol(e.to_string())
+    }
+
+    #[sqlx::test]
+    async fn test_purge_keeps_rows_within_retention(pool: PgPool) -> sqlx::Result<()> {
+        let equipment = EquipmentService::new(pool.clone());
+        let modes = ModeService::new(pool.clone());
+
+        let enterprise_type = sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = 'enterprise'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let enterprise = equipment
+            .create("Acme", enterprise_type, None, None, None)
+            .await
+            .map_err(to_sqlx)?;
+        let group = ModeGroupService::new(pool.clone())
+            .create("Packaging", "Packaging modes")
+            .await
+            .map_err(to_sqlx)?;
+        let mode = modes
+            .create(group.mode_group_id, "Changeover")
+            .await
+            .map_err(to_sqlx)?;
+
+        equipment
+            .delete(enterprise.equipment_id)
+            .await
+            .map_err(to_sqlx)?;
+        modes.delete(mode.mode_id).await.map_err(to_sqlx)?;
+
+        // deleted just now, still within the retention
+        let service = PurgeService::new(pool.clone(), 30);
+        let rows = service.purge().await.map_err(to_sqlx)?;
+        assert_eq!(purged(&rows, "equipment"), 0);
+        assert_eq!(purged(&rows, "mode"), 0);
+
+        sqlx::query!(
+            "UPDATE core.equipment SET deleted_at = now() - interval '31 days' WHERE equipment_id = $1",
+            enterprise.equipment_id
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!(
+            "UPDATE core.mode SET deleted_at = now() - interval '31 days' WHERE mode_id = $1",
+            mode.mode_id
+        )
+        .execute(&pool)
+        .await?;
+
+        let rows = service.purge().await.map_err(to_sqlx)?;
+        assert_eq!(purged(&rows, "equipment"), 1);
+        assert_eq!(purged(&rows, "mode"), 1);
+        // the group is live, it stays
+        assert_eq!(purged(&rows, "mode_group"), 0);
+
+        let left = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM core.equipment WHERE equipment_id = $1",
+            enterprise.equipment_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(left, Some(0));
+
+        let action = sqlx::query_scalar!(
+            "SELECT action FROM app.audit_log WHERE entity_id = $1 AND actor = 'purge'",
+            enterprise.equipment_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(action, "purged");
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_purge_keeps_equipment_with_history(pool: PgPool) -> sqlx::Result<()> {
+        let equipment = EquipmentService::new(pool.clone());
+        let enterprise_type = sqlx::query_scalar!(
+            "SELECT type_id FROM core.equipment_type WHERE type_name = 'enterprise'"
+        )
+        .fetch_one(&pool)
+        .await?;
+        let enterprise = equipment
+            .create("Acme", enterprise_type, None, None, None)
+            .await
+            .map_err(to_sqlx)?;
+
+        sqlx::query!(
+            r#"INSERT INTO core.equipment_mode_group_mapping (equipment_id, mode_group_id)
+               SELECT $1, mode_group_id FROM core.mode_group
+               WHERE mode_group_name = 'Default MES Mode Group'"#,
+            enterprise.equipment_id
+        )
+        .execute(&pool)
+        .await?;
+        let production = sqlx::query_scalar!(
+            "SELECT mode_id FROM core.mode WHERE mode_description = $1",
+            PRODUCTION_MODE
+        )
+        .fetch_one(&pool)
+        .await?;
+        EquipmentModeService::new(pool.clone())
+            .set_mode(enterprise.equipment_id, production, None)
+            .await
+            .map_err(to_sqlx)?;
+
+        equipment
+            .delete(enterprise.equipment_id)
+            .await
+            .map_err(to_sqlx)?;
+        sqlx::query!(
+            "UPDATE core.equipment SET deleted_at = now() - interval '31 days' WHERE equipment_id = $1",
+            enterprise.equipment_id
+        )
+        .execute(&pool)
+        .await?;
+
+        let rows = PurgeService::new(pool.clone(), 30)
+            .purge()
+            .await
+            .map_err(to_sqlx)?;
+        assert_eq!(purged(&rows, "equipment"), 0);
+
+        let history = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM core.equipment_mode_history WHERE equipment_id = $1",
+            enterprise.equipment_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(history, Some(1));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use crate::database::audit::AuditQueries;
+use crate::database::state_code_reservations::{
+    StateCodeReservationQueries, StateCodeReservationRow,
+};
+use crate::database::state_groups::StateGroupQueries;
+use crate::error::{AppError, AppResult, DatabaseContext, is_exclusion_violation};
+use sqlx::{PgPool, Postgres, Transaction};
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+const MAX_STATE_CODE: i32 = 9999;
+
+#[derive(Debug, Clone)]
+pub struct StateCodeReservation {
+    pub reservation_id: Uuid,
+    pub state_group_id: Uuid,
+    pub min_code: i32,
+    pub max_code: i32,
+    pub owner: String,
+    pub label: Option<String>,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<StateCodeReservationRow> for StateCodeReservation {
+    fn from(row: StateCodeReservationRow) -> Self {
+        Self {
+            reservation_id: row.reservation_id,
+            state_group_id: row.state_group_id,
+            min_code: row.min_code,
+            max_code: row.max_code,
+            owner: row.owner,
+            label: row.label,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StateCodeReservationService {
+    db: PgPool,
+    actor: Option<String>,
+}
+
+impl StateCodeReservationService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn get_by_state_group(
+        &self,
+        state_group_id: Uuid,
+    ) -> AppResult<Vec<StateCodeReservation>> {
+        debug!("Fetching state code reservations");
+
+        if !StateGroupQueries::exists(&self.db, state_group_id)
+            .await
+            .context("Failed to check if state group exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "State group with ID {} not found",
+                state_group_id
+            )));
+        }
+
+        let rows = StateCodeReservationQueries::get_by_state_group(&self.db, state_group_id)
+            .await
+            .context("Failed to fetch state code reservations")?;
+        let reservations: Vec<StateCodeReservation> =
+            rows.into_iter().map(StateCodeReservation::from).collect();
+        debug!("Found {} state code reservations", reservations.len());
+        Ok(reservations)
+    }
+
+    #[instrument(skip(self), fields(reservation_id = %reservation_id))]
+    pub async fn get_by_id(&self, reservation_id: Uuid) -> AppResult<StateCodeReservation> {
+        let row = StateCodeReservationQueries::get_by_id(&self.db, reservation_id)
+            .await
+            .context("Failed to fetch state code reservation")?
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "State code reservation with ID {} not found",
+                    reservation_id
+                ))
+            })?;
+        Ok(StateCodeReservation::from(row))
+    }
+
+    /// Set `min_code..=max_code` of a state group aside for `owner`, the range may not share a
+    /// code with another reservation in the group
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn create(
+        &self,
+        state_group_id: Uuid,
+        min_code: i32,
+        max_code: i32,
+        owner: &str,
+        label: Option<&str>,
+    ) -> AppResult<StateCodeReservation> {
+        debug!("Reserving state codes {}-{}", min_code, max_code);
+
+        let owner = owner.trim();
+        if owner.is_empty() {
+            return Err(AppError::Validation("owner cannot be empty".to_string()));
+        }
+        if min_code < 0 {
+            return Err(AppError::Validation(
+                "min_code cannot be negative".to_string(),
+            ));
+        }
+        if max_code > MAX_STATE_CODE {
+            return Err(AppError::Validation(format!(
+                "max_code cannot be greater than {}",
+                MAX_STATE_CODE
+            )));
+        }
+        if min_code > max_code {
+            return Err(AppError::Validation(
+                "min_code cannot be greater than max_code".to_string(),
+            ));
+        }
+        let label = label.map(str::trim).filter(|label| !label.is_empty());
+
+        if !StateGroupQueries::exists(&self.db, state_group_id)
+            .await
+            .context("Failed to check if state group exists")?
+        {
+            return Err(AppError::Validation(format!(
+                "state_group_id '{}' does not exist",
+                state_group_id
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        if let Some(existing) = StateCodeReservationQueries::find_overlapping(
+            &mut tx,
+            state_group_id,
+            min_code,
+            max_code,
+        )
+        .await
+        .context("Failed to check for overlapping reservations")?
+        {
+            return Err(AppError::Conflict(format!(
+                "state codes {}-{} are already reserved for '{}'",
+                existing.min_code, existing.max_code, existing.owner
+            )));
+        }
+
+        let reservation_id = StateCodeReservationQueries::create(
+            &mut tx,
+            state_group_id,
+            min_code,
+            max_code,
+            owner,
+            label,
+        )
+        .await
+        .map_err(|e| {
+            // reserved by someone else since the check above
+            if is_exclusion_violation(&e) {
+                AppError::Conflict(format!(
+                    "state codes {}-{} are already reserved",
+                    min_code, max_code
+                ))
+            } else {
+                AppError::database("Failed to create state code reservation", e)
+            }
+        })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!(
+            "Successfully created state code reservation {}",
+            reservation_id
+        );
+        self.get_by_id(reservation_id).await
+    }
+
+    #[instrument(skip(self), fields(reservation_id = %reservation_id))]
+    pub async fn delete(&self, reservation_id: Uuid) -> AppResult<()> {
+        debug!("Deleting state code reservation");
+        let mut tx = self.begin().await?;
+        let deleted = StateCodeReservationQueries::delete(&mut tx, reservation_id)
+            .await
+            .with_context(|| {
+                format!("Failed to delete state code reservation {}", reservation_id)
+            })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "State code reservation with ID {} not found",
+                reservation_id
+            )));
+        }
+
+        debug!("Successfully deleted state code reservation");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn default_state_group_id(pool: &PgPool) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "SELECT state_group_id FROM core.state_group WHERE state_group_name = 'Default MES State Group'"
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_service_create_and_delete(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateCodeReservationService::new(pool.clone());
+        let group_id = default_state_group_id(&pool).await?;
+
+        let reservation = service
+            .create(group_id, 100, 199, "  Rockwell  ", Some("AB PLCs"))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(reservation.owner, "Rockwell");
+        assert_eq!(reservation.label.as_deref(), Some("AB PLCs"));
+
+        let result = service.create(group_id, 150, 250, "Siemens", None).await;
+        assert!(matches!(
+            result,
+            Err(AppError::Conflict(ref message))
+                if message == "state codes 100-199 are already reserved for 'Rockwell'"
+        ));
+
+        let result = service.create(group_id, 300, 200, "Siemens", None).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let result = service.create(group_id, 200, 299, " ", None).await;
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+
+        let result = service
+            .create(Uuid::new_v4(), 200, 299, "Siemens", None)
+            .await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+
+        service
+            .delete(reservation.reservation_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service.delete(reservation.reservation_id).await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        Ok(())
+    }
+}
@@ -1 +1,758 @@
+use crate::database::audit::AuditQueries;
+use crate::database::equipment::EquipmentQueries;
+use crate::database::state_groups::{StateGroupQueries, StateGroupRow};
+use crate::error::{AppError, AppResult, DatabaseContext, is_unique_violation};
+use crate::models::core::{
+    AvailableStateCodes, BulkGroupAssignment, EquipmentStateGroupMapping, GroupEquipment,
+    StateGroupUsageStats,
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
 
+#[derive(Debug, Clone)]
+pub struct StateGroup {
+    pub state_group_id: Uuid,
+    pub state_group_name: String,
+    pub state_group_description: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<StateGroupRow> for StateGroup {
+    fn from(row: StateGroupRow) -> Self {
+        Self {
+            state_group_id: row.state_group_id,
+            state_group_name: row.state_group_name,
+            state_group_description: row.state_group_description,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StateGroupService {
+    db: PgPool,
+    actor: Option<String>,
+}
+
+impl StateGroupService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_all(&self) -> AppResult<Vec<StateGroup>> {
+        debug!("Fetching all state groups");
+        let rows = StateGroupQueries::get_all(&self.db)
+            .await
+            .context("Failed to fetch all state groups")?;
+        let groups: Vec<StateGroup> = rows.into_iter().map(StateGroup::from).collect();
+        debug!("Found {} state groups", groups.len());
+        Ok(groups)
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn get_by_id(&self, state_group_id: Uuid) -> AppResult<StateGroup> {
+        debug!("Fetching state group by ID");
+        let row = StateGroupQueries::get_by_state_group_id(&self.db, state_group_id)
+            .await
+            .context("Failed to fetch state group by ID")?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("State group with ID {} not found", state_group_id))
+            })?;
+
+        debug!("Found state group: {}", row.state_group_name);
+        Ok(StateGroup::from(row))
+    }
+
+    #[instrument(skip(self), fields(state_group_name = %state_group_name))]
+    pub async fn get_by_name(&self, state_group_name: &str) -> AppResult<Option<StateGroup>> {
+        debug!("Fetching state group by name");
+        let row = StateGroupQueries::get_by_state_group_name(&self.db, state_group_name)
+            .await
+            .context("Failed to fetch state group by name")?;
+
+        match row {
+            Some(r) => {
+                debug!("Found state group: {}", r.state_group_name);
+                Ok(Some(StateGroup::from(r)))
+            }
+            None => {
+                debug!("State group not found");
+                Ok(None)
+            }
+        }
+    }
+
+    #[instrument(skip(self), fields(state_group_description = %state_group_description))]
+    pub async fn get_by_description(
+        &self,
+        state_group_description: &str,
+    ) -> AppResult<Option<StateGroup>> {
+        debug!("Fetching state group by description");
+        let row =
+            StateGroupQueries::get_by_state_group_description(&self.db, state_group_description)
+                .await
+                .context("Failed to fetch state group by description")?;
+
+        match row {
+            Some(r) => {
+                debug!("Found state group: {}", r.state_group_name);
+                Ok(Some(StateGroup::from(r)))
+            }
+            None => {
+                debug!("State group not found");
+                Ok(None)
+            }
+        }
+    }
+
+    #[instrument(skip(self), fields(state_group_name = %state_group_name))]
+    pub async fn create(
+        &self,
+        state_group_name: &str,
+        state_group_description: &str,
+    ) -> AppResult<StateGroup> {
+        debug!("Creating new state group");
+
+        let mut tx = self.begin().await?;
+        let row = StateGroupQueries::create_state_group(
+            &mut tx,
+            state_group_name,
+            state_group_description,
+        )
+        .await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully created state group: {}", row.state_group_name);
+        Ok(StateGroup::from(row))
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id, state_group_name = %state_group_name))]
+    pub async fn update_name(
+        &self,
+        state_group_id: Uuid,
+        state_group_name: &str,
+    ) -> AppResult<StateGroup> {
+        debug!("Updating state group name");
+
+        let mut tx = self.begin().await?;
+        let row =
+            StateGroupQueries::update_state_group_name(&mut tx, state_group_id, state_group_name)
+                .await?
+                .ok_or_else(|| {
+                    AppError::NotFound(format!("State group with ID {} not found", state_group_id))
+                })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!(
+            "Successfully updated state group name: {}",
+            row.state_group_name
+        );
+        Ok(StateGroup::from(row))
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn update_description(
+        &self,
+        state_group_id: Uuid,
+        state_group_description: &str,
+    ) -> AppResult<StateGroup> {
+        debug!("Updating state group description");
+
+        let mut tx = self.begin().await?;
+        let row = StateGroupQueries::update_state_group_description(
+            &mut tx,
+            state_group_id,
+            state_group_description,
+        )
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("State group with ID {} not found", state_group_id))
+        })?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully updated state group description");
+        Ok(StateGroup::from(row))
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn delete(&self, state_group_id: Uuid) -> AppResult<()> {
+        debug!("Deleting state group");
+
+        if self.is_in_use(state_group_id).await? {
+            return Err(AppError::Conflict(format!(
+                "State group {} is in use and cannot be deleted",
+                state_group_id
+            )));
+        }
+
+        let mut tx = self.begin().await?;
+        let deleted = StateGroupQueries::delete_state_group(&mut tx, state_group_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "State group with ID {} not found",
+                state_group_id
+            )));
+        }
+
+        debug!("Successfully deleted state group");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn restore(&self, state_group_id: Uuid) -> AppResult<StateGroup> {
+        debug!("Restoring state group");
+
+        let mut tx = self.begin().await?;
+        let row = match StateGroupQueries::restore_state_group(&mut tx, state_group_id).await {
+            Err(e) if is_unique_violation(&e) => {
+                return Err(AppError::Conflict(format!(
+                    "State group {} can't be restored, a state group with its name already exists",
+                    state_group_id
+                )));
+            }
+            result => result
+                .with_context(|| format!("Failed to restore state group {}", state_group_id))?
+                .ok_or_else(|| {
+                    AppError::NotFound(format!(
+                        "Deleted state group with ID {} not found",
+                        state_group_id
+                    ))
+                })?,
+        };
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!(
+            "Successfully restored state group: {}",
+            row.state_group_name
+        );
+        Ok(StateGroup::from(row))
+    }
+
+    /// A state group is in use while it still owns states or is mapped to equipment
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn is_in_use(&self, state_group_id: Uuid) -> AppResult<bool> {
+        let in_use = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM core.state WHERE state_group_id = $1 AND deleted_at IS NULL)
+                   OR EXISTS(SELECT 1 FROM core.equipment_state_group_mapping m
+                             JOIN core.equipment e ON e.equipment_id = m.equipment_id
+                             WHERE m.state_group_id = $1 AND e.deleted_at IS NULL)"#,
+            state_group_id
+        )
+        .fetch_one(&self.db)
+        .await
+        .context("Failed to check state group usage")?
+        .unwrap_or(false);
+
+        debug!("State group {} in use: {}", state_group_id, in_use);
+        Ok(in_use)
+    }
+
+    /// Maps the equipment to the state group, its states can then be reported for the
+    /// equipment
+    #[instrument(skip(self), fields(state_group_id = %state_group_id, equipment_id = %equipment_id))]
+    pub async fn assign_equipment(
+        &self,
+        state_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> AppResult<EquipmentStateGroupMapping> {
+        debug!("Assigning equipment to state group");
+
+        let mut tx = self.begin().await?;
+        StateGroupQueries::assign_equipment(&mut tx, state_group_id, equipment_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully assigned equipment to state group");
+        Ok(EquipmentStateGroupMapping {
+            equipment_id,
+            state_group_id,
+        })
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id, equipment_id = %equipment_id))]
+    pub async fn unassign_equipment(
+        &self,
+        state_group_id: Uuid,
+        equipment_id: Uuid,
+    ) -> AppResult<EquipmentStateGroupMapping> {
+        debug!("Unassigning equipment from state group");
+
+        let mut tx = self.begin().await?;
+        StateGroupQueries::unassign_equipment(&mut tx, state_group_id, equipment_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully unassigned equipment from state group");
+        Ok(EquipmentStateGroupMapping {
+            equipment_id,
+            state_group_id,
+        })
+    }
+
+    /// Maps all the equipment to the state group, fails when every one of them already is
+    #[instrument(skip(self, equipment_ids), fields(state_group_id = %state_group_id, count = equipment_ids.len()))]
+    pub async fn bulk_assign_equipment(
+        &self,
+        state_group_id: Uuid,
+        equipment_ids: &[Uuid],
+    ) -> AppResult<BulkGroupAssignment> {
+        debug!("Bulk assigning equipment to state group");
+
+        let mut tx = self.begin().await?;
+        let assignment =
+            StateGroupQueries::bulk_assign_equipment(&mut tx, state_group_id, equipment_ids)
+                .await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!(
+            "Successfully assigned {} equipment to state group, {} already were",
+            assignment.assigned_equipment_ids.len(),
+            assignment.skipped_equipment_ids.len()
+        );
+        Ok(assignment)
+    }
+
+    /// Live equipment mapped to the state group
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn get_equipment(&self, state_group_id: Uuid) -> AppResult<Vec<GroupEquipment>> {
+        debug!("Fetching equipment for state group");
+
+        let equipment = StateGroupQueries::get_equipment(&self.db, state_group_id).await?;
+
+        debug!("Found {} equipment for state group", equipment.len());
+        Ok(equipment)
+    }
+
+    /// State and equipment counts per group and the time spent in its states between
+    /// `from` and `to`, for one group when `state_group_id` is given
+    #[instrument(skip(self))]
+    pub async fn usage_stats(
+        &self,
+        state_group_id: Option<Uuid>,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> AppResult<StateGroupUsageStats> {
+        debug!("Fetching state group usage stats");
+
+        let stats = StateGroupQueries::get_usage_stats(&self.db, from, to, state_group_id).await?;
+
+        debug!(
+            "{} of {} state groups were idle",
+            stats.summary.idle_state_groups, stats.summary.total_state_groups
+        );
+        Ok(stats)
+    }
+
+    /// Free codes in a state group between `min_code` and `max_code`, codes reserved for
+    /// anyone but `owner` are not free
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn available_codes(
+        &self,
+        state_group_id: Uuid,
+        min_code: Option<i32>,
+        max_code: Option<i32>,
+        owner: Option<&str>,
+    ) -> AppResult<AvailableStateCodes> {
+        debug!("Fetching available state codes");
+
+        let codes = StateGroupQueries::get_available_codes(
+            &self.db,
+            state_group_id,
+            min_code,
+            max_code,
+            owner,
+        )
+        .await?;
+
+        debug!(
+            "{} of {} codes available",
+            codes.usage_stats.available_count, codes.code_range.total_range
+        );
+        Ok(codes)
+    }
+
+    /// Live state groups the equipment is mapped to
+    #[instrument(skip(self), fields(equipment_id = %equipment_id))]
+    pub async fn get_by_equipment_id(&self, equipment_id: Uuid) -> AppResult<Vec<StateGroup>> {
+        debug!("Fetching state groups for equipment");
+
+        if !EquipmentQueries::exists(&self.db, equipment_id)
+            .await
+            .context("Failed to check if equipment exists")?
+        {
+            return Err(AppError::NotFound(format!(
+                "Equipment with ID {} not found",
+                equipment_id
+            )));
+        }
+
+        let rows = StateGroupQueries::get_by_equipment_id(&self.db, equipment_id)
+            .await
+            .context("Failed to fetch state groups for equipment")?;
+        let groups: Vec<StateGroup> = rows.into_iter().map(StateGroup::from).collect();
+        debug!("Found {} state groups for equipment", groups.len());
+        Ok(groups)
+    }
+
+    #[instrument(skip(self), fields(state_group_id = %state_group_id))]
+    pub async fn exists(&self, state_group_id: Uuid) -> AppResult<bool> {
+        let exists = StateGroupQueries::exists(&self.db, state_group_id)
+            .await
+            .context("Failed to check if state group exists")?;
+        debug!("State group {} exists: {}", state_group_id, exists);
+        Ok(exists)
+    }
+
+    #[instrument(skip(self), fields(state_group_name = %state_group_name))]
+    pub async fn name_exists(&self, state_group_name: &str) -> AppResult<bool> {
+        let exists = StateGroupQueries::name_exists(&self.db, state_group_name)
+            .await
+            .context("Failed to check if state group name exists")?;
+        debug!("State group name '{}' exists: {}", state_group_name, exists);
+        Ok(exists)
+    }
+
+    /// Search state groups by name or description (case-insensitive)
+    #[instrument(skip(self), fields(search_term = %search_term))]
+    pub async fn search(&self, search_term: &str) -> AppResult<Vec<StateGroup>> {
+        debug!("Searching state groups: {}", search_term);
+
+        if search_term.trim().is_empty() {
+            return Err(AppError::Validation(
+                "Search term cannot be empty".to_string(),
+            ));
+        }
+
+        let mut rows = StateGroupQueries::search_by_name(&self.db, search_term)
+            .await
+            .context("Failed to search state groups by name")?;
+
+        let by_description = StateGroupQueries::search_by_description(&self.db, search_term)
+            .await
+            .context("Failed to search state groups by description")?;
+
+        for row in by_description {
+            if !rows.iter().any(|r| r.state_group_id == row.state_group_id) {
+                rows.push(row);
+            }
+        }
+        rows.sort_by(|a, b| a.state_group_name.cmp(&b.state_group_name));
+
+        let groups: Vec<StateGroup> = rows.into_iter().map(StateGroup::from).collect();
+        debug!(
+            "Found {} state groups matching '{}'",
+            groups.len(),
+            search_term
+        );
+        Ok(groups)
+    }
+
+    /// Get state groups with pagination
+    #[instrument(skip(self))]
+    pub async fn get_paginated(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> AppResult<(Vec<StateGroup>, i64)> {
+        debug!(
+            "Fetching state groups with pagination: offset={}, limit={}",
+            offset, limit
+        );
+
+        if offset < 0 {
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
+        }
+
+        if limit <= 0 || limit > 1000 {
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
+        }
+
+        let total_count =
+            sqlx::query_scalar!("SELECT COUNT(*) FROM core.state_group WHERE deleted_at IS NULL")
+                .fetch_one(&self.db)
+                .await
+                .context("Failed to get total count of state groups")?
+                .unwrap_or(0);
+
+        let rows = sqlx::query_as!(
+            StateGroupRow,
+            r#"SELECT state_group_id, state_group_name, state_group_description, created_at, updated_at
+               FROM core.state_group
+               WHERE deleted_at IS NULL
+               ORDER BY state_group_name
+               LIMIT $1 OFFSET $2"#,
+            limit,
+            offset
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch paginated state groups")?;
+
+        let groups: Vec<StateGroup> = rows.into_iter().map(StateGroup::from).collect();
+        debug!(
+            "Found {} state groups (total: {})",
+            groups.len(),
+            total_count
+        );
+
+        Ok((groups, total_count))
+    }
+
+    /// Get count of all state groups
+    #[instrument(skip(self))]
+    pub async fn count(&self) -> AppResult<i64> {
+        let count =
+            sqlx::query_scalar!("SELECT COUNT(*) FROM core.state_group WHERE deleted_at IS NULL")
+                .fetch_one(&self.db)
+                .await
+                .context("Failed to count state groups")?
+                .unwrap_or(0);
+
+        debug!("Total state groups count: {}", count);
+        Ok(count)
+    }
+
+    /// Bulk create state groups (useful for initial setup or imports)
+    #[instrument(skip(self, state_groups))]
+    pub async fn bulk_create(&self, state_groups: Vec<(&str, &str)>) -> AppResult<Vec<StateGroup>> {
+        debug!("Bulk creating {} state groups", state_groups.len());
+        let mut created_groups = Vec::new();
+        let mut errors = Vec::new();
+
+        for (name, description) in state_groups {
+            match self.create(name, description).await {
+                Ok(state_group) => {
+                    created_groups.push(state_group);
+                }
+                Err(AppError::Conflict(_)) => {
+                    debug!("Skipping duplicate state group: {}", name);
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to create '{}': {}", name, e));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Bulk create failed with errors: {}",
+                errors.join(", ")
+            )));
+        }
+
+        debug!("Successfully created {} state groups", created_groups.len());
+        Ok(created_groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    #[sqlx::test]
+    async fn test_service_create_and_get(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateGroupService::new(pool);
+
+        let created = service
+            .create("Test Service Group", "Test Service Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(created.state_group_name, "Test Service Group");
+        assert_eq!(created.state_group_description, "Test Service Description");
+
+        let found = service
+            .get_by_id(created.state_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(found.state_group_id, created.state_group_id);
+        assert_eq!(found.state_group_name, "Test Service Group");
+
+        let by_name = service
+            .get_by_name("Test Service Group")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(by_name.is_some());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_validation(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateGroupService::new(pool);
+
+        let result = service.create("", "Valid description").await;
+        assert!(result.unwrap_err().to_string().contains("cannot be empty"));
+
+        let result = service.create("Valid name", "   ").await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_duplicate_handling(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateGroupService::new(pool);
+
+        service
+            .create("Duplicate Test", "Description 1")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service.create("Duplicate Test", "Description 2").await;
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+
+        let exists = service
+            .name_exists("Duplicate Test")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(exists);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_updates(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateGroupService::new(pool);
+
+        let created = service
+            .create("Original Name", "Original Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let updated_name = service
+            .update_name(created.state_group_id, "Updated Name")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated_name.state_group_name, "Updated Name");
+
+        let updated_desc = service
+            .update_description(created.state_group_id, "Updated Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated_desc.state_group_name, "Updated Name");
+        assert_eq!(updated_desc.state_group_description, "Updated Description");
+
+        let result = service.update_name(Uuid::new_v4(), "Missing").await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_delete_in_use(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateGroupService::new(pool.clone());
+
+        let group = service
+            .create("Delete Group", "Delete Description")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        sqlx::query!(
+            "INSERT INTO core.state (state_group_id, state_code, state_description) VALUES ($1, 1, 'running')",
+            group.state_group_id
+        )
+        .execute(&pool)
+        .await?;
+
+        let result = service.delete(group.state_group_id).await;
+        assert!(result.unwrap_err().to_string().contains("in use"));
+
+        sqlx::query!(
+            "DELETE FROM core.state WHERE state_group_id = $1",
+            group.state_group_id
+        )
+        .execute(&pool)
+        .await?;
+
+        service
+            .delete(group.state_group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service.delete(group.state_group_id).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_search_and_pagination(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateGroupService::new(pool);
+
+        for i in 1..=5 {
+            service
+                .create(
+                    &format!("Packaging Group {}", i),
+                    &format!("Line {} states", i),
+                )
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let found = service
+            .search("packaging")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(found.len(), 5);
+
+        let found = service
+            .search("line 3")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(found.len(), 1);
+
+        let (page, total) = service
+            .get_paginated(0, 3)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(page.len(), 3);
+        assert!(total >= 5);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_bulk_create(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateGroupService::new(pool);
+
+        let created = service
+            .bulk_create(vec![
+                ("Bulk Group 1", "Bulk Description 1"),
+                ("Bulk Group 2", "Bulk Description 2"),
+                ("Bulk Group 1", "Duplicate is skipped"),
+            ])
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(created.len(), 2);
+
+        Ok(())
+    }
+}
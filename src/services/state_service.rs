@@ -1 +1,918 @@
+use crate::database::audit::AuditQueries;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use time::OffsetDateTime;
+use tracing::{debug, instrument};
+use uuid::Uuid;
 
+use crate::database::state_code_reservations::StateCodeReservationQueries;
+use crate::database::state_groups::StateGroupQueries;
+use crate::database::states::{StateRow, StateRowQueries};
+use crate::error::{AppError, AppResult, DatabaseContext, is_unique_violation};
+use crate::models::core::StateUsageStats;
+
+#[derive(Debug, Clone)]
+pub struct State {
+    pub state_id: Uuid,
+    pub state_group_id: Uuid,
+    pub state_code: i32,
+    pub state_description: String,
+    pub created_at: Option<OffsetDateTime>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+impl From<StateRow> for State {
+    fn from(row: StateRow) -> Self {
+        Self {
+            state_id: row.state_id,
+            state_group_id: row.state_group_id,
+            state_code: row.state_code,
+            state_description: row.state_description,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StateService {
+    db: PgPool,
+    actor: Option<String>,
+}
+
+impl StateService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, actor: None }
+    }
+
+    /// The same service with its changes logged as made by `actor`
+    pub fn acting_as(&self, actor: Option<String>) -> Self {
+        Self {
+            db: self.db.clone(),
+            actor,
+        }
+    }
+
+    /// Transaction for a change, audit log entries written in it name `self.actor`
+    async fn begin(&self) -> AppResult<Transaction<'static, Postgres>> {
+        AuditQueries::begin(&self.db, self.actor.as_deref())
+            .await
+            .context("Failed to start a transaction")
+    }
+
+    /// Fails when `state_code` is inside a range reserved for someone other than `owner`.
+    /// Run it in the transaction that writes the code.
+    pub(crate) async fn check_reservation(
+        db: &mut PgConnection,
+        state_group_id: Uuid,
+        state_code: i32,
+        owner: Option<&str>,
+    ) -> AppResult<()> {
+        if let Some(reservation) = StateCodeReservationQueries::find_overlapping(
+            db,
+            state_group_id,
+            state_code,
+            state_code,
+        )
+        .await
+        .context("Failed to check state code reservations")?
+            && owner.map(str::trim) != Some(reservation.owner.as_str())
+        {
+            return Err(AppError::Conflict(format!(
+                "state_code {} is reserved for '{}' ({}-{})",
+                state_code, reservation.owner, reservation.min_code, reservation.max_code
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_all(&self) -> AppResult<Vec<State>> {
+        debug!("Fetching all states");
+
+        let rows = StateRowQueries::get_all(&self.db)
+            .await
+            .context("Failed to fetch all states")?;
+
+        let states: Vec<State> = rows.into_iter().map(State::from).collect();
+
+        debug!("Retrieved {} states", states.len());
+        Ok(states)
+    }
+
+    #[instrument(skip(self), fields(id = %state_id))]
+    pub async fn get_by_id(&self, state_id: Uuid) -> AppResult<State> {
+        debug!("Fetching state by id: {}", state_id);
+
+        let row = StateRowQueries::get_by_state_id(&self.db, state_id)
+            .await
+            .context("Failed to fetch state by id")?
+            .ok_or_else(|| AppError::NotFound(format!("State with id '{}' not found", state_id)))?;
+
+        let state = State::from(row);
+        debug!("Retrieved state: {}", state.state_description);
+        Ok(state)
+    }
+
+    /// Resolve a raw PLC state code within a state group
+    #[instrument(skip(self), fields(group_id = %state_group_id, code = %state_code))]
+    pub async fn get_by_code(
+        &self,
+        state_group_id: Uuid,
+        state_code: i32,
+    ) -> AppResult<Option<State>> {
+        debug!(
+            "Fetching state by code {} in group {}",
+            state_code, state_group_id
+        );
+
+        let row =
+            StateRowQueries::get_by_state_code_and_group(&self.db, state_group_id, state_code)
+                .await
+                .context("Failed to fetch state by code")?;
+
+        Ok(row.map(State::from))
+    }
+
+    #[instrument(skip(self), fields(group_id = %state_group_id))]
+    pub async fn get_by_state_group_id(&self, state_group_id: Uuid) -> AppResult<Vec<State>> {
+        debug!("Fetching states by state_group_id: {}", state_group_id);
+
+        let rows = StateRowQueries::get_states_for_group(&self.db, state_group_id).await?;
+
+        let states: Vec<State> = rows.into_iter().map(State::from).collect();
+
+        debug!(
+            "Retrieved {} states for group {}",
+            states.len(),
+            state_group_id
+        );
+        Ok(states)
+    }
+
+    #[instrument(skip(self), fields(group_id = %state_group_id, min = %min_code, max = %max_code))]
+    pub async fn get_by_code_range(
+        &self,
+        state_group_id: Uuid,
+        min_code: i32,
+        max_code: i32,
+    ) -> AppResult<Vec<State>> {
+        debug!(
+            "Fetching states in code range {}..={} for group {}",
+            min_code, max_code, state_group_id
+        );
+
+        let rows =
+            StateRowQueries::get_states_by_code_range(&self.db, state_group_id, min_code, max_code)
+                .await?;
+
+        let states: Vec<State> = rows.into_iter().map(State::from).collect();
+
+        debug!("Retrieved {} states in code range", states.len());
+        Ok(states)
+    }
+
+    #[instrument(skip(self), fields(search_term = %search_term))]
+    pub async fn search_by_description(&self, search_term: &str) -> AppResult<Vec<State>> {
+        debug!("Searching states by description: {}", search_term);
+
+        if search_term.trim().is_empty() {
+            return Err(AppError::Validation(
+                "Search term cannot be empty".to_string(),
+            ));
+        }
+
+        let rows = StateRowQueries::search_by_description(&self.db, search_term)
+            .await
+            .context("Failed to search states by description")?;
+
+        let states: Vec<State> = rows.into_iter().map(State::from).collect();
+
+        debug!(
+            "Found {} states matching search term '{}'",
+            states.len(),
+            search_term
+        );
+        Ok(states)
+    }
+
+    /// Codes inside a reserved range can only be taken by the `owner` of the reservation
+    #[instrument(skip(self), fields(group_id = %state_group_id, code = %state_code, description = %state_description))]
+    pub async fn create(
+        &self,
+        state_group_id: Uuid,
+        state_code: i32,
+        state_description: &str,
+        owner: Option<&str>,
+    ) -> AppResult<State> {
+        debug!(
+            "Creating state {} '{}' in group {}",
+            state_code, state_description, state_group_id
+        );
+
+        let mut tx = self.begin().await?;
+        Self::check_reservation(&mut tx, state_group_id, state_code, owner).await?;
+        let row =
+            StateRowQueries::create_state(&mut tx, state_group_id, state_code, state_description)
+                .await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        let state = State::from(row);
+        debug!("Successfully created state: {}", state.state_description);
+        Ok(state)
+    }
+
+    /// All or nothing, the batch is rolled back when any state fails
+    #[instrument(skip(self))]
+    pub async fn bulk_create(
+        &self,
+        state_data: Vec<(Uuid, i32, &str, Option<&str>)>,
+    ) -> AppResult<Vec<State>> {
+        debug!("Bulk creating {} states", state_data.len());
+
+        if state_data.is_empty() {
+            return Err(AppError::Validation(
+                "No states provided for bulk creation".to_string(),
+            ));
+        }
+
+        if state_data.len() > 100 {
+            return Err(AppError::Validation(
+                "Cannot create more than 100 states at once".to_string(),
+            ));
+        }
+
+        // one transaction so a state that can't be created rolls the whole batch back
+        let mut tx = self.begin().await?;
+        let mut created_states = Vec::with_capacity(state_data.len());
+        for (state_group_id, state_code, state_description, owner) in state_data {
+            Self::check_reservation(&mut tx, state_group_id, state_code, owner).await?;
+            let row = StateRowQueries::create_state(
+                &mut tx,
+                state_group_id,
+                state_code,
+                state_description,
+            )
+            .await?;
+            created_states.push(State::from(row));
+        }
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully bulk created {} states", created_states.len());
+        Ok(created_states)
+    }
+
+    #[instrument(skip(self), fields(id = %state_id, new_description = %state_description))]
+    pub async fn update_description(
+        &self,
+        state_id: Uuid,
+        state_description: &str,
+    ) -> AppResult<State> {
+        debug!("Updating state description for id: {}", state_id);
+
+        let mut tx = self.begin().await?;
+        let row = StateRowQueries::update_state_description(&mut tx, state_id, state_description)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("State with id '{}' not found", state_id)))?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        let state = State::from(row);
+        debug!(
+            "Successfully updated state description: {}",
+            state.state_description
+        );
+        Ok(state)
+    }
+
+    /// Like `create`, a code inside a reserved range needs the reservation's `owner`
+    #[instrument(skip(self), fields(id = %state_id, new_code = %state_code))]
+    pub async fn update_code(
+        &self,
+        state_id: Uuid,
+        state_code: i32,
+        owner: Option<&str>,
+    ) -> AppResult<State> {
+        debug!("Updating state code for id: {}", state_id);
+
+        let mut tx = self.begin().await?;
+        let row = StateRowQueries::update_state_code(&mut tx, state_id, state_code)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("State with id '{}' not found", state_id)))?;
+        Self::check_reservation(&mut tx, row.state_group_id, row.state_code, owner).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        let state = State::from(row);
+        debug!("Successfully updated state code: {}", state.state_code);
+        Ok(state)
+    }
+
+    /// The state keeps its code, which may be reserved in the new group
+    #[instrument(skip(self), fields(id = %state_id, new_group_id = %state_group_id))]
+    pub async fn update_state_group(
+        &self,
+        state_id: Uuid,
+        state_group_id: Uuid,
+        owner: Option<&str>,
+    ) -> AppResult<State> {
+        debug!("Updating state group for id: {}", state_id);
+
+        let mut tx = self.begin().await?;
+        let row = StateRowQueries::update_state_group(&mut tx, state_id, state_group_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("State with id '{}' not found", state_id)))?;
+        Self::check_reservation(&mut tx, row.state_group_id, row.state_code, owner).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        let state = State::from(row);
+        debug!(
+            "Successfully updated state group for state: {}",
+            state.state_description
+        );
+        Ok(state)
+    }
+
+    #[instrument(skip(self), fields(id = %state_id))]
+    pub async fn delete(&self, state_id: Uuid) -> AppResult<()> {
+        debug!("Deleting state: {}", state_id);
+
+        let mut tx = self.begin().await?;
+        let deleted = StateRowQueries::delete_state(&mut tx, state_id).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "State with id '{}' not found",
+                state_id
+            )));
+        }
+
+        debug!("Successfully deleted state: {}", state_id);
+        Ok(())
+    }
+
+    /// A state can only be restored into a state group that is not deleted itself, and its
+    /// code may have been reserved while it was deleted
+    #[instrument(skip(self), fields(id = %state_id))]
+    pub async fn restore(&self, state_id: Uuid, owner: Option<&str>) -> AppResult<State> {
+        debug!("Restoring state: {}", state_id);
+
+        let mut tx = self.begin().await?;
+        let row = match StateRowQueries::restore_state(&mut tx, state_id).await {
+            Err(e) if is_unique_violation(&e) => {
+                return Err(AppError::Conflict(format!(
+                    "State {} can't be restored, a state with its code or description already exists in its state group",
+                    state_id
+                )));
+            }
+            result => result.context("Failed to restore state")?.ok_or_else(|| {
+                AppError::NotFound(format!("Deleted state with id '{}' not found", state_id))
+            })?,
+        };
+
+        if !StateGroupQueries::exists(&self.db, row.state_group_id)
+            .await
+            .context("Failed to check if state group exists")?
+        {
+            return Err(AppError::Conflict(format!(
+                "State {} can't be restored while its state group {} is deleted",
+                state_id, row.state_group_id
+            )));
+        }
+        Self::check_reservation(&mut tx, row.state_group_id, row.state_code, owner).await?;
+        tx.commit().await.context("Failed to commit the change")?;
+
+        debug!("Successfully restored state: {}", state_id);
+        Ok(State::from(row))
+    }
+
+    #[instrument(skip(self))]
+    pub async fn count(&self) -> AppResult<i64> {
+        debug!("Getting total state count");
+
+        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM core.state WHERE deleted_at IS NULL")
+            .fetch_one(&self.db)
+            .await
+            .context("Failed to get state count")?
+            .unwrap_or(0);
+
+        debug!("Total states: {}", count);
+        Ok(count)
+    }
+
+    #[instrument(skip(self), fields(id = %state_id))]
+    pub async fn exists(&self, state_id: Uuid) -> AppResult<bool> {
+        debug!("Checking if state exists: {}", state_id);
+
+        let exists = StateRowQueries::exists(&self.db, state_id)
+            .await
+            .context("Failed to check if state exists")?;
+
+        debug!("State {} exists: {}", state_id, exists);
+        Ok(exists)
+    }
+
+    /// Time equipment spent in each state between `from` and `to`, listed per state group
+    #[instrument(skip(self))]
+    pub async fn usage_stats(
+        &self,
+        state_id: Option<Uuid>,
+        from: Option<OffsetDateTime>,
+        to: Option<OffsetDateTime>,
+    ) -> AppResult<StateUsageStats> {
+        debug!("Fetching state usage stats");
+
+        let stats = StateRowQueries::get_usage_stats(&self.db, from, to, state_id).await?;
+
+        debug!(
+            "{} of {} states were unused",
+            stats.summary.unused_states, stats.summary.total_states
+        );
+        Ok(stats)
+    }
+
+    #[instrument(skip(self), fields(group_id = %state_group_id, code = %state_code))]
+    pub async fn code_exists_in_group(
+        &self,
+        state_group_id: Uuid,
+        state_code: i32,
+    ) -> AppResult<bool> {
+        let exists = StateRowQueries::code_exists_in_group(&self.db, state_group_id, state_code)
+            .await
+            .context("Failed to check if code exists in group")?;
+
+        debug!(
+            "Code {} exists in group {}: {}",
+            state_code, state_group_id, exists
+        );
+        Ok(exists)
+    }
+
+    #[instrument(skip(self), fields(group_id = %state_group_id, description = %state_description))]
+    pub async fn description_exists_in_group(
+        &self,
+        state_group_id: Uuid,
+        state_description: &str,
+    ) -> AppResult<bool> {
+        let exists = StateRowQueries::description_exists_in_group(
+            &self.db,
+            state_group_id,
+            state_description,
+        )
+        .await
+        .context("Failed to check if description exists in group")?;
+
+        debug!(
+            "Description '{}' exists in group {}: {}",
+            state_description, state_group_id, exists
+        );
+        Ok(exists)
+    }
+
+    /// Get states that match multiple criteria
+    #[instrument(skip(self))]
+    pub async fn search_with_filters(
+        &self,
+        state_group_id: Option<Uuid>,
+        description_search: Option<&str>,
+        offset: i64,
+        limit: i64,
+    ) -> AppResult<(Vec<State>, i64)> {
+        debug!(
+            "Searching states with filters: group_id={:?}, search={:?}",
+            state_group_id, description_search
+        );
+
+        if offset < 0 {
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
+        }
+        if limit <= 0 || limit > 1000 {
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
+        }
+
+        let description_pattern = description_search
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("%{}%", s.to_lowercase()));
+
+        let total_count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) FROM core.state
+               WHERE ($1::uuid IS NULL OR state_group_id = $1)
+                 AND ($2::text IS NULL OR lower(state_description::text COLLATE "C") LIKE $2) AND deleted_at IS NULL"#,
+            state_group_id,
+            description_pattern
+        )
+        .fetch_one(&self.db)
+        .await
+        .context("Failed to count filtered states")?
+        .unwrap_or(0);
+
+        let rows = sqlx::query_as!(
+            StateRow,
+            r#"SELECT state_id, state_group_id, state_code, state_description, created_at, updated_at
+               FROM core.state
+               WHERE ($1::uuid IS NULL OR state_group_id = $1)
+                 AND ($2::text IS NULL OR lower(state_description::text COLLATE "C") LIKE $2) AND deleted_at IS NULL
+               ORDER BY state_group_id, state_code
+               LIMIT $3 OFFSET $4"#,
+            state_group_id,
+            description_pattern,
+            limit,
+            offset
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch filtered states")?;
+
+        let states: Vec<State> = rows.into_iter().map(State::from).collect();
+
+        debug!(
+            "Found {} states with filters (total: {})",
+            states.len(),
+            total_count
+        );
+
+        Ok((states, total_count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_test_state_group(pool: &PgPool) -> sqlx::Result<Uuid> {
+        let unique_name = format!("Test Group {}", Uuid::new_v4());
+        sqlx::query_scalar!(
+            r#"INSERT INTO core.state_group (state_group_name, state_group_description)
+               VALUES ($1, 'Test Group Description')
+               RETURNING state_group_id"#,
+            unique_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_create_and_get_state(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+
+        let state = service
+            .create(group_id, 1, "running", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert_eq!(state.state_code, 1);
+        assert_eq!(state.state_group_id, group_id);
+
+        let found = service
+            .get_by_id(state.state_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(found.state_description, "running");
+
+        let by_code = service
+            .get_by_code(group_id, 1)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(by_code.map(|s| s.state_id), Some(state.state_id));
+
+        let result = service.get_by_id(Uuid::new_v4()).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_create_state_uniqueness(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+
+        service
+            .create(group_id, 1, "running", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service.create(group_id, 1, "other", None).await;
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+
+        let result = service.create(group_id, 2, "running", None).await;
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+
+        let result = service.create(group_id, -1, "negative", None).await;
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("cannot be negative")
+        );
+
+        assert!(
+            service
+                .code_exists_in_group(group_id, 1)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+        );
+        assert!(
+            service
+                .description_exists_in_group(group_id, "running")
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+        );
+        assert!(
+            !service
+                .code_exists_in_group(group_id, 2)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+        );
+
+        Ok(())
+    }
+
+    async fn reserve_for_rockwell(pool: &PgPool, group_id: Uuid) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO core.state_code_reservation (state_group_id, min_code, max_code, owner)
+               VALUES ($1, 100, 199, 'Rockwell')"#,
+            group_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_create_in_reserved_range(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+        reserve_for_rockwell(&pool, group_id).await?;
+
+        let result = service.create(group_id, 150, "faulted", None).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "state_code 150 is reserved for 'Rockwell' (100-199)"
+        );
+        let result = service
+            .create(group_id, 150, "faulted", Some("Siemens"))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("is reserved for"));
+
+        let state = service
+            .create(group_id, 150, "faulted", Some("Rockwell"))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(state.state_code, 150);
+
+        // codes outside the reservation are open to everyone
+        service
+            .create(group_id, 200, "starved", Some("Siemens"))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_update_code_into_reserved_range(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+        reserve_for_rockwell(&pool, group_id).await?;
+
+        let state = service
+            .create(group_id, 1, "running", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service
+            .update_code(state.state_id, 150, Some("Siemens"))
+            .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let unchanged = service
+            .get_by_id(state.state_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(unchanged.state_code, 1);
+
+        let updated = service
+            .update_code(state.state_id, 150, Some("Rockwell"))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated.state_code, 150);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_update_state_group_into_reserved_range(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+        let reserved_group_id = create_test_state_group(&pool).await?;
+        reserve_for_rockwell(&pool, reserved_group_id).await?;
+
+        let state = service
+            .create(group_id, 150, "faulted", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let result = service
+            .update_state_group(state.state_id, reserved_group_id, None)
+            .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let unchanged = service
+            .get_by_id(state.state_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(unchanged.state_group_id, group_id);
+
+        let moved = service
+            .update_state_group(state.state_id, reserved_group_id, Some("Rockwell"))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(moved.state_group_id, reserved_group_id);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_restore_into_reserved_range(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+
+        let state = service
+            .create(group_id, 150, "faulted", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        service
+            .delete(state.state_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        // reserved while the state was deleted
+        reserve_for_rockwell(&pool, group_id).await?;
+
+        let result = service.restore(state.state_id, None).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert!(
+            !service
+                .exists(state.state_id)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+        );
+
+        let restored = service
+            .restore(state.state_id, Some("Rockwell"))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(restored.state_code, 150);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_updates(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+        let other_group_id = create_test_state_group(&pool).await?;
+
+        let state = service
+            .create(group_id, 1, "running", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let updated = service
+            .update_description(state.state_id, "producing")
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated.state_description, "producing");
+
+        let updated = service
+            .update_code(state.state_id, 42, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated.state_code, 42);
+
+        let moved = service
+            .update_state_group(state.state_id, other_group_id, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(moved.state_group_id, other_group_id);
+
+        let result = service.update_code(Uuid::new_v4(), 3, None).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_bulk_create_rolls_back(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+
+        let result = service
+            .bulk_create(vec![
+                (group_id, 1, "running", None),
+                (group_id, 2, "idle", None),
+                (group_id, 2, "duplicate code", None),
+            ])
+            .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        let states = service
+            .get_by_state_group_id(group_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert!(states.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_code_range(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+
+        let created = service
+            .bulk_create(vec![
+                (group_id, 1, "running", None),
+                (group_id, 10, "idle", None),
+                (group_id, 20, "blocked", None),
+            ])
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(created.len(), 3);
+
+        let in_range = service
+            .get_by_code_range(group_id, 5, 20)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        let codes: Vec<i32> = in_range.iter().map(|s| s.state_code).collect();
+        assert_eq!(codes, vec![10, 20]);
+
+        let result = service.get_by_code_range(group_id, 20, 5).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let result = service.get_by_code_range(Uuid::new_v4(), 0, 5).await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_search_with_filters(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+
+        for (code, description) in [(1, "running"), (2, "starved"), (3, "run out")] {
+            service
+                .create(group_id, code, description, None)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        }
+
+        let (states, total) = service
+            .search_with_filters(Some(group_id), Some("RUN"), 0, 50)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(total, 2);
+        assert_eq!(states.len(), 2);
+
+        let (states, total) = service
+            .search_with_filters(Some(group_id), None, 0, 2)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(total, 3);
+        assert_eq!(states.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delete_state(pool: PgPool) -> sqlx::Result<()> {
+        let service = StateService::new(pool.clone());
+        let group_id = create_test_state_group(&pool).await?;
+
+        let state = service
+            .create(group_id, 1, "running", None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        service
+            .delete(state.state_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        assert!(
+            !service
+                .exists(state.state_id)
+                .await
+                .map_err(|e| sqlx::Error::Protocol(e.to_string()))?
+        );
+
+        let result = service.delete(state.state_id).await;
+        assert!(result.unwrap_err().to_string().contains("not found"));
+
+        Ok(())
+    }
+}
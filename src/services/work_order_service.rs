@@ -0,0 +1,368 @@
+use crate::database::products::ProductQueries;
+use crate::database::work_orders::{WorkOrderQueries, WorkOrderRow};
+use crate::error::{AppError, AppResult, DatabaseContext};
+use crate::models::operations::WorkOrder;
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+const MAX_NAME_LEN: usize = 255;
+
+impl From<WorkOrderRow> for WorkOrder {
+    fn from(row: WorkOrderRow) -> Self {
+        Self {
+            work_order_id: row.work_order_id,
+            work_order_name: row.work_order_name,
+            product_id: row.product_id,
+            planned_quantity: row.planned_quantity,
+            work_order_metadata: row.work_order_metadata,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Planned quantities are optional, but when given they must be positive
+pub(crate) fn validate_planned_quantity(planned_quantity: Option<i32>) -> AppResult<()> {
+    if let Some(quantity) = planned_quantity
+        && quantity <= 0
+    {
+        return Err(AppError::Validation(
+            "planned_quantity must be greater than 0".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkOrderService {
+    db: PgPool,
+}
+
+impl WorkOrderService {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    /// Validates and sanitizes work order name input
+    fn validate_name(work_order_name: &str) -> AppResult<String> {
+        let trimmed = work_order_name.trim().to_string();
+
+        if trimmed.is_empty() {
+            return Err(AppError::Validation(
+                "work_order_name cannot be empty".to_string(),
+            ));
+        }
+
+        if trimmed.len() > MAX_NAME_LEN {
+            return Err(AppError::Validation(format!(
+                "work_order_name exceeds max length of {} characters",
+                MAX_NAME_LEN
+            )));
+        }
+
+        Ok(trimmed)
+    }
+
+    /// Work order metadata is stored as a json object, anything else is rejected
+    fn validate_metadata(metadata: &Value) -> AppResult<()> {
+        if !metadata.is_object() {
+            return Err(AppError::Validation(
+                "work_order_metadata must be a json object".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(work_order_id = %work_order_id))]
+    pub async fn get_by_id(&self, work_order_id: Uuid) -> AppResult<WorkOrder> {
+        debug!("Fetching work order by ID");
+        let row = WorkOrderQueries::get_by_id(&self.db, work_order_id)
+            .await
+            .context("Failed to fetch work order by ID")?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Work order with ID {} not found", work_order_id))
+            })?;
+
+        debug!("Found work order: {}", row.work_order_name);
+        Ok(WorkOrder::from(row))
+    }
+
+    #[instrument(skip(self, work_order_metadata), fields(work_order_name = %work_order_name))]
+    pub async fn create(
+        &self,
+        work_order_name: &str,
+        product_id: Uuid,
+        planned_quantity: Option<i32>,
+        work_order_metadata: Option<&Value>,
+    ) -> AppResult<WorkOrder> {
+        debug!("Creating new work order");
+        let name = Self::validate_name(work_order_name)?;
+        validate_planned_quantity(planned_quantity)?;
+
+        if let Some(metadata) = work_order_metadata {
+            Self::validate_metadata(metadata)?;
+        }
+
+        if !ProductQueries::exists(&self.db, product_id)
+            .await
+            .context("Failed to check product")?
+        {
+            return Err(AppError::Validation(format!(
+                "product_id '{}' does not exist",
+                product_id
+            )));
+        }
+
+        if WorkOrderQueries::name_exists(&self.db, &name, None)
+            .await
+            .context("Failed to check for duplicate work_order_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "work_order_name '{}' already exists",
+                name
+            )));
+        }
+
+        let row = WorkOrderQueries::create(
+            &self.db,
+            &name,
+            product_id,
+            planned_quantity,
+            work_order_metadata,
+        )
+        .await
+        .with_context(|| format!("Failed to create work order '{}'", name))?;
+
+        debug!("Successfully created work order: {}", row.work_order_name);
+        Ok(WorkOrder::from(row))
+    }
+
+    #[instrument(skip(self), fields(work_order_id = %work_order_id, work_order_name = %work_order_name))]
+    pub async fn update(
+        &self,
+        work_order_id: Uuid,
+        work_order_name: &str,
+        planned_quantity: Option<i32>,
+    ) -> AppResult<WorkOrder> {
+        debug!("Updating work order");
+        let name = Self::validate_name(work_order_name)?;
+        validate_planned_quantity(planned_quantity)?;
+
+        if WorkOrderQueries::name_exists(&self.db, &name, Some(work_order_id))
+            .await
+            .context("Failed to check for duplicate work_order_name")?
+        {
+            return Err(AppError::Conflict(format!(
+                "work_order_name '{}' already exists",
+                name
+            )));
+        }
+
+        let row = WorkOrderQueries::update(&self.db, work_order_id, &name, planned_quantity)
+            .await
+            .with_context(|| format!("Failed to update work order {}", work_order_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Work order with ID {} not found", work_order_id))
+            })?;
+
+        debug!("Successfully updated work order: {}", row.work_order_name);
+        Ok(WorkOrder::from(row))
+    }
+
+    #[instrument(skip(self, metadata), fields(work_order_id = %work_order_id))]
+    pub async fn update_metadata(
+        &self,
+        work_order_id: Uuid,
+        metadata: &Value,
+    ) -> AppResult<WorkOrder> {
+        debug!("Updating work order metadata");
+        Self::validate_metadata(metadata)?;
+
+        let row = WorkOrderQueries::update_metadata(&self.db, work_order_id, metadata)
+            .await
+            .with_context(|| format!("Failed to update metadata for work order {}", work_order_id))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Work order with ID {} not found", work_order_id))
+            })?;
+
+        debug!("Successfully updated work order metadata");
+        Ok(WorkOrder::from(row))
+    }
+
+    #[instrument(skip(self), fields(work_order_id = %work_order_id))]
+    pub async fn delete(&self, work_order_id: Uuid) -> AppResult<()> {
+        debug!("Deleting work order");
+
+        if WorkOrderQueries::has_jobs(&self.db, work_order_id)
+            .await
+            .context("Failed to check work order jobs")?
+        {
+            return Err(AppError::Conflict(format!(
+                "Work order {} is in use by jobs and cannot be deleted",
+                work_order_id
+            )));
+        }
+
+        let deleted = WorkOrderQueries::delete(&self.db, work_order_id)
+            .await
+            .with_context(|| format!("Failed to delete work order {}", work_order_id))?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!(
+                "Work order with ID {} not found",
+                work_order_id
+            )));
+        }
+
+        debug!("Successfully deleted work order");
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(work_order_id = %work_order_id))]
+    pub async fn exists(&self, work_order_id: Uuid) -> AppResult<bool> {
+        let exists = WorkOrderQueries::exists(&self.db, work_order_id)
+            .await
+            .context("Failed to check if work order exists")?;
+        debug!("Work order {} exists: {}", work_order_id, exists);
+        Ok(exists)
+    }
+
+    /// Get work orders with pagination
+    #[instrument(skip(self))]
+    pub async fn get_paginated(&self, offset: i64, limit: i64) -> AppResult<(Vec<WorkOrder>, i64)> {
+        debug!(
+            "Fetching work orders with pagination: offset={}, limit={}",
+            offset, limit
+        );
+
+        if offset < 0 {
+            return Err(AppError::Validation(
+                "Offset cannot be negative".to_string(),
+            ));
+        }
+
+        if limit <= 0 || limit > 1000 {
+            return Err(AppError::Validation(
+                "Limit must be between 1 and 1000".to_string(),
+            ));
+        }
+
+        let total_count = self.count().await?;
+
+        let rows = sqlx::query_as!(
+            WorkOrderRow,
+            r#"SELECT work_order_id, work_order_name, product_id, planned_quantity,
+                      work_order_metadata, created_at, updated_at
+               FROM operations.work_order
+               ORDER BY work_order_name, work_order_id
+               LIMIT $1 OFFSET $2"#,
+            limit,
+            offset
+        )
+        .fetch_all(&self.db)
+        .await
+        .context("Failed to fetch paginated work orders")?;
+
+        let work_orders: Vec<WorkOrder> = rows.into_iter().map(WorkOrder::from).collect();
+        debug!(
+            "Found {} work orders (total: {})",
+            work_orders.len(),
+            total_count
+        );
+
+        Ok((work_orders, total_count))
+    }
+
+    /// Get count of all work orders
+    #[instrument(skip(self))]
+    pub async fn count(&self) -> AppResult<i64> {
+        let count = sqlx::query_scalar!("SELECT COUNT(*) FROM operations.work_order")
+            .fetch_one(&self.db)
+            .await
+            .context("Failed to count work orders")?
+            .unwrap_or(0);
+
+        debug!("Total work order count: {}", count);
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    async fn create_product(pool: &PgPool, product_name: &str) -> sqlx::Result<Uuid> {
+        sqlx::query_scalar!(
+            "INSERT INTO operations.product (product_name) VALUES ($1) RETURNING product_id",
+            product_name
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    #[sqlx::test]
+    async fn test_service_create_and_validation(pool: PgPool) -> sqlx::Result<()> {
+        let product_id = create_product(&pool, "Widget").await?;
+        let service = WorkOrderService::new(pool);
+
+        let created = service
+            .create(" WO-1 ", product_id, Some(100), None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(created.work_order_name, "WO-1");
+        assert_eq!(created.planned_quantity, Some(100));
+
+        let err = service
+            .create("WO-2", Uuid::new_v4(), None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+
+        let err = service
+            .create("WO-2", product_id, Some(0), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("greater than 0"));
+
+        let err = service
+            .create("wo-1", product_id, None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_service_update_and_delete(pool: PgPool) -> sqlx::Result<()> {
+        let product_id = create_product(&pool, "Widget").await?;
+        let service = WorkOrderService::new(pool);
+
+        let created = service
+            .create("WO-1", product_id, None, None)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let updated = service
+            .update(created.work_order_id, "WO-1A", Some(50))
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        assert_eq!(updated.work_order_name, "WO-1A");
+        assert_eq!(updated.planned_quantity, Some(50));
+
+        service
+            .delete(created.work_order_id)
+            .await
+            .map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+        let err = service.get_by_id(created.work_order_id).await.unwrap_err();
+        assert!(err.to_string().contains("not found"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// Tells the servers to stop taking new connections. Clones share the same signal.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    requested: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    /// Shut down on the first SIGINT or SIGTERM
+    pub fn on_signal() -> Self {
+        let (tx, shutdown) = Self::channel();
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            info!("Shutdown requested, draining in-flight requests");
+            let _ = tx.send(true);
+        });
+        shutdown
+    }
+
+    fn channel() -> (watch::Sender<bool>, Self) {
+        let (tx, requested) = watch::channel(false);
+        (tx, Self { requested })
+    }
+
+    /// Resolves once shutdown has been requested
+    pub async fn wait(mut self) {
+        // an error means the sender is gone without ever requesting shutdown
+        if self
+            .requested
+            .wait_for(|requested| *requested)
+            .await
+            .is_err()
+        {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Run a server that stops gracefully on `wait`, but give up on it once it has had
+    /// `timeout` to finish. Long lived streams would otherwise keep it running forever.
+    pub async fn drain<T>(self, server: impl Future<Output = T>, timeout: Duration) -> Option<T> {
+        tokio::pin!(server);
+        tokio::select! {
+            result = &mut server => Some(result),
+            _ = self.wait() => match tokio::time::timeout(timeout, server).await {
+                Ok(result) => Some(result),
+                Err(_) => {
+                    warn!("Connections still open after {:?}, closing them", timeout);
+                    None
+                }
+            },
+        }
+    }
+}
+
+async fn wait_for_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_server_then_gives_up() {
+        let (tx, shutdown) = Shutdown::channel();
+
+        // a server that finishes on its own is not affected
+        let result = shutdown.clone().drain(async { 1 }, Duration::ZERO).await;
+        assert_eq!(result, Some(1));
+
+        tx.send(true).unwrap();
+
+        // one that stops soon after the signal gets to finish
+        let stopping = shutdown.clone().wait();
+        let result = shutdown
+            .clone()
+            .drain(
+                async {
+                    stopping.await;
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    2
+                },
+                Duration::from_secs(5),
+            )
+            .await;
+        assert_eq!(result, Some(2));
+
+        // one that never stops is cut off after the timeout
+        let result = shutdown
+            .drain(std::future::pending::<i32>(), Duration::from_millis(10))
+            .await;
+        assert_eq!(result, None);
+    }
+}